@@ -0,0 +1,417 @@
+//! Anthropic Messages API backend.
+//!
+//! [`ApiBackend`] runs an iteration as a single `POST /v1/messages` call
+//! instead of spawning a CLI, so loops can run where no agent binary is
+//! installed. The API reports token counts for every call; the cost is
+//! computed from them with the published per-model prices, so the cost
+//! ledger and `max_cost_usd` see real numbers rather than CLI estimates.
+//!
+//! The API runs no tools. The agent's whole turn is its text response, which
+//! is scanned for events like any other backend's output.
+
+use ralph_core::{ApiConfig, Usage};
+use serde::Deserialize;
+use std::io::Write;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Backend name that selects the Messages API (`cli.backend: api`).
+pub const API_BACKEND: &str = "api";
+
+/// `anthropic-version` header sent with every request.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Prices in USD per million input and output tokens, by model ID prefix.
+/// The first matching prefix wins, so more specific prefixes come first.
+const PRICES: &[(&str, f64, f64)] = &[
+    ("claude-opus-4-5", 5.0, 25.0),
+    ("claude-opus-4", 15.0, 75.0),
+    ("claude-sonnet-4", 3.0, 15.0),
+    ("claude-3-7-sonnet", 3.0, 15.0),
+    ("claude-3-5-sonnet", 3.0, 15.0),
+    ("claude-haiku-4-5", 1.0, 5.0),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    ("claude-3-haiku", 0.25, 1.25),
+];
+
+/// Cache writes cost this much more than plain input tokens.
+const CACHE_WRITE_MULTIPLIER: f64 = 1.25;
+
+/// Cache reads cost this fraction of plain input tokens.
+const CACHE_READ_MULTIPLIER: f64 = 0.1;
+
+/// Errors from the Messages API backend.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    /// No API key in the environment or `credentials`.
+    #[error("No API key for the api backend; set {0} or add it to `credentials`")]
+    MissingKey(String),
+
+    /// The request could not be sent or the response could not be read.
+    #[error("Messages API request failed: {0}")]
+    Request(#[from] reqwest::Error),
+}
+
+/// Result of one Messages API call.
+#[derive(Debug, Clone)]
+pub struct ApiResult {
+    /// The response text.
+    pub output: String,
+    /// Whether the API returned a response.
+    pub success: bool,
+    /// Whether the call was abandoned at the timeout.
+    pub timed_out: bool,
+    /// Tokens used and their cost.
+    pub usage: Usage,
+}
+
+/// Calls the Anthropic Messages API.
+#[derive(Debug, Clone)]
+pub struct ApiBackend {
+    base_url: String,
+    api_key: String,
+    model: String,
+    max_tokens: u32,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    #[serde(default)]
+    content: Vec<ResponseBlock>,
+    stop_reason: Option<String>,
+    usage: ApiUsage,
+}
+
+#[derive(Deserialize)]
+struct ResponseBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[allow(clippy::struct_field_names)] // Named as in the API's usage object
+struct ApiUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorDetail,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+    #[serde(rename = "type")]
+    kind: String,
+    message: String,
+}
+
+impl ApiBackend {
+    /// Creates a backend from the `api` config section.
+    ///
+    /// The key is read from `credentials` first, then from the environment
+    /// variable named by `api_key_env`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::MissingKey`] if neither has the key.
+    pub fn from_config(
+        config: &ApiConfig,
+        credentials: &[(String, String)],
+    ) -> Result<Self, ApiError> {
+        let api_key = credentials
+            .iter()
+            .find(|(name, _)| *name == config.api_key_env)
+            .map(|(_, value)| value.clone())
+            .or_else(|| std::env::var(&config.api_key_env).ok())
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| ApiError::MissingKey(config.api_key_env.clone()))?;
+        Ok(Self {
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model: config.model.clone(),
+            max_tokens: config.max_tokens,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Overrides the model, e.g. from a routing rule.
+    #[must_use]
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+
+    /// Returns the model requests are sent to.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Sends `prompt` as one user message and writes the response text to
+    /// `output_writer`.
+    ///
+    /// API errors (rate limits, overload, bad requests) are returned as an
+    /// unsuccessful result with the error message as output, like a CLI that
+    /// exits non-zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request can't be sent or the response can't
+    /// be read.
+    pub async fn execute<W: Write + Send>(
+        &self,
+        prompt: &str,
+        mut output_writer: W,
+        timeout: Option<Duration>,
+    ) -> Result<ApiResult, ApiError> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "max_tokens": self.max_tokens,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+        let mut request = self
+            .client
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body);
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+
+        debug!(model = %self.model, "Sending Messages API request");
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) if e.is_timeout() => return Ok(Self::timed_out(timeout)),
+            Err(e) => return Err(e.into()),
+        };
+        let status = response.status();
+        let text = match response.text().await {
+            Ok(text) => text,
+            Err(e) if e.is_timeout() => return Ok(Self::timed_out(timeout)),
+            Err(e) => return Err(e.into()),
+        };
+
+        if !status.is_success() {
+            let message = serde_json::from_str::<ErrorResponse>(&text)
+                .map_or(text, |e| format!("{}: {}", e.error.kind, e.error.message));
+            warn!(status = %status, "Messages API returned an error: {}", message);
+            return Ok(ApiResult {
+                output: format!("Messages API error ({status}): {message}"),
+                success: false,
+                timed_out: false,
+                usage: Usage::default(),
+            });
+        }
+
+        let response: MessagesResponse = match serde_json::from_str(&text) {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Unreadable Messages API response: {}", e);
+                return Ok(ApiResult {
+                    output: format!("Unreadable Messages API response: {e}"),
+                    success: false,
+                    timed_out: false,
+                    usage: Usage::default(),
+                });
+            }
+        };
+        if response.stop_reason.as_deref() == Some("max_tokens") {
+            warn!(
+                max_tokens = self.max_tokens,
+                "Messages API response was cut off at api.max_tokens"
+            );
+        }
+
+        let output = response
+            .content
+            .iter()
+            .filter(|block| block.kind == "text")
+            .map(|block| block.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let _ = writeln!(output_writer, "{output}");
+        let _ = output_writer.flush();
+
+        Ok(ApiResult {
+            output,
+            success: true,
+            timed_out: false,
+            usage: usage_for(&self.model, &response.usage),
+        })
+    }
+
+    fn timed_out(timeout: Option<Duration>) -> ApiResult {
+        let secs = timeout.map_or(0, |timeout| timeout.as_secs());
+        warn!("Messages API request timed out after {}s", secs);
+        ApiResult {
+            output: format!("Messages API request timed out after {secs}s"),
+            success: false,
+            timed_out: true,
+            usage: Usage::default(),
+        }
+    }
+}
+
+/// Converts the API's usage report to a [`Usage`], pricing it for `model`.
+///
+/// Unknown models are recorded with their tokens and no cost.
+fn usage_for(model: &str, usage: &ApiUsage) -> Usage {
+    let input_tokens =
+        usage.input_tokens + usage.cache_creation_input_tokens + usage.cache_read_input_tokens;
+    let Some(&(_, input_price, output_price)) = PRICES
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+    else {
+        debug!(model, "No price for model; recording tokens without cost");
+        return Usage::new(0.0, input_tokens, usage.output_tokens);
+    };
+
+    #[allow(clippy::cast_precision_loss)] // Token counts are far below 2^52
+    let cost_usd = (usage.input_tokens as f64 * input_price
+        + usage.cache_creation_input_tokens as f64 * input_price * CACHE_WRITE_MULTIPLIER
+        + usage.cache_read_input_tokens as f64 * input_price * CACHE_READ_MULTIPLIER
+        + usage.output_tokens as f64 * output_price)
+        / 1_000_000.0;
+    Usage::new(cost_usd, input_tokens, usage.output_tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serves one HTTP response and returns the base URL and the raw request.
+    async fn serve_once(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break;
+                    }
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+        (url, handle)
+    }
+
+    fn backend(base_url: String) -> ApiBackend {
+        let config = ApiConfig {
+            base_url,
+            ..ApiConfig::default()
+        };
+        let credentials = [("ANTHROPIC_API_KEY".to_string(), "sk-test".to_string())];
+        ApiBackend::from_config(&config, &credentials).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_execute_returns_text_and_usage() {
+        let (url, request) = serve_once(
+            "200 OK",
+            r#"{"content":[{"type":"text","text":"Done.\n<event topic=\"build.done\">ok</event>"}],
+               "stop_reason":"end_turn",
+               "usage":{"input_tokens":1000,"output_tokens":500,"cache_read_input_tokens":10000}}"#,
+        )
+        .await;
+
+        let mut written = Vec::new();
+        let result = backend(url)
+            .with_model("claude-sonnet-4-5")
+            .execute("Build it", &mut written, Some(Duration::from_secs(10)))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(result.output.contains("<event topic=\"build.done\">"));
+        assert!(String::from_utf8(written).unwrap().starts_with("Done."));
+        assert_eq!(result.usage.input_tokens, 11_000);
+        assert_eq!(result.usage.output_tokens, 500);
+        // 1000 × $3 + 10000 × $0.30 + 500 × $15, per million
+        assert!((result.usage.cost_usd - 0.0135).abs() < 1e-9);
+
+        let request = request.await.unwrap();
+        assert!(request.starts_with("POST /v1/messages"));
+        assert!(request.contains("x-api-key: sk-test"));
+        assert!(request.contains("anthropic-version: 2023-06-01"));
+        assert!(request.contains(r#""content":"Build it""#));
+    }
+
+    #[tokio::test]
+    async fn test_api_errors_fail_the_iteration() {
+        let (url, _request) = serve_once(
+            "529 Overloaded",
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        )
+        .await;
+
+        let result = backend(url)
+            .execute("Build it", std::io::sink(), None)
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(result.output.contains("overloaded_error: Overloaded"));
+        assert_eq!(result.usage, Usage::default());
+    }
+
+    #[test]
+    fn test_missing_key_names_the_variable() {
+        let config = ApiConfig {
+            api_key_env: "RALPH_TEST_NO_SUCH_KEY".to_string(),
+            ..ApiConfig::default()
+        };
+        let err = ApiBackend::from_config(&config, &[]).unwrap_err();
+        assert!(err.to_string().contains("RALPH_TEST_NO_SUCH_KEY"));
+    }
+
+    #[test]
+    fn test_usage_pricing_by_model_prefix() {
+        let usage = ApiUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+            ..ApiUsage::default()
+        };
+        assert!((usage_for("claude-opus-4-5-20251101", &usage).cost_usd - 30.0).abs() < 1e-9);
+        assert!((usage_for("claude-opus-4-1", &usage).cost_usd - 90.0).abs() < 1e-9);
+        assert!((usage_for("claude-haiku-4-5", &usage).cost_usd - 6.0).abs() < 1e-9);
+        let unknown = usage_for("some-other-model", &usage);
+        assert!(unknown.cost_usd.abs() < f64::EPSILON);
+        assert_eq!(unknown.output_tokens, 1_000_000);
+    }
+}
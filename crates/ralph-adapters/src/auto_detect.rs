@@ -9,7 +9,7 @@ use tracing::debug;
 
 /// Default priority order for backend detection.
 pub const DEFAULT_PRIORITY: &[&str] = &[
-    "claude", "kiro", "gemini", "codex", "amp", "copilot", "opencode", "pi",
+    "claude", "kiro", "gemini", "codex", "amp", "goose", "copilot", "opencode", "pi",
 ];
 
 /// Maps backend config names to their actual CLI command names.
@@ -54,6 +54,7 @@ impl std::fmt::Display for NoBackendError {
         writeln!(f, "  • Gemini CLI:   https://cloud.google.com/gemini")?;
         writeln!(f, "  • Codex CLI:    https://openai.com/codex")?;
         writeln!(f, "  • Amp CLI:      https://amp.dev")?;
+        writeln!(f, "  • Goose CLI:    https://block.github.io/goose")?;
         writeln!(f, "  • Copilot CLI:  https://docs.github.com/copilot")?;
         writeln!(f, "  • OpenCode CLI: https://opencode.ai")?;
         writeln!(
@@ -37,6 +37,9 @@ pub enum ClaudeStreamEvent {
         total_cost_usd: f64,
         num_turns: u32,
         is_error: bool,
+        /// Token totals for the session.
+        #[serde(default)]
+        usage: Option<Usage>,
     },
 }
 
@@ -78,7 +81,7 @@ pub enum UserContentBlock {
 }
 
 /// Token usage statistics.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Usage {
     pub input_tokens: u64,
     pub output_tokens: u64,
@@ -222,7 +225,9 @@ mod tests {
                 total_cost_usd,
                 num_turns,
                 is_error,
+                usage,
             } => {
+                assert!(usage.is_none());
                 assert_eq!(duration_ms, 5000);
                 assert!((total_cost_usd - 0.02).abs() < f64::EPSILON);
                 assert_eq!(num_turns, 2);
@@ -1,6 +1,8 @@
 //! CLI backend definitions for different AI tools.
 
-use ralph_core::{CliConfig, HatBackend};
+use crate::container::ContainerEnvironment;
+use crate::limits::apply_limits;
+use ralph_core::{CliConfig, GenerationConfig, HatBackend, ReasoningEffort, ResourceLimits};
 use std::fmt;
 use std::io::Write;
 use tempfile::NamedTempFile;
@@ -18,6 +20,8 @@ pub enum OutputFormat {
     StreamJson,
     /// Newline-delimited JSON stream (Pi with --mode json)
     PiStreamJson,
+    /// Newline-delimited JSON stream (Goose with --output-format stream-json)
+    GooseStreamJson,
 }
 
 /// Error when creating a custom backend without a command.
@@ -56,6 +60,8 @@ pub struct CliBackend {
     pub output_format: OutputFormat,
     /// Environment variables to set when spawning the process.
     pub env_vars: Vec<(String, String)>,
+    /// Container to run the command in (None runs it on the host).
+    pub container: Option<ContainerEnvironment>,
 }
 
 impl CliBackend {
@@ -70,6 +76,7 @@ impl CliBackend {
             "gemini" => Self::gemini(),
             "codex" => Self::codex(),
             "amp" => Self::amp(),
+            "goose" => Self::goose(),
             "copilot" => Self::copilot(),
             "opencode" => Self::opencode(),
             "pi" => Self::pi(),
@@ -114,6 +121,7 @@ impl CliBackend {
             prompt_flag: Some("-p".to_string()),
             output_format: OutputFormat::StreamJson,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -137,6 +145,7 @@ impl CliBackend {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -155,6 +164,7 @@ impl CliBackend {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -175,6 +185,7 @@ impl CliBackend {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
         backend.args.extend(extra_args.iter().cloned());
         backend
@@ -207,6 +218,7 @@ impl CliBackend {
             "gemini" => Ok(Self::gemini()),
             "codex" => Ok(Self::codex()),
             "amp" => Ok(Self::amp()),
+            "goose" => Ok(Self::goose()),
             "copilot" => Ok(Self::copilot()),
             "opencode" => Ok(Self::opencode()),
             "pi" => Ok(Self::pi()),
@@ -234,6 +246,7 @@ impl CliBackend {
                 prompt_flag: None,
                 output_format: OutputFormat::Text,
                 env_vars: vec![],
+                container: None,
             }),
         }
     }
@@ -247,6 +260,7 @@ impl CliBackend {
             prompt_flag: Some("-p".to_string()),
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -259,18 +273,50 @@ impl CliBackend {
             prompt_flag: None, // Positional argument
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
     /// Creates the Amp backend.
+    ///
+    /// Uses `-x` (execute mode) so Amp runs the prompt and exits, with
+    /// `--stream-json` for Claude-compatible NDJSON output. Tool calls and
+    /// the final usage report are parsed by the Claude stream parser.
     pub fn amp() -> Self {
         Self {
             command: "amp".to_string(),
-            args: vec!["--dangerously-allow-all".to_string()],
+            args: vec![
+                "--dangerously-allow-all".to_string(),
+                "--stream-json".to_string(),
+            ],
             prompt_mode: PromptMode::Arg,
             prompt_flag: Some("-x".to_string()),
-            output_format: OutputFormat::Text,
+            output_format: OutputFormat::StreamJson,
             env_vars: vec![],
+            container: None,
+        }
+    }
+
+    /// Creates the Goose backend for headless execution.
+    ///
+    /// Runs `goose run -t <prompt>` with `--output-format stream-json` for
+    /// NDJSON output and `--no-session`, since every iteration starts from a
+    /// fresh context. `GOOSE_MODE=auto` approves tool calls without asking.
+    /// Prompts over 7000 chars are passed as an instructions file (`-i`).
+    pub fn goose() -> Self {
+        Self {
+            command: "goose".to_string(),
+            args: vec![
+                "run".to_string(),
+                "--no-session".to_string(),
+                "--output-format".to_string(),
+                "stream-json".to_string(),
+            ],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: Some("-t".to_string()),
+            output_format: OutputFormat::GooseStreamJson,
+            env_vars: vec![("GOOSE_MODE".to_string(), "auto".to_string())],
+            container: None,
         }
     }
 
@@ -286,6 +332,7 @@ impl CliBackend {
             prompt_flag: Some("-p".to_string()),
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -302,6 +349,7 @@ impl CliBackend {
             prompt_flag: None, // Positional argument
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -323,6 +371,7 @@ impl CliBackend {
                 "CLAUDE_CODE_EXPERIMENTAL_AGENT_TEAMS".to_string(),
                 "1".to_string(),
             )],
+            container: None,
         }
     }
 
@@ -338,7 +387,8 @@ impl CliBackend {
     /// | Kiro    | removes `--no-interactive` |
     /// | Gemini  | uses `-i` instead of `-p` |
     /// | Codex   | no `exec` subcommand |
-    /// | Amp     | removes `--dangerously-allow-all` |
+    /// | Amp     | removes `--dangerously-allow-all` and `--stream-json` |
+    /// | Goose   | `run --interactive`, tool calls need approval |
     /// | Copilot | removes `--allow-all-tools` |
     /// | OpenCode| `run` subcommand with positional prompt |
     ///
@@ -351,6 +401,7 @@ impl CliBackend {
             "gemini" => Ok(Self::gemini_interactive()),
             "codex" => Ok(Self::codex_interactive()),
             "amp" => Ok(Self::amp_interactive()),
+            "goose" => Ok(Self::goose_interactive()),
             "copilot" => Ok(Self::copilot_interactive()),
             "opencode" => Ok(Self::opencode_interactive()),
             "pi" => Ok(Self::pi_interactive()),
@@ -370,6 +421,7 @@ impl CliBackend {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -385,6 +437,7 @@ impl CliBackend {
             prompt_flag: Some("-i".to_string()), // NOT -p!
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -400,6 +453,7 @@ impl CliBackend {
             prompt_flag: None, // Positional argument
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -415,6 +469,24 @@ impl CliBackend {
             prompt_flag: Some("-x".to_string()),
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
+        }
+    }
+
+    /// Goose in interactive mode with initial prompt.
+    ///
+    /// `goose run --interactive` processes the prompt, then keeps the session
+    /// open for the user. Without `GOOSE_MODE=auto`, tool calls follow the
+    /// user's configured approval mode.
+    pub fn goose_interactive() -> Self {
+        Self {
+            command: "goose".to_string(),
+            args: vec!["run".to_string(), "--interactive".to_string()],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: Some("-t".to_string()),
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
         }
     }
 
@@ -430,6 +502,7 @@ impl CliBackend {
             prompt_flag: Some("-p".to_string()),
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -450,6 +523,7 @@ impl CliBackend {
             prompt_flag: None, // Positional argument
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -468,6 +542,7 @@ impl CliBackend {
             prompt_flag: None, // Positional argument
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -488,6 +563,7 @@ impl CliBackend {
             prompt_flag: Some("--prompt".to_string()),
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -508,6 +584,7 @@ impl CliBackend {
             prompt_flag: None, // Positional argument
             output_format: OutputFormat::PiStreamJson,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -523,6 +600,7 @@ impl CliBackend {
             prompt_flag: None, // Positional argument
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         }
     }
 
@@ -545,6 +623,7 @@ impl CliBackend {
             prompt_flag: config.prompt_flag.clone(),
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         })
     }
 
@@ -557,6 +636,20 @@ impl CliBackend {
         &self,
         prompt: &str,
         interactive: bool,
+    ) -> (String, Vec<String>, Option<String>, Option<NamedTempFile>) {
+        self.build_limited_command(prompt, interactive, &ResourceLimits::default())
+    }
+
+    /// Builds the command like [`build_command`](Self::build_command), running
+    /// it under `limits`.
+    ///
+    /// Outside a container the command is wrapped with [`apply_limits`]; a
+    /// container gets the limits as `<runtime> run` flags instead.
+    pub fn build_limited_command(
+        &self,
+        prompt: &str,
+        interactive: bool,
+        limits: &ResourceLimits,
     ) -> (String, Vec<String>, Option<String>, Option<NamedTempFile>) {
         let mut args = self.args.clone();
 
@@ -565,28 +658,35 @@ impl CliBackend {
             args = self.filter_args_for_interactive(args);
         }
 
-        // Handle large prompts for Claude (>7000 chars)
+        // Handle large prompts for Claude and Goose (>7000 chars)
         let (stdin_input, temp_file) = match self.prompt_mode {
+            PromptMode::Arg if self.command == "goose" && prompt.len() > 7000 => {
+                // Goose reads instructions from a file with -i instead of -t
+                match write_prompt_file(prompt) {
+                    Some(file) => {
+                        args.push("-i".to_string());
+                        args.push(file.path().display().to_string());
+                        (None, Some(file))
+                    }
+                    None => {
+                        args.extend(self.prompt_flag.iter().cloned());
+                        args.push(prompt.to_string());
+                        (None, None)
+                    }
+                }
+            }
             PromptMode::Arg => {
                 let (prompt_text, temp_file) = if self.command == "claude" && prompt.len() > 7000 {
                     // Write to temp file and instruct Claude to read it
-                    match NamedTempFile::new() {
-                        Ok(mut file) => {
-                            if let Err(e) = file.write_all(prompt.as_bytes()) {
-                                tracing::warn!("Failed to write prompt to temp file: {}", e);
-                                (prompt.to_string(), None)
-                            } else {
-                                let path = file.path().display().to_string();
-                                (
-                                    format!("Please read and execute the task in {}", path),
-                                    Some(file),
-                                )
-                            }
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to create temp file: {}", e);
-                            (prompt.to_string(), None)
+                    match write_prompt_file(prompt) {
+                        Some(file) => {
+                            let path = file.path().display().to_string();
+                            (
+                                format!("Please read and execute the task in {}", path),
+                                Some(file),
+                            )
                         }
+                        None => (prompt.to_string(), None),
                     }
                 } else {
                     (prompt.to_string(), None)
@@ -601,9 +701,26 @@ impl CliBackend {
             PromptMode::Stdin => (Some(prompt.to_string()), None),
         };
 
+        // Run inside the configured container, mounting the prompt temp file if any
+        let (command, args) = match &self.container {
+            Some(container) => {
+                let extra_files: Vec<&std::path::Path> =
+                    temp_file.iter().map(NamedTempFile::path).collect();
+                container.wrap(
+                    &self.command,
+                    args,
+                    &self.env_vars,
+                    &extra_files,
+                    interactive,
+                    limits,
+                )
+            }
+            None => apply_limits(limits, self.command.clone(), args),
+        };
+
         // Log the full command being built
         tracing::debug!(
-            command = %self.command,
+            command = %command,
             args_count = args.len(),
             prompt_len = prompt.len(),
             interactive = interactive,
@@ -614,7 +731,120 @@ impl CliBackend {
         // Log full prompt at trace level for debugging
         tracing::trace!(prompt = %prompt, "Full prompt content");
 
-        (self.command.clone(), args, stdin_input, temp_file)
+        (command, args, stdin_input, temp_file)
+    }
+
+    /// Runs this backend inside a container environment.
+    #[must_use]
+    pub fn with_container(mut self, container: ContainerEnvironment) -> Self {
+        self.container = Some(container);
+        self
+    }
+
+    /// Sets environment variables for the backend process, replacing any
+    /// already set with the same name.
+    #[must_use]
+    pub fn with_env_vars(mut self, vars: &[(String, String)]) -> Self {
+        for (key, value) in vars {
+            self.env_vars.retain(|(existing, _)| existing != key);
+            self.env_vars.push((key.clone(), value.clone()));
+        }
+        self
+    }
+
+    /// Passes `--model <model>` to the backend, replacing any model already set.
+    #[must_use]
+    pub fn with_model(mut self, model: &str) -> Self {
+        if let Some(pos) = self.args.iter().position(|arg| arg == "--model") {
+            let end = (pos + 2).min(self.args.len());
+            self.args.drain(pos..end);
+        }
+        self.args.retain(|arg| !arg.starts_with("--model="));
+        self.args.push("--model".to_string());
+        self.args.push(model.to_string());
+        self
+    }
+
+    /// Returns the model passed with `--model`, if any.
+    pub fn model(&self) -> Option<&str> {
+        self.args.iter().enumerate().find_map(|(i, arg)| {
+            if arg == "--model" {
+                self.args.get(i + 1).map(String::as_str)
+            } else {
+                arg.strip_prefix("--model=")
+            }
+        })
+    }
+
+    /// Applies a hat's generation settings that this backend's CLI accepts.
+    ///
+    /// Settings it can't take are left out; see
+    /// [`unsupported_generation`](Self::unsupported_generation).
+    #[must_use]
+    pub fn with_generation(mut self, generation: &GenerationConfig) -> Self {
+        if let Some(model) = &generation.model {
+            self = self.with_model(model);
+        }
+        let mut env = Vec::new();
+        match self.command.as_str() {
+            "claude" => {
+                if let Some(tokens) = generation.max_output_tokens {
+                    env.push(("CLAUDE_CODE_MAX_OUTPUT_TOKENS", tokens.to_string()));
+                }
+                if let Some(effort) = generation.reasoning_effort {
+                    env.push(("MAX_THINKING_TOKENS", thinking_tokens(effort).to_string()));
+                }
+            }
+            "codex" => {
+                if let Some(effort) = generation.reasoning_effort {
+                    let setting = format!("model_reasoning_effort=\"{}\"", effort.as_str());
+                    if let Some(pos) = self
+                        .args
+                        .iter()
+                        .position(|arg| arg.starts_with("model_reasoning_effort="))
+                        .filter(|&pos| pos > 0 && self.args[pos - 1] == "-c")
+                    {
+                        self.args[pos] = setting;
+                    } else {
+                        self.args.push("-c".to_string());
+                        self.args.push(setting);
+                    }
+                }
+            }
+            "goose" => {
+                if let Some(temperature) = generation.temperature {
+                    env.push(("GOOSE_TEMPERATURE", temperature.to_string()));
+                }
+            }
+            _ => {}
+        }
+        let env: Vec<(String, String)> = env
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        self.with_env_vars(&env)
+    }
+
+    /// Returns the names of configured generation settings this backend
+    /// can't take, for a one-time warning.
+    pub fn unsupported_generation(&self, generation: &GenerationConfig) -> Vec<&'static str> {
+        let (temperature, max_output_tokens, reasoning_effort) = match self.command.as_str() {
+            "claude" => (false, true, true),
+            "codex" => (false, false, true),
+            "goose" => (true, false, false),
+            _ => (false, false, false),
+        };
+        let mut unsupported = Vec::new();
+        if generation.temperature.is_some() && !temperature {
+            unsupported.push("temperature");
+        }
+        if generation.max_output_tokens.is_some() && !max_output_tokens {
+            unsupported.push("max_output_tokens");
+        }
+        if generation.reasoning_effort.is_some() && !reasoning_effort {
+            unsupported.push("reasoning_effort");
+        }
+        unsupported
     }
 
     /// Filters args for interactive mode per spec table.
@@ -627,7 +857,7 @@ impl CliBackend {
             "codex" => args.into_iter().filter(|a| a != "--full-auto").collect(),
             "amp" => args
                 .into_iter()
-                .filter(|a| a != "--dangerously-allow-all")
+                .filter(|a| a != "--dangerously-allow-all" && a != "--stream-json")
                 .collect(),
             "copilot" => args
                 .into_iter()
@@ -676,6 +906,32 @@ impl CliBackend {
     }
 }
 
+/// Writes a prompt to a temp file, for prompts too large for an argument.
+fn write_prompt_file(prompt: &str) -> Option<NamedTempFile> {
+    let mut file = match NamedTempFile::new() {
+        Ok(file) => file,
+        Err(e) => {
+            tracing::warn!("Failed to create temp file: {}", e);
+            return None;
+        }
+    };
+    if let Err(e) = file.write_all(prompt.as_bytes()) {
+        tracing::warn!("Failed to write prompt to temp file: {}", e);
+        return None;
+    }
+    Some(file)
+}
+
+/// Claude thinking budget for a reasoning effort, matching the budgets of
+/// the "think", "think hard", and "ultrathink" keywords.
+fn thinking_tokens(effort: ReasoningEffort) -> u32 {
+    match effort {
+        ReasoningEffort::Low => 4_000,
+        ReasoningEffort::Medium => 10_000,
+        ReasoningEffort::High => 31_999,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -794,8 +1050,82 @@ mod tests {
         let (cmd, args, stdin, _temp) = backend.build_command("test prompt", false);
 
         assert_eq!(cmd, "amp");
-        assert_eq!(args, vec!["--dangerously-allow-all", "-x", "test prompt"]);
+        assert_eq!(
+            args,
+            vec![
+                "--dangerously-allow-all",
+                "--stream-json",
+                "-x",
+                "test prompt"
+            ]
+        );
         assert!(stdin.is_none());
+        assert_eq!(backend.output_format, OutputFormat::StreamJson);
+    }
+
+    #[test]
+    fn test_goose_backend() {
+        let backend = CliBackend::goose();
+        let (cmd, args, stdin, temp) = backend.build_command("test prompt", false);
+
+        assert_eq!(cmd, "goose");
+        assert_eq!(
+            args,
+            vec![
+                "run",
+                "--no-session",
+                "--output-format",
+                "stream-json",
+                "-t",
+                "test prompt"
+            ]
+        );
+        assert!(stdin.is_none());
+        assert!(temp.is_none());
+        assert_eq!(backend.output_format, OutputFormat::GooseStreamJson);
+        assert_eq!(
+            backend.env_vars,
+            vec![("GOOSE_MODE".to_string(), "auto".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_goose_large_prompt_uses_instructions_file() {
+        let backend = CliBackend::goose();
+        let large_prompt = "x".repeat(7001);
+        let (_cmd, args, _stdin, temp) = backend.build_command(&large_prompt, false);
+
+        let temp = temp.expect("instructions file");
+        assert_eq!(std::fs::read_to_string(temp.path()).unwrap(), large_prompt);
+        assert_eq!(args[args.len() - 2], "-i");
+        assert_eq!(args[args.len() - 1], temp.path().display().to_string());
+        assert!(!args.contains(&"-t".to_string()));
+    }
+
+    #[test]
+    fn test_named_backend_contract() {
+        // Every auto-detectable backend must work headless and interactively,
+        // and declare the output format its stream parser expects.
+        for name in crate::DEFAULT_PRIORITY {
+            let headless = CliBackend::from_name(name).unwrap();
+            let (_cmd, args, stdin, _temp) = headless.build_command("contract prompt", false);
+            assert!(
+                args.iter().any(|a| a == "contract prompt") || stdin.is_some(),
+                "{name}: prompt not passed"
+            );
+
+            let interactive = CliBackend::for_interactive_prompt(name).unwrap();
+            assert_eq!(interactive.command, headless.command, "{name}");
+            assert_eq!(interactive.output_format, OutputFormat::Text, "{name}");
+
+            let expected = match *name {
+                "claude" | "amp" => OutputFormat::StreamJson,
+                "pi" => OutputFormat::PiStreamJson,
+                "goose" => OutputFormat::GooseStreamJson,
+                _ => OutputFormat::Text,
+            };
+            assert_eq!(headless.output_format, expected, "{name}");
+        }
     }
 
     #[test]
@@ -884,6 +1214,7 @@ mod tests {
         assert_eq!(args, vec!["-x", "test prompt"]);
         assert!(stdin.is_none());
         assert!(!args.contains(&"--dangerously-allow-all".to_string()));
+        assert!(!args.contains(&"--stream-json".to_string()));
     }
 
     #[test]
@@ -1080,6 +1411,13 @@ mod tests {
         assert_eq!(backend.command, "amp");
     }
 
+    #[test]
+    fn test_from_name_goose() {
+        let backend = CliBackend::from_name("goose").unwrap();
+        assert_eq!(backend.command, "goose");
+        assert_eq!(backend.prompt_flag, Some("-t".to_string()));
+    }
+
     #[test]
     fn test_from_name_copilot() {
         let backend = CliBackend::from_name("copilot").unwrap();
@@ -1141,6 +1479,105 @@ mod tests {
         assert!(backend.args.contains(&"claude-sonnet-4".to_string()));
     }
 
+    #[test]
+    fn test_with_env_vars_replaces_same_name() {
+        let backend = CliBackend::goose().with_env_vars(&[
+            ("GOOSE_MODE".to_string(), "approve".to_string()),
+            ("OPENAI_API_KEY".to_string(), "sk-test".to_string()),
+        ]);
+        assert_eq!(
+            backend.env_vars,
+            vec![
+                ("GOOSE_MODE".to_string(), "approve".to_string()),
+                ("OPENAI_API_KEY".to_string(), "sk-test".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_model_reads_either_flag_form() {
+        assert_eq!(
+            CliBackend::claude().with_model("haiku").model(),
+            Some("haiku")
+        );
+
+        let mut backend = CliBackend::claude();
+        backend.args.push("--model=opus".to_string());
+        assert_eq!(backend.model(), Some("opus"));
+        assert_eq!(CliBackend::claude().model(), None);
+    }
+
+    #[test]
+    fn test_with_model_replaces_existing_model() {
+        let hat_backend = HatBackend::NamedWithArgs {
+            backend_type: "claude".to_string(),
+            args: vec!["--model".to_string(), "claude-sonnet-4".to_string()],
+        };
+        let backend = CliBackend::from_hat_backend(&hat_backend)
+            .unwrap()
+            .with_model("haiku");
+        let (_, args, _, _) = backend.build_command("test", false);
+        assert!(!args.contains(&"claude-sonnet-4".to_string()));
+        let pos = args.iter().position(|a| a == "--model").unwrap();
+        assert_eq!(args[pos + 1], "haiku");
+        assert_eq!(args.iter().filter(|a| *a == "--model").count(), 1);
+
+        let backend = CliBackend::gemini().with_model("gemini-2.5-pro");
+        assert!(
+            backend
+                .args
+                .ends_with(&["--model".to_string(), "gemini-2.5-pro".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_with_generation_passes_supported_settings() {
+        let generation = GenerationConfig {
+            model: Some("opus".to_string()),
+            temperature: Some(0.2),
+            max_output_tokens: Some(2048),
+            reasoning_effort: Some(ReasoningEffort::High),
+        };
+
+        let claude = CliBackend::claude().with_generation(&generation);
+        assert!(
+            claude
+                .args
+                .ends_with(&["--model".to_string(), "opus".to_string()])
+        );
+        assert!(claude.env_vars.contains(&(
+            "CLAUDE_CODE_MAX_OUTPUT_TOKENS".to_string(),
+            "2048".to_string()
+        )));
+        assert!(
+            claude
+                .env_vars
+                .contains(&("MAX_THINKING_TOKENS".to_string(), "31999".to_string()))
+        );
+        assert_eq!(
+            CliBackend::claude().unsupported_generation(&generation),
+            vec!["temperature"]
+        );
+
+        let codex = CliBackend::codex()
+            .with_generation(&generation)
+            .with_generation(&generation);
+        let pos = codex.args.iter().position(|a| a == "-c").unwrap();
+        assert_eq!(codex.args[pos + 1], "model_reasoning_effort=\"high\"");
+        assert_eq!(codex.args.iter().filter(|a| *a == "-c").count(), 1);
+
+        let goose = CliBackend::goose().with_generation(&generation);
+        assert!(
+            goose
+                .env_vars
+                .contains(&("GOOSE_TEMPERATURE".to_string(), "0.2".to_string()))
+        );
+        assert_eq!(
+            CliBackend::goose().unsupported_generation(&generation),
+            vec!["max_output_tokens", "reasoning_effort"]
+        );
+    }
+
     #[test]
     fn test_codex_named_with_args_dangerous_bypass_normalizes_to_yolo() {
         let hat_backend = HatBackend::NamedWithArgs {
@@ -1250,6 +1687,17 @@ mod tests {
         assert!(stdin.is_none());
     }
 
+    #[test]
+    fn test_for_interactive_prompt_goose() {
+        let backend = CliBackend::for_interactive_prompt("goose").unwrap();
+        let (cmd, args, stdin, _temp) = backend.build_command("test prompt", false);
+
+        assert_eq!(cmd, "goose");
+        assert_eq!(args, vec!["run", "--interactive", "-t", "test prompt"]);
+        assert!(backend.env_vars.is_empty());
+        assert!(stdin.is_none());
+    }
+
     #[test]
     fn test_for_interactive_prompt_copilot() {
         let backend = CliBackend::for_interactive_prompt("copilot").unwrap();
@@ -1564,4 +2012,32 @@ mod tests {
         assert!(CliBackend::opencode().env_vars.is_empty());
         assert!(CliBackend::pi().env_vars.is_empty());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_limited_command_limits_the_container_not_the_runtime() {
+        let limits = ResourceLimits {
+            memory_mb: Some(512),
+            ..Default::default()
+        };
+
+        let (cmd, args, _, _) = CliBackend::claude().build_limited_command("hi", false, &limits);
+        assert_eq!(cmd, "sh");
+        assert!(args[1].contains("ulimit -v 524288"));
+
+        let container = ContainerEnvironment {
+            runtime: "docker".to_string(),
+            image: "node:20".to_string(),
+            workspace: std::path::PathBuf::from("/ws"),
+            mounts: vec![],
+            forward_env: vec![],
+            run_args: vec![],
+        };
+        let (cmd, args, _, _) = CliBackend::claude()
+            .with_container(container)
+            .build_limited_command("hi", false, &limits);
+        assert_eq!(cmd, "docker");
+        assert!(args.windows(2).any(|pair| pair == ["--memory", "512m"]));
+        assert!(!args.iter().any(|arg| arg.contains("ulimit")));
+    }
 }
@@ -1,22 +1,38 @@
 //! CLI executor for running prompts through backends.
 //!
-//! Executes prompts via CLI tools with real-time streaming output.
-//! Supports optional execution timeout with graceful SIGTERM termination.
+//! Executes prompts via CLI tools with real-time streaming output: lines reach
+//! the output writer, and any [`OutputLine`] channel, as the backend prints them.
+//! Supports an optional execution timeout: the backend's process tree is asked
+//! to exit (SIGTERM to its process group on Unix, `CTRL_BREAK` on Windows) and
+//! killed if it is still running after a grace period.
 
 use crate::cli_backend::CliBackend;
 #[cfg(test)]
 use crate::cli_backend::{OutputFormat, PromptMode};
-#[cfg(unix)]
-use nix::sys::signal::{Signal, kill};
-#[cfg(unix)]
-use nix::unistd::Pid;
+use crate::output_log::OutputLog;
+use crate::process::{ProcessTree, configure_command};
+use crate::response_cache::ResponseCache;
+use ralph_core::{ResourceLimits, RetryPolicy};
 use std::io::Write;
+use std::path::PathBuf;
 use std::process::Stdio;
+use std::sync::Mutex;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
 use tracing::{debug, warn};
 
+/// A line the backend printed, sent while it runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputLine {
+    Stdout(String),
+    Stderr(String),
+}
+
+/// How long a timed-out backend gets to exit before it is killed.
+const KILL_GRACE: Duration = Duration::from_secs(5);
+
 /// Result of a CLI execution.
 #[derive(Debug)]
 pub struct ExecutionResult {
@@ -28,28 +44,103 @@ pub struct ExecutionResult {
     pub exit_code: Option<i32>,
     /// Whether the execution was terminated due to timeout.
     pub timed_out: bool,
+    /// Whether the output was served from the response cache.
+    pub cached: bool,
+    /// Attempts retried under the [`RetryPolicy`] before this result.
+    pub retries: u32,
 }
 
 /// Executor for running prompts through CLI backends.
 #[derive(Debug)]
 pub struct CliExecutor {
     backend: CliBackend,
+    limits: ResourceLimits,
+    retry: RetryPolicy,
+    working_dir: Option<PathBuf>,
+    cache: Option<ResponseCache>,
+    output_log: Option<PathBuf>,
+    line_sender: Option<UnboundedSender<OutputLine>>,
 }
 
 impl CliExecutor {
     /// Creates a new executor with the given backend.
     pub fn new(backend: CliBackend) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            limits: ResourceLimits::default(),
+            retry: RetryPolicy::default(),
+            working_dir: None,
+            cache: None,
+            output_log: None,
+            line_sender: None,
+        }
+    }
+
+    /// Sets resource limits applied to the spawned backend process.
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Retries failures that `policy` considers transient.
+    #[must_use]
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Runs the backend in `dir` instead of the current directory.
+    #[must_use]
+    pub fn with_working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    /// Serves identical prompts from `cache` instead of running the backend.
+    ///
+    /// Successful responses are written back to the cache.
+    #[must_use]
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Copies output to `path` line by line as the backend prints it.
+    ///
+    /// Stderr lines get the same `[stderr] ` prefix as in the returned output.
+    /// See [`OutputLog`].
+    #[must_use]
+    pub fn with_output_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output_log = Some(path.into());
+        self
+    }
+
+    /// Sends each stdout and stderr line to `sender` as the backend prints it.
+    ///
+    /// Lets callers react to events or the completion promise mid-iteration
+    /// instead of after the process exits. Lines are sent whether or not
+    /// `verbose` shows stderr; a response served from the cache is sent as
+    /// stdout lines. A closed receiver is ignored.
+    #[must_use]
+    pub fn with_line_sender(mut self, sender: UnboundedSender<OutputLine>) -> Self {
+        self.line_sender = Some(sender);
+        self
     }
 
     /// Executes a prompt and streams output to the provided writer.
     ///
-    /// Output is streamed line-by-line to the writer while being accumulated
-    /// for the return value. If `timeout` is provided and the execution exceeds
-    /// it, the process receives SIGTERM and the result indicates timeout.
+    /// Output is streamed line-by-line to the writer as it arrives, stdout and
+    /// stderr interleaved, while being accumulated for the return value
+    /// (stdout first, then stderr). If `timeout` is provided and the execution exceeds
+    /// it, the backend's process group receives SIGTERM, then SIGKILL after a grace
+    /// period, and the result indicates timeout.
     ///
     /// When `verbose` is true, stderr output is also written to the output writer
     /// with a `[stderr]` prefix. When false, stderr is captured but not displayed.
+    ///
+    /// A failure the [`RetryPolicy`] considers transient is retried after its
+    /// backoff; `timeout` applies to each attempt. Only the last attempt's
+    /// output is returned.
     pub async fn execute<W: Write + Send>(
         &self,
         prompt: &str,
@@ -57,18 +148,98 @@ impl CliExecutor {
         timeout: Option<Duration>,
         verbose: bool,
     ) -> std::io::Result<ExecutionResult> {
+        let mut retries = 0;
+        loop {
+            let mut result = self
+                .execute_once(prompt, &mut output_writer, timeout, verbose)
+                .await?;
+            result.retries = retries;
+            if result.success || result.timed_out || retries >= self.retry.max_retries {
+                return Ok(result);
+            }
+            let stderr: String = result
+                .output
+                .lines()
+                .filter_map(|line| line.strip_prefix("[stderr] "))
+                .collect::<Vec<_>>()
+                .join("\n");
+            if !self.retry.is_transient(result.exit_code, &stderr) {
+                return Ok(result);
+            }
+
+            retries += 1;
+            let delay = self.retry.backoff(retries);
+            warn!(
+                exit_code = ?result.exit_code,
+                retry = retries,
+                max_retries = self.retry.max_retries,
+                delay_secs = delay.as_secs(),
+                "Transient backend failure, retrying"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Runs the backend once; see [`execute`](Self::execute).
+    async fn execute_once<W: Write + Send>(
+        &self,
+        prompt: &str,
+        mut output_writer: W,
+        timeout: Option<Duration>,
+        verbose: bool,
+    ) -> std::io::Result<ExecutionResult> {
+        let output_log = Mutex::new(OutputLog::open(self.output_log.as_deref()));
+        let log = |data: &[u8]| {
+            if let Ok(mut guard) = output_log.lock()
+                && let Some(log) = guard.as_mut()
+            {
+                log.write(data);
+            }
+        };
+
+        let send = |line: OutputLine| {
+            if let Some(sender) = &self.line_sender {
+                let _ = sender.send(line);
+            }
+        };
+
+        if let Some(cache) = &self.cache
+            && let Some(entry) = cache.get(&self.backend, prompt)
+        {
+            debug!(cache_dir = ?cache.dir(), "Serving prompt from response cache");
+            log(entry.output.as_bytes());
+            for line in entry.output.lines() {
+                send(OutputLine::Stdout(line.to_string()));
+            }
+            output_writer.write_all(entry.output.as_bytes())?;
+            output_writer.flush()?;
+            return Ok(ExecutionResult {
+                output: entry.output,
+                success: true,
+                exit_code: Some(0),
+                timed_out: false,
+                cached: true,
+                retries: 0,
+            });
+        }
+
         // Note: _temp_file is kept alive for the duration of this function scope.
         // For large prompts (>7000 chars), Claude reads from the temp file.
-        let (cmd, args, stdin_input, _temp_file) = self.backend.build_command(prompt, false);
+        let (cmd, args, stdin_input, _temp_file) =
+            self.backend
+                .build_limited_command(prompt, false, &self.limits);
 
         let mut command = Command::new(&cmd);
         command.args(&args);
         command.stdout(Stdio::piped());
         command.stderr(Stdio::piped());
+        configure_command(&mut command);
 
         // Set working directory to current directory (mirrors PTY executor behavior)
         // Use fallback to "." if current_dir fails (e.g., E2E test workspaces)
-        let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        let cwd = self.working_dir.clone().unwrap_or_else(|| {
+            std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
+        });
         command.current_dir(&cwd);
 
         // Apply backend-specific environment variables (e.g., Agent Teams env var)
@@ -81,11 +252,18 @@ impl CliExecutor {
             "Spawning CLI command"
         );
 
-        if stdin_input.is_some() {
-            command.stdin(Stdio::piped());
-        }
+        // The backend runs outside the terminal's foreground process group, so
+        // it must not read the terminal.
+        command.stdin(if stdin_input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        });
 
         let mut child = command.spawn()?;
+        // Held until the function returns; dropping it kills any processes the
+        // backend left running.
+        let process_tree = child.id().map(ProcessTree::attach_group);
 
         // Write to stdin if needed
         if let Some(input) = stdin_input
@@ -102,6 +280,17 @@ impl CliExecutor {
         let stdout_handle = child.stdout.take();
         let stderr_handle = child.stderr.take();
 
+        // Both readers write lines through as they arrive
+        let output_writer = Mutex::new(output_writer);
+        let write_line = |line: std::fmt::Arguments<'_>| -> std::io::Result<()> {
+            let mut writer = output_writer
+                .lock()
+                .map_err(|_| std::io::Error::other("output writer poisoned"))?;
+            writer.write_fmt(line)?;
+            writer.write_all(b"\n")?;
+            writer.flush()
+        };
+
         // Wrap the streaming in a timeout if configured
         // Read stdout and stderr CONCURRENTLY to avoid pipe buffer deadlock
         let stream_result = async {
@@ -112,6 +301,9 @@ impl CliExecutor {
                     let reader = BufReader::new(stdout);
                     let mut lines = reader.lines();
                     while let Some(line) = lines.next_line().await? {
+                        log(format!("{line}\n").as_bytes());
+                        write_line(format_args!("{line}"))?;
+                        send(OutputLine::Stdout(line.clone()));
                         lines_out.push(line);
                     }
                 }
@@ -124,6 +316,12 @@ impl CliExecutor {
                     let reader = BufReader::new(stderr);
                     let mut lines = reader.lines();
                     while let Some(line) = lines.next_line().await? {
+                        log(format!("[stderr] {line}\n").as_bytes());
+                        // Stderr is shown (prefixed) only in verbose mode
+                        if verbose {
+                            write_line(format_args!("[stderr] {line}"))?;
+                        }
+                        send(OutputLine::Stderr(line.clone()));
                         lines_out.push(line);
                     }
                 }
@@ -133,20 +331,6 @@ impl CliExecutor {
             // Read both streams concurrently to prevent deadlock
             let (stdout_lines, stderr_lines) = tokio::try_join!(stdout_future, stderr_future)?;
 
-            // Write stdout lines first (main output)
-            for line in &stdout_lines {
-                writeln!(output_writer, "{line}")?;
-            }
-
-            // Write stderr lines (prefixed) only in verbose mode
-            if verbose {
-                for line in &stderr_lines {
-                    writeln!(output_writer, "[stderr] {line}")?;
-                }
-            }
-
-            output_writer.flush()?;
-
             // Build accumulated output (stdout first, then stderr)
             let mut accumulated = String::new();
             for line in stdout_lines {
@@ -168,13 +352,13 @@ impl CliExecutor {
                 match tokio::time::timeout(duration, stream_result).await {
                     Ok(result) => result?,
                     Err(_) => {
-                        // Timeout elapsed - send SIGTERM to the child process
+                        // Timeout elapsed - ask the child process to exit
                         warn!(
                             timeout_secs = duration.as_secs(),
-                            "Execution timeout reached, sending SIGTERM"
+                            "Execution timeout reached, terminating child process"
                         );
                         timed_out = true;
-                        Self::terminate_child(&mut child)?;
+                        Self::terminate_child(process_tree.as_ref());
                         String::new() // Return empty output on timeout
                     }
                 }
@@ -182,34 +366,50 @@ impl CliExecutor {
             None => stream_result.await?,
         };
 
-        let status = child.wait().await?;
+        let status = if timed_out {
+            match tokio::time::timeout(KILL_GRACE, child.wait()).await {
+                Ok(status) => status?,
+                Err(_) => {
+                    warn!("Child process ignored termination, killing it");
+                    Self::kill_child(process_tree.as_ref());
+                    child.wait().await?
+                }
+            }
+        } else {
+            child.wait().await?
+        };
+        let success = status.success() && !timed_out;
+
+        if success
+            && let Some(cache) = &self.cache
+            && let Err(e) = cache.put(&self.backend, prompt, &accumulated_output)
+        {
+            warn!(cache_dir = ?cache.dir(), error = %e, "Failed to cache backend response");
+        }
 
         Ok(ExecutionResult {
             output: accumulated_output,
-            success: status.success() && !timed_out,
+            success,
             exit_code: status.code(),
             timed_out,
+            cached: false,
+            retries: 0,
         })
     }
 
-    /// Terminates the child process with SIGTERM.
-    fn terminate_child(child: &mut tokio::process::Child) -> std::io::Result<()> {
-        #[cfg(not(unix))]
-        {
-            // SIGTERM doesn't exist on Windows. Best-effort termination:
-            // On Unix this would be SIGKILL, on Windows it maps to process termination.
-            child.start_kill()
+    /// Terminates the child process (SIGTERM on Unix, `CTRL_BREAK` on Windows).
+    fn terminate_child(process_tree: Option<&ProcessTree>) {
+        if let Some(tree) = process_tree {
+            debug!(pid = tree.pid(), "Terminating child process");
+            let _ = tree.terminate();
         }
+    }
 
-        #[cfg(unix)]
-        if let Some(pid) = child.id() {
-            #[allow(clippy::cast_possible_wrap)]
-            let pid = Pid::from_raw(pid as i32);
-            debug!(%pid, "Sending SIGTERM to child process");
-            let _ = kill(pid, Signal::SIGTERM);
-            Ok(())
-        } else {
-            Ok(())
+    /// Kills the child process and its descendants.
+    fn kill_child(process_tree: Option<&ProcessTree>) {
+        if let Some(tree) = process_tree {
+            debug!(pid = tree.pid(), "Killing child process");
+            let _ = tree.kill();
         }
     }
 
@@ -247,6 +447,7 @@ mod tests {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
 
         let executor = CliExecutor::new(backend);
@@ -262,6 +463,83 @@ mod tests {
         assert!(result.output.contains("hello world"));
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_output_log_keeps_output_of_timed_out_run() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log_path = dir.path().join("sessions/primary/iter-1.out");
+        let backend = CliBackend {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "echo started; echo warming up >&2; exec sleep 5".to_string(),
+            ],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: None,
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        };
+
+        let executor = CliExecutor::new(backend).with_output_log(&log_path);
+        let result = executor
+            .execute("prompt", Vec::new(), Some(Duration::from_secs(1)), false)
+            .await
+            .unwrap();
+
+        assert!(result.timed_out);
+        assert_eq!(result.output, "");
+        // Stdout and stderr are read concurrently, so their lines may interleave
+        let logged = std::fs::read_to_string(&log_path).unwrap();
+        let mut lines: Vec<_> = logged.lines().collect();
+        lines.sort_unstable();
+        assert_eq!(lines, vec!["[stderr] warming up", "started"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_line_sender_streams_before_exit() {
+        let backend = CliBackend {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "echo first; sleep 1; echo warming up >&2; echo second".to_string(),
+            ],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: None,
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        };
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let executor = CliExecutor::new(backend).with_line_sender(tx);
+
+        let result = {
+            let run = executor.execute("prompt", Vec::new(), None, false);
+            tokio::pin!(run);
+            let first = tokio::select! {
+                line = rx.recv() => line,
+                _ = &mut run => panic!("backend exited before its first line arrived"),
+            };
+            assert_eq!(first, Some(OutputLine::Stdout("first".to_string())));
+            run.await.unwrap()
+        };
+        assert_eq!(result.output, "first\nsecond\n[stderr] warming up\n");
+        drop(executor);
+        let mut rest = Vec::new();
+        while let Some(line) = rx.recv().await {
+            rest.push(line);
+        }
+        rest.sort_by_key(|line| matches!(line, OutputLine::Stderr(_)));
+        assert_eq!(
+            rest,
+            [
+                OutputLine::Stdout("second".to_string()),
+                OutputLine::Stderr("warming up".to_string()),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_execute_stdin() {
         // Use cat to test stdin mode
@@ -272,6 +550,7 @@ mod tests {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
 
         let executor = CliExecutor::new(backend);
@@ -281,6 +560,60 @@ mod tests {
         assert!(result.output.contains("stdin test"));
     }
 
+    #[tokio::test]
+    async fn test_execute_serves_repeated_prompt_from_cache() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let backend = CliBackend {
+            command: "echo".to_string(),
+            args: vec![],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: None,
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        };
+        let executor = CliExecutor::new(backend).with_cache(ResponseCache::new(dir.path()));
+
+        let first = executor.execute_capture("plan it").await.unwrap();
+        assert!(!first.cached);
+
+        let mut output = Vec::new();
+        let second = executor
+            .execute("plan it", &mut output, None, false)
+            .await
+            .unwrap();
+        assert!(second.cached);
+        assert!(second.success);
+        assert_eq!(second.output, first.output);
+        assert_eq!(String::from_utf8(output).unwrap(), first.output);
+
+        let other = executor
+            .execute_capture("plan something else")
+            .await
+            .unwrap();
+        assert!(!other.cached);
+    }
+
+    #[tokio::test]
+    async fn test_execute_failure_is_not_cached() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let backend = CliBackend {
+            command: "false".to_string(),
+            args: vec![],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: None,
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        };
+        let executor = CliExecutor::new(backend).with_cache(ResponseCache::new(dir.path()));
+
+        executor.execute_capture("").await.unwrap();
+        let again = executor.execute_capture("").await.unwrap();
+        assert!(!again.cached);
+        assert!(!again.success);
+    }
+
     #[tokio::test]
     async fn test_execute_failure() {
         let backend = CliBackend {
@@ -290,6 +623,7 @@ mod tests {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
 
         let executor = CliExecutor::new(backend);
@@ -312,6 +646,7 @@ mod tests {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
 
         let executor = CliExecutor::new(backend);
@@ -330,6 +665,111 @@ mod tests {
         );
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_timeout_kills_backend_ignoring_sigterm() {
+        // A stuck agent: it and its children ignore SIGTERM
+        let backend = CliBackend {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "trap '' TERM; sleep 30 & wait".to_string(),
+            ],
+            prompt_mode: PromptMode::Stdin,
+            prompt_flag: None,
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        };
+
+        let started = std::time::Instant::now();
+        let result = CliExecutor::new(backend)
+            .execute_capture_with_timeout("", Some(Duration::from_millis(100)))
+            .await
+            .unwrap();
+
+        assert!(result.timed_out);
+        assert!(!result.success);
+        assert!(
+            started.elapsed() < KILL_GRACE + Duration::from_secs(5),
+            "Backend should be killed after the grace period, took {:?}",
+            started.elapsed()
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_retries_transient_failures() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // Overloaded on the first two attempts, then succeeds
+        let script = format!(
+            "n=$(cat {0} 2>/dev/null || echo 0); echo $((n + 1)) > {0}; \
+             if [ $n -lt 2 ]; then echo 'API Error: 529 Overloaded' >&2; exit 1; fi; echo done",
+            dir.path().join("attempts").display()
+        );
+        let backend = CliBackend {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script],
+            prompt_mode: PromptMode::Stdin,
+            prompt_flag: None,
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        };
+        let policy = RetryPolicy {
+            max_retries: 3,
+            backoff_seconds: 0,
+            ..RetryPolicy::default()
+        };
+
+        let result = CliExecutor::new(backend.clone())
+            .with_retry(policy.clone())
+            .execute_capture("")
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.retries, 2);
+        assert_eq!(result.output, "done\n");
+
+        // Out of retries: the last failure is reported
+        std::fs::remove_file(dir.path().join("attempts")).unwrap();
+        let result = CliExecutor::new(backend)
+            .with_retry(RetryPolicy {
+                max_retries: 1,
+                ..policy
+            })
+            .execute_capture("")
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.retries, 1);
+        assert!(result.output.contains("529 Overloaded"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_does_not_retry_other_failures() {
+        let backend = CliBackend {
+            command: "false".to_string(),
+            args: vec![],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: None,
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        };
+        let result = CliExecutor::new(backend)
+            .with_retry(RetryPolicy {
+                max_retries: 3,
+                backoff_seconds: 0,
+                ..RetryPolicy::default()
+            })
+            .execute_capture("")
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.retries, 0);
+    }
+
     #[tokio::test]
     async fn test_execute_no_timeout_when_fast() {
         // Use echo which completes immediately
@@ -340,6 +780,7 @@ mod tests {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
 
         let executor = CliExecutor::new(backend);
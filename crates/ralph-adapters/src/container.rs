@@ -0,0 +1,234 @@
+//! Container execution environments for backend processes.
+//!
+//! Wraps a backend command line in `<runtime> run` so the agent CLI runs
+//! inside a fixed image with the workspace bind-mounted at the same path.
+//! Paths the agent sees (prompts, scratchpad, events file) stay identical
+//! inside and outside the container.
+
+use ralph_core::{EnvironmentConfig, ResourceLimits};
+use std::path::{Path, PathBuf};
+
+/// A resolved container environment, ready to wrap commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerEnvironment {
+    /// Container runtime CLI ("docker" or "podman").
+    pub runtime: String,
+    /// Image the backend runs in.
+    pub image: String,
+    /// Workspace mounted read-write and used as the working directory.
+    pub workspace: PathBuf,
+    /// Extra `host:container[:options]` mounts, with `~` already expanded.
+    pub mounts: Vec<String>,
+    /// Host environment variable names forwarded into the container.
+    pub forward_env: Vec<String>,
+    /// Extra arguments for `<runtime> run`.
+    pub run_args: Vec<String>,
+}
+
+impl ContainerEnvironment {
+    /// Resolves a config entry against the loop's workspace.
+    pub fn from_config(config: &EnvironmentConfig, workspace: &Path) -> Self {
+        Self {
+            runtime: config.runtime.clone(),
+            image: config.image.clone(),
+            workspace: workspace.to_path_buf(),
+            mounts: config.mounts.iter().map(|m| expand_home(m)).collect(),
+            forward_env: config.env.clone(),
+            run_args: config.run_args.clone(),
+        }
+    }
+
+    /// Wraps `command args...` so it runs inside the container.
+    ///
+    /// `env_vars` are the backend's own variables. They're forwarded by name
+    /// (`-e NAME`), so values such as API keys stay out of the runtime's
+    /// command line; the caller sets them in the runtime's environment.
+    /// `extra_files` are host files the command references (e.g. a prompt temp
+    /// file) and are mounted read-only at the same path. `tty` allocates a
+    /// pseudo-terminal for PTY-driven backends. `limits` are passed to the
+    /// runtime (`--memory`, `--ulimit`), because a `ulimit` wrapper around the
+    /// runtime would only limit its client process, not the container.
+    pub fn wrap(
+        &self,
+        command: &str,
+        args: Vec<String>,
+        env_vars: &[(String, String)],
+        extra_files: &[&Path],
+        tty: bool,
+        limits: &ResourceLimits,
+    ) -> (String, Vec<String>) {
+        let workspace = self.workspace.display().to_string();
+        let mut wrapped = vec!["run".to_string(), "--rm".to_string(), "-i".to_string()];
+        if tty {
+            wrapped.push("-t".to_string());
+        }
+        wrapped.extend([
+            "-v".to_string(),
+            format!("{workspace}:{workspace}"),
+            "-w".to_string(),
+            workspace,
+        ]);
+        for mount in &self.mounts {
+            wrapped.extend(["-v".to_string(), mount.clone()]);
+        }
+        for file in extra_files {
+            let path = file.display();
+            wrapped.extend(["-v".to_string(), format!("{path}:{path}:ro")]);
+        }
+        for (key, _) in env_vars {
+            wrapped.extend(["-e".to_string(), key.clone()]);
+        }
+        for name in &self.forward_env {
+            // `-e NAME` copies the value from the host environment.
+            wrapped.extend(["-e".to_string(), name.clone()]);
+        }
+        wrapped.extend(limit_args(limits));
+        wrapped.extend(self.run_args.iter().cloned());
+        wrapped.push(self.image.clone());
+        wrapped.push(command.to_string());
+        wrapped.extend(args);
+
+        (self.runtime.clone(), wrapped)
+    }
+}
+
+/// Maps `cli.limits` to `<runtime> run` flags. They come before `run_args`, so
+/// flags set there take precedence.
+fn limit_args(limits: &ResourceLimits) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(secs) = limits.cpu_seconds {
+        args.extend(["--ulimit".to_string(), format!("cpu={secs}:{secs}")]);
+    }
+    if let Some(mb) = limits.memory_mb {
+        args.extend(["--memory".to_string(), format!("{mb}m")]);
+    }
+    if let Some(files) = limits.max_open_files {
+        args.extend(["--ulimit".to_string(), format!("nofile={files}:{files}")]);
+    }
+    args
+}
+
+fn expand_home(mount: &str) -> String {
+    match (mount.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => mount.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env() -> ContainerEnvironment {
+        ContainerEnvironment {
+            runtime: "docker".to_string(),
+            image: "rust:1.80".to_string(),
+            workspace: PathBuf::from("/work/project"),
+            mounts: vec!["/cache:/cache".to_string()],
+            forward_env: vec!["ANTHROPIC_API_KEY".to_string()],
+            run_args: vec!["--network=host".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_wrap_mounts_workspace_and_runs_command_in_image() {
+        let (cmd, args) = env().wrap(
+            "claude",
+            vec!["-p".to_string(), "hello".to_string()],
+            &[("FOO".to_string(), "1".to_string())],
+            &[],
+            false,
+            &ResourceLimits::default(),
+        );
+
+        assert_eq!(cmd, "docker");
+        assert_eq!(
+            args,
+            [
+                "run",
+                "--rm",
+                "-i",
+                "-v",
+                "/work/project:/work/project",
+                "-w",
+                "/work/project",
+                "-v",
+                "/cache:/cache",
+                "-e",
+                "FOO",
+                "-e",
+                "ANTHROPIC_API_KEY",
+                "--network=host",
+                "rust:1.80",
+                "claude",
+                "-p",
+                "hello",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrap_mounts_extra_files_read_only_and_allocates_tty() {
+        let (_, args) = env().wrap(
+            "claude",
+            vec![],
+            &[],
+            &[Path::new("/tmp/prompt.md")],
+            true,
+            &ResourceLimits::default(),
+        );
+        assert_eq!(&args[..4], ["run", "--rm", "-i", "-t"]);
+        assert!(args.contains(&"/tmp/prompt.md:/tmp/prompt.md:ro".to_string()));
+    }
+
+    #[test]
+    fn test_wrap_passes_limits_to_the_runtime() {
+        let limits = ResourceLimits {
+            cpu_seconds: Some(60),
+            memory_mb: Some(512),
+            max_open_files: Some(256),
+        };
+        let (cmd, args) = env().wrap("claude", vec![], &[], &[], false, &limits);
+
+        // The runtime enforces the limits on the container; no `sh -c ulimit`
+        // wrapper around the runtime client
+        assert_eq!(cmd, "docker");
+        let image = args.iter().position(|arg| arg == "rust:1.80").unwrap();
+        let flags = &args[..image];
+        assert!(
+            flags
+                .windows(2)
+                .any(|pair| pair == ["--ulimit", "cpu=60:60"])
+        );
+        assert!(flags.windows(2).any(|pair| pair == ["--memory", "512m"]));
+        assert!(
+            flags
+                .windows(2)
+                .any(|pair| pair == ["--ulimit", "nofile=256:256"])
+        );
+        // run_args come after, so they can override
+        let memory = flags.iter().position(|arg| arg == "--memory").unwrap();
+        let run_arg = flags
+            .iter()
+            .position(|arg| arg == "--network=host")
+            .unwrap();
+        assert!(memory < run_arg);
+    }
+
+    #[test]
+    fn test_from_config_expands_home_in_mounts() {
+        let config = EnvironmentConfig {
+            image: "node:20".to_string(),
+            mounts: vec!["~/.npm:/root/.npm".to_string(), "/abs:/abs".to_string()],
+            env: vec![],
+            runtime: "podman".to_string(),
+            run_args: vec![],
+        };
+        let env = ContainerEnvironment::from_config(&config, Path::new("/ws"));
+
+        assert_eq!(env.runtime, "podman");
+        assert!(!env.mounts[0].starts_with('~'));
+        assert!(env.mounts[0].ends_with("/.npm:/root/.npm"));
+        assert_eq!(env.mounts[1], "/abs:/abs");
+    }
+}
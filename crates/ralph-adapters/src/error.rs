@@ -0,0 +1,114 @@
+//! Error type for backend adapters.
+//!
+//! Wraps [`ralph_core::Error`] and adds the failures that only happen while
+//! picking or running a backend. [`Error::code`] maps every variant onto the
+//! shared [`ErrorCode`] classes.
+
+use crate::api_backend::ApiError;
+use crate::auto_detect::NoBackendError;
+use crate::cli_backend::CustomBackendError;
+use ralph_core::ErrorCode;
+
+/// Top-level error for `ralph-adapters`.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// A failure from `ralph-core`.
+    #[error(transparent)]
+    Core(#[from] ralph_core::Error),
+
+    /// No backend CLI was found in PATH.
+    #[error(transparent)]
+    NoBackend(#[from] NoBackendError),
+
+    /// A custom backend was configured without a command.
+    #[error(transparent)]
+    CustomBackend(#[from] CustomBackendError),
+
+    /// A configured credential could not be fetched.
+    #[error(transparent)]
+    Credentials(#[from] ralph_core::credentials::CredentialError),
+
+    /// The Messages API backend failed.
+    #[error(transparent)]
+    Api(#[from] ApiError),
+
+    /// The backend process could not be started.
+    #[error("Failed to spawn backend '{command}': {source}")]
+    Spawn {
+        command: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Any other I/O failure.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl Error {
+    /// Returns the failure class of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::Core(err) => err.code(),
+            Error::NoBackend(_) | Error::Spawn { .. } => ErrorCode::Backend,
+            Error::Api(ApiError::MissingKey(_)) => ErrorCode::Config,
+            Error::Api(ApiError::Request(_)) => ErrorCode::Backend,
+            Error::CustomBackend(_) | Error::Credentials(_) => ErrorCode::Config,
+            Error::Io(_) => ErrorCode::Io,
+        }
+    }
+}
+
+/// Classifies a single error value from this crate, `ralph-core`, or `ralph-proto`.
+///
+/// Extends [`ErrorCode::classify`] with the adapter error types.
+pub fn classify(err: &(dyn std::error::Error + 'static)) -> Option<ErrorCode> {
+    if let Some(err) = err.downcast_ref::<Error>() {
+        Some(err.code())
+    } else if err.is::<NoBackendError>() {
+        Some(ErrorCode::Backend)
+    } else if err.is::<CustomBackendError>() {
+        Some(ErrorCode::Config)
+    } else {
+        ErrorCode::classify(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_errors_map_to_backend_class() {
+        let err: Error = NoBackendError {
+            checked: vec!["claude".to_string()],
+        }
+        .into();
+        assert_eq!(err.code(), ErrorCode::Backend);
+        assert_eq!(err.code().exit_code(), 69);
+
+        let err = Error::Spawn {
+            command: "claude".to_string(),
+            source: std::io::Error::from(std::io::ErrorKind::NotFound),
+        };
+        assert_eq!(err.code(), ErrorCode::Backend);
+    }
+
+    #[test]
+    fn test_core_errors_keep_their_class() {
+        let err: Error =
+            ralph_core::Error::from(ralph_core::ConfigError::InvalidCompletionPromise).into();
+        assert_eq!(err.code(), ErrorCode::Config);
+    }
+
+    #[test]
+    fn test_classify_falls_back_to_core() {
+        assert_eq!(classify(&CustomBackendError), Some(ErrorCode::Config));
+        assert_eq!(
+            classify(&ralph_core::ConfigError::InvalidCompletionPromise),
+            Some(ErrorCode::Config)
+        );
+        assert_eq!(classify(&std::fmt::Error), None);
+    }
+}
@@ -0,0 +1,303 @@
+//! Goose stream event types for parsing `--output-format stream-json` output.
+//!
+//! When invoked as `goose run --output-format stream-json`, goose emits one
+//! JSON event per line: `message` events carrying (partial) conversation
+//! messages, `model_change` when the lead/worker model switches, `error`,
+//! and a final `complete` event with the session's token count.
+//!
+//! Messages hold a list of content items. Assistant `text` items are
+//! streamed output, `toolRequest` items are tool calls, and `toolResponse`
+//! items (sent back under the user role) are tool results. Unknown events
+//! and content types are ignored for forward compatibility.
+
+use crate::stream_handler::StreamHandler;
+use serde::{Deserialize, Serialize};
+
+/// Events from goose's `stream-json` output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GooseStreamEvent {
+    /// A conversation message, possibly a partial chunk of one.
+    Message { message: GooseMessage },
+
+    /// The active model changed.
+    ModelChange { model: String },
+
+    /// The session failed.
+    Error { error: String },
+
+    /// The session finished.
+    Complete {
+        #[serde(default)]
+        total_tokens: Option<u64>,
+        #[serde(default)]
+        input_tokens: Option<u64>,
+        #[serde(default)]
+        output_tokens: Option<u64>,
+    },
+
+    /// All other events (notifications, etc.)
+    #[serde(other)]
+    Other,
+}
+
+/// A goose conversation message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GooseMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: Vec<GooseContent>,
+}
+
+/// A content item within a goose message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GooseContent {
+    Text {
+        text: String,
+    },
+    Thinking {
+        thinking: String,
+    },
+    ToolRequest {
+        id: String,
+        #[serde(rename = "toolCall")]
+        tool_call: serde_json::Value,
+    },
+    ToolResponse {
+        id: String,
+        #[serde(rename = "toolResult")]
+        tool_result: serde_json::Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Parses NDJSON lines from goose's stream output.
+pub struct GooseStreamParser;
+
+impl GooseStreamParser {
+    /// Parse a single line of NDJSON output.
+    ///
+    /// Returns `None` for empty lines or malformed JSON (logged at debug level).
+    pub fn parse_line(line: &str) -> Option<GooseStreamEvent> {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        match serde_json::from_str::<GooseStreamEvent>(trimmed) {
+            Ok(event) => Some(event),
+            Err(e) => {
+                tracing::debug!("Skipping malformed goose JSON: {} (error: {})", trimmed, e);
+                None
+            }
+        }
+    }
+}
+
+/// State accumulated across events for the session summary.
+#[derive(Debug, Default)]
+pub struct GooseSessionState {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub num_turns: u32,
+    pub model: Option<String>,
+    pub is_error: bool,
+}
+
+/// Dispatch a goose stream event to the `StreamHandler`.
+///
+/// Accumulates token and turn data in `state` for the final `on_complete()`
+/// call. Appends assistant text to `extracted_text` for LOOP_COMPLETE detection.
+pub fn dispatch_goose_stream_event<H: StreamHandler>(
+    event: GooseStreamEvent,
+    handler: &mut H,
+    extracted_text: &mut String,
+    state: &mut GooseSessionState,
+    verbose: bool,
+) {
+    match event {
+        GooseStreamEvent::Message { message } => {
+            let assistant = message.role == "assistant";
+            let mut requested_tool = false;
+            for content in message.content {
+                match content {
+                    GooseContent::Text { text } if assistant => {
+                        handler.on_text(&text);
+                        extracted_text.push_str(&text);
+                    }
+                    GooseContent::Thinking { thinking } if assistant && verbose => {
+                        handler.on_text(&thinking);
+                    }
+                    GooseContent::ToolRequest { id, tool_call } => {
+                        requested_tool = true;
+                        let call = tool_call.get("value").unwrap_or(&tool_call);
+                        let name = call.get("name").and_then(|n| n.as_str()).unwrap_or("tool");
+                        let arguments = call
+                            .get("arguments")
+                            .cloned()
+                            .unwrap_or(serde_json::Value::Null);
+                        handler.on_tool_call(name, &id, &arguments);
+                    }
+                    GooseContent::ToolResponse { id, tool_result } => {
+                        let output = tool_result_text(&tool_result);
+                        if tool_result.get("status").and_then(|s| s.as_str()) == Some("error")
+                            || tool_result
+                                .pointer("/value/isError")
+                                .and_then(serde_json::Value::as_bool)
+                                == Some(true)
+                        {
+                            handler.on_error(&output);
+                        } else {
+                            handler.on_tool_result(&id, &output);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if requested_tool {
+                state.num_turns += 1;
+            }
+        }
+        GooseStreamEvent::ModelChange { model } => {
+            state.model = Some(model);
+        }
+        GooseStreamEvent::Error { error } => {
+            state.is_error = true;
+            handler.on_error(&error);
+        }
+        GooseStreamEvent::Complete {
+            total_tokens,
+            input_tokens,
+            output_tokens,
+        } => {
+            state.num_turns += 1;
+            match (input_tokens, output_tokens) {
+                (None, None) => {
+                    // Goose only reports a total; prompt tokens dominate agent
+                    // sessions, so count it as input.
+                    state.input_tokens += total_tokens.unwrap_or(0);
+                }
+                (input, output) => {
+                    state.input_tokens += input.unwrap_or(0);
+                    state.output_tokens += output.unwrap_or(0);
+                }
+            }
+        }
+        GooseStreamEvent::Other => {}
+    }
+}
+
+/// Joins the text items of a tool result.
+///
+/// The result is `{"status": "success", "value": ...}` where the value is
+/// either a list of content items or an object with a `content` list.
+fn tool_result_text(result: &serde_json::Value) -> String {
+    let value = result.get("value").unwrap_or(result);
+    let items = value
+        .as_array()
+        .or_else(|| value.get("content").and_then(|c| c.as_array()));
+    match items {
+        Some(items) => items
+            .iter()
+            .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => value
+            .get("error")
+            .and_then(|e| e.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SessionResult;
+
+    #[derive(Default)]
+    struct Recorder {
+        texts: Vec<String>,
+        tool_calls: Vec<(String, String, serde_json::Value)>,
+        tool_results: Vec<(String, String)>,
+        errors: Vec<String>,
+    }
+
+    impl StreamHandler for Recorder {
+        fn on_text(&mut self, text: &str) {
+            self.texts.push(text.to_string());
+        }
+        fn on_tool_call(&mut self, name: &str, id: &str, input: &serde_json::Value) {
+            self.tool_calls
+                .push((name.to_string(), id.to_string(), input.clone()));
+        }
+        fn on_tool_result(&mut self, id: &str, output: &str) {
+            self.tool_results.push((id.to_string(), output.to_string()));
+        }
+        fn on_error(&mut self, error: &str) {
+            self.errors.push(error.to_string());
+        }
+        fn on_complete(&mut self, _result: &SessionResult) {}
+    }
+
+    fn dispatch_all(lines: &[&str]) -> (Recorder, String, GooseSessionState) {
+        let mut handler = Recorder::default();
+        let mut text = String::new();
+        let mut state = GooseSessionState::default();
+        for line in lines {
+            let event = GooseStreamParser::parse_line(line).unwrap();
+            dispatch_goose_stream_event(event, &mut handler, &mut text, &mut state, false);
+        }
+        (handler, text, state)
+    }
+
+    #[test]
+    fn test_session_with_tool_use() {
+        let (handler, text, state) = dispatch_all(&[
+            r#"{"type":"message","message":{"id":"m1","role":"assistant","created":1,"content":[{"type":"text","text":"Running tests. "}]}}"#,
+            r#"{"type":"message","message":{"id":"m1","role":"assistant","created":1,"content":[{"type":"toolRequest","id":"call_1","toolCall":{"status":"success","value":{"name":"developer__shell","arguments":{"command":"cargo test"}}}}]}}"#,
+            r#"{"type":"message","message":{"id":"m2","role":"user","created":2,"content":[{"type":"toolResponse","id":"call_1","toolResult":{"status":"success","value":[{"type":"text","text":"ok"}]}}]}}"#,
+            r#"{"type":"model_change","model":"gpt-4.1","mode":"auto"}"#,
+            r#"{"type":"message","message":{"id":"m3","role":"assistant","created":3,"content":[{"type":"text","text":"LOOP_COMPLETE"}]}}"#,
+            r#"{"type":"complete","total_tokens":4200}"#,
+        ]);
+
+        assert_eq!(text, "Running tests. LOOP_COMPLETE");
+        assert_eq!(handler.tool_calls.len(), 1);
+        assert_eq!(handler.tool_calls[0].0, "developer__shell");
+        assert_eq!(handler.tool_calls[0].2["command"], "cargo test");
+        assert_eq!(
+            handler.tool_results,
+            vec![("call_1".to_string(), "ok".to_string())]
+        );
+        assert_eq!(state.model.as_deref(), Some("gpt-4.1"));
+        assert_eq!(state.num_turns, 2);
+        assert_eq!(state.input_tokens, 4200);
+        assert!(!state.is_error);
+    }
+
+    #[test]
+    fn test_split_usage_and_errors() {
+        let (handler, _, state) = dispatch_all(&[
+            r#"{"type":"message","message":{"role":"user","content":[{"type":"toolResponse","id":"c","toolResult":{"status":"success","value":{"content":[{"type":"text","text":"boom"}],"isError":true}}}]}}"#,
+            r#"{"type":"error","error":"provider rate limited"}"#,
+            r#"{"type":"complete","total_tokens":300,"input_tokens":200,"output_tokens":100}"#,
+        ]);
+
+        assert_eq!(handler.errors, vec!["boom", "provider rate limited"]);
+        assert!(state.is_error);
+        assert_eq!((state.input_tokens, state.output_tokens), (200, 100));
+    }
+
+    #[test]
+    fn test_unknown_and_malformed_lines() {
+        assert!(matches!(
+            GooseStreamParser::parse_line(r#"{"type":"notification","extension_id":"developer"}"#),
+            Some(GooseStreamEvent::Other)
+        ));
+        assert!(GooseStreamParser::parse_line("starting session | provider: openai").is_none());
+        assert!(GooseStreamParser::parse_line("").is_none());
+    }
+}
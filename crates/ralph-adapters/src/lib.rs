@@ -8,7 +8,9 @@
 //! - Codex (OpenAI)
 //! - Pi (pi-coding-agent)
 //! - Amp
+//! - Goose (Block)
 //! - Custom commands
+//! - The Anthropic Messages API, without a CLI (`api`)
 //!
 //! Each adapter implements the common CLI executor interface.
 //!
@@ -17,6 +19,12 @@
 //! When config specifies `agent: auto`, the `auto_detect` module handles
 //! detecting which backends are available in the system PATH.
 //!
+//! ## Embedding
+//!
+//! `BackendExecutor` implements `ralph_core::Executor`, so a
+//! `ralph_core::Orchestrator` can run loops against the CLI backends without
+//! the `ralph` binary.
+//!
 //! ## PTY Mode
 //!
 //! The `pty_executor` module provides PTY-based execution for Claude CLI,
@@ -24,15 +32,28 @@
 //! allowing Ralph to orchestrate iterations. Supports interactive mode (user
 //! input forwarded) and observe mode (output-only).
 
+mod api_backend;
 mod auto_detect;
 mod claude_stream;
 mod cli_backend;
 mod cli_executor;
+mod container;
+mod error;
+mod goose_stream;
+mod limits;
+mod loop_executor;
+mod output_log;
+mod parallel_hats;
 mod pi_stream;
+mod process;
 mod pty_executor;
 pub mod pty_handle;
+mod response_cache;
+mod scouts;
+mod speculative;
 mod stream_handler;
 
+pub use api_backend::{API_BACKEND, ApiBackend, ApiError, ApiResult};
 pub use auto_detect::{
     DEFAULT_PRIORITY, NoBackendError, detect_backend, detect_backend_default, is_backend_available,
 };
@@ -41,16 +62,32 @@ pub use claude_stream::{
     UserMessage,
 };
 pub use cli_backend::{CliBackend, CustomBackendError, OutputFormat, PromptMode};
-pub use cli_executor::{CliExecutor, ExecutionResult};
+pub use cli_executor::{CliExecutor, ExecutionResult, OutputLine};
+pub use container::ContainerEnvironment;
+pub use error::{Error, classify};
+pub use goose_stream::{
+    GooseContent, GooseMessage, GooseSessionState, GooseStreamEvent, GooseStreamParser,
+    dispatch_goose_stream_event,
+};
+pub use limits::apply_limits;
+pub use loop_executor::{BackendExecutor, resolve_hat_backend, resolve_hat_cli};
+pub use output_log::OutputLog;
+pub use parallel_hats::{
+    ParallelHat, ParallelHatRun, ParallelRequest, combine_outputs, run_parallel_hats, total_usage,
+};
 pub use pi_stream::{
     PiAssistantEvent, PiContentBlock, PiCost, PiSessionState, PiStreamEvent, PiStreamParser,
     PiToolResult, PiTurnMessage, PiUsage, dispatch_pi_stream_event,
 };
+pub use process::{ProcessTree, configure_command, force_kill, is_process_alive, normalize_path};
 pub use pty_executor::{
     CtrlCAction, CtrlCState, PtyConfig, PtyExecutionResult, PtyExecutor, TerminationType,
 };
 pub use pty_handle::{ControlCommand, PtyHandle};
+pub use response_cache::{CachedResponse, ResponseCache};
+pub use scouts::{ScoutRequest, run_scouts};
+pub use speculative::{SpeculativeOutcome, SpeculativeRequest, run_speculative};
 pub use stream_handler::{
-    ConsoleStreamHandler, PrettyStreamHandler, QuietStreamHandler, SessionResult, StreamHandler,
-    TuiStreamHandler,
+    ConsoleStreamHandler, PrettyStreamHandler, QuietStreamHandler, ResultRecorder, SessionResult,
+    StreamHandler, TuiStreamHandler,
 };
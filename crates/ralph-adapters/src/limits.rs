@@ -0,0 +1,116 @@
+//! Resource limits for backend processes.
+//!
+//! Limits are applied by launching the backend through `sh`, which sets the
+//! rlimits with `ulimit` and then `exec`s the real command. The limits are
+//! inherited by everything the agent spawns, so a runaway build or test suite
+//! is killed by the kernel instead of taking down the host.
+//!
+//! Both executors build limited commands through
+//! `CliBackend::build_limited_command`. A backend running in a container is
+//! not wrapped; its limits become `<runtime> run` flags (see
+//! `ContainerEnvironment::wrap`).
+
+use ralph_core::ResourceLimits;
+use tracing::warn;
+
+/// Wraps `command args...` so it runs under `limits`.
+///
+/// Returns the command unchanged when no limit is set, or on platforms
+/// without POSIX rlimits.
+pub fn apply_limits(
+    limits: &ResourceLimits,
+    command: String,
+    args: Vec<String>,
+) -> (String, Vec<String>) {
+    if limits.is_empty() {
+        return (command, args);
+    }
+
+    if !cfg!(unix) {
+        warn!("cli.limits is not supported on this platform; running without limits");
+        return (command, args);
+    }
+
+    let mut script = String::new();
+    if let Some(secs) = limits.cpu_seconds {
+        script.push_str(&format!("ulimit -t {secs} && "));
+    }
+    if let Some(mb) = limits.memory_mb {
+        // `ulimit -v` takes kilobytes.
+        script.push_str(&format!("ulimit -v {} && ", mb.saturating_mul(1024)));
+    }
+    if let Some(files) = limits.max_open_files {
+        script.push_str(&format!("ulimit -n {files} && "));
+    }
+    script.push_str("exec \"$@\"");
+
+    // The command and its arguments are passed positionally, never
+    // interpolated into the script, so no quoting is needed.
+    let mut wrapped = vec![
+        "-c".to_string(),
+        script,
+        "ralph-limits".to_string(),
+        command,
+    ];
+    wrapped.extend(args);
+    ("sh".to_string(), wrapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_limits_leaves_command_unchanged() {
+        let (cmd, args) = apply_limits(
+            &ResourceLimits::default(),
+            "claude".to_string(),
+            vec!["-p".to_string()],
+        );
+        assert_eq!(cmd, "claude");
+        assert_eq!(args, ["-p"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_limits_wrap_command_in_shell() {
+        let limits = ResourceLimits {
+            cpu_seconds: Some(60),
+            memory_mb: Some(512),
+            max_open_files: Some(256),
+        };
+        let (cmd, args) = apply_limits(
+            &limits,
+            "claude".to_string(),
+            vec!["-p".to_string(), "it's $HOME".to_string()],
+        );
+
+        assert_eq!(cmd, "sh");
+        assert_eq!(
+            args[1],
+            "ulimit -t 60 && ulimit -v 524288 && ulimit -n 256 && exec \"$@\""
+        );
+        assert_eq!(&args[2..], ["ralph-limits", "claude", "-p", "it's $HOME"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_limits_are_visible_to_child() {
+        let limits = ResourceLimits {
+            max_open_files: Some(64),
+            ..Default::default()
+        };
+        let (cmd, args) = apply_limits(
+            &limits,
+            "sh".to_string(),
+            vec!["-c".to_string(), "ulimit -n".to_string()],
+        );
+        let output = tokio::process::Command::new(cmd)
+            .args(args)
+            .output()
+            .await
+            .unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "64");
+    }
+}
@@ -0,0 +1,269 @@
+//! [`Executor`] implementation for embedding the orchestrator with CLI backends.
+//!
+//! [`BackendExecutor`] resolves each iteration's backend the same way
+//! `ralph run` does — routing rule, hat `backend:` override or `cli:` block, routed model,
+//! then credentials and container environment, then resource limits — and runs it headless
+//! with [`CliExecutor`], streaming output to the orchestrator's progress subscribers.
+//! The `api` backend calls the Messages API with [`ApiBackend`] instead and
+//! reports its usage with the response.
+
+use crate::api_backend::{API_BACKEND, ApiBackend};
+use crate::cli_backend::{CliBackend, PromptMode};
+use crate::cli_executor::CliExecutor;
+use crate::container::ContainerEnvironment;
+use async_trait::async_trait;
+use ralph_core::credentials;
+use ralph_core::{
+    CliConfig, ExecutionRequest, ExecutionResponse, Executor, HatBackend, HatCliConfig, RalphConfig,
+};
+use std::path::PathBuf;
+use tracing::{debug, warn};
+
+/// Picks the backend for a hat: its own `backend:` override, else `global`.
+///
+/// Returns the backend and the name to look up adapter settings (timeouts)
+/// with. An invalid hat backend falls back to `global` with a warning. For
+/// `backend: api` the name is [`API_BACKEND`] and the returned CLI backend is
+/// `global`, unused.
+pub fn resolve_hat_backend(
+    global: &CliBackend,
+    global_name: &str,
+    hat: &str,
+    hat_backend: Option<&HatBackend>,
+) -> (CliBackend, String) {
+    let Some(hat_backend) = hat_backend else {
+        debug!("Using global backend for '{}': {}", hat, global_name);
+        return (global.clone(), global_name.to_string());
+    };
+    if hat_backend.to_cli_backend() == API_BACKEND {
+        debug!("Using the Messages API for '{}'", hat);
+        return (global.clone(), API_BACKEND.to_string());
+    }
+
+    match CliBackend::from_hat_backend(hat_backend) {
+        Ok(backend) => {
+            debug!("Using hat-level backend for '{}': {:?}", hat, hat_backend);
+            (backend, hat_backend.adapter_name())
+        }
+        Err(e) => {
+            warn!(
+                "Failed to create backend from hat configuration for '{}': {}. Falling back to global backend.",
+                hat, e
+            );
+            (global.clone(), global_name.to_string())
+        }
+    }
+}
+
+/// Picks the backend for a hat with a `cli:` block, laid over `global_cli`.
+///
+/// Returns the backend and the name to look up adapter settings with, like
+/// [`resolve_hat_backend`]; the block's model is applied. An invalid block
+/// falls back to `global` with a warning.
+pub fn resolve_hat_cli(
+    global: &CliBackend,
+    global_cli: &CliConfig,
+    hat: &str,
+    hat_cli: &HatCliConfig,
+) -> (CliBackend, String) {
+    let cli = hat_cli.apply(global_cli);
+    if cli.backend == API_BACKEND {
+        debug!("Using the Messages API for '{}'", hat);
+        return (global.clone(), API_BACKEND.to_string());
+    }
+
+    match CliBackend::from_config(&cli) {
+        Ok(mut backend) => {
+            debug!("Using hat-level cli for '{}': {:?}", hat, hat_cli);
+            if hat_cli.prompt_mode.is_some() {
+                backend.prompt_mode = if cli.prompt_mode == "stdin" {
+                    PromptMode::Stdin
+                } else {
+                    PromptMode::Arg
+                };
+            }
+            if let Some(model) = &hat_cli.model {
+                backend = backend.with_model(model);
+            }
+            (backend, cli.backend)
+        }
+        Err(e) => {
+            warn!(
+                "Failed to create backend from the cli block of '{}': {}. Falling back to global backend.",
+                hat, e
+            );
+            (global.clone(), global_cli.backend.clone())
+        }
+    }
+}
+
+/// Runs iterations with the configured CLI backends.
+#[derive(Debug, Clone)]
+pub struct BackendExecutor {
+    backend: CliBackend,
+    backend_name: String,
+    workspace: PathBuf,
+    credentials: Vec<(String, String)>,
+}
+
+impl BackendExecutor {
+    /// Creates an executor from `cli.backend` in `config`.
+    ///
+    /// `cli.backend: auto` must be resolved (e.g. with [`detect_backend`](crate::detect_backend))
+    /// before calling this.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the custom backend is missing a command or a
+    /// `credentials` entry can't be fetched.
+    pub fn from_config(config: &RalphConfig) -> Result<Self, crate::Error> {
+        let workspace = config.core.workspace_root.clone();
+        Ok(Self {
+            backend: CliBackend::from_config(&config.cli)?,
+            backend_name: config.cli.backend.clone(),
+            credentials: credentials::resolve(config, &workspace)?,
+            workspace,
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for BackendExecutor {
+    async fn execute(
+        &mut self,
+        request: ExecutionRequest<'_>,
+    ) -> Result<ExecutionResponse, ralph_core::Error> {
+        let hat = request.active_hat_id.as_str();
+        let (mut backend, backend_name) = match (request.backend, request.cli) {
+            (None, Some(hat_cli)) => {
+                let mut global_cli = request.config.cli.clone();
+                global_cli.backend.clone_from(&self.backend_name);
+                resolve_hat_cli(&self.backend, &global_cli, hat, hat_cli)
+            }
+            (hat_backend, _) => {
+                resolve_hat_backend(&self.backend, &self.backend_name, hat, hat_backend)
+            }
+        };
+        let timeout = request.config.iteration_timeout(&backend_name);
+
+        if backend_name == API_BACKEND {
+            let mut api = ApiBackend::from_config(&request.config.api, &self.credentials)
+                .map_err(|e| ralph_core::Error::Backend(e.to_string()))?;
+            if let Some(model) = request
+                .model
+                .or_else(|| request.cli.and_then(|cli| cli.model.as_deref()))
+            {
+                api = api.with_model(model);
+            }
+            let result = api
+                .execute(request.prompt, request.output.clone(), Some(timeout))
+                .await
+                .map_err(|e| ralph_core::Error::Backend(format!("{backend_name}: {e}")))?;
+            return Ok(
+                ExecutionResponse::new(result.output, result.success).with_usage(result.usage)
+            );
+        }
+
+        if let Some(model) = request.model {
+            backend = backend.with_model(model);
+        }
+        backend = backend.with_env_vars(&self.credentials);
+        if let Some(environment) = request.environment {
+            backend = backend.with_container(ContainerEnvironment::from_config(
+                environment,
+                &self.workspace,
+            ));
+        }
+
+        let result = CliExecutor::new(backend)
+            .with_limits(request.config.cli.limits)
+            .with_retry(request.config.cli.retry.clone())
+            .execute(request.prompt, request.output.clone(), Some(timeout), false)
+            .await
+            .map_err(|e| ralph_core::Error::Backend(format!("{backend_name}: {e}")))?;
+
+        Ok(ExecutionResponse::new(result.output, result.success))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_hat_backend_prefers_hat_override() {
+        let global = CliBackend::claude();
+        let hat_backend = HatBackend::Custom {
+            command: "/usr/local/bin/my-agent".to_string(),
+            args: vec![],
+        };
+
+        let (backend, name) = resolve_hat_backend(&global, "claude", "builder", Some(&hat_backend));
+        assert_eq!(backend.command, "/usr/local/bin/my-agent");
+        assert_eq!(name, "my-agent");
+
+        let (backend, name) = resolve_hat_backend(&global, "claude", "builder", None);
+        assert_eq!(backend.command, "claude");
+        assert_eq!(name, "claude");
+
+        let api = HatBackend::Named("api".to_string());
+        let (_, name) = resolve_hat_backend(&global, "claude", "builder", Some(&api));
+        assert_eq!(name, API_BACKEND);
+    }
+
+    #[test]
+    fn test_resolve_hat_cli_applies_block() {
+        let global = CliBackend::claude();
+        let global_cli = CliConfig::default();
+        let hat_cli = HatCliConfig {
+            backend: Some("codex".to_string()),
+            prompt_mode: Some("stdin".to_string()),
+            model: Some("gpt-5-codex".to_string()),
+            ..Default::default()
+        };
+
+        let (backend, name) = resolve_hat_cli(&global, &global_cli, "builder", &hat_cli);
+        assert_eq!(backend.command, "codex");
+        assert_eq!(name, "codex");
+        assert_eq!(backend.prompt_mode, PromptMode::Stdin);
+        assert_eq!(backend.model(), Some("gpt-5-codex"));
+
+        // A custom backend without a command falls back to the global one
+        let broken = HatCliConfig {
+            backend: Some("custom".to_string()),
+            ..Default::default()
+        };
+        let (backend, name) = resolve_hat_cli(&global, &global_cli, "builder", &broken);
+        assert_eq!(backend.command, "claude");
+        assert_eq!(name, global_cli.backend);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_backend_executor_runs_custom_command() {
+        let mut config = RalphConfig::default();
+        config.cli.backend = "custom".to_string();
+        config.cli.command = Some("echo".to_string());
+        let mut executor = BackendExecutor::from_config(&config).unwrap();
+
+        let hat = ralph_proto::HatId::new("ralph");
+        let response = executor
+            .execute(ExecutionRequest {
+                iteration: 1,
+                hat_id: &hat,
+                active_hat_id: &hat,
+                prompt: "hello from ralph",
+                backend: None,
+                cli: None,
+                model: None,
+                environment: None,
+                config: &config,
+                output: ralph_core::OutputSink::default(),
+            })
+            .await
+            .unwrap();
+
+        assert!(response.success);
+        assert!(response.output.contains("hello from ralph"));
+    }
+}
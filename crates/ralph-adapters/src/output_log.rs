@@ -0,0 +1,84 @@
+//! Incremental copy of an iteration's backend output on disk.
+//!
+//! Executors append each chunk as it arrives, unbuffered, so a killed or
+//! hung iteration still leaves everything the backend printed up to that
+//! point. Logging is best effort: if the file can't be created or written,
+//! a warning is logged once and execution carries on.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Append-only output file for one iteration.
+#[derive(Debug)]
+pub struct OutputLog {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl OutputLog {
+    /// Creates (or truncates) the file at `path`, creating parent directories.
+    pub fn create(path: &Path) -> Self {
+        let file = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| File::create(path))
+            .inspect_err(|e| {
+                warn!(path = %path.display(), error = %e, "Failed to create iteration output file");
+            })
+            .ok();
+        Self {
+            path: path.to_path_buf(),
+            file,
+        }
+    }
+
+    /// Opens the log at `path`, if one was requested.
+    pub fn open(path: Option<&Path>) -> Option<Self> {
+        path.map(Self::create)
+    }
+
+    /// Returns the path of the output file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `data`; stops logging after the first write error.
+    pub fn write(&mut self, data: &[u8]) {
+        if let Some(file) = &mut self.file
+            && let Err(e) = file.write_all(data)
+        {
+            warn!(path = %self.path.display(), error = %e, "Failed to write iteration output");
+            self.file = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_is_on_disk_after_each_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sessions/primary/iter-3.out");
+
+        let mut log = OutputLog::create(&path);
+        log.write(b"partial ");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "partial ");
+        log.write(b"output\n");
+        assert_eq!(fs::read_to_string(log.path()).unwrap(), "partial output\n");
+    }
+
+    #[test]
+    fn test_unwritable_path_disables_logging() {
+        let dir = tempfile::tempdir().unwrap();
+        let blocker = dir.path().join("file");
+        fs::write(&blocker, "").unwrap();
+
+        let mut log = OutputLog::create(&blocker.join("iter-1.out"));
+        log.write(b"ignored");
+        assert!(!blocker.join("iter-1.out").exists());
+    }
+}
@@ -0,0 +1,233 @@
+//! Concurrent execution of independent hats with the CLI backends.
+//!
+//! [`run_parallel_hats`] runs one iteration's batch of hats (see
+//! `EventLoop::next_parallel_hats`) headless and all at once in the shared
+//! workspace, and returns their results in batch order. Events the agents
+//! publish with `ralph emit` are appended to the events file under its lock,
+//! so the orchestrator reads them all once the batch has finished.
+
+use crate::cli_backend::CliBackend;
+use crate::cli_executor::CliExecutor;
+use crate::pty_executor::session_usage;
+use ralph_core::{ResourceLimits, RetryPolicy, Usage};
+use std::path::Path;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+/// One hat of a parallel batch.
+#[derive(Debug, Clone)]
+pub struct ParallelHat {
+    /// Hat ID, for logs and the combined output.
+    pub hat: String,
+    /// The hat's prompt.
+    pub prompt: String,
+    /// Backend the hat runs on, with its overrides already applied.
+    pub backend: CliBackend,
+    /// Timeout for the hat's backend.
+    pub timeout: Option<Duration>,
+}
+
+/// A batch of hats to run together.
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelRequest<'a> {
+    /// The hats, in batch order.
+    pub hats: &'a [ParallelHat],
+    /// Directory the backends run in.
+    pub workspace: &'a Path,
+    /// Resource limits for each backend process.
+    pub limits: ResourceLimits,
+    /// Retry policy for each backend.
+    pub retry: &'a RetryPolicy,
+}
+
+/// What one hat of a batch produced.
+#[derive(Debug, Clone)]
+pub struct ParallelHatRun {
+    /// Hat ID.
+    pub hat: String,
+    /// Backend output.
+    pub output: String,
+    /// Whether the backend exited successfully.
+    pub success: bool,
+    /// Whether the backend was killed for exceeding its timeout.
+    pub timed_out: bool,
+    /// What the hat's session spent, when its backend streams usage.
+    pub usage: Option<Usage>,
+}
+
+/// Runs every hat of the batch concurrently and returns the runs in batch
+/// order.
+///
+/// Hats that fail to start are reported with `success: false` rather than
+/// failing the batch.
+pub async fn run_parallel_hats(request: ParallelRequest<'_>) -> Vec<ParallelHatRun> {
+    info!(
+        hats = ?request.hats.iter().map(|h| h.hat.as_str()).collect::<Vec<_>>(),
+        "Running hats in parallel"
+    );
+
+    let mut runs: Vec<ParallelHatRun> = request
+        .hats
+        .iter()
+        .map(|hat| ParallelHatRun {
+            hat: hat.hat.clone(),
+            output: String::new(),
+            success: false,
+            timed_out: false,
+            usage: None,
+        })
+        .collect();
+
+    let mut running = JoinSet::new();
+    for (index, hat) in request.hats.iter().enumerate() {
+        let executor = CliExecutor::new(hat.backend.clone())
+            .with_limits(request.limits)
+            .with_retry(request.retry.clone())
+            .with_working_dir(request.workspace);
+        let prompt = hat.prompt.clone();
+        let timeout = hat.timeout;
+        let format = hat.backend.output_format;
+        running.spawn(async move {
+            let result = executor
+                .execute(&prompt, std::io::sink(), timeout, false)
+                .await;
+            let result = result.map(|result| {
+                let usage = session_usage(format, &result.output);
+                (result, usage)
+            });
+            (index, result)
+        });
+    }
+
+    while let Some(joined) = running.join_next().await {
+        match joined {
+            Ok((index, Ok((result, usage)))) => {
+                let run = &mut runs[index];
+                run.output = result.output;
+                run.success = result.success;
+                run.timed_out = result.timed_out;
+                run.usage = usage;
+            }
+            Ok((index, Err(e))) => {
+                warn!(hat = %runs[index].hat, error = %e, "Parallel hat failed to run");
+                runs[index].output = format!("Failed to run backend: {e}");
+            }
+            Err(e) => warn!(error = %e, "Parallel hat task panicked"),
+        }
+    }
+    runs
+}
+
+/// Adds up what the batch's hats spent, or `None` if no hat reported usage.
+pub fn total_usage(runs: &[ParallelHatRun]) -> Option<Usage> {
+    runs.iter()
+        .filter_map(|run| run.usage)
+        .reduce(|mut total, usage| {
+            total += usage;
+            total
+        })
+}
+
+/// Joins a batch's outputs into one iteration output, each under a
+/// `## <hat>` heading.
+pub fn combine_outputs(runs: &[ParallelHatRun]) -> String {
+    runs.iter()
+        .map(|run| {
+            let status = if run.success { "" } else { " (failed)" };
+            format!("## {}{status}\n\n{}\n", run.hat, run.output.trim_end())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli_backend::{OutputFormat, PromptMode};
+
+    fn backend(command: &str) -> CliBackend {
+        CliBackend {
+            command: command.to_string(),
+            args: vec![],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: None,
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        }
+    }
+
+    fn hat(id: &str, command: &str) -> ParallelHat {
+        ParallelHat {
+            hat: id.to_string(),
+            prompt: format!("prompt for {id}"),
+            backend: backend(command),
+            timeout: Some(Duration::from_secs(30)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_parallel_hats_returns_runs_in_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let hats = [hat("docs", "echo"), hat("linter", "false")];
+
+        let runs = run_parallel_hats(ParallelRequest {
+            hats: &hats,
+            workspace: dir.path(),
+            limits: ResourceLimits::default(),
+            retry: &RetryPolicy::default(),
+        })
+        .await;
+
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].hat, "docs");
+        assert!(runs[0].success);
+        assert!(runs[0].output.contains("prompt for docs"));
+        assert_eq!(runs[1].hat, "linter");
+        assert!(!runs[1].success);
+
+        let combined = combine_outputs(&runs);
+        assert!(combined.starts_with("## docs\n\nprompt for docs\n"));
+        assert!(combined.contains("## linter (failed)"));
+        // Text output reports no spend
+        assert!(total_usage(&runs).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_parallel_hats_reports_usage_per_hat() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let result = |cost: f64, input: u64| ParallelHat {
+            prompt: format!(
+                r#"{{"type":"result","duration_ms":10,"total_cost_usd":{cost},"num_turns":1,"is_error":false,"usage":{{"input_tokens":{input},"output_tokens":10}}}}"#
+            ),
+            backend: CliBackend {
+                output_format: OutputFormat::StreamJson,
+                ..backend("echo")
+            },
+            ..hat("unused", "echo")
+        };
+        let hats = [
+            ParallelHat {
+                hat: "docs".to_string(),
+                ..result(0.25, 100)
+            },
+            ParallelHat {
+                hat: "linter".to_string(),
+                ..result(0.5, 200)
+            },
+        ];
+
+        let runs = run_parallel_hats(ParallelRequest {
+            hats: &hats,
+            workspace: dir.path(),
+            limits: ResourceLimits::default(),
+            retry: &RetryPolicy::default(),
+        })
+        .await;
+
+        assert_eq!(runs[0].usage, Some(Usage::new(0.25, 100, 10)));
+        assert_eq!(runs[1].usage, Some(Usage::new(0.5, 200, 10)));
+        assert_eq!(total_usage(&runs), Some(Usage::new(0.75, 300, 20)));
+    }
+}
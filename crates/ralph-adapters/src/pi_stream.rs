@@ -148,6 +148,8 @@ impl PiStreamParser {
 /// State accumulated across events for session summary.
 pub struct PiSessionState {
     pub total_cost_usd: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
     pub num_turns: u32,
     pub stream_provider: Option<String>,
     pub stream_model: Option<String>,
@@ -157,6 +159,8 @@ impl PiSessionState {
     pub fn new() -> Self {
         Self {
             total_cost_usd: 0.0,
+            input_tokens: 0,
+            output_tokens: 0,
             num_turns: 0,
             stream_provider: None,
             stream_model: None,
@@ -240,10 +244,12 @@ pub fn dispatch_pi_stream_event<H: StreamHandler>(
                 {
                     state.stream_model = Some(model.clone());
                 }
-                if let Some(usage) = &msg.usage
-                    && let Some(cost) = &usage.cost
-                {
-                    state.total_cost_usd += cost.total;
+                if let Some(usage) = &msg.usage {
+                    state.input_tokens += usage.input;
+                    state.output_tokens += usage.output;
+                    if let Some(cost) = &usage.cost {
+                        state.total_cost_usd += cost.total;
+                    }
                 }
             }
         }
@@ -676,6 +682,8 @@ mod tests {
 
         assert_eq!(state.num_turns, 3);
         assert!((state.total_cost_usd - 0.09).abs() < 1e-10);
+        assert_eq!(state.input_tokens, 300);
+        assert_eq!(state.output_tokens, 150);
     }
 
     #[test]
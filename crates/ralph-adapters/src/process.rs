@@ -0,0 +1,444 @@
+//! Cross-platform process control for backend processes.
+//!
+//! On Unix, backends are stopped with signals (SIGTERM, then SIGKILL). Backends
+//! spawned with [`configure_command`] lead their own process group, which is
+//! signalled as a whole so descendants stop too; others rely on the
+//! orchestrator's process group. Windows has neither,
+//! so each backend is placed in a Job Object that kills every process in it when
+//! the job is closed, and graceful termination sends `CTRL_BREAK` to the
+//! backend's console process group.
+
+use std::io;
+use std::path::{Path, PathBuf};
+#[cfg(windows)]
+use tracing::debug;
+
+/// Prepares a command so its process tree can be controlled by [`ProcessTree`].
+///
+/// On Unix the child leads a new process group, so [`ProcessTree::attach_group`]
+/// can signal it with its descendants. On Windows the child is started in a new
+/// console process group, which is what `CTRL_BREAK` is delivered to.
+pub fn configure_command(command: &mut tokio::process::Command) {
+    #[cfg(unix)]
+    command.process_group(0);
+    #[cfg(windows)]
+    command.creation_flags(windows_sys::Win32::System::Threading::CREATE_NEW_PROCESS_GROUP);
+    #[cfg(not(any(unix, windows)))]
+    let _ = command;
+}
+
+/// A spawned backend and, on Windows, the Job Object holding its descendants.
+///
+/// Dropping a `ProcessTree` on Windows closes the job, which kills any process
+/// the backend left behind. On Unix the same holds for a tree attached with
+/// [`attach_group`](Self::attach_group): the process group is killed.
+#[derive(Debug)]
+pub struct ProcessTree {
+    pid: u32,
+    #[cfg(unix)]
+    group: bool,
+    #[cfg(windows)]
+    job: Option<win32::Job>,
+}
+
+impl ProcessTree {
+    /// Takes control of the process tree rooted at `pid`.
+    ///
+    /// Call this right after spawning: on Windows, only processes started after
+    /// the root joins the job are tracked.
+    pub fn attach(pid: u32) -> Self {
+        #[cfg(windows)]
+        {
+            let job = win32::Job::assign(pid)
+                .inspect_err(|e| {
+                    debug!(pid, error = %e, "Could not assign process to a job object");
+                })
+                .ok();
+            Self { pid, job }
+        }
+
+        #[cfg(unix)]
+        {
+            Self { pid, group: false }
+        }
+
+        #[cfg(not(any(unix, windows)))]
+        {
+            Self { pid }
+        }
+    }
+
+    /// Takes control of the process group led by `pid`, a child spawned with
+    /// [`configure_command`].
+    ///
+    /// On Unix, [`terminate`](Self::terminate) and [`kill`](Self::kill) signal
+    /// the whole group, and dropping the tree kills whatever is left in it.
+    /// Elsewhere this is [`attach`](Self::attach).
+    pub fn attach_group(pid: u32) -> Self {
+        #[cfg(unix)]
+        {
+            Self { pid, group: true }
+        }
+
+        #[cfg(not(unix))]
+        {
+            Self::attach(pid)
+        }
+    }
+
+    /// Returns the root process ID.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Asks the process to exit: SIGTERM on Unix, `CTRL_BREAK` on Windows.
+    ///
+    /// On Windows, falls back to [`kill`](Self::kill) if the process is not in
+    /// a console process group it can receive `CTRL_BREAK` in.
+    pub fn terminate(&self) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            self.signal(nix::sys::signal::Signal::SIGTERM)
+        }
+
+        #[cfg(windows)]
+        {
+            win32::ctrl_break(self.pid).or_else(|e| {
+                debug!(pid = self.pid, error = %e, "CTRL_BREAK failed, killing process tree");
+                self.kill()
+            })
+        }
+
+        #[cfg(not(any(unix, windows)))]
+        {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+    }
+
+    /// Kills the process immediately.
+    ///
+    /// On Windows this kills every process in the job, not just the root.
+    pub fn kill(&self) -> io::Result<()> {
+        #[cfg(windows)]
+        if let Some(job) = &self.job {
+            return job.terminate();
+        }
+
+        #[cfg(unix)]
+        if self.group {
+            return self.signal(nix::sys::signal::Signal::SIGKILL);
+        }
+
+        force_kill(self.pid)
+    }
+
+    /// Sends `sig` to the process, or to its group when attached as one.
+    #[cfg(unix)]
+    fn signal(&self, sig: nix::sys::signal::Signal) -> io::Result<()> {
+        if self.group {
+            #[allow(clippy::cast_possible_wrap)]
+            let pgid = nix::unistd::Pid::from_raw(self.pid as i32);
+            nix::sys::signal::killpg(pgid, sig).map_err(io::Error::from)
+        } else {
+            signal(self.pid, sig)
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        if self.group {
+            // The group is gone once its last process exits; ESRCH is expected.
+            let _ = self.signal(nix::sys::signal::Signal::SIGKILL);
+        }
+    }
+}
+
+/// Returns true if a process with `pid` is running.
+pub fn is_process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        #[allow(clippy::cast_possible_wrap)]
+        let pid = nix::unistd::Pid::from_raw(pid as i32);
+        // Signal 0 checks if the process exists without sending a signal
+        nix::sys::signal::kill(pid, None).is_ok()
+    }
+
+    #[cfg(windows)]
+    {
+        win32::is_alive(pid)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+/// Kills a process immediately: SIGKILL on Unix.
+///
+/// On Windows this also kills the process's descendants (`taskkill /T /F`),
+/// since they are not cleaned up by a process group.
+pub fn force_kill(pid: u32) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        signal(pid, nix::sys::signal::Signal::SIGKILL)
+    }
+
+    #[cfg(windows)]
+    {
+        let status = std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("taskkill exited with {status}")))
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = pid;
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+}
+
+#[cfg(unix)]
+fn signal(pid: u32, sig: nix::sys::signal::Signal) -> io::Result<()> {
+    #[allow(clippy::cast_possible_wrap)]
+    let pid = nix::unistd::Pid::from_raw(pid as i32);
+    nix::sys::signal::kill(pid, sig).map_err(io::Error::from)
+}
+
+/// Normalizes a path for handing to backends and child processes.
+///
+/// `canonicalize` on Windows returns verbatim paths (`\\?\C:\repo`), which
+/// many tools (Node, git, shells) reject. This strips the verbatim prefix when
+/// the path can be expressed without it. Other paths are returned unchanged.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let Some(s) = path.to_str() else {
+        return path.to_path_buf();
+    };
+
+    if let Some(rest) = s.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{rest}"));
+    }
+
+    match s.strip_prefix(r"\\?\") {
+        // Only drive paths like `C:\...`; other verbatim forms have no plain equivalent
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => PathBuf::from(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Win32 FFI for job objects and console control events.
+///
+/// This is the only module in the workspace allowed to use `unsafe`; every
+/// handle it creates is owned by an [`OwnedHandle`] and closed exactly once.
+#[cfg(windows)]
+#[allow(unsafe_code)]
+mod win32 {
+    use std::io;
+    use windows_sys::Win32::Foundation::{CloseHandle, FALSE, HANDLE, STILL_ACTIVE};
+    use windows_sys::Win32::System::Console::{CTRL_BREAK_EVENT, GenerateConsoleCtrlEvent};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, TerminateJobObject,
+    };
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_QUOTA,
+        PROCESS_TERMINATE,
+    };
+
+    #[derive(Debug)]
+    struct OwnedHandle(HANDLE);
+
+    // SAFETY: Win32 handles are process-wide and may be used from any thread.
+    unsafe impl Send for OwnedHandle {}
+    // SAFETY: All operations used on these handles are thread-safe in Win32.
+    unsafe impl Sync for OwnedHandle {}
+
+    impl Drop for OwnedHandle {
+        fn drop(&mut self) {
+            // SAFETY: The handle is valid and owned by this value.
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    fn check(ok: i32) -> io::Result<()> {
+        if ok == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn open_process(pid: u32, access: u32) -> io::Result<OwnedHandle> {
+        // SAFETY: OpenProcess has no preconditions; a null return is handled.
+        let handle = unsafe { OpenProcess(access, FALSE, pid) };
+        if handle.is_null() {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(OwnedHandle(handle))
+        }
+    }
+
+    /// A job object that kills all of its processes when closed.
+    #[derive(Debug)]
+    pub struct Job(OwnedHandle);
+
+    impl Job {
+        /// Creates a kill-on-close job and assigns `pid` to it.
+        pub fn assign(pid: u32) -> io::Result<Self> {
+            // SAFETY: Null attributes and name create an anonymous job with defaults.
+            let handle = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+            if handle.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let job = OwnedHandle(handle);
+
+            // SAFETY: The struct is plain old data; all-zero is a valid "no limits" value.
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            #[allow(clippy::cast_possible_truncation)]
+            let size = std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32;
+            // SAFETY: `info` is a valid JOBOBJECT_EXTENDED_LIMIT_INFORMATION of `size` bytes.
+            check(unsafe {
+                SetInformationJobObject(
+                    job.0,
+                    JobObjectExtendedLimitInformation,
+                    std::ptr::from_ref(&info).cast(),
+                    size,
+                )
+            })?;
+
+            let process = open_process(pid, PROCESS_SET_QUOTA | PROCESS_TERMINATE)?;
+            // SAFETY: Both handles are valid and opened with the required access.
+            check(unsafe { AssignProcessToJobObject(job.0, process.0) })?;
+
+            Ok(Self(job))
+        }
+
+        /// Kills every process in the job.
+        pub fn terminate(&self) -> io::Result<()> {
+            // SAFETY: The job handle is valid for the lifetime of `self`.
+            check(unsafe { TerminateJobObject((self.0).0, 1) })
+        }
+    }
+
+    /// Sends `CTRL_BREAK` to the console process group led by `pid`.
+    pub fn ctrl_break(pid: u32) -> io::Result<()> {
+        // SAFETY: GenerateConsoleCtrlEvent has no memory-safety preconditions.
+        check(unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid) })
+    }
+
+    pub fn is_alive(pid: u32) -> bool {
+        let Ok(process) = open_process(pid, PROCESS_QUERY_LIMITED_INFORMATION) else {
+            return false;
+        };
+        let mut code = 0u32;
+        // SAFETY: The handle is valid and `code` is a valid out pointer.
+        let ok = unsafe { GetExitCodeProcess(process.0, &raw mut code) };
+        #[allow(clippy::cast_sign_loss)]
+        let still_active = STILL_ACTIVE as u32;
+        ok != 0 && code == still_active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path_strips_verbatim_drive_prefix() {
+        assert_eq!(
+            normalize_path(Path::new(r"\\?\C:\repo\src")),
+            PathBuf::from(r"C:\repo\src")
+        );
+        assert_eq!(
+            normalize_path(Path::new(r"\\?\UNC\server\share\repo")),
+            PathBuf::from(r"\\server\share\repo")
+        );
+    }
+
+    #[test]
+    fn test_normalize_path_leaves_other_paths_unchanged() {
+        assert_eq!(
+            normalize_path(Path::new("/home/user/repo")),
+            PathBuf::from("/home/user/repo")
+        );
+        assert_eq!(
+            normalize_path(Path::new(r"\\?\Volume{1234}\repo")),
+            PathBuf::from(r"\\?\Volume{1234}\repo")
+        );
+    }
+
+    #[test]
+    fn test_current_process_is_alive() {
+        assert!(is_process_alive(std::process::id()));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_process_tree_terminate_stops_child() {
+        let mut command = tokio::process::Command::new("sleep");
+        command.arg("30");
+        configure_command(&mut command);
+        let mut child = command.spawn().expect("spawn sleep");
+        let tree = ProcessTree::attach(child.id().expect("pid"));
+
+        tree.terminate().expect("terminate");
+        let status = child.wait().await.expect("wait");
+        assert!(!status.success());
+    }
+
+    /// Whether `pid` is running and not a zombie awaiting its reaper.
+    #[cfg(target_os = "linux")]
+    fn is_running(pid: u32) -> bool {
+        std::fs::read_to_string(format!("/proc/{pid}/stat")).is_ok_and(|stat| {
+            !stat
+                .rsplit(')')
+                .next()
+                .unwrap_or("")
+                .trim_start()
+                .starts_with('Z')
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_process_group_drop_kills_descendants() {
+        let mut command = tokio::process::Command::new("sh");
+        command
+            .args(["-c", "sleep 30 & echo $!; wait"])
+            .stdout(std::process::Stdio::piped());
+        configure_command(&mut command);
+        let mut child = command.spawn().expect("spawn sh");
+        let tree = ProcessTree::attach_group(child.id().expect("pid"));
+
+        let mut stdout = tokio::io::BufReader::new(child.stdout.take().expect("stdout"));
+        let mut line = String::new();
+        tokio::io::AsyncBufReadExt::read_line(&mut stdout, &mut line)
+            .await
+            .expect("read pid");
+        let grandchild: u32 = line.trim().parse().expect("grandchild pid");
+        assert!(is_running(grandchild));
+
+        drop(tree);
+        let status = child.wait().await.expect("wait");
+        assert!(!status.success());
+        for _ in 0..50 {
+            if !is_running(grandchild) {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("grandchild {grandchild} survived the process group kill");
+    }
+}
@@ -20,13 +20,17 @@
 
 use crate::claude_stream::{ClaudeStreamEvent, ClaudeStreamParser, ContentBlock, UserContentBlock};
 use crate::cli_backend::{CliBackend, OutputFormat};
+use crate::goose_stream::{GooseSessionState, GooseStreamParser, dispatch_goose_stream_event};
+use crate::output_log::OutputLog;
 use crate::pi_stream::{PiSessionState, PiStreamParser, dispatch_pi_stream_event};
-use crate::stream_handler::{SessionResult, StreamHandler};
+use crate::process::ProcessTree;
+use crate::stream_handler::{QuietStreamHandler, ResultRecorder, SessionResult, StreamHandler};
 #[cfg(unix)]
 use nix::sys::signal::{Signal, kill};
 #[cfg(unix)]
 use nix::unistd::Pid;
 use portable_pty::{CommandBuilder, PtyPair, PtySize, native_pty_system};
+use ralph_core::ResourceLimits;
 use std::io::{self, Read, Write};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -192,6 +196,9 @@ pub struct PtyExecutor {
     // This replaces the previous inference via output_rx.is_none() which broke
     // after the streaming refactor (handle() is no longer called in TUI mode).
     tui_mode: bool,
+    // File the next run copies raw output to as it arrives.
+    output_log: Option<std::path::PathBuf>,
+    limits: ResourceLimits,
 }
 
 impl PtyExecutor {
@@ -214,9 +221,18 @@ impl PtyExecutor {
             terminated_tx,
             terminated_rx: Some(terminated_rx),
             tui_mode: false,
+            output_log: None,
+            limits: ResourceLimits::default(),
         }
     }
 
+    /// Sets resource limits applied to the spawned backend process.
+    #[must_use]
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Sets the TUI mode flag.
     ///
     /// When TUI mode is enabled, PTY output is sent to the TUI channel instead of
@@ -240,6 +256,13 @@ impl PtyExecutor {
         self.backend = backend;
     }
 
+    /// Copies raw output of subsequent runs to `path` as it arrives.
+    ///
+    /// Pass `None` to stop. See [`OutputLog`].
+    pub fn set_output_log(&mut self, path: Option<std::path::PathBuf>) {
+        self.output_log = path;
+    }
+
     /// Returns a handle for TUI integration.
     ///
     /// Can only be called once - panics if called multiple times.
@@ -252,7 +275,8 @@ impl PtyExecutor {
         }
     }
 
-    /// Spawns Claude in a PTY and returns the PTY pair, child process, stdin input, and temp file.
+    /// Spawns Claude in a PTY and returns the PTY pair, child process, process tree,
+    /// stdin input, and temp file.
     ///
     /// The temp file is returned to keep it alive for the duration of execution.
     /// For large prompts (>7000 chars), Claude is instructed to read from a temp file.
@@ -260,12 +284,17 @@ impl PtyExecutor {
     ///
     /// The stdin_input is returned so callers can write it to the PTY after taking the writer.
     /// This is necessary because `take_writer()` can only be called once per PTY.
+    ///
+    /// The process tree must be held for the duration of execution: on Windows,
+    /// dropping it kills everything the backend spawned, which stands in for the
+    /// process-group cleanup Unix gets from signals.
     fn spawn_pty(
         &self,
         prompt: &str,
     ) -> io::Result<(
         PtyPair,
         Box<dyn portable_pty::Child + Send>,
+        Option<ProcessTree>,
         Option<String>,
         Option<tempfile::NamedTempFile>,
     )> {
@@ -281,7 +310,8 @@ impl PtyExecutor {
             .map_err(|e| io::Error::other(e.to_string()))?;
 
         let (cmd, args, stdin_input, temp_file) =
-            self.backend.build_command(prompt, self.config.interactive);
+            self.backend
+                .build_limited_command(prompt, self.config.interactive, &self.limits);
 
         let mut cmd_builder = CommandBuilder::new(&cmd);
         cmd_builder.args(&args);
@@ -301,9 +331,10 @@ impl PtyExecutor {
             .slave
             .spawn_command(cmd_builder)
             .map_err(|e| io::Error::other(e.to_string()))?;
+        let process_tree = child.process_id().map(ProcessTree::attach);
 
         // Return stdin_input so callers can write it after taking the writer
-        Ok((pair, child, stdin_input, temp_file))
+        Ok((pair, child, process_tree, stdin_input, temp_file))
     }
 
     /// Runs in observe mode (output-only, no input forwarding).
@@ -328,7 +359,7 @@ impl PtyExecutor {
         mut interrupt_rx: tokio::sync::watch::Receiver<bool>,
     ) -> io::Result<PtyExecutionResult> {
         // Keep temp_file alive for the duration of execution (large prompts use temp files)
-        let (pair, mut child, stdin_input, _temp_file) = self.spawn_pty(prompt)?;
+        let (pair, mut child, _process_tree, stdin_input, _temp_file) = self.spawn_pty(prompt)?;
 
         let reader = pair
             .master
@@ -377,6 +408,7 @@ impl PtyExecutor {
             None
         };
 
+        let mut output_log = OutputLog::open(self.output_log.as_deref());
         debug!("Spawning PTY output reader thread (observe mode)");
         std::thread::spawn(move || {
             let mut reader = reader;
@@ -396,6 +428,9 @@ impl PtyExecutor {
                     }
                     Ok(n) => {
                         let data = buf[..n].to_vec();
+                        if let Some(log) = &mut output_log {
+                            log.write(&data);
+                        }
                         // Send to TUI channel if connected
                         if let Some(ref tx) = tui_output_tx {
                             let _ = tx.send(data.clone());
@@ -575,9 +610,11 @@ impl PtyExecutor {
 
         // StreamJson format uses NDJSON line parsing (Claude)
         // PiStreamJson format uses NDJSON line parsing (Pi)
+        // GooseStreamJson format uses NDJSON line parsing (Goose)
         // Text format streams raw output directly to handler
         let is_stream_json = output_format == OutputFormat::StreamJson;
         let is_pi_stream = output_format == OutputFormat::PiStreamJson;
+        let is_goose_stream = output_format == OutputFormat::GooseStreamJson;
         // Pi thinking deltas are noisy for plain console output but useful in TUI.
         let show_pi_thinking = is_pi_stream && self.tui_mode;
         let is_real_pi_backend = self.backend.command == "pi";
@@ -594,7 +631,7 @@ impl PtyExecutor {
         }
 
         // Keep temp_file alive for the duration of execution
-        let (pair, mut child, stdin_input, _temp_file) = self.spawn_pty(prompt)?;
+        let (pair, mut child, _process_tree, stdin_input, _temp_file) = self.spawn_pty(prompt)?;
 
         let reader = pair
             .master
@@ -621,6 +658,8 @@ impl PtyExecutor {
         let mut extracted_text = String::new();
         // Pi session state for accumulating cost/turns (wall-clock for duration)
         let mut pi_state = PiSessionState::new();
+        // Goose session state for accumulating tokens/turns
+        let mut goose_state = GooseSessionState::default();
         let start_time = Instant::now();
         let timeout_duration = if !self.config.interactive || self.config.idle_timeout_secs == 0 {
             None
@@ -645,6 +684,7 @@ impl PtyExecutor {
             None
         };
 
+        let mut output_log = OutputLog::open(self.output_log.as_deref());
         debug!("Spawning PTY output reader thread (streaming mode)");
         std::thread::spawn(move || {
             let mut reader = reader;
@@ -664,6 +704,9 @@ impl PtyExecutor {
                     }
                     Ok(n) => {
                         let data = buf[..n].to_vec();
+                        if let Some(log) = &mut output_log {
+                            log.write(&data);
+                        }
                         if let Some(ref tx) = tui_output_tx {
                             let _ = tx.send(data.clone());
                         }
@@ -744,6 +787,24 @@ impl PtyExecutor {
                                             );
                                         }
                                     }
+                                } else if is_goose_stream {
+                                    // GooseStreamJson format: Parse NDJSON lines from goose
+                                    line_buffer.push_str(text);
+
+                                    while let Some(newline_pos) = line_buffer.find('\n') {
+                                        let line = line_buffer[..newline_pos].to_string();
+                                        line_buffer = line_buffer[newline_pos + 1..].to_string();
+
+                                        if let Some(event) = GooseStreamParser::parse_line(&line) {
+                                            dispatch_goose_stream_event(
+                                                event,
+                                                handler,
+                                                &mut extracted_text,
+                                                &mut goose_state,
+                                                self.tui_mode,
+                                            );
+                                        }
+                                    }
                                 } else {
                                     // Text format: Stream raw output directly to handler
                                     // This preserves ANSI escape codes for TUI rendering
@@ -768,6 +829,16 @@ impl PtyExecutor {
                                     &mut pi_state,
                                     show_pi_thinking,
                                 );
+                            } else if is_goose_stream && !line_buffer.is_empty()
+                                && let Some(event) = GooseStreamParser::parse_line(&line_buffer)
+                            {
+                                dispatch_goose_stream_event(
+                                    event,
+                                    handler,
+                                    &mut extracted_text,
+                                    &mut goose_state,
+                                    self.tui_mode,
+                                );
                             }
                             break;
                         }
@@ -836,6 +907,22 @@ impl PtyExecutor {
                                         );
                                     }
                                 }
+                            } else if is_goose_stream {
+                                // GooseStreamJson: parse NDJSON lines
+                                line_buffer.push_str(text);
+                                while let Some(newline_pos) = line_buffer.find('\n') {
+                                    let line = line_buffer[..newline_pos].to_string();
+                                    line_buffer = line_buffer[newline_pos + 1..].to_string();
+                                    if let Some(event) = GooseStreamParser::parse_line(&line) {
+                                        dispatch_goose_stream_event(
+                                            event,
+                                            handler,
+                                            &mut extracted_text,
+                                            &mut goose_state,
+                                            self.tui_mode,
+                                        );
+                                    }
+                                }
                             } else {
                                 // Text: stream raw output to handler
                                 handler.on_text(text);
@@ -861,6 +948,17 @@ impl PtyExecutor {
                         &mut pi_state,
                         show_pi_thinking,
                     );
+                } else if is_goose_stream
+                    && !line_buffer.is_empty()
+                    && let Some(event) = GooseStreamParser::parse_line(&line_buffer)
+                {
+                    dispatch_goose_stream_event(
+                        event,
+                        handler,
+                        &mut extracted_text,
+                        &mut goose_state,
+                        self.tui_mode,
+                    );
                 }
 
                 let final_termination = resolve_termination_type(exit_code, termination);
@@ -880,6 +978,23 @@ impl PtyExecutor {
                         total_cost_usd: pi_state.total_cost_usd,
                         num_turns: pi_state.num_turns,
                         is_error: !status.success(),
+                        input_tokens: pi_state.input_tokens,
+                        output_tokens: pi_state.output_tokens,
+                    });
+                }
+
+                // Synthesize on_complete for Goose sessions (goose reports no cost)
+                if is_goose_stream {
+                    if let Some(model) = &goose_state.model {
+                        handler.on_text(&format!("Goose model: {model}\n"));
+                    }
+                    handler.on_complete(&SessionResult {
+                        duration_ms: start_time.elapsed().as_millis() as u64,
+                        total_cost_usd: 0.0,
+                        num_turns: goose_state.num_turns,
+                        is_error: !status.success() || goose_state.is_error,
+                        input_tokens: goose_state.input_tokens,
+                        output_tokens: goose_state.output_tokens,
                     });
                 }
 
@@ -929,6 +1044,20 @@ impl PtyExecutor {
                 total_cost_usd: pi_state.total_cost_usd,
                 num_turns: pi_state.num_turns,
                 is_error: !success,
+                input_tokens: pi_state.input_tokens,
+                output_tokens: pi_state.output_tokens,
+            });
+        }
+
+        // Synthesize on_complete for Goose sessions (goose reports no cost)
+        if is_goose_stream {
+            handler.on_complete(&SessionResult {
+                duration_ms: start_time.elapsed().as_millis() as u64,
+                total_cost_usd: 0.0,
+                num_turns: goose_state.num_turns,
+                is_error: !success || goose_state.is_error,
+                input_tokens: goose_state.input_tokens,
+                output_tokens: goose_state.output_tokens,
             });
         }
 
@@ -968,7 +1097,7 @@ impl PtyExecutor {
         mut interrupt_rx: tokio::sync::watch::Receiver<bool>,
     ) -> io::Result<PtyExecutionResult> {
         // Keep temp_file alive for the duration of execution (large prompts use temp files)
-        let (pair, mut child, stdin_input, _temp_file) = self.spawn_pty(prompt)?;
+        let (pair, mut child, _process_tree, stdin_input, _temp_file) = self.spawn_pty(prompt)?;
 
         let reader = pair
             .master
@@ -1015,6 +1144,7 @@ impl PtyExecutor {
             None
         };
 
+        let mut output_log = OutputLog::open(self.output_log.as_deref());
         debug!("Spawning PTY output reader thread");
         std::thread::spawn(move || {
             debug!("PTY output reader thread started");
@@ -1036,6 +1166,9 @@ impl PtyExecutor {
                     }
                     Ok(n) => {
                         let data = buf[..n].to_vec();
+                        if let Some(log) = &mut output_log {
+                            log.write(&data);
+                        }
                         // Send to TUI channel if connected
                         if let Some(ref tx) = tui_output_tx {
                             let _ = tx.send(data.clone());
@@ -1400,6 +1533,10 @@ impl PtyExecutor {
     /// If `graceful` is true, sends SIGTERM and waits up to 5 seconds before SIGKILL.
     /// If `graceful` is false, sends SIGKILL immediately.
     ///
+    /// On Windows the backend runs under ConPTY, outside Ralph's console, so
+    /// `CTRL_BREAK` cannot reach it; the child is killed directly and its
+    /// descendants are killed when the run drops its `ProcessTree`.
+    ///
     /// This is an async function to avoid blocking the tokio runtime during the
     /// grace period wait. Previously used `std::thread::sleep` which blocked the
     /// worker thread for up to 5 seconds, making the TUI appear frozen.
@@ -1578,6 +1715,61 @@ fn extract_cli_flag_value(args: &[String], long_flag: &str, short_flag: &str) ->
     None
 }
 
+/// Reads the session's spend from a finished run's raw `output`.
+///
+/// Headless runs keep the backend's NDJSON stream as their output; this
+/// replays it the way a PTY run would. Returns `None` for text output, which
+/// reports no usage, and for a Claude session that never sent its result.
+pub(crate) fn session_usage(format: OutputFormat, output: &str) -> Option<ralph_core::Usage> {
+    let mut handler = QuietStreamHandler;
+    let mut extracted_text = String::new();
+    match format {
+        OutputFormat::Text => None,
+        OutputFormat::StreamJson => {
+            let mut recorder = ResultRecorder::new(handler);
+            for event in output.lines().filter_map(ClaudeStreamParser::parse_line) {
+                dispatch_stream_event(event, &mut recorder, &mut extracted_text);
+            }
+            recorder.into_result().map(|result| result.usage())
+        }
+        OutputFormat::PiStreamJson => {
+            let mut state = PiSessionState::new();
+            for event in output.lines().filter_map(PiStreamParser::parse_line) {
+                dispatch_pi_stream_event(
+                    event,
+                    &mut handler,
+                    &mut extracted_text,
+                    &mut state,
+                    false,
+                );
+            }
+            Some(ralph_core::Usage::new(
+                state.total_cost_usd,
+                state.input_tokens,
+                state.output_tokens,
+            ))
+        }
+        OutputFormat::GooseStreamJson => {
+            let mut state = GooseSessionState::default();
+            for event in output.lines().filter_map(GooseStreamParser::parse_line) {
+                dispatch_goose_stream_event(
+                    event,
+                    &mut handler,
+                    &mut extracted_text,
+                    &mut state,
+                    false,
+                );
+            }
+            // Goose reports tokens but no cost
+            Some(ralph_core::Usage::new(
+                0.0,
+                state.input_tokens,
+                state.output_tokens,
+            ))
+        }
+    }
+}
+
 /// Dispatches a Claude stream event to the appropriate handler method.
 /// Also accumulates text content into `extracted_text` for event parsing.
 fn dispatch_stream_event<H: StreamHandler>(
@@ -1621,15 +1813,19 @@ fn dispatch_stream_event<H: StreamHandler>(
             total_cost_usd,
             num_turns,
             is_error,
+            usage,
         } => {
             if is_error {
                 handler.on_error("Session ended with error");
             }
+            let usage = usage.unwrap_or_default();
             handler.on_complete(&SessionResult {
                 duration_ms,
                 total_cost_usd,
                 num_turns,
                 is_error,
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
             });
         }
     }
@@ -1987,6 +2183,7 @@ mod tests {
             total_cost_usd: 0.01,
             num_turns: 2,
             is_error: true,
+            usage: None,
         };
 
         dispatch_stream_event(event, &mut handler, &mut extracted_text);
@@ -2125,6 +2322,7 @@ mod tests {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -2148,6 +2346,42 @@ mod tests {
         assert_eq!(result.termination, TerminationType::Natural);
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_observe_applies_limits() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let backend = CliBackend {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string()],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: None,
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        };
+        let config = PtyConfig {
+            interactive: false,
+            idle_timeout_secs: 0,
+            cols: 80,
+            rows: 24,
+            workspace_root: temp_dir.path().to_path_buf(),
+        };
+        let limits = ResourceLimits {
+            max_open_files: Some(64),
+            ..Default::default()
+        };
+        let executor = PtyExecutor::new(backend, config).with_limits(limits);
+        let (_tx, rx) = tokio::sync::watch::channel(false);
+
+        let result = executor
+            .run_observe("echo nofile=$(ulimit -n)", rx)
+            .await
+            .expect("run_observe");
+
+        assert!(result.success);
+        assert!(result.stripped_output.contains("nofile=64"));
+    }
+
     #[cfg(unix)]
     #[tokio::test]
     async fn test_run_observe_writes_stdin_prompt() {
@@ -2159,6 +2393,7 @@ mod tests {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -2192,6 +2427,7 @@ mod tests {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -2228,6 +2464,7 @@ mod tests {
             prompt_flag: None,
             output_format: OutputFormat::StreamJson,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -2269,6 +2506,7 @@ mod tests {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: true,
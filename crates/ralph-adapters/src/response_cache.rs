@@ -0,0 +1,162 @@
+//! Content-addressed cache of backend responses.
+//!
+//! Maps a prompt (plus the command that would run it) to the output the
+//! backend produced for it, so re-running an identical prompt is free. Each
+//! entry is a JSON file named after the key's hash. Entries store the full
+//! prompt and command, and a lookup only hits when both match exactly.
+
+use crate::cli_backend::CliBackend;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// A cached backend response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// Backend command the response came from.
+    pub command: String,
+    /// Arguments the command ran with.
+    pub args: Vec<String>,
+    /// The prompt that produced the response.
+    pub prompt: String,
+    /// The backend's output.
+    pub output: String,
+}
+
+/// Directory of cached responses keyed by prompt hash.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    /// Creates a cache stored in `dir`. The directory is created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory the entries live in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the cached response for `prompt` run through `backend`.
+    ///
+    /// Missing, unreadable, or mismatched entries are all misses.
+    pub fn get(&self, backend: &CliBackend, prompt: &str) -> Option<CachedResponse> {
+        let path = self.entry_path(backend, prompt);
+        let contents = std::fs::read_to_string(&path).ok()?;
+        let entry: CachedResponse = match serde_json::from_str(&contents) {
+            Ok(entry) => entry,
+            Err(e) => {
+                debug!(path = %path.display(), error = %e, "Ignoring unreadable cache entry");
+                return None;
+            }
+        };
+        (entry.command == backend.command && entry.args == backend.args && entry.prompt == prompt)
+            .then_some(entry)
+    }
+
+    /// Stores `output` as the response to `prompt` run through `backend`.
+    pub fn put(&self, backend: &CliBackend, prompt: &str, output: &str) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let entry = CachedResponse {
+            command: backend.command.clone(),
+            args: backend.args.clone(),
+            prompt: prompt.to_string(),
+            output: output.to_string(),
+        };
+        let path = self.entry_path(backend, prompt);
+        // Write then rename so a concurrent reader never sees a partial entry
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&entry)?)?;
+        std::fs::rename(&tmp, &path)
+    }
+
+    fn entry_path(&self, backend: &CliBackend, prompt: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.json", cache_key(backend, prompt)))
+    }
+}
+
+/// Hashes the command, its arguments, and the prompt (64-bit FNV-1a).
+///
+/// FNV is stable across builds, unlike `DefaultHasher`, so keys survive
+/// toolchain upgrades. Collisions are caught by comparing the stored prompt.
+fn cache_key(backend: &CliBackend, prompt: &str) -> String {
+    let parts = std::iter::once(backend.command.as_str())
+        .chain(backend.args.iter().map(String::as_str))
+        .chain(std::iter::once(prompt))
+        .map(str::as_bytes);
+    ralph_core::repro::fingerprint(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli_backend::{OutputFormat, PromptMode};
+    use tempfile::TempDir;
+
+    fn backend(args: &[&str]) -> CliBackend {
+        CliBackend {
+            command: "claude".to_string(),
+            args: args.iter().map(ToString::to_string).collect(),
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: Some("-p".to_string()),
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        }
+    }
+
+    #[test]
+    fn test_put_then_get_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let cache = ResponseCache::new(dir.path().join("responses"));
+        let backend = backend(&["--model", "opus"]);
+
+        assert!(cache.get(&backend, "plan the work").is_none());
+        cache.put(&backend, "plan the work", "1. do it\n").unwrap();
+
+        let entry = cache.get(&backend, "plan the work").unwrap();
+        assert_eq!(entry.output, "1. do it\n");
+    }
+
+    #[test]
+    fn test_different_prompt_or_args_miss() {
+        let dir = TempDir::new().unwrap();
+        let cache = ResponseCache::new(dir.path());
+        cache
+            .put(&backend(&["--model", "opus"]), "plan", "out")
+            .unwrap();
+
+        assert!(cache.get(&backend(&["--model", "opus"]), "plan!").is_none());
+        assert!(cache.get(&backend(&["--model", "haiku"]), "plan").is_none());
+    }
+
+    #[test]
+    fn test_mismatched_entry_is_a_miss() {
+        let dir = TempDir::new().unwrap();
+        let cache = ResponseCache::new(dir.path());
+        let backend = backend(&[]);
+        cache.put(&backend, "plan", "out").unwrap();
+
+        // Simulate a hash collision: same file, different stored prompt
+        let path = cache.entry_path(&backend, "plan");
+        let mut entry: CachedResponse =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        entry.prompt = "something else".to_string();
+        std::fs::write(&path, serde_json::to_string(&entry).unwrap()).unwrap();
+
+        assert!(cache.get(&backend, "plan").is_none());
+    }
+
+    #[test]
+    fn test_key_separates_argument_boundaries() {
+        assert_ne!(
+            cache_key(&backend(&["ab", "c"]), "p"),
+            cache_key(&backend(&["a", "bc"]), "p")
+        );
+        assert_eq!(cache_key(&backend(&["x"]), "p").len(), 16);
+    }
+}
@@ -0,0 +1,191 @@
+//! Parallel read-only scouts with the CLI backends.
+//!
+//! [`run_scouts`] runs each of a hat's `scouts.prompts` headless, at most
+//! `max_parallel` at a time, and returns their answers in config order. See
+//! [`ralph_core::scouts`] for the prompt and report side.
+
+use crate::cli_backend::CliBackend;
+use crate::cli_executor::CliExecutor;
+use crate::container::ContainerEnvironment;
+use ralph_core::scouts::{ScoutReport, scout_prompt};
+use ralph_core::{EnvironmentConfig, ResourceLimits, ScoutsConfig};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+/// Scouts to run before one iteration.
+#[derive(Debug, Clone, Copy)]
+pub struct ScoutRequest<'a> {
+    /// The hat's scout configuration.
+    pub config: &'a ScoutsConfig,
+    /// Backend used when `scouts.backend` is unset (the hat's backend).
+    pub default_backend: &'a CliBackend,
+    /// Container environment for the hat, applied to `scouts.backend`.
+    pub environment: Option<&'a EnvironmentConfig>,
+    /// Directory the scouts run in.
+    pub workspace: &'a Path,
+    /// Resource limits for each scout process.
+    pub limits: ResourceLimits,
+    /// Resolved `credentials`, set in `scouts.backend`'s environment.
+    pub credentials: &'a [(String, String)],
+}
+
+/// Runs every scout and returns the reports in config order.
+///
+/// Scouts that fail to start, time out, or exit unsuccessfully are reported
+/// with `success: false` rather than failing the iteration.
+pub async fn run_scouts(request: ScoutRequest<'_>) -> Vec<ScoutReport> {
+    let config = request.config;
+    let backend = scout_backend(&request);
+    let timeout = Duration::from_secs(config.timeout_seconds);
+    let semaphore = Arc::new(Semaphore::new(config.max_parallel.max(1)));
+    info!(
+        scouts = config.prompts.len(),
+        max_parallel = config.max_parallel,
+        "Running scouts"
+    );
+
+    let mut reports: Vec<ScoutReport> = config
+        .prompts
+        .iter()
+        .map(|task| ScoutReport {
+            task: task.clone(),
+            output: String::new(),
+            success: false,
+        })
+        .collect();
+
+    let mut running = JoinSet::new();
+    for (index, task) in config.prompts.iter().enumerate() {
+        let semaphore = Arc::clone(&semaphore);
+        let executor = CliExecutor::new(backend.clone())
+            .with_limits(request.limits)
+            .with_working_dir(request.workspace);
+        let prompt = scout_prompt(task);
+        running.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = executor
+                .execute(&prompt, std::io::sink(), Some(timeout), false)
+                .await;
+            (index, result)
+        });
+    }
+
+    while let Some(joined) = running.join_next().await {
+        match joined {
+            Ok((index, Ok(result))) => {
+                reports[index].output = result.output;
+                reports[index].success = result.success;
+            }
+            Ok((index, Err(e))) => {
+                warn!(scout = %reports[index].task, error = %e, "Scout failed to run");
+            }
+            Err(e) => warn!(error = %e, "Scout task panicked"),
+        }
+    }
+    reports
+}
+
+fn scout_backend(request: &ScoutRequest<'_>) -> CliBackend {
+    let Some(configured) = request.config.backend.as_ref() else {
+        return request.default_backend.clone();
+    };
+    match CliBackend::from_hat_backend(configured) {
+        Ok(backend) => {
+            let backend = backend.with_env_vars(request.credentials);
+            match request.environment {
+                Some(environment) => backend.with_container(ContainerEnvironment::from_config(
+                    environment,
+                    request.workspace,
+                )),
+                None => backend,
+            }
+        }
+        Err(e) => {
+            warn!(error = %e, "Invalid scouts backend; using the hat's backend");
+            request.default_backend.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli_backend::{OutputFormat, PromptMode};
+
+    fn echo_backend() -> CliBackend {
+        CliBackend {
+            command: "echo".to_string(),
+            args: vec![],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: None,
+            output_format: OutputFormat::Text,
+            env_vars: vec![],
+            container: None,
+        }
+    }
+
+    fn scouts(prompts: &[&str], max_parallel: usize) -> ScoutsConfig {
+        ScoutsConfig {
+            prompts: prompts.iter().map(ToString::to_string).collect(),
+            max_parallel,
+            backend: None,
+            timeout_seconds: 30,
+            max_output_chars: 4000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_scouts_returns_reports_in_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = scouts(&["Summarize A", "Summarize B", "Summarize C"], 2);
+        let backend = echo_backend();
+
+        let reports = run_scouts(ScoutRequest {
+            config: &config,
+            default_backend: &backend,
+            environment: None,
+            workspace: dir.path(),
+            limits: ResourceLimits::default(),
+            credentials: &[],
+        })
+        .await;
+
+        assert_eq!(reports.len(), 3);
+        for (report, task) in reports
+            .iter()
+            .zip(["Summarize A", "Summarize B", "Summarize C"])
+        {
+            assert_eq!(report.task, task);
+            assert!(report.success);
+            assert!(report.output.contains(task));
+            assert!(report.output.contains("read-only scout"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_scouts_reports_failures() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = scouts(&["Summarize A"], 0);
+        let backend = CliBackend {
+            command: "false".to_string(),
+            ..echo_backend()
+        };
+
+        let reports = run_scouts(ScoutRequest {
+            config: &config,
+            default_backend: &backend,
+            environment: None,
+            workspace: dir.path(),
+            limits: ResourceLimits::default(),
+            credentials: &[],
+        })
+        .await;
+
+        assert_eq!(reports.len(), 1);
+        assert!(!reports[0].success);
+    }
+}
@@ -0,0 +1,272 @@
+//! Speculative dual-backend execution with the CLI backends.
+//!
+//! [`run_speculative`] runs one prompt on both `speculative.backends` at once,
+//! each headless in its own worktree, then lets the configured arbiter pick a
+//! winner. The winner's changes and events land in the main workspace; the
+//! other candidate is discarded. See [`ralph_core::speculative`] for the
+//! workspace side.
+
+use crate::cli_backend::CliBackend;
+use crate::cli_executor::{CliExecutor, ExecutionResult};
+use crate::container::ContainerEnvironment;
+use crate::error::Error;
+use ralph_core::speculative::{self, Candidate, CandidateReport, Verdict};
+use ralph_core::utils::run_blocking;
+use ralph_core::{ArbiterKind, EnvironmentConfig, EventWriter, RalphConfig};
+use std::path::Path;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// One speculative iteration to run.
+#[derive(Debug, Clone, Copy)]
+pub struct SpeculativeRequest<'a> {
+    /// Iteration number, used to name the candidate worktrees.
+    pub iteration: u32,
+    /// The prompt both backends receive.
+    pub prompt: &'a str,
+    /// Root of the main workspace (a git repository).
+    pub workspace: &'a Path,
+    /// Main events file the winner's events are appended to.
+    pub events_path: &'a Path,
+    /// Container environment for the active hat, if any.
+    pub environment: Option<&'a EnvironmentConfig>,
+    /// Backend used as the judge when `speculative.judge` is unset.
+    pub default_backend: &'a CliBackend,
+    /// Resolved `credentials`, set in the candidates' and judge's environment.
+    pub credentials: &'a [(String, String)],
+    /// Loop configuration.
+    pub config: &'a RalphConfig,
+}
+
+/// The committed result of a speculative iteration.
+#[derive(Debug, Clone)]
+pub struct SpeculativeOutcome {
+    /// The winner's output, scanned for events like a normal iteration.
+    pub output: String,
+    /// Whether the winning backend succeeded.
+    pub success: bool,
+    /// Label of the winning backend.
+    pub winner: String,
+    /// Labels of all candidates, in config order.
+    pub candidates: Vec<String>,
+    /// Why the arbiter chose the winner.
+    pub reason: String,
+}
+
+/// Runs `request.prompt` on both speculative backends and commits the winner.
+///
+/// # Errors
+///
+/// Returns an error if a backend can't be built, the worktrees can't be
+/// created, or the winner's changes don't apply. Candidate worktrees are
+/// removed in every case.
+pub async fn run_speculative(request: SpeculativeRequest<'_>) -> Result<SpeculativeOutcome, Error> {
+    let settings = &request.config.speculative;
+    let [first, second] = settings.backends.as_slice() else {
+        return Err(ralph_core::Error::Backend(
+            "speculative execution needs exactly two backends".to_string(),
+        )
+        .into());
+    };
+    let backends = [
+        (CliBackend::from_hat_backend(first)?, first.adapter_name()),
+        (CliBackend::from_hat_backend(second)?, second.adapter_name()),
+    ];
+    let labels: Vec<String> = backends.iter().map(|(_, name)| name.clone()).collect();
+
+    let candidates = run_blocking(|| {
+        speculative::prepare_candidates(request.workspace, request.iteration, &labels)
+    })
+    .map_err(ralph_core::Error::from)?;
+    info!(
+        iteration = request.iteration,
+        candidates = ?labels,
+        "Running speculative iteration"
+    );
+
+    let result = race_and_commit(&request, &candidates, backends).await;
+    run_blocking(|| speculative::discard(request.workspace, &candidates));
+    result
+}
+
+async fn race_and_commit(
+    request: &SpeculativeRequest<'_>,
+    candidates: &[Candidate],
+    backends: [(CliBackend, String); 2],
+) -> Result<SpeculativeOutcome, Error> {
+    let [(first, first_name), (second, second_name)] = backends;
+    let (first_result, second_result) = tokio::join!(
+        run_candidate(request, &candidates[0], first, &first_name),
+        run_candidate(request, &candidates[1], second, &second_name),
+    );
+
+    let settings = &request.config.speculative;
+    let mut reports = Vec::with_capacity(candidates.len());
+    for (candidate, result) in candidates.iter().zip([first_result, second_result]) {
+        let (output, success) = match result {
+            Ok(result) => (result.output, result.success),
+            Err(e) => {
+                warn!(candidate = %candidate.label, error = %e, "Speculative candidate failed to run");
+                (String::new(), false)
+            }
+        };
+        let mut report =
+            run_blocking(|| candidate.collect(output, success)).map_err(ralph_core::Error::from)?;
+        if settings.arbiter == ArbiterKind::Check
+            && let Some(check) = settings.check.as_deref()
+        {
+            report.check_passed = Some(run_blocking(|| candidate.run_check(check)));
+        }
+        reports.push(report);
+    }
+
+    let verdict = match settings.arbiter {
+        ArbiterKind::Judge => judge(request, &reports).await,
+        ArbiterKind::Heuristic | ArbiterKind::Check => speculative::arbitrate(&reports),
+    };
+    let winner = &reports[verdict.winner];
+    info!(
+        winner = %winner.label,
+        arbiter = settings.arbiter.as_str(),
+        reason = %verdict.reason,
+        "Speculative iteration resolved"
+    );
+
+    let events = EventWriter::new(request.events_path);
+    run_blocking(|| speculative::commit(request.workspace, winner, &events))
+        .map_err(ralph_core::Error::from)?;
+
+    Ok(SpeculativeOutcome {
+        output: winner.output.clone(),
+        success: winner.success,
+        winner: winner.label.clone(),
+        candidates: reports.iter().map(|r| r.label.clone()).collect(),
+        reason: verdict.reason,
+    })
+}
+
+async fn run_candidate(
+    request: &SpeculativeRequest<'_>,
+    candidate: &Candidate,
+    backend: CliBackend,
+    backend_name: &str,
+) -> std::io::Result<ExecutionResult> {
+    let mut backend = backend.with_env_vars(request.credentials);
+    if let Some(environment) = request.environment {
+        backend = backend.with_container(ContainerEnvironment::from_config(
+            environment,
+            &candidate.path,
+        ));
+    }
+    let timeout_secs = request.config.adapter_settings(backend_name).timeout;
+    CliExecutor::new(backend)
+        .with_limits(request.config.cli.limits)
+        .with_working_dir(&candidate.path)
+        .execute(
+            request.prompt,
+            std::io::sink(),
+            Some(Duration::from_secs(timeout_secs)),
+            false,
+        )
+        .await
+}
+
+/// Asks the judge backend for a winner, falling back to the heuristic.
+async fn judge(request: &SpeculativeRequest<'_>, reports: &[CandidateReport]) -> Verdict {
+    let backend = match request.config.speculative.judge.as_ref() {
+        Some(judge) => match CliBackend::from_hat_backend(judge) {
+            Ok(backend) => backend.with_env_vars(request.credentials),
+            Err(e) => {
+                warn!(error = %e, "Invalid speculative judge backend; using heuristic");
+                return speculative::arbitrate(reports);
+            }
+        },
+        None => request.default_backend.clone(),
+    };
+
+    let prompt = speculative::judge_prompt(request.prompt, reports);
+    let response = CliExecutor::new(backend)
+        .with_limits(request.config.cli.limits)
+        .with_working_dir(request.workspace)
+        .execute(&prompt, std::io::sink(), None, false)
+        .await;
+    match response {
+        Ok(result) => match speculative::parse_verdict(&result.output, reports.len()) {
+            Some(winner) => Verdict {
+                winner,
+                reason: "chosen by judge".to_string(),
+            },
+            None => {
+                warn!("Speculative judge gave no verdict; using heuristic");
+                speculative::arbitrate(reports)
+            }
+        },
+        Err(e) => {
+            warn!(error = %e, "Speculative judge failed; using heuristic");
+            speculative::arbitrate(reports)
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use ralph_core::HatBackend;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        if let Err(err) = ralph_core::run_git(dir, args) {
+            panic!("git {args:?} failed: {err}");
+        }
+    }
+
+    fn custom(script: &str) -> HatBackend {
+        HatBackend::Custom {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_check_arbiter_commits_passing_candidate() {
+        let tmp = TempDir::new().unwrap();
+        let repo = tmp.path();
+        git(repo, &["init", "--initial-branch=main"]);
+        git(repo, &["config", "user.email", "test@test.local"]);
+        git(repo, &["config", "user.name", "Test User"]);
+        fs::write(repo.join(".gitignore"), ".worktrees/\n.ralph/\n").unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-m", "init"]);
+
+        let mut config = RalphConfig::default();
+        config.speculative.enabled = true;
+        config.speculative.arbiter = ArbiterKind::Check;
+        config.speculative.check = Some("test -f passing.txt".to_string());
+        // The prompt is passed as $0 to `sh -c`.
+        config.speculative.backends = vec![
+            custom("echo first > failing.txt"),
+            custom("echo second > passing.txt"),
+        ];
+
+        let events_path = repo.join(".ralph/events.jsonl");
+        let outcome = run_speculative(SpeculativeRequest {
+            iteration: 1,
+            prompt: "do it",
+            workspace: repo,
+            events_path: &events_path,
+            environment: None,
+            default_backend: &CliBackend::claude(),
+            credentials: &[],
+            config: &config,
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(outcome.candidates, ["sh", "sh"]);
+        assert!(outcome.reason.starts_with("check passed"));
+        assert!(repo.join("passing.txt").exists());
+        assert!(!repo.join("failing.txt").exists());
+        assert!(!repo.join(".worktrees/speculative-1-a").exists());
+    }
+}
@@ -80,6 +80,17 @@ pub struct SessionResult {
     pub total_cost_usd: f64,
     pub num_turns: u32,
     pub is_error: bool,
+    /// Prompt tokens for the session (0 if the backend doesn't report them).
+    pub input_tokens: u64,
+    /// Completion tokens for the session (0 if the backend doesn't report them).
+    pub output_tokens: u64,
+}
+
+impl SessionResult {
+    /// Returns the session's spend for cost attribution.
+    pub fn usage(&self) -> ralph_core::Usage {
+        ralph_core::Usage::new(self.total_cost_usd, self.input_tokens, self.output_tokens)
+    }
 }
 
 /// Renders streaming output with colors and markdown.
@@ -216,6 +227,53 @@ pub trait StreamHandler: Send {
     fn on_complete(&mut self, result: &SessionResult);
 }
 
+/// Forwards to another handler and keeps the session's final [`SessionResult`].
+///
+/// Used to pick up cost and token totals for attribution without changing
+/// how the inner handler displays them.
+pub struct ResultRecorder<H> {
+    inner: H,
+    result: Option<SessionResult>,
+}
+
+impl<H: StreamHandler> ResultRecorder<H> {
+    /// Wraps `inner`.
+    pub fn new(inner: H) -> Self {
+        Self {
+            inner,
+            result: None,
+        }
+    }
+
+    /// Returns the recorded session result, if the session completed.
+    pub fn into_result(self) -> Option<SessionResult> {
+        self.result
+    }
+}
+
+impl<H: StreamHandler> StreamHandler for ResultRecorder<H> {
+    fn on_text(&mut self, text: &str) {
+        self.inner.on_text(text);
+    }
+
+    fn on_tool_call(&mut self, name: &str, id: &str, input: &serde_json::Value) {
+        self.inner.on_tool_call(name, id, input);
+    }
+
+    fn on_tool_result(&mut self, id: &str, output: &str) {
+        self.inner.on_tool_result(id, output);
+    }
+
+    fn on_error(&mut self, error: &str) {
+        self.inner.on_error(error);
+    }
+
+    fn on_complete(&mut self, result: &SessionResult) {
+        self.result = Some(result.clone());
+        self.inner.on_complete(result);
+    }
+}
+
 /// Writes streaming output to stdout/stderr.
 ///
 /// In normal mode, displays assistant text and tool invocations.
@@ -620,6 +678,8 @@ mod tests {
             total_cost_usd: 0.01,
             num_turns: 1,
             is_error: false,
+            input_tokens: 0,
+            output_tokens: 0,
         });
     }
 
@@ -637,6 +697,8 @@ mod tests {
             total_cost_usd: 0.01,
             num_turns: 1,
             is_error: false,
+            input_tokens: 0,
+            output_tokens: 0,
         }); // Should be silent
     }
 
@@ -655,7 +717,27 @@ mod tests {
             total_cost_usd: 0.01,
             num_turns: 1,
             is_error: false,
+            input_tokens: 0,
+            output_tokens: 0,
+        });
+    }
+
+    #[test]
+    fn test_session_recorder_keeps_result() {
+        let mut recorder = ResultRecorder::new(QuietStreamHandler);
+        recorder.on_text("Hello");
+        recorder.on_complete(&SessionResult {
+            duration_ms: 1000,
+            total_cost_usd: 0.25,
+            num_turns: 2,
+            is_error: false,
+            input_tokens: 1200,
+            output_tokens: 300,
         });
+
+        let usage = recorder.into_result().unwrap().usage();
+        assert!((usage.cost_usd - 0.25).abs() < f64::EPSILON);
+        assert_eq!(usage.total_tokens(), 1500);
     }
 
     #[test]
@@ -1112,6 +1194,8 @@ mod tests {
                 total_cost_usd: 0.0025,
                 num_turns: 3,
                 is_error: false,
+                input_tokens: 0,
+                output_tokens: 0,
             });
 
             // Then buffer is flushed and summary line appears
@@ -1142,6 +1226,8 @@ mod tests {
                 total_cost_usd: 0.01,
                 num_turns: 1,
                 is_error: true,
+                input_tokens: 0,
+                output_tokens: 0,
             });
 
             let lines = collect_lines(&handler);
@@ -1164,6 +1250,8 @@ mod tests {
                 total_cost_usd: 0.01,
                 num_turns: 1,
                 is_error: false,
+                input_tokens: 0,
+                output_tokens: 0,
             });
 
             let lines = collect_lines(&handler);
@@ -48,6 +48,7 @@ mod pty_executor_integration {
             prompt_flag: None,
             output_format: OutputFormat::Text,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -79,6 +80,7 @@ mod pty_executor_integration {
             prompt_flag: None,
             output_format: OutputFormat::StreamJson,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -113,6 +115,7 @@ mod pty_executor_integration {
             prompt_flag: None,
             output_format: OutputFormat::StreamJson,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -154,6 +157,7 @@ mod pty_executor_integration {
             prompt_flag: None,
             output_format: OutputFormat::PiStreamJson,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -206,6 +210,61 @@ mod pty_executor_integration {
         );
     }
 
+    #[tokio::test]
+    async fn run_observe_streaming_goose_stream_json_parses_events() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let backend = CliBackend {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string()],
+            prompt_mode: PromptMode::Arg,
+            prompt_flag: None,
+            output_format: OutputFormat::GooseStreamJson,
+            env_vars: vec![],
+            container: None,
+        };
+        let config = PtyConfig {
+            interactive: false,
+            idle_timeout_secs: 0,
+            cols: 80,
+            rows: 24,
+            workspace_root: temp_dir.path().to_path_buf(),
+        };
+        let executor = PtyExecutor::new(backend, config);
+        let (_tx, rx) = tokio::sync::watch::channel(false);
+        let mut handler = CapturingHandler::default();
+
+        // Simulate a Goose session with text, tool request/response, and completion
+        let script = r#"printf '%s\n' \
+'starting session | provider: openai model: gpt-4.1' \
+'{"type":"message","message":{"role":"assistant","content":[{"type":"text","text":"Hello from Goose"}]}}' \
+'{"type":"message","message":{"role":"assistant","content":[{"type":"toolRequest","id":"call_1","toolCall":{"status":"success","value":{"name":"developer__shell","arguments":{"command":"echo hi"}}}}]}}' \
+'{"type":"message","message":{"role":"user","content":[{"type":"toolResponse","id":"call_1","toolResult":{"status":"success","value":[{"type":"text","text":"hi"}]}}]}}' \
+'{"type":"complete","total_tokens":1500}'"#;
+
+        let result = executor
+            .run_observe_streaming(script, rx, &mut handler)
+            .await
+            .expect("run_observe_streaming");
+
+        assert!(result.success);
+        assert!(
+            handler.texts.iter().any(|t| t.contains("Hello from Goose")),
+            "Expected text, got: {:?}",
+            handler.texts
+        );
+        assert_eq!(handler.tool_calls.len(), 1);
+        assert_eq!(handler.tool_calls[0].0, "developer__shell");
+        assert_eq!(
+            handler.tool_results,
+            vec![("call_1".to_string(), "hi".to_string())]
+        );
+        assert_eq!(handler.completions.len(), 1);
+        assert_eq!(handler.completions[0].input_tokens, 1500);
+        assert_eq!(handler.completions[0].num_turns, 2);
+        assert!(!handler.completions[0].is_error);
+        assert_eq!(result.extracted_text, "Hello from Goose");
+    }
+
     #[tokio::test]
     async fn run_observe_streaming_pi_multi_turn_cost_accumulation() {
         let temp_dir = TempDir::new().expect("temp dir");
@@ -216,6 +275,7 @@ mod pty_executor_integration {
             prompt_flag: None,
             output_format: OutputFormat::PiStreamJson,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -254,6 +314,7 @@ mod pty_executor_integration {
             prompt_flag: None,
             output_format: OutputFormat::PiStreamJson,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -290,6 +351,7 @@ mod pty_executor_integration {
             prompt_flag: None,
             output_format: OutputFormat::PiStreamJson,
             env_vars: vec![],
+            container: None,
         };
         let config = PtyConfig {
             interactive: false,
@@ -364,6 +364,7 @@ async fn run_task_loop(
 
     // Initialize event loop
     let mut event_loop = EventLoop::new(config.clone());
+    event_loop.ensure_extensions_loaded()?;
     event_loop.initialize(&prompt_content);
 
     // Create CLI executor
@@ -486,7 +487,10 @@ async fn run_task_loop(
         };
 
         // Process output
-        if let Some(reason) = event_loop.process_output(&hat_id, &result.output, result.success) {
+        if let Some(reason) = event_loop
+            .process_output(&hat_id, &result.output, result.success)
+            .await
+        {
             termination_reason = reason;
             break;
         }
@@ -521,15 +525,17 @@ async fn run_task_loop(
 fn format_termination_reason(reason: &TerminationReason) -> String {
     match reason {
         TerminationReason::CompletionPromise => "CompletionPromise".to_string(),
-        TerminationReason::MaxIterations => "MaxIterations".to_string(),
-        TerminationReason::MaxRuntime => "MaxRuntime".to_string(),
-        TerminationReason::MaxCost => "MaxCost".to_string(),
-        TerminationReason::ConsecutiveFailures => "ConsecutiveFailures".to_string(),
-        TerminationReason::LoopThrashing => "LoopThrashing".to_string(),
-        TerminationReason::ValidationFailure => "ValidationFailure".to_string(),
+        TerminationReason::MaxIterations { .. } => "MaxIterations".to_string(),
+        TerminationReason::MaxRuntime { .. } => "MaxRuntime".to_string(),
+        TerminationReason::MaxCost { .. } => "MaxCost".to_string(),
+        TerminationReason::ConsecutiveFailures { .. } => "ConsecutiveFailures".to_string(),
+        TerminationReason::LoopThrashing { .. } => "LoopThrashing".to_string(),
+        TerminationReason::DelegationLoop { .. } => "DelegationLoop".to_string(),
+        TerminationReason::ValidationFailure { .. } => "ValidationFailure".to_string(),
         TerminationReason::Stopped => "Stopped".to_string(),
         TerminationReason::Interrupted => "Interrupted".to_string(),
         TerminationReason::RestartRequested => "RestartRequested".to_string(),
+        TerminationReason::IdleTimeout { .. } => "IdleTimeout".to_string(),
     }
 }
 
@@ -0,0 +1,63 @@
+//! `ralph amend`: steer a live run without restarting it.
+//!
+//! Appends an instruction to the loop's amendments file. The loop injects
+//! every amendment into each prompt from the next iteration on, and the
+//! amendment is recorded in the events file as `ralph.amended`.
+
+use crate::display::colors;
+use anyhow::{Context, Result};
+use clap::Parser;
+use ralph_core::LoopContext;
+use ralph_core::amendments;
+use std::path::PathBuf;
+
+/// Arguments for the amend subcommand.
+#[derive(Parser, Debug)]
+pub struct AmendArgs {
+    /// Instruction to add to the objective
+    #[arg(required_unless_present = "list")]
+    pub text: Option<String>,
+
+    /// List the loop's amendments instead of adding one
+    #[arg(long, conflicts_with = "text")]
+    pub list: bool,
+
+    /// Workspace of the loop to amend, e.g. a worktree (default: current directory)
+    #[arg(long)]
+    pub root: Option<PathBuf>,
+}
+
+/// Executes `ralph amend`.
+pub fn execute(args: AmendArgs, use_colors: bool) -> Result<()> {
+    let root = args.root.unwrap_or_else(|| PathBuf::from("."));
+    let ctx = LoopContext::primary(root);
+    let amendments_path = ctx.amendments_path();
+
+    let Some(text) = args.text else {
+        let amendments = amendments::read(&amendments_path)
+            .with_context(|| format!("Failed to read amendments: {}", amendments_path.display()))?;
+        if amendments.is_empty() {
+            println!("No amendments.");
+        }
+        for (index, amendment) in amendments.iter().enumerate() {
+            println!("{}. [{}] {}", index + 1, amendment.ts, amendment.text);
+        }
+        return Ok(());
+    };
+
+    let events_path = ctx.current_events_path();
+    let amendment = amendments::amend(&amendments_path, &events_path, &text)
+        .context("Failed to amend the objective")?;
+    let count = amendments::read(&amendments_path).map_or(0, |all| all.len());
+
+    let message = format!(
+        "Amendment {count} added; it applies from the next iteration: {}",
+        amendment.text
+    );
+    if use_colors {
+        println!("{}✓{} {message}", colors::GREEN, colors::RESET);
+    } else {
+        println!("{message}");
+    }
+    Ok(())
+}
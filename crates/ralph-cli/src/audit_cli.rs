@@ -0,0 +1,273 @@
+//! CLI command for `ralph tools audit`.
+//!
+//! Runs `cargo audit` and `npm audit` for whichever lockfiles the workspace
+//! has and prints their findings in one format. With `--emit`, each finding
+//! becomes a `security.advisory` event (or a single `audit.clean` when there
+//! are none) for the maintenance pack's hats to act on.
+
+use crate::display::colors;
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use ralph_core::audit::{
+    ADVISORY_TOPIC, Advisory, CLEAN_TOPIC, parse_cargo_audit, parse_npm_audit,
+};
+use ralph_core::{EventWriter, LoopContext};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Output format for `audit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// One line per advisory
+    #[default]
+    Text,
+    /// JSON array of advisories
+    Json,
+}
+
+/// Arguments for the `audit` command.
+#[derive(Parser, Debug)]
+pub struct AuditArgs {
+    /// Publish findings as `security.advisory` events (or `audit.clean`)
+    #[arg(long)]
+    pub emit: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Working directory (default: current directory)
+    #[arg(long)]
+    pub root: Option<PathBuf>,
+}
+
+/// An auditor and the lockfile that enables it.
+struct Auditor {
+    lockfile: &'static str,
+    program: &'static str,
+    args: &'static [&'static str],
+    parse: fn(&str) -> serde_json::Result<Vec<Advisory>>,
+}
+
+const AUDITORS: &[Auditor] = &[
+    Auditor {
+        lockfile: "Cargo.lock",
+        program: "cargo",
+        args: &["audit", "--json"],
+        parse: parse_cargo_audit,
+    },
+    Auditor {
+        lockfile: "package-lock.json",
+        program: "npm",
+        args: &["audit", "--json"],
+        parse: parse_npm_audit,
+    },
+];
+
+/// Execute the audit command.
+pub fn execute(args: AuditArgs, use_colors: bool) -> Result<()> {
+    let root = args.root.unwrap_or_else(|| PathBuf::from("."));
+    let auditors: Vec<&Auditor> = AUDITORS
+        .iter()
+        .filter(|auditor| root.join(auditor.lockfile).exists())
+        .collect();
+    if auditors.is_empty() {
+        eprintln!("No Cargo.lock or package-lock.json found; nothing to audit");
+    }
+
+    let mut advisories = Vec::new();
+    let mut audited = false;
+    for auditor in auditors {
+        if let Some(found) = run_auditor(&root, auditor)? {
+            advisories.extend(found);
+            audited = true;
+        }
+    }
+
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&advisories)?),
+        OutputFormat::Text if advisories.is_empty() => {
+            if audited {
+                println!("No known vulnerabilities");
+            }
+        }
+        OutputFormat::Text => {
+            for advisory in &advisories {
+                print_advisory(advisory, use_colors);
+            }
+        }
+    }
+
+    // Without a report, "clean" would be a claim nothing checked
+    if args.emit && !audited {
+        eprintln!("Nothing was audited; no events emitted");
+    } else if args.emit {
+        let topics = emit(&root, &advisories)?;
+        eprintln!("Emitted {topics}");
+    }
+    Ok(())
+}
+
+/// Runs one auditor, returning `None` (with a warning) if it isn't installed.
+fn run_auditor(root: &Path, auditor: &Auditor) -> Result<Option<Vec<Advisory>>> {
+    let command = format!("{} {}", auditor.program, auditor.args.join(" "));
+    let output = match Command::new(auditor.program)
+        .args(auditor.args)
+        .current_dir(root)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            eprintln!(
+                "Skipping {}: {} is not installed",
+                auditor.lockfile, auditor.program
+            );
+            return Ok(None);
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to run {command}")),
+    };
+
+    // Both auditors exit non-zero when they find something; only a missing
+    // report means the run itself failed (e.g. cargo-audit isn't installed).
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        eprintln!(
+            "Skipping {}: {command} produced no report ({})",
+            auditor.lockfile,
+            stderr.lines().next().unwrap_or("no output").trim()
+        );
+        return Ok(None);
+    }
+    (auditor.parse)(&stdout)
+        .map(Some)
+        .with_context(|| format!("Failed to parse {command} output"))
+}
+
+/// Appends one event per advisory, or `audit.clean`, to the active run's
+/// events file. Returns a summary of what was emitted.
+fn emit(root: &Path, advisories: &[Advisory]) -> Result<String> {
+    let ts = chrono::Utc::now().to_rfc3339();
+    let records: Vec<serde_json::Value> = if advisories.is_empty() {
+        vec![serde_json::json!({
+            "topic": CLEAN_TOPIC,
+            "payload": "No known vulnerabilities",
+            "ts": ts,
+        })]
+    } else {
+        advisories
+            .iter()
+            .map(|advisory| {
+                serde_json::json!({
+                    "topic": ADVISORY_TOPIC,
+                    "payload": advisory,
+                    "ts": ts,
+                })
+            })
+            .collect()
+    };
+
+    let lines = records
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()?;
+    let events_file = LoopContext::primary(root.to_path_buf()).current_events_path();
+    EventWriter::new(&events_file)
+        .append_lines(&lines)
+        .with_context(|| format!("Failed to write events file: {}", events_file.display()))?;
+
+    Ok(if advisories.is_empty() {
+        CLEAN_TOPIC.to_string()
+    } else {
+        format!("{} {ADVISORY_TOPIC} event(s)", advisories.len())
+    })
+}
+
+fn print_advisory(advisory: &Advisory, use_colors: bool) {
+    let severity = advisory.severity.as_deref().unwrap_or("unrated");
+    let fix = advisory
+        .fixed_in
+        .as_deref()
+        .map_or_else(|| "no fix".to_string(), |v| format!("fixed in {v}"));
+    if use_colors {
+        println!(
+            "{}{}{} {} {} ({}, {}, {})",
+            colors::BOLD,
+            advisory.id,
+            colors::RESET,
+            advisory.package,
+            advisory.version,
+            advisory.ecosystem,
+            severity,
+            fix
+        );
+        println!("  {}{}{}", colors::DIM, advisory.title, colors::RESET);
+    } else {
+        println!(
+            "{} {} {} ({}, {}, {})",
+            advisory.id, advisory.package, advisory.version, advisory.ecosystem, severity, fix
+        );
+        println!("  {}", advisory.title);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn advisory() -> Advisory {
+        Advisory {
+            ecosystem: "cargo".to_string(),
+            id: "RUSTSEC-2024-0006".to_string(),
+            package: "shlex".to_string(),
+            version: "1.1.0".to_string(),
+            severity: None,
+            title: "Multiple issues involving quote API".to_string(),
+            fixed_in: Some("1.3.0".to_string()),
+            url: None,
+        }
+    }
+
+    fn read_events(path: &Path) -> Vec<serde_json::Value> {
+        fs::read_to_string(path)
+            .expect("events")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("event json"))
+            .collect()
+    }
+
+    #[test]
+    fn test_emit_writes_one_event_per_advisory() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".ralph")).expect("ralph dir");
+        fs::write(
+            root.join(".ralph/current-events"),
+            ".ralph/events-test.jsonl",
+        )
+        .expect("marker");
+
+        emit(root, &[advisory(), advisory()]).expect("emit");
+
+        let events = read_events(&root.join(".ralph/events-test.jsonl"));
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["topic"], ADVISORY_TOPIC);
+        assert_eq!(events[0]["payload"]["package"], "shlex");
+        assert_eq!(events[0]["payload"]["fixed_in"], "1.3.0");
+    }
+
+    #[test]
+    fn test_emit_clean_without_advisories() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let root = temp_dir.path();
+
+        emit(root, &[]).expect("emit");
+
+        let events = read_events(&root.join(".ralph/events.jsonl"));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["topic"], CLEAN_TOPIC);
+    }
+}
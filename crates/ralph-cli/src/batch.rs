@@ -0,0 +1,384 @@
+//! `ralph batch`: one full orchestration per prompt file.
+//!
+//! Each file becomes a headless `ralph run -P <file>` child whose output goes
+//! to `.ralph/batch/<batch-id>/<n>-<name>.log`. With `--parallel 1` (the
+//! default) runs go one after another in the workspace, waiting for the
+//! primary loop slot. With more, every run gets its own worktree on branch
+//! `ralph/batch-<batch-id>-<n>`, which is left in place for review.
+//!
+//! When all runs are done, a table of outcomes, iterations, costs, and
+//! branches is printed; the command fails if any run didn't complete.
+
+use crate::OutputFormat;
+use crate::display::{colors, truncate};
+use anyhow::{Context, Result, bail};
+use chrono::{Local, Utc};
+use clap::Parser;
+use ralph_core::worktree::{WorktreeConfig, create_worktree, ensure_gitignore};
+use ralph_core::{HistoryEvent, HistoryEventType, LoopContext, LoopHistory, get_current_branch};
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+/// Arguments for the batch subcommand.
+#[derive(Parser, Debug)]
+pub struct BatchArgs {
+    /// Prompt files to run, one orchestration each (e.g. `tasks/*.md`)
+    #[arg(required = true, value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+
+    /// Runs at a time; above 1, each run gets its own worktree and branch
+    #[arg(short = 'j', long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub parallel: u32,
+
+    /// Override max iterations for every run
+    #[arg(long)]
+    pub max_iterations: Option<u32>,
+
+    /// Override backend for every run
+    #[arg(short = 'b', long)]
+    pub backend: Option<String>,
+
+    /// Output format for the summary
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+/// Everything a run needs that is shared across the batch.
+#[derive(Debug)]
+struct BatchPlan {
+    id: String,
+    workspace: PathBuf,
+    program: PathBuf,
+    config_args: Vec<String>,
+    max_iterations: Option<u32>,
+    backend: Option<String>,
+    worktrees: bool,
+    total: usize,
+}
+
+/// Outcome of one prompt file's run.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchRun {
+    pub index: usize,
+    pub file: String,
+    /// Termination reason from the run's history, or `exit <code>`.
+    pub outcome: String,
+    pub exit_code: Option<i32>,
+    pub iterations: u32,
+    pub cost_usd: f64,
+    pub branch: Option<String>,
+    pub log: String,
+}
+
+impl BatchRun {
+    /// Whether the run ended on its completion promise.
+    pub fn completed(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Executes `ralph batch`.
+pub async fn execute(config_args: &[String], args: BatchArgs, use_colors: bool) -> Result<()> {
+    let workspace = std::env::current_dir().context("Failed to get current directory")?;
+    let files = args
+        .files
+        .iter()
+        .map(|file| {
+            file.canonicalize()
+                .with_context(|| format!("Prompt file not found: {}", file.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let worktrees = args.parallel > 1;
+    if worktrees {
+        ensure_gitignore(&workspace, ".worktrees")
+            .context("Failed to update .gitignore for worktrees")?;
+    }
+    let plan = Arc::new(BatchPlan {
+        id: Local::now().format("%Y%m%d-%H%M%S").to_string(),
+        workspace,
+        program: std::env::current_exe().context("Failed to locate the ralph executable")?,
+        config_args: config_args.to_vec(),
+        max_iterations: args.max_iterations,
+        backend: args.backend,
+        worktrees,
+        total: files.len(),
+    });
+    let log_dir = batch_dir(&plan.workspace, &plan.id);
+    fs::create_dir_all(&log_dir)
+        .with_context(|| format!("Failed to create {}", log_dir.display()))?;
+
+    let mut pending = files.into_iter().enumerate();
+    let mut running = JoinSet::new();
+    let mut runs = Vec::new();
+    loop {
+        while running.len() < args.parallel as usize
+            && let Some((i, file)) = pending.next()
+        {
+            let plan = Arc::clone(&plan);
+            running.spawn_blocking(move || run_one(&plan, i + 1, &file));
+        }
+        let Some(joined) = running.join_next().await else {
+            break;
+        };
+        let run = joined.context("Batch run panicked")??;
+        println!(
+            "[{}/{}] {} {} after {} iteration(s) (${:.2})",
+            run.index, plan.total, run.file, run.outcome, run.iterations, run.cost_usd
+        );
+        runs.push(run);
+    }
+    runs.sort_by_key(|run| run.index);
+
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&runs)?);
+    } else {
+        print_table(&runs, use_colors);
+        println!("Logs: {}", log_dir.display());
+    }
+
+    let failed = runs.iter().filter(|run| !run.completed()).count();
+    if failed > 0 {
+        bail!("{failed} of {} run(s) did not complete", runs.len());
+    }
+    Ok(())
+}
+
+/// Directory holding a batch's run logs.
+fn batch_dir(workspace: &Path, id: &str) -> PathBuf {
+    workspace.join(".ralph/batch").join(id)
+}
+
+/// Runs one prompt file to termination and reads back how it went.
+fn run_one(plan: &BatchPlan, index: usize, file: &Path) -> Result<BatchRun> {
+    let started = Utc::now();
+    let (dir, branch) = if plan.worktrees {
+        let loop_id = format!("batch-{}-{index}", plan.id);
+        let worktree = create_worktree(&plan.workspace, &loop_id, &WorktreeConfig::default())
+            .with_context(|| format!("Failed to create worktree for {}", file.display()))?;
+        (worktree.path, Some(worktree.branch))
+    } else {
+        (plan.workspace.clone(), None)
+    };
+
+    let name = file
+        .file_stem()
+        .map_or_else(|| "prompt".into(), |s| s.to_string_lossy());
+    let log_path = batch_dir(&plan.workspace, &plan.id).join(format!("{index}-{name}.log"));
+    let log = fs::File::create(&log_path)
+        .with_context(|| format!("Failed to create {}", log_path.display()))?;
+    println!(
+        "[{index}/{}] Starting {} in {}",
+        plan.total,
+        file.display(),
+        dir.display()
+    );
+
+    let status = Command::new(&plan.program)
+        .args(run_args(plan, file))
+        .current_dir(&dir)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .status()
+        .with_context(|| format!("Failed to start {}", plan.program.display()))?;
+
+    let history = LoopHistory::new(LoopContext::primary(dir.clone()).history_path());
+    let events: Vec<_> = history
+        .read_all()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|event| event.timestamp >= started)
+        .collect();
+    let (iterations, cost_usd, reason) = tally(&events);
+
+    Ok(BatchRun {
+        index,
+        file: file
+            .strip_prefix(&plan.workspace)
+            .unwrap_or(file)
+            .display()
+            .to_string(),
+        outcome: reason.unwrap_or_else(|| match status.code() {
+            Some(code) => format!("exit {code}"),
+            None => "killed".to_string(),
+        }),
+        exit_code: status.code(),
+        iterations,
+        cost_usd,
+        branch: branch.or_else(|| get_current_branch(&dir).ok()),
+        log: log_path.display().to_string(),
+    })
+}
+
+/// Arguments for a run's `ralph` child process.
+fn run_args(plan: &BatchPlan, file: &Path) -> Vec<OsString> {
+    let mut args: Vec<OsString> = Vec::new();
+    for source in &plan.config_args {
+        args.push("--config".into());
+        args.push(source.into());
+    }
+    args.extend(["run".into(), "--autonomous".into()]);
+    if !plan.worktrees {
+        // Wait for the primary slot rather than spawning into a worktree,
+        // so the run's history lands in this workspace
+        args.push("--exclusive".into());
+    }
+    args.push("--prompt-file".into());
+    args.push(file.into());
+    if let Some(max) = plan.max_iterations {
+        args.push("--max-iterations".into());
+        args.push(max.to_string().into());
+    }
+    if let Some(backend) = &plan.backend {
+        args.push("--backend".into());
+        args.push(backend.into());
+    }
+    args
+}
+
+/// Iterations, spend, and termination reason recorded by one run.
+fn tally(events: &[HistoryEvent]) -> (u32, f64, Option<String>) {
+    let mut iterations = 0;
+    let mut cost = 0.0;
+    let mut reason = None;
+    for event in events {
+        match &event.event_type {
+            HistoryEventType::IterationCompleted { iteration, .. } => {
+                iterations = iterations.max(*iteration);
+            }
+            HistoryEventType::CostRecorded(entry) => cost += entry.usage.cost_usd,
+            HistoryEventType::LoopCompleted { reason: r } => reason = Some(r.clone()),
+            HistoryEventType::LoopTerminated { signal } => {
+                reason = Some(format!("terminated ({signal})"));
+            }
+            _ => {}
+        }
+    }
+    (iterations, cost, reason)
+}
+
+fn print_table(runs: &[BatchRun], use_colors: bool) {
+    let (bold, dim, reset) = if use_colors {
+        (colors::BOLD, colors::DIM, colors::RESET)
+    } else {
+        ("", "", "")
+    };
+
+    println!(
+        "{bold}{:>3} {:<32} {:<20} {:>5} {:>10} BRANCH{reset}",
+        "#", "FILE", "OUTCOME", "ITER", "COST"
+    );
+    for run in runs {
+        let (color, end) = match (use_colors, run.completed()) {
+            (false, _) => ("", ""),
+            (true, true) => (colors::GREEN, colors::RESET),
+            (true, false) => (colors::RED, colors::RESET),
+        };
+        println!(
+            "{:>3} {:<32} {color}{:<20}{end} {:>5} {:>10} {}",
+            run.index,
+            truncate(&run.file, 32),
+            truncate(&run.outcome, 20),
+            run.iterations,
+            format!("${:.4}", run.cost_usd),
+            run.branch.as_deref().unwrap_or("-")
+        );
+    }
+    let completed = runs.iter().filter(|run| run.completed()).count();
+    let cost: f64 = runs.iter().map(|run| run.cost_usd).sum();
+    println!(
+        "{dim}{completed}/{} completed, {:>10} total{reset}",
+        runs.len(),
+        format!("${cost:.4}")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ralph_core::{CostEntry, Usage};
+
+    fn plan(worktrees: bool) -> BatchPlan {
+        BatchPlan {
+            id: "20260101-000000".to_string(),
+            workspace: PathBuf::from("/repo"),
+            program: PathBuf::from("ralph"),
+            config_args: vec!["ralph.yml".to_string(), "core.specs_dir=specs".to_string()],
+            max_iterations: Some(20),
+            backend: None,
+            worktrees,
+            total: 2,
+        }
+    }
+
+    #[test]
+    fn test_run_args_forward_config_and_overrides() {
+        let args = run_args(&plan(false), Path::new("/repo/tasks/a.md"));
+        let args: Vec<_> = args.iter().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(
+            args,
+            [
+                "--config",
+                "ralph.yml",
+                "--config",
+                "core.specs_dir=specs",
+                "run",
+                "--autonomous",
+                "--exclusive",
+                "--prompt-file",
+                "/repo/tasks/a.md",
+                "--max-iterations",
+                "20",
+            ]
+        );
+
+        // Worktree runs own their checkout; no need to wait for the slot
+        let args = run_args(&plan(true), Path::new("/repo/tasks/a.md"));
+        assert!(!args.iter().any(|a| a == "--exclusive"));
+    }
+
+    #[test]
+    fn test_tally_reads_iterations_cost_and_reason() {
+        let events = [
+            HistoryEventType::LoopStarted {
+                prompt: "a".to_string(),
+            },
+            HistoryEventType::IterationCompleted {
+                iteration: 1,
+                success: true,
+            },
+            HistoryEventType::CostRecorded(CostEntry {
+                iteration: 1,
+                hat: "ralph".to_string(),
+                topic: None,
+                usage: Usage::new(0.25, 100, 50),
+            }),
+            HistoryEventType::IterationCompleted {
+                iteration: 2,
+                success: false,
+            },
+            HistoryEventType::CostRecorded(CostEntry {
+                iteration: 2,
+                hat: "ralph".to_string(),
+                topic: None,
+                usage: Usage::new(0.5, 100, 50),
+            }),
+            HistoryEventType::LoopCompleted {
+                reason: "max_iterations".to_string(),
+            },
+        ]
+        .map(HistoryEvent::new);
+
+        let (iterations, cost, reason) = tally(&events);
+        assert_eq!(iterations, 2);
+        assert!((cost - 0.75).abs() < f64::EPSILON);
+        assert_eq!(reason.as_deref(), Some("max_iterations"));
+        assert_eq!(tally(&[]), (0, 0.0, None));
+    }
+}
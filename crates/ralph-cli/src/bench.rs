@@ -0,0 +1,422 @@
+//! CLI commands for the `ralph bench` namespace.
+//!
+//! Subcommands:
+//! - `backends`: Send a standard small prompt through each configured backend
+//!   several times and compare latency, token throughput, and cost, so
+//!   per-hat `backend:` choices can be made from measurements.
+
+use crate::display::{colors, truncate};
+use crate::{ConfigSource, OutputFormat};
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use ralph_adapters::{
+    CliBackend, PtyConfig, PtyExecutor, QuietStreamHandler, ResultRecorder, detect_backend,
+};
+use ralph_core::{RalphConfig, Usage};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Prompt sent to every backend: no tools, a short fixed answer.
+const BENCH_PROMPT: &str = "This is a latency benchmark. Do not read files, run commands, or use \
+any tools. Reply with the numbers one through twenty written as words, separated by commas, \
+and nothing else.";
+
+/// Benchmark backends.
+#[derive(Parser, Debug)]
+pub struct BenchArgs {
+    #[command(subcommand)]
+    pub command: BenchCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BenchCommands {
+    /// Time a standard prompt through each configured backend
+    Backends(BackendsArgs),
+}
+
+/// Arguments for `ralph bench backends`.
+#[derive(Parser, Debug)]
+pub struct BackendsArgs {
+    /// Runs per backend
+    #[arg(short = 'n', long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub runs: u32,
+
+    /// Benchmark these backends instead of the configured ones (repeatable)
+    #[arg(short = 'b', long = "backend")]
+    pub backends: Vec<String>,
+
+    /// Send this prompt instead of the standard one
+    #[arg(long)]
+    pub prompt: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+/// Execute a bench command.
+pub async fn execute(
+    config_sources: &[ConfigSource],
+    args: BenchArgs,
+    use_colors: bool,
+) -> Result<()> {
+    match args.command {
+        BenchCommands::Backends(args) => backends(config_sources, &args, use_colors).await,
+    }
+}
+
+/// A backend to benchmark and the hats that use it.
+struct Candidate {
+    name: String,
+    hats: Vec<String>,
+    backend: CliBackend,
+    timeout: Duration,
+}
+
+/// One timed run.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    latency: Duration,
+    success: bool,
+    usage: Option<Usage>,
+}
+
+/// Aggregated results for one backend.
+#[derive(Debug, Serialize)]
+struct BackendStats {
+    backend: String,
+    hats: Vec<String>,
+    runs: usize,
+    failures: usize,
+    /// Median latency of successful runs, in milliseconds.
+    p50_ms: Option<u64>,
+    /// Mean latency of successful runs, in milliseconds.
+    mean_ms: Option<u64>,
+    /// Slowest successful run, in milliseconds.
+    max_ms: Option<u64>,
+    /// Output tokens per second over successful runs that reported usage.
+    tokens_per_sec: Option<f64>,
+    /// Mean cost per successful run that reported usage.
+    cost_per_run_usd: Option<f64>,
+}
+
+async fn backends(
+    config_sources: &[ConfigSource],
+    args: &BackendsArgs,
+    use_colors: bool,
+) -> Result<()> {
+    let mut config = crate::load_config_with_overrides(config_sources)?;
+    if config.cli.backend == "auto" {
+        let priority = config.get_agent_priority();
+        config.cli.backend = detect_backend(&priority, |backend| {
+            config.adapter_settings(backend).enabled
+        })?;
+    }
+    let workspace = std::env::current_dir().context("Failed to get current directory")?;
+    let candidates = if args.backends.is_empty() {
+        configured(&config)?
+    } else {
+        named(&config, &args.backends)?
+    };
+    let credentials = ralph_core::credentials::resolve(&config, &workspace)?;
+
+    // Run in a scratch directory so a backend that ignores the prompt can't touch the repo
+    let scratch = workspace.join(".ralph").join("bench");
+    fs::create_dir_all(&scratch)
+        .with_context(|| format!("Failed to create {}", scratch.display()))?;
+    let prompt = args.prompt.as_deref().unwrap_or(BENCH_PROMPT);
+
+    let mut results = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let backend = candidate.backend.clone().with_env_vars(&credentials);
+        let mut samples = Vec::with_capacity(args.runs as usize);
+        for run in 1..=args.runs {
+            eprint!("{} run {run}/{}... ", candidate.name, args.runs);
+            let sample = run_once(&backend, &scratch, prompt, candidate.timeout).await;
+            if sample.success {
+                eprintln!("{:.1}s", sample.latency.as_secs_f64());
+            } else {
+                eprintln!("failed after {:.1}s", sample.latency.as_secs_f64());
+            }
+            samples.push(sample);
+        }
+        results.push(summarize(&candidate.name, candidate.hats, &samples));
+    }
+
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        eprintln!();
+        print_table(&results, use_colors);
+    }
+    Ok(())
+}
+
+/// The global backend plus every distinct hat-level backend.
+fn configured(config: &RalphConfig) -> Result<Vec<Candidate>> {
+    let global = CliBackend::from_config(&config.cli)?;
+    let mut candidates = vec![Candidate {
+        name: config.cli.backend.clone(),
+        hats: Vec::new(),
+        timeout: timeout(config, &config.cli.backend),
+        backend: global,
+    }];
+
+    let mut hats: Vec<_> = config.hats.iter().collect();
+    hats.sort_by_key(|(id, _)| id.as_str());
+    for (id, hat) in hats {
+        let index = match &hat.backend {
+            None => 0,
+            Some(hat_backend) => {
+                let backend = CliBackend::from_hat_backend(hat_backend)
+                    .with_context(|| format!("Invalid backend for hat '{id}'"))?;
+                match candidates
+                    .iter()
+                    .position(|c| same_command(&c.backend, &backend))
+                {
+                    Some(index) => index,
+                    None => {
+                        let name = hat_backend.adapter_name();
+                        candidates.push(Candidate {
+                            timeout: timeout(config, &name),
+                            name,
+                            hats: Vec::new(),
+                            backend,
+                        });
+                        candidates.len() - 1
+                    }
+                }
+            }
+        };
+        candidates[index].hats.push(id.clone());
+    }
+    Ok(candidates)
+}
+
+/// Backends named with `--backend`, whether or not the config uses them.
+fn named(config: &RalphConfig, names: &[String]) -> Result<Vec<Candidate>> {
+    names
+        .iter()
+        .map(|name| {
+            let backend = if *name == config.cli.backend {
+                CliBackend::from_config(&config.cli)?
+            } else {
+                CliBackend::from_name(name).with_context(|| format!("Unknown backend '{name}'"))?
+            };
+            Ok(Candidate {
+                name: name.clone(),
+                hats: Vec::new(),
+                timeout: timeout(config, name),
+                backend,
+            })
+        })
+        .collect()
+}
+
+fn same_command(a: &CliBackend, b: &CliBackend) -> bool {
+    a.command == b.command && a.args == b.args
+}
+
+fn timeout(config: &RalphConfig, backend: &str) -> Duration {
+    Duration::from_secs(config.adapter_settings(backend).timeout)
+}
+
+/// Runs `prompt` once through `backend`, stopping it after `timeout`.
+async fn run_once(backend: &CliBackend, dir: &Path, prompt: &str, timeout: Duration) -> Sample {
+    let pty_config = PtyConfig {
+        interactive: false,
+        idle_timeout_secs: 0,
+        workspace_root: dir.to_path_buf(),
+        ..PtyConfig::from_env()
+    };
+    let executor = PtyExecutor::new(backend.clone(), pty_config);
+    let mut recorder = ResultRecorder::new(QuietStreamHandler);
+    let (stop_tx, stop_rx) = tokio::sync::watch::channel(false);
+
+    let started = Instant::now();
+    let success = {
+        let run = executor.run_observe_streaming(prompt, stop_rx, &mut recorder);
+        tokio::pin!(run);
+        tokio::select! {
+            result = &mut run => result.is_ok_and(|result| result.success),
+            () = tokio::time::sleep(timeout) => {
+                let _ = stop_tx.send(true);
+                let _ = run.await;
+                false
+            }
+        }
+    };
+    let latency = started.elapsed();
+
+    Sample {
+        latency,
+        success,
+        usage: recorder.into_result().map(|session| session.usage()),
+    }
+}
+
+fn summarize(backend: &str, hats: Vec<String>, samples: &[Sample]) -> BackendStats {
+    let ok: Vec<&Sample> = samples.iter().filter(|s| s.success).collect();
+    let mut latencies: Vec<Duration> = ok.iter().map(|s| s.latency).collect();
+    latencies.sort();
+    let millis = |d: Duration| u64::try_from(d.as_millis()).unwrap_or(u64::MAX);
+
+    let mean = (!latencies.is_empty()).then(|| {
+        let total: Duration = latencies.iter().sum();
+        millis(total) / latencies.len() as u64
+    });
+
+    // Throughput and cost only count runs whose backend reported usage
+    let reported: Vec<(Duration, Usage)> = ok
+        .iter()
+        .filter_map(|s| s.usage.map(|usage| (s.latency, usage)))
+        .collect();
+    let reported_time: f64 = reported.iter().map(|(d, _)| d.as_secs_f64()).sum();
+    let output_tokens: u64 = reported.iter().map(|(_, u)| u.output_tokens).sum();
+    let cost: f64 = reported.iter().map(|(_, u)| u.cost_usd).sum();
+
+    BackendStats {
+        backend: backend.to_string(),
+        hats,
+        runs: samples.len(),
+        failures: samples.len() - ok.len(),
+        p50_ms: latencies.get(latencies.len() / 2).copied().map(millis),
+        mean_ms: mean,
+        max_ms: latencies.last().copied().map(millis),
+        tokens_per_sec: (output_tokens > 0 && reported_time > 0.0)
+            .then(|| output_tokens as f64 / reported_time),
+        cost_per_run_usd: (!reported.is_empty()).then(|| cost / reported.len() as f64),
+    }
+}
+
+fn print_table(results: &[BackendStats], use_colors: bool) {
+    let (bold, dim, reset) = if use_colors {
+        (colors::BOLD, colors::DIM, colors::RESET)
+    } else {
+        ("", "", "")
+    };
+    let seconds =
+        |ms: Option<u64>| ms.map_or("-".to_string(), |ms| format!("{:.1}s", ms as f64 / 1000.0));
+
+    println!(
+        "{bold}{:<12} {:<24} {:>5} {:>8} {:>8} {:>8} {:>8} {:>10}{reset}",
+        "BACKEND", "HATS", "OK", "P50", "MEAN", "MAX", "TOK/S", "COST/RUN"
+    );
+    for stats in results {
+        let hats = if stats.hats.is_empty() {
+            "-".to_string()
+        } else {
+            stats.hats.join(", ")
+        };
+        println!(
+            "{:<12} {:<24} {:>5} {:>8} {:>8} {:>8} {:>8} {:>10}",
+            truncate(&stats.backend, 12),
+            truncate(&hats, 24),
+            format!("{}/{}", stats.runs - stats.failures, stats.runs),
+            seconds(stats.p50_ms),
+            seconds(stats.mean_ms),
+            seconds(stats.max_ms),
+            stats
+                .tokens_per_sec
+                .map_or("-".to_string(), |rate| format!("{rate:.1}")),
+            stats
+                .cost_per_run_usd
+                .map_or("-".to_string(), |cost| format!("${cost:.4}")),
+        );
+    }
+    if results.iter().any(|stats| stats.tokens_per_sec.is_none()) {
+        println!("{dim}- means the backend didn't report usage or no run succeeded{reset}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(ms: u64, success: bool, usage: Option<Usage>) -> Sample {
+        Sample {
+            latency: Duration::from_millis(ms),
+            success,
+            usage,
+        }
+    }
+
+    #[test]
+    fn test_summarize_ignores_failed_runs() {
+        let samples = [
+            sample(1000, true, Some(Usage::new(0.01, 100, 50))),
+            sample(3000, true, Some(Usage::new(0.03, 100, 150))),
+            sample(9000, false, None),
+            sample(2000, true, None),
+        ];
+        let stats = summarize("claude", vec!["builder".to_string()], &samples);
+
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.p50_ms, Some(2000));
+        assert_eq!(stats.mean_ms, Some(2000));
+        assert_eq!(stats.max_ms, Some(3000));
+        // 200 output tokens over the 4s of runs that reported usage
+        assert_eq!(stats.tokens_per_sec, Some(50.0));
+        assert!((stats.cost_per_run_usd.unwrap() - 0.02).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_summarize_without_usage_or_successes() {
+        let stats = summarize("kiro", Vec::new(), &[sample(500, false, None)]);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.p50_ms, None);
+        assert_eq!(stats.mean_ms, None);
+        assert_eq!(stats.tokens_per_sec, None);
+        assert_eq!(stats.cost_per_run_usd, None);
+    }
+
+    #[test]
+    fn test_configured_groups_hats_by_backend() {
+        let config: RalphConfig = serde_yaml::from_str(
+            r#"
+cli:
+  backend: claude
+hats:
+  planner:
+    name: Planner
+    triggers: ["task.start"]
+  builder:
+    name: Builder
+    triggers: ["build.task"]
+    backend: gemini
+  reviewer:
+    name: Reviewer
+    triggers: ["review.request"]
+    backend: gemini
+  tester:
+    name: Tester
+    triggers: ["test.request"]
+    backend: claude
+"#,
+        )
+        .unwrap();
+
+        let candidates = configured(&config).unwrap();
+        let summary: Vec<(&str, Vec<&str>)> = candidates
+            .iter()
+            .map(|c| (c.name.as_str(), c.hats.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("claude", vec!["planner", "tester"]),
+                ("gemini", vec!["builder", "reviewer"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_named_rejects_unknown_backend() {
+        let config = RalphConfig::default();
+        assert!(named(&config, &["gemini".to_string()]).is_ok());
+        assert!(named(&config, &["no-such-backend".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,854 @@
+//! Event bus bridges to external message brokers (`bridges:` in ralph.yml).
+//!
+//! Each bridge mirrors published events to a NATS subject or Redis channel
+//! named `<prefix>.<topic>`, wrapped in a JSON envelope, and optionally feeds
+//! messages from the broker back in as events. Incoming messages go through
+//! the events file, like `ralph emit`, so they're validated and routed the
+//! same way as anything an agent emits.
+//!
+//! Bridges run on their own threads with plain blocking sockets: the NATS
+//! and Redis pub/sub protocols are small line-based exchanges, and a broker
+//! that is down or slow never blocks the loop. Events published while the
+//! broker is unreachable are dropped with a warning. When the bridge is
+//! dropped at the end of the loop, queued events get a moment to flush.
+
+use ralph_core::child_loop::SPAWN_TOPIC;
+use ralph_core::lifecycle::is_lifecycle_topic;
+use ralph_core::{BridgeConfig, BrokerEndpoint, BrokerKind, EventWriter};
+use ralph_proto::{Event, Topic};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Events queued for a broker before new ones are dropped.
+const QUEUE_CAPACITY: usize = 1024;
+/// Time allowed to connect to, or hear back from, a broker.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long dropping a bridge waits for queued events to be sent.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+/// Longest wait between reconnection attempts for incoming messages.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Injected events remembered so they aren't echoed back to the broker.
+const RECENT_INJECTED: usize = 64;
+/// Largest message body or protocol line accepted from a broker.
+const MAX_PAYLOAD: usize = 1024 * 1024;
+/// Most elements in a Redis array reply; pub/sub replies have three or four.
+const MAX_RESP_ELEMENTS: usize = 16;
+/// Deepest nesting of Redis array replies.
+const MAX_RESP_DEPTH: usize = 2;
+
+/// Resolves the events file incoming messages are appended to.
+pub type EventsPath = Arc<dyn Fn() -> PathBuf + Send + Sync>;
+
+/// The message sent to the broker for each event.
+#[derive(Debug, Serialize)]
+struct Envelope<'a> {
+    topic: &'a str,
+    payload: &'a str,
+    source: Option<String>,
+    target: Option<String>,
+    /// Identifies this run, so it can ignore its own messages.
+    origin: &'a str,
+    ts: String,
+}
+
+/// The fields read back from an incoming envelope.
+#[derive(Debug, Deserialize)]
+struct IncomingEnvelope {
+    payload: serde_json::Value,
+    #[serde(default)]
+    origin: Option<String>,
+}
+
+/// A running bridge. Dropping it stops the bridge.
+pub struct BrokerBridge {
+    sender: Arc<Mutex<Option<SyncSender<(String, String)>>>>,
+    publish: Arc<Vec<Topic>>,
+    prefix: String,
+    origin: Arc<str>,
+    injected: Arc<Mutex<VecDeque<(String, String)>>>,
+    flushed: Receiver<()>,
+    stop: Arc<AtomicBool>,
+    inbound: Arc<Mutex<Option<TcpStream>>>,
+}
+
+impl BrokerBridge {
+    /// Starts a bridge for a validated `bridges:` entry.
+    ///
+    /// `origin` identifies this run; `events_path` is where incoming events
+    /// are written.
+    pub fn start(config: &BridgeConfig, origin: &str, events_path: EventsPath) -> Option<Self> {
+        let endpoint = match config.endpoint() {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                warn!(url = %config.url, "Skipping broker bridge: {}", e);
+                return None;
+            }
+        };
+        let (sender, queue) = mpsc::sync_channel(QUEUE_CAPACITY);
+        let (flushed_tx, flushed) = mpsc::channel();
+        let bridge = Self {
+            sender: Arc::new(Mutex::new(Some(sender))),
+            publish: Arc::new(config.publish.iter().map(Topic::new).collect()),
+            prefix: config.prefix.clone(),
+            origin: Arc::from(origin),
+            injected: Arc::new(Mutex::new(VecDeque::new())),
+            flushed,
+            stop: Arc::new(AtomicBool::new(false)),
+            inbound: Arc::new(Mutex::new(None)),
+        };
+
+        let publisher = endpoint.clone();
+        thread::spawn(move || {
+            publish_loop(&publisher, &queue);
+            let _ = flushed_tx.send(());
+        });
+
+        if !config.subscribe.is_empty() {
+            let subscriber = Subscriber {
+                endpoint,
+                prefix: config.prefix.clone(),
+                patterns: config.subscribe.iter().map(Topic::new).collect(),
+                origin: Arc::clone(&bridge.origin),
+                injected: Arc::clone(&bridge.injected),
+                events_path,
+                stop: Arc::clone(&bridge.stop),
+                current: Arc::clone(&bridge.inbound),
+            };
+            thread::spawn(move || subscriber.run());
+        }
+
+        info!(url = %config.url, prefix = %config.prefix, "Bridging events to broker");
+        Some(bridge)
+    }
+
+    /// Returns an observer closure to wire into the event bus.
+    pub fn observer(&self) -> impl Fn(&Event) + Send + 'static {
+        let sender = Arc::clone(&self.sender);
+        let publish = Arc::clone(&self.publish);
+        let prefix = self.prefix.clone();
+        let origin = Arc::clone(&self.origin);
+        let injected = Arc::clone(&self.injected);
+        let full_warned = AtomicBool::new(false);
+
+        move |event: &Event| {
+            let topic = event.topic.as_str();
+            if !publish.iter().any(|pattern| pattern.matches_str(topic))
+                || take_injected(&injected, topic, &event.payload)
+            {
+                return;
+            }
+            let envelope = Envelope {
+                topic,
+                payload: &event.payload,
+                source: event.source.as_ref().map(ToString::to_string),
+                target: event.target.as_ref().map(ToString::to_string),
+                origin: &origin,
+                ts: chrono::Utc::now().to_rfc3339(),
+            };
+            let Ok(body) = serde_json::to_string(&envelope) else {
+                return;
+            };
+            let sender = sender
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            let Some(sender) = sender.as_ref() else {
+                return;
+            };
+            match sender.try_send((format!("{prefix}.{topic}"), body)) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    if !full_warned.swap(true, Ordering::Relaxed) {
+                        warn!("Broker bridge is backed up; dropping events");
+                    }
+                }
+                Err(TrySendError::Disconnected(_)) => {}
+            }
+        }
+    }
+}
+
+impl Drop for BrokerBridge {
+    fn drop(&mut self) {
+        // Closing the queue lets the publisher drain what's left and exit
+        self.sender
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take();
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(stream) = self
+            .inbound
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take()
+        {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        if self.flushed.recv_timeout(FLUSH_TIMEOUT).is_err() {
+            warn!("Broker bridge didn't flush in time; some events may not have been sent");
+        }
+    }
+}
+
+/// Sends queued events until the queue is closed, reconnecting as needed.
+fn publish_loop(endpoint: &BrokerEndpoint, queue: &Receiver<(String, String)>) {
+    let mut connection: Option<Connection> = None;
+    let mut reachable = true;
+    for (subject, body) in queue {
+        // One retry on a fresh connection covers a broker that dropped us
+        for _ in 0..2 {
+            if connection.is_none() {
+                match Connection::open(endpoint) {
+                    Ok(opened) => {
+                        reachable = true;
+                        connection = Some(opened);
+                    }
+                    Err(e) => {
+                        if reachable {
+                            warn!(host = %endpoint.host, error = %e, "Can't reach broker; dropping events until it's back");
+                        }
+                        reachable = false;
+                        break;
+                    }
+                }
+            }
+            let Some(open) = connection.as_mut() else {
+                break;
+            };
+            match open.publish(&subject, body.as_bytes()) {
+                Ok(()) => break,
+                Err(e) => {
+                    debug!(subject, error = %e, "Broker publish failed; reconnecting");
+                    connection = None;
+                }
+            }
+        }
+    }
+}
+
+/// Receives messages from the broker and injects them as events.
+struct Subscriber {
+    endpoint: BrokerEndpoint,
+    prefix: String,
+    patterns: Vec<Topic>,
+    origin: Arc<str>,
+    injected: Arc<Mutex<VecDeque<(String, String)>>>,
+    events_path: EventsPath,
+    stop: Arc<AtomicBool>,
+    current: Arc<Mutex<Option<TcpStream>>>,
+}
+
+impl Subscriber {
+    fn run(self) {
+        let mut backoff = Duration::from_secs(1);
+        while !self.stop.load(Ordering::Relaxed) {
+            match self.subscribe() {
+                Ok(mut connection) => {
+                    backoff = Duration::from_secs(1);
+                    loop {
+                        match connection.next_message() {
+                            Ok((subject, body)) => self.handle(&subject, &body),
+                            Err(e) => {
+                                if !self.stop.load(Ordering::Relaxed) {
+                                    warn!(host = %self.endpoint.host, error = %e, "Lost broker subscription; reconnecting");
+                                }
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!(host = %self.endpoint.host, error = %e, "Can't subscribe to broker; retrying in {}s", backoff.as_secs());
+                }
+            }
+            thread::sleep(backoff);
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    fn subscribe(&self) -> io::Result<Connection> {
+        let mut connection = Connection::open(&self.endpoint)?;
+        connection.subscribe(&self.prefix)?;
+        // Incoming messages arrive whenever they're sent; only the drop
+        // handler interrupts the wait, by shutting the socket down.
+        connection.stream().set_read_timeout(None)?;
+        *self
+            .current
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) =
+            connection.stream().try_clone().ok();
+        if self.stop.load(Ordering::Relaxed) {
+            return Err(io::Error::other("bridge stopped"));
+        }
+        Ok(connection)
+    }
+
+    fn handle(&self, subject: &str, body: &[u8]) {
+        let Some((topic, payload)) =
+            incoming_event(subject, body, &self.prefix, &self.patterns, &self.origin)
+        else {
+            return;
+        };
+        remember_injected(&self.injected, &topic, &payload);
+
+        let record = serde_json::json!({
+            "topic": topic,
+            "payload": payload,
+            "ts": chrono::Utc::now().to_rfc3339(),
+        });
+        let path = (self.events_path)();
+        match EventWriter::new(&path).append(&record) {
+            Ok(()) => debug!(topic, "Injected event from broker"),
+            Err(e) => {
+                warn!(topic, error = %e, "Failed to write broker event to {}", path.display())
+            }
+        }
+    }
+}
+
+/// Turns a broker message into the `(topic, payload)` to inject, if it should be.
+///
+/// Skips messages outside the prefix, topics not subscribed to, lifecycle
+/// topics, and this run's own messages. Child loop requests and `human.*`
+/// topics are never taken from a broker: they spawn processes or speak for
+/// the user.
+fn incoming_event(
+    subject: &str,
+    body: &[u8],
+    prefix: &str,
+    patterns: &[Topic],
+    origin: &str,
+) -> Option<(String, String)> {
+    let topic = subject.strip_prefix(prefix)?.strip_prefix('.')?;
+    let topic = match Topic::parse(topic) {
+        Ok(topic) => topic,
+        Err(e) => {
+            debug!(subject, error = %e, "Ignoring broker message with invalid topic");
+            return None;
+        }
+    };
+    if is_lifecycle_topic(topic.as_str())
+        || topic.as_str() == SPAWN_TOPIC
+        || topic.as_str().starts_with("human.")
+        || !patterns
+            .iter()
+            .any(|pattern| pattern.matches_str(topic.as_str()))
+    {
+        return None;
+    }
+
+    let payload = match serde_json::from_slice::<IncomingEnvelope>(body) {
+        Ok(envelope) if envelope.origin.as_deref() == Some(origin) => return None,
+        Ok(envelope) => match envelope.payload {
+            serde_json::Value::String(payload) => payload,
+            other => other.to_string(),
+        },
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    };
+    Some((topic.as_str().to_string(), payload))
+}
+
+fn remember_injected(injected: &Mutex<VecDeque<(String, String)>>, topic: &str, payload: &str) {
+    let mut injected = injected
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if injected.len() == RECENT_INJECTED {
+        injected.pop_front();
+    }
+    injected.push_back((topic.to_string(), payload.to_string()));
+}
+
+/// Returns true (and forgets it) if the event was injected from the broker.
+fn take_injected(injected: &Mutex<VecDeque<(String, String)>>, topic: &str, payload: &str) -> bool {
+    let mut injected = injected
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    match injected
+        .iter()
+        .position(|(t, p)| t == topic && p == payload)
+    {
+        Some(index) => {
+            injected.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+/// A connection to either broker.
+struct Connection {
+    kind: BrokerKind,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn open(endpoint: &BrokerEndpoint) -> io::Result<Self> {
+        let address = (endpoint.host.as_str(), endpoint.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::other(format!("can't resolve {}", endpoint.host)))?;
+        let stream = TcpStream::connect_timeout(&address, IO_TIMEOUT)?;
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut connection = Self {
+            kind: endpoint.kind,
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        };
+        match endpoint.kind {
+            BrokerKind::Nats => connection.nats_handshake(endpoint)?,
+            BrokerKind::Redis => connection.redis_auth(endpoint)?,
+        }
+        Ok(connection)
+    }
+
+    fn stream(&self) -> &TcpStream {
+        &self.writer
+    }
+
+    fn publish(&mut self, subject: &str, body: &[u8]) -> io::Result<()> {
+        match self.kind {
+            BrokerKind::Nats => {
+                let mut frame = format!("PUB {subject} {}\r\n", body.len()).into_bytes();
+                frame.extend_from_slice(body);
+                // The PING round trip confirms the broker took the message
+                frame.extend_from_slice(b"\r\nPING\r\n");
+                self.writer.write_all(&frame)?;
+                self.nats_await_pong()
+            }
+            BrokerKind::Redis => {
+                self.writer
+                    .write_all(&resp_command(&[b"PUBLISH", subject.as_bytes(), body]))?;
+                match read_resp(&mut self.reader)? {
+                    Resp::Error(e) => Err(io::Error::other(e)),
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+
+    /// Subscribes to every topic under `prefix`; patterns are applied locally.
+    fn subscribe(&mut self, prefix: &str) -> io::Result<()> {
+        match self.kind {
+            BrokerKind::Nats => {
+                self.writer
+                    .write_all(format!("SUB {prefix}.> 1\r\nPING\r\n").as_bytes())?;
+                self.nats_await_pong()
+            }
+            BrokerKind::Redis => {
+                let pattern = format!("{prefix}.*");
+                self.writer
+                    .write_all(&resp_command(&[b"PSUBSCRIBE", pattern.as_bytes()]))?;
+                match read_resp(&mut self.reader)? {
+                    Resp::Error(e) => Err(io::Error::other(e)),
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+
+    /// Blocks until the next message arrives, as `(subject, body)`.
+    fn next_message(&mut self) -> io::Result<(String, Vec<u8>)> {
+        loop {
+            match self.kind {
+                BrokerKind::Nats => {
+                    let line = self.nats_line()?;
+                    if let Some(message) = self.nats_message(&line)? {
+                        return Ok(message);
+                    }
+                }
+                BrokerKind::Redis => {
+                    // [pmessage, pattern, channel, payload]
+                    if let Resp::Array(Some(mut parts)) = read_resp(&mut self.reader)?
+                        && parts.len() == 4
+                        && let (Resp::Bulk(Some(payload)), Resp::Bulk(Some(channel))) =
+                            (parts.remove(3), parts.remove(2))
+                    {
+                        return Ok((String::from_utf8_lossy(&channel).into_owned(), payload));
+                    }
+                }
+            }
+        }
+    }
+
+    fn nats_handshake(&mut self, endpoint: &BrokerEndpoint) -> io::Result<()> {
+        let info = self.nats_line()?;
+        if !info.starts_with("INFO") {
+            return Err(io::Error::other(format!("unexpected greeting: {info}")));
+        }
+        let mut options = serde_json::json!({
+            "verbose": false,
+            "pedantic": false,
+            "name": "ralph",
+            "lang": "rust",
+            "version": env!("CARGO_PKG_VERSION"),
+            "protocol": 0,
+        });
+        match (&endpoint.user, &endpoint.password) {
+            (Some(user), password) => {
+                options["user"] = user.as_str().into();
+                options["pass"] = password.as_deref().unwrap_or_default().into();
+            }
+            (None, Some(token)) => options["auth_token"] = token.as_str().into(),
+            (None, None) => {}
+        }
+        self.writer
+            .write_all(format!("CONNECT {options}\r\nPING\r\n").as_bytes())?;
+        self.nats_await_pong()
+    }
+
+    fn nats_await_pong(&mut self) -> io::Result<()> {
+        loop {
+            let line = self.nats_line()?;
+            if line == "PONG" {
+                return Ok(());
+            }
+            // A message can arrive ahead of the PONG on a subscribed connection
+            self.nats_message(&line)?;
+        }
+    }
+
+    /// Handles a control line; returns the message if it starts one.
+    fn nats_message(&mut self, line: &str) -> io::Result<Option<(String, Vec<u8>)>> {
+        if line == "PING" {
+            self.writer.write_all(b"PONG\r\n")?;
+            return Ok(None);
+        }
+        if let Some(error) = line.strip_prefix("-ERR") {
+            return Err(io::Error::other(
+                error.trim().trim_matches('\'').to_string(),
+            ));
+        }
+        let Some((subject, len)) = parse_nats_msg(line) else {
+            return Ok(None);
+        };
+        if len > MAX_PAYLOAD {
+            return Err(too_large(len));
+        }
+        let mut body = vec![0; len + 2];
+        self.reader.read_exact(&mut body)?;
+        body.truncate(len);
+        Ok(Some((subject.to_string(), body)))
+    }
+
+    fn nats_line(&mut self) -> io::Result<String> {
+        read_line(&mut self.reader)
+    }
+
+    fn redis_auth(&mut self, endpoint: &BrokerEndpoint) -> io::Result<()> {
+        let Some(password) = &endpoint.password else {
+            return Ok(());
+        };
+        let command = match &endpoint.user {
+            Some(user) => resp_command(&[b"AUTH", user.as_bytes(), password.as_bytes()]),
+            None => resp_command(&[b"AUTH", password.as_bytes()]),
+        };
+        self.writer.write_all(&command)?;
+        match read_resp(&mut self.reader)? {
+            Resp::Error(e) => Err(io::Error::other(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parses `MSG <subject> <sid> [reply-to] <#bytes>` into subject and length.
+fn parse_nats_msg(line: &str) -> Option<(&str, usize)> {
+    let mut parts = line.split_whitespace();
+    if parts.next()? != "MSG" {
+        return None;
+    }
+    let subject = parts.next()?;
+    let len = parts.last()?.parse().ok()?;
+    Some((subject, len))
+}
+
+/// A Redis protocol (RESP2) reply.
+#[derive(Debug, PartialEq, Eq)]
+enum Resp {
+    Simple(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Option<Vec<Resp>>),
+}
+
+/// Encodes a command as a RESP array of bulk strings.
+fn resp_command(args: &[&[u8]]) -> Vec<u8> {
+    let mut out = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        out.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        out.extend_from_slice(arg);
+        out.extend_from_slice(b"\r\n");
+    }
+    out
+}
+
+/// Reads a line of at most [`MAX_PAYLOAD`] bytes, without the line ending.
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    let limit = MAX_PAYLOAD as u64 + 2;
+    let read = reader.take(limit).read_line(&mut line)?;
+    if read == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    if !line.ends_with('\n') && read as u64 == limit {
+        return Err(too_large(read));
+    }
+    Ok(line.trim_end().to_string())
+}
+
+fn too_large(len: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("broker sent {len} bytes, more than the {MAX_PAYLOAD} allowed"),
+    )
+}
+
+fn read_resp(reader: &mut impl BufRead) -> io::Result<Resp> {
+    read_resp_nested(reader, 0)
+}
+
+fn read_resp_nested(reader: &mut impl BufRead, depth: usize) -> io::Result<Resp> {
+    let line = read_line(reader)?;
+    let line = line.as_str();
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("bad reply: {line}"));
+    let (kind, rest) = line.split_at_checked(1).ok_or_else(invalid)?;
+    let length = || rest.parse::<i64>().map_err(|_| invalid());
+    Ok(match kind {
+        "+" => Resp::Simple(rest.to_string()),
+        "-" => Resp::Error(rest.to_string()),
+        ":" => Resp::Integer(length()?),
+        "$" => match usize::try_from(length()?) {
+            Ok(len) if len > MAX_PAYLOAD => return Err(too_large(len)),
+            Ok(len) => {
+                let mut data = vec![0; len + 2];
+                reader.read_exact(&mut data)?;
+                data.truncate(len);
+                Resp::Bulk(Some(data))
+            }
+            Err(_) => Resp::Bulk(None),
+        },
+        "*" => match usize::try_from(length()?) {
+            Ok(len) if len > MAX_RESP_ELEMENTS || depth >= MAX_RESP_DEPTH => {
+                return Err(invalid());
+            }
+            Ok(len) => Resp::Array(Some(
+                (0..len)
+                    .map(|_| read_resp_nested(reader, depth + 1))
+                    .collect::<io::Result<_>>()?,
+            )),
+            Err(_) => Resp::Array(None),
+        },
+        _ => return Err(invalid()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn topics(patterns: &[&str]) -> Vec<Topic> {
+        patterns.iter().map(|p| Topic::new(*p)).collect()
+    }
+
+    #[test]
+    fn test_incoming_event_maps_subject_to_topic() {
+        let patterns = topics(&["review.*"]);
+        assert_eq!(
+            incoming_event(
+                "ralph.review.request",
+                b"look at #42",
+                "ralph",
+                &patterns,
+                "me"
+            ),
+            Some(("review.request".to_string(), "look at #42".to_string()))
+        );
+        // Outside the prefix, not subscribed, or a lifecycle topic
+        assert_eq!(
+            incoming_event("other.review.request", b"", "ralph", &patterns, "me"),
+            None
+        );
+        assert_eq!(
+            incoming_event("ralphy.review.request", b"", "ralph", &patterns, "me"),
+            None
+        );
+        assert_eq!(
+            incoming_event("ralph.build.done", b"", "ralph", &patterns, "me"),
+            None
+        );
+        assert_eq!(
+            incoming_event(
+                "ralph.ralph.hat_completed",
+                b"",
+                "ralph",
+                &topics(&["*"]),
+                "me"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_incoming_event_rejects_spawn_and_human_topics() {
+        let patterns = topics(&["*"]);
+        assert!(incoming_event("ralph.human.said", b"{}", "ralph", &patterns, "me").is_none());
+        assert!(incoming_event("ralph.humans.said", b"{}", "ralph", &patterns, "me").is_some());
+        for subject in [
+            "ralph.ralph.spawn_loop",
+            "ralph.human.response",
+            "ralph.human.guidance",
+        ] {
+            assert_eq!(
+                incoming_event(subject, b"{}", "ralph", &patterns, "me"),
+                None,
+                "{subject}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_incoming_envelopes_unwrap_payload_and_skip_own_origin() {
+        let patterns = topics(&["*"]);
+        let theirs = br#"{"topic":"build.done","payload":"ok","origin":"ralph:other"}"#;
+        assert_eq!(
+            incoming_event("ralph.build.done", theirs, "ralph", &patterns, "ralph:me"),
+            Some(("build.done".to_string(), "ok".to_string()))
+        );
+        let object = br#"{"payload":{"pr":42}}"#;
+        assert_eq!(
+            incoming_event("ralph.build.done", object, "ralph", &patterns, "ralph:me"),
+            Some(("build.done".to_string(), r#"{"pr":42}"#.to_string()))
+        );
+        let ours = br#"{"topic":"build.done","payload":"ok","origin":"ralph:me"}"#;
+        assert_eq!(
+            incoming_event("ralph.build.done", ours, "ralph", &patterns, "ralph:me"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_injected_events_are_not_echoed() {
+        let injected = Mutex::new(VecDeque::new());
+        remember_injected(&injected, "review.request", "a");
+        assert!(!take_injected(&injected, "review.request", "b"));
+        assert!(take_injected(&injected, "review.request", "a"));
+        assert!(!take_injected(&injected, "review.request", "a"));
+    }
+
+    #[test]
+    fn test_resp_round_trip() {
+        assert_eq!(
+            resp_command(&[b"PUBLISH", b"ralph.x", b"hi"]),
+            b"*3\r\n$7\r\nPUBLISH\r\n$7\r\nralph.x\r\n$2\r\nhi\r\n"
+        );
+        let mut reply: &[u8] =
+            b"*4\r\n$8\r\npmessage\r\n$7\r\nralph.*\r\n$7\r\nralph.x\r\n$2\r\nhi\r\n:3\r\n-ERR no\r\n";
+        assert_eq!(
+            read_resp(&mut reply).unwrap(),
+            Resp::Array(Some(vec![
+                Resp::Bulk(Some(b"pmessage".to_vec())),
+                Resp::Bulk(Some(b"ralph.*".to_vec())),
+                Resp::Bulk(Some(b"ralph.x".to_vec())),
+                Resp::Bulk(Some(b"hi".to_vec())),
+            ]))
+        );
+        assert_eq!(read_resp(&mut reply).unwrap(), Resp::Integer(3));
+        assert_eq!(
+            read_resp(&mut reply).unwrap(),
+            Resp::Error("ERR no".to_string())
+        );
+    }
+
+    #[test]
+    fn test_read_resp_rejects_oversized_replies() {
+        let header = format!("${}\r\n", MAX_PAYLOAD + 1);
+        let err = read_resp(&mut header.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut wide: &[u8] = b"*1000000\r\n";
+        assert_eq!(
+            read_resp(&mut wide).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let mut deep: &[u8] = b"*1\r\n*1\r\n*1\r\n:1\r\n";
+        assert_eq!(
+            read_resp(&mut deep).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let line = format!("+{}\r\n", "x".repeat(MAX_PAYLOAD + 1));
+        assert_eq!(
+            read_resp(&mut line.as_bytes()).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_parse_nats_msg() {
+        assert_eq!(parse_nats_msg("MSG ralph.a 1 5"), Some(("ralph.a", 5)));
+        assert_eq!(
+            parse_nats_msg("MSG ralph.a 1 inbox.x 12"),
+            Some(("ralph.a", 12))
+        );
+        assert_eq!(parse_nats_msg("+OK"), None);
+    }
+
+    /// Accepts one NATS client, acknowledges everything, and returns what it sent.
+    fn fake_nats_server() -> (u16, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"INFO {}\r\n").unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut received = String::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                if line == "PING\r\n" {
+                    stream.write_all(b"PONG\r\n").unwrap();
+                } else {
+                    received.push_str(&line);
+                }
+                line.clear();
+            }
+            received
+        });
+        (port, server)
+    }
+
+    #[test]
+    fn test_events_are_published_to_nats() {
+        let (port, server) = fake_nats_server();
+        let config = BridgeConfig {
+            url: format!("nats://127.0.0.1:{port}"),
+            prefix: "ralph".to_string(),
+            publish: vec!["build.*".to_string()],
+            subscribe: Vec::new(),
+        };
+        let bridge = BrokerBridge::start(&config, "ralph:test", Arc::new(PathBuf::new)).unwrap();
+        let observer = bridge.observer();
+        observer(&Event::new("build.done", "all green"));
+        observer(&Event::new("review.done", "not mirrored"));
+        drop(bridge);
+
+        let received = server.join().unwrap();
+        assert!(received.starts_with("CONNECT {"));
+        let publishes: Vec<&str> = received.lines().filter(|l| l.starts_with("PUB ")).collect();
+        assert_eq!(publishes.len(), 1);
+        assert!(publishes[0].starts_with("PUB ralph.build.done "));
+        assert!(received.contains(r#""payload":"all green""#));
+        assert!(received.contains(r#""origin":"ralph:test""#));
+    }
+}
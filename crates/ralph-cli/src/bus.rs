@@ -0,0 +1,229 @@
+//! CLI commands for the `ralph bus` namespace.
+//!
+//! Subcommands:
+//! - `dump`: Show the event bus's registered hats, their subscriptions and
+//!   queue depths, and the events being handled. Reads the snapshot a loop
+//!   writes to `.ralph/bus.json` each iteration, or asks a running
+//!   `ralph serve` for a session's snapshot.
+
+use crate::OutputFormat;
+use crate::display::{colors, truncate};
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use ralph_core::{LoopContext, LoopRegistry};
+use ralph_proto::BusSnapshot;
+use std::path::{Path, PathBuf};
+
+/// Inspect the event bus.
+#[derive(Parser, Debug)]
+pub struct BusArgs {
+    #[command(subcommand)]
+    pub command: BusCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BusCommands {
+    /// Show registered hats, queue depths, and in-flight events
+    Dump(DumpArgs),
+}
+
+/// Arguments for `ralph bus dump`.
+#[derive(Parser, Debug)]
+pub struct DumpArgs {
+    /// Loop ID from `ralph loops list`, or a session ID with --server (default: the primary loop)
+    pub id: Option<String>,
+
+    /// Ask a running `ralph serve` at this URL instead of reading the snapshot file
+    #[arg(long, requires = "id", conflicts_with = "file")]
+    pub server: Option<String>,
+
+    /// Bearer token for --server (default: $RALPH_API_TOKEN)
+    #[arg(long, requires = "server")]
+    pub token: Option<String>,
+
+    /// Path to a bus snapshot file
+    #[arg(long, conflicts_with = "id")]
+    pub file: Option<PathBuf>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+/// Execute a bus command.
+pub async fn execute(args: BusArgs, use_colors: bool) -> Result<()> {
+    match args.command {
+        BusCommands::Dump(args) => dump(args, use_colors).await,
+    }
+}
+
+async fn dump(args: DumpArgs, use_colors: bool) -> Result<()> {
+    let snapshot = match (&args.server, &args.id) {
+        (Some(server), Some(id)) => {
+            let token = crate::serve::resolve_token(args.token.clone())?;
+            fetch(server, id, &token).await?
+        }
+        _ => {
+            let path = match (&args.file, &args.id) {
+                (Some(file), _) => file.clone(),
+                (None, id) => {
+                    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+                    snapshot_path(&cwd, id.as_deref())?
+                }
+            };
+            read(&path)?
+        }
+    };
+
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&snapshot)?);
+    } else {
+        print_snapshot(&snapshot, use_colors);
+    }
+    Ok(())
+}
+
+/// Where the loop `id` (or the primary loop) writes its snapshot.
+fn snapshot_path(workspace: &Path, id: Option<&str>) -> Result<PathBuf> {
+    let Some(id) = id else {
+        return Ok(LoopContext::primary(workspace.to_path_buf()).bus_snapshot_path());
+    };
+    let entry = LoopRegistry::new(workspace)
+        .get(id)
+        .context("Failed to read the loop registry")?
+        .with_context(|| format!("Loop '{id}' not found"))?;
+    Ok(crate::serve::loop_workspace(&entry).join(".ralph/bus.json"))
+}
+
+fn read(path: &Path) -> Result<BusSnapshot> {
+    if !path.exists() {
+        bail!(
+            "No event bus snapshot at {}. Loops write one each iteration; is a loop running here?",
+            path.display()
+        );
+    }
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("Invalid bus snapshot {}", path.display()))
+}
+
+async fn fetch(server: &str, id: &str, token: &str) -> Result<BusSnapshot> {
+    let url = format!("{}/sessions/{id}/bus", server.trim_end_matches('/'));
+    let response = reqwest::Client::new()
+        .get(&url)
+        .bearer_auth(token)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {url}"))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        let message = body["message"].as_str().unwrap_or("request failed");
+        bail!("{url} returned {status}: {message}");
+    }
+    response
+        .json()
+        .await
+        .with_context(|| format!("Invalid bus snapshot from {url}"))
+}
+
+fn print_snapshot(snapshot: &BusSnapshot, use_colors: bool) {
+    let (bold, dim, reset) = if use_colors {
+        (colors::BOLD, colors::DIM, colors::RESET)
+    } else {
+        ("", "", "")
+    };
+
+    println!(
+        "{bold}{:<20} {:>7} {:>9}  SUBSCRIPTIONS{reset}",
+        "HAT", "PENDING", "IN FLIGHT"
+    );
+    for hat in &snapshot.hats {
+        let subscriptions: Vec<&str> = hat.subscriptions.iter().map(|t| t.as_str()).collect();
+        println!(
+            "{:<20} {:>7} {:>9}  {}",
+            truncate(hat.id.as_str(), 20),
+            hat.pending,
+            snapshot.in_flight.get(&hat.id).map_or(0, Vec::len),
+            subscriptions.join(", ")
+        );
+    }
+    if snapshot.human_pending > 0 {
+        println!(
+            "{dim}human.* events pending: {}{reset}",
+            snapshot.human_pending
+        );
+    }
+
+    if !snapshot.in_flight.is_empty() {
+        println!();
+        println!("{bold}In flight{reset}");
+        for (hat, events) in &snapshot.in_flight {
+            for event in events {
+                let payload = event.payload.lines().next().unwrap_or_default();
+                println!(
+                    "  {:<20} {:<24} {dim}{}{reset}",
+                    truncate(hat.as_str(), 20),
+                    truncate(event.topic.as_str(), 24),
+                    truncate(payload, 60)
+                );
+            }
+        }
+    }
+
+    if !snapshot.topic_renames.is_empty() || !snapshot.hat_aliases.is_empty() {
+        println!();
+        println!("{bold}Renames{reset}");
+        for (old, new) in &snapshot.topic_renames {
+            println!("  topic {old} → {new}");
+        }
+        for (alias, hat) in &snapshot.hat_aliases {
+            println!("  hat {alias} → {hat}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ralph_core::LoopEntry;
+    use ralph_proto::{Event, EventBus, Hat};
+
+    #[test]
+    fn test_snapshot_path_resolves_loops() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        assert_eq!(
+            snapshot_path(root, None).unwrap(),
+            root.join(".ralph/bus.json")
+        );
+
+        let worktree = root.join(".worktrees/fix-bug");
+        let entry = LoopEntry::with_workspace(
+            "fix the bug",
+            Some(worktree.display().to_string()),
+            root.display().to_string(),
+        );
+        let id = LoopRegistry::new(root).register(entry).unwrap();
+        assert_eq!(
+            snapshot_path(root, Some(&id)).unwrap(),
+            worktree.join(".ralph/bus.json")
+        );
+        assert!(snapshot_path(root, Some("nope")).is_err());
+    }
+
+    #[test]
+    fn test_read_round_trips_snapshot() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("bus.json");
+        assert!(read(&path).is_err());
+
+        let mut bus = EventBus::new();
+        bus.register(Hat::new("builder", "Builder").subscribe("build.*"));
+        bus.publish(Event::new("build.task", "Add login"));
+        bus.take_pending(&"builder".into());
+        std::fs::write(&path, serde_json::to_string(&bus.snapshot()).unwrap()).unwrap();
+
+        assert_eq!(read(&path).unwrap(), bus.snapshot());
+    }
+}
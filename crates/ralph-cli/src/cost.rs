@@ -0,0 +1,152 @@
+//! CLI command for `ralph cost`.
+//!
+//! Shows what a loop spent, broken down by hat or by triggering topic, from
+//! the cost entries recorded in the loop history.
+
+use crate::OutputFormat;
+use crate::display::{colors, truncate};
+use anyhow::{Context, Result};
+use clap::Parser;
+use ralph_core::{CostLedger, LoopContext, LoopHistory, Usage};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Show cost and token spend for a loop.
+#[derive(Parser, Debug)]
+pub struct CostArgs {
+    /// Break spend down by hat (default)
+    #[arg(long, conflicts_with = "by_topic")]
+    pub by_hat: bool,
+
+    /// Break spend down by the topic that triggered each iteration
+    #[arg(long)]
+    pub by_topic: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+
+    /// Path to the loop history file (default: .ralph/history.jsonl)
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+}
+
+/// Execute the cost command.
+pub fn execute(args: &CostArgs, use_colors: bool) -> Result<()> {
+    let path = match &args.file {
+        Some(path) => path.clone(),
+        None => {
+            let cwd = std::env::current_dir().context("Failed to get current directory")?;
+            LoopContext::primary(cwd).history_path()
+        }
+    };
+    let ledger = LoopHistory::new(&path)
+        .cost_ledger()
+        .with_context(|| format!("Failed to read loop history at {}", path.display()))?;
+
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&ledger)?);
+        return Ok(());
+    }
+
+    if ledger.by_hat.is_empty() {
+        if use_colors {
+            println!("{}No cost recorded yet.{}", colors::DIM, colors::RESET);
+        } else {
+            println!("No cost recorded yet.");
+        }
+        return Ok(());
+    }
+
+    let (label, rows) = breakdown(&ledger, args.by_topic);
+    print_table(label, rows, &ledger.total, use_colors);
+    Ok(())
+}
+
+/// Returns the column label and the rows for the requested breakdown.
+fn breakdown(ledger: &CostLedger, by_topic: bool) -> (&'static str, &BTreeMap<String, Usage>) {
+    if by_topic {
+        ("TOPIC", &ledger.by_topic)
+    } else {
+        ("HAT", &ledger.by_hat)
+    }
+}
+
+/// Rows sorted by cost, most expensive first.
+fn sorted_rows(rows: &BTreeMap<String, Usage>) -> Vec<(&str, Usage)> {
+    let mut sorted: Vec<_> = rows.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+    sorted.sort_by(|a, b| b.1.cost_usd.total_cmp(&a.1.cost_usd));
+    sorted
+}
+
+fn share(part: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        part / total * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn print_table(label: &str, rows: &BTreeMap<String, Usage>, total: &Usage, use_colors: bool) {
+    let (bold, dim, reset) = if use_colors {
+        (colors::BOLD, colors::DIM, colors::RESET)
+    } else {
+        ("", "", "")
+    };
+
+    println!(
+        "{bold}{label:<28} {:>10} {:>12} {:>12} {:>7}{reset}",
+        "COST", "INPUT", "OUTPUT", "SHARE"
+    );
+    for (name, usage) in sorted_rows(rows) {
+        println!(
+            "{:<28} {:>10} {:>12} {:>12} {:>6.1}%",
+            truncate(name, 28),
+            format!("${:.4}", usage.cost_usd),
+            usage.input_tokens,
+            usage.output_tokens,
+            share(usage.cost_usd, total.cost_usd)
+        );
+    }
+    println!(
+        "{dim}{:<28} {:>10} {:>12} {:>12}{reset}",
+        "TOTAL",
+        format!("${:.4}", total.cost_usd),
+        total.input_tokens,
+        total.output_tokens
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ralph_core::CostEntry;
+
+    #[test]
+    fn test_rows_sorted_by_cost_descending() {
+        let ledger = CostLedger::from_entries(&[
+            CostEntry {
+                iteration: 1,
+                hat: "planner".to_string(),
+                topic: Some("work.start".to_string()),
+                usage: Usage::new(0.10, 10, 5),
+            },
+            CostEntry {
+                iteration: 2,
+                hat: "builder".to_string(),
+                topic: Some("build.task".to_string()),
+                usage: Usage::new(0.90, 90, 45),
+            },
+        ]);
+
+        let (label, rows) = breakdown(&ledger, false);
+        assert_eq!(label, "HAT");
+        let names: Vec<_> = sorted_rows(rows).into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, ["builder", "planner"]);
+
+        let (label, rows) = breakdown(&ledger, true);
+        assert_eq!(label, "TOPIC");
+        assert!(rows.contains_key("build.task"));
+        assert!((share(0.9, ledger.total.cost_usd) - 90.0).abs() < 1e-9);
+    }
+}
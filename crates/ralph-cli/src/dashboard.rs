@@ -0,0 +1,673 @@
+//! Browser dashboard for supervising a running loop.
+//!
+//! Mirrors the TUI over HTTP: live status, the event stream (server-sent
+//! events), iteration history with cumulative cost, and pause/resume/stop
+//! controls. State tracking is always compiled; the HTTP server itself needs
+//! the `dashboard` feature.
+//!
+//! The dashboard answers only to this machine unless `RALPH_API_TOKEN` is
+//! set; see [`request_allowed`].
+//!
+//! Controls reuse the existing signal files (see `ralph_core::loop_control`)
+//! so the loop runner stays the single owner of loop state:
+//! - pause  → creates `.ralph/pause-requested`
+//! - resume → removes `.ralph/pause-requested`
+//! - stop   → creates `.ralph/stop-requested`
+
+use ralph_core::loop_control;
+use ralph_proto::Event;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::broadcast;
+
+/// Maximum number of events kept for clients that connect mid-run.
+const EVENT_BACKLOG: usize = 500;
+
+/// Capacity of the live update channel; slow clients skip ahead when lagging.
+const UPDATE_CHANNEL_CAPACITY: usize = 256;
+
+/// An event as shown in the dashboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardEvent {
+    pub topic: String,
+    pub payload: String,
+    pub source: Option<String>,
+    pub target: Option<String>,
+    pub ts: String,
+}
+
+impl From<&Event> for DashboardEvent {
+    fn from(event: &Event) -> Self {
+        Self {
+            topic: event.topic.to_string(),
+            payload: event.payload.clone(),
+            source: event.source.as_ref().map(ToString::to_string),
+            target: event.target.as_ref().map(ToString::to_string),
+            ts: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// One completed iteration.
+#[derive(Debug, Clone, Serialize)]
+pub struct IterationRecord {
+    pub iteration: u32,
+    pub hat: String,
+    pub backend: String,
+    pub success: bool,
+    pub duration_secs: f64,
+    pub cumulative_cost: f64,
+}
+
+/// Snapshot of the loop for the status panel.
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardStatus {
+    pub iteration: u32,
+    pub max_iterations: u32,
+    pub hat: Option<String>,
+    pub cumulative_cost: f64,
+    pub elapsed_secs: u64,
+    pub paused: bool,
+    pub termination: Option<String>,
+}
+
+/// Live update pushed to connected clients.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DashboardUpdate {
+    Event(DashboardEvent),
+    Iteration(IterationRecord),
+    Status(DashboardStatus),
+}
+
+struct DashboardState {
+    started: Instant,
+    iteration: u32,
+    max_iterations: u32,
+    hat: Option<String>,
+    cumulative_cost: f64,
+    termination: Option<String>,
+    history: Vec<IterationRecord>,
+    events: VecDeque<DashboardEvent>,
+}
+
+/// Shared dashboard handle. Cheap to clone; all clones see the same state.
+#[derive(Clone)]
+pub struct Dashboard {
+    state: Arc<Mutex<DashboardState>>,
+    updates: broadcast::Sender<DashboardUpdate>,
+    workspace_root: PathBuf,
+}
+
+impl Dashboard {
+    /// Creates a dashboard for the loop rooted at `workspace_root`.
+    pub fn new(workspace_root: impl Into<PathBuf>, max_iterations: u32) -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        Self {
+            state: Arc::new(Mutex::new(DashboardState {
+                started: Instant::now(),
+                iteration: 0,
+                max_iterations,
+                hat: None,
+                cumulative_cost: 0.0,
+                termination: None,
+                history: Vec::new(),
+                events: VecDeque::with_capacity(EVENT_BACKLOG),
+            })),
+            updates,
+            workspace_root: workspace_root.into(),
+        }
+    }
+
+    /// Returns an observer closure to wire into the event bus.
+    pub fn observer(&self) -> impl Fn(&Event) + Send + 'static {
+        let dashboard = self.clone();
+        move |event: &Event| dashboard.record_event(DashboardEvent::from(event))
+    }
+
+    fn record_event(&self, event: DashboardEvent) {
+        if let Ok(mut state) = self.state.lock() {
+            if state.events.len() == EVENT_BACKLOG {
+                state.events.pop_front();
+            }
+            state.events.push_back(event.clone());
+        }
+        // No receivers is fine — nobody has the page open.
+        let _ = self.updates.send(DashboardUpdate::Event(event));
+    }
+
+    /// Marks the start of an iteration.
+    pub fn start_iteration(&self, iteration: u32, hat: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            state.iteration = iteration;
+            state.hat = Some(hat.to_string());
+        }
+        self.broadcast_status();
+    }
+
+    /// Records a completed iteration.
+    pub fn finish_iteration(&self, record: IterationRecord) {
+        if let Ok(mut state) = self.state.lock() {
+            state.cumulative_cost = record.cumulative_cost;
+            state.history.push(record.clone());
+        }
+        let _ = self.updates.send(DashboardUpdate::Iteration(record));
+    }
+
+    /// Records why the loop ended.
+    pub fn set_terminated(&self, reason: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            state.termination = Some(reason.to_string());
+            state.hat = None;
+        }
+        self.broadcast_status();
+    }
+
+    fn broadcast_status(&self) {
+        let _ = self.updates.send(DashboardUpdate::Status(self.status()));
+    }
+
+    /// Current status snapshot.
+    pub fn status(&self) -> DashboardStatus {
+        let paused = self.is_paused();
+        let Ok(state) = self.state.lock() else {
+            return DashboardStatus {
+                iteration: 0,
+                max_iterations: 0,
+                hat: None,
+                cumulative_cost: 0.0,
+                elapsed_secs: 0,
+                paused,
+                termination: None,
+            };
+        };
+        DashboardStatus {
+            iteration: state.iteration,
+            max_iterations: state.max_iterations,
+            hat: state.hat.clone(),
+            cumulative_cost: state.cumulative_cost,
+            elapsed_secs: state.started.elapsed().as_secs(),
+            paused,
+            termination: state.termination.clone(),
+        }
+    }
+
+    /// Completed iterations, oldest first.
+    pub fn history(&self) -> Vec<IterationRecord> {
+        self.state
+            .lock()
+            .map(|state| state.history.clone())
+            .unwrap_or_default()
+    }
+
+    /// The most recent events, oldest first.
+    pub fn recent_events(&self) -> Vec<DashboardEvent> {
+        self.state
+            .lock()
+            .map(|state| state.events.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Subscribes to live updates.
+    pub fn subscribe(&self) -> broadcast::Receiver<DashboardUpdate> {
+        self.updates.subscribe()
+    }
+
+    fn signal_path(&self, name: &str) -> PathBuf {
+        self.workspace_root.join(".ralph").join(name)
+    }
+
+    /// Returns true while a pause is requested.
+    pub fn is_paused(&self) -> bool {
+        loop_control::pause_requested(&self.signal_path(loop_control::PAUSE_REQUESTED_FILE))
+    }
+
+    /// Asks the loop to hold before its next iteration.
+    pub fn pause(&self) -> std::io::Result<()> {
+        loop_control::request_pause(&self.signal_path(loop_control::PAUSE_REQUESTED_FILE))?;
+        self.broadcast_status();
+        Ok(())
+    }
+
+    /// Lets a paused loop continue.
+    pub fn resume(&self) -> std::io::Result<()> {
+        loop_control::request_resume(&self.signal_path(loop_control::PAUSE_REQUESTED_FILE))?;
+        self.broadcast_status();
+        Ok(())
+    }
+
+    /// Asks the loop to stop at the next termination check.
+    pub fn stop(&self) -> std::io::Result<()> {
+        loop_control::request_stop(&self.signal_path(loop_control::STOP_REQUESTED_FILE))
+    }
+
+    /// Starts serving the dashboard on `bind` in a background task.
+    ///
+    /// Requests must carry `RALPH_API_TOKEN` as a bearer token when it's set.
+    /// Without it, only loopback addresses may be bound.
+    ///
+    /// Returns the bound address (useful when `bind` uses port 0).
+    #[cfg(feature = "dashboard")]
+    pub async fn serve(&self, bind: &str) -> anyhow::Result<std::net::SocketAddr> {
+        let token = std::env::var(crate::serve::TOKEN_ENV)
+            .ok()
+            .filter(|token| !token.trim().is_empty());
+        server::serve(self.clone(), bind, token).await
+    }
+
+    /// Starts serving the dashboard on `bind` in a background task.
+    #[cfg(not(feature = "dashboard"))]
+    #[allow(clippy::unused_async)]
+    pub async fn serve(&self, bind: &str) -> anyhow::Result<std::net::SocketAddr> {
+        anyhow::bail!(
+            "cannot serve dashboard on {bind}: ralph was built without the `dashboard` feature \
+             (rebuild with `cargo install ralph-cli --features dashboard`)"
+        )
+    }
+}
+
+/// Returns true if a dashboard request may proceed.
+///
+/// With a token configured, a request carrying it as
+/// `Authorization: Bearer` is allowed from anywhere. Otherwise the request
+/// must come from a loopback `peer`, and `Host` must be `localhost` or a
+/// loopback address, not a DNS name that could be rebound to this machine. The
+/// controls change loop state, so a page on another site must not be able
+/// to POST to them either: browsers send `Origin` with cross-origin
+/// requests, and it must name the dashboard itself. Requests without
+/// `Origin` come from scripts such as `curl` and are allowed.
+pub(crate) fn request_allowed(
+    token: Option<&str>,
+    authorization: Option<&str>,
+    peer: std::net::IpAddr,
+    host: Option<&str>,
+    origin: Option<&str>,
+) -> bool {
+    if token.is_some_and(|token| crate::serve::bearer_matches(authorization, token)) {
+        return true;
+    }
+    let Some(host) = host else {
+        return false;
+    };
+    if !peer.is_loopback() || !is_loopback_host(host) {
+        return false;
+    }
+    origin.is_none_or(|origin| origin.strip_prefix("http://") == Some(host))
+}
+
+/// Returns true if a `Host` header value names this machine's loopback
+/// interface.
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _port)| name),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+#[cfg(feature = "dashboard")]
+mod server {
+    use super::{Dashboard, DashboardUpdate, request_allowed};
+    use axum::Router;
+    use axum::extract::{ConnectInfo, Request, State};
+    use axum::http::{HeaderMap, StatusCode, header};
+    use axum::middleware::{self, Next};
+    use axum::response::sse::{Event as SseEvent, KeepAlive, Sse};
+    use axum::response::{Html, IntoResponse, Json, Response};
+    use axum::routing::{get, post};
+    use futures::stream::{self, Stream};
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::sync::broadcast::error::RecvError;
+    use tracing::{debug, warn};
+
+    const INDEX_HTML: &str = include_str!("dashboard.html");
+
+    pub(super) async fn serve(
+        dashboard: Dashboard,
+        bind: &str,
+        token: Option<String>,
+    ) -> anyhow::Result<SocketAddr> {
+        let listener = tokio::net::TcpListener::bind(bind).await?;
+        let addr = listener.local_addr()?;
+        if token.is_none() && !addr.ip().is_loopback() {
+            anyhow::bail!(
+                "refusing to serve the dashboard on non-loopback address {addr} without a token \
+                 (set {} or bind to 127.0.0.1)",
+                crate::serve::TOKEN_ENV
+            );
+        }
+        let app = Router::new()
+            .route("/", get(index))
+            .route("/api/status", get(status))
+            .route("/api/history", get(history))
+            .route("/api/events", get(events))
+            .route("/api/stream", get(stream))
+            .route("/api/pause", post(pause))
+            .route("/api/resume", post(resume))
+            .route("/api/stop", post(stop))
+            .layer(middleware::from_fn_with_state(
+                token.map(Arc::<str>::from),
+                require_access,
+            ))
+            .with_state(dashboard);
+
+        tokio::spawn(async move {
+            let app = app.into_make_service_with_connect_info::<SocketAddr>();
+            if let Err(e) = axum::serve(listener, app).await {
+                warn!(error = %e, "Dashboard server stopped");
+            }
+        });
+        debug!(%addr, "Dashboard listening");
+        Ok(addr)
+    }
+
+    async fn index() -> Html<&'static str> {
+        Html(INDEX_HTML)
+    }
+
+    async fn status(State(dashboard): State<Dashboard>) -> impl IntoResponse {
+        Json(dashboard.status())
+    }
+
+    async fn history(State(dashboard): State<Dashboard>) -> impl IntoResponse {
+        Json(dashboard.history())
+    }
+
+    async fn events(State(dashboard): State<Dashboard>) -> impl IntoResponse {
+        Json(dashboard.recent_events())
+    }
+
+    async fn stream(
+        State(dashboard): State<Dashboard>,
+    ) -> Sse<impl Stream<Item = Result<SseEvent, Infallible>>> {
+        let updates = stream::unfold(dashboard.subscribe(), |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(update) => return Some((Ok(to_sse(&update)), rx)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!(skipped, "Dashboard client lagged, skipping updates");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        Sse::new(updates).keep_alive(KeepAlive::default())
+    }
+
+    fn to_sse(update: &DashboardUpdate) -> SseEvent {
+        let name = match update {
+            DashboardUpdate::Event(_) => "event",
+            DashboardUpdate::Iteration(_) => "iteration",
+            DashboardUpdate::Status(_) => "status",
+        };
+        SseEvent::default()
+            .event(name)
+            .data(serde_json::to_string(update).unwrap_or_default())
+    }
+
+    async fn require_access(
+        State(token): State<Option<Arc<str>>>,
+        ConnectInfo(peer): ConnectInfo<SocketAddr>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        let headers = request.headers();
+        let value = |headers: &HeaderMap, name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let authorization = value(headers, header::AUTHORIZATION);
+        let host = value(headers, header::HOST);
+        let origin = value(headers, header::ORIGIN);
+        if request_allowed(
+            token.as_deref(),
+            authorization.as_deref(),
+            peer.ip(),
+            host.as_deref(),
+            origin.as_deref(),
+        ) {
+            next.run(request).await
+        } else {
+            warn!(%peer, ?host, ?origin, "Rejected dashboard request");
+            (
+                StatusCode::FORBIDDEN,
+                "The dashboard only accepts requests from this machine or with its token",
+            )
+                .into_response()
+        }
+    }
+
+    fn control_result(result: std::io::Result<()>) -> impl IntoResponse {
+        match result {
+            Ok(()) => StatusCode::NO_CONTENT.into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+
+    async fn pause(State(dashboard): State<Dashboard>) -> impl IntoResponse {
+        control_result(dashboard.pause())
+    }
+
+    async fn resume(State(dashboard): State<Dashboard>) -> impl IntoResponse {
+        control_result(dashboard.resume())
+    }
+
+    async fn stop(State(dashboard): State<Dashboard>) -> impl IntoResponse {
+        control_result(dashboard.stop())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(iteration: u32, cost: f64) -> IterationRecord {
+        IterationRecord {
+            iteration,
+            hat: "builder".to_string(),
+            backend: "claude".to_string(),
+            success: true,
+            duration_secs: 1.5,
+            cumulative_cost: cost,
+        }
+    }
+
+    #[test]
+    fn test_observer_records_events_and_broadcasts() {
+        let temp = TempDir::new().unwrap();
+        let dashboard = Dashboard::new(temp.path(), 10);
+        let mut rx = dashboard.subscribe();
+
+        let observer = dashboard.observer();
+        observer(&Event::new("build.done", "tests pass"));
+
+        let events = dashboard.recent_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].topic, "build.done");
+        assert!(
+            matches!(rx.try_recv(), Ok(DashboardUpdate::Event(e)) if e.payload == "tests pass")
+        );
+    }
+
+    #[test]
+    fn test_event_backlog_is_bounded() {
+        let temp = TempDir::new().unwrap();
+        let dashboard = Dashboard::new(temp.path(), 10);
+        let observer = dashboard.observer();
+        for i in 0..EVENT_BACKLOG + 5 {
+            observer(&Event::new("tick", i.to_string()));
+        }
+
+        let events = dashboard.recent_events();
+        assert_eq!(events.len(), EVENT_BACKLOG);
+        assert_eq!(events[0].payload, "5");
+    }
+
+    #[test]
+    fn test_iterations_update_status_and_history() {
+        let temp = TempDir::new().unwrap();
+        let dashboard = Dashboard::new(temp.path(), 10);
+
+        dashboard.start_iteration(1, "Builder");
+        dashboard.finish_iteration(record(1, 0.25));
+        dashboard.start_iteration(2, "Reviewer");
+
+        let status = dashboard.status();
+        assert_eq!(status.iteration, 2);
+        assert_eq!(status.hat.as_deref(), Some("Reviewer"));
+        assert!((status.cumulative_cost - 0.25).abs() < f64::EPSILON);
+        assert_eq!(dashboard.history().len(), 1);
+
+        dashboard.set_terminated("CompletionPromise");
+        let status = dashboard.status();
+        assert_eq!(status.termination.as_deref(), Some("CompletionPromise"));
+        assert!(status.hat.is_none());
+    }
+
+    #[test]
+    fn test_controls_write_signal_files() {
+        let temp = TempDir::new().unwrap();
+        let dashboard = Dashboard::new(temp.path(), 10);
+
+        dashboard.pause().unwrap();
+        assert!(temp.path().join(".ralph/pause-requested").exists());
+        assert!(dashboard.status().paused);
+
+        dashboard.resume().unwrap();
+        assert!(!dashboard.is_paused());
+        // Resuming twice is harmless.
+        dashboard.resume().unwrap();
+
+        dashboard.stop().unwrap();
+        assert!(temp.path().join(".ralph/stop-requested").exists());
+    }
+
+    #[test]
+    fn test_requests_reject_other_origins() {
+        let local = std::net::IpAddr::from([127, 0, 0, 1]);
+        let allowed = |host, origin| request_allowed(None, None, local, host, origin);
+        // The dashboard's own page, by loopback address or localhost
+        assert!(allowed(
+            Some("127.0.0.1:7070"),
+            Some("http://127.0.0.1:7070")
+        ));
+        assert!(allowed(
+            Some("localhost:7070"),
+            Some("http://localhost:7070")
+        ));
+        assert!(allowed(Some("[::1]:7070"), Some("http://[::1]:7070")));
+        // Scripts send no Origin
+        assert!(allowed(Some("127.0.0.1:7070"), None));
+
+        // Another site posting to the dashboard
+        assert!(!allowed(
+            Some("127.0.0.1:7070"),
+            Some("https://example.com")
+        ));
+        assert!(!allowed(Some("127.0.0.1:7070"), Some("null")));
+        // DNS rebinding: the page's own origin, but under an attacker's name
+        assert!(!allowed(
+            Some("rebind.example.com:7070"),
+            Some("http://rebind.example.com:7070")
+        ));
+        // Another machine reaching a `0.0.0.0` bind, by address or spoofing Host
+        assert!(!allowed(Some("192.168.1.20:7070"), None));
+        assert!(!allowed(Some("[fe80::1]:7070"), None));
+        let remote = std::net::IpAddr::from([192, 168, 1, 30]);
+        assert!(!request_allowed(
+            None,
+            None,
+            remote,
+            Some("127.0.0.1:7070"),
+            None
+        ));
+        assert!(!allowed(None, None));
+    }
+
+    #[test]
+    fn test_requests_with_token_need_not_be_local() {
+        let token = Some("s3cret");
+        let remote = std::net::IpAddr::from([192, 168, 1, 30]);
+        let host = Some("192.168.1.20:7070");
+        assert!(request_allowed(
+            token,
+            Some("Bearer s3cret"),
+            remote,
+            host,
+            None
+        ));
+        assert!(!request_allowed(
+            token,
+            Some("Bearer wrong"),
+            remote,
+            host,
+            None
+        ));
+        assert!(!request_allowed(token, None, remote, host, None));
+        // The local page still works without presenting the token
+        let local = std::net::IpAddr::from([127, 0, 0, 1]);
+        assert!(request_allowed(
+            token,
+            None,
+            local,
+            Some("127.0.0.1:7070"),
+            None
+        ));
+    }
+
+    #[cfg(feature = "dashboard")]
+    #[tokio::test]
+    async fn test_serve_refuses_non_loopback_bind_without_token() {
+        let temp = TempDir::new().unwrap();
+        let dashboard = Dashboard::new(temp.path(), 10);
+        let err = server::serve(dashboard.clone(), "0.0.0.0:0", None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("non-loopback"));
+
+        let addr = server::serve(dashboard, "127.0.0.1:0", None).await.unwrap();
+        assert!(addr.ip().is_loopback());
+    }
+
+    #[cfg(feature = "dashboard")]
+    #[tokio::test]
+    async fn test_read_routes_check_host() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let temp = TempDir::new().unwrap();
+        let addr = server::serve(Dashboard::new(temp.path(), 10), "127.0.0.1:0", None)
+            .await
+            .unwrap();
+        let get_status = |host: String| async move {
+            let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            let request =
+                format!("GET /api/status HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\r\n");
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+
+        assert!(
+            get_status(addr.to_string())
+                .await
+                .starts_with("HTTP/1.1 200")
+        );
+        assert!(
+            get_status("rebind.example.com".to_string())
+                .await
+                .starts_with("HTTP/1.1 403")
+        );
+    }
+}
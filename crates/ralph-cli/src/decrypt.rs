@@ -0,0 +1,63 @@
+//! CLI command for `ralph decrypt`.
+//!
+//! Restores a session artifact sealed by the `encryption` config (an
+//! iteration's prompt or output, an events journal, a forensics bundle) so
+//! other tools can read it. Ralph's own readers open sealed files directly.
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use ralph_core::encryption::{self, ArtifactKey, EncryptionError};
+use ralph_core::{EncryptionConfig, RalphConfig, credentials};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Decrypt a sealed session artifact.
+#[derive(Parser, Debug)]
+pub struct DecryptArgs {
+    /// Sealed file to decrypt
+    pub path: PathBuf,
+
+    /// Write the plaintext to a file instead of stdout
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+/// Execute the decrypt command.
+pub fn execute(args: &DecryptArgs) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let key = artifact_key(&cwd)?;
+    let plaintext = encryption::read_artifact(&args.path, key.as_ref())?;
+    match &args.output {
+        Some(output) => fs::write(output, plaintext)
+            .with_context(|| format!("Failed to write {}", output.display())),
+        None => std::io::stdout()
+            .write_all(&plaintext)
+            .context("Failed to write to stdout"),
+    }
+}
+
+/// Resolves the key for opening sealed artifacts the way the loop does: from
+/// the `credentials` entry named by `encryption.key_env`, then from the
+/// environment. Works whether or not encryption is still on; `None` if the
+/// key isn't set anywhere.
+pub(crate) fn artifact_key(workspace: &Path) -> Result<Option<ArtifactKey>> {
+    let mut config = ["ralph.yml", "ralph.yaml"]
+        .iter()
+        .map(|name| workspace.join(name))
+        .filter(|path| path.exists())
+        .find_map(|path| RalphConfig::from_file(&path).ok())
+        .unwrap_or_default();
+    // Only the key's own credential is fetched, not every backend secret
+    let key_env = config.encryption.key_env.clone();
+    config.credentials.retain(|name, _| *name == key_env);
+    let credentials = credentials::resolve(&config, workspace)?;
+    let encryption = EncryptionConfig {
+        enabled: true,
+        ..config.encryption
+    };
+    match ArtifactKey::resolve(&encryption, &credentials) {
+        Err(EncryptionError::MissingKey(_)) => Ok(None),
+        key => Ok(key?),
+    }
+}
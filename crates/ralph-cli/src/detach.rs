@@ -0,0 +1,402 @@
+//! Detached runs and `ralph logs`.
+//!
+//! `ralph run --detach` re-executes the same command line without `--detach`
+//! as a background process in its own process group, with its output going
+//! to `.ralph/sessions/<session>.log`, then prints the session id and
+//! returns. The loop keeps running after the terminal (or SSH connection)
+//! goes away. `ralph logs -f <session>` streams that log, the same file
+//! `ralph serve` writes for sessions it starts.
+//!
+//! `ralph logs --iteration` instead shows the backend output a loop copies to
+//! `.ralph/agent/sessions/<loop>/iter-<n>.out` while each iteration runs, so
+//! a hung or killed iteration can be inspected.
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use ralph_core::{LoopContext, LoopLock};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Set in a detached run's environment to its session id.
+///
+/// The loop ignores SIGHUP when this is set, since a detached run has no
+/// terminal whose hangup should stop it.
+pub(crate) const DETACHED_SESSION_ENV: &str = "RALPH_DETACHED_SESSION";
+
+/// How often `--follow` checks the log for new output.
+const FOLLOW_POLL: Duration = Duration::from_millis(250);
+
+/// Arguments for the logs subcommand.
+#[derive(Parser, Debug)]
+pub struct LogsArgs {
+    /// Session id printed by `ralph run --detach` (default: most recent)
+    pub session: Option<String>,
+
+    /// Keep streaming new output until the session exits
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Show a loop iteration's backend output instead (default: the latest);
+    /// SESSION is then a loop id
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "0")]
+    pub iteration: Option<u32>,
+}
+
+/// A background run started by [`spawn`].
+#[derive(Debug)]
+pub(crate) struct DetachedSession {
+    pub id: String,
+    pub pid: u32,
+    pub log_path: PathBuf,
+}
+
+/// Directory holding session logs and pid files.
+fn sessions_dir(workspace: &Path) -> PathBuf {
+    workspace.join(".ralph").join("sessions")
+}
+
+/// Returns the command-line arguments for the background run: the current
+/// ones without `--detach`, plus `--no-tui` unless the run is already headless.
+pub(crate) fn child_args(
+    args: impl IntoIterator<Item = OsString>,
+    already_headless: bool,
+) -> Vec<OsString> {
+    let mut child: Vec<OsString> = args.into_iter().filter(|arg| arg != "--detach").collect();
+    if !already_headless {
+        child.push("--no-tui".into());
+    }
+    child
+}
+
+/// Starts `program` with `args` in the background, detached from the
+/// terminal, logging to `.ralph/sessions/<id>.log` under `workspace`.
+pub(crate) fn spawn(
+    workspace: &Path,
+    program: &Path,
+    args: &[OsString],
+) -> Result<DetachedSession> {
+    let dir = sessions_dir(workspace);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let id = crate::serve::generate_session_id();
+    let log_path = dir.join(format!("{id}.log"));
+    let log = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&log_path)
+        .with_context(|| format!("Failed to create {}", log_path.display()))?;
+
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .current_dir(workspace)
+        .env(DETACHED_SESSION_ENV, &id)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    // Leave the terminal's process group so Ctrl+C and hangups in the
+    // launching shell don't reach the loop
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let child = command
+        .spawn()
+        .with_context(|| format!("Failed to start {}", program.display()))?;
+    let pid = child.id();
+    fs::write(dir.join(format!("{id}.pid")), pid.to_string())
+        .context("Failed to record the session's process id")?;
+
+    Ok(DetachedSession { id, pid, log_path })
+}
+
+/// Executes `ralph logs`.
+pub async fn execute(args: LogsArgs) -> Result<()> {
+    let workspace = std::env::current_dir().context("Failed to get current directory")?;
+    if let Some(iteration) = args.iteration {
+        let iteration = (iteration > 0).then_some(iteration);
+        return show_iteration(&workspace, args.session.as_deref(), iteration, args.follow).await;
+    }
+    let log_path = resolve_log(&workspace, args.session.as_deref())?;
+    let mut stdout = std::io::stdout();
+
+    let mut file = fs::File::open(&log_path)
+        .with_context(|| format!("Failed to open {}", log_path.display()))?;
+    copy_new_output(&mut file, &mut stdout)?;
+    if !args.follow {
+        return Ok(());
+    }
+
+    let pid = read_pid(&log_path);
+    loop {
+        let running = pid.is_none_or(is_running);
+        copy_new_output(&mut file, &mut stdout)?;
+        if !running {
+            return Ok(());
+        }
+        tokio::time::sleep(FOLLOW_POLL).await;
+    }
+}
+
+/// Streams an iteration's output file, switching to newer iterations when
+/// following the latest one.
+async fn show_iteration(
+    workspace: &Path,
+    session: Option<&str>,
+    iteration: Option<u32>,
+    follow: bool,
+) -> Result<()> {
+    let dir = resolve_entry(
+        &LoopContext::primary(workspace.to_path_buf()).sessions_dir(),
+        session,
+        |name| Some(name.to_string()),
+        "No iteration output found in {}. Start a loop with `ralph run`.",
+    )?;
+    let mut current = match iteration {
+        Some(n) => n,
+        None => latest_iteration(&dir)
+            .with_context(|| format!("No iteration output in {}", dir.display()))?,
+    };
+    let mut stdout = std::io::stdout();
+
+    loop {
+        let path = dir.join(format!("iter-{current}.out"));
+        let mut file =
+            fs::File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        copy_new_output(&mut file, &mut stdout)?;
+        if !follow {
+            return Ok(());
+        }
+
+        loop {
+            let newer = latest_iteration(&dir).filter(|&n| n > current);
+            let running = LoopLock::is_locked(workspace).unwrap_or(false);
+            copy_new_output(&mut file, &mut stdout)?;
+            match newer {
+                Some(next) if iteration.is_none() => {
+                    writeln!(stdout, "\n==> iteration {next} <==")?;
+                    current = next;
+                    break;
+                }
+                Some(_) => return Ok(()),
+                None if !running => return Ok(()),
+                None => tokio::time::sleep(FOLLOW_POLL).await,
+            }
+        }
+    }
+}
+
+/// Highest `n` among the `iter-<n>.out` files in `dir`.
+fn latest_iteration(dir: &Path) -> Option<u32> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("iter-")?
+                .strip_suffix(".out")?
+                .parse()
+                .ok()
+        })
+        .max()
+}
+
+/// Finds the log for `session`, or the most recently written one.
+///
+/// A session id may be abbreviated to any unique prefix.
+fn resolve_log(workspace: &Path, session: Option<&str>) -> Result<PathBuf> {
+    resolve_entry(
+        &sessions_dir(workspace),
+        session,
+        |name| name.strip_suffix(".log").map(str::to_string),
+        "No sessions found in {}. Start one with `ralph run --detach`.",
+    )
+}
+
+/// Finds the entry of `dir` whose id (as extracted by `id_of` from its file
+/// name) starts with `session`, or the most recently modified one.
+///
+/// `missing` is the error when `dir` has no entries; `{}` becomes its path.
+fn resolve_entry(
+    dir: &Path,
+    session: Option<&str>,
+    id_of: impl Fn(&str) -> Option<String>,
+    missing: &str,
+) -> Result<PathBuf> {
+    let mut logs: Vec<(std::time::SystemTime, String, PathBuf)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    let id = id_of(path.file_name()?.to_str()?)?;
+                    let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+                    Some((modified, id, path))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let Some(session) = session else {
+        logs.sort();
+        return match logs.pop() {
+            Some((_, _, path)) => Ok(path),
+            None => bail!("{}", missing.replace("{}", &dir.display().to_string())),
+        };
+    };
+
+    let matches: Vec<_> = logs
+        .into_iter()
+        .filter(|(_, id, _)| id.starts_with(session))
+        .collect();
+    match matches.as_slice() {
+        [(_, _, path)] => Ok(path.clone()),
+        [] => bail!("No session '{session}' in {}", dir.display()),
+        _ => {
+            let ids: Vec<_> = matches.iter().map(|(_, id, _)| id.as_str()).collect();
+            bail!("Session '{session}' is ambiguous: {}", ids.join(", "))
+        }
+    }
+}
+
+/// Reads the pid recorded next to a session log, if any.
+fn read_pid(log_path: &Path) -> Option<u32> {
+    fs::read_to_string(log_path.with_extension("pid"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+    i32::try_from(pid).is_ok_and(|pid| kill(Pid::from_raw(pid), None).is_ok())
+}
+
+#[cfg(not(unix))]
+fn is_running(_pid: u32) -> bool {
+    // No cheap liveness check; follow until interrupted.
+    true
+}
+
+/// Writes everything appended to `file` since the last call.
+fn copy_new_output(file: &mut fs::File, out: &mut impl Write) -> Result<()> {
+    let position = file.stream_position()?;
+    if file.metadata()?.len() < position {
+        // Truncated: start over
+        file.seek(SeekFrom::Start(0))?;
+    }
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    if !buf.is_empty() {
+        out.write_all(&buf)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_child_args_drop_detach_and_force_headless() {
+        let args = ["run", "--detach", "-p", "Fix the bug"].map(OsString::from);
+        assert_eq!(
+            child_args(args.clone(), false),
+            ["run", "-p", "Fix the bug", "--no-tui"].map(OsString::from)
+        );
+        assert_eq!(
+            child_args(args, true),
+            ["run", "-p", "Fix the bug"].map(OsString::from)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_logs_output_and_records_pid() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = ["-c", "echo \"started $RALPH_DETACHED_SESSION\""].map(OsString::from);
+
+        let session = spawn(dir.path(), Path::new("sh"), &args).unwrap();
+
+        assert_eq!(
+            read_pid(&session.log_path),
+            Some(session.pid),
+            "pid file sits next to the log"
+        );
+        let expected = format!("started {}\n", session.id);
+        let mut output = String::new();
+        for _ in 0..100 {
+            output = fs::read_to_string(&session.log_path).unwrap();
+            if output == expected {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_resolve_log_by_prefix_or_latest() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = sessions_dir(dir.path());
+        assert!(resolve_log(dir.path(), None).is_err());
+
+        fs::create_dir_all(&sessions).unwrap();
+        fs::write(sessions.join("session-100-a-0.log"), "old").unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        fs::write(sessions.join("session-200-b-0.log"), "new").unwrap();
+        fs::write(sessions.join("session-200-b-0.pid"), "1").unwrap();
+
+        assert_eq!(
+            resolve_log(dir.path(), None).unwrap(),
+            sessions.join("session-200-b-0.log")
+        );
+        assert_eq!(
+            resolve_log(dir.path(), Some("session-1")).unwrap(),
+            sessions.join("session-100-a-0.log")
+        );
+        let err = resolve_log(dir.path(), Some("session-")).unwrap_err();
+        assert!(err.to_string().contains("ambiguous"));
+        assert!(resolve_log(dir.path(), Some("session-300")).is_err());
+    }
+
+    #[test]
+    fn test_latest_iteration_is_numeric_max() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(latest_iteration(dir.path()), None);
+
+        for name in ["iter-2.out", "iter-10.out", "iter-9.out", "notes.txt"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        assert_eq!(latest_iteration(dir.path()), Some(10));
+    }
+
+    #[test]
+    fn test_copy_new_output_resumes_where_it_left_off() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s.log");
+        fs::write(&path, "one\n").unwrap();
+        let mut file = fs::File::open(&path).unwrap();
+        let mut out = Vec::new();
+
+        copy_new_output(&mut file, &mut out).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"two\n")
+            .unwrap();
+        copy_new_output(&mut file, &mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "one\ntwo\n");
+    }
+}
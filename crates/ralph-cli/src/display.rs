@@ -140,17 +140,30 @@ pub fn print_termination(
     // Determine status color and message based on termination reason
     let (color, icon, label) = match reason {
         TerminationReason::CompletionPromise => (GREEN, "?", "Completion promise detected"),
-        TerminationReason::MaxIterations => (YELLOW, "?", "Maximum iterations reached"),
-        TerminationReason::MaxRuntime => (YELLOW, "?", "Maximum runtime exceeded"),
-        TerminationReason::MaxCost => (YELLOW, "?", "Maximum cost exceeded"),
-        TerminationReason::ConsecutiveFailures => (RED, "?", "Too many consecutive failures"),
-        TerminationReason::LoopThrashing => (RED, "?", "Loop thrashing detected"),
-        TerminationReason::ValidationFailure => (RED, "?", "Too many malformed JSONL events"),
+        TerminationReason::MaxIterations { .. } => (YELLOW, "?", "Maximum iterations reached"),
+        TerminationReason::MaxRuntime { .. } => (YELLOW, "?", "Maximum runtime exceeded"),
+        TerminationReason::MaxCost { .. } => (YELLOW, "?", "Maximum cost exceeded"),
+        TerminationReason::ConsecutiveFailures { .. } => {
+            (RED, "?", "Too many consecutive failures")
+        }
+        TerminationReason::LoopThrashing { .. } => (RED, "?", "Loop thrashing detected"),
+        TerminationReason::DelegationLoop { .. } => (RED, "?", "Repeated delegation detected"),
+        TerminationReason::ValidationFailure { .. } => {
+            (RED, "?", "Too many malformed JSONL events")
+        }
         TerminationReason::Stopped => (CYAN, "?", "Manually stopped"),
         TerminationReason::Interrupted => (YELLOW, "?", "Interrupted by signal"),
         TerminationReason::RestartRequested => (CYAN, "↻", "Restarting by human request"),
+        TerminationReason::IdleTimeout { .. } => (CYAN, "⏸", "Idle timeout while parked"),
     };
 
+    let detail = reason.detail();
+    // Only the final line fits the banner; the summary file keeps the full excerpt
+    let last_error = reason
+        .last_error()
+        .and_then(|e| e.lines().rev().find(|l| !l.trim().is_empty()))
+        .map(|line| truncate(line.trim(), 44));
+
     let separator = "-".repeat(58);
 
     if use_colors {
@@ -159,6 +172,12 @@ pub fn print_termination(
             "{BOLD}|{RESET} {color}{BOLD}{icon}{RESET} Loop terminated: {color}{label}{RESET}"
         );
         println!("{BOLD}+{separator}+{RESET}");
+        if let Some(detail) = &detail {
+            println!("{BOLD}|{RESET}   Details:     {detail}");
+        }
+        if let Some(error) = &last_error {
+            println!("{BOLD}|{RESET}   Last error:  {DIM}{error}{RESET}");
+        }
         println!(
             "{BOLD}|{RESET}   Iterations:  {CYAN}{}{RESET}",
             state.iteration
@@ -178,6 +197,12 @@ pub fn print_termination(
         println!("\n+{}+", "-".repeat(58));
         println!("| {icon} Loop terminated: {label}");
         println!("+{}+", "-".repeat(58));
+        if let Some(detail) = &detail {
+            println!("|   Details:     {detail}");
+        }
+        if let Some(error) = &last_error {
+            println!("|   Last error:  {error}");
+        }
         println!("|   Iterations:  {}", state.iteration);
         println!("|   Elapsed:     {:.1}s", state.elapsed().as_secs_f64());
         if state.cumulative_cost > 0.0 {
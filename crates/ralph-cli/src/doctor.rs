@@ -2,8 +2,10 @@
 
 use anyhow::Result;
 use clap::Parser;
-use ralph_adapters::{CliBackend, DEFAULT_PRIORITY};
-use ralph_core::{CheckResult, CheckStatus, ConfigError, HatBackend, PreflightReport, RalphConfig};
+use ralph_adapters::{API_BACKEND, CliBackend, DEFAULT_PRIORITY};
+use ralph_core::{
+    CheckResult, CheckStatus, ConfigError, HatBackend, Mode, PreflightReport, RalphConfig,
+};
 use std::collections::HashSet;
 use std::env;
 use std::ffi::OsString;
@@ -137,6 +139,12 @@ where
                 );
             }
         }
+        API_BACKEND => {
+            checks.push(CheckResult::pass(
+                "backend:api",
+                "Messages API backend needs no CLI",
+            ));
+        }
         backend => {
             let backend = backend.trim().to_lowercase();
             match command_for_named_backend(&backend, config.cli.command.as_deref()) {
@@ -168,6 +176,9 @@ where
         let Some(hat_backend) = &hat_config.backend else {
             continue;
         };
+        if hat_backend.to_cli_backend() == API_BACKEND {
+            continue;
+        }
 
         let check_mode = match hat_backend {
             HatBackend::Custom { .. } => CommandCheckMode::PathOnly,
@@ -202,6 +213,59 @@ where
         }
     }
 
+    for (hat_id, hat_config) in &config.hats {
+        let Some(hat_cli) = &hat_config.cli else {
+            continue;
+        };
+        if hat_cli.backend.is_none() && hat_cli.command.is_none() {
+            continue;
+        }
+        let cli = hat_cli.apply(&config.cli);
+        let backend = cli.backend.trim().to_lowercase();
+        if backend == API_BACKEND || backend == "auto" {
+            continue;
+        }
+
+        let resolved = if backend == "custom" {
+            cli.command
+                .clone()
+                .filter(|command| !command.trim().is_empty())
+                .map(|command| {
+                    (
+                        canonical_backend_name("custom", Some(&command)),
+                        command,
+                        CommandCheckMode::PathOnly,
+                    )
+                })
+                .ok_or_else(|| format!("Hat '{hat_id}' uses the custom backend without a command"))
+        } else {
+            command_for_named_backend(&backend, cli.command.as_deref())
+                .map(|command| (backend.clone(), command, CommandCheckMode::Version))
+        };
+        match resolved {
+            Ok((backend_name, command, check_mode)) => {
+                push_backend_check(
+                    &mut checks,
+                    &mut seen,
+                    &backend_name,
+                    &command,
+                    true,
+                    check_mode,
+                    &command_version_ok,
+                    &command_exists,
+                    None,
+                );
+            }
+            Err(err) => {
+                checks.push(CheckResult::fail(
+                    &format!("backend:hat:{hat_id}"),
+                    "Invalid hat cli",
+                    err,
+                ));
+            }
+        }
+    }
+
     checks
 }
 
@@ -301,16 +365,13 @@ fn hat_collection_check(_config: &RalphConfig) -> CheckResult {
     let config = _config;
 
     match config.validate() {
-        Ok(_) => {
-            if config.hats.is_empty() {
-                CheckResult::pass("hats", "No custom hats configured (solo mode)")
-            } else {
-                CheckResult::pass(
-                    "hats",
-                    format!("Hat collection parsed ({} hat(s))", config.hats.len()),
-                )
-            }
-        }
+        Ok(_) => match config.mode() {
+            Mode::Single => CheckResult::pass("hats", "No custom hats configured (solo mode)"),
+            Mode::Multi => CheckResult::pass(
+                "hats",
+                format!("Hat collection parsed ({} hat(s))", config.hats.len()),
+            ),
+        },
         Err(err) => match err {
             ConfigError::AmbiguousRouting { .. }
             | ConfigError::ReservedTrigger { .. }
@@ -344,6 +405,15 @@ fn auth_backend_names(config: &RalphConfig) -> Vec<String> {
     }
 
     for hat in config.hats.values() {
+        if let Some(hat_cli) = &hat.cli
+            && let Some(backend) = &hat_cli.backend
+        {
+            let name = match backend.as_str() {
+                "custom" => canonical_backend_name("custom", hat_cli.command.as_deref()),
+                backend => backend.to_string(),
+            };
+            names.insert(name.to_lowercase());
+        }
         let Some(backend) = &hat.backend else {
             continue;
         };
@@ -363,7 +433,7 @@ fn auth_backend_names(config: &RalphConfig) -> Vec<String> {
 
 fn auth_env_vars(backend: &str) -> Option<Vec<&'static str>> {
     match backend {
-        "claude" => Some(vec!["ANTHROPIC_API_KEY"]),
+        "claude" | API_BACKEND => Some(vec!["ANTHROPIC_API_KEY"]),
         "gemini" => Some(vec!["GEMINI_API_KEY"]),
         "codex" => Some(vec!["OPENAI_API_KEY", "CODEX_API_KEY"]),
         "kiro" => Some(vec!["KIRO_API_KEY"]),
@@ -429,6 +499,7 @@ fn canonical_backend_name(backend: &str, command: Option<&str>) -> String {
         "gemini" => "gemini".to_string(),
         "codex" => "codex".to_string(),
         "amp" => "amp".to_string(),
+        "goose" => "goose".to_string(),
         "copilot" => "copilot".to_string(),
         "opencode" => "opencode".to_string(),
         _ => normalized,
@@ -609,8 +680,23 @@ mod tests {
             instructions: String::new(),
             extra_instructions: vec![],
             backend,
+            cli: None,
+            generation: ralph_core::GenerationConfig::default(),
             default_publishes: None,
             max_activations: None,
+            plugin: None,
+            environment: None,
+            when: None,
+            max_cost_share: None,
+            max_runtime_seconds: None,
+            windows: vec![],
+            cache_responses: false,
+            scouts: None,
+            command: None,
+            aliases: vec![],
+            deprecated: false,
+            postprocess: vec![],
+            mutex_group: None,
         }
     }
 
@@ -640,6 +726,50 @@ mod tests {
         assert!(names.contains(&"backend:opencode"));
     }
 
+    #[test]
+    fn backend_checks_pass_api_backend_without_cli() {
+        let mut config = RalphConfig::default();
+        config.cli.backend = "api".to_string();
+        config.hats.insert(
+            "planner".to_string(),
+            base_hat("Planner", Some(HatBackend::Named("api".to_string()))),
+        );
+        let checks = backend_checks(&config, |_| false, |_| false);
+
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].name, "backend:api");
+        assert_eq!(checks[0].status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn backend_checks_include_hat_cli_backends() {
+        let mut config = RalphConfig::default();
+        config.cli.backend = "claude".to_string();
+        let mut builder = base_hat("Builder", None);
+        builder.cli = Some(ralph_core::HatCliConfig {
+            backend: Some("codex".to_string()),
+            ..Default::default()
+        });
+        config.hats.insert("builder".to_string(), builder);
+        let mut broken = base_hat("Broken", None);
+        broken.cli = Some(ralph_core::HatCliConfig {
+            backend: Some("custom".to_string()),
+            ..Default::default()
+        });
+        config.hats.insert("broken".to_string(), broken);
+
+        let checks = backend_checks(&config, |_| true, |_| true);
+        let names: HashSet<_> = checks.iter().map(|check| check.name.as_str()).collect();
+        assert!(names.contains("backend:codex"));
+        let broken = checks
+            .iter()
+            .find(|check| check.name == "backend:hat:broken")
+            .expect("expected a failed hat cli check");
+        assert_eq!(broken.status, CheckStatus::Fail);
+
+        assert!(auth_backend_names(&config).contains(&"codex".to_string()));
+    }
+
     #[test]
     fn backend_checks_fail_required_missing() {
         let mut config = RalphConfig::default();
@@ -0,0 +1,457 @@
+//! `ralph run --dry-run`: catch misconfigurations before anything executes.
+//!
+//! A dry run validates the hat topology, renders the prompt each hat would
+//! receive to `.ralph/agent/dry-run/`, and simulates a `task.start` event
+//! routing through the real event bus. In the simulation every activated hat
+//! publishes its first declared topic, so the trace shows the path a run
+//! takes when each hat succeeds, and which hats that path never reaches.
+
+use anyhow::{Context, Result};
+use ralph_core::{EventLoop, HatRegistry, LoopContext, RalphConfig};
+use ralph_proto::{Event, HatId};
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Payload for events published by the simulation.
+const SAMPLE_PAYLOAD: &str = "(dry run) sample event";
+
+/// One iteration of the simulated route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RouteStep {
+    /// Topics pending when the iteration starts.
+    pub triggers: Vec<String>,
+    /// Hats activated by those topics; empty when Ralph coordinates.
+    pub hats: Vec<String>,
+}
+
+/// How the simulated route ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RouteEnd {
+    /// No hats are configured; Ralph handles everything.
+    Solo,
+    /// A hat published the completion promise.
+    Completed { hat: String },
+    /// Only Ralph receives these topics, so Ralph decides what happens next.
+    ReturnsToRalph { topics: Vec<String> },
+    /// The route came back to an earlier step (1-based).
+    Repeats { step: usize },
+    /// Nothing is pending: the active hats publish nothing.
+    Stalled,
+    /// The route kept moving without repeating or finishing.
+    StepLimit,
+}
+
+/// Result of [`simulate_routing`].
+#[derive(Debug, Clone)]
+pub(crate) struct RouteSimulation {
+    pub steps: Vec<RouteStep>,
+    pub end: RouteEnd,
+    /// Configured hats the route never activates.
+    pub unreached: Vec<String>,
+}
+
+/// Runs the full dry run, writing the report to `writer`.
+pub(crate) fn run<W: Write>(writer: &mut W, config: &RalphConfig, use_colors: bool) -> Result<()> {
+    let registry = HatRegistry::from_config(config);
+    crate::hats::validate_hats(writer, config, &registry, use_colors)?;
+    writeln!(writer)?;
+
+    let prompt = crate::loop_runner::resolve_prompt_content(&config.event_loop)?;
+
+    let simulation = simulate_routing(config, &prompt);
+    write_route(writer, config, &simulation)?;
+
+    let ctx = LoopContext::primary(config.core.workspace_root.clone());
+    let out_dir = ctx.agent_dir().join("dry-run");
+    let files = render_prompts(config, &prompt, &out_dir)?;
+    writeln!(writer)?;
+    writeln!(writer, "Rendered prompts ({}):", out_dir.display())?;
+    for file in &files {
+        if let Some(name) = file.file_name() {
+            writeln!(writer, "  {}", name.to_string_lossy())?;
+        }
+    }
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Simulates `task.start` routing through the event bus.
+///
+/// Each activated hat publishes the first concrete topic it declares. When Ralph coordinates the first
+/// iteration, it delegates to the entry hats: those whose triggers no hat
+/// publishes.
+pub(crate) fn simulate_routing(config: &RalphConfig, prompt: &str) -> RouteSimulation {
+    let mut event_loop = new_event_loop(config);
+    event_loop.initialize(prompt);
+
+    let hat_count = event_loop.registry().len();
+    let max_steps = hat_count * 2 + 2;
+    let ralph = HatId::new("ralph");
+    let mut steps: Vec<RouteStep> = Vec::new();
+    let mut reached = BTreeSet::new();
+
+    let end = loop {
+        if steps.len() >= max_steps {
+            break RouteEnd::StepLimit;
+        }
+        let Some(next) = event_loop.next_hat().cloned() else {
+            break RouteEnd::Stalled;
+        };
+        let triggers = pending_topics(&mut event_loop);
+        event_loop.build_prompt(&next);
+        let hats: Vec<String> = event_loop
+            .state()
+            .last_active_hat_ids
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        let step = RouteStep { triggers, hats };
+        let seen = steps.iter().position(|s| *s == step);
+        steps.push(step.clone());
+        if let Some(index) = seen {
+            break RouteEnd::Repeats { step: index + 1 };
+        }
+
+        if hat_count == 0 {
+            break RouteEnd::Solo;
+        }
+
+        if step.hats.is_empty() {
+            if steps.len() > 1 {
+                break RouteEnd::ReturnsToRalph {
+                    topics: step.triggers,
+                };
+            }
+            for topic in entry_topics(event_loop.registry()) {
+                event_loop
+                    .bus()
+                    .publish(Event::new(topic, SAMPLE_PAYLOAD).with_source(ralph.clone()));
+            }
+            continue;
+        }
+
+        let mut completed = None;
+        for hat in &step.hats {
+            reached.insert(hat.clone());
+            let Some(topic) = published_topic(config, event_loop.registry(), hat) else {
+                continue;
+            };
+            if topic == config.event_loop.completion_promise {
+                completed = Some(hat.clone());
+                break;
+            }
+            event_loop
+                .bus()
+                .publish(Event::new(topic, SAMPLE_PAYLOAD).with_source(hat.as_str()));
+        }
+        if let Some(hat) = completed {
+            break RouteEnd::Completed { hat };
+        }
+    };
+
+    let unreached = event_loop
+        .registry()
+        .all()
+        .map(|hat| hat.id.to_string())
+        .filter(|id| !reached.contains(id))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    RouteSimulation {
+        steps,
+        end,
+        unreached,
+    }
+}
+
+/// Renders the prompt each hat would receive to `out_dir`.
+///
+/// Writes `ralph.md` for the coordinator plus `<hat>.md` for every hat,
+/// activated by its first trigger. Earlier renders are removed first.
+pub(crate) fn render_prompts(
+    config: &RalphConfig,
+    prompt: &str,
+    out_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    if out_dir.exists() {
+        std::fs::remove_dir_all(out_dir)
+            .with_context(|| format!("Failed to clear {}", out_dir.display()))?;
+    }
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+
+    let mut renders = Vec::new();
+
+    let mut event_loop = new_event_loop(config);
+    event_loop.initialize(prompt);
+    if let Some(next) = event_loop.next_hat().cloned()
+        && let Some(rendered) = event_loop.build_prompt(&next)
+    {
+        renders.push(("ralph".to_string(), rendered));
+    }
+
+    let hats: Vec<(String, String)> = {
+        let mut hats: Vec<_> = HatRegistry::from_config(config)
+            .all()
+            .filter_map(|hat| {
+                let trigger = hat.subscriptions.first()?.as_str().replace('*', "dry-run");
+                Some((hat.id.to_string(), trigger))
+            })
+            .collect();
+        hats.sort();
+        hats
+    };
+    for (hat, trigger) in hats {
+        let mut event_loop = new_event_loop(config);
+        event_loop.initialize(prompt);
+        event_loop
+            .bus()
+            .publish(Event::new(trigger.as_str(), SAMPLE_PAYLOAD));
+        if let Some(next) = event_loop.next_hat().cloned()
+            && let Some(rendered) = event_loop.build_prompt(&next)
+        {
+            renders.push((hat, rendered));
+        }
+    }
+
+    let mut files = Vec::new();
+    for (name, rendered) in renders {
+        let path = out_dir.join(format!("{}.md", name));
+        std::fs::write(&path, rendered)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        files.push(path);
+    }
+    Ok(files)
+}
+
+fn new_event_loop(config: &RalphConfig) -> EventLoop {
+    let ctx = LoopContext::primary(config.core.workspace_root.clone());
+    EventLoop::with_context(config.clone(), ctx)
+}
+
+/// Triggers of hats that no other hat publishes to, i.e. where Ralph starts.
+fn entry_topics(registry: &HatRegistry) -> Vec<String> {
+    let published: BTreeSet<&str> = registry
+        .all()
+        .flat_map(|hat| hat.publishes.iter().map(|t| t.as_str()))
+        .collect();
+    let mut entries: Vec<_> = registry
+        .all()
+        .filter_map(|hat| {
+            hat.subscriptions
+                .iter()
+                .map(|t| t.as_str())
+                .find(|t| !t.contains('*') && !published.contains(t))
+        })
+        .map(str::to_string)
+        .collect();
+    entries.sort();
+    entries.dedup();
+    entries
+}
+
+/// Distinct topics waiting in any hat's queue.
+fn pending_topics(event_loop: &mut EventLoop) -> Vec<String> {
+    let bus = event_loop.bus();
+    let mut hat_ids: Vec<HatId> = bus.hat_ids().cloned().collect();
+    hat_ids.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    let mut topics: Vec<String> = Vec::new();
+    for event in hat_ids
+        .iter()
+        .filter_map(|id| bus.peek_pending(id))
+        .flatten()
+    {
+        let topic = event.topic.to_string();
+        if !topics.contains(&topic) {
+            topics.push(topic);
+        }
+    }
+    topics
+}
+
+/// The topic a hat publishes when it succeeds: its first concrete
+/// `publishes` entry, falling back to `default_publishes`.
+fn published_topic(config: &RalphConfig, registry: &HatRegistry, hat: &str) -> Option<String> {
+    registry
+        .get(&HatId::new(hat))?
+        .publishes
+        .iter()
+        .map(|t| t.as_str())
+        .find(|t| !t.contains('*'))
+        .map(str::to_string)
+        .or_else(|| {
+            config
+                .hats
+                .get(hat)
+                .and_then(|h| h.default_publishes.clone())
+        })
+}
+
+fn write_route<W: Write>(
+    writer: &mut W,
+    config: &RalphConfig,
+    simulation: &RouteSimulation,
+) -> Result<()> {
+    writeln!(writer, "Routing Simulation")?;
+    writeln!(writer, "==================")?;
+    writeln!(writer)?;
+    for (i, step) in simulation.steps.iter().enumerate() {
+        writeln!(writer, "  {}. {}", i + 1, describe_step(step))?;
+    }
+    writeln!(writer)?;
+    writeln!(writer, "Outcome: {}", describe_end(config, &simulation.end))?;
+    if !simulation.unreached.is_empty() {
+        writeln!(
+            writer,
+            "Warning: hats never activated from task.start: {}",
+            simulation.unreached.join(", ")
+        )?;
+    }
+    Ok(())
+}
+
+/// Formats a route step as `<topics> -> <hats>`.
+pub(crate) fn describe_step(step: &RouteStep) -> String {
+    let target = if step.hats.is_empty() {
+        "ralph".to_string()
+    } else {
+        step.hats.join(", ")
+    };
+    format!("{} -> {}", step.triggers.join(", "), target)
+}
+
+/// Describes how a simulated route ends.
+pub(crate) fn describe_end(config: &RalphConfig, end: &RouteEnd) -> String {
+    match end {
+        RouteEnd::Solo => "Ralph handles the task alone (solo mode)".to_string(),
+        RouteEnd::Completed { hat } => format!(
+            "'{}' publishes {}; the loop completes",
+            hat, config.event_loop.completion_promise
+        ),
+        RouteEnd::ReturnsToRalph { topics } => format!(
+            "No hat subscribes to {}; Ralph takes over and must decide to finish",
+            topics.join(", ")
+        ),
+        RouteEnd::Repeats { step } => format!("Repeats step {}; the route cycles", step),
+        RouteEnd::Stalled => "Nothing is published; a real run would stall".to_string(),
+        RouteEnd::StepLimit => "Still routing after the step limit".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> (tempfile::TempDir, RalphConfig) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        config.core.workspace_root = temp_dir.path().to_path_buf();
+        (temp_dir, config)
+    }
+
+    const PIPELINE: &str = r#"
+hats:
+  builder:
+    name: "Builder"
+    triggers: ["build.task"]
+    publishes: ["build.done"]
+  reviewer:
+    name: "Reviewer"
+    triggers: ["build.done"]
+    publishes: ["review.approved", "review.rejected"]
+  auditor:
+    name: "Auditor"
+    triggers: ["audit.request"]
+    publishes: ["audit.done"]
+"#;
+
+    #[test]
+    fn test_simulation_follows_pipeline_and_reports_unreached() {
+        let (_temp, config) = config(PIPELINE);
+        let simulation = simulate_routing(&config, "Build the thing");
+
+        let hats: Vec<_> = simulation.steps.iter().map(|s| s.hats.clone()).collect();
+        assert_eq!(simulation.steps[0].triggers, vec!["task.start"]);
+        assert!(hats[0].is_empty(), "Ralph coordinates task.start");
+        assert_eq!(hats[1], vec!["auditor", "builder"]);
+        assert_eq!(hats[2], vec!["reviewer"]);
+        assert_eq!(
+            simulation.end,
+            RouteEnd::ReturnsToRalph {
+                topics: vec!["review.approved".to_string()]
+            }
+        );
+        assert!(simulation.unreached.is_empty());
+    }
+
+    #[test]
+    fn test_simulation_detects_cycle_and_completion() {
+        let (_cyclic_temp, cyclic) = config(
+            r#"
+event_loop:
+  starting_event: "ping"
+hats:
+  a:
+    name: "A"
+    triggers: ["ping"]
+    publishes: ["pong"]
+  b:
+    name: "B"
+    triggers: ["pong"]
+    publishes: ["ping"]
+  c:
+    name: "C"
+    triggers: ["never"]
+    publishes: ["LOOP_COMPLETE"]
+"#,
+        );
+        let simulation = simulate_routing(&cyclic, "Play");
+        assert_eq!(simulation.end, RouteEnd::Repeats { step: 1 });
+        assert_eq!(simulation.unreached, vec!["c"]);
+
+        let (_finishing_temp, finishing) = config(
+            r#"
+event_loop:
+  starting_event: "work"
+hats:
+  worker:
+    name: "Worker"
+    triggers: ["work"]
+    publishes: ["LOOP_COMPLETE"]
+"#,
+        );
+        let simulation = simulate_routing(&finishing, "Work");
+        assert_eq!(
+            simulation.end,
+            RouteEnd::Completed {
+                hat: "worker".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_render_prompts_writes_one_file_per_hat() {
+        let (_temp, config) = config(PIPELINE);
+        let out_dir = config.core.workspace_root.join(".ralph/agent/dry-run");
+        std::fs::create_dir_all(&out_dir).unwrap();
+        std::fs::write(out_dir.join("stale.md"), "old").unwrap();
+
+        let files = render_prompts(&config, "Build the thing", &out_dir).unwrap();
+
+        let names: Vec<_> = files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["ralph.md", "auditor.md", "builder.md", "reviewer.md"]
+        );
+        assert!(!out_dir.join("stale.md").exists());
+        let builder = std::fs::read_to_string(out_dir.join("builder.md")).unwrap();
+        assert!(builder.contains("Build the thing"));
+        assert!(builder.contains("build.task"));
+    }
+}
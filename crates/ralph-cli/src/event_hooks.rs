@@ -0,0 +1,316 @@
+//! Shell hooks for published events (`on_event:` in ralph.yml).
+//!
+//! Each hook pairs a topic pattern with a shell command. When a matching event
+//! is published, the command runs in the background in the workspace root with
+//! the event as one line of JSON on stdin and `RALPH_EVENT_TOPIC` set. Hooks
+//! are fire-and-forget: a slow or failing hook is logged and never blocks or
+//! fails the loop. When the loop ends, hooks still running (typically for
+//! `loop.terminate`) get up to [`DRAIN_TIMEOUT`] to finish; see
+//! [`HookTasks::drain`].
+
+use ralph_proto::{Event, Topic};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// How long a hook may run before it is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_mins(5);
+
+/// How long the loop waits on exit for hooks still running.
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The JSON document written to a hook's stdin.
+#[derive(Debug, Serialize)]
+struct HookPayload<'a> {
+    topic: &'a str,
+    payload: &'a str,
+    source: Option<String>,
+    target: Option<String>,
+    ts: String,
+}
+
+/// Hooks started by [`EventHooks`] that may still be running.
+#[derive(Debug, Clone, Default)]
+pub struct HookTasks(Arc<Mutex<Vec<JoinHandle<()>>>>);
+
+impl HookTasks {
+    fn track(&self, handle: JoinHandle<()>) {
+        if let Ok(mut handles) = self.0.lock() {
+            handles.retain(|handle| !handle.is_finished());
+            handles.push(handle);
+        }
+    }
+
+    /// Waits up to [`DRAIN_TIMEOUT`] for running hooks. Hooks still running
+    /// after that are left to be killed when the runtime shuts down.
+    pub async fn drain(&self) {
+        self.drain_within(DRAIN_TIMEOUT).await;
+    }
+
+    async fn drain_within(&self, timeout: Duration) {
+        let handles = self
+            .0
+            .lock()
+            .map(|mut handles| std::mem::take(&mut *handles))
+            .unwrap_or_default();
+        if handles.is_empty() {
+            return;
+        }
+        let pending = handles.len();
+        let wait_all = async {
+            for handle in handles {
+                let _ = handle.await;
+            }
+        };
+        if tokio::time::timeout(timeout, wait_all).await.is_err() {
+            warn!(
+                pending,
+                timeout_secs = timeout.as_secs(),
+                "on_event hooks still running at exit were killed"
+            );
+        }
+    }
+}
+
+/// Topic-pattern → command hooks, ready to be attached to the event bus.
+#[derive(Debug, Clone)]
+pub struct EventHooks {
+    hooks: Arc<Vec<(Topic, String)>>,
+    workspace: PathBuf,
+    tasks: HookTasks,
+}
+
+impl EventHooks {
+    /// Builds hooks from the `on_event` config map. Started hooks are tracked
+    /// in `tasks`.
+    ///
+    /// Returns `None` when no hooks are configured.
+    pub fn from_config(
+        on_event: &HashMap<String, String>,
+        workspace: PathBuf,
+        tasks: HookTasks,
+    ) -> Option<Self> {
+        let mut hooks: Vec<(Topic, String)> = on_event
+            .iter()
+            .filter(|(_, command)| !command.trim().is_empty())
+            .map(|(pattern, command)| (Topic::new(pattern), command.clone()))
+            .collect();
+        if hooks.is_empty() {
+            return None;
+        }
+        // Stable order so hooks for the same event start predictably.
+        hooks.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        Some(Self {
+            hooks: Arc::new(hooks),
+            workspace,
+            tasks,
+        })
+    }
+
+    /// Returns the commands whose pattern matches `topic`.
+    pub fn matching<'a>(&'a self, topic: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.hooks
+            .iter()
+            .filter(move |(pattern, _)| pattern.matches_str(topic))
+            .map(|(_, command)| command.as_str())
+    }
+
+    /// Returns an observer closure to wire into the event bus.
+    pub fn observer(&self) -> impl Fn(&Event) + Send + 'static {
+        let hooks = self.clone();
+        move |event: &Event| hooks.dispatch(event)
+    }
+
+    fn dispatch(&self, event: &Event) {
+        let topic = event.topic.as_str();
+        let mut commands = self.matching(topic).peekable();
+        if commands.peek().is_none() {
+            return;
+        }
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!(topic, "No async runtime; skipping on_event hooks");
+            return;
+        };
+
+        let payload = HookPayload {
+            topic,
+            payload: &event.payload,
+            source: event.source.as_ref().map(ToString::to_string),
+            target: event.target.as_ref().map(ToString::to_string),
+            ts: chrono::Utc::now().to_rfc3339(),
+        };
+        let mut stdin = match serde_json::to_string(&payload) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!(topic, error = %e, "Failed to serialize event for on_event hook");
+                return;
+            }
+        };
+        stdin.push('\n');
+
+        for command in commands {
+            self.tasks.track(runtime.spawn(run_hook(
+                command.to_string(),
+                topic.to_string(),
+                stdin.clone(),
+                self.workspace.clone(),
+            )));
+        }
+    }
+}
+
+async fn run_hook(command: String, topic: String, stdin: String, workspace: PathBuf) {
+    let mut cmd = shell_command(&command);
+    cmd.current_dir(&workspace)
+        .env("RALPH_EVENT_TOPIC", &topic)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            warn!(topic, command, error = %e, "Failed to start on_event hook");
+            return;
+        }
+    };
+
+    if let Some(mut pipe) = child.stdin.take() {
+        // A hook that ignores stdin may exit before reading it; that's fine.
+        let _ = pipe.write_all(stdin.as_bytes()).await;
+    }
+
+    match tokio::time::timeout(HOOK_TIMEOUT, child.wait_with_output()).await {
+        Ok(Ok(output)) if output.status.success() => {
+            debug!(topic, command, "on_event hook finished");
+        }
+        Ok(Ok(output)) => {
+            warn!(
+                topic,
+                command,
+                status = %output.status,
+                stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+                "on_event hook failed"
+            );
+        }
+        Ok(Err(e)) => warn!(topic, command, error = %e, "on_event hook failed"),
+        Err(_) => warn!(
+            topic,
+            command,
+            timeout_secs = HOOK_TIMEOUT.as_secs(),
+            "on_event hook timed out and was killed"
+        ),
+    }
+}
+
+fn shell_command(command: &str) -> tokio::process::Command {
+    if cfg!(windows) {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn hooks(pairs: &[(&str, &str)], workspace: PathBuf) -> EventHooks {
+        let map = pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect();
+        EventHooks::from_config(&map, workspace, HookTasks::default()).expect("hooks configured")
+    }
+
+    #[test]
+    fn test_empty_config_yields_no_hooks() {
+        let tasks = HookTasks::default();
+        assert!(
+            EventHooks::from_config(&HashMap::new(), PathBuf::from("."), tasks.clone()).is_none()
+        );
+        let blank = HashMap::from([("build.done".to_string(), "  ".to_string())]);
+        assert!(EventHooks::from_config(&blank, PathBuf::from("."), tasks).is_none());
+    }
+
+    #[test]
+    fn test_matching_uses_topic_patterns() {
+        let hooks = hooks(
+            &[("build.done", "a"), ("review.*", "b"), ("*", "c")],
+            PathBuf::from("."),
+        );
+        assert_eq!(hooks.matching("build.done").collect::<Vec<_>>(), ["c", "a"]);
+        assert_eq!(hooks.matching("review.ok").collect::<Vec<_>>(), ["c", "b"]);
+        assert_eq!(hooks.matching("build.blocked").collect::<Vec<_>>(), ["c"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hook_receives_event_json_on_stdin() {
+        let tmp = TempDir::new().unwrap();
+        let hooks = hooks(
+            &[("build.done", "cat > hook-input.json")],
+            tmp.path().to_path_buf(),
+        );
+
+        let observer = hooks.observer();
+        observer(&Event::new("build.done", "all green"));
+
+        let output = tmp.path().join("hook-input.json");
+        for _ in 0..100 {
+            if std::fs::read_to_string(&output).is_ok_and(|s| s.ends_with('\n')) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(json["topic"], "build.done");
+        assert_eq!(json["payload"], "all green");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_drain_waits_for_running_hooks() {
+        let tmp = TempDir::new().unwrap();
+        let tasks = HookTasks::default();
+        let map = HashMap::from([(
+            "loop.terminate".to_string(),
+            "sleep 0.3 && touch notified".to_string(),
+        )]);
+        let hooks = EventHooks::from_config(&map, tmp.path().to_path_buf(), tasks.clone())
+            .expect("hooks configured");
+
+        hooks.observer()(&Event::new("loop.terminate", "done"));
+        tasks.drain().await;
+        assert!(tmp.path().join("notified").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_drain_is_bounded() {
+        let tmp = TempDir::new().unwrap();
+        let tasks = HookTasks::default();
+        let map = HashMap::from([("loop.terminate".to_string(), "sleep 30".to_string())]);
+        let hooks = EventHooks::from_config(&map, tmp.path().to_path_buf(), tasks.clone())
+            .expect("hooks configured");
+
+        hooks.observer()(&Event::new("loop.terminate", "done"));
+        let started = std::time::Instant::now();
+        tasks.drain_within(Duration::from_millis(100)).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,320 @@
+//! CLI command for `ralph export`.
+//!
+//! Renders a session's event journal as a diagram of which hat published
+//! which topic to whom, in order. Where `ralph hats graph` shows what the
+//! configuration allows, `ralph export --flow` shows what actually happened.
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, ValueEnum};
+use ralph_core::{EventHistory, EventRecord};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Export a session's event flow as a diagram.
+#[derive(Parser, Debug)]
+pub struct ExportArgs {
+    /// Session to export: `current`, a run ID (e.g. 20260127-123456), or a path to an events file
+    #[arg(long, value_name = "SESSION")]
+    pub flow: String,
+
+    /// Diagram format
+    #[arg(long, value_enum, default_value_t = FlowFormat::Mermaid)]
+    pub format: FlowFormat,
+
+    /// Write the diagram to a file instead of stdout
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+/// Output format for `ralph export --flow`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum FlowFormat {
+    /// Mermaid sequence diagram
+    #[default]
+    Mermaid,
+    /// Graphviz digraph with numbered edges
+    Dot,
+}
+
+/// Execute the export command.
+pub fn execute(args: &ExportArgs) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let path = resolve_session(&cwd, &args.flow)?;
+    let records = EventHistory::new(&path)
+        .with_key(crate::decrypt::artifact_key(&cwd)?)
+        .read_all()
+        .with_context(|| format!("Failed to read events at {}", path.display()))?;
+    if records.is_empty() {
+        bail!("No events recorded in {}", path.display());
+    }
+
+    let flow = Flow::from_records(&records);
+    let diagram = match args.format {
+        FlowFormat::Mermaid => flow.to_mermaid(),
+        FlowFormat::Dot => flow.to_dot(),
+    };
+
+    match &args.output {
+        Some(out) => {
+            fs::write(out, diagram).with_context(|| format!("Failed to write {}", out.display()))?
+        }
+        None => print!("{diagram}"),
+    }
+    Ok(())
+}
+
+/// Finds the events file for a session name.
+pub(crate) fn resolve_session(workspace: &Path, session: &str) -> Result<PathBuf> {
+    let ralph_dir = workspace.join(".ralph");
+    if session == "current" {
+        return Ok(
+            fs::read_to_string(ralph_dir.join("current-events")).map_or_else(
+                |_| ralph_dir.join("events.jsonl"),
+                |marker| workspace.join(marker.trim()),
+            ),
+        );
+    }
+
+    let as_path = Path::new(session);
+    if as_path.is_file() {
+        return Ok(as_path.to_path_buf());
+    }
+
+    let by_id = ralph_dir.join(format!("events-{session}.jsonl"));
+    if by_id.is_file() {
+        return Ok(by_id);
+    }
+    bail!(
+        "No session '{session}': expected `current`, a run ID with {}, or an events file",
+        ralph_dir.join("events-<id>.jsonl").display()
+    )
+}
+
+/// One published event in the session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FlowMessage {
+    iteration: u32,
+    from: String,
+    /// Hat the event triggered; `None` when nothing subscribed.
+    to: Option<String>,
+    topic: String,
+}
+
+/// The session's participants and messages, in order of first appearance.
+#[derive(Debug, Default)]
+struct Flow {
+    participants: Vec<String>,
+    messages: Vec<FlowMessage>,
+}
+
+impl Flow {
+    /// Builds the flow from journal records.
+    ///
+    /// Records written by Ralph carry the publishing hat. Records an agent
+    /// appended itself don't, so they're attributed to the hat the previous
+    /// event triggered, which is the one that was running.
+    fn from_records(records: &[EventRecord]) -> Self {
+        let mut flow = Self::default();
+        let mut active: Option<String> = None;
+        let mut iteration = 0;
+
+        for record in records {
+            if record.iteration > 0 {
+                iteration = record.iteration;
+            }
+            let from = if record.hat.is_empty() {
+                active.clone().unwrap_or_else(|| "agent".to_string())
+            } else {
+                record.hat.clone()
+            };
+            let to = record.triggered.clone().filter(|t| !t.is_empty());
+
+            flow.add_participant(&from);
+            if let Some(to) = &to {
+                flow.add_participant(to);
+                active = Some(to.clone());
+            }
+            flow.messages.push(FlowMessage {
+                iteration,
+                from,
+                to,
+                topic: record.topic.clone(),
+            });
+        }
+        flow
+    }
+
+    fn add_participant(&mut self, name: &str) {
+        if !self.participants.iter().any(|p| p == name) {
+            self.participants.push(name.to_string());
+        }
+    }
+
+    /// Diagram ID for a participant (Mermaid reserves words like `loop`).
+    fn id(&self, name: &str) -> String {
+        let index = self
+            .participants
+            .iter()
+            .position(|p| p == name)
+            .unwrap_or_default();
+        format!("p{index}")
+    }
+
+    /// Renders a Mermaid sequence diagram.
+    fn to_mermaid(&self) -> String {
+        let mut out = String::from("sequenceDiagram\n");
+        for name in &self.participants {
+            let _ = writeln!(
+                out,
+                "    participant {} as {}",
+                self.id(name),
+                mermaid_text(name)
+            );
+        }
+        for message in &self.messages {
+            let from = self.id(&message.from);
+            let label = mermaid_text(&format!("[{}] {}", message.iteration, message.topic));
+            match &message.to {
+                Some(to) => {
+                    let _ = writeln!(out, "    {from}->>{}: {label}", self.id(to));
+                }
+                None => {
+                    let _ = writeln!(out, "    Note over {from}: {label} (no subscriber)");
+                }
+            }
+        }
+        out
+    }
+
+    /// Renders a Graphviz digraph; edge labels are numbered in publish order.
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph flow {\n    rankdir=LR;\n    node [shape=box];\n");
+        for name in &self.participants {
+            let _ = writeln!(out, "    {} [label=\"{}\"];", self.id(name), dot_text(name));
+        }
+        for (seq, message) in self.messages.iter().enumerate() {
+            let from = self.id(&message.from);
+            let label = dot_text(&format!(
+                "{}. [{}] {}",
+                seq + 1,
+                message.iteration,
+                message.topic
+            ));
+            match &message.to {
+                Some(to) => {
+                    let _ = writeln!(out, "    {from} -> {} [label=\"{label}\"];", self.id(to));
+                }
+                None => {
+                    let _ = writeln!(
+                        out,
+                        "    {from} -> {from} [label=\"{label}\", style=dashed];"
+                    );
+                }
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Escapes characters Mermaid treats as syntax in message text.
+fn mermaid_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '#' => out.push_str("#35;"),
+            ';' => out.push_str("#59;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escapes a string for a double-quoted DOT attribute.
+fn dot_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(iteration: u32, hat: &str, topic: &str, triggered: Option<&str>) -> EventRecord {
+        EventRecord {
+            ts: "2026-01-27T12:00:00Z".to_string(),
+            iteration,
+            hat: hat.to_string(),
+            topic: topic.to_string(),
+            triggered: triggered.map(str::to_string),
+            payload: String::new(),
+            blocked_count: None,
+        }
+    }
+
+    fn session() -> Flow {
+        Flow::from_records(&[
+            record(1, "loop", "task.start", Some("planner")),
+            record(1, "planner", "build.task", Some("builder")),
+            // Agent-written record: no hat, attributed to builder
+            record(0, "", "build.done", Some("planner")),
+            record(3, "planner", "notes.saved", None),
+        ])
+    }
+
+    #[test]
+    fn test_flow_attributes_agent_events_to_running_hat() {
+        let flow = session();
+        assert_eq!(flow.participants, ["loop", "planner", "builder"]);
+        assert_eq!(flow.messages[2].from, "builder");
+        assert_eq!(flow.messages[2].iteration, 1);
+        assert_eq!(flow.messages[3].to, None);
+    }
+
+    #[test]
+    fn test_mermaid_sequence_diagram() {
+        let out = session().to_mermaid();
+        assert!(out.starts_with("sequenceDiagram\n"));
+        assert!(out.contains("participant p0 as loop"));
+        assert!(out.contains("p0->>p1: [1] task.start"));
+        assert!(out.contains("p2->>p1: [1] build.done"));
+        assert!(out.contains("Note over p1: [3] notes.saved (no subscriber)"));
+    }
+
+    #[test]
+    fn test_mermaid_text_escapes_entities() {
+        assert_eq!(mermaid_text("a;b#c"), "a#59;b#35;c");
+    }
+
+    #[test]
+    fn test_dot_digraph_numbers_edges() {
+        let out = session().to_dot();
+        assert!(out.starts_with("digraph flow {"));
+        assert!(out.contains("p1 [label=\"planner\"];"));
+        assert!(out.contains("p1 -> p2 [label=\"2. [1] build.task\"];"));
+        assert!(out.contains("p1 -> p1 [label=\"4. [3] notes.saved\", style=dashed];"));
+        assert!(out.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_resolve_session_by_id_and_marker() {
+        let temp = TempDir::new().unwrap();
+        let ralph_dir = temp.path().join(".ralph");
+        fs::create_dir_all(&ralph_dir).unwrap();
+        fs::write(ralph_dir.join("events-20260127-120000.jsonl"), "").unwrap();
+        fs::write(
+            ralph_dir.join("current-events"),
+            ".ralph/events-20260127-120000.jsonl\n",
+        )
+        .unwrap();
+
+        let expected = ralph_dir.join("events-20260127-120000.jsonl");
+        assert_eq!(
+            resolve_session(temp.path(), "20260127-120000").unwrap(),
+            expected
+        );
+        assert_eq!(resolve_session(temp.path(), "current").unwrap(), expected);
+        assert!(resolve_session(temp.path(), "19990101-000000").is_err());
+    }
+}
@@ -216,7 +216,7 @@ fn list_hats<W: Write>(writer: &mut W, registry: &HatRegistry, _use_colors: bool
     Ok(())
 }
 
-fn validate_hats<W: Write>(
+pub(crate) fn validate_hats<W: Write>(
     writer: &mut W,
     config: &RalphConfig,
     registry: &HatRegistry,
@@ -403,7 +403,7 @@ fn render_hat_dag_via_ai(
 
     // Create backend and generate diagram
     let backend = CliBackend::from_name(&backend_name)
-        .map_err(|e| anyhow::anyhow!("Failed to create backend '{}': {}", backend_name, e))?;
+        .with_context(|| format!("Failed to create backend '{backend_name}'"))?;
 
     // Show spinner while generating
     let spinner = ProgressBar::new_spinner();
@@ -487,15 +487,16 @@ fn resolve_backend(flag_override: Option<&str>, config: &RalphConfig) -> Result<
     }
 
     // 3. Auto-detect
-    detect_backend_default().map_err(|e| anyhow::anyhow!("{}", e))
+    Ok(detect_backend_default()?)
 }
 
 /// Validates a backend name.
 fn validate_backend_name(name: &str) -> Result<()> {
     match name {
-        "claude" | "kiro" | "gemini" | "codex" | "amp" | "copilot" | "opencode" | "pi" => Ok(()),
+        "claude" | "kiro" | "gemini" | "codex" | "amp" | "goose" | "copilot" | "opencode"
+        | "pi" => Ok(()),
         _ => Err(anyhow::anyhow!(
-            "Unknown backend: {}\n\nValid backends: claude, kiro, gemini, codex, amp, copilot, opencode, pi",
+            "Unknown backend: {}\n\nValid backends: claude, kiro, gemini, codex, amp, goose, copilot, opencode, pi",
             name
         )),
     }
@@ -1,11 +1,18 @@
 //! Init command implementation for ralph.
 //!
 //! Handles initialization of ralph.yml configuration files, either from
-//! a minimal backend template or from an embedded preset.
+//! a minimal backend template, an embedded preset, a remote project
+//! template repository, or an interactive wizard.
+//!
+//! Generated configs get a `verify:` preset when the project's ecosystem is
+//! recognized (a `Cargo.toml` or `package.json` in the current directory).
 
 use crate::presets::{get_preset, list_presets, preset_names};
+use ralph_core::VerifyPreset;
 use std::fs;
-use std::path::Path;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 /// Errors that can occur during initialization.
 #[derive(Debug, thiserror::Error)]
@@ -21,7 +28,7 @@ pub enum InitError {
     UnknownPreset(String, String),
 
     #[error(
-        "Unknown backend '{0}'. Valid backends: claude, kiro, gemini, codex, amp, copilot, opencode, pi, custom.\nSee: docs/reference/troubleshooting.md#unknown-backend"
+        "Unknown backend '{0}'. Valid backends: claude, kiro, gemini, codex, amp, goose, copilot, opencode, pi, custom.\nSee: docs/reference/troubleshooting.md#unknown-backend"
     )]
     UnknownBackend(String),
 
@@ -30,11 +37,17 @@ pub enum InitError {
 
     #[error("Failed to parse/generate YAML: {0}")]
     YamlError(String),
+
+    #[error("Failed to fetch template '{0}': {1}")]
+    TemplateFetch(String, String),
+
+    #[error("Template '{0}' has no ralph.yml at its root")]
+    InvalidTemplate(String),
 }
 
 /// Valid backend names.
 const VALID_BACKENDS: &[&str] = &[
-    "claude", "kiro", "gemini", "codex", "amp", "copilot", "opencode", "pi", "custom",
+    "claude", "kiro", "gemini", "codex", "amp", "goose", "copilot", "opencode", "pi", "custom",
 ];
 
 /// Generates the minimal config template for a given backend.
@@ -90,7 +103,7 @@ fn check_file_exists(force: bool) -> Result<(), InitError> {
 /// Initializes ralph.yml from a minimal backend template.
 ///
 /// # Arguments
-/// * `backend` - The backend name (claude, kiro, gemini, codex, amp, copilot, opencode, custom)
+/// * `backend` - The backend name (claude, kiro, gemini, codex, amp, goose, copilot, opencode, custom)
 /// * `force` - If true, overwrite existing ralph.yml
 ///
 /// # Errors
@@ -103,12 +116,325 @@ pub fn init_from_backend(backend: &str, force: bool) -> Result<(), InitError> {
 
     check_file_exists(force)?;
 
-    let content = generate_template(backend);
+    let content = with_verify_preset(generate_template(backend), Path::new("."));
     fs::write("ralph.yml", content)?;
 
     Ok(())
 }
 
+/// Starter hat topology offered by `ralph init --interactive`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// Ralph works alone; no hats.
+    Solo,
+    /// Builder implements, reviewer checks.
+    BuilderReviewer,
+    /// Planner breaks the work down, builder implements, reviewer checks.
+    PlannerBuilderReviewer,
+}
+
+const TOPOLOGIES: &[(Topology, &str, &str)] = &[
+    (
+        Topology::Solo,
+        "solo",
+        "Ralph works alone. Simplest; good for small, well-defined tasks.",
+    ),
+    (
+        Topology::BuilderReviewer,
+        "builder-reviewer",
+        "A builder implements, a reviewer checks each change.",
+    ),
+    (
+        Topology::PlannerBuilderReviewer,
+        "planner-builder-reviewer",
+        "A planner splits the work into tasks first. Best for larger features.",
+    ),
+];
+
+/// Answers collected by `ralph init --interactive`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WizardAnswers {
+    pub backend: String,
+    pub topology: Topology,
+    pub max_iterations: u32,
+    pub max_runtime_seconds: u64,
+    pub max_cost_usd: Option<f64>,
+}
+
+/// Walks the user through backend, mode, budgets, and hat topology, then
+/// writes a commented ralph.yml.
+///
+/// Installed backend CLIs are probed first and the first one found is
+/// offered as the default.
+///
+/// # Errors
+/// Returns error if file exists (without force) or it can't be written.
+pub fn init_interactive(force: bool) -> Result<WizardAnswers, InitError> {
+    check_file_exists(force)?;
+
+    print!("Checking for installed backends... ");
+    io::stdout().flush()?;
+    let installed: Vec<&str> = VALID_BACKENDS
+        .iter()
+        .copied()
+        .filter(|b| *b != "custom" && ralph_adapters::is_backend_available(b))
+        .collect();
+    if installed.is_empty() {
+        println!("none found");
+    } else {
+        println!("found {}", installed.join(", "));
+    }
+
+    let answers = run_wizard(&mut io::stdin().lock(), &mut io::stdout(), &installed)?;
+    let content = with_verify_preset(generate_wizard_config(&answers), Path::new("."));
+    fs::write("ralph.yml", content)?;
+    Ok(answers)
+}
+
+/// Asks the wizard questions on `input`/`output`.
+///
+/// Empty answers (and end of input) take the default shown in brackets.
+pub fn run_wizard(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    installed: &[&str],
+) -> io::Result<WizardAnswers> {
+    writeln!(
+        output,
+        "\n1. Backend: the AI CLI Ralph runs each iteration."
+    )?;
+    let backends: Vec<(&str, String)> = VALID_BACKENDS
+        .iter()
+        .map(|b| {
+            let note = if installed.contains(b) {
+                "installed".to_string()
+            } else if *b == "custom" {
+                "any command; set cli.command afterwards".to_string()
+            } else {
+                "not found in PATH".to_string()
+            };
+            (*b, note)
+        })
+        .collect();
+    let default = installed
+        .first()
+        .and_then(|b| VALID_BACKENDS.iter().position(|v| v == b))
+        .unwrap_or(0);
+    let backend = VALID_BACKENDS[choose(input, output, &backends, default)?].to_string();
+
+    writeln!(
+        output,
+        "\n2. Mode: solo Ralph, or a team of hats that hand work to each other via events."
+    )?;
+    let topologies: Vec<(&str, String)> = TOPOLOGIES
+        .iter()
+        .map(|(_, name, about)| (*name, (*about).to_string()))
+        .collect();
+    let topology = TOPOLOGIES[choose(input, output, &topologies, 0)?].0;
+
+    writeln!(
+        output,
+        "\n3. Budgets: the loop stops at whichever limit it hits first."
+    )?;
+    let max_iterations = ask_parsed(input, output, "Max iterations", 100)?;
+    let hours: f64 = ask_parsed(input, output, "Max runtime in hours", 4.0)?;
+    let max_cost_usd = ask(input, output, "Max cost in USD (blank for no limit)", "")?
+        .parse::<f64>()
+        .ok()
+        .filter(|cost| *cost > 0.0);
+
+    Ok(WizardAnswers {
+        backend,
+        topology,
+        max_iterations,
+        max_runtime_seconds: (hours.max(0.0) * 3600.0).round() as u64,
+        max_cost_usd,
+    })
+}
+
+/// Prints numbered options and returns the chosen index.
+fn choose(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    options: &[(&str, String)],
+    default: usize,
+) -> io::Result<usize> {
+    for (i, (name, about)) in options.iter().enumerate() {
+        writeln!(output, "   {}) {name:<26} {about}", i + 1)?;
+    }
+    loop {
+        let answer = ask(input, output, "Choice", options[default].0)?;
+        if let Ok(n) = answer.parse::<usize>()
+            && (1..=options.len()).contains(&n)
+        {
+            return Ok(n - 1);
+        }
+        if let Some(i) = options.iter().position(|(name, _)| *name == answer) {
+            return Ok(i);
+        }
+        writeln!(output, "   Enter a number from 1 to {}.", options.len())?;
+    }
+}
+
+fn ask_parsed<T: std::str::FromStr + std::fmt::Display>(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    default: T,
+) -> io::Result<T> {
+    loop {
+        let answer = ask(input, output, question, &default.to_string())?;
+        match answer.parse() {
+            Ok(value) => return Ok(value),
+            Err(_) => writeln!(output, "   '{answer}' is not a number.")?,
+        }
+    }
+}
+
+/// Reads one answer; empty input and end of input yield `default`.
+fn ask(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    default: &str,
+) -> io::Result<String> {
+    if default.is_empty() {
+        write!(output, "   {question}: ")?;
+    } else {
+        write!(output, "   {question} [{default}]: ")?;
+    }
+    output.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let answer = line.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+/// Renders the commented ralph.yml for the wizard's answers.
+pub fn generate_wizard_config(answers: &WizardAnswers) -> String {
+    let WizardAnswers {
+        backend,
+        topology,
+        max_iterations,
+        max_runtime_seconds,
+        max_cost_usd,
+    } = answers;
+    let hours = *max_runtime_seconds as f64 / 3600.0;
+    let mut budgets = String::new();
+    let mut line = |setting: String, comment: &str| {
+        budgets.push_str(&format!("  {setting:<34}# {comment}\n"));
+    };
+    match topology {
+        Topology::Solo => {}
+        Topology::BuilderReviewer => line(
+            "starting_event: \"build.task\"".to_string(),
+            "Ralph hands the objective to the builder",
+        ),
+        Topology::PlannerBuilderReviewer => line(
+            "starting_event: \"plan.start\"".to_string(),
+            "Ralph hands the objective to the planner",
+        ),
+    }
+    line(
+        format!("max_iterations: {max_iterations}"),
+        "Stop after this many iterations",
+    );
+    line(
+        format!("max_runtime_seconds: {max_runtime_seconds}"),
+        &format!("Stop after {hours:.1} hours"),
+    );
+    match max_cost_usd {
+        Some(cost) => line(
+            format!("max_cost_usd: {cost:.2}"),
+            "Stop after spending this much (USD)",
+        ),
+        None => line(
+            "# max_cost_usd: 10.0".to_string(),
+            "Stop after spending this much (USD)",
+        ),
+    }
+
+    let mut config = format!(
+        r#"# Ralph Orchestrator Configuration
+# Generated by: ralph init --interactive
+# Docs: https://github.com/mikeyobrien/ralph-orchestrator
+
+# Which AI CLI runs each iteration.
+cli:
+  backend: "{backend}"
+
+# The loop: Ralph reads PROMPT.md, works in iterations, and stops when the
+# agent prints the completion promise or a budget runs out.
+event_loop:
+  prompt_file: "PROMPT.md"
+  completion_promise: "LOOP_COMPLETE"
+{budgets}
+# Rules every iteration sees. Add your project's conventions here.
+core:
+  guardrails:
+    - "Fresh context each iteration — save learnings to memories for next time"
+    - "Run the tests before declaring anything done"
+"#
+    );
+
+    match topology {
+        Topology::Solo => config.push_str(
+            r"
+# Solo mode: no hats. To split the work into roles later, add a `hats:`
+# section or re-run `ralph init --interactive`.
+",
+        ),
+        Topology::BuilderReviewer | Topology::PlannerBuilderReviewer => {
+            config.push_str(
+                r"
+# Hats are roles. Each hat wakes up on its `triggers` and hands off by
+# publishing one of its `publishes` events. Ralph coordinates between them.
+hats:
+",
+            );
+            if *topology == Topology::PlannerBuilderReviewer {
+                config.push_str(
+                    r#"  planner:
+    name: "📋 Planner"
+    description: "Breaks the objective into small, testable tasks."
+    triggers: ["plan.start", "review.approved"]
+    publishes: ["build.task"]
+    instructions: |
+      Split the objective into small tasks that can each be built and
+      tested on their own. Publish `build.task` for the next one, or print
+      LOOP_COMPLETE when every task is approved.
+
+"#,
+                );
+            }
+            config.push_str(
+                r#"  builder:
+    name: "🔨 Builder"
+    description: "Implements one task and verifies it."
+    triggers: ["build.task", "review.changes_requested"]
+    publishes: ["build.done", "build.blocked"]
+    instructions: |
+      Implement the task, then run the tests, linter, and type checker.
+      Publish `build.done` with the evidence (`tests: pass, lint: pass,
+      typecheck: pass`), or `build.blocked` explaining what is in the way.
+
+  reviewer:
+    name: "🔍 Reviewer"
+    description: "Reviews each change before it is accepted."
+    triggers: ["build.done"]
+    publishes: ["review.approved", "review.changes_requested"]
+    instructions: |
+      Review the latest change for correctness, tests, and style. Publish
+      `review.approved`, or `review.changes_requested` listing what to fix.
+"#,
+            );
+        }
+    }
+
+    config.push_str("\n# Create PROMPT.md with your task, then run: ralph run\n");
+    config
+}
+
 /// Initializes ralph.yml from an embedded preset.
 ///
 /// # Arguments
@@ -142,12 +468,51 @@ pub fn init_from_preset(
     } else {
         preset.content.to_string()
     };
+    let content = with_verify_preset(content, Path::new("."));
 
     fs::write("ralph.yml", content)?;
 
     Ok(())
 }
 
+/// Detects the verification preset for the project in `dir` from its manifest.
+pub fn detect_verify_preset(dir: &Path) -> Option<VerifyPreset> {
+    if dir.join("Cargo.toml").is_file() {
+        Some(VerifyPreset::Rust)
+    } else if dir.join("package.json").is_file() {
+        Some(VerifyPreset::Node)
+    } else {
+        None
+    }
+}
+
+/// Appends a `verify:` preset for the project in `dir`, unless `content`
+/// already configures verification or the ecosystem isn't recognized.
+fn with_verify_preset(mut content: String, dir: &Path) -> String {
+    let Some(preset) = detect_verify_preset(dir) else {
+        return content;
+    };
+    if content.lines().any(|line| line.starts_with("verify:")) {
+        return content;
+    }
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    let (language, checks) = match preset {
+        VerifyPreset::Rust => ("Rust", "cargo check, clippy, and tests run"),
+        VerifyPreset::Node => ("Node", "npm test and lint run"),
+    };
+    content.push_str(&format!(
+        r"
+# Detected a {language} project. After each iteration {checks}, and the
+# result is published as a `ci.passed` or `ci.failed` event.
+verify: {}
+",
+        preset.as_str()
+    ));
+    content
+}
+
 /// Overrides the backend field in YAML content using regex for surgical replacement.
 /// Preserves all comments and formatting.
 fn override_backend_in_yaml(content: &str, backend: &str) -> Result<String, InitError> {
@@ -191,6 +556,191 @@ fn override_backend_in_yaml(content: &str, backend: &str) -> Result<String, Init
     }
 }
 
+/// Result of initializing from a project template.
+#[derive(Debug, Default)]
+pub struct TemplateInit {
+    /// Files written, relative to the current directory.
+    pub written: Vec<PathBuf>,
+    /// Existing files left untouched (re-run with --force to overwrite).
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Initializes the current directory from a project template repository.
+///
+/// `source` is `gh:org/repo` (GitHub), `gl:org/repo` (GitLab), or any URL or
+/// path `git clone` accepts, optionally followed by `#<ref>`. The template must
+/// have a `ralph.yml` at its root; every file (hats, skills, prompts, ...) is
+/// copied with `{{project_name}}` replaced in contents and paths.
+///
+/// # Arguments
+/// * `source` - Template location
+/// * `project_name` - Value for `{{project_name}}`
+/// * `backend_override` - Optional backend to override the template's backend
+/// * `force` - If true, overwrite existing files
+///
+/// # Errors
+/// Returns error if ralph.yml exists (without force), the template can't be
+/// cloned, or it has no ralph.yml.
+pub fn init_from_template(
+    source: &str,
+    project_name: &str,
+    backend_override: Option<&str>,
+    force: bool,
+) -> Result<TemplateInit, InitError> {
+    if let Some(backend) = backend_override
+        && !VALID_BACKENDS.contains(&backend)
+    {
+        return Err(InitError::UnknownBackend(backend.to_string()));
+    }
+
+    check_file_exists(force)?;
+
+    let checkout = std::env::temp_dir().join(format!(
+        "ralph-template-{}-{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    let result = clone_template(source, &checkout).and_then(|()| {
+        let config = fs::symlink_metadata(checkout.join("ralph.yml"));
+        if !config.is_ok_and(|meta| meta.is_file()) {
+            return Err(InitError::InvalidTemplate(source.to_string()));
+        }
+        copy_template(&checkout, project_name, backend_override, force)
+    });
+    let _ = fs::remove_dir_all(&checkout);
+    result
+}
+
+/// Expands template shorthands into a clone URL and optional ref.
+fn resolve_template_source(source: &str) -> (String, Option<String>) {
+    let (location, reference) = match source.rsplit_once('#') {
+        Some((location, reference)) if !reference.is_empty() => {
+            (location, Some(reference.to_string()))
+        }
+        _ => (source, None),
+    };
+    let url = if let Some(repo) = location.strip_prefix("gh:") {
+        format!("https://github.com/{repo}.git")
+    } else if let Some(repo) = location.strip_prefix("gl:") {
+        format!("https://gitlab.com/{repo}.git")
+    } else {
+        location.to_string()
+    };
+    (url, reference)
+}
+
+fn clone_template(source: &str, target: &Path) -> Result<(), InitError> {
+    let (url, reference) = resolve_template_source(source);
+    let mut command = Command::new("git");
+    command.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(reference) = &reference {
+        command.args(["--branch", reference]);
+    }
+    // Local paths need file:// for --depth to take effect.
+    let url = if Path::new(&url).exists() {
+        format!("file://{}", fs::canonicalize(&url)?.display())
+    } else {
+        url
+    };
+    let output = command
+        .arg("--")
+        .arg(&url)
+        .arg(target)
+        .output()
+        .map_err(|e| InitError::TemplateFetch(source.to_string(), e.to_string()))?;
+    if !output.status.success() {
+        return Err(InitError::TemplateFetch(
+            source.to_string(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Copies the checked-out template into the current directory.
+fn copy_template(
+    checkout: &Path,
+    project_name: &str,
+    backend_override: Option<&str>,
+    force: bool,
+) -> Result<TemplateInit, InitError> {
+    let mut files = Vec::new();
+    collect_template_files(checkout, checkout, &mut files)?;
+    files.sort();
+
+    let mut init = TemplateInit::default();
+    for relative in files {
+        let target = PathBuf::from(substitute(&relative.to_string_lossy(), project_name));
+        // ralph.yml was already checked against --force.
+        if target.exists() && !force && target != Path::new("ralph.yml") {
+            init.skipped.push(target);
+            continue;
+        }
+
+        let bytes = fs::read(checkout.join(&relative))?;
+        let bytes = match String::from_utf8(bytes) {
+            Ok(text) => {
+                let mut text = substitute(&text, project_name);
+                if target == Path::new("ralph.yml")
+                    && let Some(backend) = backend_override
+                {
+                    text = override_backend_in_yaml(&text, backend)?;
+                }
+                text.into_bytes()
+            }
+            Err(e) => e.into_bytes(),
+        };
+
+        if let Some(parent) = target.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, bytes)?;
+        init.written.push(target);
+    }
+    Ok(init)
+}
+
+/// Lists template files relative to `root`, skipping `.git` and symlinks.
+///
+/// A template symlink could point anywhere on this machine, so it is never
+/// followed or copied.
+fn collect_template_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), InitError> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.file_name().is_some_and(|name| name == ".git") {
+            continue;
+        }
+        let file_type = fs::symlink_metadata(&path)?.file_type();
+        if file_type.is_symlink() {
+            continue;
+        }
+        if file_type.is_dir() {
+            collect_template_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Replaces `{{project_name}}` (spaces inside the braces allowed).
+fn substitute(text: &str, project_name: &str) -> String {
+    use regex::Regex;
+    use std::sync::LazyLock;
+
+    static PLACEHOLDER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\{\{\s*project_name\s*\}\}").expect("valid regex"));
+    PLACEHOLDER
+        .replace_all(text, regex::NoExpand(project_name))
+        .into_owned()
+}
+
 /// Formats the list of presets for display.
 pub fn format_preset_list() -> String {
     let mut output = String::from("Available presets:\n\n");
@@ -261,6 +811,79 @@ mod tests {
         );
     }
 
+    fn wizard(input: &str, installed: &[&str]) -> WizardAnswers {
+        let mut output = Vec::new();
+        run_wizard(&mut io::Cursor::new(input), &mut output, installed).expect("wizard runs")
+    }
+
+    #[test]
+    fn test_wizard_defaults_to_first_installed_backend() {
+        let answers = wizard("", &["gemini", "codex"]);
+        assert_eq!(answers.backend, "gemini");
+        assert_eq!(answers.topology, Topology::Solo);
+        assert_eq!(answers.max_iterations, 100);
+        assert_eq!(answers.max_runtime_seconds, 14400);
+        assert_eq!(answers.max_cost_usd, None);
+    }
+
+    #[test]
+    fn test_wizard_accepts_numbers_names_and_retries() {
+        let answers = wizard("12\nbogus\nkiro\n3\nmany\n40\n1.5\n12.5\n", &[]);
+        assert_eq!(answers.backend, "kiro");
+        assert_eq!(answers.topology, Topology::PlannerBuilderReviewer);
+        assert_eq!(answers.max_iterations, 40);
+        assert_eq!(answers.max_runtime_seconds, 5400);
+        assert_eq!(answers.max_cost_usd, Some(12.5));
+    }
+
+    #[test]
+    fn test_wizard_configs_are_valid() {
+        for (topology, _, _) in TOPOLOGIES {
+            let answers = WizardAnswers {
+                backend: "claude".to_string(),
+                topology: *topology,
+                max_iterations: 40,
+                max_runtime_seconds: 5400,
+                max_cost_usd: Some(12.5),
+            };
+            let content = generate_wizard_config(&answers);
+            let config: ralph_core::RalphConfig =
+                serde_yaml::from_str(&content).expect("wizard config parses");
+            config.validate().expect("wizard config validates");
+
+            assert_eq!(config.cli.backend, "claude");
+            assert_eq!(config.event_loop.max_iterations, 40);
+            assert_eq!(config.event_loop.max_cost_usd, Some(12.5));
+            let hats = match topology {
+                Topology::Solo => 0,
+                Topology::BuilderReviewer => 2,
+                Topology::PlannerBuilderReviewer => 3,
+            };
+            assert_eq!(config.hats.len(), hats);
+        }
+    }
+
+    #[test]
+    fn test_verify_preset_detected_from_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = generate_template("claude");
+        assert_eq!(with_verify_preset(config.clone(), dir.path()), config);
+
+        fs::write(dir.path().join("package.json"), "{}").unwrap();
+        assert_eq!(detect_verify_preset(dir.path()), Some(VerifyPreset::Node));
+        fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+        assert_eq!(detect_verify_preset(dir.path()), Some(VerifyPreset::Rust));
+
+        let with_verify = with_verify_preset(config, dir.path());
+        assert!(with_verify.ends_with("verify: rust\n"));
+        let parsed: ralph_core::RalphConfig = serde_yaml::from_str(&with_verify).unwrap();
+        assert_eq!(parsed.verify.preset, Some(VerifyPreset::Rust));
+
+        // An existing verify section is left alone
+        let custom = "verify:\n  command: \"make check\"\n".to_string();
+        assert_eq!(with_verify_preset(custom.clone(), dir.path()), custom);
+    }
+
     #[test]
     fn test_unknown_backend_error() {
         // We can't actually test file operations without filesystem mocking,
@@ -332,4 +955,180 @@ cli:
         assert!(result.contains("# This is a preset"));
         assert!(result.contains("# With helpful comments"));
     }
+
+    #[test]
+    fn test_resolve_template_source() {
+        assert_eq!(
+            resolve_template_source("gh:acme/ralph-template-rust"),
+            (
+                "https://github.com/acme/ralph-template-rust.git".to_string(),
+                None
+            )
+        );
+        assert_eq!(
+            resolve_template_source("gl:acme/tpl#v2"),
+            (
+                "https://gitlab.com/acme/tpl.git".to_string(),
+                Some("v2".to_string())
+            )
+        );
+        assert_eq!(
+            resolve_template_source("../local-template"),
+            ("../local-template".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_substitute_project_name() {
+        assert_eq!(
+            substitute("name: {{project_name}} / {{ project_name }}", "demo"),
+            "name: demo / demo"
+        );
+        assert_eq!(substitute("$1 {{x}}", "demo"), "$1 {{x}}");
+    }
+
+    #[test]
+    fn test_init_from_template_copies_and_substitutes() {
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let template = temp_dir.path().join("template");
+        fs::create_dir_all(template.join(".claude/skills/{{project_name}}-style")).unwrap();
+        fs::write(
+            template.join("ralph.yml"),
+            "cli:\n  backend: \"claude\"\nevent_loop:\n  prompt_file: PROMPT.md\n",
+        )
+        .unwrap();
+        fs::write(template.join("PROMPT.md"), "Build {{project_name}}.\n").unwrap();
+        fs::write(
+            template.join(".claude/skills/{{project_name}}-style/SKILL.md"),
+            "Style guide for {{ project_name }}\n",
+        )
+        .unwrap();
+        for args in [
+            &["init", "--quiet"][..],
+            &["add", "."],
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@test.local",
+                "commit",
+                "--quiet",
+                "-m",
+                "init",
+            ],
+        ] {
+            let status = Command::new("git")
+                .args(args)
+                .current_dir(&template)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("PROMPT.md"), "existing prompt\n").unwrap();
+        let _cwd = CwdGuard::set(&project);
+
+        let init = init_from_template(&template.to_string_lossy(), "demo", Some("gemini"), false)
+            .expect("init_from_template succeeds");
+
+        assert_eq!(init.skipped, [PathBuf::from("PROMPT.md")]);
+        assert!(
+            fs::read_to_string("ralph.yml")
+                .unwrap()
+                .contains("backend: \"gemini\"")
+        );
+        assert_eq!(
+            fs::read_to_string("PROMPT.md").unwrap(),
+            "existing prompt\n"
+        );
+        assert_eq!(
+            fs::read_to_string(".claude/skills/demo-style/SKILL.md").unwrap(),
+            "Style guide for demo\n"
+        );
+        assert!(!Path::new(".git").exists());
+    }
+
+    #[test]
+    fn test_init_from_template_requires_ralph_yml() {
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let template = temp_dir.path().join("template");
+        fs::create_dir_all(&template).unwrap();
+        fs::write(template.join("README.md"), "no config\n").unwrap();
+        let status = Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(&template)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let status = Command::new("git")
+            .args([
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@test.local",
+                "commit",
+                "--quiet",
+                "--allow-empty",
+                "-m",
+                "init",
+            ])
+            .current_dir(&template)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        let _cwd = CwdGuard::set(&project);
+
+        let err = init_from_template(&template.to_string_lossy(), "demo", None, false)
+            .expect_err("template without ralph.yml");
+        assert!(matches!(err, InitError::InvalidTemplate(_)));
+        assert!(!Path::new("ralph.yml").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_init_from_template_skips_symlinks() {
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let secret = temp_dir.path().join("secret.txt");
+        fs::write(&secret, "do not copy\n").unwrap();
+        let template = temp_dir.path().join("template");
+        fs::create_dir_all(&template).unwrap();
+        fs::write(template.join("ralph.yml"), "cli:\n  backend: \"claude\"\n").unwrap();
+        std::os::unix::fs::symlink(&secret, template.join("creds.txt")).unwrap();
+        for args in [
+            &["init", "--quiet"][..],
+            &["add", "."],
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@test.local",
+                "commit",
+                "--quiet",
+                "-m",
+                "init",
+            ],
+        ] {
+            let status = Command::new("git")
+                .args(args)
+                .current_dir(&template)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        let _cwd = CwdGuard::set(&project);
+
+        let init = init_from_template(&template.to_string_lossy(), "demo", None, false)
+            .expect("init_from_template succeeds");
+
+        assert_eq!(init.written, [PathBuf::from("ralph.yml")]);
+        assert!(fs::symlink_metadata("creds.txt").is_err());
+    }
 }
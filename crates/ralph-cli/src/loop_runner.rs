@@ -6,34 +6,66 @@
 
 use anyhow::{Context, Result};
 use ralph_adapters::{
-    CliBackend, CliExecutor, ConsoleStreamHandler, OutputFormat as BackendOutputFormat,
-    PrettyStreamHandler, PtyConfig, PtyExecutor, QuietStreamHandler, TuiStreamHandler,
+    API_BACKEND, ApiBackend, ApiResult, CliBackend, CliExecutor, ConsoleStreamHandler,
+    ContainerEnvironment, OutputFormat as BackendOutputFormat, ParallelHat, ParallelRequest,
+    PrettyStreamHandler, PtyConfig, PtyExecutionResult, PtyExecutor, QuietStreamHandler,
+    ResponseCache, ResultRecorder, ScoutRequest, SessionResult, SpeculativeRequest, StreamHandler,
+    TuiStreamHandler, combine_outputs, resolve_hat_backend, resolve_hat_cli, run_parallel_hats,
+    run_scouts, run_speculative, total_usage,
 };
+use ralph_core::bootstrap::{SESSION_SUMMARY_FILE, SessionBootstrap};
+use ralph_core::encryption::ArtifactKey;
+use ralph_core::repro::{self, IterationManifest};
+use ralph_core::shell_tool;
+use ralph_core::state_store::StateSync;
 use ralph_core::{
-    CompletionAction, EventLogger, EventLoop, EventParser, EventRecord, LoopCompletionHandler,
-    LoopContext, LoopHistory, LoopRegistry, MergeQueue, RalphConfig, Record, SessionRecorder,
-    SummaryWriter, TerminationReason,
+    CompletionAction, EventLogger, EventLoop, EventParser, EventRecord, EventWriter, FileLock,
+    FileLockGuard, LoopCompletionHandler, LoopContext, LoopHistory, LoopRegistry, LoopStatus,
+    MergeQueue, RalphConfig, Record, RepoLock, RepoLockGuard, RepoLockOwner, SessionRecorder,
+    SummaryWriter, SurveyApproval, TerminationReason,
 };
 use ralph_proto::{Event, HatId};
 use ralph_tui::Tui;
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fs::{self, File};
 use std::io::{BufWriter, IsTerminal, stdin, stdout};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
+use crate::bridge::BrokerBridge;
+use crate::dashboard::{Dashboard, IterationRecord};
 use crate::display::{build_tui_hat_map, print_iteration_separator, print_termination};
+use crate::event_hooks::{EventHooks, HookTasks};
 use crate::process_management;
 use crate::{ColorMode, Verbosity};
 
+/// Takes the repo lock for a run in `workspace_root`.
+///
+/// Returns `None` when another run of the same user on this host already
+/// holds it. Fails with the owner's details when someone else's run does,
+/// unless `force` takes the lock over.
+pub(crate) fn acquire_repo_lock(
+    workspace_root: &Path,
+    prompt_summary: &str,
+    force: bool,
+) -> Result<Option<RepoLockGuard>> {
+    let session = std::env::var(crate::detach::DETACHED_SESSION_ENV)
+        .unwrap_or_else(|_| crate::serve::generate_session_id());
+    let owner = RepoLockOwner::current(session, prompt_summary);
+    Ok(RepoLock::acquire(workspace_root, owner, force)?)
+}
+
 /// Outcome of executing a prompt via PTY or CLI executor.
 pub(crate) struct ExecutionOutcome {
     pub output: String,
     pub success: bool,
     pub termination: Option<TerminationReason>,
+    /// Cost and tokens reported by the backend, when it reports them.
+    pub usage: Option<ralph_core::Usage>,
 }
 
 /// Core loop implementation supporting both fresh start and continue modes.
@@ -45,6 +77,11 @@ pub(crate) struct ExecutionOutcome {
 /// * `record_session` - If provided, records all events to the specified JSONL file for replay testing.
 /// * `auto_merge_override` - Explicit auto-merge setting. If `Some(false)`, disables auto-merge
 ///   (equivalent to `--no-auto-merge`). If `None`, uses `config.features.auto_merge`.
+/// * `repo_lock` - The repo lock held for this run, if any. The loop stops if another
+///   user takes it over, and releases it on return.
+///
+/// `on_event` hooks still running when the loop ends (typically for
+/// `loop.terminate`) get a bounded drain before this returns.
 pub async fn run_loop_impl(
     config: RalphConfig,
     color_mode: ColorMode,
@@ -55,6 +92,39 @@ pub async fn run_loop_impl(
     loop_context: Option<LoopContext>,
     custom_args: Vec<String>,
     auto_merge_override: Option<bool>,
+    repo_lock: Option<RepoLockGuard>,
+) -> Result<TerminationReason> {
+    let hook_tasks = HookTasks::default();
+    let result = Box::pin(run_loop(
+        config,
+        color_mode,
+        resume,
+        enable_tui,
+        verbosity,
+        record_session,
+        loop_context,
+        custom_args,
+        auto_merge_override,
+        repo_lock,
+        hook_tasks.clone(),
+    ))
+    .await;
+    hook_tasks.drain().await;
+    result
+}
+
+async fn run_loop(
+    config: RalphConfig,
+    color_mode: ColorMode,
+    resume: bool,
+    enable_tui: bool,
+    verbosity: Verbosity,
+    record_session: Option<PathBuf>,
+    loop_context: Option<LoopContext>,
+    custom_args: Vec<String>,
+    auto_merge_override: Option<bool>,
+    repo_lock: Option<RepoLockGuard>,
+    hook_tasks: HookTasks,
 ) -> Result<TerminationReason> {
     // Set up process group leadership per spec
     // "The orchestrator must run as a process group leader"
@@ -103,6 +173,15 @@ pub async fn run_loop_impl(
         .clone()
         .unwrap_or_else(|| LoopContext::primary(config.core.workspace_root.clone()));
 
+    // Restore agent state persisted by an earlier run (e.g. on another CI runner)
+    let state_sync = StateSync::from_config(&config.state_store, ctx.ralph_dir());
+    if let Some(sync) = &state_sync {
+        match ralph_core::utils::run_blocking(|| sync.pull()) {
+            Ok(restored) => info!(store = %sync.describe(), restored, "Restored agent state"),
+            Err(e) => warn!(store = %sync.describe(), error = %e, "Failed to restore agent state"),
+        }
+    }
+
     // Write loop ID to marker file for task ownership tracking.
     // For worktree loops, use the loop_id; for primary loops, generate one.
     // This file is read by `ralph tools task add` to tag new tasks.
@@ -114,6 +193,23 @@ pub async fn run_loop_impl(
     fs::write(&loop_id_marker, &loop_id).context("Failed to write current-loop-id marker")?;
     debug!(loop_id = %loop_id, marker = ?loop_id_marker, "Wrote loop ID marker file");
 
+    // Backend output is copied to <session_dir>/iter-<n>.out while each iteration runs
+    let session_dir = ctx.sessions_dir().join(&loop_id);
+    // Commits after this one are reported as the run's work in ralph-run.json
+    let base_commit = ralph_core::get_head_sha(ctx.workspace()).ok();
+    let recording_path = record_session.clone();
+    // Inputs shared by every iteration's manifest; versions are probed once per command
+    let config_hash = repro::config_hash(&config);
+    let mut backend_versions: HashMap<String, Option<String>> = HashMap::new();
+
+    // Fetch configured credentials once; every backend process gets them in its environment
+    let credentials = ralph_core::credentials::resolve(&config, ctx.workspace())?;
+    if !credentials.is_empty() {
+        info!("Loaded {} credential(s) for backends", credentials.len());
+    }
+    // Session artifacts are sealed with this key; without it, the run stops here
+    let artifact_key = ArtifactKey::resolve(&config.encryption, &credentials)?;
+
     // For fresh runs (not resume), generate a unique timestamped events file
     // This prevents stale events from previous runs polluting new runs (issue #82)
     // The marker file `.ralph/current-events` coordinates path between Ralph and agents
@@ -140,10 +236,28 @@ pub async fn run_loop_impl(
                 scratchpad_path
             );
         }
+
+        // Amendments steer the run they were made for, not the next objective
+        let amendments_path = ctx.amendments_path();
+        if amendments_path.exists() {
+            fs::remove_file(&amendments_path)
+                .with_context(|| format!("Failed to clear amendments: {:?}", amendments_path))?;
+        }
+    } else if let Some(key) = &artifact_key {
+        // The previous run sealed the journal this one appends to
+        let events_path = ctx.current_events_path();
+        key.open_file(&events_path)
+            .with_context(|| format!("Failed to decrypt {}", events_path.display()))?;
     }
 
     // Initialize event loop with context for proper path resolution
     let mut event_loop = EventLoop::with_context(config.clone(), ctx.clone());
+    event_loop.ensure_extensions_loaded()?;
+    // Sealed with the session when encryption is on
+    let diagnostics_dir = event_loop.diagnostics_dir().map(Path::to_path_buf);
+
+    // Child loops re-invoke this binary so they run the same ralph version
+    let ralph_bin = std::env::current_exe().unwrap_or_else(|_| PathBuf::from("ralph"));
 
     // Inject robot service (Telegram) for human-in-the-loop communication
     if config.robot.enabled
@@ -153,9 +267,68 @@ pub async fn run_loop_impl(
         event_loop.set_robot_service(service);
     }
 
+    // Phase changes, topology drift, and pauses are detected inside the event loop;
+    // record them like the runner's own lifecycle events
+    {
+        use ralph_core::lifecycle::{
+            PAUSED_TOPIC, PHASE_STARTED_TOPIC, Paused, PhaseStarted, RESUMED_TOPIC, Resumed,
+            TOPOLOGY_DRIFT_TOPIC, TopologyDrift,
+        };
+        let logger = std::sync::Mutex::new(EventLogger::from_context(&ctx));
+        event_loop.add_observer(move |event: &Event| {
+            let iteration = match event.topic.as_str() {
+                PHASE_STARTED_TOPIC => serde_json::from_str::<PhaseStarted>(&event.payload)
+                    .map_or(0, |phase| phase.iteration),
+                TOPOLOGY_DRIFT_TOPIC => serde_json::from_str::<TopologyDrift>(&event.payload)
+                    .map_or(0, |drift| drift.iteration),
+                PAUSED_TOPIC => serde_json::from_str::<Paused>(&event.payload)
+                    .map_or(0, |paused| paused.iteration),
+                RESUMED_TOPIC => serde_json::from_str::<Resumed>(&event.payload)
+                    .map_or(0, |resumed| resumed.iteration),
+                _ => return,
+            };
+            if let Ok(mut logger) = logger.lock() {
+                log_lifecycle_event(&mut logger, iteration, event);
+            }
+        });
+    }
+
+    // Run on_event shell hooks for matching published events
+    if let Some(hooks) =
+        EventHooks::from_config(&config.on_event, ctx.workspace().to_path_buf(), hook_tasks)
+    {
+        event_loop.add_observer(hooks.observer());
+    }
+
+    // Bridges live until the loop returns; dropping them flushes queued events
+    let _bridges: Vec<BrokerBridge> = config
+        .bridges
+        .iter()
+        .filter_map(|bridge| {
+            let events_ctx = ctx.clone();
+            BrokerBridge::start(
+                bridge,
+                &format!("ralph:{loop_id}"),
+                Arc::new(move || events_ctx.current_events_path()),
+            )
+        })
+        .inspect(|bridge| event_loop.add_observer(bridge.observer()))
+        .collect();
+
     // Capture the robot service shutdown flag so signal handlers can interrupt wait_for_response()
     let robot_shutdown = event_loop.robot_shutdown_flag();
 
+    // Carry a previous session's summary and unfinished tasks into the first prompt
+    if !resume && let Some(source) = &config.event_loop.bootstrap_from {
+        let bootstrap = SessionBootstrap::load(&ctx, source, &loop_id)?;
+        info!(
+            "Bootstrapping from session {} ({} unfinished tasks)",
+            bootstrap.session,
+            bootstrap.unfinished_tasks.len()
+        );
+        event_loop.set_bootstrap(bootstrap);
+    }
+
     // For resume mode, we initialize with a different event topic
     // This tells the planner to read existing scratchpad rather than creating a new one
     if resume {
@@ -193,15 +366,13 @@ pub async fn run_loop_impl(
     // Initialize event logger for debugging (uses context for path resolution)
     let mut event_logger = EventLogger::from_context(&ctx);
 
-    // Log initial event (use configured starting_event or default to task.start/task.resume)
-    let default_start_topic = if resume { "task.resume" } else { "task.start" };
-    let start_topic = config
-        .event_loop
-        .starting_event
-        .as_deref()
-        .unwrap_or(default_start_topic);
+    // Log initial event: task.resume, or the event initialize() published
     let start_triggered = "planner"; // Default triggered hat for backward compat
-    let start_event = Event::new(start_topic, &prompt_content);
+    let start_event = if resume {
+        Event::new("task.resume", &prompt_content)
+    } else {
+        event_loop.start_event(&prompt_content)
+    };
     let start_record =
         EventRecord::new(0, "loop", &start_event, Some(&HatId::new(start_triggered)));
     if let Err(e) = event_logger.log(&start_record) {
@@ -210,13 +381,33 @@ pub async fn run_loop_impl(
 
     // Create backend from config - TUI mode uses the same backend as non-TUI
     // The TUI is an observation layer that displays output, not a different mode
-    let mut backend = CliBackend::from_config(&config.cli).map_err(|e| anyhow::Error::new(e))?;
+    let mut backend = CliBackend::from_config(&config.cli)
+        .map_err(|e| anyhow::Error::new(e))?
+        .with_env_vars(&credentials);
 
     // Append custom args from CLI if provided (e.g., `ralph run -b opencode -- --model="some-model"`)
     if !custom_args.is_empty() {
         backend.args.extend(custom_args);
     }
 
+    // Check the backend round trip before iteration 1 spends real budget
+    if config.event_loop.self_test {
+        run_self_test(&config, &backend, &ctx, verbosity).await?;
+    }
+
+    if config.search_index.enabled {
+        refresh_search_index(&config, ctx.workspace());
+    }
+
+    // Survey the repo and plan before the main loop spends its budget (fresh runs only)
+    if config.survey.enabled
+        && !resume
+        && !run_survey(&config, &backend, &ctx, &prompt_content, verbosity).await?
+    {
+        info!("Survey plan not approved. The loop was not started.");
+        return Ok(TerminationReason::Stopped);
+    }
+
     // Create PTY executor if using interactive mode
     let mut pty_executor = if use_pty {
         let idle_timeout_secs = if user_interactive {
@@ -230,7 +421,7 @@ pub async fn run_loop_impl(
             workspace_root: config.core.workspace_root.clone(),
             ..PtyConfig::from_env()
         };
-        Some(PtyExecutor::new(backend.clone(), pty_config))
+        Some(PtyExecutor::new(backend.clone(), pty_config).with_limits(config.cli.limits))
     } else {
         None
     };
@@ -251,7 +442,7 @@ pub async fn run_loop_impl(
         let tui = Tui::new()
             .with_hat_map(hat_map)
             .with_termination_signal(terminated_rx)
-            .with_events_path(resolve_current_events_path(&ctx));
+            .with_events_path(ctx.current_events_path());
 
         // Get shared state and guidance queue before spawning (for content streaming)
         let state = tui.state();
@@ -283,6 +474,23 @@ pub async fn run_loop_impl(
         s.max_iterations = Some(config.event_loop.max_iterations);
     }
 
+    // Serve the browser dashboard if enabled. It observes the same bus as the TUI
+    // and controls the loop through the .ralph signal files.
+    let dashboard = if config.dashboard.enabled {
+        let dashboard = Dashboard::new(
+            config.core.workspace_root.clone(),
+            config.event_loop.max_iterations,
+        );
+        event_loop.add_observer(dashboard.observer());
+        match dashboard.serve(&config.dashboard.bind).await {
+            Ok(addr) => info!("Dashboard available at http://{}", addr),
+            Err(e) => warn!("Dashboard not started: {:#}", e),
+        }
+        Some(dashboard)
+    } else {
+        None
+    };
+
     // Spawn signal handlers AFTER TUI initialization to avoid deadlock
     // (TUI must enter raw mode and create EventStream before signal handlers are registered)
 
@@ -326,6 +534,15 @@ pub async fn run_loop_impl(
             let mut sighup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
                 .expect("Failed to register SIGHUP handler");
             sighup.recv().await;
+            if std::env::var_os(crate::detach::DETACHED_SESSION_ENV).is_some() {
+                // Detached runs outlive the terminal that started them
+                loop {
+                    debug!("SIGHUP received in detached run, ignoring");
+                    if sighup.recv().await.is_none() {
+                        return;
+                    }
+                }
+            }
             warn!("SIGHUP received (terminal closed), terminating immediately...");
             if let Some(ref flag) = robot_shutdown_sighup {
                 flag.store(true, std::sync::atomic::Ordering::Relaxed);
@@ -334,6 +551,27 @@ pub async fn run_loop_impl(
         });
     }
 
+    // Spawn task to listen for CTRL_BREAK and console close (Windows only)
+    #[cfg(windows)]
+    {
+        let interrupt_tx_ctrl = interrupt_tx.clone();
+        let robot_shutdown_ctrl = robot_shutdown.clone();
+        tokio::spawn(async move {
+            let mut ctrl_break = tokio::signal::windows::ctrl_break()
+                .expect("Failed to register CTRL_BREAK handler");
+            let mut ctrl_close = tokio::signal::windows::ctrl_close()
+                .expect("Failed to register console close handler");
+            tokio::select! {
+                _ = ctrl_break.recv() => debug!("CTRL_BREAK received, terminating immediately..."),
+                _ = ctrl_close.recv() => warn!("Console closed, terminating immediately..."),
+            }
+            if let Some(ref flag) = robot_shutdown_ctrl {
+                flag.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+            let _ = interrupt_tx_ctrl.send(true);
+        });
+    }
+
     // Log execution mode - hat info already logged by initialize()
     let exec_mode = if user_interactive {
         "interactive"
@@ -345,10 +583,19 @@ pub async fn run_loop_impl(
     // Track the last hat to detect hat changes for logging
     let mut last_hat: Option<HatId> = None;
 
+    // Hats already warned about generation settings their backend ignores
+    let mut generation_warned: std::collections::HashSet<HatId> = std::collections::HashSet::new();
+
     // Track consecutive fallback attempts to prevent infinite loops
     let mut consecutive_fallbacks: u32 = 0;
     const MAX_FALLBACK_ATTEMPTS: u32 = 3;
 
+    const PAUSE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    // When the loop parked waiting for new events (`park_when_idle`)
+    let mut parked_since: Option<Instant> = None;
+    const PARK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
     // Initialize loop history if we have a loop context
     let loop_history = loop_context
         .as_ref()
@@ -392,7 +639,8 @@ pub async fn run_loop_impl(
         }
     }
 
-    // Helper closure to handle termination (writes summary, prints status, records history)
+    // Helper closure to handle termination (writes summary, prints status, records history).
+    // Returns the commit SHA when landing checkpointed the loop's work.
     let handle_termination = |reason: &TerminationReason,
                               state: &ralph_core::LoopState,
                               scratchpad: &str,
@@ -400,186 +648,309 @@ pub async fn run_loop_impl(
                               context: &Option<LoopContext>,
                               auto_merge: bool,
                               prompt: &str| {
-        // Per spec: Write summary file on termination
-        let summary_writer = SummaryWriter::default();
-        let scratchpad_path = std::path::Path::new(scratchpad);
-        let scratchpad_opt = if scratchpad_path.exists() {
-            Some(scratchpad_path)
-        } else {
-            None
-        };
-
-        // Get final commit SHA if available
-        let final_commit = get_last_commit_info();
+        // Summary, history, merge-queue and git landing work is all blocking
+        // file and git IO; keep it off the async worker so the TUI stays live.
+        ralph_core::utils::run_blocking(|| {
+            if let Some(ref dashboard) = dashboard {
+                dashboard.set_terminated(reason.as_str());
+            }
 
-        if let Err(e) = summary_writer.write(reason, state, scratchpad_opt, final_commit.as_deref())
-        {
-            warn!("Failed to write summary file: {}", e);
-        }
-
-        // Record termination in history
-        if let Some(hist) = history {
-            let reason_str = match reason {
-                TerminationReason::CompletionPromise => "completion_promise",
-                TerminationReason::MaxIterations => "max_iterations",
-                TerminationReason::MaxRuntime => "max_runtime",
-                TerminationReason::MaxCost => "max_cost",
-                TerminationReason::ConsecutiveFailures => "consecutive_failures",
-                TerminationReason::LoopThrashing => "loop_thrashing",
-                TerminationReason::ValidationFailure => "validation_failure",
-                TerminationReason::Stopped => "stopped",
-                TerminationReason::Interrupted => "interrupted",
-                TerminationReason::RestartRequested => "restart_requested",
+            // Per spec: Write summary file on termination
+            let summary_writer = SummaryWriter::default();
+            let scratchpad_path = std::path::Path::new(scratchpad);
+            let scratchpad_opt = if scratchpad_path.exists() {
+                Some(scratchpad_path)
+            } else {
+                None
             };
 
-            if matches!(reason, TerminationReason::Interrupted) {
-                if let Err(e) = hist.record_terminated("SIGTERM") {
-                    warn!("Failed to record termination in history: {}", e);
+            // Get final commit SHA if available
+            let final_commit = get_last_commit_info();
+
+            if let Err(e) =
+                summary_writer.write(reason, state, scratchpad_opt, final_commit.as_deref())
+            {
+                warn!("Failed to write summary file: {}", e);
+            } else if let Err(e) = fs::create_dir_all(&session_dir).and_then(|()| {
+                // Kept per session for `ralph run --bootstrap-from`
+                fs::copy(
+                    summary_writer.path(),
+                    session_dir.join(SESSION_SUMMARY_FILE),
+                )
+            }) {
+                warn!("Failed to keep summary in session directory: {}", e);
+            }
+
+            // Record termination in history
+            if let Some(hist) = history {
+                let reason_str = match reason {
+                    TerminationReason::CompletionPromise => "completion_promise",
+                    TerminationReason::MaxIterations { .. } => "max_iterations",
+                    TerminationReason::MaxRuntime { .. } => "max_runtime",
+                    TerminationReason::MaxCost { .. } => "max_cost",
+                    TerminationReason::ConsecutiveFailures { .. } => "consecutive_failures",
+                    TerminationReason::LoopThrashing { .. } => "loop_thrashing",
+                    TerminationReason::DelegationLoop { .. } => "delegation_loop",
+                    TerminationReason::ValidationFailure { .. } => "validation_failure",
+                    TerminationReason::Stopped => "stopped",
+                    TerminationReason::Interrupted => "interrupted",
+                    TerminationReason::RestartRequested => "restart_requested",
+                    TerminationReason::IdleTimeout { .. } => "idle_timeout",
+                };
+
+                if matches!(reason, TerminationReason::Interrupted) {
+                    if let Err(e) = hist.record_terminated("SIGTERM") {
+                        warn!("Failed to record termination in history: {}", e);
+                    }
+                } else if let Err(e) =
+                    hist.record_completed_with_context(reason_str, reason.to_json())
+                {
+                    warn!("Failed to record completion in history: {}", e);
                 }
-            } else if let Err(e) = hist.record_completed(reason_str) {
-                warn!("Failed to record completion in history: {}", e);
             }
-        }
 
-        // Handle merge queue state transitions for merge loops
-        // Per spec: CompletionPromise → merged, other → needs-review
-        if let Some(ref loop_id) = merge_loop_id {
-            let repo_root = context
-                .as_ref()
-                .map(|ctx| ctx.repo_root().to_path_buf())
-                .unwrap_or_else(|| PathBuf::from("."));
-            let queue = MergeQueue::new(&repo_root);
-
-            if matches!(reason, TerminationReason::CompletionPromise) {
-                // Get commit SHA from git rev-parse HEAD
-                let commit = Command::new("git")
-                    .args(["rev-parse", "HEAD"])
-                    .output()
-                    .ok()
-                    .and_then(|output| {
-                        if output.status.success() {
-                            String::from_utf8(output.stdout)
-                                .ok()
-                                .map(|s| s.trim().to_string())
-                        } else {
-                            None
-                        }
-                    });
-
-                match commit {
-                    Some(sha) => {
-                        if let Err(e) = queue.mark_merged(loop_id, &sha) {
-                            warn!(loop_id = %loop_id, error = %e, "Failed to mark merge as completed");
-                        } else {
-                            info!(loop_id = %loop_id, commit = %sha, "Merge completed successfully");
+            // Handle merge queue state transitions for merge loops
+            // Per spec: CompletionPromise → merged, other → needs-review
+            if let Some(ref loop_id) = merge_loop_id {
+                let repo_root = context
+                    .as_ref()
+                    .map(|ctx| ctx.repo_root().to_path_buf())
+                    .unwrap_or_else(|| PathBuf::from("."));
+                let queue = MergeQueue::new(&repo_root);
+
+                if matches!(reason, TerminationReason::CompletionPromise) {
+                    // Get commit SHA from git rev-parse HEAD
+                    let commit = Command::new("git")
+                        .args(["rev-parse", "HEAD"])
+                        .output()
+                        .ok()
+                        .and_then(|output| {
+                            if output.status.success() {
+                                String::from_utf8(output.stdout)
+                                    .ok()
+                                    .map(|s| s.trim().to_string())
+                            } else {
+                                None
+                            }
+                        });
+
+                    match commit {
+                        Some(sha) => {
+                            if let Err(e) = queue.mark_merged(loop_id, &sha) {
+                                warn!(loop_id = %loop_id, error = %e, "Failed to mark merge as completed");
+                            } else {
+                                info!(loop_id = %loop_id, commit = %sha, "Merge completed successfully");
+                            }
                         }
-                    }
-                    None => {
-                        // Per spec: "If commit SHA cannot be resolved, mark as needs-review"
-                        if let Err(e) =
-                            queue.mark_needs_review(loop_id, "merge complete but commit not found")
-                        {
-                            warn!(loop_id = %loop_id, error = %e, "Failed to mark merge as needs-review");
-                        } else {
-                            warn!(loop_id = %loop_id, "Merge completed but could not resolve commit SHA");
+                        None => {
+                            // Per spec: "If commit SHA cannot be resolved, mark as needs-review"
+                            if let Err(e) = queue
+                                .mark_needs_review(loop_id, "merge complete but commit not found")
+                            {
+                                warn!(loop_id = %loop_id, error = %e, "Failed to mark merge as needs-review");
+                            } else {
+                                warn!(loop_id = %loop_id, "Merge completed but could not resolve commit SHA");
+                            }
                         }
                     }
-                }
-            } else {
-                // Any non-CompletionPromise termination → needs-review
-                let reason_str = match reason {
-                    TerminationReason::MaxIterations => "max iterations reached",
-                    TerminationReason::MaxRuntime => "max runtime exceeded",
-                    TerminationReason::MaxCost => "max cost exceeded",
-                    TerminationReason::ConsecutiveFailures => "consecutive failures",
-                    TerminationReason::LoopThrashing => "loop thrashing detected",
-                    TerminationReason::ValidationFailure => "validation failure",
-                    TerminationReason::Stopped => "manually stopped",
-                    TerminationReason::Interrupted => "interrupted by signal",
-                    TerminationReason::CompletionPromise => unreachable!(),
-                    TerminationReason::RestartRequested => "restart requested",
-                };
-                if let Err(e) = queue.mark_needs_review(loop_id, reason_str) {
-                    warn!(loop_id = %loop_id, error = %e, "Failed to mark merge as needs-review");
                 } else {
-                    info!(loop_id = %loop_id, reason = reason_str, "Merge marked as needs-review");
+                    // Any non-CompletionPromise termination → needs-review
+                    let reason_str = match reason {
+                        TerminationReason::MaxIterations { .. } => "max iterations reached",
+                        TerminationReason::MaxRuntime { .. } => "max runtime exceeded",
+                        TerminationReason::MaxCost { .. } => "max cost exceeded",
+                        TerminationReason::ConsecutiveFailures { .. } => "consecutive failures",
+                        TerminationReason::LoopThrashing { .. } => "loop thrashing detected",
+                        TerminationReason::DelegationLoop { .. } => "delegation loop detected",
+                        TerminationReason::ValidationFailure { .. } => "validation failure",
+                        TerminationReason::Stopped => "manually stopped",
+                        TerminationReason::Interrupted => "interrupted by signal",
+                        TerminationReason::CompletionPromise => unreachable!(),
+                        TerminationReason::RestartRequested => "restart requested",
+                        TerminationReason::IdleTimeout { .. } => "idle timeout while parked",
+                    };
+                    let reason_str = match reason.detail() {
+                        Some(detail) => format!("{reason_str} ({detail})"),
+                        None => reason_str.to_string(),
+                    };
+                    if let Err(e) = queue.mark_needs_review(loop_id, &reason_str) {
+                        warn!(loop_id = %loop_id, error = %e, "Failed to mark merge as needs-review");
+                    } else {
+                        info!(loop_id = %loop_id, reason = %reason_str, "Merge marked as needs-review");
+                    }
                 }
             }
-        }
 
-        // Handle completion for all loops (landing + merge queue for worktrees)
-        // Per spec: merge loops do NOT enqueue themselves, even if run in worktree context
-        if let Some(ctx) = context {
-            if merge_loop_id.is_none() && matches!(reason, TerminationReason::CompletionPromise) {
-                let handler = LoopCompletionHandler::new(auto_merge);
-                match handler.handle_completion(ctx, prompt) {
-                    Ok(CompletionAction::None) => {
-                        debug!("Loop completed, no action needed");
-                    }
-                    Ok(CompletionAction::Landed { landing }) => {
-                        info!(
-                            committed = landing.committed,
-                            handoff = %landing.handoff_path,
-                            open_tasks = landing.open_task_count,
-                            "Primary loop landed successfully"
-                        );
+            // Handle completion for all loops (landing + merge queue for worktrees)
+            // Per spec: merge loops do NOT enqueue themselves, even if run in worktree context
+            let mut checkpoint = None;
+            if let Some(ctx) = context {
+                if merge_loop_id.is_none() && matches!(reason, TerminationReason::CompletionPromise)
+                {
+                    let mut handler = LoopCompletionHandler::new(auto_merge)
+                        .with_commit_options(config.checkpoint.commit_options());
+                    let attribution = &config.attribution;
+                    if attribution.trailers || attribution.changelog {
+                        let summary = session_summary(ctx, &loop_id, state);
+                        if attribution.changelog {
+                            let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+                            if let Err(e) = ralph_core::attribution::update_changelog(
+                                ctx.workspace(),
+                                &summary.changelog_entry(&date),
+                            ) {
+                                warn!("Failed to update the changelog: {}", e);
+                            }
+                        }
+                        if attribution.trailers {
+                            handler = handler.with_trailers(summary.trailers());
+                        }
                     }
-                    Ok(CompletionAction::Enqueued { loop_id, landing }) => {
-                        info!(loop_id = %loop_id, "Loop queued for auto-merge");
-                        if let Some(ref l) = landing {
-                            debug!(
-                                committed = l.committed,
-                                handoff = %l.handoff_path,
-                                "Landing completed before enqueue"
+                    match handler.handle_completion(ctx, prompt) {
+                        Ok(CompletionAction::None) => {
+                            debug!("Loop completed, no action needed");
+                        }
+                        Ok(CompletionAction::Landed { landing }) => {
+                            checkpoint = landing.commit_sha.clone();
+                            info!(
+                                committed = landing.committed,
+                                handoff = %landing.handoff_path,
+                                open_tasks = landing.open_task_count,
+                                "Primary loop landed successfully"
                             );
                         }
-                        if let Some(hist) = history {
-                            let _ = hist.record_merge_queued();
+                        Ok(CompletionAction::Enqueued { loop_id, landing }) => {
+                            info!(loop_id = %loop_id, "Loop queued for auto-merge");
+                            checkpoint = landing.as_ref().and_then(|l| l.commit_sha.clone());
+                            if let Some(ref l) = landing {
+                                debug!(
+                                    committed = l.committed,
+                                    handoff = %l.handoff_path,
+                                    "Landing completed before enqueue"
+                                );
+                            }
+                            if let Some(hist) = history {
+                                let _ = hist.record_merge_queued();
+                            }
+                            // Worktree loop exits cleanly; merge will be processed
+                            // when the primary loop completes and checks the queue
                         }
-                        // Worktree loop exits cleanly; merge will be processed
-                        // when the primary loop completes and checks the queue
-                    }
-                    Ok(CompletionAction::ManualMerge {
-                        loop_id,
-                        worktree_path,
-                        landing,
-                    }) => {
-                        info!(
-                            loop_id = %loop_id,
-                            "Loop completed. To merge manually: cd {} && git merge",
-                            worktree_path
-                        );
-                        if let Some(ref l) = landing {
-                            debug!(
-                                committed = l.committed,
-                                handoff = %l.handoff_path,
-                                "Landing completed (manual merge mode)"
+                        Ok(CompletionAction::ManualMerge {
+                            loop_id,
+                            worktree_path,
+                            landing,
+                        }) => {
+                            info!(
+                                loop_id = %loop_id,
+                                "Loop completed. To merge manually: cd {} && git merge",
+                                worktree_path
                             );
+                            checkpoint = landing.as_ref().and_then(|l| l.commit_sha.clone());
+                            if let Some(ref l) = landing {
+                                debug!(
+                                    committed = l.committed,
+                                    handoff = %l.handoff_path,
+                                    "Landing completed (manual merge mode)"
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            warn!("Completion handler failed: {}", e);
                         }
-                    }
-                    Err(e) => {
-                        warn!("Completion handler failed: {}", e);
                     }
                 }
+
+                // Re-embed what the checkpoint changed
+                if checkpoint.is_some() && config.search_index.enabled {
+                    refresh_search_index(&config, ctx.workspace());
+                }
+
+                // Handle merge queue processing for primary loop completion
+                if ctx.is_primary() && matches!(reason, TerminationReason::CompletionPromise) {
+                    process_pending_merges(ctx.repo_root());
+                }
+
+                // Always deregister from registry — process is exiting regardless of reason.
+                // CompletionPromise loops are tracked by the merge queue from here on.
+                let registry = LoopRegistry::new(ctx.repo_root());
+                if let Err(e) = registry.deregister_current_process() {
+                    warn!("Failed to deregister loop from registry: {}", e);
+                }
+            }
+
+            // Seal what's left in plain text before state leaves the machine
+            if let (Some(key), Some(ctx)) = (&artifact_key, context) {
+                seal_session(
+                    key,
+                    &[Some(session_dir.as_path()), diagnostics_dir.as_deref()],
+                    &ctx.current_events_path(),
+                    &ctx.workspace().join(shell_tool::AUDIT_LOG),
+                );
             }
 
-            // Handle merge queue processing for primary loop completion
-            if ctx.is_primary() && matches!(reason, TerminationReason::CompletionPromise) {
-                process_pending_merges(ctx.repo_root());
+            // Persist the final scratchpad, summary, and history
+            if let Some(sync) = &state_sync
+                && let Err(e) = sync.push()
+            {
+                warn!(store = %sync.describe(), error = %e, "Failed to persist agent state");
             }
 
-            // Always deregister from registry — process is exiting regardless of reason.
-            // CompletionPromise loops are tracked by the merge queue from here on.
-            let registry = LoopRegistry::new(ctx.repo_root());
-            if let Err(e) = registry.deregister_current_process() {
-                warn!("Failed to deregister loop from registry: {}", e);
+            // Bundle what a postmortem needs when the loop failed
+            let forensics = context
+                .as_ref()
+                .filter(|_| config.forensics.enabled && ralph_core::forensics::applies_to(reason))
+                .and_then(|ctx| {
+                    let events_path = ctx.current_events_path();
+                    let bundle = ralph_core::forensics::Bundle {
+                        loop_id: &loop_id,
+                        reason,
+                        iteration: state.iteration,
+                        workspace: ctx.workspace(),
+                        session_dir: &session_dir,
+                        events_path: &events_path,
+                        config: &config,
+                        key: artifact_key.as_ref(),
+                    };
+                    bundle
+                        .write(&ctx.forensics_dir())
+                        .inspect(|path| info!(path = %path.display(), "Wrote forensics bundle"))
+                        .inspect_err(|e| warn!("Failed to write forensics bundle: {}", e))
+                        .ok()
+                });
+
+            // Describe the run for CI artifact upload and later pipeline steps
+            let events_path = ctx.current_events_path();
+            let manifest_path = ctx.run_manifest_path();
+            let manifest = ralph_core::run_manifest::Run {
+                loop_id: &loop_id,
+                reason,
+                state,
+                workspace: ctx.workspace(),
+                base_commit: base_commit.as_deref(),
+                events_path: &events_path,
+                session_dir: &session_dir,
+                recording: recording_path.as_deref(),
+                forensics: forensics.as_deref(),
+                key: artifact_key.as_ref(),
+            }
+            .manifest();
+            let manifest_written = manifest
+                .write(&manifest_path)
+                .inspect_err(|e| warn!("Failed to write run manifest: {}", e))
+                .is_ok();
+
+            // Print termination info to console (skip in TUI mode - TUI handles display)
+            if !enable_tui {
+                print_termination(reason, state, use_colors);
+                if let Some(path) = forensics {
+                    println!("Forensics bundle: {}", path.display());
+                }
+                if manifest_written {
+                    println!("Run manifest: {}", manifest_path.display());
+                }
             }
-        }
 
-        // Print termination info to console (skip in TUI mode - TUI handles display)
-        if !enable_tui {
-            print_termination(reason, state, use_colors);
-        }
+            checkpoint
+        })
     };
 
     // Main orchestration loop
@@ -629,42 +1000,24 @@ pub async fn run_loop_impl(
                 q.drain(..).collect()
             };
             if !messages.is_empty() {
-                let events_path = resolve_current_events_path(&ctx);
-
-                use std::io::Write;
-                let file = std::fs::OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(&events_path);
-
-                let mut writer = match file {
-                    Ok(f) => std::io::BufWriter::new(f),
-                    Err(e) => {
-                        warn!(error = %e, path = ?events_path, "Failed to open events file for guidance flush");
-                        // Skip flushing - keep loop running
-                        continue;
-                    }
-                };
-
-                for msg in &messages {
-                    let timestamp = chrono::Utc::now().to_rfc3339();
-                    let event = serde_json::json!({
-                        "topic": "human.guidance",
-                        "payload": msg,
-                        "ts": timestamp,
-                    });
-
-                    match serde_json::to_string(&event) {
-                        Ok(line) => {
-                            if writeln!(writer, "{}", line).is_err() {
-                                warn!(path = ?events_path, "Failed writing guidance event line");
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            warn!(error = %e, "Failed serializing guidance event");
-                        }
-                    }
+                let events_path = ctx.current_events_path();
+                let timestamp = chrono::Utc::now().to_rfc3339();
+                let lines: Vec<String> = messages
+                    .iter()
+                    .filter_map(|msg| {
+                        let event = serde_json::json!({
+                            "topic": "human.guidance",
+                            "payload": msg,
+                            "ts": timestamp,
+                        });
+                        serde_json::to_string(&event)
+                            .inspect_err(|e| warn!(error = %e, "Failed serializing guidance event"))
+                            .ok()
+                    })
+                    .collect();
+
+                if let Err(e) = EventWriter::new(&events_path).append_lines(&lines) {
+                    warn!(error = %e, path = ?events_path, "Failed to write guidance events");
                 }
                 info!(
                     count = messages.len(),
@@ -673,8 +1026,13 @@ pub async fn run_loop_impl(
             }
         }
 
-        // Check termination before execution
-        if let Some(reason) = event_loop.check_termination() {
+        // Check termination before execution, including a --force takeover of the repo lock
+        let taken_over = || {
+            let owner = repo_lock.as_ref()?.taken_over_by()?;
+            warn!("Repo lock taken over by {}; stopping", owner.describe());
+            Some(TerminationReason::Stopped)
+        };
+        if let Some(reason) = event_loop.check_termination().or_else(taken_over) {
             // Per spec: Publish loop.terminate event to observers
             let terminate_event = event_loop.publish_terminate_event(&reason);
             log_terminate_event(
@@ -698,13 +1056,96 @@ pub async fn run_loop_impl(
             return Ok(reason);
         }
 
+        // Hold between iterations while a pause is requested (`ralph pause`,
+        // the dashboard, or `.ralph/pause-requested`). Looping back keeps
+        // interrupt and stop handling live.
+        let was_paused = event_loop.state().status == LoopStatus::Paused;
+        let paused = event_loop.update_pause();
+        if paused != was_paused {
+            // The state file shows the pause to `ralph status` and scripts
+            write_snapshots(&event_loop);
+        }
+        if paused {
+            tokio::time::sleep(PAUSE_POLL_INTERVAL).await;
+            continue;
+        }
+
         // Get next hat to execute, with fallback recovery if no pending events
+        event_loop.release_debounced();
         let hat_id = match event_loop.next_hat() {
             Some(id) => {
                 // Reset fallback counter on successful event routing
                 consecutive_fallbacks = 0;
+                if let Some(since) = parked_since.take() {
+                    info!(
+                        parked_seconds = since.elapsed().as_secs(),
+                        "New events arrived, resuming"
+                    );
+                }
                 id.clone()
             }
+            None if event_loop.next_debounce_due().is_some() => {
+                // A debounce window is still open; wait for it rather than
+                // treating the loop as idle.
+                if let Some(due) = event_loop.next_debounce_due() {
+                    let wait = due.saturating_duration_since(Instant::now());
+                    tokio::time::sleep(wait.min(PARK_POLL_INTERVAL)).await;
+                }
+                if let Err(e) = event_loop.process_events_from_jsonl_async().await {
+                    warn!(error = %e, "Failed to read events while debouncing");
+                }
+                continue;
+            }
+            None if config.event_loop.park_when_idle || !event_loop.blockers().is_empty() => {
+                // Park: wait for external events instead of recovering or stopping.
+                // Looping back keeps interrupt, stop, and limit checks live.
+                // With only blocked work left, that means waiting for an unblock.
+                let blocked = !event_loop.blockers().is_empty();
+                let since = *parked_since.get_or_insert_with(|| {
+                    if blocked {
+                        info!("Only blocked work remains; parked until human.unblock");
+                    } else {
+                        info!("No pending events; parked until new events arrive");
+                    }
+                    Instant::now()
+                });
+                let idle_timeout = if blocked {
+                    config.blocked.timeout_seconds
+                } else {
+                    config.event_loop.park_timeout_seconds
+                };
+                if idle_timeout > 0 && since.elapsed() >= Duration::from_secs(idle_timeout) {
+                    let reason = TerminationReason::IdleTimeout {
+                        idle_seconds: since.elapsed().as_secs(),
+                    };
+                    let terminate_event = event_loop.publish_terminate_event(&reason);
+                    log_terminate_event(
+                        &mut event_logger,
+                        event_loop.state().iteration,
+                        &terminate_event,
+                    );
+                    handle_termination(
+                        &reason,
+                        event_loop.state(),
+                        &config.core.scratchpad,
+                        &loop_history,
+                        &loop_context,
+                        auto_merge,
+                        &prompt_content,
+                    );
+                    // Wait for user to exit TUI (press 'q') on natural completion
+                    if let Some(handle) = tui_handle.take() {
+                        let _ = handle.await;
+                    }
+                    return Ok(reason);
+                }
+
+                tokio::time::sleep(PARK_POLL_INTERVAL).await;
+                if let Err(e) = event_loop.process_events_from_jsonl_async().await {
+                    warn!(error = %e, "Failed to read events while parked");
+                }
+                continue;
+            }
             None => {
                 // No pending events - try to recover by injecting a fallback event
                 // This triggers the built-in planner to assess the situation
@@ -777,13 +1218,49 @@ pub async fn run_loop_impl(
 
         let iteration = event_loop.state().iteration + 1;
 
+        // Independent hats with pending events run side by side, headless, as one iteration
+        let mut parallel_hats = if user_interactive {
+            Vec::new()
+        } else {
+            event_loop.next_parallel_hats()
+        };
+        let parallel_backends: Vec<IterationBackend> = parallel_hats
+            .iter()
+            .map(|hat| {
+                resolve_iteration_backend(
+                    &event_loop,
+                    &backend,
+                    &config,
+                    hat,
+                    &credentials,
+                    ctx.workspace(),
+                    &mut generation_warned,
+                )
+            })
+            .collect();
+        if parallel_backends.iter().any(|b| b.name == API_BACKEND) {
+            debug!("Running hats one at a time: the api backend can't run in a parallel batch");
+            parallel_hats.clear();
+        }
+
         // Determine which hat to display in iteration separator
         // When Ralph is coordinating (hat_id == "ralph"), show the active hat being worked on
-        let display_hat = if hat_id.as_str() == "ralph" {
+        let display_hat = if let Some(first) = parallel_hats.first() {
+            first.clone()
+        } else if hat_id.as_str() == "ralph" {
             event_loop.get_active_hat_id()
         } else {
             hat_id.clone()
         };
+        let display_label = if parallel_hats.is_empty() {
+            display_hat.to_string()
+        } else {
+            parallel_hats
+                .iter()
+                .map(HatId::as_str)
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
 
         // Per spec: Print iteration demarcation separator
         // "Each iteration must be clearly demarcated in the output so users can
@@ -792,9 +1269,9 @@ pub async fn run_loop_impl(
         if tui_state.is_none() {
             print_iteration_separator(
                 iteration,
-                display_hat.as_str(),
+                &display_label,
                 event_loop.state().elapsed(),
-                config.event_loop.max_iterations,
+                event_loop.max_iterations(),
                 use_colors,
             );
         }
@@ -813,17 +1290,37 @@ pub async fn run_loop_impl(
         }
         debug!(
             "Iteration {}/{} - {} active",
-            iteration, config.event_loop.max_iterations, hat_id
+            iteration,
+            event_loop.max_iterations(),
+            hat_id
         );
 
-        // Build prompt for this hat
-        let prompt = match event_loop.build_prompt(&hat_id) {
-            Some(p) => p,
-            None => {
-                error!("Failed to build prompt for hat '{}'", hat_id);
-                continue;
+        // Build prompt for this hat, or one per hat of a parallel batch
+        let parallel_prompts = if parallel_hats.is_empty() {
+            Vec::new()
+        } else {
+            event_loop.build_parallel_prompts(&parallel_hats)
+        };
+        let prompt = if parallel_hats.is_empty() {
+            match event_loop.build_prompt(&hat_id) {
+                Some(p) => p,
+                None => {
+                    error!("Failed to build prompt for hat '{}'", hat_id);
+                    continue;
+                }
             }
+        } else if parallel_prompts.is_empty() {
+            // Every hat's events were dropped; the notices are queued for Ralph
+            continue;
+        } else {
+            parallel_prompts
+                .iter()
+                .map(|(hat, prompt)| format!("# {hat}\n\n{prompt}"))
+                .collect::<Vec<_>>()
+                .join("\n\n")
         };
+        // The hats' events are now in flight; let `ralph bus dump` see them
+        write_snapshots(&event_loop);
 
         // In verbose mode, print the full prompt before execution
         if verbosity == Verbosity::Verbose {
@@ -836,85 +1333,86 @@ pub async fn run_loop_impl(
 
         // Execute the prompt (interactive or autonomous mode)
         // Determine which backend to use for this hat and the appropriate timeout
-        // Hat-level backend configuration takes precedence over global cli.backend
+        // A matching routing rule takes precedence over hat-level backend configuration,
+        // which takes precedence over global cli.backend
 
-        // Step 1: Get hat backend configuration for the active hat
+        // Steps 1-2b: routing, hat overrides, credentials, and container environment
         // Use display_hat (the active hat) instead of hat_id ("ralph" in multi-hat mode)
-        let hat_backend_opt = event_loop.get_hat_backend(&display_hat);
-
-        // Step 2: Resolve effective backend and determine backend name for timeout
-        // Note: backend_name_for_timeout is owned String to avoid lifetime issues with hat_backend reference
-        let (effective_backend, backend_name_for_timeout): (CliBackend, String) =
-            match hat_backend_opt {
-                Some(hat_backend) => {
-                    // Hat has custom backend configuration
-                    match CliBackend::from_hat_backend(hat_backend) {
-                        Ok(hat_backend_instance) => {
-                            debug!(
-                                "Using hat-level backend for '{}': {:?}",
-                                display_hat, hat_backend
-                            );
-
-                            // Determine backend name for timeout based on hat backend type
-                            // Use owned String to avoid borrowing issues and improve code clarity
-                            let backend_name = match hat_backend {
-                                ralph_core::HatBackend::Named(name) => name.clone(),
-                                ralph_core::HatBackend::NamedWithArgs { backend_type, .. } => {
-                                    backend_type.clone()
-                                }
-                                ralph_core::HatBackend::KiroAgent { .. } => "kiro".to_string(),
-                                // For Custom backends, extract command name from path
-                                // Handles both Unix ("/usr/bin/codex") and commands with args ("ollama run llama3")
-                                ralph_core::HatBackend::Custom { command, .. } => {
-                                    // First split by whitespace to handle commands with arguments
-                                    // e.g., "ollama run llama3" -> "ollama"
-                                    let base_command =
-                                        command.split_whitespace().next().unwrap_or(command);
-                                    // Then extract filename from path
-                                    // e.g., "/usr/bin/codex" -> "codex"
-                                    std::path::Path::new(base_command)
-                                        .file_name()
-                                        .and_then(|s| s.to_str())
-                                        .unwrap_or("custom")
-                                        .to_string()
-                                }
-                            };
-
-                            (hat_backend_instance, backend_name)
-                        }
-                        Err(e) => {
-                            // Failed to create backend from hat config - fall back to global
-                            warn!(
-                                "Failed to create backend from hat configuration for '{}': {}. Falling back to global backend.",
-                                display_hat, e
-                            );
-                            // IMPORTANT: Use global backend name for timeout since we're using global backend
-                            (backend.clone(), config.cli.backend.clone())
-                        }
-                    }
-                }
-                None => {
-                    // No custom backend - use global configuration
-                    debug!(
-                        "Using global backend for '{}': {}",
-                        display_hat, config.cli.backend
-                    );
-                    (backend.clone(), config.cli.backend.clone())
-                }
-            };
+        let IterationBackend {
+            backend: effective_backend,
+            name: backend_name_for_timeout,
+            route,
+            cli_model: hat_cli_model,
+        } = resolve_iteration_backend(
+            &event_loop,
+            &backend,
+            &config,
+            &display_hat,
+            &credentials,
+            ctx.workspace(),
+            &mut generation_warned,
+        );
+        if let Some(ref history) = loop_history
+            && let Err(e) = history.record_route(
+                iteration,
+                display_hat.as_str(),
+                &backend_name_for_timeout,
+                route.as_ref(),
+            )
+        {
+            warn!("Failed to record iteration route in history: {}", e);
+        }
 
         // Step 3: Get timeout from config based on actual backend being used
-        let timeout_secs = config.adapter_settings(&backend_name_for_timeout).timeout;
-        let timeout = Some(Duration::from_secs(timeout_secs));
+        let timeout = Some(config.iteration_timeout(&backend_name_for_timeout));
+
+        let mut parallel_runs = Vec::with_capacity(parallel_prompts.len());
+        for (hat, prompt) in parallel_prompts {
+            let Some(resolved) = parallel_hats
+                .iter()
+                .position(|h| *h == hat)
+                .map(|index| &parallel_backends[index])
+            else {
+                continue;
+            };
+            if hat != display_hat
+                && let Some(ref history) = loop_history
+                && let Err(e) = history.record_route(
+                    iteration,
+                    hat.as_str(),
+                    &resolved.name,
+                    resolved.route.as_ref(),
+                )
+            {
+                warn!("Failed to record iteration route in history: {}", e);
+            }
+            parallel_runs.push(ParallelHat {
+                hat: hat.to_string(),
+                prompt,
+                backend: resolved.backend.clone(),
+                timeout: Some(config.iteration_timeout(&resolved.name)),
+            });
+        }
 
         // For TUI mode, get the shared lines buffer for this iteration.
         // The buffer is owned by TuiState's IterationBuffer, so writes from
         // TuiStreamHandler appear immediately in the TUI (real-time streaming).
-        let hat_display = event_loop
-            .registry()
-            .get(&display_hat)
-            .map(|hat| hat.name.clone())
-            .unwrap_or_else(|| display_hat.as_str().to_string());
+        let hat_display = if parallel_runs.is_empty() {
+            event_loop
+                .registry()
+                .get(&display_hat)
+                .map(|hat| hat.name.clone())
+                .unwrap_or_else(|| display_hat.as_str().to_string())
+        } else {
+            display_label.clone()
+        };
+
+        if let Some(ref dashboard) = dashboard {
+            dashboard.start_iteration(iteration, &hat_display);
+        }
+        let started_event = event_loop.publish_iteration_started(&display_hat);
+        log_lifecycle_event(&mut event_logger, iteration, &started_event);
+        let iteration_started = std::time::Instant::now();
 
         let tui_lines: Option<Arc<std::sync::Mutex<Vec<ratatui::text::Line<'static>>>>> =
             if let Some(ref state) = tui_state {
@@ -925,18 +1423,195 @@ pub async fn run_loop_impl(
                     state,
                     hat_display.clone(),
                     backend_name_for_timeout.clone(),
-                    config.event_loop.max_iterations,
+                    event_loop.max_iterations(),
                 )
             } else {
                 None
             };
 
+        // Speculative iterations run headless on both `speculative.backends`
+        let speculate = !user_interactive
+            && parallel_runs.is_empty()
+            && config.speculative.applies_to(display_hat.as_str());
+        let speculative_environment = event_loop.get_hat_environment(&display_hat).cloned();
+        // Caching hats run headless so their output can be captured and replayed
+        let cache_responses = !user_interactive
+            && parallel_runs.is_empty()
+            && event_loop.hat_caches_responses(&display_hat);
+
+        // Fan out the hat's read-only scouts and put their answers before the prompt
+        let prompt = match event_loop.get_hat_scouts(&display_hat).cloned() {
+            Some(scouts) if !scouts.prompts.is_empty() && parallel_runs.is_empty() => {
+                let reports = run_scouts(ScoutRequest {
+                    config: &scouts,
+                    default_backend: &effective_backend,
+                    environment: speculative_environment.as_ref(),
+                    workspace: ctx.workspace(),
+                    limits: config.cli.limits,
+                    credentials: &credentials,
+                })
+                .await;
+                let failed = reports.iter().filter(|r| !r.success).count();
+                info!(
+                    "{} scouts finished for {} ({} failed)",
+                    reports.len(),
+                    display_hat,
+                    failed
+                );
+                format!(
+                    "{}{prompt}",
+                    ralph_core::scouts::render_reports(&reports, scouts.max_output_chars)
+                )
+            }
+            _ => prompt,
+        };
+
+        // Other loops' hats in the same mutex group finish before this one starts.
+        // A batch takes its groups in name order, so two loops can't deadlock on them.
+        let running_hats = if parallel_runs.is_empty() {
+            std::slice::from_ref(&display_hat)
+        } else {
+            parallel_hats.as_slice()
+        };
+        let mut mutex_groups: Vec<(&HatId, String)> = running_hats
+            .iter()
+            .filter_map(|hat| Some((hat, event_loop.hat_mutex_group(hat)?.to_string())))
+            .collect();
+        mutex_groups.sort_by(|a, b| a.1.cmp(&b.1));
+        let mut mutex_guards = Vec::with_capacity(mutex_groups.len());
+        for (hat, group) in &mutex_groups {
+            mutex_guards.extend(wait_for_mutex_group(&ctx, hat, group, &interrupt_rx).await);
+            if *interrupt_rx.borrow() {
+                break;
+            }
+        }
+        if !mutex_groups.is_empty() && *interrupt_rx.borrow() {
+            continue;
+        }
+
+        // Record the iteration's inputs so `ralph repro` can rebuild them
+        let prompt_path = session_dir.join(format!("iter-{iteration}.prompt"));
+        let prompt_file = fs::create_dir_all(&session_dir)
+            .and_then(|()| fs::write(&prompt_path, &prompt))
+            .inspect_err(|e| warn!("Failed to save iteration prompt: {}", e))
+            .ok()
+            .map(|()| {
+                let relative = prompt_path.strip_prefix(ctx.workspace());
+                relative.unwrap_or(&prompt_path).display().to_string()
+            });
+        let manifest = IterationManifest {
+            iteration,
+            hat: display_hat.to_string(),
+            head: ralph_core::get_head_sha(ctx.workspace()).ok(),
+            dirty: repro::dirty_fingerprint(ctx.workspace()),
+            backend: backend_name_for_timeout.clone(),
+            backend_version: backend_versions
+                .entry(effective_backend.command.clone())
+                .or_insert_with_key(|command| repro::backend_version(command))
+                .clone(),
+            model: effective_backend.model().map(str::to_string),
+            config_hash: config_hash.clone(),
+            prompt_hash: repro::prompt_hash(&prompt),
+            prompt_file,
+        };
+        log_lifecycle_event(&mut event_logger, iteration, &manifest.to_event());
+
+        let output_log = session_dir.join(format!("iter-{iteration}.out"));
+
         // Race execution against interrupt signal for immediate termination on Ctrl+C
         let mut interrupt_rx_clone = interrupt_rx.clone();
         let interrupt_rx_for_pty = interrupt_rx.clone();
         let tui_lines_for_pty = tui_lines.clone();
+        let mut completed_hats: Vec<(HatId, bool)> = Vec::new();
+        let mut hat_usage: Vec<(HatId, ralph_core::Usage)> = Vec::new();
         let execute_future = async {
-            if use_pty {
+            if !parallel_runs.is_empty() {
+                let runs = run_parallel_hats(ParallelRequest {
+                    hats: &parallel_runs,
+                    workspace: ctx.workspace(),
+                    limits: config.cli.limits,
+                    retry: &config.cli.retry,
+                })
+                .await;
+                for run in runs.iter().filter(|run| run.timed_out) {
+                    warn!("{} timed out; counting the iteration as failed", run.hat);
+                }
+                completed_hats = runs
+                    .iter()
+                    .map(|run| (HatId::new(&run.hat), run.success))
+                    .collect();
+                hat_usage = runs
+                    .iter()
+                    .filter_map(|run| Some((HatId::new(&run.hat), run.usage?)))
+                    .collect();
+                if let Some(total) = total_usage(&runs) {
+                    info!(
+                        "Parallel batch spent ${:.4} across {} hats",
+                        total.cost_usd,
+                        runs.len()
+                    );
+                }
+                let output = combine_outputs(&runs);
+                if let Err(e) = fs::write(&output_log, &output) {
+                    warn!("Failed to save iteration output: {}", e);
+                }
+                Ok(ExecutionOutcome {
+                    output,
+                    success: runs.iter().all(|run| run.success),
+                    termination: None,
+                    // Recorded per hat from `hat_usage`
+                    usage: None,
+                })
+            } else if speculate {
+                let events_path = ctx.current_events_path();
+                let result = run_speculative(SpeculativeRequest {
+                    iteration,
+                    prompt: &prompt,
+                    workspace: ctx.workspace(),
+                    events_path: &events_path,
+                    environment: speculative_environment.as_ref(),
+                    default_backend: &effective_backend,
+                    credentials: &credentials,
+                    config: &config,
+                })
+                .await?;
+                info!(
+                    "Speculative iteration won by {} ({})",
+                    result.winner, result.reason
+                );
+                if let Some(ref history) = loop_history
+                    && let Err(e) = history.record_speculation(
+                        iteration,
+                        display_hat.as_str(),
+                        &result.candidates,
+                        &result.winner,
+                        config.speculative.arbiter.as_str(),
+                        &result.reason,
+                    )
+                {
+                    warn!("Failed to record speculation in history: {}", e);
+                }
+                Ok(ExecutionOutcome {
+                    output: result.output,
+                    success: result.success,
+                    termination: None,
+                    usage: None,
+                })
+            } else if backend_name_for_timeout == API_BACKEND {
+                execute_api(
+                    &config,
+                    &credentials,
+                    route
+                        .as_ref()
+                        .and_then(|route| route.model.as_deref())
+                        .or(hat_cli_model.as_deref()),
+                    &prompt,
+                    timeout,
+                    verbosity,
+                    tui_lines_for_pty,
+                )
+                .await
+            } else if use_pty && !cache_responses {
                 execute_pty(
                     pty_executor.as_mut(),
                     &effective_backend,
@@ -946,17 +1621,46 @@ pub async fn run_loop_impl(
                     interrupt_rx_for_pty,
                     verbosity,
                     tui_lines_for_pty,
+                    &output_log,
                 )
                 .await
             } else {
-                let executor = CliExecutor::new(effective_backend.clone());
+                let mut executor = CliExecutor::new(effective_backend.clone())
+                    .with_limits(config.cli.limits)
+                    .with_retry(config.cli.retry.clone())
+                    .with_output_log(&output_log);
+                if cache_responses {
+                    executor = executor.with_cache(ResponseCache::new(ctx.response_cache_dir()));
+                }
                 let result = executor
                     .execute(&prompt, stdout(), timeout, verbosity == Verbosity::Verbose)
                     .await?;
+                if result.timed_out {
+                    warn!(
+                        "{} timed out after {}s; counting the iteration as failed",
+                        display_hat,
+                        timeout.map_or(0, |t| t.as_secs())
+                    );
+                }
+                if cache_responses {
+                    if result.cached {
+                        info!("Served {} from the response cache", display_hat);
+                    }
+                    if let Some(ref history) = loop_history
+                        && let Err(e) = history.record_cache_lookup(
+                            iteration,
+                            display_hat.as_str(),
+                            result.cached,
+                        )
+                    {
+                        warn!("Failed to record cache lookup in history: {}", e);
+                    }
+                }
                 Ok(ExecutionOutcome {
                     output: result.output,
                     success: result.success,
                     termination: None,
+                    usage: None,
                 })
             }
         };
@@ -1011,15 +1715,71 @@ pub async fn run_loop_impl(
             return Ok(reason);
         }
 
-        let output = outcome.output;
+        let output = postprocess_output(
+            &config,
+            &event_loop.state().last_active_hat_ids,
+            &hat_id,
+            outcome.output,
+            &ctx,
+            &output_log,
+        );
+        drop(mutex_guards);
         let success = outcome.success;
 
+        // A parallel batch reports each hat's spend; other iterations one total
+        let cost_entries: Vec<_> = match outcome.usage {
+            Some(usage) => vec![event_loop.record_usage(&hat_id, usage)],
+            None => hat_usage
+                .iter()
+                .map(|(hat, usage)| event_loop.record_hat_usage(hat, *usage))
+                .collect(),
+        };
+        for entry in &cost_entries {
+            if let Some(ref history) = loop_history
+                && let Err(e) = history.record_cost(entry)
+            {
+                warn!("Failed to record iteration cost in history: {}", e);
+            }
+        }
+
+        if let Some(ref dashboard) = dashboard {
+            dashboard.finish_iteration(IterationRecord {
+                iteration,
+                hat: hat_display.clone(),
+                backend: backend_name_for_timeout.clone(),
+                success,
+                duration_secs: iteration_started.elapsed().as_secs_f64(),
+                cumulative_cost: event_loop.state().cumulative_cost,
+            });
+        }
+        if completed_hats.is_empty() {
+            completed_hats.push((display_hat.clone(), success));
+        }
+        for (hat, hat_success) in &completed_hats {
+            let completed_event =
+                event_loop.publish_hat_completed(hat, *hat_success, iteration_started.elapsed());
+            log_lifecycle_event(&mut event_logger, iteration, &completed_event);
+        }
+
         // Note: TUI lines are now written directly to IterationBuffer during streaming,
         // so no post-execution transfer is needed.
         if let Some(mut s) = tui_state.as_ref().and_then(|state| state.lock().ok()) {
             s.finish_latest_iteration();
         }
 
+        if config.event_loop.trace_events {
+            write_parse_trace(
+                &session_dir,
+                iteration,
+                &display_hat,
+                &output,
+                &event_loop.output_event_parser(),
+            );
+        }
+        if let Some(key) = &artifact_key {
+            seal_iteration(key, &session_dir, iteration);
+        }
+
         // Log events from output before processing
         log_events_from_output(
             &mut event_logger,
@@ -1027,10 +1787,13 @@ pub async fn run_loop_impl(
             &hat_id,
             &output,
             event_loop.registry(),
+            &event_loop.output_event_parser(),
         );
 
         // Process output
-        if let Some(reason) = event_loop.process_output(&hat_id, &output, success) {
+        let termination = event_loop.process_output(&hat_id, &output, success).await;
+        write_snapshots(&event_loop);
+        if let Some(reason) = termination {
             // Per spec: Log "All done! {promise} detected." when completion promise found
             if reason == TerminationReason::CompletionPromise {
                 info!(
@@ -1045,7 +1808,7 @@ pub async fn run_loop_impl(
                 event_loop.state().iteration,
                 &terminate_event,
             );
-            handle_termination(
+            let checkpoint = handle_termination(
                 &reason,
                 event_loop.state(),
                 &config.core.scratchpad,
@@ -1054,6 +1817,14 @@ pub async fn run_loop_impl(
                 auto_merge,
                 &prompt_content,
             );
+            if let Some(commit) = checkpoint {
+                let checkpoint_event = event_loop.publish_checkpoint_created(&commit);
+                log_lifecycle_event(
+                    &mut event_logger,
+                    event_loop.state().iteration,
+                    &checkpoint_event,
+                );
+            }
             // Wait for user to exit TUI (press 'q') on natural completion
             if let Some(handle) = tui_handle.take() {
                 let _ = handle.await;
@@ -1066,14 +1837,34 @@ pub async fn run_loop_impl(
             warn!(error = %e, "Failed to check planning session responses");
         }
 
+        // Revert changes to protected paths and queue a protect.violation event
+        event_loop.enforce_protected_paths();
+
+        // Move the iteration limit with the progress this iteration made
+        event_loop.adjust_iteration_budget();
+
+        // Run verify.command and queue its ci.passed/ci.failed event
+        event_loop.verify_iteration(&display_hat).await;
+
+        // Persist agent state so a killed runner loses at most one iteration
+        if let Some(sync) = &state_sync
+            && let Err(e) = ralph_core::utils::run_blocking(|| sync.push())
+        {
+            warn!(store = %sync.describe(), error = %e, "Failed to persist agent state");
+        }
+
         // Read events from JSONL that agent may have written
         let agent_wrote_events = matches!(
             event_loop
-                .process_events_from_jsonl()
+                .process_events_from_jsonl_async()
+                .await
                 .inspect_err(|e| warn!(error = %e, "Failed to read events from JSONL")),
             Ok(true)
         );
 
+        // Run child loops requested via ralph.spawn_loop and publish their results
+        event_loop.run_child_loops(&ralph_bin).await;
+
         // Inject default_publishes for active hats only when agent wrote no events
         if !agent_wrote_events {
             let active_hats = event_loop.state().last_active_hat_ids.clone();
@@ -1085,6 +1876,8 @@ pub async fn run_loop_impl(
             }
         }
 
+        write_snapshots(&event_loop);
+
         if let Some(reason) = event_loop.check_completion_event() {
             info!(
                 "Completion event {} detected.",
@@ -1097,7 +1890,7 @@ pub async fn run_loop_impl(
                 event_loop.state().iteration,
                 &terminate_event,
             );
-            handle_termination(
+            let checkpoint = handle_termination(
                 &reason,
                 event_loop.state(),
                 &config.core.scratchpad,
@@ -1106,6 +1899,14 @@ pub async fn run_loop_impl(
                 auto_merge,
                 &prompt_content,
             );
+            if let Some(commit) = checkpoint {
+                let checkpoint_event = event_loop.publish_checkpoint_created(&commit);
+                log_lifecycle_event(
+                    &mut event_logger,
+                    event_loop.state().iteration,
+                    &checkpoint_event,
+                );
+            }
             if let Some(handle) = tui_handle.take() {
                 let _ = handle.await;
             }
@@ -1138,6 +1939,280 @@ pub async fn run_loop_impl(
     }
 }
 
+/// The backend one hat's iteration runs on.
+struct IterationBackend {
+    /// Backend with the hat's overrides, credentials, and container applied.
+    backend: CliBackend,
+    /// Adapter name, used for timeouts and the loop history.
+    name: String,
+    /// The routing rule that matched, if any.
+    route: Option<ralph_core::RouteDecision>,
+    /// Model set in the hat's `cli:` block.
+    cli_model: Option<String>,
+}
+
+/// Resolves the backend for `hat`.
+///
+/// A matching routing rule takes precedence over the hat-level backend,
+/// which takes precedence over the hat's `cli:` block and then the global
+/// `cli.backend`. The hat's generation settings, the routed model, the
+/// resolved credentials, and the hat's (or the global) container
+/// environment are applied on top.
+fn resolve_iteration_backend(
+    event_loop: &EventLoop,
+    base: &CliBackend,
+    config: &RalphConfig,
+    hat: &HatId,
+    credentials: &[(String, String)],
+    workspace: &Path,
+    generation_warned: &mut std::collections::HashSet<HatId>,
+) -> IterationBackend {
+    // Step 1: Get the routed or hat-level backend for the hat
+    let route = event_loop.route(hat);
+    let hat_backend_opt = route
+        .as_ref()
+        .and_then(|route| route.backend.as_ref())
+        .or_else(|| event_loop.get_hat_backend(hat));
+
+    // Step 2: Resolve effective backend and determine backend name for timeout
+    // A hat's `cli:` block applies when nothing above picked a backend
+    let hat_cli = event_loop
+        .get_hat_cli(hat)
+        .filter(|_| hat_backend_opt.is_none());
+    let cli_model = hat_cli.and_then(|cli| cli.model.clone());
+    let (backend, name) = match hat_cli {
+        Some(hat_cli) => resolve_hat_cli(base, &config.cli, hat.as_str(), hat_cli),
+        None => resolve_hat_backend(base, &config.cli.backend, hat.as_str(), hat_backend_opt),
+    };
+
+    // Step 2a: Apply the hat's generation settings, then the routed model and credentials
+    let backend = match event_loop.get_hat_generation(hat) {
+        Some(generation) => {
+            let unsupported = backend.unsupported_generation(generation);
+            if !unsupported.is_empty() && generation_warned.insert(hat.clone()) {
+                warn!(
+                    "Backend '{}' ignores {} for hat '{}'",
+                    name,
+                    unsupported.join(", "),
+                    hat
+                );
+            }
+            backend.with_generation(generation)
+        }
+        None => backend,
+    };
+    let backend = match route.as_ref().and_then(|route| route.model.as_deref()) {
+        Some(model) => backend.with_model(model),
+        None => backend,
+    }
+    .with_env_vars(credentials);
+
+    // Step 2b: Run the backend inside the hat's (or the global) container environment
+    let backend = match event_loop.get_hat_environment(hat) {
+        Some(environment) => {
+            debug!("Running '{}' in container image {}", hat, environment.image);
+            backend.with_container(ContainerEnvironment::from_config(environment, workspace))
+        }
+        None => backend,
+    };
+
+    IterationBackend {
+        backend,
+        name,
+        route,
+        cli_model,
+    }
+}
+
+/// Runs the active hats' `postprocess` pipelines over an iteration's output.
+///
+/// Events the steps publish are written to the events file, and the
+/// iteration's output log is rewritten so it matches what gets journaled.
+fn postprocess_output(
+    config: &RalphConfig,
+    active_hats: &[HatId],
+    hat_id: &HatId,
+    output: String,
+    ctx: &LoopContext,
+    output_log: &Path,
+) -> String {
+    let hats = if active_hats.is_empty() {
+        std::slice::from_ref(hat_id)
+    } else {
+        active_hats
+    };
+    let pipeline: Vec<_> = hats
+        .iter()
+        .filter_map(|id| config.hats.get(id.as_str()))
+        .flat_map(|hat| hat.postprocess.iter().cloned())
+        .collect();
+    if pipeline.is_empty() {
+        return output;
+    }
+
+    let processed = ralph_core::utils::run_blocking(|| {
+        ralph_core::postprocess::run(&pipeline, &output, ctx.workspace(), hats[0].as_str())
+    });
+    if !processed.events.is_empty() {
+        let lines: Vec<String> = processed
+            .events
+            .iter()
+            .filter_map(|event| serde_json::to_string(event).ok())
+            .collect();
+        let events_path = ctx.current_events_path();
+        if let Err(e) = EventWriter::new(&events_path).append_lines(&lines) {
+            warn!(error = %e, path = ?events_path, "Failed to write postprocess events");
+        }
+    }
+    if processed.output != output
+        && output_log.exists()
+        && let Err(e) = fs::write(output_log, &processed.output)
+    {
+        warn!(error = %e, "Failed to rewrite the iteration output log");
+    }
+    processed.output
+}
+
+/// Builds or refreshes the workspace's search index, logging what changed.
+///
+/// A failure only costs agents the index, so it is logged, not returned.
+fn refresh_search_index(config: &RalphConfig, workspace: &Path) {
+    match ralph_core::search_index::update(workspace, &config.search_index) {
+        Ok(stats) => info!(
+            embedded = stats.embedded,
+            unchanged = stats.unchanged,
+            removed = stats.removed,
+            "Search index up to date"
+        ),
+        Err(e) => warn!("Failed to update the search index: {}", e),
+    }
+}
+
+/// Sends the self-test prompt through `backend` and fails with a diagnosis
+/// unless the reply comes back with a parseable event.
+async fn run_self_test(
+    config: &RalphConfig,
+    backend: &CliBackend,
+    ctx: &LoopContext,
+    verbosity: Verbosity,
+) -> Result<()> {
+    use ralph_core::self_test::{self, Failure};
+
+    info!("Running backend self-test");
+    let prompt = self_test::build_prompt(config.event_loop.event_syntax);
+    let output_log = ctx.ralph_dir().join("self-test.out");
+
+    // The PTY executor has no time box of its own; stop it through its interrupt channel
+    let (stop_tx, stop_rx) = tokio::sync::watch::channel(false);
+    let run = execute_pty(
+        None,
+        backend,
+        config,
+        &prompt,
+        false,
+        stop_rx,
+        verbosity,
+        None,
+        &output_log,
+    );
+    tokio::pin!(run);
+    let timeout = Duration::from_secs(self_test::TIMEOUT_SECONDS);
+    let finished = tokio::select! {
+        outcome = &mut run => Some(outcome),
+        () = tokio::time::sleep(timeout) => None,
+    };
+
+    let checked = match finished {
+        Some(Ok(outcome)) => {
+            let parser = EventParser::new()
+                .with_syntax(config.event_loop.event_syntax)
+                .with_formats(&config.event_loop.event_formats);
+            self_test::check(&outcome.output, outcome.success, &parser)
+        }
+        Some(Err(e)) => Err(Failure::BackendFailed {
+            excerpt: e.to_string(),
+        }),
+        None => {
+            let _ = stop_tx.send(true);
+            let _ = run.await;
+            Err(Failure::TimedOut {
+                seconds: self_test::TIMEOUT_SECONDS,
+            })
+        }
+    };
+
+    match checked {
+        Ok(()) => {
+            info!("Backend self-test passed");
+            Ok(())
+        }
+        Err(failure) => Err(anyhow::anyhow!(
+            "Backend self-test failed: {failure}\n{}\nFull output: {}",
+            failure.hint(),
+            output_log.display()
+        )),
+    }
+}
+
+/// Runs the survey iteration and returns whether its plan was approved.
+///
+/// The survey answer is appended to the scratchpad whether or not the plan is
+/// approved, so a rejected plan can still be read and edited.
+async fn run_survey(
+    config: &RalphConfig,
+    backend: &CliBackend,
+    ctx: &LoopContext,
+    objective: &str,
+    verbosity: Verbosity,
+) -> Result<bool> {
+    let survey = &config.survey;
+    info!("Surveying the repository before the loop starts");
+
+    let prompt = ralph_core::survey::build_prompt(objective, survey.timeout_seconds);
+    let result = CliExecutor::new(backend.clone())
+        .with_limits(config.cli.limits)
+        .execute(
+            &prompt,
+            stdout(),
+            Some(Duration::from_secs(survey.timeout_seconds)),
+            verbosity == Verbosity::Verbose,
+        )
+        .await?;
+    if result.timed_out {
+        warn!(
+            "Survey hit its {}s time box; keeping what it produced",
+            survey.timeout_seconds
+        );
+    } else if !result.success {
+        warn!("Survey iteration failed; keeping what it produced");
+    }
+
+    let scratchpad = ralph_core::Scratchpad::new(ctx.scratchpad_path());
+    if let Err(e) = scratchpad.append(&ralph_core::survey::scratchpad_entry(&result.output)) {
+        warn!("Failed to write survey to scratchpad: {}", e);
+    }
+
+    match survey.approval {
+        SurveyApproval::Auto => Ok(true),
+        SurveyApproval::Human => {
+            if !stdin().is_terminal() {
+                warn!(
+                    "survey.approval is human but stdin is not a terminal; not starting the loop"
+                );
+                return Ok(false);
+            }
+            eprintln!(
+                "\nSurvey saved to {}. Review the plan there.",
+                ctx.scratchpad_path().display()
+            );
+            eprintln!("Start the loop with this plan? [y/N] ");
+            let mut input = String::new();
+            stdin().read_line(&mut input)?;
+            Ok(ralph_core::survey::is_approval(&input))
+        }
+    }
+}
+
 /// Executes a prompt in PTY mode with raw terminal handling.
 /// Converts PTY termination type to loop termination reason.
 ///
@@ -1179,18 +2254,36 @@ fn convert_termination_type(
 /// relative path like `.ralph/events-YYYYMMDD-HHMMSS.jsonl`.
 ///
 /// Falls back to `ctx.events_path()` if the marker is missing/unreadable.
-fn resolve_current_events_path(ctx: &LoopContext) -> PathBuf {
-    fs::read_to_string(ctx.current_events_marker())
-        .ok()
-        .map(|relative| {
-            let relative = relative.trim().to_string();
-            if std::path::Path::new(&relative).is_relative() {
-                ctx.workspace().join(relative)
-            } else {
-                PathBuf::from(relative)
-            }
+/// Summarizes the session for checkpoint trailers and the changelog.
+fn session_summary(
+    ctx: &LoopContext,
+    loop_id: &str,
+    state: &ralph_core::LoopState,
+) -> ralph_core::attribution::SessionSummary {
+    let records = ralph_core::EventHistory::new(ctx.current_events_path())
+        .read_all()
+        .unwrap_or_default();
+    let started = chrono::Utc::now()
+        - chrono::Duration::from_std(state.started_at.elapsed()).unwrap_or_default();
+    let completed_tasks = ralph_core::TaskStore::load(&ctx.tasks_path())
+        .map(|store| {
+            ralph_core::attribution::tasks_closed_since(store.all(), ctx.loop_id(), started)
         })
-        .unwrap_or_else(|| ctx.events_path())
+        .unwrap_or_default();
+    let mut hats: Vec<String> = state
+        .hat_activation_counts
+        .keys()
+        .map(ToString::to_string)
+        .collect();
+    hats.sort();
+
+    ralph_core::attribution::SessionSummary {
+        session_id: loop_id.to_string(),
+        iterations: state.iteration,
+        hats,
+        topics: ralph_core::attribution::event_topics(&records),
+        completed_tasks,
+    }
 }
 
 fn prepare_tui_iteration(
@@ -1218,6 +2311,7 @@ async fn execute_pty(
     interrupt_rx: tokio::sync::watch::Receiver<bool>,
     verbosity: Verbosity,
     tui_lines: Option<Arc<std::sync::Mutex<Vec<ratatui::text::Line<'static>>>>>,
+    output_log: &Path,
 ) -> Result<ExecutionOutcome> {
     use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 
@@ -1243,10 +2337,13 @@ async fn execute_pty(
             workspace_root: config.core.workspace_root.clone(),
             ..PtyConfig::from_env()
         };
-        temp_executor = PtyExecutor::new(backend.clone(), pty_config);
+        temp_executor =
+            PtyExecutor::new(backend.clone(), pty_config).with_limits(config.cli.limits);
         &mut temp_executor
     };
 
+    exec.set_output_log(Some(output_log.to_path_buf()));
+
     // Set TUI mode flag when TUI is connected (tui_lines is Some)
     // This replaces the broken output_rx.is_none() detection in PtyExecutor
     if tui_lines.is_some() {
@@ -1268,15 +2365,14 @@ async fn execute_pty(
     });
 
     // Run PTY executor with shared interrupt channel
-    let result = if interactive && tui_lines.is_none() {
+    let (result, session) = if interactive && tui_lines.is_none() {
         // Raw interactive mode only when not using TUI (TUI handles its own terminal)
-        exec.run_interactive(prompt, interrupt_rx).await
+        (exec.run_interactive(prompt, interrupt_rx).await, None)
     } else if let Some(lines) = tui_lines {
         // TUI mode: use TuiStreamHandler to capture output for TUI display
         let verbose = verbosity == Verbosity::Verbose;
-        let mut handler = TuiStreamHandler::with_lines(verbose, lines);
-        exec.run_observe_streaming(prompt, interrupt_rx, &mut handler)
-            .await
+        let handler = TuiStreamHandler::with_lines(verbose, lines);
+        observe_streaming(exec, prompt, interrupt_rx, handler).await
     } else {
         // Use streaming handler for non-interactive mode (respects verbosity)
         // Use PrettyStreamHandler for StreamJson backends (Claude) on TTY for markdown rendering
@@ -1286,30 +2382,24 @@ async fn execute_pty(
 
         match verbosity {
             Verbosity::Quiet => {
-                let mut handler = QuietStreamHandler;
-                exec.run_observe_streaming(prompt, interrupt_rx, &mut handler)
-                    .await
+                observe_streaming(exec, prompt, interrupt_rx, QuietStreamHandler).await
             }
             Verbosity::Normal => {
                 if use_pretty {
-                    let mut handler = PrettyStreamHandler::new(false);
-                    exec.run_observe_streaming(prompt, interrupt_rx, &mut handler)
-                        .await
+                    let handler = PrettyStreamHandler::new(false);
+                    observe_streaming(exec, prompt, interrupt_rx, handler).await
                 } else {
-                    let mut handler = ConsoleStreamHandler::new(false);
-                    exec.run_observe_streaming(prompt, interrupt_rx, &mut handler)
-                        .await
+                    let handler = ConsoleStreamHandler::new(false);
+                    observe_streaming(exec, prompt, interrupt_rx, handler).await
                 }
             }
             Verbosity::Verbose => {
                 if use_pretty {
-                    let mut handler = PrettyStreamHandler::new(true);
-                    exec.run_observe_streaming(prompt, interrupt_rx, &mut handler)
-                        .await
+                    let handler = PrettyStreamHandler::new(true);
+                    observe_streaming(exec, prompt, interrupt_rx, handler).await
                 } else {
-                    let mut handler = ConsoleStreamHandler::new(true);
-                    exec.run_observe_streaming(prompt, interrupt_rx, &mut handler)
-                        .await
+                    let handler = ConsoleStreamHandler::new(true);
+                    observe_streaming(exec, prompt, interrupt_rx, handler).await
                 }
             }
         }
@@ -1332,6 +2422,7 @@ async fn execute_pty(
                 output: output_for_parsing,
                 success: pty_result.success,
                 termination,
+                usage: session.as_ref().map(SessionResult::usage),
             })
         }
         Err(e) => {
@@ -1342,18 +2433,197 @@ async fn execute_pty(
     }
 }
 
+/// Runs one iteration on the Messages API backend and shows its response.
+async fn execute_api(
+    config: &RalphConfig,
+    credentials: &[(String, String)],
+    model: Option<&str>,
+    prompt: &str,
+    timeout: Option<Duration>,
+    verbosity: Verbosity,
+    tui_lines: Option<Arc<std::sync::Mutex<Vec<ratatui::text::Line<'static>>>>>,
+) -> Result<ExecutionOutcome> {
+    let mut api = ApiBackend::from_config(&config.api, credentials)?;
+    if let Some(model) = model {
+        api = api.with_model(model);
+    }
+    let started = std::time::Instant::now();
+    let result = api.execute(prompt, std::io::sink(), timeout).await?;
+    let elapsed = started.elapsed();
+
+    let verbose = verbosity == Verbosity::Verbose;
+    if let Some(lines) = tui_lines {
+        show_api_result(
+            TuiStreamHandler::with_lines(verbose, lines),
+            &result,
+            elapsed,
+        );
+    } else if verbosity != Verbosity::Quiet {
+        if stdout().is_terminal() {
+            show_api_result(PrettyStreamHandler::new(verbose), &result, elapsed);
+        } else {
+            show_api_result(ConsoleStreamHandler::new(verbose), &result, elapsed);
+        }
+    }
+
+    Ok(ExecutionOutcome {
+        output: result.output,
+        success: result.success,
+        termination: None,
+        usage: Some(result.usage),
+    })
+}
+
+/// Replays a Messages API response through a stream handler.
+fn show_api_result<H: StreamHandler>(mut handler: H, result: &ApiResult, elapsed: Duration) {
+    if result.success {
+        handler.on_text(&result.output);
+    } else {
+        handler.on_error(&result.output);
+    }
+    handler.on_complete(&SessionResult {
+        duration_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+        total_cost_usd: result.usage.cost_usd,
+        num_turns: 1,
+        is_error: !result.success,
+        input_tokens: result.usage.input_tokens,
+        output_tokens: result.usage.output_tokens,
+    });
+}
+
+/// Runs the PTY executor with `handler`, keeping the session result for cost tracking.
+async fn observe_streaming<H: StreamHandler>(
+    exec: &PtyExecutor,
+    prompt: &str,
+    interrupt_rx: tokio::sync::watch::Receiver<bool>,
+    handler: H,
+) -> (std::io::Result<PtyExecutionResult>, Option<SessionResult>) {
+    let mut recorder = ResultRecorder::new(handler);
+    let result = exec
+        .run_observe_streaming(prompt, interrupt_rx, &mut recorder)
+        .await;
+    (result, recorder.into_result())
+}
+
 /// Logs events parsed from output to the event history file.
 ///
 /// When an event has no subscriber (orphan), also logs an `event.orphaned`
 /// system event to help Ralph understand the misconfiguration.
+/// Writes what the event parser made of an iteration's output to
+/// `<session_dir>/iter-<n>.events.json` (`event_loop.trace_events`).
+fn write_parse_trace(
+    session_dir: &Path,
+    iteration: u32,
+    hat: &HatId,
+    output: &str,
+    parser: &EventParser,
+) {
+    let (events, trace) = parser.parse_traced(output);
+    let skipped = trace.skipped().count();
+    if skipped > 0 {
+        debug!(
+            iteration,
+            skipped, "Event parser skipped candidates; see the iteration's events.json"
+        );
+    }
+    let report = serde_json::json!({
+        "iteration": iteration,
+        "hat": hat.as_str(),
+        "events": events.len(),
+        "skipped": skipped,
+        "candidates": trace.candidates,
+    });
+    let path = session_dir.join(format!("iter-{iteration}.events.json"));
+    if let Err(e) = fs::create_dir_all(session_dir).and_then(|()| {
+        fs::write(
+            &path,
+            serde_json::to_string_pretty(&report).unwrap_or_default(),
+        )
+    }) {
+        warn!("Failed to write event parser trace: {}", e);
+    }
+}
+
+/// Waits until no other loop runs a hat in mutex group `group`, polling so
+/// an interrupt stays responsive.
+///
+/// Returns `None` if interrupted first, or if the lock can't be taken (the
+/// hat then runs unserialized).
+async fn wait_for_mutex_group(
+    ctx: &LoopContext,
+    hat: &HatId,
+    group: &str,
+    interrupt_rx: &tokio::sync::watch::Receiver<bool>,
+) -> Option<FileLockGuard> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    let lock = FileLock::new(ctx.mutex_group_path(group))
+        .inspect_err(|e| warn!(group, error = %e, "Failed to open mutex group lock"))
+        .ok()?;
+    let mut waiting_since: Option<Instant> = None;
+    loop {
+        match lock.try_exclusive() {
+            Ok(Some(guard)) => {
+                if let Some(since) = waiting_since {
+                    info!(hat = %hat, group, waited_ms = since.elapsed().as_millis(), "Acquired mutex group");
+                }
+                return Some(guard);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!(group, error = %e, "Failed to lock mutex group; running unserialized");
+                return None;
+            }
+        }
+        if *interrupt_rx.borrow() {
+            return None;
+        }
+        if waiting_since.is_none() {
+            info!(hat = %hat, group, "Waiting for another loop's hat in mutex group");
+            waiting_since = Some(Instant::now());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Encrypts an iteration's prompt, output, and parser trace in place.
+fn seal_iteration(key: &ArtifactKey, session_dir: &Path, iteration: u32) {
+    for extension in ["prompt", "out", "events.json"] {
+        let path = session_dir.join(format!("iter-{iteration}.{extension}"));
+        if let Err(e) = key.seal_file(&path) {
+            warn!(path = %path.display(), error = %e, "Failed to encrypt iteration artifact");
+        }
+    }
+}
+
+/// Encrypts the events journal with its archives, the tool audit log, and
+/// every file in `dirs` (the session and diagnostics directories),
+/// including iterations that ended before they were sealed.
+fn seal_session(key: &ArtifactKey, dirs: &[Option<&Path>], events_path: &Path, audit_log: &Path) {
+    let files = dirs
+        .iter()
+        .flatten()
+        .flat_map(|dir| fs::read_dir(dir).into_iter().flatten().flatten())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file());
+    let journal = ralph_core::archive_paths(events_path)
+        .into_iter()
+        .chain([events_path.to_path_buf(), audit_log.to_path_buf()]);
+    for path in files.chain(journal) {
+        if let Err(e) = key.seal_file(&path) {
+            warn!(path = %path.display(), error = %e, "Failed to encrypt session artifact");
+        }
+    }
+}
+
 fn log_events_from_output(
     logger: &mut EventLogger,
     iteration: u32,
     hat_id: &HatId,
     output: &str,
     registry: &ralph_core::HatRegistry,
+    parser: &EventParser,
 ) {
-    let parser = EventParser::new();
     let events = parser.parse(output);
 
     for event in events {
@@ -1423,6 +2693,26 @@ fn log_terminate_event(logger: &mut EventLogger, iteration: u32, event: &Event)
     }
 }
 
+/// Persists the event bus state for `ralph bus dump` and the loop's public
+/// state file.
+fn write_snapshots(event_loop: &EventLoop) {
+    if let Err(e) = event_loop.write_bus_snapshot() {
+        warn!("Failed to write event bus snapshot: {}", e);
+    }
+    if let Err(e) = event_loop.write_state_file() {
+        warn!("Failed to write state file: {}", e);
+    }
+}
+
+/// Logs an orchestrator lifecycle event (`ralph.*`) to the event history.
+fn log_lifecycle_event(logger: &mut EventLogger, iteration: u32, event: &Event) {
+    let record = EventRecord::new(iteration, "loop", event, None::<&HatId>);
+
+    if let Err(e) = logger.log(&record) {
+        warn!("Failed to log {} event: {}", event.topic, e);
+    }
+}
+
 /// Gets the last commit info (short SHA and subject) for the summary file.
 fn get_last_commit_info_with_cmd(git_cmd: &OsStr) -> Option<String> {
     let output = Command::new(git_cmd)
@@ -1452,7 +2742,9 @@ fn get_last_commit_info() -> Option<String> {
 /// 5. Default PROMPT.md
 ///
 /// Note: CLI overrides are already applied to config before this function is called.
-fn resolve_prompt_content(event_loop_config: &ralph_core::EventLoopConfig) -> Result<String> {
+pub(crate) fn resolve_prompt_content(
+    event_loop_config: &ralph_core::EventLoopConfig,
+) -> Result<String> {
     debug!(
         inline_prompt = ?event_loop_config.prompt.as_ref().map(|s| format!("{}...", &s[..s.len().min(50)])),
         prompt_file = %event_loop_config.prompt_file,
@@ -1750,13 +3042,14 @@ pub async fn start_loop(
     let prompt_summary = config.event_loop.prompt.as_deref().unwrap_or("[daemon]");
     let prompt_summary = ralph_core::truncate_with_ellipsis(prompt_summary, 100);
 
+    let repo_lock = acquire_repo_lock(&workspace_root, &prompt_summary, false)?;
     let _lock_guard = ralph_core::LoopLock::try_acquire(&workspace_root, &prompt_summary)
         .context("Failed to acquire loop lock — another loop may be running")?;
 
     let loop_context = ralph_core::LoopContext::primary(workspace_root);
 
     // Run the loop headlessly
-    run_loop_impl(
+    Box::pin(run_loop_impl(
         config,
         ColorMode::Never,
         false, // not resume
@@ -1766,7 +3059,8 @@ pub async fn start_loop(
         Some(loop_context),
         Vec::new(), // no custom args
         None,       // default auto-merge
-    )
+        repo_lock,
+    ))
     .await
 }
 
@@ -2142,7 +3436,14 @@ mod tests {
 <event topic=\"unknown.event\">oops</event>";
         let hat_id = HatId::new("tester");
 
-        log_events_from_output(&mut logger, 1, &hat_id, output, &registry);
+        log_events_from_output(
+            &mut logger,
+            1,
+            &hat_id,
+            output,
+            &registry,
+            &EventParser::new(),
+        );
 
         let content = std::fs::read_to_string(&log_path).expect("read events");
         let records: Vec<EventRecord> = content
@@ -2163,6 +3464,84 @@ mod tests {
         assert_eq!(triggered.as_deref(), Some("planner"));
     }
 
+    #[test]
+    fn test_seal_session_encrypts_journal_archives_and_logs() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let session_dir = temp_dir.path().join("sessions").join("primary-1");
+        std::fs::create_dir_all(&session_dir).expect("session dir");
+        let events_path = temp_dir.path().join("events-1.jsonl");
+        EventLogger::new(&events_path)
+            .log_event(1, "builder", &Event::new("build.done", "ok"), None)
+            .expect("events");
+        for name in ["iter-1.prompt", "iter-1.out", "iter-2.prompt"] {
+            std::fs::write(session_dir.join(name), "proprietary").expect("artifact");
+        }
+        let archive = temp_dir.path().join("events-1").join("archive-1.jsonl");
+        let diagnostics = temp_dir.path().join("diagnostics").join("run-1");
+        let audit_log = temp_dir.path().join("tool-audit.jsonl");
+        std::fs::create_dir_all(archive.parent().unwrap()).expect("archive dir");
+        std::fs::create_dir_all(&diagnostics).expect("diagnostics dir");
+        for path in [
+            &archive,
+            &diagnostics.join("agent-output.jsonl"),
+            &audit_log,
+        ] {
+            std::fs::write(path, "proprietary\n").expect("artifact");
+        }
+        let key = ArtifactKey::from_hex("KEY", &"07".repeat(32)).expect("key");
+
+        seal_iteration(&key, &session_dir, 1);
+        let sealed = |path: &Path| {
+            ralph_core::encryption::is_sealed(&std::fs::read(path).expect("read artifact"))
+        };
+        assert!(sealed(&session_dir.join("iter-1.out")));
+        assert!(!sealed(&session_dir.join("iter-2.prompt")));
+
+        seal_session(
+            &key,
+            &[Some(session_dir.as_path()), Some(diagnostics.as_path())],
+            &events_path,
+            &audit_log,
+        );
+        assert!(sealed(&session_dir.join("iter-2.prompt")));
+        assert!(sealed(&events_path));
+        assert!(sealed(&archive));
+        assert!(sealed(&diagnostics.join("agent-output.jsonl")));
+        assert!(sealed(&audit_log));
+        let records = ralph_core::EventHistory::new(&events_path)
+            .with_key(Some(key))
+            .read_all()
+            .expect("read sealed journal");
+        assert_eq!(records.len(), 1);
+    }
+
+    #[test]
+    fn test_write_parse_trace() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let session_dir = temp_dir.path().join("sessions").join("primary-1");
+        let output = "<event topic=\"build.done\">ok</event>\n<event topic=build.done>oops</event>";
+
+        write_parse_trace(
+            &session_dir,
+            3,
+            &HatId::new("builder"),
+            output,
+            &EventParser::new(),
+        );
+
+        let content =
+            std::fs::read_to_string(session_dir.join("iter-3.events.json")).expect("read trace");
+        let report: serde_json::Value = serde_json::from_str(&content).expect("json");
+        assert_eq!(report["hat"], "builder");
+        assert_eq!(report["events"], 1);
+        assert_eq!(report["skipped"], 1);
+        assert_eq!(
+            report["candidates"][1]["skipped"],
+            "attribute value is not quoted"
+        );
+        assert_eq!(report["candidates"][1]["start"], 37);
+    }
+
     #[test]
     fn test_log_terminate_event_writes_record() {
         let temp_dir = tempfile::tempdir().expect("temp dir");
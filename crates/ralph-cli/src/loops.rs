@@ -15,12 +15,15 @@
 
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
 use clap::{Parser, Subcommand};
 
 use ralph_core::worktree::{list_ralph_worktrees, remove_worktree};
-use ralph_core::{LoopRegistry, MergeButtonState, MergeQueue, MergeState, merge_button_state};
+use ralph_core::{
+    EventReader, LoopRegistry, MergeButtonState, MergeQueue, MergeState, merge_button_state,
+};
 
 /// Manage parallel loops.
 #[derive(Parser, Debug)]
@@ -211,20 +214,7 @@ fn get_merge_button_state(args: MergeButtonStateArgs) -> Result<()> {
 
 /// Check if a process is alive.
 fn is_process_alive(pid: u32) -> bool {
-    #[cfg(unix)]
-    {
-        use nix::sys::signal::kill;
-        use nix::unistd::Pid;
-
-        // Signal 0 checks if process exists without sending a signal
-        kill(Pid::from_raw(pid as i32), None).is_ok()
-    }
-
-    #[cfg(not(unix))]
-    {
-        let _ = pid;
-        false
-    }
+    ralph_adapters::is_process_alive(pid)
 }
 
 /// Format duration as relative age (e.g., "5m", "2h", "1d").
@@ -563,14 +553,17 @@ fn show_logs(args: LogsArgs) -> Result<()> {
     }
 
     if args.follow {
-        // Use tail -f for following
-        let status = Command::new("tail")
-            .args(["-f", events_path.to_string_lossy().as_ref()])
-            .status()
-            .context("Failed to run tail")?;
-
-        if !status.success() {
-            bail!("tail exited with error");
+        // Wake on filesystem notifications; the reader restarts from the top
+        // if the file is truncated or rotated underneath us.
+        let mut reader = EventReader::new(&events_path);
+        let watcher = reader.watch().context("Failed to watch events file")?;
+        loop {
+            let result = reader
+                .wait_for_events(&watcher, Duration::from_secs(1))
+                .context("Failed to read events file")?;
+            for event in result.events {
+                println!("{}", serde_json::to_string(&event)?);
+            }
         }
     } else {
         // Just cat the file
@@ -715,26 +708,11 @@ fn stop_loop(args: StopArgs) -> Result<()> {
     }
 
     if args.force {
-        // Force-stop with SIGKILL for immediate termination.
-        #[cfg(unix)]
-        {
-            use nix::sys::signal::{Signal, kill};
-            use nix::unistd::Pid;
-
-            println!(
-                "Sending SIGKILL to loop '{}' (PID {})...",
-                loop_id, metadata.pid
-            );
-            kill(Pid::from_raw(metadata.pid as i32), Signal::SIGKILL)
-                .context("Failed to send SIGKILL")?;
-            println!("Signal sent.");
-            return Ok(());
-        }
-
-        #[cfg(not(unix))]
-        {
-            bail!("--force is only supported on Unix systems");
-        }
+        // Force-stop for immediate termination (SIGKILL on Unix, process tree kill on Windows).
+        println!("Killing loop '{}' (PID {})...", loop_id, metadata.pid);
+        ralph_adapters::force_kill(metadata.pid).context("Failed to kill loop process")?;
+        println!("Loop killed.");
+        return Ok(());
     }
 
     let stop_path = target_root.join(".ralph/stop-requested");
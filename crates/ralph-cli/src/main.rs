@@ -12,19 +12,46 @@
 //! - Code task generation via `ralph code-task`
 //! - Work item tracking via `ralph task`
 
+mod amend;
+mod audit_cli;
+mod batch;
+mod bench;
 mod bot;
+mod bridge;
+mod bus;
+mod cost;
+// Server routes and controls are only reachable with the `dashboard` feature.
+#[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+mod dashboard;
+mod decrypt;
+mod detach;
 mod display;
 mod doctor;
+mod dry_run;
+mod event_hooks;
+mod export;
 mod hats;
 mod init;
 mod interact;
 mod loop_runner;
 mod loops;
 mod memory;
+mod pause_cli;
+mod plan_only;
 mod preflight;
 mod presets;
+mod replay_iteration;
+mod repro;
+mod run_cli;
+mod scratchpad_cli;
+mod search_cli;
+// Endpoint handlers are only reachable with the `api` feature.
+#[cfg_attr(not(feature = "api"), allow(dead_code))]
+mod serve;
+mod sessions;
 mod skill_cli;
 mod sop_runner;
+mod status;
 mod task_cli;
 #[cfg(test)]
 mod test_support;
@@ -32,13 +59,17 @@ mod tools;
 mod web;
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use clap::{ArgAction, CommandFactory, Parser, Subcommand, ValueEnum};
 use ralph_adapters::detect_backend;
+use ralph_core::bootstrap::BootstrapSource;
 use ralph_core::{
-    CheckStatus, EventHistory, LockError, LoopContext, LoopEntry, LoopLock, LoopRegistry,
-    PreflightReport, PreflightRunner, RalphConfig, TerminationReason,
+    CheckStatus, EventHistory, EventIndex, EventQuery, EventWriter, LockError, LoopContext,
+    LoopEntry, LoopLock, LoopRegistry, PreflightReport, PreflightRunner, RalphConfig, StartEvent,
+    TerminationReason,
     worktree::{WorktreeConfig, create_worktree, ensure_gitignore, remove_worktree},
 };
+use ralph_proto::Topic;
 use std::fs;
 use std::io::{IsTerminal, Write, stdout};
 use std::path::{Path, PathBuf};
@@ -318,7 +349,7 @@ pub(crate) fn ensure_scratchpad_directory(config: &RalphConfig) -> anyhow::Resul
 
 /// Loads configuration from file sources with override support.
 ///
-/// This is the common sync path used by resume_command and clean_command.
+/// This is the common sync path used by clean_command.
 /// For the full async path (including Remote URLs), see run_command.
 ///
 /// Returns the loaded config with overrides applied and workspace_root set.
@@ -392,6 +423,9 @@ enum Commands {
     /// Run the orchestration loop (default if no subcommand given)
     Run(RunArgs),
 
+    /// Run one orchestration per prompt file and summarize the outcomes
+    Batch(batch::BatchArgs),
+
     /// Run preflight checks to validate configuration and environment
     Preflight(preflight::PreflightArgs),
 
@@ -401,14 +435,39 @@ enum Commands {
     /// Interactive walkthrough of hats, presets, and workflow
     Tutorial(TutorialArgs),
 
-    /// DEPRECATED: Use `ralph run --continue` instead.
-    /// Resume a previously interrupted loop from existing scratchpad.
-    #[command(hide = true)]
-    Resume(ResumeArgs),
-
     /// View event history for debugging
     Events(EventsArgs),
 
+    /// Inspect event bus routing state
+    Bus(bus::BusArgs),
+
+    /// Show cost and token spend by hat or topic
+    Cost(cost::CostArgs),
+
+    /// Benchmark latency, throughput, and cost across backends
+    Bench(bench::BenchArgs),
+
+    /// Export a session's event flow as a Mermaid or DOT diagram
+    Export(export::ExportArgs),
+
+    /// Compare past sessions
+    Sessions(sessions::SessionsArgs),
+
+    /// Rebuild an iteration's exact prompt and commit from its manifest
+    Repro(repro::ReproArgs),
+
+    /// Re-run one iteration, optionally with an edited prompt, and diff it against the original
+    ReplayIteration(replay_iteration::ReplayIterationArgs),
+
+    /// Decrypt a session artifact sealed by the encryption config
+    Decrypt(decrypt::DecryptArgs),
+
+    /// Show the output of a detached run
+    Logs(detach::LogsArgs),
+
+    /// Show who holds the repo lock and whether a loop is running
+    Status(status::StatusArgs),
+
     /// Initialize a new ralph.yml configuration file
     Init(InitArgs),
 
@@ -418,6 +477,15 @@ enum Commands {
     /// Emit an event to the current run's events file with proper JSON formatting
     Emit(EmitArgs),
 
+    /// Add an instruction to the running loop's objective
+    Amend(amend::AmendArgs),
+
+    /// Hold the running loop after its current iteration
+    Pause(pause_cli::PauseArgs),
+
+    /// Let a paused loop continue
+    Resume(pause_cli::PauseArgs),
+
     /// Start a Prompt-Driven Development planning session
     Plan(PlanArgs),
 
@@ -439,6 +507,9 @@ enum Commands {
     /// Run the web dashboard
     Web(web::WebArgs),
 
+    /// Serve the HTTP API for submitting and controlling loops
+    Serve(serve::ServeArgs),
+
     /// Manage Telegram bot setup and testing
     Bot(bot::BotArgs),
 
@@ -459,10 +530,26 @@ struct InitArgs {
     #[arg(long, conflicts_with = "list_presets")]
     preset: Option<String>,
 
+    /// Scaffold from a template repo (gh:org/repo, gl:org/repo, git URL, or path; `#ref` selects a branch or tag)
+    #[arg(long, conflicts_with = "list_presets", conflicts_with = "preset")]
+    template: Option<String>,
+
+    /// Project name substituted for {{project_name}} in templates (default: current directory name)
+    #[arg(long, requires = "template")]
+    name: Option<String>,
+
     /// List all available embedded presets
     #[arg(long, conflicts_with = "backend", conflicts_with = "preset")]
     list_presets: bool,
 
+    /// Walk through backend, mode, budgets, and hats to generate a commented ralph.yml
+    #[arg(
+        long,
+        short = 'i',
+        conflicts_with_all = ["backend", "preset", "template", "list_presets"]
+    )]
+    interactive: bool,
+
     /// Overwrite existing ralph.yml if present
     #[arg(long)]
     force: bool,
@@ -483,6 +570,16 @@ struct RunArgs {
     #[arg(short = 'P', long = "prompt-file", conflicts_with = "prompt_text")]
     prompt_file: Option<PathBuf>,
 
+    /// Publish `task.start` with this payload instead of reading a prompt file
+    #[arg(long, value_name = "TASK", conflicts_with_all = ["prompt_text", "prompt_file"])]
+    task: Option<String>,
+
+    /// Seed the loop with this event instead of the configured starting_event,
+    /// e.g. `review.request` or `review.request:Re-review the auth change`.
+    /// Without a payload the event carries the prompt.
+    #[arg(long, value_name = "TOPIC[:PAYLOAD]", conflicts_with_all = ["task", "continue_mode"])]
+    start_event: Option<StartEvent>,
+
     /// Override max iterations
     #[arg(long)]
     max_iterations: Option<u32>,
@@ -491,16 +588,30 @@ struct RunArgs {
     #[arg(long)]
     completion_promise: Option<String>,
 
-    /// Dry run - show what would be executed without running
+    /// Dry run - validate hats, render prompts, and simulate routing without executing
     #[arg(long)]
     dry_run: bool,
 
+    /// Draft a plan skeleton into the scratchpad from local heuristics, without calling a backend
+    #[arg(long, conflicts_with = "dry_run")]
+    plan_only: bool,
+
+    /// Run in the background: print the session id and return immediately.
+    /// Follow the output with `ralph logs -f <session>`.
+    #[arg(long, conflicts_with_all = ["dry_run", "plan_only"])]
+    detach: bool,
+
     /// Continue from existing scratchpad (resume interrupted loop).
     /// Use this when a previous run was interrupted and you want to
     /// continue from where it left off.
     #[arg(long = "continue")]
     continue_mode: bool,
 
+    /// Start with a previous session's summary and unfinished tasks in the
+    /// first prompt: `last`, or a loop id from `.ralph/agent/sessions/`.
+    #[arg(long, value_name = "SESSION", conflicts_with = "continue_mode")]
+    bootstrap_from: Option<BootstrapSource>,
+
     // ─────────────────────────────────────────────────────────────────────────
     // Execution Mode Options
     // ─────────────────────────────────────────────────────────────────────────
@@ -519,6 +630,16 @@ struct RunArgs {
     #[arg(long)]
     idle_timeout: Option<u32>,
 
+    /// Serve the browser dashboard while the loop runs (address from
+    /// `dashboard.bind`). Requires a build with the `dashboard` feature.
+    #[arg(long)]
+    dashboard: bool,
+
+    /// Write each iteration's event parser trace (every candidate event and
+    /// why skipped ones were ignored) to the session directory.
+    #[arg(long)]
+    trace_events: bool,
+
     // ─────────────────────────────────────────────────────────────────────────
     // Multi-Loop Concurrency Options
     // ─────────────────────────────────────────────────────────────────────────
@@ -527,6 +648,11 @@ struct RunArgs {
     #[arg(long)]
     exclusive: bool,
 
+    /// Take over the repo lock from another user's run. That run stops at
+    /// its next iteration boundary and this one waits for the primary slot.
+    #[arg(long)]
+    force: bool,
+
     /// Skip automatic merge after loop completes (keep worktree for manual handling).
     /// Only relevant for parallel loops running in worktrees.
     #[arg(long)]
@@ -560,44 +686,13 @@ struct RunArgs {
     custom_args: Vec<String>,
 }
 
-/// Arguments for the resume subcommand.
-///
-/// Per spec: "When loop terminates due to safeguard (not completion promise),
-/// user can run `ralph resume` to restart reading existing scratchpad."
-#[derive(Parser, Debug)]
-struct ResumeArgs {
-    /// Override max iterations (from current position)
-    #[arg(long)]
-    max_iterations: Option<u32>,
-
-    /// Disable TUI observation mode (TUI is enabled by default)
-    #[arg(long, conflicts_with = "autonomous")]
-    no_tui: bool,
-
-    /// Force autonomous mode
-    #[arg(short, long, conflicts_with = "no_tui")]
-    autonomous: bool,
-
-    /// Idle timeout in seconds for TUI mode
-    #[arg(long)]
-    idle_timeout: Option<u32>,
-
-    /// Enable verbose output (show tool results and session summary)
-    #[arg(short = 'v', long, conflicts_with = "quiet")]
-    verbose: bool,
-
-    /// Suppress streaming output (for CI/scripting)
-    #[arg(short = 'q', long, conflicts_with = "verbose")]
-    quiet: bool,
-
-    /// Record session to JSONL file for replay testing
-    #[arg(long, value_name = "FILE")]
-    record_session: Option<PathBuf>,
-}
-
 /// Arguments for the events subcommand.
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 struct EventsArgs {
+    #[command(subcommand)]
+    command: Option<EventsCommands>,
+
     /// Show only the last N events
     #[arg(long)]
     last: Option<usize>,
@@ -623,6 +718,61 @@ struct EventsArgs {
     clear: bool,
 }
 
+#[derive(Subcommand, Debug)]
+enum EventsCommands {
+    /// Search events across every session in this workspace
+    Query(EventsQueryArgs),
+}
+
+/// Arguments for `ralph events query`.
+#[derive(Parser, Debug)]
+struct EventsQueryArgs {
+    /// Topic or glob pattern (e.g., "build.*")
+    #[arg(long)]
+    topic: Option<String>,
+
+    /// Only events newer than this: a duration like 30m, 2h, 7d, or an RFC 3339 time
+    #[arg(long, value_parser = parse_since)]
+    since: Option<DateTime<Utc>>,
+
+    /// Only events older than this (same forms as --since)
+    #[arg(long, value_parser = parse_since)]
+    until: Option<DateTime<Utc>>,
+
+    /// Only events whose payload contains this text (case-insensitive)
+    #[arg(long)]
+    payload_contains: Option<String>,
+
+    /// Show only the last N matches
+    #[arg(long)]
+    last: Option<usize>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+}
+
+/// Parses `--since`/`--until`: `<n>s|m|h|d` ago, or an RFC 3339 timestamp.
+fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let invalid =
+        || format!("expected a duration like 30m, 2h, 7d, or an RFC 3339 time, got '{value}'");
+    let (split, _) = value.char_indices().last().ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount = i64::from(amount.parse::<u32>().map_err(|_| invalid())?);
+    let ago = match unit {
+        "s" => chrono::Duration::try_seconds(amount),
+        "m" => chrono::Duration::try_minutes(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "d" => chrono::Duration::try_days(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    Ok(Utc::now() - ago)
+}
+
 /// Arguments for the clean subcommand.
 #[derive(Parser, Debug)]
 struct CleanArgs {
@@ -741,8 +891,7 @@ async fn main() -> Result<()> {
     // Detect if TUI mode is requested - TUI owns the terminal, so logs must not go to stdout
     // TUI is enabled by default unless --no-tui is specified or --autonomous is used
     let tui_enabled = match &cli.command {
-        Some(Commands::Run(args)) => !args.no_tui && !args.autonomous,
-        Some(Commands::Resume(args)) => !args.no_tui && !args.autonomous,
+        Some(Commands::Run(args)) => !args.no_tui && !args.autonomous && !args.detach,
         None => true,
         _ => false,
     };
@@ -830,10 +979,13 @@ async fn main() -> Result<()> {
     let config_sources: Vec<ConfigSource> =
         cli.config.iter().map(|s| ConfigSource::parse(s)).collect();
 
-    match cli.command {
+    let result = match cli.command {
         Some(Commands::Run(args)) => {
             run_command(&config_sources, cli.verbose, cli.color, args).await
         }
+        Some(Commands::Batch(args)) => {
+            batch::execute(&cli.config, args, cli.color.should_use_colors()).await
+        }
         Some(Commands::Preflight(args)) => {
             preflight::execute(&config_sources, args, cli.color.should_use_colors()).await
         }
@@ -841,13 +993,29 @@ async fn main() -> Result<()> {
             doctor::execute(&config_sources, args, cli.color.should_use_colors()).await
         }
         Some(Commands::Tutorial(args)) => tutorial_command(cli.color, args),
-        Some(Commands::Resume(args)) => {
-            resume_command(&config_sources, cli.verbose, cli.color, args).await
-        }
         Some(Commands::Events(args)) => events_command(cli.color, args),
+        Some(Commands::Cost(args)) => cost::execute(&args, cli.color.should_use_colors()),
+        Some(Commands::Bus(args)) => bus::execute(args, cli.color.should_use_colors()).await,
+        Some(Commands::Bench(args)) => {
+            bench::execute(&config_sources, args, cli.color.should_use_colors()).await
+        }
+        Some(Commands::Export(args)) => export::execute(&args),
+        Some(Commands::Sessions(args)) => sessions::execute(&args, cli.color.should_use_colors()),
+        Some(Commands::Repro(args)) => {
+            repro::execute(&config_sources, &args, cli.color.should_use_colors())
+        }
+        Some(Commands::ReplayIteration(args)) => {
+            replay_iteration::execute(&config_sources, &args, cli.color.should_use_colors()).await
+        }
+        Some(Commands::Decrypt(args)) => decrypt::execute(&args),
+        Some(Commands::Logs(args)) => detach::execute(args).await,
+        Some(Commands::Status(args)) => status::execute(&args, cli.color.should_use_colors()),
         Some(Commands::Init(args)) => init_command(cli.color, args),
         Some(Commands::Clean(args)) => clean_command(&config_sources, cli.color, args),
         Some(Commands::Emit(args)) => emit_command(cli.color, args),
+        Some(Commands::Amend(args)) => amend::execute(args, cli.color.should_use_colors()),
+        Some(Commands::Pause(args)) => pause_cli::pause(&args, cli.color.should_use_colors()),
+        Some(Commands::Resume(args)) => pause_cli::resume(&args, cli.color.should_use_colors()),
         Some(Commands::Plan(args)) => plan_command(&config_sources, cli.color, args),
         Some(Commands::CodeTask(args)) => code_task_command(&config_sources, cli.color, args),
         Some(Commands::Task(args)) => code_task_command(&config_sources, cli.color, args),
@@ -857,6 +1025,7 @@ async fn main() -> Result<()> {
             hats::execute(&config_sources, args, cli.color.should_use_colors())
         }
         Some(Commands::Web(args)) => web::execute(args).await,
+        Some(Commands::Serve(args)) => serve::execute(args).await,
         Some(Commands::Bot(args)) => {
             bot::execute(args, &config_sources, cli.color.should_use_colors()).await
         }
@@ -866,15 +1035,23 @@ async fn main() -> Result<()> {
             let args = RunArgs {
                 prompt_text: None,
                 prompt_file: None,
+                task: None,
+                start_event: None,
                 backend: None,
                 max_iterations: None,
                 completion_promise: None,
                 dry_run: false,
+                plan_only: false,
+                detach: false,
                 continue_mode: false,
+                bootstrap_from: None,
                 no_tui: false, // TUI enabled by default
                 autonomous: false,
                 idle_timeout: None,
+                dashboard: false,
+                trace_events: false,
                 exclusive: false,
+                force: false,
                 no_auto_merge: false,
                 skip_preflight: false,
                 verbose: false,
@@ -884,7 +1061,25 @@ async fn main() -> Result<()> {
             };
             run_command(&config_sources, cli.verbose, cli.color, args).await
         }
+    };
+
+    if let Err(err) = result {
+        exit_with_error(&err);
     }
+    Ok(())
+}
+
+/// Prints `err` and exits with the code for its failure class.
+///
+/// Errors that don't carry a typed Ralph error anywhere in their chain exit
+/// with 1, as before.
+fn exit_with_error(err: &anyhow::Error) -> ! {
+    eprintln!("Error: {err:?}");
+    let code = err
+        .chain()
+        .find_map(|e| ralph_adapters::classify(e))
+        .map_or(1, ralph_core::ErrorCode::exit_code);
+    std::process::exit(code);
 }
 
 fn format_preflight_summary(report: &PreflightReport) -> String {
@@ -1150,13 +1345,36 @@ async fn run_command(
 
     // Apply CLI overrides (after normalization so they take final precedence)
     // Per spec: CLI -p and -P are mutually exclusive (enforced by clap)
-    if let Some(text) = args.prompt_text {
+    if let Some(task) = args.task {
+        // A one-off task always starts with task.start, whatever the preset's starting_event
+        config.event_loop.prompt = Some(task);
+        config.event_loop.prompt_file = String::new();
+        config.event_loop.starting_event = Some("task.start".to_string());
+    } else if let Some(text) = args.prompt_text {
         config.event_loop.prompt = Some(text);
         config.event_loop.prompt_file = String::new(); // Clear file path
     } else if let Some(path) = args.prompt_file {
         config.event_loop.prompt_file = path.to_string_lossy().to_string();
         config.event_loop.prompt = None; // Clear inline
     }
+    if let Some(start) = args.start_event {
+        if !config.hats.is_empty()
+            && !config.hats.values().any(|hat| {
+                hat.triggers
+                    .iter()
+                    .any(|trigger| Topic::new(trigger.as_str()).matches_str(&start.topic))
+            })
+        {
+            warn!(
+                "No hat triggers on '{}'; Ralph will handle the start event itself",
+                start.topic
+            );
+        }
+        // Jumping mid-workflow: Ralph mustn't fast-path to the configured start
+        config.event_loop.starting_event = None;
+        config.event_loop.start_event = Some(start);
+    }
+    config.event_loop.bootstrap_from = args.bootstrap_from;
     if let Some(max_iter) = args.max_iterations {
         config.event_loop.max_iterations = max_iter;
     }
@@ -1180,6 +1398,13 @@ async fn run_command(
         config.cli.idle_timeout_secs = timeout;
     }
 
+    if args.dashboard {
+        config.dashboard.enabled = true;
+    }
+    if args.trace_events {
+        config.event_loop.trace_events = true;
+    }
+
     // Apply backend override from CLI (takes precedence over config)
     if let Some(backend) = args.backend {
         config.cli.backend = backend;
@@ -1193,6 +1418,11 @@ async fn run_command(
         eprintln!("{warning}");
     }
 
+    // Plan-only never calls a backend, so it doesn't need one detected
+    if args.plan_only {
+        return plan_only::run(&mut stdout(), &config, color_mode.should_use_colors());
+    }
+
     // Handle auto-detection if backend is "auto"
     if config.cli.backend == "auto" {
         let priority = config.get_agent_priority();
@@ -1222,6 +1452,7 @@ async fn run_command(
             AutoPreflightMode::DryRun,
         )
         .await?;
+        dry_run::run(&mut stdout(), &config, color_mode.should_use_colors())?;
         println!("Dry run mode - configuration:");
         println!(
             "  Hats: {}",
@@ -1243,6 +1474,9 @@ async fn run_command(
         } else {
             println!("  Prompt file: {}", config.event_loop.prompt_file);
         }
+        if let Some(ref event) = config.event_loop.starting_event {
+            println!("  Starting event: {}", event);
+        }
 
         println!(
             "  Completion promise: {}",
@@ -1268,6 +1502,21 @@ async fn run_command(
         return Ok(());
     }
 
+    if args.detach {
+        let ralph_bin = std::env::current_exe().context("Failed to locate the ralph executable")?;
+        let child_args =
+            detach::child_args(std::env::args_os().skip(1), args.no_tui || args.autonomous);
+        let session = detach::spawn(&config.core.workspace_root, &ralph_bin, &child_args)?;
+        println!("{}", session.id);
+        eprintln!(
+            "Running in the background (PID {}), logging to {}\nFollow with: ralph logs -f {}",
+            session.pid,
+            session.log_path.display(),
+            session.id
+        );
+        return Ok(());
+    }
+
     // Ensure scratchpad directory exists (auto-create with depth limit)
     // This is done after dry-run check to avoid creating directories during dry-run
     ensure_scratchpad_directory(&config)?;
@@ -1299,6 +1548,8 @@ async fn run_command(
     // Try to acquire the loop lock for multi-loop concurrency support
     // This implements the lock detection flow from the multi-loop spec
     let workspace_root = &config.core.workspace_root;
+    // Refuse to share the clone with another user's run (unless --force)
+    let repo_lock = loop_runner::acquire_repo_lock(workspace_root, &prompt_summary, args.force)?;
     let (loop_context, _lock_guard) = match LoopLock::try_acquire(workspace_root, &prompt_summary) {
         Ok(guard) => {
             // We're the primary loop - run in place
@@ -1308,8 +1559,9 @@ async fn run_command(
         }
         Err(LockError::AlreadyLocked(existing)) => {
             // Another loop is running
-            if args.exclusive {
+            if args.exclusive || args.force {
                 // --exclusive: wait for the lock instead of spawning worktree
+                // --force: the previous owner stops at its next iteration, then we take over
                 info!(
                     "Loop lock held by PID {} (started {}), waiting for lock (--exclusive mode)...",
                     existing.pid, existing.started
@@ -1462,7 +1714,7 @@ async fn run_command(
         None
     };
     let workspace_root = config.core.workspace_root.clone();
-    let reason = loop_runner::run_loop_impl(
+    let reason = Box::pin(loop_runner::run_loop_impl(
         config,
         color_mode,
         resume,
@@ -1472,7 +1724,8 @@ async fn run_command(
         Some(loop_context),
         custom_args,
         auto_merge_override,
-    )
+        repo_lock,
+    ))
     .await?;
 
     // Handle restart: exec-replace current process with same CLI args
@@ -1490,9 +1743,15 @@ async fn run_command(
             anyhow::bail!("Failed to exec-replace process: {}", err);
         }
 
+        // Windows has no exec(): run the new process as a child and forward its exit code
         #[cfg(not(unix))]
         {
-            anyhow::bail!("Restart via exec-replace is only supported on Unix");
+            let args: Vec<String> = std::env::args().collect();
+            let status = std::process::Command::new(&args[0])
+                .args(&args[1..])
+                .status()
+                .context("Failed to restart process")?;
+            std::process::exit(status.code().unwrap_or(1));
         }
     }
 
@@ -1506,119 +1765,6 @@ async fn run_command(
     Ok(())
 }
 
-/// Resume a previously interrupted loop from existing scratchpad.
-///
-/// DEPRECATED: Use `ralph run --continue` instead.
-///
-/// Per spec: "When loop terminates due to safeguard (not completion promise),
-/// user can run `ralph run --continue` to restart reading existing scratchpad,
-/// continuing from where it left off."
-async fn resume_command(
-    config_sources: &[ConfigSource],
-    verbose: bool,
-    color_mode: ColorMode,
-    args: ResumeArgs,
-) -> Result<()> {
-    // Show deprecation warning
-    eprintln!(
-        "{}warning:{} `ralph resume` is deprecated. Use `ralph run --continue` instead.",
-        colors::YELLOW,
-        colors::RESET
-    );
-
-    // Load config with overrides applied
-    let mut config = load_config_with_overrides(config_sources)?;
-
-    // Check that scratchpad exists (required for resume)
-    let scratchpad_path = std::path::Path::new(&config.core.scratchpad);
-    if !scratchpad_path.exists() {
-        anyhow::bail!(
-            "Cannot continue: scratchpad not found at '{}'. \
-             Start a fresh run with `ralph run`.",
-            config.core.scratchpad
-        );
-    }
-
-    info!(
-        "Found existing scratchpad at '{}', continuing from previous state",
-        config.core.scratchpad
-    );
-
-    // Apply CLI overrides
-    if let Some(max_iter) = args.max_iterations {
-        config.event_loop.max_iterations = max_iter;
-    }
-    if verbose {
-        config.verbose = true;
-    }
-
-    // Apply execution mode overrides
-    // TUI is enabled by default (unless --no-tui is specified)
-    if args.autonomous {
-        config.cli.default_mode = "autonomous".to_string();
-    } else if !args.no_tui {
-        config.cli.default_mode = "interactive".to_string();
-    }
-
-    // Override idle timeout if specified
-    if let Some(timeout) = args.idle_timeout {
-        config.cli.idle_timeout_secs = timeout;
-    }
-
-    // Validate configuration
-    let warnings = config
-        .validate()
-        .context("Configuration validation failed")?;
-    for warning in &warnings {
-        eprintln!("{warning}");
-    }
-
-    // Handle auto-detection if backend is "auto"
-    if config.cli.backend == "auto" {
-        let priority = config.get_agent_priority();
-        let detected = detect_backend(&priority, |backend| {
-            config.adapter_settings(backend).enabled
-        });
-
-        match detected {
-            Ok(backend) => {
-                info!("Auto-detected backend: {}", backend);
-                config.cli.backend = backend;
-            }
-            Err(e) => {
-                eprintln!("{e}");
-                return Err(anyhow::Error::new(e));
-            }
-        }
-    }
-
-    // Run the orchestration loop in resume mode
-    // The key difference: we publish task.resume instead of task.start,
-    // signaling the planner to read the existing scratchpad
-    // TUI is enabled by default (unless --no-tui or --autonomous is specified)
-    let enable_tui = !args.no_tui && !args.autonomous;
-    let verbosity = Verbosity::resolve(verbose || args.verbose, args.quiet);
-    let reason = loop_runner::run_loop_impl(
-        config,
-        color_mode,
-        true,
-        enable_tui,
-        verbosity,
-        args.record_session,
-        None,       // Deprecated resume command doesn't have loop_context
-        Vec::new(), // Resume command doesn't support custom args
-        None,       // Use config.features.auto_merge (deprecated command)
-    )
-    .await?;
-    let exit_code = reason.exit_code();
-
-    if exit_code != 0 {
-        std::process::exit(exit_code);
-    }
-
-    Ok(())
-}
-
 fn init_command(color_mode: ColorMode, args: InitArgs) -> Result<()> {
     let use_colors = color_mode.should_use_colors();
 
@@ -1628,6 +1774,24 @@ fn init_command(color_mode: ColorMode, args: InitArgs) -> Result<()> {
         return Ok(());
     }
 
+    // Handle --interactive
+    if args.interactive {
+        let answers = init::init_interactive(args.force).map_err(|e| anyhow::anyhow!("{}", e))?;
+        let msg = format!("Created ralph.yml with {} backend", answers.backend);
+        if use_colors {
+            println!("\n{}✓{} {}", colors::GREEN, colors::RESET, msg);
+            println!(
+                "\n{}Next steps:{}\n  1. Create PROMPT.md with your task\n  2. Run: ralph run",
+                colors::DIM,
+                colors::RESET
+            );
+        } else {
+            println!("\n{}", msg);
+            println!("\nNext steps:\n  1. Create PROMPT.md with your task\n  2. Run: ralph run");
+        }
+        return Ok(());
+    }
+
     // Handle --preset (with optional --backend override)
     if let Some(preset) = args.preset {
         let backend_override = args.backend.as_deref();
@@ -1662,6 +1826,43 @@ fn init_command(color_mode: ColorMode, args: InitArgs) -> Result<()> {
         }
     }
 
+    // Handle --template (with optional --backend override)
+    if let Some(template) = args.template {
+        let project_name = match args.name {
+            Some(name) => name,
+            None => std::env::current_dir()?.file_name().map_or_else(
+                || "project".to_string(),
+                |n| n.to_string_lossy().into_owned(),
+            ),
+        };
+        let init = init::init_from_template(
+            &template,
+            &project_name,
+            args.backend.as_deref(),
+            args.force,
+        )
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let msg = format!(
+            "Created {} files from template '{}'",
+            init.written.len(),
+            template
+        );
+        if use_colors {
+            println!("{}✓{} {}", colors::GREEN, colors::RESET, msg);
+        } else {
+            println!("{}", msg);
+        }
+        for path in &init.skipped {
+            println!(
+                "  skipped {} (already exists; use --force to overwrite)",
+                path.display()
+            );
+        }
+        println!("\nNext steps:\n  1. Review ralph.yml and PROMPT.md\n  2. Run: ralph run");
+        return Ok(());
+    }
+
     // Handle --backend alone (minimal config)
     if let Some(backend) = args.backend {
         match init::init_from_backend(&backend, args.force) {
@@ -1695,8 +1896,10 @@ fn init_command(color_mode: ColorMode, args: InitArgs) -> Result<()> {
     // No flag specified - show help
     println!("Initialize a new ralph.yml configuration file.\n");
     println!("Usage:");
+    println!("  ralph init --interactive         Answer a few questions to generate a config");
     println!("  ralph init --backend <backend>   Generate minimal config for backend");
     println!("  ralph init --preset <preset>     Use an embedded preset");
+    println!("  ralph init --template <source>   Scaffold from a template repository");
     println!("  ralph init --list-presets        Show available presets\n");
     println!("Backends: claude, kiro, gemini, codex, amp, custom");
     println!("\nRun 'ralph init --list-presets' to see available presets.");
@@ -1706,14 +1909,17 @@ fn init_command(color_mode: ColorMode, args: InitArgs) -> Result<()> {
 
 fn events_command(color_mode: ColorMode, args: EventsArgs) -> Result<()> {
     let use_colors = color_mode.should_use_colors();
+    if let Some(EventsCommands::Query(query)) = args.command {
+        return events_query_command(use_colors, query);
+    }
 
     // Read events path from marker file, fall back to default if marker doesn't exist
     // This ensures `ralph events` reads from the same events file as the active run
     let history = match args.file {
         Some(path) => EventHistory::new(path),
-        None => fs::read_to_string(".ralph/current-events")
-            .map(|s| EventHistory::new(s.trim()))
-            .unwrap_or_else(|_| EventHistory::default_path()),
+        None => LoopContext::primary(PathBuf::from("."))
+            .active_events_path()
+            .map_or_else(EventHistory::default_path, EventHistory::new),
     };
 
     // Handle clear command
@@ -1781,6 +1987,53 @@ fn events_command(color_mode: ColorMode, args: EventsArgs) -> Result<()> {
     Ok(())
 }
 
+fn events_query_command(use_colors: bool, args: EventsQueryArgs) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let ralph_dir = cwd.join(".ralph");
+    let index = EventIndex::open(&ralph_dir).context("Failed to index event journals")?;
+    let query = EventQuery {
+        topic: args.topic.map(Topic::new),
+        since: args.since,
+        until: args.until,
+        payload_contains: args.payload_contains,
+    };
+    let mut matches = index
+        .query(&ralph_dir, &query)
+        .context("Failed to read event journals")?;
+    if let Some(n) = args.last {
+        matches.drain(..matches.len().saturating_sub(n));
+    }
+
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&matches)?);
+        return Ok(());
+    }
+    if matches.is_empty() {
+        if use_colors {
+            println!("{}No matching events found.{}", colors::DIM, colors::RESET);
+        } else {
+            println!("No matching events found.");
+        }
+        return Ok(());
+    }
+    for group in matches.chunk_by(|a, b| a.session == b.session) {
+        let records: Vec<_> = group.iter().map(|m| m.record.clone()).collect();
+        if use_colors {
+            println!(
+                "{}Session {}{}",
+                colors::BOLD,
+                group[0].session,
+                colors::RESET
+            );
+        } else {
+            println!("Session {}", group[0].session);
+        }
+        display::print_events_table(&records, use_colors);
+        println!();
+    }
+    Ok(())
+}
+
 fn clean_command(
     config_sources: &[ConfigSource],
     color_mode: ColorMode,
@@ -1881,6 +2134,8 @@ fn clean_command(
 fn emit_command(color_mode: ColorMode, args: EmitArgs) -> Result<()> {
     let use_colors = color_mode.should_use_colors();
 
+    let topic = Topic::parse(&args.topic).context("Invalid topic")?;
+
     // Generate timestamp if not provided
     let ts = args.ts.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
 
@@ -1896,7 +2151,7 @@ fn emit_command(color_mode: ColorMode, args: EmitArgs) -> Result<()> {
     // Build the event record
     // We use serde_json directly to ensure proper escaping
     let record = serde_json::json!({
-        "topic": args.topic,
+        "topic": topic.as_str(),
         "payload": if args.json && !payload.is_empty() {
             // Parse and embed as object
             serde_json::from_str::<serde_json::Value>(&payload)?
@@ -1910,28 +2165,14 @@ fn emit_command(color_mode: ColorMode, args: EmitArgs) -> Result<()> {
 
     // Read events path from marker file, fall back to CLI arg if marker doesn't exist
     // This ensures `ralph emit` writes to the same events file as the active run
-    let events_file = fs::read_to_string(".ralph/current-events")
-        .map(|s| PathBuf::from(s.trim()))
-        .unwrap_or_else(|_| args.file.clone());
+    let events_file = LoopContext::primary(PathBuf::from("."))
+        .active_events_path()
+        .unwrap_or_else(|| args.file.clone());
 
-    // Ensure parent directory exists
-    if let Some(parent) = events_file.parent()
-        && !parent.as_os_str().is_empty()
-    {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
-    }
-
-    // Append to file
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&events_file)
-        .with_context(|| format!("Failed to open events file: {}", events_file.display()))?;
-
-    // Write as single-line JSON (JSONL format)
-    let json_line = serde_json::to_string(&record)?;
-    writeln!(file, "{}", json_line)?;
+    // Append under the events file lock (creates the parent directory if needed)
+    EventWriter::new(&events_file)
+        .append(&record)
+        .with_context(|| format!("Failed to write events file: {}", events_file.display()))?;
 
     // Success message
     if use_colors {
@@ -1939,10 +2180,10 @@ fn emit_command(color_mode: ColorMode, args: EmitArgs) -> Result<()> {
             "{}✓{} Event emitted: {}",
             colors::GREEN,
             colors::RESET,
-            args.topic
+            topic
         );
     } else {
-        println!("Event emitted: {}", args.topic);
+        println!("Event emitted: {}", topic);
     }
 
     Ok(())
@@ -2354,6 +2595,115 @@ mod tests {
         assert!(matches!(cli.command, Some(Commands::Doctor(_))));
     }
 
+    #[test]
+    fn test_events_query_parses_filters() {
+        let cli = Cli::try_parse_from([
+            "ralph",
+            "events",
+            "query",
+            "--topic",
+            "build.*",
+            "--since",
+            "2h",
+            "--payload-contains",
+            "auth",
+        ])
+        .expect("CLI parse failed");
+        let Some(Commands::Events(EventsArgs {
+            command: Some(EventsCommands::Query(query)),
+            ..
+        })) = cli.command
+        else {
+            panic!("expected events query");
+        };
+        assert_eq!(query.topic.as_deref(), Some("build.*"));
+        assert_eq!(query.payload_contains.as_deref(), Some("auth"));
+        assert!(query.since.is_some());
+
+        // The plain filters still work without the subcommand
+        assert!(Cli::try_parse_from(["ralph", "events", "--last", "5"]).is_ok());
+        assert!(Cli::try_parse_from(["ralph", "events", "query", "--since", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_since_accepts_durations_and_timestamps() {
+        let before = Utc::now();
+        let two_hours = parse_since("2h").unwrap();
+        assert!(two_hours >= before - chrono::Duration::hours(2));
+        assert!(two_hours <= Utc::now() - chrono::Duration::hours(2));
+        assert!(parse_since("7d").unwrap() < parse_since("30m").unwrap());
+
+        assert_eq!(
+            parse_since("2026-01-01T09:00:00+01:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2026-01-01T08:00:00+00:00"
+        );
+        for bad in ["", "h", "2w", "-1h", "2.5h", "2é"] {
+            assert!(parse_since(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_run_parses_start_event() {
+        let cli = Cli::try_parse_from(["ralph", "run", "--start-event", "review.request:Re-check"])
+            .expect("CLI parse failed");
+        let Some(Commands::Run(RunArgs {
+            start_event: Some(start),
+            ..
+        })) = cli.command
+        else {
+            panic!("expected run with --start-event");
+        };
+        assert_eq!(start.topic, "review.request");
+        assert_eq!(start.payload.as_deref(), Some("Re-check"));
+
+        assert!(
+            Cli::try_parse_from(["ralph", "run", "--start-event", "a.b", "--continue"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["ralph", "run", "--start-event", "build.*"]).is_err());
+    }
+
+    #[test]
+    fn test_cost_parses_breakdown_flags() {
+        let cli = Cli::try_parse_from(["ralph", "cost", "--by-topic"]).expect("CLI parse failed");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Cost(cost::CostArgs { by_topic: true, .. }))
+        ));
+
+        assert!(Cli::try_parse_from(["ralph", "cost", "--by-hat", "--by-topic"]).is_err());
+    }
+
+    #[test]
+    fn test_bench_backends_parses_runs() {
+        let cli = Cli::try_parse_from(["ralph", "bench", "backends", "-n", "5", "-b", "claude"])
+            .expect("CLI parse failed");
+        let Some(Commands::Bench(bench::BenchArgs {
+            command: bench::BenchCommands::Backends(args),
+        })) = cli.command
+        else {
+            panic!("expected bench backends");
+        };
+        assert_eq!(args.runs, 5);
+        assert_eq!(args.backends, vec!["claude".to_string()]);
+
+        assert!(Cli::try_parse_from(["ralph", "bench", "backends", "-n", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_run_task_conflicts_with_prompt_flags() {
+        let cli = Cli::try_parse_from(["ralph", "run", "--task", "Fix the login bug"])
+            .expect("CLI parse failed");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Run(RunArgs { task: Some(ref task), .. })) if task == "Fix the login bug"
+        ));
+
+        assert!(Cli::try_parse_from(["ralph", "run", "--task", "x", "-p", "y"]).is_err());
+        assert!(Cli::try_parse_from(["ralph", "run", "--task", "x", "-P", "PROMPT.md"]).is_err());
+    }
+
     #[test]
     fn test_tutorial_parses_command() {
         let cli = Cli::try_parse_from(["ralph", "tutorial"]).expect("CLI parse failed");
@@ -2891,14 +3241,22 @@ core:
             prompt_text: None,
             backend: Some("claude".to_string()),
             prompt_file: None,
+            task: None,
+            start_event: None,
             max_iterations: None,
             completion_promise: None,
             dry_run: false,
+            plan_only: false,
+            detach: false,
             continue_mode: false,
+            bootstrap_from: None,
             no_tui: true,
             autonomous: false,
             idle_timeout: None,
+            dashboard: false,
+            trace_events: false,
             exclusive: false,
+            force: false,
             no_auto_merge: false,
             skip_preflight: true,
             verbose: false,
@@ -2922,6 +3280,38 @@ core:
         assert!(err.to_string().contains("scratchpad not found"));
     }
 
+    #[tokio::test]
+    async fn test_run_command_dry_run_task_needs_no_prompt_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let _cwd = CwdGuard::set(temp_dir.path());
+
+        let mut args = default_run_args();
+        args.dry_run = true;
+        args.task = Some("Fix the login bug".to_string());
+
+        run_command(&[], false, ColorMode::Never, args)
+            .await
+            .expect("dry run should succeed without PROMPT.md");
+    }
+
+    #[tokio::test]
+    async fn test_run_command_plan_only_needs_no_backend() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let _cwd = CwdGuard::set(temp_dir.path());
+
+        let mut args = default_run_args();
+        args.plan_only = true;
+        args.backend = Some("auto".to_string());
+        args.task = Some("Fix the login bug".to_string());
+
+        run_command(&[], false, ColorMode::Never, args)
+            .await
+            .expect("plan-only should succeed without a backend");
+        let scratchpad =
+            std::fs::read_to_string(temp_dir.path().join(".ralph/agent/scratchpad.md")).unwrap();
+        assert!(scratchpad.contains("## PLAN SKELETON"));
+    }
+
     #[tokio::test]
     async fn test_run_command_dry_run_inline_prompt_skips_execution() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,68 @@
+//! `ralph pause` and `ralph resume`: hold a running loop between iterations.
+//!
+//! Both commands only touch the loop's pause signal file (see
+//! `ralph_core::loop_control`); the loop notices the change before its next
+//! iteration, so the iteration in flight always finishes.
+
+use crate::display::colors;
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use ralph_core::{LoopContext, loop_control};
+use std::path::PathBuf;
+
+/// Arguments for the pause and resume subcommands.
+#[derive(Parser, Debug)]
+pub struct PauseArgs {
+    /// Workspace of the loop, e.g. a worktree (default: current directory)
+    #[arg(long)]
+    pub root: Option<PathBuf>,
+}
+
+/// Executes `ralph pause`.
+pub fn pause(args: &PauseArgs, use_colors: bool) -> Result<()> {
+    let path = pause_path(args);
+    let requested = loop_control::request_pause(&path)
+        .with_context(|| format!("Failed to request pause: {}", path.display()))?;
+    if requested {
+        print_status(
+            use_colors,
+            "Pause requested; the loop holds after its current iteration. Run `ralph resume` to continue.",
+        );
+    } else {
+        println!("The loop is already paused or pausing.");
+    }
+    Ok(())
+}
+
+/// Executes `ralph resume`.
+///
+/// `ralph resume` used to continue a stopped loop (now `ralph run --continue`),
+/// so it fails rather than succeeding quietly when nothing is paused.
+pub fn resume(args: &PauseArgs, use_colors: bool) -> Result<()> {
+    let path = pause_path(args);
+    let resumed = loop_control::request_resume(&path)
+        .with_context(|| format!("Failed to resume: {}", path.display()))?;
+    if !resumed {
+        bail!(
+            "The loop isn't paused. To continue a stopped loop from its scratchpad, use `ralph run --continue`."
+        );
+    }
+    print_status(
+        use_colors,
+        "Resumed; the loop continues with its next iteration.",
+    );
+    Ok(())
+}
+
+fn pause_path(args: &PauseArgs) -> PathBuf {
+    let root = args.root.clone().unwrap_or_else(|| PathBuf::from("."));
+    LoopContext::primary(root).pause_requested_path()
+}
+
+fn print_status(use_colors: bool, message: &str) {
+    if use_colors {
+        println!("{}✓{} {message}", colors::GREEN, colors::RESET);
+    } else {
+        println!("{message}");
+    }
+}
@@ -0,0 +1,113 @@
+//! `ralph run --plan-only`: draft a plan without calling a backend.
+//!
+//! Runs only the deterministic parts of a run: hat validation, routing
+//! simulation, prompt rendering, a map of the repository, and a read of the
+//! scratchpad. The plan skeleton built from them is appended to the
+//! scratchpad, where the first real iteration (or a human) picks it up.
+
+use anyhow::{Context, Result};
+use ralph_core::plan_skeleton::{self, PlanInputs, RepoMap};
+use ralph_core::scratchpad::Scratchpad;
+use ralph_core::{HatRegistry, LoopContext, RalphConfig};
+use std::io::Write;
+
+use crate::dry_run::{describe_end, describe_step, render_prompts, simulate_routing};
+
+/// Drafts the plan skeleton and appends it to the scratchpad, reporting to `writer`.
+pub(crate) fn run<W: Write>(writer: &mut W, config: &RalphConfig, use_colors: bool) -> Result<()> {
+    let registry = HatRegistry::from_config(config);
+    crate::hats::validate_hats(writer, config, &registry, use_colors)?;
+    writeln!(writer)?;
+
+    let prompt = crate::loop_runner::resolve_prompt_content(&config.event_loop)?;
+    let workspace = &config.core.workspace_root;
+    let ctx = LoopContext::primary(workspace.clone());
+    let out_dir = ctx.agent_dir().join("plan-only");
+    let prompts = render_prompts(config, &prompt, &out_dir)?;
+
+    let simulation = simulate_routing(config, &prompt);
+    let repo = RepoMap::scan(workspace);
+    crate::ensure_scratchpad_directory(config)?;
+    let scratchpad = Scratchpad::new(config.core.resolve_path(&config.core.scratchpad));
+    let current = scratchpad
+        .read()
+        .with_context(|| format!("Failed to read {}", scratchpad.path().display()))?;
+
+    let skeleton = plan_skeleton::render(&PlanInputs {
+        objective: &prompt,
+        repo: &repo,
+        route: simulation.steps.iter().map(describe_step).collect(),
+        route_end: Some(describe_end(config, &simulation.end)),
+        mentioned_paths: plan_skeleton::mentioned_paths(&prompt, workspace),
+        scratchpad: &current.content,
+    });
+    scratchpad
+        .append(&skeleton)
+        .with_context(|| format!("Failed to write {}", scratchpad.path().display()))?;
+
+    writeln!(
+        writer,
+        "Plan skeleton appended to {} (no backend was called)",
+        scratchpad.path().display()
+    )?;
+    let toolchains: Vec<&str> = repo.toolchains.iter().map(|t| t.name).collect();
+    writeln!(
+        writer,
+        "  Repo: {} files{}",
+        repo.total_files,
+        if toolchains.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", toolchains.join(", "))
+        }
+    )?;
+    writeln!(
+        writer,
+        "  Route: {} step(s); {}",
+        simulation.steps.len(),
+        describe_end(config, &simulation.end)
+    )?;
+    writeln!(
+        writer,
+        "  Prompts: {} rendered to {}",
+        prompts.len(),
+        out_dir.display()
+    )?;
+    writeln!(writer, "Run `ralph run --continue` to build from the plan.")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_only_appends_skeleton_to_scratchpad() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut config: RalphConfig = serde_yaml::from_str(
+            r#"
+hats:
+  builder:
+    name: "Builder"
+    triggers: ["build.task"]
+    publishes: ["LOOP_COMPLETE"]
+"#,
+        )
+        .unwrap();
+        config.core.workspace_root = temp_dir.path().to_path_buf();
+        config.event_loop.prompt =
+            Some("Speed up the parser\n- Profile it\n- Fix the hot loop".to_string());
+        std::fs::write(temp_dir.path().join("Cargo.toml"), "").unwrap();
+
+        let mut out = Vec::new();
+        run(&mut out, &config, false).unwrap();
+
+        let scratchpad =
+            std::fs::read_to_string(config.core.resolve_path(&config.core.scratchpad)).unwrap();
+        assert!(scratchpad.contains("## PLAN SKELETON"));
+        assert!(scratchpad.contains("- [ ] Profile it\n- [ ] Fix the hot loop"));
+        assert!(scratchpad.contains("Rust (`Cargo.toml`)"));
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains("no backend was called"));
+    }
+}
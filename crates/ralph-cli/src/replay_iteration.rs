@@ -0,0 +1,428 @@
+//! CLI command for `ralph replay-iteration`.
+//!
+//! Re-runs one recorded iteration, optionally with an edited prompt, in a
+//! throwaway worktree at the commit it started from, then compares the
+//! replay with what the original iteration did: its output against the saved
+//! `iter-<n>.out`, and its file changes against the commit the loop was on
+//! when the next iteration started.
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use ralph_adapters::{CliBackend, CliExecutor};
+use ralph_core::repro::IterationManifest;
+use ralph_core::{EventRecord, remove_worktree, run_git};
+use std::fs;
+use std::io::stdout;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::ConfigSource;
+use crate::display::colors;
+use crate::repro::{Recorded, checkout, load_iteration, session_name};
+
+/// Events file `ralph emit` writes to inside the replay worktree.
+const REPLAY_EVENTS: &str = ".ralph/events-replay.jsonl";
+
+/// Pathspec that keeps Ralph's own state out of the diffs.
+const EXCLUDE_RALPH_DIR: &str = ":(exclude).ralph";
+
+/// Re-run one iteration in a throwaway worktree and diff it against the original.
+#[derive(Parser, Debug)]
+pub struct ReplayIterationArgs {
+    /// Session: `current`, a run ID (e.g. 20260127-123456), or a path to an events file
+    pub session: String,
+
+    /// Iteration to replay
+    pub iteration: u32,
+
+    /// Open the recorded prompt in $VISUAL/$EDITOR before replaying
+    #[arg(long)]
+    pub edit_prompt: bool,
+
+    /// Keep the replay worktree instead of removing it afterwards
+    #[arg(long)]
+    pub keep: bool,
+}
+
+/// Execute the replay-iteration command.
+pub async fn execute(
+    config_sources: &[ConfigSource],
+    args: &ReplayIterationArgs,
+    use_colors: bool,
+) -> Result<()> {
+    let workspace = std::env::current_dir().context("Failed to get current directory")?;
+    let Recorded {
+        events,
+        records,
+        manifest,
+        prompt: recorded_prompt,
+    } = load_iteration(&workspace, &args.session, args.iteration)?;
+    let Some(head) = manifest.head.clone() else {
+        bail!("No commit was recorded for iteration {}", args.iteration);
+    };
+    let config = crate::load_config_with_overrides(config_sources)?;
+
+    let (bold, dim, yellow, reset) = if use_colors {
+        (colors::BOLD, colors::DIM, colors::YELLOW, colors::RESET)
+    } else {
+        ("", "", "", "")
+    };
+
+    let name = format!("{}-{}", session_name(&events), manifest.iteration);
+    let replay_dir = workspace.join(".ralph").join("replay");
+    fs::create_dir_all(&replay_dir)
+        .with_context(|| format!("Failed to create {}", replay_dir.display()))?;
+    let prompt_path = replay_dir.join(format!("{name}.prompt"));
+    fs::write(&prompt_path, &recorded_prompt)
+        .with_context(|| format!("Failed to write {}", prompt_path.display()))?;
+    if args.edit_prompt {
+        edit(&prompt_path)?;
+    }
+    let prompt = fs::read_to_string(&prompt_path)
+        .with_context(|| format!("Failed to read {}", prompt_path.display()))?;
+    if args.edit_prompt && prompt == recorded_prompt {
+        println!("{dim}Prompt unchanged; replaying it as recorded.{reset}");
+    }
+
+    let worktree = replay_dir.join(&name);
+    if worktree.exists() {
+        remove_worktree(&workspace, &worktree)
+            .with_context(|| format!("Failed to remove old replay at {}", worktree.display()))?;
+    }
+    checkout(&workspace, &worktree, &head)?;
+    if manifest.dirty.is_some() {
+        println!(
+            "{yellow}note{reset} the tree had uncommitted changes when iteration {} ran; the replay starts from {} without them",
+            manifest.iteration,
+            short(&head)
+        );
+    }
+
+    println!(
+        "{bold}Replaying iteration {} of {}{reset} {dim}({}, {}){reset}",
+        manifest.iteration,
+        session_name(&events),
+        manifest.hat,
+        manifest.backend
+    );
+    let output_path = replay_dir.join(format!("{name}.out"));
+    let outcome = replay(&config, &manifest, &worktree, &output_path, &prompt).await;
+    let result = outcome.and_then(|success| {
+        report(ReportRequest {
+            workspace: &workspace,
+            worktree: &worktree,
+            manifest: &manifest,
+            original_end: original_end(&records, &manifest),
+            output_path: &output_path,
+            success,
+            use_colors,
+        })
+    });
+
+    if args.keep {
+        println!("Worktree: {}", relative(&workspace, &worktree));
+    } else if let Err(e) = remove_worktree(&workspace, &worktree) {
+        eprintln!("Failed to remove {}: {e}", worktree.display());
+    }
+    println!("Prompt:   {}", relative(&workspace, &prompt_path));
+    println!("Output:   {}", relative(&workspace, &output_path));
+    result
+}
+
+/// Runs the iteration's backend on `prompt` in `worktree`; returns whether it succeeded.
+async fn replay(
+    config: &ralph_core::RalphConfig,
+    manifest: &IterationManifest,
+    worktree: &Path,
+    output_path: &Path,
+    prompt: &str,
+) -> Result<bool> {
+    // Give `ralph emit` in the worktree its own events file
+    let events_path = worktree.join(REPLAY_EVENTS);
+    if let Some(parent) = events_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&events_path, "")?;
+    fs::write(worktree.join(".ralph/current-events"), REPLAY_EVENTS)?;
+
+    let mut backend = CliBackend::from_name(&manifest.backend)
+        .with_context(|| format!("Unknown backend '{}'", manifest.backend))?;
+    if let Some(model) = &manifest.model {
+        backend = backend.with_model(model);
+    }
+    let credentials = ralph_core::credentials::resolve(config, worktree)?;
+    let timeout = config.adapter_settings(&manifest.backend).timeout;
+    let result = CliExecutor::new(backend.with_env_vars(&credentials))
+        .with_limits(config.cli.limits)
+        .with_working_dir(worktree)
+        .with_output_log(output_path)
+        .execute(prompt, stdout(), Some(Duration::from_secs(timeout)), false)
+        .await
+        .with_context(|| format!("Failed to run {}", manifest.backend))?;
+    Ok(result.success)
+}
+
+/// Commit the loop was on when the iteration after `manifest`'s started.
+///
+/// That's the original iteration's committed outcome. Returns `None` when
+/// there's no later iteration or it started on the same commit, in which
+/// case the original's changes weren't committed and can't be compared.
+fn original_end(records: &[EventRecord], manifest: &IterationManifest) -> Option<String> {
+    let next = IterationManifest::find(records, manifest.iteration + 1)?.head?;
+    (manifest.head.as_deref() != Some(next.as_str())).then_some(next)
+}
+
+/// Where the loop saved an iteration's output, next to its saved prompt.
+fn original_output(workspace: &Path, manifest: &IterationManifest) -> Option<PathBuf> {
+    let prompt_file = manifest.prompt_file.as_deref()?;
+    Some(workspace.join(prompt_file).with_extension("out"))
+}
+
+struct ReportRequest<'a> {
+    workspace: &'a Path,
+    worktree: &'a Path,
+    manifest: &'a IterationManifest,
+    original_end: Option<String>,
+    output_path: &'a Path,
+    success: bool,
+    use_colors: bool,
+}
+
+/// Prints how the replay's output and changes differ from the original's.
+fn report(request: ReportRequest<'_>) -> Result<()> {
+    let (bold, dim, reset) = if request.use_colors {
+        (colors::BOLD, colors::DIM, colors::RESET)
+    } else {
+        ("", "", "")
+    };
+    let color = if request.use_colors {
+        "--color=always"
+    } else {
+        "--color=never"
+    };
+    let head = request.manifest.head.as_deref().unwrap_or("HEAD");
+
+    println!();
+    println!(
+        "{bold}Replay {}{reset}",
+        if request.success {
+            "finished"
+        } else {
+            "failed"
+        }
+    );
+
+    println!();
+    println!("{bold}Output vs original{reset}");
+    match original_output(request.workspace, request.manifest).filter(|path| path.exists()) {
+        Some(original) => {
+            let mut args = vec!["diff", "--no-index", color, "--"];
+            let (original, replay) = (
+                original.display().to_string(),
+                request.output_path.display().to_string(),
+            );
+            args.extend([original.as_str(), replay.as_str()]);
+            show_diff(request.workspace, &args, dim, reset)?;
+        }
+        None => println!("  {dim}(the original output wasn't saved){reset}"),
+    }
+
+    // Stage everything so new files show up, and diff the resulting tree
+    run_git(request.worktree, &["add", "-A"]).context("Failed to stage the replay")?;
+    let replay_tree =
+        run_git(request.worktree, &["write-tree"]).context("Failed to snapshot the replay")?;
+    let replay_tree = replay_tree.trim();
+
+    println!();
+    println!("{bold}Changes{reset}");
+    println!("  replay:");
+    show_diff(
+        request.worktree,
+        &[
+            "diff",
+            "--stat",
+            color,
+            head,
+            replay_tree,
+            "--",
+            ".",
+            EXCLUDE_RALPH_DIR,
+        ],
+        dim,
+        reset,
+    )?;
+    let Some(end) = &request.original_end else {
+        println!(
+            "  {dim}The original iteration's changes weren't committed before the next one started, so they can't be compared.{reset}"
+        );
+        return Ok(());
+    };
+    println!("  original ({}..{}):", short(head), short(end));
+    show_diff(
+        request.worktree,
+        &[
+            "diff",
+            "--stat",
+            color,
+            head,
+            end,
+            "--",
+            ".",
+            EXCLUDE_RALPH_DIR,
+        ],
+        dim,
+        reset,
+    )?;
+
+    println!();
+    println!("{bold}Replay vs original{reset}");
+    show_diff(
+        request.worktree,
+        &[
+            "diff",
+            color,
+            end,
+            replay_tree,
+            "--",
+            ".",
+            EXCLUDE_RALPH_DIR,
+        ],
+        dim,
+        reset,
+    )
+}
+
+/// Runs a `git diff` command, printing its output or a note when it's empty.
+fn show_diff(dir: &Path, args: &[&str], dim: &str, reset: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git diff")?;
+    // `git diff --no-index` exits 1 when the files differ
+    if output.status.code().is_none_or(|code| code > 1) {
+        bail!(
+            "git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let diff = String::from_utf8_lossy(&output.stdout);
+    if diff.trim().is_empty() {
+        println!("  {dim}(no differences){reset}");
+    } else {
+        print!("{diff}");
+    }
+    Ok(())
+}
+
+/// Opens `path` in the user's editor and waits for it to exit.
+fn edit(path: &Path) -> Result<()> {
+    let editor = editor_command(
+        std::env::var("VISUAL").ok().as_deref(),
+        std::env::var("EDITOR").ok().as_deref(),
+    );
+    let Some((program, args)) = editor.split_first() else {
+        bail!("No editor configured; set $EDITOR");
+    };
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start editor '{program}'"))?;
+    if !status.success() {
+        bail!("Editor '{program}' exited with {status}; not replaying");
+    }
+    Ok(())
+}
+
+/// Splits `$VISUAL`, else `$EDITOR`, else `vi`, into program and arguments.
+fn editor_command(visual: Option<&str>, editor: Option<&str>) -> Vec<String> {
+    [visual, editor]
+        .into_iter()
+        .flatten()
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or("vi")
+        .split_whitespace()
+        .map(str::to_string)
+        .collect()
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(8)]
+}
+
+fn relative(workspace: &Path, path: &Path) -> String {
+    path.strip_prefix(workspace)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(iteration: u32, head: &str) -> IterationManifest {
+        IterationManifest {
+            iteration,
+            hat: "builder".to_string(),
+            head: Some(head.to_string()),
+            dirty: None,
+            backend: "claude".to_string(),
+            backend_version: None,
+            model: None,
+            config_hash: "1111".to_string(),
+            prompt_hash: "2222".to_string(),
+            prompt_file: Some(format!(
+                ".ralph/agent/sessions/primary/iter-{iteration}.prompt"
+            )),
+        }
+    }
+
+    fn records(manifests: &[IterationManifest]) -> Vec<EventRecord> {
+        manifests
+            .iter()
+            .map(|m| {
+                EventRecord::new(
+                    m.iteration,
+                    "loop",
+                    &m.to_event(),
+                    None::<&ralph_proto::HatId>,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_original_end_is_the_next_iterations_commit() {
+        let records = records(&[manifest(1, "aaa"), manifest(2, "bbb"), manifest(3, "bbb")]);
+        assert_eq!(
+            original_end(&records, &manifest(1, "aaa")),
+            Some("bbb".to_string())
+        );
+        // Iteration 2 didn't commit, and iteration 3 was the last
+        assert_eq!(original_end(&records, &manifest(2, "bbb")), None);
+        assert_eq!(original_end(&records, &manifest(3, "bbb")), None);
+    }
+
+    #[test]
+    fn test_original_output_sits_next_to_the_prompt() {
+        assert_eq!(
+            original_output(Path::new("/repo"), &manifest(4, "aaa")),
+            Some(PathBuf::from(
+                "/repo/.ralph/agent/sessions/primary/iter-4.out"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_editor_command_prefers_visual_then_editor() {
+        assert_eq!(
+            editor_command(Some("code --wait"), Some("nano")),
+            vec!["code", "--wait"]
+        );
+        assert_eq!(editor_command(Some(" "), Some("nano")), vec!["nano"]);
+        assert_eq!(editor_command(None, None), vec!["vi"]);
+    }
+}
@@ -0,0 +1,298 @@
+//! CLI command for `ralph repro`.
+//!
+//! Rebuilds one iteration's inputs from the manifest the loop recorded
+//! before running it: a detached worktree at the recorded commit and the
+//! exact prompt the backend received. Anything that no longer matches
+//! (config, backend version, uncommitted changes) is reported as drift.
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use ralph_core::repro::{self, IterationManifest};
+use ralph_core::{EventHistory, EventRecord};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::ConfigSource;
+use crate::display::colors;
+use crate::export::resolve_session;
+
+/// Rebuild an iteration's prompt and commit.
+#[derive(Parser, Debug)]
+pub struct ReproArgs {
+    /// Session: `current`, a run ID (e.g. 20260127-123456), or a path to an events file
+    pub session: String,
+
+    /// Iteration to reproduce
+    pub iteration: u32,
+
+    /// Only compare the recorded inputs with the current ones; don't create a worktree
+    #[arg(long)]
+    pub check: bool,
+}
+
+/// Current values of the inputs that can drift between runs.
+#[derive(Debug, Default)]
+struct Current {
+    config_hash: Option<String>,
+    backend_version: Option<String>,
+}
+
+/// Execute the repro command.
+pub fn execute(config_sources: &[ConfigSource], args: &ReproArgs, use_colors: bool) -> Result<()> {
+    let workspace = std::env::current_dir().context("Failed to get current directory")?;
+    let Recorded {
+        events,
+        manifest,
+        prompt,
+        ..
+    } = load_iteration(&workspace, &args.session, args.iteration)?;
+    let prompt_file = manifest.prompt_file.as_deref().unwrap_or_default();
+
+    let current = Current {
+        config_hash: crate::load_config_with_overrides(config_sources)
+            .ok()
+            .map(|config| repro::config_hash(&config)),
+        backend_version: manifest.backend_version.as_ref().and_then(|_| {
+            ralph_adapters::CliBackend::from_name(&manifest.backend)
+                .ok()
+                .and_then(|backend| repro::backend_version(&backend.command))
+        }),
+    };
+
+    let (bold, dim, yellow, reset) = if use_colors {
+        (colors::BOLD, colors::DIM, colors::YELLOW, colors::RESET)
+    } else {
+        ("", "", "", "")
+    };
+    println!(
+        "{bold}Iteration {} of {}{reset} {dim}({}){reset}",
+        manifest.iteration,
+        session_name(&events),
+        manifest.hat
+    );
+    println!(
+        "  commit   {}",
+        manifest.head.as_deref().unwrap_or("(not a git repository)")
+    );
+    println!(
+        "  prompt   {} {dim}{}{reset}",
+        manifest.prompt_hash, prompt_file
+    );
+    println!(
+        "  backend  {}{}{}",
+        manifest.backend,
+        manifest
+            .backend_version
+            .as_deref()
+            .map(|version| format!(" {version}"))
+            .unwrap_or_default(),
+        manifest
+            .model
+            .as_deref()
+            .map(|model| format!(", model {model}"))
+            .unwrap_or_default()
+    );
+    for warning in drift(&manifest, &current) {
+        println!("  {yellow}drift{reset}    {warning}");
+    }
+
+    if args.check {
+        return Ok(());
+    }
+    let Some(head) = &manifest.head else {
+        bail!("No commit was recorded for iteration {}", args.iteration);
+    };
+    let name = format!("{}-{}", session_name(&events), manifest.iteration);
+    let repro_dir = workspace.join(".ralph").join("repro");
+    let worktree = repro_dir.join(&name);
+    checkout(&workspace, &worktree, head)?;
+    let saved_prompt = repro_dir.join(format!("{name}.prompt"));
+    fs::write(&saved_prompt, &prompt)
+        .with_context(|| format!("Failed to write {}", saved_prompt.display()))?;
+
+    println!();
+    let relative = |path: &Path| {
+        path.strip_prefix(&workspace)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    println!("Worktree: {}", relative(&worktree));
+    println!("Prompt:   {}", relative(&saved_prompt));
+    println!(
+        "{dim}Run {} on the prompt from the worktree to replay the iteration.{reset}",
+        manifest.backend
+    );
+    Ok(())
+}
+
+/// An iteration's manifest and saved prompt, read back from its session.
+pub(crate) struct Recorded {
+    /// The session's events file.
+    pub events: PathBuf,
+    pub records: Vec<EventRecord>,
+    pub manifest: IterationManifest,
+    pub prompt: String,
+}
+
+/// Loads the manifest and prompt recorded for `iteration` of `session`.
+///
+/// Fails if the iteration has no manifest or its saved prompt is missing or
+/// no longer matches the recorded hash.
+pub(crate) fn load_iteration(workspace: &Path, session: &str, iteration: u32) -> Result<Recorded> {
+    let events = resolve_session(workspace, session)?;
+    let key = crate::decrypt::artifact_key(workspace)?;
+    let records = EventHistory::new(&events)
+        .with_key(key.clone())
+        .read_all()
+        .with_context(|| format!("Failed to read events at {}", events.display()))?;
+    let Some(manifest) = IterationManifest::find(&records, iteration) else {
+        bail!(
+            "No manifest for iteration {} in {}; it never started or was run by an older ralph",
+            iteration,
+            events.display()
+        );
+    };
+
+    let Some(prompt_file) = &manifest.prompt_file else {
+        bail!("Iteration {}'s prompt wasn't saved", iteration);
+    };
+    let prompt_path = workspace.join(prompt_file);
+    let prompt = ralph_core::encryption::read_artifact(&prompt_path, key.as_ref())
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .with_context(|| format!("Failed to read prompt at {}", prompt_path.display()))?;
+    if repro::prompt_hash(&prompt) != manifest.prompt_hash {
+        bail!(
+            "{} no longer matches the prompt hash recorded for iteration {}",
+            prompt_path.display(),
+            iteration
+        );
+    }
+    Ok(Recorded {
+        events,
+        records,
+        manifest,
+        prompt,
+    })
+}
+
+/// Lists the recorded inputs that no longer match.
+fn drift(manifest: &IterationManifest, current: &Current) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if manifest.dirty.is_some() {
+        warnings
+            .push("the tree had uncommitted changes, which the worktree won't have".to_string());
+    }
+    match &current.config_hash {
+        Some(hash) if *hash != manifest.config_hash => warnings.push(format!(
+            "config changed ({} -> {hash})",
+            manifest.config_hash
+        )),
+        Some(_) => {}
+        None => warnings.push("current config couldn't be loaded to compare".to_string()),
+    }
+    if let (Some(then), Some(now)) = (&manifest.backend_version, &current.backend_version)
+        && then != now
+    {
+        warnings.push(format!("{} is now {now}", manifest.backend));
+    }
+    warnings
+}
+
+/// Run ID of an events file, or its file stem for other names.
+pub(crate) fn session_name(events: &Path) -> String {
+    let stem = events
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    stem.strip_prefix("events-").unwrap_or(&stem).to_string()
+}
+
+/// Checks out `commit` in a detached worktree at `path`, reusing one already there.
+pub(crate) fn checkout(workspace: &Path, path: &Path, commit: &str) -> Result<()> {
+    if path.exists() {
+        let existing = ralph_core::get_head_sha(path).ok();
+        if existing.as_deref() == Some(commit) {
+            return Ok(());
+        }
+        bail!(
+            "{} exists at a different commit; remove it with `git worktree remove`",
+            path.display()
+        );
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let output = Command::new("git")
+        .args(["worktree", "add", "--detach"])
+        .arg(path)
+        .arg(commit)
+        .current_dir(workspace)
+        .output()
+        .context("Failed to run git worktree add")?;
+    if !output.status.success() {
+        bail!(
+            "git worktree add failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> IterationManifest {
+        IterationManifest {
+            iteration: 2,
+            hat: "builder".to_string(),
+            head: Some("abc".to_string()),
+            dirty: None,
+            backend: "claude".to_string(),
+            backend_version: Some("2.1.0".to_string()),
+            model: None,
+            config_hash: "1111".to_string(),
+            prompt_hash: repro::prompt_hash("prompt"),
+            prompt_file: Some(".ralph/agent/sessions/primary/iter-2.prompt".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_no_drift_when_inputs_match() {
+        let current = Current {
+            config_hash: Some("1111".to_string()),
+            backend_version: Some("2.1.0".to_string()),
+        };
+        assert!(drift(&manifest(), &current).is_empty());
+    }
+
+    #[test]
+    fn test_drift_reports_each_changed_input() {
+        let manifest = IterationManifest {
+            dirty: Some("ffff".to_string()),
+            ..manifest()
+        };
+        let current = Current {
+            config_hash: Some("2222".to_string()),
+            backend_version: Some("2.2.0".to_string()),
+        };
+
+        let warnings = drift(&manifest, &current);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("uncommitted"));
+        assert!(warnings[1].contains("1111 -> 2222"));
+        assert_eq!(warnings[2], "claude is now 2.2.0");
+    }
+
+    #[test]
+    fn test_session_name_strips_events_prefix() {
+        assert_eq!(
+            session_name(Path::new(".ralph/events-20260127-123456.jsonl")),
+            "20260127-123456"
+        );
+        assert_eq!(session_name(Path::new(".ralph/events.jsonl")), "events");
+    }
+}
@@ -0,0 +1,159 @@
+//! CLI command for `ralph tools run`.
+//!
+//! The guarded shell proxy: agents run shell commands as
+//! `ralph tools run -- <command>` so the `shell_tool` policy (allow and deny
+//! patterns, timeout, output cap) applies. Every invocation is appended to
+//! `.ralph/agent/tool-audit.jsonl`; refusals and failures are also published
+//! as `tool.denied` and `tool.failed` events so hats can react to them.
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use ralph_core::shell_tool::{self, ToolInvocation};
+use ralph_core::{EventWriter, LoopContext, RalphConfig};
+use std::path::{Path, PathBuf};
+
+/// Arguments for the `run` command.
+#[derive(Parser, Debug)]
+pub struct RunArgs {
+    /// Working directory (default: current directory)
+    #[arg(long)]
+    pub root: Option<PathBuf>,
+
+    /// The command to run, after `--`; a single argument is passed to the
+    /// shell as-is, several are quoted as needed and joined
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    pub command: Vec<String>,
+}
+
+/// Execute the run command, exiting with the command's exit code.
+pub async fn execute(args: RunArgs) -> Result<()> {
+    let root = args.root.unwrap_or_else(|| PathBuf::from("."));
+    let config = load_config(&root);
+    let command = command_line(&args.command);
+
+    let run = shell_tool::run_guarded(&config.shell_tool, &command, &root)
+        .await
+        .with_context(|| format!("Failed to run {command}"))?;
+    let invocation = &run.invocation;
+
+    print!("{}", run.stdout);
+    eprint!("{}", run.stderr);
+    if let Some(reason) = &invocation.denied {
+        eprintln!("ralph: command refused by shell_tool policy: {reason}");
+    }
+
+    shell_tool::append_audit(&root, invocation).context("Failed to write tool audit log")?;
+    if let Some(topic) = invocation.topic() {
+        emit(&root, topic, invocation)?;
+    }
+
+    let code = invocation.process_exit_code();
+    if code != 0 {
+        std::process::exit(code);
+    }
+    Ok(())
+}
+
+/// Builds the shell command line: a lone argument is already one
+/// (`ralph tools run -- "cargo test && cargo clippy"`), otherwise each
+/// argument is quoted when the shell would split or expand it.
+fn command_line(args: &[String]) -> String {
+    if let [command] = args {
+        return command.clone();
+    }
+    args.iter()
+        .map(|arg| {
+            let plain = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+            if plain {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Appends a `tool.denied` or `tool.failed` event to the active run's events
+/// file.
+fn emit(root: &Path, topic: &str, invocation: &ToolInvocation) -> Result<()> {
+    let line = serde_json::to_string(&serde_json::json!({
+        "topic": topic,
+        "payload": invocation,
+        "ts": invocation.ts,
+    }))?;
+    let events_file = LoopContext::primary(root.to_path_buf()).current_events_path();
+    EventWriter::new(&events_file)
+        .append_lines(&[line])
+        .with_context(|| format!("Failed to write events file: {}", events_file.display()))
+}
+
+/// Load config from the workspace root, falling back to defaults.
+fn load_config(root: &Path) -> RalphConfig {
+    ["ralph.yml", "ralph.yaml"]
+        .iter()
+        .map(|name| root.join(name))
+        .filter(|path| path.exists())
+        .find_map(|path| RalphConfig::from_file(&path).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_command_line_quotes_split_arguments() {
+        let args = |list: &[&str]| list.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(command_line(&args(&["ls -la && pwd"])), "ls -la && pwd");
+        assert_eq!(
+            command_line(&args(&["sh", "-c", "exit 5", "it's"])),
+            r"sh -c 'exit 5' 'it'\''s'"
+        );
+    }
+
+    #[test]
+    fn test_emit_writes_to_current_events_file() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join(".ralph")).unwrap();
+        fs::write(
+            dir.path().join(".ralph/current-events"),
+            ".ralph/events-1.jsonl",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("ralph.yml"),
+            "shell_tool:\n  deny: [\"rm *\"]\n",
+        )
+        .unwrap();
+
+        let config = load_config(dir.path());
+        let invocation = ToolInvocation {
+            ts: "2026-01-01T00:00:00Z".to_string(),
+            command: "rm -rf src".to_string(),
+            allowed: false,
+            denied: shell_tool::check(&config.shell_tool, "rm -rf src").err(),
+            exit_code: None,
+            timed_out: false,
+            duration_ms: 0,
+            truncated: false,
+        };
+        emit(dir.path(), invocation.topic().unwrap(), &invocation).unwrap();
+
+        let events = fs::read_to_string(dir.path().join(".ralph/events-1.jsonl")).unwrap();
+        let event: serde_json::Value = serde_json::from_str(events.trim()).unwrap();
+        assert_eq!(event["topic"], "tool.denied");
+        assert_eq!(event["payload"]["command"], "rm -rf src");
+        assert!(
+            event["payload"]["denied"]
+                .as_str()
+                .unwrap()
+                .contains("deny pattern 'rm *'")
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! CLI commands for the `ralph tools scratchpad` namespace.
+//!
+//! Provides conflict-aware access to the scratchpad:
+//! - `show`: Print the scratchpad and its revision
+//! - `write`: Replace the scratchpad, merging with edits since `--base`
+//! - `append`: Append a paragraph (never conflicts)
+//!
+//! When a write conflicts, the scratchpad is left untouched and a
+//! `scratchpad.conflict` event is emitted so Ralph can resolve it.
+
+use crate::display::colors;
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand, ValueEnum};
+use ralph_core::scratchpad::CONFLICT_TOPIC;
+use ralph_core::{EventWriter, LoopContext, Scratchpad, WriteOutcome};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Output format for `scratchpad show`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Raw scratchpad content
+    #[default]
+    Text,
+    /// JSON with content and revision
+    Json,
+    /// Revision only
+    Quiet,
+}
+
+/// Conflict-aware scratchpad commands.
+#[derive(Parser, Debug)]
+pub struct ScratchpadArgs {
+    #[command(subcommand)]
+    pub command: ScratchpadCommands,
+
+    /// Working directory (default: current directory)
+    #[arg(long, global = true)]
+    pub root: Option<PathBuf>,
+
+    /// Scratchpad path relative to the root (default: .ralph/agent/scratchpad.md)
+    #[arg(long, global = true)]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ScratchpadCommands {
+    /// Print the scratchpad and its revision
+    Show(ShowArgs),
+
+    /// Replace the scratchpad, merging with edits made since --base
+    Write(WriteArgs),
+
+    /// Append a paragraph to the scratchpad
+    Append(AppendArgs),
+}
+
+/// Arguments for the `scratchpad show` command.
+#[derive(Parser, Debug)]
+pub struct ShowArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `scratchpad write` command.
+#[derive(Parser, Debug)]
+pub struct WriteArgs {
+    /// Revision the new content was based on (from `show` or the
+    /// `<scratchpad revision="...">` tag). Omit to overwrite unconditionally.
+    #[arg(long)]
+    pub base: Option<String>,
+
+    /// Read the new content from this file instead of stdin
+    #[arg(long, short = 'f')]
+    pub file: Option<PathBuf>,
+}
+
+/// Arguments for the `scratchpad append` command.
+#[derive(Parser, Debug)]
+pub struct AppendArgs {
+    /// Text to append
+    pub text: String,
+}
+
+/// Execute a scratchpad command.
+pub fn execute(args: ScratchpadArgs, use_colors: bool) -> Result<()> {
+    let root = args.root.unwrap_or_else(|| PathBuf::from("."));
+    let scratchpad = Scratchpad::new(get_scratchpad_path(&root, args.path.as_deref()));
+
+    match args.command {
+        ScratchpadCommands::Show(show_args) => execute_show(&scratchpad, &show_args),
+        ScratchpadCommands::Write(write_args) => {
+            execute_write(&scratchpad, &root, write_args, use_colors)
+        }
+        ScratchpadCommands::Append(append_args) => {
+            let revision = scratchpad
+                .append(&append_args.text)
+                .context("Failed to append to scratchpad")?;
+            print_status(use_colors, &format!("Appended to scratchpad ({revision})"));
+            Ok(())
+        }
+    }
+}
+
+/// Gets the scratchpad path.
+fn get_scratchpad_path(root: &Path, path: Option<&Path>) -> PathBuf {
+    root.join(path.unwrap_or(Path::new(".ralph/agent/scratchpad.md")))
+}
+
+fn execute_show(scratchpad: &Scratchpad, args: &ShowArgs) -> Result<()> {
+    let snapshot = scratchpad.read().context("Failed to read scratchpad")?;
+    match args.format {
+        OutputFormat::Text => {
+            print!("{}", snapshot.content);
+        }
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "path": scratchpad.path(),
+                "revision": snapshot.revision,
+                "content": snapshot.content,
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Quiet => {
+            println!("{}", snapshot.revision);
+        }
+    }
+    Ok(())
+}
+
+fn execute_write(
+    scratchpad: &Scratchpad,
+    root: &Path,
+    args: WriteArgs,
+    use_colors: bool,
+) -> Result<()> {
+    let content = match &args.file {
+        Some(file) => fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?,
+        None => {
+            let mut content = String::new();
+            std::io::stdin()
+                .read_to_string(&mut content)
+                .context("Failed to read stdin")?;
+            content
+        }
+    };
+
+    let outcome = scratchpad
+        .write(args.base.as_deref(), &content)
+        .context("Failed to write scratchpad")?;
+    match outcome {
+        WriteOutcome::Written { revision } => {
+            print_status(use_colors, &format!("Scratchpad written ({revision})"));
+            Ok(())
+        }
+        WriteOutcome::Merged { revision } => {
+            print_status(
+                use_colors,
+                &format!("Scratchpad merged with concurrent edits ({revision})"),
+            );
+            Ok(())
+        }
+        WriteOutcome::Conflict {
+            current_revision,
+            conflict_path,
+        } => {
+            emit_conflict(
+                root,
+                scratchpad.path(),
+                args.base.as_deref().unwrap_or_default(),
+                &current_revision,
+                &conflict_path,
+            )?;
+            bail!(
+                "Scratchpad changed since revision {} and the edits conflict. \
+                 The scratchpad was not modified; the conflicted merge is at {}",
+                args.base.unwrap_or_default(),
+                conflict_path.display()
+            )
+        }
+    }
+}
+
+/// Publishes a `scratchpad.conflict` event for Ralph to resolve.
+fn emit_conflict(
+    root: &Path,
+    path: &Path,
+    base: &str,
+    current: &str,
+    conflict_path: &Path,
+) -> Result<()> {
+    let payload = format!(
+        "Concurrent scratchpad edits conflict.\n\
+         - scratchpad: {}\n\
+         - base_revision: {base}\n\
+         - current_revision: {current}\n\
+         - conflict_file: {}\n\
+         Resolve the <<<<<<< markers in the conflict file, then run \
+         `ralph tools scratchpad write --base {current} --file <conflict_file>`.",
+        path.display(),
+        conflict_path.display(),
+    );
+    let record = serde_json::json!({
+        "topic": CONFLICT_TOPIC,
+        "payload": payload,
+        "ts": chrono::Utc::now().to_rfc3339(),
+    });
+    let events_file = LoopContext::primary(root.to_path_buf()).current_events_path();
+    EventWriter::new(&events_file)
+        .append(&record)
+        .with_context(|| format!("Failed to write events file: {}", events_file.display()))
+}
+
+fn print_status(use_colors: bool, message: &str) {
+    if use_colors {
+        println!("{}✓{} {}", colors::GREEN, colors::RESET, message);
+    } else {
+        println!("{message}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_conflicting_write_emits_event() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".ralph")).expect("ralph dir");
+        fs::write(
+            root.join(".ralph/current-events"),
+            ".ralph/events-test.jsonl",
+        )
+        .expect("marker");
+
+        let scratchpad = Scratchpad::new(get_scratchpad_path(root, None));
+        scratchpad.append("goal: ship v1").expect("append");
+        let base = scratchpad.read().expect("read").revision;
+        fs::write(scratchpad.path(), "goal: ship v2\n").expect("human edit");
+
+        let ours = root.join("ours.md");
+        fs::write(&ours, "goal: ship v1.1\n").expect("ours");
+        let err = execute_write(
+            &scratchpad,
+            root,
+            WriteArgs {
+                base: Some(base),
+                file: Some(ours),
+            },
+            false,
+        )
+        .expect_err("conflict");
+        assert!(err.to_string().contains("conflict"));
+
+        assert_eq!(
+            fs::read_to_string(scratchpad.path()).expect("scratchpad"),
+            "goal: ship v2\n"
+        );
+        let events = fs::read_to_string(root.join(".ralph/events-test.jsonl")).expect("events");
+        assert!(events.contains("\"topic\":\"scratchpad.conflict\""));
+    }
+}
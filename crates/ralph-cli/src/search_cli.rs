@@ -0,0 +1,151 @@
+//! CLI command for `ralph tools search`.
+//!
+//! Searches the workspace's embedding index for code related to a query and
+//! prints the best snippets, so an iteration can find its way around without
+//! grepping the whole repository. The index is built on first use when no
+//! loop has built it yet.
+
+use crate::display::colors;
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use ralph_core::SearchIndexConfig;
+use ralph_core::search_index::{SearchHit, SearchIndex};
+use std::path::{Path, PathBuf};
+
+/// Output format for `search`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Locations with snippets
+    #[default]
+    Text,
+    /// JSON array of hits
+    Json,
+}
+
+/// Arguments for the `search` command.
+#[derive(Parser, Debug)]
+pub struct SearchArgs {
+    /// What to look for, in words or identifiers
+    pub query: String,
+
+    /// Maximum number of results
+    #[arg(long, short = 'n', default_value_t = 5)]
+    pub limit: usize,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Re-embed files changed since the last checkpoint before searching
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// Working directory (default: current directory)
+    #[arg(long)]
+    pub root: Option<PathBuf>,
+}
+
+/// Execute the search command.
+pub fn execute(args: SearchArgs, use_colors: bool) -> Result<()> {
+    let root = args.root.clone().unwrap_or_else(|| PathBuf::from("."));
+    let hits = search(&root, &args)?;
+
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&hits)?),
+        OutputFormat::Text if hits.is_empty() => println!("No matches for '{}'", args.query),
+        OutputFormat::Text => {
+            for hit in &hits {
+                print_hit(hit, use_colors);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Loads (or builds) the index and runs the query.
+fn search(root: &Path, args: &SearchArgs) -> Result<Vec<SearchHit>> {
+    let existing = SearchIndex::load(root).context("Failed to read the search index")?;
+    let index = match existing {
+        Some(mut index) => {
+            if args.refresh {
+                index.refresh(root);
+                index
+                    .save(root)
+                    .context("Failed to save the search index")?;
+            }
+            index
+        }
+        None => {
+            eprintln!("Building the search index (first use)...");
+            let mut index = SearchIndex::new(&SearchIndexConfig::default());
+            index.refresh(root);
+            index
+                .save(root)
+                .context("Failed to save the search index")?;
+            index
+        }
+    };
+    Ok(index.search(root, &args.query, args.limit))
+}
+
+fn print_hit(hit: &SearchHit, use_colors: bool) {
+    let stale = if hit.stale {
+        " [changed since indexed]"
+    } else {
+        ""
+    };
+    if use_colors {
+        println!(
+            "{}{}:{}-{}{} {}({:.2}){}{stale}",
+            colors::BOLD,
+            hit.path,
+            hit.start_line,
+            hit.end_line,
+            colors::RESET,
+            colors::DIM,
+            hit.score,
+            colors::RESET,
+        );
+    } else {
+        println!(
+            "{}:{}-{} ({:.2}){stale}",
+            hit.path, hit.start_line, hit.end_line, hit.score
+        );
+    }
+    for line in hit.snippet.lines() {
+        println!("    {line}");
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_search_builds_index_on_first_use() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("billing.rs"), "fn charge_invoice() {}\n").unwrap();
+        fs::write(root.join("auth.rs"), "fn verify_password() {}\n").unwrap();
+        let args = |query: &str, refresh: bool| SearchArgs {
+            query: query.to_string(),
+            limit: 5,
+            format: OutputFormat::Json,
+            refresh,
+            root: None,
+        };
+
+        let hits = search(root, &args("invoice", false)).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].path, "billing.rs");
+        assert!(root.join(ralph_core::search_index::INDEX_FILE).exists());
+
+        // New files are only seen after a refresh
+        fs::write(root.join("refund.rs"), "fn refund_invoice() {}\n").unwrap();
+        assert_eq!(search(root, &args("refund", false)).unwrap().len(), 0);
+        let hits = search(root, &args("refund", true)).unwrap();
+        assert_eq!(hits[0].path, "refund.rs");
+    }
+}
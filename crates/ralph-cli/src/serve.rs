@@ -0,0 +1,718 @@
+//! HTTP control API for driving Ralph programmatically.
+//!
+//! Provides the `ralph serve` command. Every request must carry
+//! `Authorization: Bearer <token>`; the token comes from `--token` or
+//! `RALPH_API_TOKEN`.
+//!
+//! | Method | Path                    | Action                                   |
+//! |--------|-------------------------|------------------------------------------|
+//! | POST   | `/tasks`                | Start a headless loop from a prompt      |
+//! | GET    | `/sessions/{id}`        | Session status                           |
+//! | GET    | `/sessions/{id}/bus`    | Event bus snapshot (`ralph bus dump`)    |
+//! | POST   | `/sessions/{id}/events` | Append an event to the loop's events file |
+//! | POST   | `/sessions/{id}/stop`   | Request a graceful stop                  |
+//!
+//! A session id is either one returned by `POST /tasks` or any loop id from
+//! the loop registry (`ralph loops list`). Session bookkeeping is always
+//! compiled; the HTTP server itself needs the `api` feature.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use ralph_core::{EventWriter, LoopContext, LoopEntry, LoopRegistry};
+use ralph_proto::BusSnapshot;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::process::Command;
+
+/// Environment variable holding the API token when `--token` is not given.
+pub(crate) const TOKEN_ENV: &str = "RALPH_API_TOKEN";
+
+/// Arguments for the serve subcommand.
+#[derive(Parser, Debug)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:7071")]
+    pub bind: String,
+
+    /// Bearer token clients must send (default: $RALPH_API_TOKEN)
+    #[arg(long)]
+    pub token: Option<String>,
+
+    /// Workspace root directory (default: current directory)
+    #[arg(long)]
+    pub workspace: Option<PathBuf>,
+}
+
+/// Body of `POST /tasks`.
+#[derive(Debug, Deserialize)]
+pub struct TaskRequest {
+    /// Prompt text for the loop.
+    pub prompt: String,
+
+    /// Config source passed to `ralph run -c` (file path or `builtin:<preset>`).
+    #[serde(default)]
+    pub config: Option<String>,
+
+    /// Override for `event_loop.max_iterations`.
+    #[serde(default)]
+    pub max_iterations: Option<u32>,
+
+    /// Override for `cli.backend`.
+    #[serde(default)]
+    pub backend: Option<String>,
+}
+
+/// Body of `POST /sessions/{id}/events`.
+#[derive(Debug, Deserialize)]
+pub struct EventRequest {
+    /// Event topic (e.g. `human.guidance`).
+    pub topic: String,
+
+    /// String or JSON payload.
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Lifecycle of a session as reported by the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    Running,
+    Exited,
+}
+
+/// Response body for session endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    /// Registry id of the loop, once it has registered.
+    pub loop_id: Option<String>,
+    pub pid: Option<u32>,
+    pub prompt: String,
+    pub status: SessionStatus,
+    pub exit_code: Option<i32>,
+    pub workspace: String,
+    pub started: DateTime<Utc>,
+}
+
+/// Errors surfaced to API clients.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("Session '{0}' not found")]
+    NotFound(String),
+
+    /// The session exists but hasn't produced the requested resource yet.
+    #[error("{0}")]
+    Unavailable(String),
+
+    #[error("{0}")]
+    BadRequest(String),
+
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+/// A loop started through `POST /tasks`.
+struct Session {
+    prompt: String,
+    started: DateTime<Utc>,
+    pid: u32,
+    /// Set by the session's reaper task once the loop exits.
+    exit_code: Arc<OnceLock<i32>>,
+}
+
+/// Tracks sessions and performs the actions behind each endpoint.
+pub struct SessionManager {
+    workspace_root: PathBuf,
+    ralph_bin: PathBuf,
+    token: String,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionManager {
+    pub fn new(workspace_root: PathBuf, ralph_bin: PathBuf, token: String) -> Self {
+        Self {
+            workspace_root,
+            ralph_bin,
+            token,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Checks an `Authorization` header value against the configured token.
+    pub fn authorized(&self, header: Option<&str>) -> bool {
+        bearer_matches(header, &self.token)
+    }
+
+    /// Starts a headless `ralph run` for the task and returns its session.
+    ///
+    /// A task waits on the loop so it is reaped as soon as it exits. Must be
+    /// called within a tokio runtime.
+    pub fn submit(&self, request: &TaskRequest) -> Result<SessionInfo, ApiError> {
+        if request.prompt.trim().is_empty() {
+            return Err(ApiError::BadRequest("prompt must not be empty".to_string()));
+        }
+
+        let id = generate_session_id();
+        if self.lock_sessions().contains_key(&id) {
+            return Err(ApiError::Internal(format!("Session '{id}' already exists")));
+        }
+        let log_dir = self.workspace_root.join(".ralph/sessions");
+        fs::create_dir_all(&log_dir)?;
+        let log = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(log_dir.join(format!("{id}.log")))
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::AlreadyExists => {
+                    ApiError::Internal(format!("Session '{id}' already exists"))
+                }
+                _ => e.into(),
+            })?;
+
+        let mut command = Command::new(&self.ralph_bin);
+        command
+            .arg("run")
+            .arg("--autonomous")
+            .arg("--prompt")
+            .arg(&request.prompt)
+            .current_dir(&self.workspace_root)
+            .env_remove(TOKEN_ENV)
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log);
+        if let Some(config) = &request.config {
+            command.arg("--config").arg(config);
+        }
+        if let Some(max) = request.max_iterations {
+            command.arg("--max-iterations").arg(max.to_string());
+        }
+        if let Some(backend) = &request.backend {
+            command.arg("--backend").arg(backend);
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| ApiError::Internal(format!("Failed to start ralph: {e}")))?;
+        // Only unset once the child has been waited on, which only the reaper does.
+        let pid = child.id().unwrap_or_default();
+        tracing::info!(session = %id, pid, "Started session");
+
+        let exit_code = Arc::new(OnceLock::new());
+        let reaped = Arc::clone(&exit_code);
+        let session_id = id.clone();
+        tokio::spawn(async move {
+            let code = match child.wait().await {
+                Ok(status) => status.code().unwrap_or(-1),
+                Err(e) => {
+                    tracing::warn!(session = %session_id, error = %e, "Failed to wait for session");
+                    -1
+                }
+            };
+            tracing::info!(session = %session_id, exit_code = code, "Session exited");
+            let _ = reaped.set(code);
+        });
+
+        let session = Session {
+            prompt: request.prompt.clone(),
+            started: Utc::now(),
+            pid,
+            exit_code,
+        };
+        let info = self.describe_session(&id, &session);
+        self.lock_sessions().insert(id, session);
+        Ok(info)
+    }
+
+    /// Reports the status of a session or registry loop.
+    pub fn get(&self, id: &str) -> Result<SessionInfo, ApiError> {
+        if let Some(session) = self.lock_sessions().get(id) {
+            return Ok(self.describe_session(id, session));
+        }
+
+        let entry = self.registry_entry(id)?;
+        Ok(SessionInfo {
+            id: entry.id.clone(),
+            loop_id: Some(entry.id.clone()),
+            pid: Some(entry.pid),
+            prompt: entry.prompt.clone(),
+            status: if entry.is_alive() {
+                SessionStatus::Running
+            } else {
+                SessionStatus::Exited
+            },
+            exit_code: None,
+            workspace: loop_workspace(&entry).display().to_string(),
+            started: entry.started,
+        })
+    }
+
+    /// Appends an event to the session's current events file.
+    pub fn append_event(&self, id: &str, request: &EventRequest) -> Result<(), ApiError> {
+        if request.topic.trim().is_empty() {
+            return Err(ApiError::BadRequest("topic must not be empty".to_string()));
+        }
+
+        let workspace = self.session_workspace(id)?;
+        let events_path = LoopContext::primary(workspace).current_events_path();
+
+        let record = serde_json::json!({
+            "topic": request.topic,
+            "payload": request.payload.clone().unwrap_or(serde_json::Value::Null),
+            "ts": Utc::now().to_rfc3339(),
+        });
+        EventWriter::new(events_path).append(&record)?;
+        Ok(())
+    }
+
+    /// Reads the event bus snapshot the session's loop last wrote.
+    pub fn bus(&self, id: &str) -> Result<BusSnapshot, ApiError> {
+        let workspace = self.session_workspace(id)?;
+        let path = workspace.join(".ralph/bus.json");
+        let json = fs::read_to_string(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                ApiError::Unavailable(format!("Session '{id}' has no event bus snapshot yet"))
+            }
+            _ => e.into(),
+        })?;
+        serde_json::from_str(&json).map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    /// Requests a graceful stop at the loop's next iteration boundary.
+    pub fn stop(&self, id: &str) -> Result<(), ApiError> {
+        let workspace = self.session_workspace(id)?;
+        let stop_path = workspace.join(".ralph/stop-requested");
+        if let Some(parent) = stop_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(stop_path, "")?;
+        Ok(())
+    }
+
+    fn lock_sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, Session>> {
+        // A poisoned map is still structurally valid; keep serving.
+        self.sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn registry(&self) -> LoopRegistry {
+        LoopRegistry::new(&self.workspace_root)
+    }
+
+    fn registry_entry(&self, id: &str) -> Result<LoopEntry, ApiError> {
+        self.registry()
+            .get(id)
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(id.to_string()))
+    }
+
+    /// Finds the registry entry a spawned session registered under.
+    fn entry_for_pid(&self, pid: u32) -> Option<LoopEntry> {
+        self.registry()
+            .list()
+            .ok()?
+            .into_iter()
+            .find(|entry| entry.pid == pid)
+    }
+
+    fn describe_session(&self, id: &str, session: &Session) -> SessionInfo {
+        let pid = session.pid;
+        let exit_code = session.exit_code.get().copied();
+        let entry = self.entry_for_pid(pid);
+        SessionInfo {
+            id: id.to_string(),
+            loop_id: entry.as_ref().map(|e| e.id.clone()),
+            pid: Some(pid),
+            prompt: session.prompt.clone(),
+            status: if exit_code.is_some() {
+                SessionStatus::Exited
+            } else {
+                SessionStatus::Running
+            },
+            exit_code,
+            workspace: entry
+                .as_ref()
+                .map_or_else(|| self.workspace_root.clone(), loop_workspace)
+                .display()
+                .to_string(),
+            started: session.started,
+        }
+    }
+
+    /// Resolves the directory whose `.ralph/` a session's loop reads.
+    fn session_workspace(&self, id: &str) -> Result<PathBuf, ApiError> {
+        let pid = self.lock_sessions().get(id).map(|s| s.pid);
+        if let Some(pid) = pid {
+            // Worktree loops register their worktree; until then it's the primary workspace.
+            return Ok(self
+                .entry_for_pid(pid)
+                .map_or_else(|| self.workspace_root.clone(), |e| loop_workspace(&e)));
+        }
+        Ok(loop_workspace(&self.registry_entry(id)?))
+    }
+}
+
+pub(crate) fn loop_workspace(entry: &LoopEntry) -> PathBuf {
+    entry
+        .worktree_path
+        .as_ref()
+        .map_or_else(|| PathBuf::from(&entry.workspace), PathBuf::from)
+}
+
+/// Generates a session ID: session-{timestamp}-{pid_hex}-{counter}
+///
+/// The process id and a per-process counter keep ids started in the same
+/// second distinct.
+pub(crate) fn generate_session_id() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs();
+    let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("session-{secs}-{:x}-{seq}", std::process::id())
+}
+
+/// Checks an `Authorization: Bearer` header value against `token`.
+pub(crate) fn bearer_matches(header: Option<&str>, token: &str) -> bool {
+    let Some(presented) = header.and_then(|h| h.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    constant_time_eq(presented.trim().as_bytes(), token.as_bytes())
+}
+
+/// Compares secrets without short-circuiting on the first mismatch.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub(crate) fn resolve_token(flag: Option<String>) -> Result<String> {
+    let token = flag
+        .or_else(|| std::env::var(TOKEN_ENV).ok())
+        .filter(|t| !t.trim().is_empty());
+    token.with_context(|| format!("An API token is required. Pass --token or set {TOKEN_ENV}."))
+}
+
+/// Runs the serve command until Ctrl+C.
+pub async fn execute(args: ServeArgs) -> Result<()> {
+    let token = resolve_token(args.token)?;
+    let workspace_root = match args.workspace {
+        Some(path) => ralph_adapters::normalize_path(
+            &path
+                .canonicalize()
+                .with_context(|| format!("Invalid workspace path: {}", path.display()))?,
+        ),
+        None => std::env::current_dir().context("Failed to get current directory")?,
+    };
+    let ralph_bin = std::env::current_exe().context("Failed to locate the ralph executable")?;
+    let manager = SessionManager::new(workspace_root, ralph_bin, token);
+
+    serve(manager, &args.bind).await
+}
+
+#[cfg(feature = "api")]
+async fn serve(manager: SessionManager, bind: &str) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(bind)
+        .await
+        .with_context(|| format!("Failed to bind {bind}"))?;
+    println!("Ralph API listening on http://{}", listener.local_addr()?);
+    axum::serve(listener, server::router(manager))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+#[cfg(not(feature = "api"))]
+#[allow(clippy::unused_async)]
+async fn serve(_manager: SessionManager, bind: &str) -> Result<()> {
+    anyhow::bail!(
+        "Cannot serve the API on {bind}: ralph was built without the `api` feature.\n\
+         Rebuild with `cargo install ralph-cli --features api`."
+    )
+}
+
+#[cfg(feature = "api")]
+mod server {
+    use super::{ApiError, EventRequest, SessionManager, TaskRequest};
+    use axum::Router;
+    use axum::extract::{Path, Request, State};
+    use axum::http::{StatusCode, header};
+    use axum::middleware::{self, Next};
+    use axum::response::{IntoResponse, Json, Response};
+    use axum::routing::{get, post};
+    use std::sync::Arc;
+
+    pub(super) fn router(manager: SessionManager) -> Router {
+        let manager = Arc::new(manager);
+        Router::new()
+            .route("/tasks", post(submit_task))
+            .route("/sessions/:id", get(get_session))
+            .route("/sessions/:id/bus", get(get_bus))
+            .route("/sessions/:id/events", post(post_event))
+            .route("/sessions/:id/stop", post(stop_session))
+            .layer(middleware::from_fn_with_state(
+                Arc::clone(&manager),
+                require_token,
+            ))
+            .with_state(manager)
+    }
+
+    impl IntoResponse for ApiError {
+        fn into_response(self) -> Response {
+            let (status, error) = match &self {
+                ApiError::NotFound(_) | ApiError::Unavailable(_) => {
+                    (StatusCode::NOT_FOUND, "Not Found")
+                }
+                ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "Bad Request"),
+                ApiError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal Error"),
+            };
+            let body = serde_json::json!({ "error": error, "message": self.to_string() });
+            (status, Json(body)).into_response()
+        }
+    }
+
+    async fn require_token(
+        State(manager): State<Arc<SessionManager>>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        let header = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok());
+        if manager.authorized(header) {
+            next.run(request).await
+        } else {
+            let body = serde_json::json!({
+                "error": "Unauthorized",
+                "message": "Missing or invalid bearer token",
+            });
+            (StatusCode::UNAUTHORIZED, Json(body)).into_response()
+        }
+    }
+
+    async fn submit_task(
+        State(manager): State<Arc<SessionManager>>,
+        Json(request): Json<TaskRequest>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        let session = manager.submit(&request)?;
+        Ok((StatusCode::ACCEPTED, Json(session)))
+    }
+
+    async fn get_session(
+        State(manager): State<Arc<SessionManager>>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        Ok(Json(manager.get(&id)?))
+    }
+
+    async fn get_bus(
+        State(manager): State<Arc<SessionManager>>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        Ok(Json(manager.bus(&id)?))
+    }
+
+    async fn post_event(
+        State(manager): State<Arc<SessionManager>>,
+        Path(id): Path<String>,
+        Json(request): Json<EventRequest>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        manager.append_event(&id, &request)?;
+        Ok(StatusCode::ACCEPTED)
+    }
+
+    async fn stop_session(
+        State(manager): State<Arc<SessionManager>>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        manager.stop(&id)?;
+        Ok(StatusCode::ACCEPTED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn manager(dir: &Path) -> SessionManager {
+        SessionManager::new(
+            dir.to_path_buf(),
+            PathBuf::from("/nonexistent/ralph"),
+            "s3cret".to_string(),
+        )
+    }
+
+    fn register_loop(dir: &Path) -> String {
+        let entry =
+            LoopEntry::with_workspace("fix the bug", None::<String>, dir.display().to_string());
+        LoopRegistry::new(dir).register(entry).unwrap()
+    }
+
+    #[test]
+    fn test_authorized_requires_matching_bearer_token() {
+        let temp = TempDir::new().unwrap();
+        let manager = manager(temp.path());
+
+        assert!(manager.authorized(Some("Bearer s3cret")));
+        assert!(!manager.authorized(Some("Bearer wrong")));
+        assert!(!manager.authorized(Some("s3cret")));
+        assert!(!manager.authorized(None));
+    }
+
+    #[test]
+    fn test_submit_rejects_empty_prompt() {
+        let temp = TempDir::new().unwrap();
+        let request = TaskRequest {
+            prompt: "  ".to_string(),
+            config: None,
+            max_iterations: None,
+            backend: None,
+        };
+        assert!(matches!(
+            manager(temp.path()).submit(&request),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_submitted_session_is_reaped_on_exit() {
+        let temp = TempDir::new().unwrap();
+        // `true` ignores the `run` arguments and exits immediately.
+        let manager = SessionManager::new(
+            temp.path().to_path_buf(),
+            PathBuf::from("true"),
+            "s3cret".to_string(),
+        );
+        let request = TaskRequest {
+            prompt: "fix the bug".to_string(),
+            config: None,
+            max_iterations: None,
+            backend: None,
+        };
+
+        let started = manager.submit(&request).unwrap();
+        assert!(
+            temp.path()
+                .join(format!(".ralph/sessions/{}.log", started.id))
+                .exists()
+        );
+
+        let mut info = manager.get(&started.id).unwrap();
+        for _ in 0..100 {
+            if info.exit_code.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            info = manager.get(&started.id).unwrap();
+        }
+        assert_eq!(info.status, SessionStatus::Exited);
+        assert_eq!(info.exit_code, Some(0));
+    }
+
+    #[test]
+    fn test_session_ids_are_unique_within_a_second() {
+        let ids: std::collections::HashSet<String> =
+            (0..1000).map(|_| generate_session_id()).collect();
+        assert_eq!(ids.len(), 1000);
+    }
+
+    #[test]
+    fn test_unknown_session_is_not_found() {
+        let temp = TempDir::new().unwrap();
+        let manager = manager(temp.path());
+        assert!(matches!(manager.get("nope"), Err(ApiError::NotFound(_))));
+        assert!(matches!(manager.stop("nope"), Err(ApiError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_bus_reads_the_loops_snapshot() {
+        let temp = TempDir::new().unwrap();
+        let id = register_loop(temp.path());
+        let manager = manager(temp.path());
+        assert!(matches!(manager.bus(&id), Err(ApiError::Unavailable(_))));
+
+        let mut bus = ralph_proto::EventBus::new();
+        bus.register(ralph_proto::Hat::new("builder", "Builder").subscribe("build.*"));
+        bus.publish(ralph_proto::Event::new("build.task", "Add login"));
+        fs::create_dir_all(temp.path().join(".ralph")).unwrap();
+        fs::write(
+            temp.path().join(".ralph/bus.json"),
+            serde_json::to_string(&bus.snapshot()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(manager.bus(&id).unwrap(), bus.snapshot());
+        assert!(matches!(manager.bus("nope"), Err(ApiError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_registry_loop_is_a_session() {
+        let temp = TempDir::new().unwrap();
+        let id = register_loop(temp.path());
+
+        let info = manager(temp.path()).get(&id).unwrap();
+        assert_eq!(info.loop_id.as_deref(), Some(id.as_str()));
+        assert_eq!(info.prompt, "fix the bug");
+        assert_eq!(info.status, SessionStatus::Running);
+    }
+
+    #[test]
+    fn test_append_event_uses_current_events_marker() {
+        let temp = TempDir::new().unwrap();
+        let id = register_loop(temp.path());
+        fs::write(
+            temp.path().join(".ralph/current-events"),
+            ".ralph/events-20260101-000000.jsonl",
+        )
+        .unwrap();
+
+        let request = EventRequest {
+            topic: "human.guidance".to_string(),
+            payload: Some(serde_json::json!("focus on tests")),
+        };
+        manager(temp.path()).append_event(&id, &request).unwrap();
+
+        let written =
+            fs::read_to_string(temp.path().join(".ralph/events-20260101-000000.jsonl")).unwrap();
+        let record: serde_json::Value = serde_json::from_str(written.trim()).unwrap();
+        assert_eq!(record["topic"], "human.guidance");
+        assert_eq!(record["payload"], "focus on tests");
+    }
+
+    #[test]
+    fn test_stop_writes_signal_file() {
+        let temp = TempDir::new().unwrap();
+        let id = register_loop(temp.path());
+
+        manager(temp.path()).stop(&id).unwrap();
+        assert!(temp.path().join(".ralph/stop-requested").exists());
+    }
+
+    #[test]
+    fn test_resolve_token_rejects_blank() {
+        assert!(resolve_token(Some("   ".to_string())).is_err());
+        assert_eq!(resolve_token(Some("abc".to_string())).unwrap(), "abc");
+    }
+}
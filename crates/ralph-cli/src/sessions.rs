@@ -0,0 +1,444 @@
+//! CLI commands for the `ralph sessions` namespace.
+//!
+//! A session is one `ralph run`: its event journal at
+//! `.ralph/events-<run-id>.jsonl`, plus the cost entries and commits recorded
+//! between its start and the start of the next session.
+//!
+//! Subcommands:
+//! - `diff`: Compare two sessions of the same config side by side
+
+use crate::OutputFormat;
+use crate::display::{colors, truncate};
+use crate::export::resolve_session;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use clap::{Parser, Subcommand};
+use ralph_core::{EventHistory, EventRecord, HistoryEventType, LoopContext, LoopHistory, Usage};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Inspect and compare past sessions.
+#[derive(Parser, Debug)]
+pub struct SessionsArgs {
+    #[command(subcommand)]
+    pub command: SessionsCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SessionsCommands {
+    /// Compare iterations, cost, hats, events, and changes of two sessions
+    Diff(DiffArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct DiffArgs {
+    /// Baseline session: `current`, a run ID (e.g. 20260127-123456), or a path to an events file
+    pub a: String,
+
+    /// Session to compare against the baseline
+    pub b: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+/// Execute a sessions command.
+pub fn execute(args: &SessionsArgs, use_colors: bool) -> Result<()> {
+    match &args.command {
+        SessionsCommands::Diff(diff) => execute_diff(diff, use_colors),
+    }
+}
+
+fn execute_diff(args: &DiffArgs, use_colors: bool) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let history = LoopHistory::new(LoopContext::primary(cwd.clone()).history_path());
+
+    let a = load_session(&cwd, &args.a, &history)?;
+    let b = load_session(&cwd, &args.b, &history)?;
+
+    if args.format == OutputFormat::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "a": a, "b": b }))?
+        );
+        return Ok(());
+    }
+
+    print_diff(&a, &b, use_colors);
+    Ok(())
+}
+
+/// What one session did.
+#[derive(Debug, Default, Serialize)]
+struct SessionStats {
+    id: String,
+    started: Option<DateTime<Utc>>,
+    duration_seconds: i64,
+    iterations: u32,
+    events: usize,
+    /// Events published, by topic.
+    topics: BTreeMap<String, usize>,
+    /// Activations, by hat.
+    hats: BTreeMap<String, usize>,
+    cost: Usage,
+    /// Commits made during the session; `None` without git history.
+    changes: Option<ChangeStats>,
+}
+
+/// Commits made during a session.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+struct ChangeStats {
+    commits: usize,
+    insertions: u64,
+    deletions: u64,
+    files: BTreeSet<String>,
+}
+
+impl SessionStats {
+    /// Counts iterations, topics, and hat activations from journal records.
+    fn from_records(id: &str, records: &[EventRecord]) -> Self {
+        let mut stats = Self {
+            id: id.to_string(),
+            events: records.len(),
+            ..Self::default()
+        };
+        for record in records {
+            stats.iterations = stats.iterations.max(record.iteration);
+            *stats.topics.entry(record.topic.clone()).or_default() += 1;
+            if let Some(hat) = record.triggered.as_ref().filter(|h| !h.is_empty()) {
+                *stats.hats.entry(hat.clone()).or_default() += 1;
+            }
+        }
+
+        let timestamps: Vec<_> = records.iter().filter_map(|r| parse_ts(&r.ts)).collect();
+        if let (Some(first), Some(last)) = (timestamps.iter().min(), timestamps.iter().max()) {
+            stats.started = Some(*first);
+            stats.duration_seconds = (*last - *first).num_seconds();
+        }
+        stats
+    }
+}
+
+/// Reads a session's journal and attributes history and commits to it.
+fn load_session(workspace: &Path, session: &str, history: &LoopHistory) -> Result<SessionStats> {
+    let path = resolve_session(workspace, session)?;
+    let records = EventHistory::new(&path)
+        .with_key(crate::decrypt::artifact_key(workspace)?)
+        .read_all()
+        .with_context(|| format!("Failed to read events at {}", path.display()))?;
+    if records.is_empty() {
+        bail!("No events recorded in {}", path.display());
+    }
+
+    let id = run_id(&path).unwrap_or(session).to_string();
+    let mut stats = SessionStats::from_records(&id, &records);
+    if let Some(started) = run_id(&path).and_then(parse_run_id) {
+        stats.started = Some(stats.started.map_or(started, |s| s.min(started)));
+    }
+    let Some(start) = stats.started else {
+        return Ok(stats);
+    };
+    let end = path.parent().and_then(|dir| next_session_start(dir, start));
+
+    let entries = history.read_all().with_context(|| {
+        format!(
+            "Failed to read loop history at {}",
+            history.path().display()
+        )
+    })?;
+    for entry in entries {
+        if let HistoryEventType::CostRecorded(cost) = entry.event_type
+            && in_window(entry.timestamp, start, end)
+        {
+            stats.cost += cost.usage;
+        }
+    }
+
+    stats.changes = commits_between(workspace, start, end);
+    Ok(stats)
+}
+
+/// Run ID from an `events-<id>.jsonl` file name.
+fn run_id(path: &Path) -> Option<&str> {
+    path.file_stem()?.to_str()?.strip_prefix("events-")
+}
+
+fn parse_run_id(id: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(id, "%Y%m%d-%H%M%S")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+fn parse_ts(ts: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Start of the first session in `dir` that began after `start`.
+fn next_session_start(dir: &Path, start: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension().is_some_and(|ext| ext == "jsonl") {
+                parse_run_id(run_id(&path)?)
+            } else {
+                None
+            }
+        })
+        .filter(|t| *t > start)
+        .min()
+}
+
+fn in_window(ts: DateTime<Utc>, start: DateTime<Utc>, end: Option<DateTime<Utc>>) -> bool {
+    ts >= start && end.is_none_or(|end| ts < end)
+}
+
+/// Summarizes commits on HEAD made within the window.
+fn commits_between(
+    workspace: &Path,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+) -> Option<ChangeStats> {
+    let mut args = vec![
+        "log".to_string(),
+        "--numstat".to_string(),
+        "--format=commit %H".to_string(),
+        format!("--since={}", start.to_rfc3339()),
+    ];
+    if let Some(end) = end {
+        args.push(format!("--until={}", end.to_rfc3339()));
+    }
+    let output = Command::new("git")
+        .args(&args)
+        .current_dir(workspace)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_numstat(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses `git log --numstat --format="commit %H"` output.
+fn parse_numstat(log: &str) -> ChangeStats {
+    let mut stats = ChangeStats::default();
+    for line in log.lines() {
+        if line.starts_with("commit ") {
+            stats.commits += 1;
+            continue;
+        }
+        let mut parts = line.splitn(3, '\t');
+        let (Some(added), Some(deleted), Some(file)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        // Binary files report `-` for both counts
+        stats.insertions += added.parse::<u64>().unwrap_or(0);
+        stats.deletions += deleted.parse::<u64>().unwrap_or(0);
+        stats.files.insert(file.to_string());
+    }
+    stats
+}
+
+/// Signed difference of two counts, e.g. `+3`, `-2`, or blank when equal.
+fn delta(a: i64, b: i64) -> String {
+    match b - a {
+        0 => String::new(),
+        d => format!("{d:+}"),
+    }
+}
+
+fn cost_delta(a: f64, b: f64) -> String {
+    let d = b - a;
+    if d.abs() < 0.00005 {
+        String::new()
+    } else if d > 0.0 {
+        format!("+${d:.4}")
+    } else {
+        format!("-${:.4}", -d)
+    }
+}
+
+/// Rows of `(label, a, b)` counts for keys present in either map.
+fn keyed_rows<'a>(
+    a: &'a BTreeMap<String, usize>,
+    b: &'a BTreeMap<String, usize>,
+) -> Vec<(&'a str, usize, usize)> {
+    let keys: BTreeSet<&str> = a.keys().chain(b.keys()).map(String::as_str).collect();
+    keys.into_iter()
+        .map(|k| {
+            (
+                k,
+                a.get(k).copied().unwrap_or(0),
+                b.get(k).copied().unwrap_or(0),
+            )
+        })
+        .collect()
+}
+
+fn print_diff(a: &SessionStats, b: &SessionStats, use_colors: bool) {
+    let (bold, dim, reset) = if use_colors {
+        (colors::BOLD, colors::DIM, colors::RESET)
+    } else {
+        ("", "", "")
+    };
+    let row = |label: &str, a: String, b: String, d: String| {
+        println!("{:<28} {:>16} {:>16} {:>12}", truncate(label, 28), a, b, d);
+    };
+    let count = |label: &str, a: i64, b: i64| row(label, a.to_string(), b.to_string(), delta(a, b));
+
+    println!(
+        "{bold}{:<28} {:>16} {:>16} {:>12}{reset}",
+        "",
+        truncate(&a.id, 16),
+        truncate(&b.id, 16),
+        "DELTA"
+    );
+    count("Iterations", a.iterations.into(), b.iterations.into());
+    count("Events", a.events as i64, b.events as i64);
+    count("Duration (s)", a.duration_seconds, b.duration_seconds);
+    row(
+        "Cost",
+        format!("${:.4}", a.cost.cost_usd),
+        format!("${:.4}", b.cost.cost_usd),
+        cost_delta(a.cost.cost_usd, b.cost.cost_usd),
+    );
+    count(
+        "Input tokens",
+        a.cost.input_tokens as i64,
+        b.cost.input_tokens as i64,
+    );
+    count(
+        "Output tokens",
+        a.cost.output_tokens as i64,
+        b.cost.output_tokens as i64,
+    );
+
+    println!("\n{bold}HATS (activations){reset}");
+    for (hat, x, y) in keyed_rows(&a.hats, &b.hats) {
+        count(&format!("  {hat}"), x as i64, y as i64);
+    }
+
+    println!("\n{bold}EVENTS (by topic){reset}");
+    for (topic, x, y) in keyed_rows(&a.topics, &b.topics) {
+        count(&format!("  {topic}"), x as i64, y as i64);
+    }
+
+    println!("\n{bold}CHANGES{reset}");
+    let (Some(ca), Some(cb)) = (&a.changes, &b.changes) else {
+        println!("{dim}  No git history; changes unavailable.{reset}");
+        return;
+    };
+    count("  Commits", ca.commits as i64, cb.commits as i64);
+    count(
+        "  Files changed",
+        ca.files.len() as i64,
+        cb.files.len() as i64,
+    );
+    count("  Insertions", ca.insertions as i64, cb.insertions as i64);
+    count("  Deletions", ca.deletions as i64, cb.deletions as i64);
+    for (label, only) in [
+        (&a.id, ca.files.difference(&cb.files)),
+        (&b.id, cb.files.difference(&ca.files)),
+    ] {
+        let files: Vec<_> = only.map(String::as_str).collect();
+        if !files.is_empty() {
+            println!("{dim}  Only in {label}:{reset} {}", files.join(", "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(ts: &str, iteration: u32, topic: &str, triggered: Option<&str>) -> EventRecord {
+        EventRecord {
+            ts: ts.to_string(),
+            iteration,
+            hat: "loop".to_string(),
+            topic: topic.to_string(),
+            triggered: triggered.map(str::to_string),
+            payload: String::new(),
+            blocked_count: None,
+        }
+    }
+
+    #[test]
+    fn test_stats_from_records() {
+        let stats = SessionStats::from_records(
+            "20260127-120000",
+            &[
+                record("2026-01-27T12:00:01Z", 1, "task.start", Some("planner")),
+                record("2026-01-27T12:01:00Z", 1, "build.task", Some("builder")),
+                record("2026-01-27T12:05:00Z", 0, "build.done", Some("planner")),
+                record("2026-01-27T12:06:01Z", 3, "build.task", Some("builder")),
+            ],
+        );
+
+        assert_eq!(stats.iterations, 3);
+        assert_eq!(stats.events, 4);
+        assert_eq!(stats.topics["build.task"], 2);
+        assert_eq!(stats.hats["planner"], 2);
+        assert_eq!(stats.duration_seconds, 360);
+    }
+
+    #[test]
+    fn test_parse_numstat() {
+        let log =
+            "commit abc\n\n3\t1\tsrc/lib.rs\n-\t-\tlogo.png\ncommit def\n\n2\t0\tsrc/lib.rs\n";
+        let stats = parse_numstat(log);
+        assert_eq!(stats.commits, 2);
+        assert_eq!(stats.insertions, 5);
+        assert_eq!(stats.deletions, 1);
+        assert_eq!(
+            stats.files.into_iter().collect::<Vec<_>>(),
+            ["logo.png", "src/lib.rs"]
+        );
+    }
+
+    #[test]
+    fn test_session_window_ends_at_next_session() {
+        let temp = TempDir::new().unwrap();
+        for id in ["20260127-120000", "20260127-130000", "20260127-140000"] {
+            fs::write(temp.path().join(format!("events-{id}.jsonl")), "").unwrap();
+        }
+        fs::write(temp.path().join("events.jsonl"), "").unwrap();
+
+        let start = parse_run_id("20260127-120000").unwrap();
+        let end = next_session_start(temp.path(), start);
+        assert_eq!(end, parse_run_id("20260127-130000"));
+
+        let latest = parse_run_id("20260127-140000").unwrap();
+        assert_eq!(next_session_start(temp.path(), latest), None);
+
+        let inside = parse_ts("2026-01-27T12:30:00Z").unwrap();
+        let after = parse_ts("2026-01-27T13:00:00Z").unwrap();
+        assert!(in_window(inside, start, end));
+        assert!(!in_window(after, start, end));
+        assert!(in_window(after, start, None));
+    }
+
+    #[test]
+    fn test_deltas() {
+        assert_eq!(delta(5, 3), "-2");
+        assert_eq!(delta(3, 5), "+2");
+        assert_eq!(delta(4, 4), "");
+        assert_eq!(cost_delta(1.0, 0.75), "-$0.2500");
+        assert_eq!(cost_delta(0.5, 0.5), "");
+    }
+
+    #[test]
+    fn test_keyed_rows_include_keys_from_either_side() {
+        let a = BTreeMap::from([("builder".to_string(), 3)]);
+        let b = BTreeMap::from([("reviewer".to_string(), 1)]);
+        assert_eq!(keyed_rows(&a, &b), [("builder", 3, 0), ("reviewer", 0, 1)]);
+    }
+}
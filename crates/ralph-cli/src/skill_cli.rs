@@ -3,10 +3,12 @@
 //! Provides subcommands for interacting with skills:
 //! - `load`: Load a skill by name and output its content
 //! - `list`: List available skills
+//! - `search`: Search the configured skill index
+//! - `install`: Install skills from the index and pin them in `.agent/skills.lock`
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use clap::{Parser, Subcommand, ValueEnum};
-use ralph_core::{RalphConfig, SkillRegistry};
+use ralph_core::{RalphConfig, SkillIndexClient, SkillIndexError, SkillLock, SkillRegistry};
 use serde::Serialize;
 use std::path::{Path, PathBuf};
 
@@ -40,6 +42,12 @@ pub enum SkillCommands {
 
     /// List available skills
     List(ListArgs),
+
+    /// Search the configured skill index
+    Search(SearchArgs),
+
+    /// Install skills from the index (all locked skills if no name is given)
+    Install(InstallArgs),
 }
 
 #[derive(Parser, Debug)]
@@ -56,13 +64,35 @@ pub struct ListArgs {
     pub format: OutputFormat,
 }
 
+/// Arguments for the `skill search` command.
+#[derive(Parser, Debug)]
+pub struct SearchArgs {
+    /// Term matched against skill names, descriptions, and tags
+    #[arg(default_value = "")]
+    pub term: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `skill install` command.
+#[derive(Parser, Debug)]
+pub struct InstallArgs {
+    /// Skills to install, as `name` or `name@version`. With none, installs
+    /// every skill in `.agent/skills.lock` at its pinned version.
+    pub names: Vec<String>,
+}
+
 /// Execute a skill command.
-pub fn execute(args: SkillArgs) -> Result<()> {
+pub async fn execute(args: SkillArgs) -> Result<()> {
     let root = resolve_root(args.root)?;
 
     match args.command {
         SkillCommands::Load(load_args) => execute_load(&root, &load_args.name),
         SkillCommands::List(list_args) => execute_list(&root, list_args),
+        SkillCommands::Search(search_args) => execute_search(&root, search_args).await,
+        SkillCommands::Install(install_args) => execute_install(&root, install_args).await,
     }
 }
 
@@ -138,6 +168,91 @@ fn execute_list(root: &Path, args: ListArgs) -> Result<()> {
     Ok(())
 }
 
+async fn execute_search(root: &Path, args: SearchArgs) -> Result<()> {
+    let client = index_client(root)?;
+    let index = client.fetch_index().await?;
+    let skills = index.search(&args.term);
+
+    match args.format {
+        OutputFormat::Table => {
+            if skills.is_empty() {
+                println!("No skills found in {}", client.source().location());
+                return Ok(());
+            }
+
+            println!("{:<24} {:<14} {:<60}", "Name", "Version", "Description");
+            println!("{}", "-".repeat(98));
+
+            for skill in skills {
+                println!(
+                    "{:<24} {:<14} {:<60}",
+                    crate::display::truncate(&skill.name, 24),
+                    crate::display::truncate(&skill.version, 14),
+                    crate::display::truncate(&skill.description, 60)
+                );
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&skills)?);
+        }
+        OutputFormat::Quiet => {
+            for skill in skills {
+                println!("{}", skill.name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_install(root: &Path, args: InstallArgs) -> Result<()> {
+    if args.names.is_empty() {
+        return install_locked(root).await;
+    }
+
+    let client = index_client(root)?;
+    let index = client.fetch_index().await?;
+    for spec in &args.names {
+        let (name, version) = match spec.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (spec.as_str(), None),
+        };
+        let entry = index
+            .find(name, version)
+            .ok_or_else(|| SkillIndexError::NotFound(spec.clone()))?;
+        let locked = client.install(root, entry).await?;
+        println!("Installed {}@{}", entry.name, locked.version);
+    }
+
+    Ok(())
+}
+
+/// Installs every locked skill at its pinned version.
+async fn install_locked(root: &Path) -> Result<()> {
+    let lock = SkillLock::load(root)?;
+    if lock.skills.is_empty() {
+        bail!("No skills in .agent/skills.lock. Use `ralph tools skill install <name>`.");
+    }
+
+    for (name, locked) in &lock.skills {
+        // Each skill is fetched from the index it was locked from.
+        let client = SkillIndexClient::new(&locked.source, root);
+        client
+            .install_locked(root, name, locked)
+            .await
+            .with_context(|| format!("Failed to install {name}@{}", locked.version))?;
+        println!("Installed {name}@{}", locked.version);
+    }
+
+    Ok(())
+}
+
+fn index_client(root: &Path) -> Result<SkillIndexClient> {
+    let config = load_config(root);
+    let location = config.skills.index.ok_or(SkillIndexError::NotConfigured)?;
+    Ok(SkillIndexClient::new(&location, root))
+}
+
 fn build_registry(root: &Path) -> Result<SkillRegistry> {
     let config = load_config(root);
     let active_backend = Some(config.cli.backend.as_str());
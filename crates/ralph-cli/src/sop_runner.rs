@@ -134,6 +134,7 @@ pub fn run_sop(config: SopRunConfig) -> Result<(), SopRunError> {
                 prompt_flag: None, // Prompt appended as last arg by default
                 output_format: ralph_adapters::OutputFormat::Text,
                 env_vars: vec![],
+                container: None,
             }
         } else {
             // For custom backend from config, we need to load the configuration to get the command/args
@@ -200,8 +201,8 @@ fn resolve_backend(
 /// Validates a backend name.
 fn validate_backend_name(name: &str) -> Result<(), SopRunError> {
     match name {
-        "claude" | "kiro" | "gemini" | "codex" | "amp" | "copilot" | "opencode" | "pi"
-        | "custom" => Ok(()),
+        "claude" | "kiro" | "gemini" | "codex" | "amp" | "goose" | "copilot" | "opencode"
+        | "pi" | "custom" => Ok(()),
         _ => Err(SopRunError::UnknownBackend(name.to_string())),
     }
 }
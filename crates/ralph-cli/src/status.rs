@@ -0,0 +1,158 @@
+//! `ralph status`: who is running Ralph in this repo.
+//!
+//! Shows the repo lock owner (user, host, pid, session) and the primary loop,
+//! so someone sharing the clone can see whose run they'd collide with before
+//! starting their own.
+
+use crate::OutputFormat;
+use crate::display::{colors, truncate};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::Parser;
+use ralph_core::{LockMetadata, LoopLock, RepoLock, RepoLockOwner};
+use serde::Serialize;
+use std::path::Path;
+
+/// Arguments for the status subcommand.
+#[derive(Parser, Debug)]
+pub struct StatusArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+/// Repo lock and primary loop state.
+#[derive(Debug, Serialize)]
+struct Status {
+    repo_lock: Option<RepoLockStatus>,
+    primary_loop: Option<LockMetadata>,
+}
+
+#[derive(Debug, Serialize)]
+struct RepoLockStatus {
+    #[serde(flatten)]
+    owner: RepoLockOwner,
+    stale: bool,
+}
+
+/// Executes `ralph status`.
+pub fn execute(args: &StatusArgs, use_colors: bool) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let status = read_status(&cwd)?;
+
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    print!("{}", render(&status, use_colors));
+    Ok(())
+}
+
+fn read_status(workspace: &Path) -> Result<Status> {
+    let repo_lock = RepoLock::read_existing(workspace)?.map(|owner| RepoLockStatus {
+        stale: owner.is_stale(),
+        owner,
+    });
+    let primary_loop = if LoopLock::is_locked(workspace).unwrap_or(false) {
+        LoopLock::read_existing(workspace)?
+    } else {
+        None
+    };
+    Ok(Status {
+        repo_lock,
+        primary_loop,
+    })
+}
+
+fn render(status: &Status, use_colors: bool) -> String {
+    let (bold, dim, yellow, reset) = if use_colors {
+        (colors::BOLD, colors::DIM, colors::YELLOW, colors::RESET)
+    } else {
+        ("", "", "", "")
+    };
+    let mut out = String::new();
+
+    match &status.repo_lock {
+        Some(lock) => {
+            let owner = &lock.owner;
+            let stale = if lock.stale {
+                format!(" {yellow}(stale){reset}")
+            } else {
+                String::new()
+            };
+            let heartbeat = Utc::now()
+                .signed_duration_since(owner.heartbeat)
+                .num_seconds()
+                .max(0);
+            out.push_str(&format!(
+                "{bold}Repo lock:{reset} {}@{}{stale}\n",
+                owner.user, owner.host
+            ));
+            out.push_str(&format!(
+                "  {dim}PID {}, session {}{reset}\n",
+                owner.pid, owner.session
+            ));
+            out.push_str(&format!(
+                "  {dim}Started {}, heartbeat {heartbeat}s ago{reset}\n",
+                owner.started.format("%Y-%m-%d %H:%M UTC")
+            ));
+            out.push_str(&format!(
+                "  {dim}Prompt: {}{reset}\n",
+                truncate(&owner.prompt, 60)
+            ));
+        }
+        None => out.push_str(&format!("{bold}Repo lock:{reset} free\n")),
+    }
+
+    match &status.primary_loop {
+        Some(primary) => out.push_str(&format!(
+            "{bold}Primary loop:{reset} PID {} since {}: {}\n",
+            primary.pid,
+            primary.started.format("%Y-%m-%d %H:%M UTC"),
+            truncate(&primary.prompt, 60)
+        )),
+        None => out.push_str(&format!("{bold}Primary loop:{reset} not running\n")),
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_of_idle_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let status = read_status(dir.path()).unwrap();
+        assert!(status.repo_lock.is_none());
+        assert!(status.primary_loop.is_none());
+        assert_eq!(
+            render(&status, false),
+            "Repo lock: free\nPrimary loop: not running\n"
+        );
+    }
+
+    #[test]
+    fn test_status_shows_lock_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let _guard = RepoLock::acquire(
+            dir.path(),
+            RepoLockOwner::current("session-1", "Fix the flaky test"),
+            false,
+        )
+        .unwrap();
+
+        let status = read_status(dir.path()).unwrap();
+        let lock = status.repo_lock.as_ref().unwrap();
+        assert!(!lock.stale);
+        let rendered = render(&status, false);
+        assert!(rendered.starts_with(&format!(
+            "Repo lock: {}@{}\n",
+            lock.owner.user, lock.owner.host
+        )));
+        assert!(rendered.contains("session session-1"));
+        assert!(rendered.contains("Prompt: Fix the flaky test"));
+    }
+}
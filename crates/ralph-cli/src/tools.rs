@@ -8,13 +8,21 @@
 //! - `memory`: Persistent memories for accumulated learning
 //! - `task`: Work item tracking (beads-lite)
 //! - `skill`: Load skill content on demand
+//! - `scratchpad`: Conflict-aware scratchpad reads and writes
+//! - `search`: Find relevant code through the workspace's embedding index
+//! - `audit`: Dependency vulnerability audits (cargo audit / npm audit)
+//! - `run`: Shell commands under the `shell_tool` policy (guarded proxy)
 //! - `interact`: Human-in-the-loop communication (progress updates, notifications)
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
+use crate::audit_cli;
 use crate::interact;
 use crate::memory;
+use crate::run_cli;
+use crate::scratchpad_cli;
+use crate::search_cli;
 use crate::skill_cli;
 use crate::task_cli;
 
@@ -36,6 +44,18 @@ pub enum ToolsCommands {
     /// Load and manage skills
     Skill(skill_cli::SkillArgs),
 
+    /// Read and write the scratchpad without losing concurrent edits
+    Scratchpad(scratchpad_cli::ScratchpadArgs),
+
+    /// Find code related to a query through the workspace's embedding index
+    Search(search_cli::SearchArgs),
+
+    /// Audit dependencies for known vulnerabilities (cargo audit / npm audit)
+    Audit(audit_cli::AuditArgs),
+
+    /// Run a shell command under the shell_tool policy, logging it for audit
+    Run(run_cli::RunArgs),
+
     /// Interact with human via Telegram (progress updates, notifications)
     Interact(interact::InteractArgs),
 }
@@ -45,7 +65,13 @@ pub async fn execute(args: ToolsArgs, use_colors: bool) -> Result<()> {
     match args.command {
         ToolsCommands::Memory(memory_args) => memory::execute(memory_args, use_colors),
         ToolsCommands::Task(task_args) => task_cli::execute(task_args, use_colors),
-        ToolsCommands::Skill(skill_args) => skill_cli::execute(skill_args),
+        ToolsCommands::Skill(skill_args) => skill_cli::execute(skill_args).await,
+        ToolsCommands::Scratchpad(scratchpad_args) => {
+            scratchpad_cli::execute(scratchpad_args, use_colors)
+        }
+        ToolsCommands::Search(search_args) => search_cli::execute(search_args, use_colors),
+        ToolsCommands::Audit(audit_args) => audit_cli::execute(audit_args, use_colors),
+        ToolsCommands::Run(run_args) => run_cli::execute(run_args).await,
         ToolsCommands::Interact(interact_args) => interact::execute(interact_args).await,
     }
 }
@@ -273,8 +273,10 @@ pub async fn execute(args: WebArgs) -> Result<()> {
     let workspace_root = match args.workspace {
         Some(path) => {
             // Canonicalize to get absolute path
-            path.canonicalize()
-                .with_context(|| format!("Invalid workspace path: {}", path.display()))?
+            let canonical = path
+                .canonicalize()
+                .with_context(|| format!("Invalid workspace path: {}", path.display()))?;
+            ralph_adapters::normalize_path(&canonical)
         }
         None => env::current_dir().context("Failed to get current directory")?,
     };
@@ -500,7 +502,16 @@ async fn terminate_gracefully(child: &mut Child, grace_period: Duration) {
 /// Gracefully terminate a child process (non-Unix fallback using start_kill)
 #[cfg(not(unix))]
 async fn terminate_gracefully(child: &mut Child, _grace_period: Duration) {
-    let _ = child.start_kill();
+    // No SIGTERM on Windows. Kill the whole tree: killing only `npm` leaves
+    // the `node` server it started running.
+    match child.id() {
+        Some(pid) => {
+            let _ = ralph_adapters::force_kill(pid);
+        }
+        None => {
+            let _ = child.start_kill();
+        }
+    }
     let _ = child.wait().await;
 }
 
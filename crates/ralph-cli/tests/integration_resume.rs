@@ -364,3 +364,36 @@ This scratchpad contains UNIQUE_CONTENT_MARKER for testing.
 
     Ok(())
 }
+
+#[test]
+fn test_resume_without_pause_points_to_continue() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+
+    // `ralph resume` used to continue a stopped loop; it now only lifts a pause
+    let output = Command::new(env!("CARGO_BIN_EXE_ralph"))
+        .arg("resume")
+        .current_dir(temp_dir.path())
+        .output()?;
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("The loop isn't paused"));
+    assert!(stderr.contains("ralph run --continue"));
+
+    // With a pause pending, it lifts the pause
+    let pause = Command::new(env!("CARGO_BIN_EXE_ralph"))
+        .arg("pause")
+        .current_dir(temp_dir.path())
+        .output()?;
+    assert!(pause.status.success());
+    assert!(temp_dir.path().join(".ralph/pause-requested").exists());
+
+    let resume = Command::new(env!("CARGO_BIN_EXE_ralph"))
+        .arg("resume")
+        .current_dir(temp_dir.path())
+        .output()?;
+    assert!(resume.status.success());
+    assert!(!temp_dir.path().join(".ralph/pause-requested").exists());
+
+    Ok(())
+}
@@ -58,3 +58,206 @@ fn test_run_continue_requires_scratchpad() {
         "stderr: {stderr}"
     );
 }
+
+#[test]
+fn test_run_parks_until_idle_timeout() {
+    let temp_dir = TempDir::new().expect("temp dir");
+    let temp_path = temp_dir.path();
+    std::fs::write(
+        temp_path.join("ralph.yml"),
+        r#"
+event_loop:
+  prompt: "Wait for work"
+  max_iterations: 5
+  max_runtime_seconds: 30
+  park_when_idle: true
+  park_timeout_seconds: 2
+
+cli:
+  backend: "custom"
+  command: "true"
+
+features:
+  preflight:
+    enabled: false
+"#,
+    )
+    .expect("write config");
+
+    let started = std::time::Instant::now();
+    let output = run_ralph(temp_path, &["run", "--no-tui"]);
+
+    // The backend publishes nothing, so the loop parks instead of re-running
+    // the planner, then stops cleanly once the idle timeout passes.
+    assert!(
+        output.status.success(),
+        "run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(started.elapsed() >= std::time::Duration::from_secs(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Idle timeout while parked"),
+        "stdout: {stdout}"
+    );
+}
+
+fn write_self_test_config(temp_path: &std::path::Path, command: &str) {
+    std::fs::write(
+        temp_path.join("ralph.yml"),
+        format!(
+            r#"
+event_loop:
+  prompt: "Say hello"
+  max_iterations: 1
+  self_test: true
+
+cli:
+  backend: "custom"
+  command: "{command}"
+
+features:
+  preflight:
+    enabled: false
+"#
+        ),
+    )
+    .expect("write config");
+}
+
+#[test]
+fn test_run_self_test_aborts_before_first_iteration() {
+    let temp_dir = TempDir::new().expect("temp dir");
+    let temp_path = temp_dir.path();
+    write_self_test_config(temp_path, "true");
+
+    let output = run_ralph(temp_path, &["run", "--no-tui"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Backend self-test failed: backend exited cleanly but produced no output"),
+        "stderr: {stderr}"
+    );
+    assert!(
+        stderr.contains("cli.prompt_mode"),
+        "stderr should carry a hint: {stderr}"
+    );
+}
+
+#[test]
+fn test_run_self_test_passes_when_event_round_trips() {
+    let temp_dir = TempDir::new().expect("temp dir");
+    let temp_path = temp_dir.path();
+    // `echo` repeats the prompt, which includes the requested event
+    write_self_test_config(temp_path, "echo");
+
+    let output = run_ralph(temp_path, &["run", "--no-tui"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("self-test failed"), "stderr: {stderr}");
+    assert!(temp_path.join(".ralph/self-test.out").exists());
+}
+
+#[test]
+fn test_run_postprocess_publishes_table_rows() {
+    let temp_dir = TempDir::new().expect("temp dir");
+    let temp_path = temp_dir.path();
+    std::fs::write(
+        temp_path.join("ralph.yml"),
+        r#"
+event_loop:
+  prompt: "Build it"
+  max_iterations: 2
+  starting_event: build.start
+
+cli:
+  backend: "custom"
+  command: "sh"
+  args: ["-c", "printf '<thinking>scratch work</thinking>| topic | payload |\\n|---|---|\\n| LOOP_COMPLETE | done |\\n'"]
+
+hats:
+  builder:
+    name: Builder
+    description: Builds it
+    triggers: ["build.start"]
+    publishes: ["LOOP_COMPLETE"]
+    postprocess:
+      - strip_thinking
+      - table_to_events
+
+features:
+  preflight:
+    enabled: false
+"#,
+    )
+    .expect("write config");
+
+    let output = run_ralph(temp_path, &["run", "--no-tui"]);
+
+    // The backend never runs `ralph emit`: the completion event comes from
+    // the table in its output.
+    assert!(
+        output.status.success(),
+        "run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let logs: Vec<String> = walk_files(&temp_path.join(".ralph/agent/sessions"))
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "out"))
+        .map(|path| std::fs::read_to_string(path).expect("read output log"))
+        .collect();
+    assert_eq!(logs.len(), 1);
+    assert!(!logs[0].contains("scratch work"), "log: {}", logs[0]);
+    assert!(logs[0].contains("| LOOP_COMPLETE | done |"));
+}
+
+fn walk_files(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(walk_files(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+#[test]
+fn test_run_writes_forensics_bundle_on_consecutive_failures() {
+    let temp_dir = TempDir::new().expect("temp dir");
+    let temp_path = temp_dir.path();
+    std::fs::write(
+        temp_path.join("ralph.yml"),
+        r#"
+event_loop:
+  prompt: "Build it"
+  max_iterations: 5
+  max_consecutive_failures: 2
+
+cli:
+  backend: "custom"
+  command: "sh"
+  args: ["-c", "echo 'compiler exploded'; exit 1"]
+
+features:
+  preflight:
+    enabled: false
+"#,
+    )
+    .expect("write config");
+
+    let output = run_ralph(temp_path, &["run", "--no-tui"]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Forensics bundle:"), "stdout: {stdout}");
+    let bundles = walk_files(&temp_path.join(".ralph/forensics"));
+    assert_eq!(bundles.len(), 1);
+    assert!(
+        bundles[0]
+            .to_string_lossy()
+            .contains("consecutive_failures")
+    );
+}
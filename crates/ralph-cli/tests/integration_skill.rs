@@ -222,3 +222,55 @@ Loaded from configured parent skills dir.
     let load_stdout = ralph_skill_no_root_ok(&nested_dir, &["load", "test-driven-development"]);
     assert!(load_stdout.contains("Loaded from configured parent skills dir."));
 }
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@test.local"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .expect("run git");
+    assert!(status.success(), "git {args:?}");
+}
+
+#[test]
+fn test_skill_search_and_install_from_git_index() {
+    let temp_dir = TempDir::new().expect("temp dir");
+    let index = temp_dir.path().join("index");
+    let skill_dir = index.join("rust-testing");
+    fs::create_dir_all(&skill_dir).expect("create index skill dir");
+    fs::write(
+        skill_dir.join("SKILL.md"),
+        "---\ndescription: Write cargo tests\n---\nUse cargo nextest.\n",
+    )
+    .expect("write index skill");
+    git(&index, &["init", "--quiet"]);
+    git(&index, &["add", "."]);
+    git(&index, &["commit", "--quiet", "-m", "init"]);
+
+    let workspace = temp_dir.path().join("workspace");
+    fs::create_dir_all(&workspace).expect("create workspace");
+    fs::write(
+        workspace.join("ralph.yml"),
+        format!("skills:\n  index: {}\n", index.display()),
+    )
+    .expect("write config");
+
+    let stdout = ralph_skill_ok(&workspace, &["search", "cargo", "--format", "quiet"]);
+    assert_eq!(stdout.trim(), "rust-testing");
+
+    ralph_skill_ok(&workspace, &["install", "rust-testing"]);
+    let lock = fs::read_to_string(workspace.join(".agent/skills.lock")).expect("read lock");
+    assert!(lock.contains("\"rust-testing\""));
+    let stdout = ralph_skill_ok(&workspace, &["load", "rust-testing"]);
+    assert!(stdout.contains("Use cargo nextest."));
+
+    // A fresh checkout restores locked skills with a bare `install`.
+    fs::remove_dir_all(workspace.join(".agent/skills")).expect("remove installed skills");
+    ralph_skill_ok(&workspace, &["install"]);
+    assert!(
+        workspace
+            .join(".agent/skills/rust-testing/SKILL.md")
+            .exists()
+    );
+}
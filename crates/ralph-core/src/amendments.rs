@@ -0,0 +1,133 @@
+//! Amendments to a running loop's objective (`ralph amend`).
+//!
+//! A human steers a live run by appending an instruction to the loop's
+//! amendments file (`.ralph/agent/amendments.jsonl`). The event loop reads the
+//! file when it builds each prompt, so an amendment applies from the next
+//! iteration on without restarting the run. Each amendment is also recorded in
+//! the events file as `ralph.amended`, which is never read back as input.
+//!
+//! Fresh runs start without amendments; `--continue` keeps them.
+
+use crate::event_logger::EventRecord;
+use crate::event_writer::EventWriter;
+use crate::lifecycle::AMENDED_TOPIC;
+use ralph_proto::Event;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use tracing::warn;
+
+/// One instruction added while the loop was running.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Amendment {
+    /// RFC 3339 timestamp.
+    pub ts: String,
+    pub text: String,
+}
+
+/// Appends `text` to the amendments file and records it in the events file.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if `text` is blank, or an error if either file
+/// can't be written.
+pub fn amend(amendments_path: &Path, events_path: &Path, text: &str) -> io::Result<Amendment> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "amendment is empty",
+        ));
+    }
+    let amendment = Amendment {
+        ts: chrono::Utc::now().to_rfc3339(),
+        text: text.to_string(),
+    };
+    EventWriter::new(amendments_path).append(&amendment)?;
+
+    let mut record = EventRecord::new(
+        0,
+        "human",
+        &Event::new(AMENDED_TOPIC, &amendment.text),
+        None,
+    );
+    record.ts.clone_from(&amendment.ts);
+    EventWriter::new(events_path).append(&record)?;
+    Ok(amendment)
+}
+
+/// Reads the amendments in the order they were made.
+///
+/// A missing file has no amendments; malformed lines are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but can't be read.
+pub fn read(path: &Path) -> io::Result<Vec<Amendment>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| {
+            serde_json::from_str(line)
+                .inspect_err(
+                    |e| warn!(line = index + 1, error = %e, "Skipping malformed amendment"),
+                )
+                .ok()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_logger::EventHistory;
+
+    #[test]
+    fn test_amend_appends_and_records_in_journal() {
+        let temp = tempfile::tempdir().unwrap();
+        let amendments_path = temp.path().join("agent/amendments.jsonl");
+        let events_path = temp.path().join("events.jsonl");
+
+        amend(
+            &amendments_path,
+            &events_path,
+            "Keep the API backward compatible\n",
+        )
+        .unwrap();
+        amend(&amendments_path, &events_path, "Skip the docs").unwrap();
+
+        let amendments = read(&amendments_path).unwrap();
+        let texts: Vec<&str> = amendments.iter().map(|a| a.text.as_str()).collect();
+        assert_eq!(texts, ["Keep the API backward compatible", "Skip the docs"]);
+
+        let records = EventHistory::new(&events_path).read_all().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].topic, AMENDED_TOPIC);
+        assert_eq!(records[1].payload, "Skip the docs");
+        assert_eq!(records[1].ts, amendments[1].ts);
+    }
+
+    #[test]
+    fn test_amend_rejects_blank_text() {
+        let temp = tempfile::tempdir().unwrap();
+        let err = amend(
+            &temp.path().join("amendments.jsonl"),
+            &temp.path().join("events.jsonl"),
+            "  \n",
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(
+            read(&temp.path().join("amendments.jsonl"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Attribution of checkpoint commits (`attribution:` in ralph.yml).
+//!
+//! When a loop completes and lands its work, the checkpoint commit can carry
+//! git trailers naming the session, how many iterations it took, which hats
+//! ran, and which events they published. `CHANGELOG.ralph.md` collects one
+//! entry per completed session with the tasks it closed, and is committed
+//! with the checkpoint. Both let `git log` and release tooling tell Ralph's
+//! changes apart from hand-written ones.
+
+use crate::event_logger::EventRecord;
+use crate::lifecycle::is_lifecycle_topic;
+use crate::task::{Task, TaskStatus};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Changelog file, relative to the workspace root.
+pub const CHANGELOG_FILE: &str = "CHANGELOG.ralph.md";
+
+const CHANGELOG_HEADER: &str = "# Ralph changelog\n\n\
+Sessions that completed in this repository, newest first. \
+Maintained by Ralph (`attribution.changelog` in ralph.yml).\n";
+
+/// Most topics listed in the `Events` trailer.
+const MAX_TRAILER_TOPICS: usize = 10;
+
+/// What a completed session did, for trailers and the changelog.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub iterations: u32,
+    /// Hats that ran, sorted.
+    pub hats: Vec<String>,
+    /// Distinct event topics, in the order they were first published.
+    pub topics: Vec<String>,
+    /// Titles of the tasks closed during the session.
+    pub completed_tasks: Vec<String>,
+}
+
+impl SessionSummary {
+    /// The `Session-Id`, `Iteration`, `Hat`, and `Events` trailers.
+    pub fn trailers(&self) -> Vec<(String, String)> {
+        let hats = if self.hats.is_empty() {
+            "ralph".to_string()
+        } else {
+            self.hats.join(", ")
+        };
+        let mut events = self
+            .topics
+            .iter()
+            .take(MAX_TRAILER_TOPICS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.topics.len() > MAX_TRAILER_TOPICS {
+            events.push_str(&format!(
+                ", +{} more",
+                self.topics.len() - MAX_TRAILER_TOPICS
+            ));
+        }
+
+        let mut trailers = vec![
+            ("Session-Id".to_string(), self.session_id.clone()),
+            ("Iteration".to_string(), self.iterations.to_string()),
+            ("Hat".to_string(), hats),
+        ];
+        if !events.is_empty() {
+            trailers.push(("Events".to_string(), events));
+        }
+        trailers
+    }
+
+    /// The session's changelog entry, dated `date`.
+    pub fn changelog_entry(&self, date: &str) -> String {
+        let mut entry = format!(
+            "## {} ({date})\n\n- Iterations: {}\n",
+            self.session_id, self.iterations
+        );
+        if !self.hats.is_empty() {
+            entry.push_str(&format!("- Hats: {}\n", self.hats.join(", ")));
+        }
+        if self.completed_tasks.is_empty() {
+            entry.push_str("- Completed tasks: none tracked\n");
+        } else {
+            entry.push_str("- Completed tasks:\n");
+            for task in &self.completed_tasks {
+                entry.push_str(&format!("  - {task}\n"));
+            }
+        }
+        entry
+    }
+}
+
+/// Distinct topics in an events file, in first-published order.
+///
+/// Lifecycle topics are left out: every session has them.
+pub fn event_topics(records: &[EventRecord]) -> Vec<String> {
+    let mut topics: Vec<String> = Vec::new();
+    for record in records {
+        if !is_lifecycle_topic(&record.topic) && !topics.contains(&record.topic) {
+            topics.push(record.topic.clone());
+        }
+    }
+    topics
+}
+
+/// Titles of tasks closed at or after `since`.
+///
+/// Tasks owned by another loop are skipped, as are tasks whose close time
+/// can't be parsed.
+pub fn tasks_closed_since(
+    tasks: &[Task],
+    loop_id: Option<&str>,
+    since: DateTime<Utc>,
+) -> Vec<String> {
+    tasks
+        .iter()
+        .filter(|task| task.status == TaskStatus::Closed)
+        .filter(|task| match (loop_id, task.loop_id.as_deref()) {
+            (Some(ours), Some(theirs)) => ours == theirs,
+            _ => true,
+        })
+        .filter(|task| {
+            task.closed
+                .as_deref()
+                .and_then(|closed| DateTime::parse_from_rfc3339(closed).ok())
+                .is_some_and(|closed| closed >= since)
+        })
+        .map(|task| task.title.clone())
+        .collect()
+}
+
+/// Adds `entry` above the newest entry in the workspace's changelog.
+///
+/// Creates the file, with a header, if it doesn't exist.
+///
+/// # Errors
+///
+/// Returns an error if the changelog can't be read or written.
+pub fn update_changelog(workspace: &Path, entry: &str) -> io::Result<()> {
+    let path = workspace.join(CHANGELOG_FILE);
+    let existing = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => CHANGELOG_HEADER.to_string(),
+        Err(e) => return Err(e),
+    };
+
+    let entry = format!("{}\n", entry.trim_end());
+    let updated = match newest_entry_offset(&existing) {
+        Some(offset) => format!("{}{entry}\n{}", &existing[..offset], &existing[offset..]),
+        None => {
+            let mut content = existing.trim_end().to_string();
+            if !content.is_empty() {
+                content.push_str("\n\n");
+            }
+            content.push_str(&entry);
+            content
+        }
+    };
+    fs::write(path, updated)
+}
+
+/// Byte offset of the first `## ` heading.
+fn newest_entry_offset(content: &str) -> Option<usize> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if line.starts_with("## ") {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ralph_proto::{Event, HatId};
+
+    fn summary() -> SessionSummary {
+        SessionSummary {
+            session_id: "primary-20260101-120000".to_string(),
+            iterations: 7,
+            hats: vec!["builder".to_string(), "reviewer".to_string()],
+            topics: vec!["build.done".to_string(), "review.approved".to_string()],
+            completed_tasks: vec!["Add login form".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_trailers() {
+        let trailers = summary().trailers();
+        assert_eq!(
+            trailers,
+            vec![
+                (
+                    "Session-Id".to_string(),
+                    "primary-20260101-120000".to_string()
+                ),
+                ("Iteration".to_string(), "7".to_string()),
+                ("Hat".to_string(), "builder, reviewer".to_string()),
+                (
+                    "Events".to_string(),
+                    "build.done, review.approved".to_string()
+                ),
+            ]
+        );
+
+        let many = SessionSummary {
+            topics: (0..12).map(|i| format!("step.{i}")).collect(),
+            hats: Vec::new(),
+            ..summary()
+        };
+        let trailers = many.trailers();
+        assert_eq!(trailers[2].1, "ralph");
+        assert!(trailers[3].1.ends_with("step.9, +2 more"));
+    }
+
+    #[test]
+    fn test_event_topics_skip_lifecycle_and_duplicates() {
+        let record =
+            |topic: &str| EventRecord::new(1, "loop", &Event::new(topic, ""), None::<&HatId>);
+        let records = [
+            record("task.start"),
+            record("ralph.iteration_started"),
+            record("build.done"),
+            record("task.start"),
+        ];
+        assert_eq!(event_topics(&records), vec!["task.start", "build.done"]);
+    }
+
+    #[test]
+    fn test_tasks_closed_since() {
+        let since = DateTime::parse_from_rfc3339("2026-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let task = |title: &str, closed: Option<&str>, loop_id: Option<&str>| {
+            let mut task = Task::new(title.to_string(), 3);
+            task.status = if closed.is_some() {
+                TaskStatus::Closed
+            } else {
+                TaskStatus::Open
+            };
+            task.closed = closed.map(str::to_string);
+            task.loop_id = loop_id.map(str::to_string);
+            task
+        };
+        let tasks = [
+            task("before", Some("2026-01-01T11:00:00Z"), None),
+            task("during", Some("2026-01-01T12:30:00Z"), None),
+            task("open", None, None),
+            task("other loop", Some("2026-01-01T12:30:00Z"), Some("loop-b")),
+            task("this loop", Some("2026-01-01T12:30:00Z"), Some("loop-a")),
+        ];
+        assert_eq!(
+            tasks_closed_since(&tasks, Some("loop-a"), since),
+            vec!["during", "this loop"]
+        );
+    }
+
+    #[test]
+    fn test_changelog_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = summary().changelog_entry("2026-01-01");
+        update_changelog(dir.path(), &first).unwrap();
+        let second = SessionSummary {
+            session_id: "primary-20260102-090000".to_string(),
+            completed_tasks: Vec::new(),
+            ..summary()
+        }
+        .changelog_entry("2026-01-02");
+        update_changelog(dir.path(), &second).unwrap();
+
+        let content = fs::read_to_string(dir.path().join(CHANGELOG_FILE)).unwrap();
+        assert!(content.starts_with("# Ralph changelog\n"));
+        let newer = content
+            .find("## primary-20260102-090000 (2026-01-02)")
+            .unwrap();
+        let older = content
+            .find("## primary-20260101-120000 (2026-01-01)")
+            .unwrap();
+        assert!(newer < older);
+        assert!(content.contains("- Completed tasks: none tracked\n"));
+        assert!(content.contains("- Completed tasks:\n  - Add login form\n"));
+    }
+}
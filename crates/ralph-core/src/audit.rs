@@ -0,0 +1,305 @@
+//! Dependency audit findings.
+//!
+//! `cargo audit --json` and `npm audit --json` report vulnerabilities in
+//! different shapes. This module normalizes both into [`Advisory`] records,
+//! which `ralph tools audit --emit` publishes as `security.advisory` events
+//! for the maintenance pack's hats.
+
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Topic published once per advisory.
+pub const ADVISORY_TOPIC: &str = "security.advisory";
+
+/// Topic published when an audit finds nothing.
+pub const CLEAN_TOPIC: &str = "audit.clean";
+
+/// A known vulnerability in one dependency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Advisory {
+    /// Package ecosystem (`cargo` or `npm`).
+    pub ecosystem: String,
+    /// Advisory ID (`RUSTSEC-...`, `GHSA-...`).
+    pub id: String,
+    /// Affected package.
+    pub package: String,
+    /// Installed version (cargo) or vulnerable range (npm).
+    pub version: String,
+    /// Severity, when the advisory states one.
+    pub severity: Option<String>,
+    /// One-line summary.
+    pub title: String,
+    /// Lowest version that fixes the advisory, if any.
+    pub fixed_in: Option<String>,
+    /// Link to the advisory.
+    pub url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CargoReport {
+    vulnerabilities: CargoVulnerabilities,
+}
+
+#[derive(Deserialize)]
+struct CargoVulnerabilities {
+    #[serde(default)]
+    list: Vec<CargoVulnerability>,
+}
+
+#[derive(Deserialize)]
+struct CargoVulnerability {
+    advisory: CargoAdvisory,
+    package: CargoPackage,
+    #[serde(default)]
+    versions: CargoVersions,
+}
+
+#[derive(Deserialize)]
+struct CargoAdvisory {
+    id: String,
+    title: String,
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    name: String,
+    version: String,
+}
+
+#[derive(Default, Deserialize)]
+struct CargoVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+}
+
+/// Parses the output of `cargo audit --json`.
+///
+/// # Errors
+///
+/// Returns an error if the output isn't a cargo-audit JSON report.
+pub fn parse_cargo_audit(json: &str) -> serde_json::Result<Vec<Advisory>> {
+    let report: CargoReport = serde_json::from_str(json)?;
+    Ok(report
+        .vulnerabilities
+        .list
+        .into_iter()
+        .map(|vuln| Advisory {
+            ecosystem: "cargo".to_string(),
+            url: vuln.advisory.url.or_else(|| {
+                Some(format!(
+                    "https://rustsec.org/advisories/{}",
+                    vuln.advisory.id
+                ))
+            }),
+            id: vuln.advisory.id,
+            package: vuln.package.name,
+            version: vuln.package.version,
+            severity: None,
+            title: vuln.advisory.title,
+            // Requirements like ">=0.9.7" or "^0.8.5"; the first is the lowest
+            fixed_in: vuln.versions.patched.first().map(|req| {
+                req.trim_start_matches(|c: char| !c.is_ascii_digit())
+                    .to_string()
+            }),
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct NpmReport {
+    #[serde(default)]
+    vulnerabilities: BTreeMap<String, NpmVulnerability>,
+}
+
+#[derive(Deserialize)]
+struct NpmVulnerability {
+    name: String,
+    #[serde(default)]
+    via: Vec<NpmVia>,
+    #[serde(default, rename = "fixAvailable")]
+    fix_available: Option<NpmFix>,
+}
+
+/// A cause of a vulnerability: an advisory, or the name of a vulnerable
+/// dependency (reported under that dependency's own entry).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NpmVia {
+    Advisory {
+        source: serde_json::Value,
+        title: String,
+        url: Option<String>,
+        severity: Option<String>,
+        range: String,
+    },
+    Dependency(IgnoredAny),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NpmFix {
+    Version { name: String, version: String },
+    Available(IgnoredAny),
+}
+
+/// Parses the output of `npm audit --json` (npm 7 and later).
+///
+/// Packages that are only vulnerable through a dependency are skipped; the
+/// dependency's own entry carries the advisory.
+///
+/// # Errors
+///
+/// Returns an error if the output isn't an npm audit JSON report.
+pub fn parse_npm_audit(json: &str) -> serde_json::Result<Vec<Advisory>> {
+    let report: NpmReport = serde_json::from_str(json)?;
+    let mut advisories = Vec::new();
+    for vuln in report.vulnerabilities.into_values() {
+        let fixed_in = match &vuln.fix_available {
+            Some(NpmFix::Version { name, version }) if *name == vuln.name => Some(version.clone()),
+            _ => None,
+        };
+        for via in vuln.via {
+            let NpmVia::Advisory {
+                source,
+                title,
+                url,
+                severity,
+                range,
+            } = via
+            else {
+                continue;
+            };
+            // GHSA IDs are only in the URL; the source is npm's numeric ID
+            let id = url
+                .as_deref()
+                .and_then(|url| url.rsplit('/').next())
+                .filter(|id| id.starts_with("GHSA-"))
+                .map_or_else(|| source.to_string(), str::to_string);
+            advisories.push(Advisory {
+                ecosystem: "npm".to_string(),
+                id,
+                package: vuln.name.clone(),
+                version: range,
+                severity,
+                title,
+                fixed_in: fixed_in.clone(),
+                url,
+            });
+        }
+    }
+    Ok(advisories)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cargo_audit() {
+        let json = r#"{
+          "database": {"advisory-count": 600},
+          "vulnerabilities": {
+            "found": true,
+            "count": 2,
+            "list": [
+              {
+                "advisory": {
+                  "id": "RUSTSEC-2023-0071",
+                  "package": "rsa",
+                  "title": "Marvin Attack: potential key recovery through timing sidechannels",
+                  "url": null,
+                  "cvss": "CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:H/I:N/A:N"
+                },
+                "versions": {"patched": [], "unaffected": []},
+                "package": {"name": "rsa", "version": "0.9.6"}
+              },
+              {
+                "advisory": {
+                  "id": "RUSTSEC-2024-0006",
+                  "package": "shlex",
+                  "title": "Multiple issues involving quote API",
+                  "url": "https://github.com/comex/rust-shlex/security/advisories/GHSA-r7qv-8r2h-pg27"
+                },
+                "versions": {"patched": [">=1.3.0"], "unaffected": []},
+                "package": {"name": "shlex", "version": "1.1.0"}
+              }
+            ]
+          },
+          "warnings": {}
+        }"#;
+
+        let advisories = parse_cargo_audit(json).unwrap();
+        assert_eq!(advisories.len(), 2);
+        assert_eq!(advisories[0].id, "RUSTSEC-2023-0071");
+        assert_eq!(advisories[0].package, "rsa");
+        assert_eq!(advisories[0].fixed_in, None);
+        assert_eq!(
+            advisories[0].url.as_deref(),
+            Some("https://rustsec.org/advisories/RUSTSEC-2023-0071")
+        );
+        assert_eq!(advisories[1].fixed_in.as_deref(), Some("1.3.0"));
+        assert_eq!(advisories[1].version, "1.1.0");
+    }
+
+    #[test]
+    fn test_parse_cargo_audit_clean() {
+        let json = r#"{"vulnerabilities": {"found": false, "count": 0, "list": []}}"#;
+        assert!(parse_cargo_audit(json).unwrap().is_empty());
+        assert!(parse_cargo_audit("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_npm_audit() {
+        let json = r#"{
+          "auditReportVersion": 2,
+          "vulnerabilities": {
+            "lodash": {
+              "name": "lodash",
+              "severity": "high",
+              "isDirect": true,
+              "via": [
+                {
+                  "source": 1096305,
+                  "name": "lodash",
+                  "dependency": "lodash",
+                  "title": "Prototype Pollution in lodash",
+                  "url": "https://github.com/advisories/GHSA-jf85-cpcp-j695",
+                  "severity": "critical",
+                  "range": "<4.17.12"
+                }
+              ],
+              "effects": ["lodash-wrapper"],
+              "range": "<=4.17.20",
+              "fixAvailable": {"name": "lodash", "version": "4.17.21", "isSemVerMajor": false}
+            },
+            "lodash-wrapper": {
+              "name": "lodash-wrapper",
+              "severity": "high",
+              "isDirect": false,
+              "via": ["lodash"],
+              "effects": [],
+              "range": "*",
+              "fixAvailable": true
+            }
+          },
+          "metadata": {"vulnerabilities": {"total": 2}}
+        }"#;
+
+        let advisories = parse_npm_audit(json).unwrap();
+        assert_eq!(
+            advisories,
+            vec![Advisory {
+                ecosystem: "npm".to_string(),
+                id: "GHSA-jf85-cpcp-j695".to_string(),
+                package: "lodash".to_string(),
+                version: "<4.17.12".to_string(),
+                severity: Some("critical".to_string()),
+                title: "Prototype Pollution in lodash".to_string(),
+                fixed_in: Some("4.17.21".to_string()),
+                url: Some("https://github.com/advisories/GHSA-jf85-cpcp-j695".to_string()),
+            }]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Structured escalation for blocked work.
+//!
+//! A hat that can't continue publishes a `*.blocked` topic. With
+//! [`BlockedConfig`](crate::BlockedConfig) enabled, the event loop pauses
+//! that hat's queue, records a [`Blocker`] with the events it was working
+//! on, and notifies the configured webhook. Other hats keep running. A
+//! `human.unblock` event, or the matching `*.unblocked` topic, resumes the
+//! hat and queues its work again.
+
+use serde::{Deserialize, Serialize};
+
+/// Suffix of topics that block the publishing hat.
+pub const BLOCKED_SUFFIX: &str = ".blocked";
+
+/// Suffix of the corrective topics that resume a blocked hat.
+pub const UNBLOCKED_SUFFIX: &str = ".unblocked";
+
+/// Topic a human publishes to resume blocked hats.
+pub const UNBLOCK_TOPIC: &str = "human.unblock";
+
+/// Checks if publishing `topic` blocks the hat that published it.
+pub fn is_blocked_topic(topic: &str) -> bool {
+    topic
+        .strip_suffix(BLOCKED_SUFFIX)
+        .is_some_and(|prefix| !prefix.is_empty())
+}
+
+/// Returns the corrective topic for a blocked topic
+/// (`build.blocked` → `build.unblocked`).
+pub fn unblocked_topic(blocked_topic: &str) -> String {
+    let prefix = blocked_topic
+        .strip_suffix(BLOCKED_SUFFIX)
+        .unwrap_or(blocked_topic);
+    format!("{prefix}{UNBLOCKED_SUFFIX}")
+}
+
+/// A paused hat and what it was doing when it blocked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Blocker {
+    /// Hat that published the blocked topic.
+    pub hat: String,
+    /// The blocked topic, e.g. `build.blocked`.
+    pub topic: String,
+    /// The blocked event's payload.
+    pub reason: String,
+    /// Iteration that blocked.
+    pub iteration: u32,
+    /// Events the hat was handling when it blocked, queued again on resume.
+    pub context: Vec<BlockedEvent>,
+}
+
+/// One event in a [`Blocker`]'s context.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockedEvent {
+    pub topic: String,
+    pub payload: String,
+}
+
+/// Body POSTed to the blocked webhook.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockedNotice<'a> {
+    #[serde(flatten)]
+    pub blocker: &'a Blocker,
+    /// Events file an unblock should be written to.
+    pub events_file: String,
+    /// Command that resumes the hat.
+    pub unblock: String,
+}
+
+impl Blocker {
+    /// Checks if `topic` and `payload` resume this blocker.
+    ///
+    /// The corrective `*.unblocked` topic resumes it. A `human.unblock`
+    /// whose payload starts with a blocked hat ID or topic resumes only that
+    /// blocker; any other `human.unblock` resumes every blocker.
+    pub fn resumed_by(&self, topic: &str, payload: &str, all: &[Blocker]) -> bool {
+        if topic == unblocked_topic(&self.topic) {
+            return true;
+        }
+        if topic != UNBLOCK_TOPIC {
+            return false;
+        }
+        let first = payload
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches(':');
+        let names = |b: &Blocker| b.hat == first || b.topic == first;
+        names(self) || !all.iter().any(names)
+    }
+
+    /// The command a human runs to resume this blocker.
+    pub fn unblock_command(&self) -> String {
+        format!("ralph emit {UNBLOCK_TOPIC} \"{} <guidance>\"", self.hat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocker(hat: &str, topic: &str) -> Blocker {
+        Blocker {
+            hat: hat.to_string(),
+            topic: topic.to_string(),
+            reason: "Need the API key".to_string(),
+            iteration: 3,
+            context: vec![BlockedEvent {
+                topic: "build.task".to_string(),
+                payload: "Add login".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_blocked_topics() {
+        assert!(is_blocked_topic("build.blocked"));
+        assert!(is_blocked_topic("deps.update.blocked"));
+        assert!(!is_blocked_topic(".blocked"));
+        assert!(!is_blocked_topic("build.unblocked"));
+        assert!(!is_blocked_topic("blocked"));
+        assert_eq!(unblocked_topic("build.blocked"), "build.unblocked");
+    }
+
+    #[test]
+    fn test_resumed_by() {
+        let builder = blocker("builder", "build.blocked");
+        let updater = blocker("dep_updater", "deps.blocked");
+        let all = [builder.clone(), updater.clone()];
+
+        assert!(builder.resumed_by("build.unblocked", "", &all));
+        assert!(!updater.resumed_by("build.unblocked", "", &all));
+
+        // Naming a blocker resumes only that one
+        assert!(builder.resumed_by(UNBLOCK_TOPIC, "builder: key is in .env", &all));
+        assert!(!updater.resumed_by(UNBLOCK_TOPIC, "builder: key is in .env", &all));
+        assert!(updater.resumed_by(UNBLOCK_TOPIC, "deps.blocked skip it", &all));
+
+        // Anything else resumes everything
+        assert!(builder.resumed_by(UNBLOCK_TOPIC, "Go ahead", &all));
+        assert!(updater.resumed_by(UNBLOCK_TOPIC, "", &all));
+        assert!(!builder.resumed_by("build.task", "builder", &all));
+    }
+
+    #[test]
+    fn test_notice_serializes_flat() {
+        let builder = blocker("builder", "build.blocked");
+        let notice = BlockedNotice {
+            blocker: &builder,
+            events_file: ".ralph/events.jsonl".to_string(),
+            unblock: builder.unblock_command(),
+        };
+        let json = serde_json::to_value(&notice).unwrap();
+        assert_eq!(json["hat"], "builder");
+        assert_eq!(json["context"][0]["topic"], "build.task");
+        assert_eq!(
+            json["unblock"],
+            "ralph emit human.unblock \"builder <guidance>\""
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Cold-start context from a previous session.
+//!
+//! Each loop copies its `summary.md` into its session directory,
+//! `.ralph/agent/sessions/<loop>/`, when it ends. A fresh run started with
+//! `ralph run --bootstrap-from last` (or a loop id) loads that summary and
+//! the task list's unfinished tasks into a [`SessionBootstrap`], and the
+//! event loop puts it in the first prompt, so multi-day work picks up where
+//! the last session left off instead of re-deriving it.
+
+use crate::loop_context::LoopContext;
+use crate::task::Task;
+use crate::task_store::TaskStore;
+use crate::text::truncate_with_ellipsis;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+/// File name of the summary copy kept in each session directory.
+pub const SESSION_SUMMARY_FILE: &str = "summary.md";
+
+/// Longest summary injected, in characters.
+const MAX_SUMMARY_CHARS: usize = 8_000;
+
+/// Which previous session to bootstrap from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BootstrapSource {
+    /// The most recently ended session.
+    Last,
+    /// A session by loop id, or an unambiguous prefix of one.
+    Session(String),
+}
+
+impl FromStr for BootstrapSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" => Err("expected `last` or a loop id".to_string()),
+            "last" => Ok(Self::Last),
+            id => Ok(Self::Session(id.to_string())),
+        }
+    }
+}
+
+/// Errors from loading a previous session.
+#[derive(Debug, thiserror::Error)]
+pub enum BootstrapError {
+    #[error("No previous session summary found in {}", dir.display())]
+    NoSessions { dir: PathBuf },
+
+    #[error("No session '{id}' with a summary in {}", dir.display())]
+    NotFound { id: String, dir: PathBuf },
+
+    #[error("Session '{id}' is ambiguous: {}", matches.join(", "))]
+    Ambiguous { id: String, matches: Vec<String> },
+
+    #[error("Failed to read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// What a previous session left behind.
+#[derive(Debug, Clone)]
+pub struct SessionBootstrap {
+    /// Loop id of the previous session.
+    pub session: String,
+    /// Its `summary.md`.
+    pub summary: String,
+    /// Tasks still open or in progress, highest priority first.
+    pub unfinished_tasks: Vec<Task>,
+}
+
+impl SessionBootstrap {
+    /// Loads the session `source` names from `context`'s session directories.
+    ///
+    /// `current` is the loop id of the run being started; its own session
+    /// directory is never picked.
+    pub fn load(
+        context: &LoopContext,
+        source: &BootstrapSource,
+        current: &str,
+    ) -> Result<Self, BootstrapError> {
+        let dir = context.sessions_dir();
+        let mut sessions: Vec<(SystemTime, String)> = fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| {
+                        let id = entry.file_name().to_str()?.to_string();
+                        let modified = entry
+                            .path()
+                            .join(SESSION_SUMMARY_FILE)
+                            .metadata()
+                            .and_then(|m| m.modified())
+                            .ok()?;
+                        (id != current).then_some((modified, id))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let session = match source {
+            BootstrapSource::Last => {
+                sessions.sort();
+                sessions
+                    .pop()
+                    .map(|(_, id)| id)
+                    .ok_or(BootstrapError::NoSessions { dir: dir.clone() })?
+            }
+            BootstrapSource::Session(prefix) => {
+                if let Some((_, id)) = sessions.iter().find(|(_, id)| id == prefix) {
+                    id.clone()
+                } else {
+                    let mut matches: Vec<String> = sessions
+                        .into_iter()
+                        .map(|(_, id)| id)
+                        .filter(|id| id.starts_with(prefix.as_str()))
+                        .collect();
+                    match matches.len() {
+                        0 => {
+                            return Err(BootstrapError::NotFound {
+                                id: prefix.clone(),
+                                dir,
+                            });
+                        }
+                        1 => matches.remove(0),
+                        _ => {
+                            matches.sort();
+                            return Err(BootstrapError::Ambiguous {
+                                id: prefix.clone(),
+                                matches,
+                            });
+                        }
+                    }
+                }
+            }
+        };
+
+        let path = dir.join(&session).join(SESSION_SUMMARY_FILE);
+        let summary =
+            fs::read_to_string(&path).map_err(|source| BootstrapError::Read { path, source })?;
+
+        let mut unfinished_tasks: Vec<Task> = TaskStore::load(&context.tasks_path())
+            .map(|store| {
+                store
+                    .all()
+                    .iter()
+                    .filter(|task| !task.status.is_terminal())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        unfinished_tasks.sort_by_key(|task| task.priority);
+
+        Ok(Self {
+            session,
+            summary,
+            unfinished_tasks,
+        })
+    }
+
+    /// Renders the section body for the first prompt.
+    pub fn render(&self) -> String {
+        let mut section = format!(
+            "This run continues earlier work. Session `{}` ended with this summary; \
+             read it before planning instead of starting from zero.\n\n\
+             <previous-session>\n{}\n</previous-session>\n",
+            self.session,
+            truncate_with_ellipsis(self.summary.trim(), MAX_SUMMARY_CHARS)
+        );
+        if !self.unfinished_tasks.is_empty() {
+            section.push_str("\nUnfinished tasks:\n");
+            for task in &self.unfinished_tasks {
+                section.push_str(&format!(
+                    "- [P{}] {} ({})\n",
+                    task.priority, task.title, task.id
+                ));
+            }
+        }
+        section
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::TaskStatus;
+    use tempfile::TempDir;
+
+    fn write_session(ctx: &LoopContext, id: &str, summary: &str) {
+        let dir = ctx.sessions_dir().join(id);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(SESSION_SUMMARY_FILE), summary).unwrap();
+        // Distinct modification times for ordering
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_last_picks_newest_other_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let ctx = LoopContext::primary(temp_dir.path().to_path_buf());
+        write_session(&ctx, "primary-20261015-090000", "# Loop Summary\nday one");
+        write_session(&ctx, "primary-20261016-090000", "# Loop Summary\nday two");
+        // A session without a summary (still running, or killed) is skipped
+        fs::create_dir_all(ctx.sessions_dir().join("primary-20261017-090000")).unwrap();
+
+        let mut store = TaskStore::load(&ctx.tasks_path()).unwrap();
+        let mut done = Task::new("Add login".to_string(), 1);
+        done.status = TaskStatus::Closed;
+        store.add(done);
+        store.add(Task::new("Add rate limiting".to_string(), 3));
+        store.add(Task::new("Fix session expiry".to_string(), 1));
+        store.save().unwrap();
+
+        let bootstrap =
+            SessionBootstrap::load(&ctx, &BootstrapSource::Last, "primary-20261017-090000")
+                .unwrap();
+        assert_eq!(bootstrap.session, "primary-20261016-090000");
+        assert!(bootstrap.summary.contains("day two"));
+        let titles: Vec<_> = bootstrap
+            .unfinished_tasks
+            .iter()
+            .map(|task| task.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Fix session expiry", "Add rate limiting"]);
+
+        let rendered = bootstrap.render();
+        assert!(rendered.contains("<previous-session>\n# Loop Summary\nday two\n"));
+        assert!(rendered.contains("- [P1] Fix session expiry"));
+        assert!(!rendered.contains("Add login"));
+    }
+
+    #[test]
+    fn test_session_by_prefix() {
+        let temp_dir = TempDir::new().unwrap();
+        let ctx = LoopContext::primary(temp_dir.path().to_path_buf());
+        write_session(&ctx, "primary-20261015-090000", "one");
+        write_session(&ctx, "primary-20261016-090000", "two");
+
+        let source: BootstrapSource = "primary-20261015".parse().unwrap();
+        let bootstrap = SessionBootstrap::load(&ctx, &source, "current").unwrap();
+        assert_eq!(bootstrap.summary, "one");
+
+        let err =
+            SessionBootstrap::load(&ctx, &"primary-2026".parse().unwrap(), "current").unwrap_err();
+        assert!(matches!(err, BootstrapError::Ambiguous { .. }));
+        let err = SessionBootstrap::load(&ctx, &"nope".parse().unwrap(), "current").unwrap_err();
+        assert!(matches!(err, BootstrapError::NotFound { .. }));
+    }
+
+    #[test]
+    fn test_no_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let ctx = LoopContext::primary(temp_dir.path().to_path_buf());
+        let err = SessionBootstrap::load(&ctx, &BootstrapSource::Last, "current").unwrap_err();
+        assert!(err.to_string().starts_with("No previous session summary"));
+        assert!("".parse::<BootstrapSource>().is_err());
+    }
+}
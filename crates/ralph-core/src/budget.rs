@@ -0,0 +1,190 @@
+//! Adaptive iteration budget.
+//!
+//! A fixed `max_iterations` cuts productive runs off at an arbitrary number
+//! and lets stalled runs burn the rest of their budget. The controller here
+//! samples progress after every iteration (tasks closed and lines changed)
+//! and, once per window, moves the iteration limit:
+//!
+//! - Progressing (tasks closed, or the diff isn't shrinking below half of the
+//!   previous window) and close to the limit: extend by `extend_by`, up to
+//!   `max_iterations`.
+//! - Stalled (no tasks closed and no lines changed in a whole window): shrink
+//!   the limit to one more window, so a run that stays stalled ends there.
+//!   Never below `min_iterations`.
+//!
+//! `max_runtime_seconds` and `max_cost_usd` still apply as hard caps.
+//! See [`AdaptiveBudgetConfig`](crate::AdaptiveBudgetConfig).
+
+use crate::config::AdaptiveBudgetConfig;
+
+/// Progress made in one iteration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProgressSample {
+    /// Tasks that moved to `closed` during the iteration.
+    pub tasks_closed: u32,
+    /// Lines inserted plus deleted during the iteration.
+    pub lines_changed: u64,
+}
+
+/// A change the controller made to the iteration limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetChange {
+    /// Progress is steady and the limit was near; it was raised.
+    Extended { from: u32, to: u32 },
+    /// Progress stalled; the limit was lowered.
+    Shrunk { from: u32, to: u32 },
+}
+
+/// Iteration limit that follows the run's progress.
+#[derive(Debug, Clone)]
+pub struct AdaptiveBudget {
+    config: AdaptiveBudgetConfig,
+    limit: u32,
+    window: Vec<ProgressSample>,
+    previous_lines: Option<u64>,
+}
+
+impl AdaptiveBudget {
+    /// Starts from the configured `event_loop.max_iterations`.
+    pub fn new(config: &AdaptiveBudgetConfig, max_iterations: u32) -> Self {
+        Self {
+            config: config.clone(),
+            limit: max_iterations,
+            window: Vec::new(),
+            previous_lines: None,
+        }
+    }
+
+    /// Returns the current iteration limit.
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// Records the progress of `iteration` and adjusts the limit at the end
+    /// of each window.
+    pub fn record(&mut self, iteration: u32, sample: ProgressSample) -> Option<BudgetChange> {
+        let window = self.config.window.max(1) as usize;
+        self.window.push(sample);
+        if self.window.len() < window {
+            return None;
+        }
+
+        let tasks: u32 = self.window.iter().map(|s| s.tasks_closed).sum();
+        let lines: u64 = self.window.iter().map(|s| s.lines_changed).sum();
+        let previous = self.previous_lines.replace(lines);
+        self.window.clear();
+
+        let from = self.limit;
+        if tasks == 0 && lines == 0 {
+            let to = (iteration + self.config.window).max(self.config.min_iterations);
+            if to < from {
+                self.limit = to;
+                return Some(BudgetChange::Shrunk { from, to });
+            }
+            return None;
+        }
+
+        let steady = tasks > 0 || previous.is_none_or(|p| lines * 2 >= p);
+        let near_limit = from.saturating_sub(iteration) <= self.config.window;
+        if steady && near_limit {
+            let to = from
+                .saturating_add(self.config.extend_by)
+                .min(self.config.max_iterations);
+            if to > from {
+                self.limit = to;
+                return Some(BudgetChange::Extended { from, to });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(start: u32) -> AdaptiveBudget {
+        let config = AdaptiveBudgetConfig {
+            enabled: true,
+            window: 3,
+            extend_by: 10,
+            min_iterations: 4,
+            max_iterations: 25,
+        };
+        AdaptiveBudget::new(&config, start)
+    }
+
+    fn progress(tasks_closed: u32, lines_changed: u64) -> ProgressSample {
+        ProgressSample {
+            tasks_closed,
+            lines_changed,
+        }
+    }
+
+    #[test]
+    fn test_extends_productive_run_near_limit_up_to_ceiling() {
+        let mut budget = budget(10);
+        for i in 1..=6 {
+            assert_eq!(budget.record(i, progress(1, 40)), None);
+        }
+        // Window ending at 9 is within one window of the limit
+        budget.record(7, progress(0, 30));
+        budget.record(8, progress(1, 30));
+        assert_eq!(
+            budget.record(9, progress(0, 30)),
+            Some(BudgetChange::Extended { from: 10, to: 20 })
+        );
+
+        for i in 10..=18 {
+            budget.record(i, progress(1, 30));
+        }
+        assert_eq!(budget.limit(), 25);
+    }
+
+    #[test]
+    fn test_shrinks_stalled_run_then_ends_it() {
+        let mut budget = budget(50);
+        for i in 1..=3 {
+            budget.record(i, progress(1, 10));
+        }
+        budget.record(4, ProgressSample::default());
+        budget.record(5, ProgressSample::default());
+        assert_eq!(
+            budget.record(6, ProgressSample::default()),
+            Some(BudgetChange::Shrunk { from: 50, to: 9 })
+        );
+
+        // Still stalled at the new limit: nothing left to shrink, the run ends
+        for i in 7..=8 {
+            budget.record(i, ProgressSample::default());
+        }
+        assert_eq!(budget.record(9, ProgressSample::default()), None);
+        assert_eq!(budget.limit(), 9);
+    }
+
+    #[test]
+    fn test_never_shrinks_below_minimum() {
+        let config = AdaptiveBudgetConfig {
+            min_iterations: 10,
+            ..budget(50).config
+        };
+        let mut budget = AdaptiveBudget::new(&config, 50);
+        for i in 1..=3 {
+            budget.record(i, ProgressSample::default());
+        }
+        assert_eq!(budget.limit(), 10);
+    }
+
+    #[test]
+    fn test_shrinking_diff_without_tasks_does_not_extend() {
+        let mut budget = budget(7);
+        for i in 1..=3 {
+            budget.record(i, progress(0, 100));
+        }
+        for i in 4..=5 {
+            budget.record(i, progress(0, 10));
+        }
+        assert_eq!(budget.record(6, progress(0, 10)), None);
+        assert_eq!(budget.limit(), 7);
+    }
+}
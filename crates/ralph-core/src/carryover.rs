@@ -0,0 +1,174 @@
+//! Iteration carry-over summaries.
+//!
+//! Each iteration starts with fresh context, so the next agent often spends
+//! its first minutes rediscovering what the last one just learned. When
+//! carry-over is enabled, the orchestrator pulls a short summary out of the
+//! iteration's output and injects it into the next prompt as
+//! `## PREVIOUS ITERATION`.
+//!
+//! Extraction is purely textual: matching markdown sections (`## Summary`,
+//! `### Next steps`, ...) are kept, and when there are none the tail of the
+//! output is used instead.
+
+use crate::config::CarryoverConfig;
+use crate::text::floor_char_boundary;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// A markdown ATX heading: level marker and title.
+static HEADING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(#{1,6})\s+(.+?)\s*#*\s*$").unwrap());
+
+/// Extracts a carry-over summary from an iteration's output.
+///
+/// Returns `None` when the output has no usable text.
+pub fn summarize(output: &str, config: &CarryoverConfig) -> Option<String> {
+    let sections = matching_sections(output, &config.sections);
+    let summary = if sections.is_empty() {
+        tail(output, config.max_chars)
+    } else {
+        sections.join("\n\n")
+    };
+
+    let summary = summary.trim();
+    if summary.is_empty() {
+        return None;
+    }
+    Some(truncate(summary, config.max_chars))
+}
+
+/// Collects sections whose heading starts with one of `names` (case-insensitive).
+///
+/// A section runs until the next heading of the same or a higher level.
+fn matching_sections(output: &str, names: &[String]) -> Vec<String> {
+    let names: Vec<String> = names.iter().map(|n| n.to_lowercase()).collect();
+    let mut sections = Vec::new();
+    // (level, lines) of the section being collected
+    let mut current: Option<(usize, Vec<&str>)> = None;
+    let mut in_fence = false;
+
+    for line in output.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let heading = if in_fence {
+            None
+        } else {
+            HEADING_RE
+                .captures(line)
+                .map(|caps| (caps[1].len(), caps[2].to_lowercase()))
+        };
+
+        if let Some((level, title)) = heading {
+            if let Some((open_level, _)) = &current
+                && level <= *open_level
+            {
+                sections.extend(current.take().map(|(_, lines)| lines.join("\n")));
+            }
+            if current.is_none() && names.iter().any(|name| title.starts_with(name.as_str())) {
+                current = Some((level, vec![line]));
+                continue;
+            }
+        }
+
+        if let Some((_, lines)) = &mut current {
+            lines.push(line);
+        }
+    }
+    sections.extend(current.map(|(_, lines)| lines.join("\n")));
+
+    sections
+        .into_iter()
+        .map(|section| section.trim_end().to_string())
+        .filter(|section| section.lines().count() > 1)
+        .collect()
+}
+
+/// Returns roughly the last `max_chars` of `output`, starting on a line boundary.
+fn tail(output: &str, max_chars: usize) -> String {
+    let output = output.trim_end();
+    if output.len() <= max_chars {
+        return output.to_string();
+    }
+    let start = floor_char_boundary(output, output.len() - max_chars);
+    let line_start = output[start..].find('\n').map_or(start, |n| start + n + 1);
+    output[line_start..].to_string()
+}
+
+/// Cuts `summary` to `max_chars` bytes on a char boundary, marking the cut.
+fn truncate(summary: &str, max_chars: usize) -> String {
+    if summary.len() <= max_chars {
+        return summary.to_string();
+    }
+    let end = floor_char_boundary(summary, max_chars);
+    format!("{}\n[...]", summary[..end].trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CarryoverConfig {
+        CarryoverConfig {
+            enabled: true,
+            ..CarryoverConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_extracts_matching_sections() {
+        let output = "\
+Exploring the repo...
+## Summary
+Parser rewritten; two tests still fail.
+### Details
+Failing: test_cdata, test_nested
+## Unrelated
+noise
+## Next Steps
+- Fix test_cdata
+```md
+## Summary
+not a heading inside a fence
+```
+";
+        let summary = summarize(output, &config()).unwrap();
+        assert!(summary.starts_with("## Summary\nParser rewritten"));
+        assert!(summary.contains("Failing: test_cdata"));
+        assert!(summary.contains("## Next Steps\n- Fix test_cdata"));
+        assert!(!summary.contains("noise"));
+    }
+
+    #[test]
+    fn test_falls_back_to_tail() {
+        let output: String = (0..200)
+            .map(|i| format!("line {i}\n"))
+            .collect::<Vec<_>>()
+            .concat();
+        let config = CarryoverConfig {
+            max_chars: 40,
+            ..config()
+        };
+        let summary = summarize(&output, &config).unwrap();
+        assert!(summary.ends_with("line 199"));
+        assert!(summary.len() <= 40);
+        assert!(summary.starts_with("line "));
+    }
+
+    #[test]
+    fn test_truncates_long_sections() {
+        let output = format!("## Summary\n{}", "é".repeat(500));
+        let config = CarryoverConfig {
+            max_chars: 101,
+            ..config()
+        };
+        let summary = summarize(&output, &config).unwrap();
+        assert!(summary.ends_with("\n[...]"));
+        assert!(summary.len() <= 101 + "\n[...]".len());
+    }
+
+    #[test]
+    fn test_empty_output_has_no_summary() {
+        assert_eq!(summarize("  \n\n", &config()), None);
+    }
+}
@@ -0,0 +1,518 @@
+//! Nested sub-orchestrations (`child_loops:` in ralph.yml).
+//!
+//! A hat hands a bounded sub-project to a child loop by emitting
+//! `ralph.spawn_loop`. The payload is the child's prompt, or JSON for more
+//! control:
+//!
+//! ```json
+//! {"name": "auth", "prompt": "Implement OAuth login", "config": "child.yml",
+//!  "max_iterations": 20, "max_cost_usd": 2.0, "max_runtime_seconds": 1800}
+//! ```
+//!
+//! The child runs `ralph run` headless in a fresh git worktree on branch
+//! `ralph/<name>`, with the parent's config (or the given config file) and a
+//! budget capped by `child_loops`. When it exits, any uncommitted work is
+//! committed to its branch and the result is published to the parent as
+//! `ralph.child_loop.done` or `ralph.child_loop.failed`, carrying the branch,
+//! worktree, and the child's summary. The worktree is kept so the parent can
+//! inspect or merge it.
+
+use crate::config::{ChildLoopsConfig, RalphConfig};
+use crate::worktree::{WorktreeConfig, create_worktree};
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Topic a hat emits to request a child loop.
+pub const SPAWN_TOPIC: &str = "ralph.spawn_loop";
+
+/// Topic published when a child loop completes.
+pub const DONE_TOPIC: &str = "ralph.child_loop.done";
+
+/// Topic published when a child loop fails or can't be started.
+pub const FAILED_TOPIC: &str = "ralph.child_loop.failed";
+
+/// Environment variable carrying the nesting depth to child processes.
+pub const DEPTH_ENV: &str = "RALPH_LOOP_DEPTH";
+
+/// Characters of the child's summary included in the result event.
+const SUMMARY_CHARS: usize = 4000;
+
+/// Extra time past the child's own runtime limit before it is killed.
+const KILL_GRACE: Duration = Duration::from_mins(1);
+
+/// Time a child gets to stop its backends after SIGTERM before SIGKILL.
+const STOP_GRACE: Duration = Duration::from_secs(10);
+
+/// Errors that prevent a child loop from running.
+#[derive(Debug, thiserror::Error)]
+pub enum ChildLoopError {
+    /// `child_loops.enabled` is false.
+    #[error("child loops are disabled (set child_loops.enabled: true)")]
+    Disabled,
+
+    /// Spawning would exceed `child_loops.max_depth`.
+    #[error("child loop depth limit reached (max_depth: {0})")]
+    TooDeep(u32),
+
+    /// The iteration already requested `child_loops.max_per_iteration` children.
+    #[error("too many child loops requested in one iteration (max_per_iteration: {0})")]
+    TooMany(u32),
+
+    /// The spawn payload is unusable.
+    #[error("invalid ralph.spawn_loop payload: {0}")]
+    InvalidRequest(String),
+
+    /// The child's config couldn't be built.
+    #[error("invalid child config: {0}")]
+    Config(String),
+
+    /// The isolated worktree couldn't be created.
+    #[error("failed to create child workspace: {0}")]
+    Worktree(#[from] crate::worktree::WorktreeError),
+
+    /// Filesystem or process error.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// A parsed `ralph.spawn_loop` request.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct SpawnRequest {
+    /// Short name used for the worktree and branch.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The child's objective.
+    pub prompt: String,
+    /// Config file for the child, relative to the parent workspace.
+    /// Defaults to the parent's config.
+    #[serde(default)]
+    pub config: Option<String>,
+    /// Requested iteration budget (capped by `child_loops.max_iterations`).
+    #[serde(default)]
+    pub max_iterations: Option<u32>,
+    /// Requested cost budget (capped by `child_loops.max_cost_usd`).
+    #[serde(default)]
+    pub max_cost_usd: Option<f64>,
+    /// Requested runtime budget (capped by `child_loops.max_runtime_seconds`).
+    #[serde(default)]
+    pub max_runtime_seconds: Option<u64>,
+}
+
+impl SpawnRequest {
+    /// Parses a spawn payload: a JSON object, or plain text used as the prompt.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for empty prompts or malformed JSON objects.
+    pub fn parse(payload: &str) -> Result<Self, ChildLoopError> {
+        let trimmed = payload.trim();
+        let request = if trimmed.starts_with('{') {
+            serde_json::from_str(trimmed)
+                .map_err(|e| ChildLoopError::InvalidRequest(e.to_string()))?
+        } else {
+            Self {
+                prompt: trimmed.to_string(),
+                ..Self::default()
+            }
+        };
+        if request.prompt.trim().is_empty() {
+            return Err(ChildLoopError::InvalidRequest(
+                "the child needs a prompt".to_string(),
+            ));
+        }
+        Ok(request)
+    }
+
+    /// The budget the child runs with: the request, capped by `limits`.
+    pub fn budget(&self, limits: &ChildLoopsConfig) -> Budget {
+        let max_cost_usd = match (self.max_cost_usd, limits.max_cost_usd) {
+            (Some(requested), Some(limit)) => Some(requested.min(limit)),
+            (requested, limit) => requested.or(limit),
+        };
+        Budget {
+            max_iterations: self
+                .max_iterations
+                .map_or(limits.max_iterations, |n| n.min(limits.max_iterations)),
+            max_cost_usd,
+            max_runtime_seconds: self
+                .max_runtime_seconds
+                .map_or(limits.max_runtime_seconds, |n| {
+                    n.min(limits.max_runtime_seconds)
+                }),
+        }
+    }
+}
+
+/// Limits a child loop runs under.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Budget {
+    pub max_iterations: u32,
+    pub max_cost_usd: Option<f64>,
+    pub max_runtime_seconds: u64,
+}
+
+/// Outcome of a finished child loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildLoopResult {
+    /// Worktree name.
+    pub name: String,
+    /// Branch holding the child's work.
+    pub branch: String,
+    /// Worktree the child ran in.
+    pub worktree: PathBuf,
+    /// Whether the child exited successfully (completion promise reached).
+    pub success: bool,
+    /// Child exit code (`None` if killed).
+    pub exit_code: Option<i32>,
+    /// The child's `.ralph/agent/summary.md`, truncated.
+    pub summary: String,
+}
+
+impl ChildLoopResult {
+    /// Topic of the result event.
+    pub fn topic(&self) -> &'static str {
+        if self.success {
+            DONE_TOPIC
+        } else {
+            FAILED_TOPIC
+        }
+    }
+
+    /// Payload of the result event.
+    pub fn payload(&self) -> String {
+        let exit = self
+            .exit_code
+            .map_or_else(|| "killed".to_string(), |code| code.to_string());
+        let mut payload = format!(
+            "Child loop '{}' {}.\n- branch: {}\n- worktree: {}\n- exit_code: {exit}\n",
+            self.name,
+            if self.success {
+                "completed"
+            } else {
+                "did not complete"
+            },
+            self.branch,
+            self.worktree.display(),
+        );
+        if !self.summary.trim().is_empty() {
+            payload.push_str("\n## Child summary\n");
+            payload.push_str(&self.summary);
+        }
+        payload
+    }
+}
+
+/// Current nesting depth of this process (0 for a top-level loop).
+pub fn current_depth() -> u32 {
+    std::env::var(DEPTH_ENV)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Runs a child loop for `request` and waits for it to finish.
+///
+/// `ralph_bin` is the `ralph` executable to run; `workspace` is the parent
+/// workspace (a git repository).
+///
+/// # Errors
+///
+/// Returns an error if child loops are disabled, nesting is too deep, or the
+/// child's worktree or config can't be set up. A child that runs and fails is
+/// not an error; see [`ChildLoopResult::success`].
+pub async fn run_child_loop(
+    request: &SpawnRequest,
+    parent_config: &RalphConfig,
+    workspace: &Path,
+    ralph_bin: &Path,
+) -> Result<ChildLoopResult, ChildLoopError> {
+    let limits = &parent_config.child_loops;
+    if !limits.enabled {
+        return Err(ChildLoopError::Disabled);
+    }
+    let depth = current_depth();
+    if depth >= limits.max_depth {
+        return Err(ChildLoopError::TooDeep(limits.max_depth));
+    }
+
+    let budget = request.budget(limits);
+    let config_yaml = child_config(request, parent_config, workspace, budget)?;
+    let name = child_name(request.name.as_deref());
+    let worktree = crate::utils::run_blocking(|| {
+        create_worktree(workspace, &name, &WorktreeConfig::default())
+    })?;
+
+    let ralph_dir = worktree.path.join(".ralph");
+    std::fs::create_dir_all(&ralph_dir)?;
+    let config_path = ralph_dir.join("child-loop.yml");
+    std::fs::write(&config_path, config_yaml)?;
+    let log = std::fs::File::create(ralph_dir.join("child-loop.log"))?;
+
+    tracing::info!(name = %name, branch = %worktree.branch, ?budget, "Starting child loop");
+    let mut command = tokio::process::Command::new(ralph_bin);
+    // The child leads its own process group so it can be stopped as a unit
+    #[cfg(unix)]
+    command.process_group(0);
+    let mut child = command
+        .arg("run")
+        .arg("-c")
+        .arg(&config_path)
+        .arg("-p")
+        .arg(&request.prompt)
+        .args(["--autonomous", "--no-auto-merge", "--skip-preflight"])
+        .arg("--max-iterations")
+        .arg(budget.max_iterations.to_string())
+        .current_dir(&worktree.path)
+        .env(DEPTH_ENV, (depth + 1).to_string())
+        .env_remove("RALPH_WORKSPACE_ROOT")
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let deadline = Duration::from_secs(budget.max_runtime_seconds) + KILL_GRACE;
+    let exit_code = match tokio::time::timeout(deadline, child.wait()).await {
+        Ok(status) => status?.code(),
+        Err(_) => {
+            tracing::warn!(name = %name, "Child loop exceeded its runtime; stopping it");
+            stop_child(&mut child).await;
+            None
+        }
+    };
+
+    crate::utils::run_blocking(|| commit_leftovers(&worktree.path, &name));
+    let summary = std::fs::read_to_string(ralph_dir.join("agent/summary.md")).unwrap_or_default();
+
+    Ok(ChildLoopResult {
+        name,
+        branch: worktree.branch,
+        worktree: worktree.path,
+        success: exit_code == Some(0),
+        exit_code,
+        summary: crate::text::truncate_with_ellipsis(&summary, SUMMARY_CHARS),
+    })
+}
+
+/// Stops a child that outran its deadline.
+///
+/// The child's backends run in process groups of their own, which only the
+/// child knows about, so it gets SIGTERM first to shut them down; its whole
+/// group is killed if it doesn't exit within [`STOP_GRACE`].
+async fn stop_child(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id().and_then(|pid| i32::try_from(pid).ok()) {
+        use nix::sys::signal::{Signal, killpg};
+        use nix::unistd::Pid;
+
+        let group = Pid::from_raw(pid);
+        let _ = killpg(group, Signal::SIGTERM);
+        if tokio::time::timeout(STOP_GRACE, child.wait()).await.is_ok() {
+            return;
+        }
+        let _ = killpg(group, Signal::SIGKILL);
+    }
+    let _ = child.kill().await;
+}
+
+/// Builds the child's ralph.yml with `budget` applied.
+fn child_config(
+    request: &SpawnRequest,
+    parent_config: &RalphConfig,
+    workspace: &Path,
+    budget: Budget,
+) -> Result<String, ChildLoopError> {
+    let config_error = |e: &dyn std::fmt::Display| ChildLoopError::Config(e.to_string());
+    let mut value = match &request.config {
+        Some(path) => {
+            let content = std::fs::read_to_string(workspace.join(path))
+                .map_err(|e| ChildLoopError::Config(format!("{path}: {e}")))?;
+            serde_yaml::from_str::<Value>(&content).map_err(|e| config_error(&e))?
+        }
+        None => serde_yaml::to_value(parent_config).map_err(|e| config_error(&e))?,
+    };
+
+    let root = value
+        .as_mapping_mut()
+        .ok_or_else(|| ChildLoopError::Config("config must be a YAML mapping".to_string()))?;
+    let event_loop = root
+        .entry(Value::from("event_loop"))
+        .or_insert_with(|| Value::Mapping(serde_yaml::Mapping::new()));
+    let event_loop = event_loop
+        .as_mapping_mut()
+        .ok_or_else(|| ChildLoopError::Config("event_loop must be a mapping".to_string()))?;
+    event_loop.insert(
+        Value::from("max_iterations"),
+        Value::from(budget.max_iterations),
+    );
+    event_loop.insert(
+        Value::from("max_runtime_seconds"),
+        Value::from(budget.max_runtime_seconds),
+    );
+    if let Some(cost) = budget.max_cost_usd {
+        event_loop.insert(Value::from("max_cost_usd"), Value::from(cost));
+    }
+
+    serde_yaml::to_string(&value).map_err(|e| config_error(&e))
+}
+
+/// Worktree name: the requested name (sanitized) plus a timestamp.
+fn child_name(requested: Option<&str>) -> String {
+    let base: String = requested
+        .unwrap_or("child")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .take(40)
+        .collect();
+    let base = base.trim_matches('-');
+    let base = if base.is_empty() { "child" } else { base };
+    format!("{base}-{}", chrono::Utc::now().format("%Y%m%d-%H%M%S-%3f"))
+}
+
+/// Commits whatever the child left uncommitted, so its branch has all of it.
+fn commit_leftovers(worktree: &Path, name: &str) {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(worktree)
+            .output()
+    };
+    let staged = git(&["add", "-A", "--", ".", ":(exclude).ralph"]);
+    if staged.is_ok_and(|o| o.status.success())
+        && git(&["diff", "--cached", "--quiet"]).is_ok_and(|o| !o.status.success())
+    {
+        let message = format!("Child loop {name}: uncommitted work");
+        if let Err(e) = git(&["commit", "--quiet", "--no-verify", "-m", &message]) {
+            tracing::warn!(error = %e, "Failed to commit child loop work");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain_and_json_requests() {
+        let request = SpawnRequest::parse("Implement OAuth login").unwrap();
+        assert_eq!(request.prompt, "Implement OAuth login");
+        assert_eq!(request.name, None);
+
+        let request = SpawnRequest::parse(
+            r#"{"name": "auth", "prompt": "Implement OAuth", "max_iterations": 5}"#,
+        )
+        .unwrap();
+        assert_eq!(request.name.as_deref(), Some("auth"));
+        assert_eq!(request.max_iterations, Some(5));
+
+        assert!(SpawnRequest::parse("  ").is_err());
+        assert!(SpawnRequest::parse(r#"{"name": "auth"}"#).is_err());
+    }
+
+    #[test]
+    fn test_budget_is_capped_by_limits() {
+        let limits = ChildLoopsConfig {
+            enabled: true,
+            max_iterations: 10,
+            max_cost_usd: Some(2.0),
+            max_runtime_seconds: 600,
+            ..ChildLoopsConfig::default()
+        };
+        let request = SpawnRequest {
+            prompt: "x".to_string(),
+            max_iterations: Some(50),
+            max_cost_usd: Some(1.0),
+            ..SpawnRequest::default()
+        };
+        assert_eq!(
+            request.budget(&limits),
+            Budget {
+                max_iterations: 10,
+                max_cost_usd: Some(1.0),
+                max_runtime_seconds: 600,
+            }
+        );
+    }
+
+    #[test]
+    fn test_child_config_applies_budget_to_parent_config() {
+        let mut parent = RalphConfig::default();
+        parent.event_loop.max_iterations = 100;
+        let request = SpawnRequest {
+            prompt: "x".to_string(),
+            ..SpawnRequest::default()
+        };
+        let budget = Budget {
+            max_iterations: 7,
+            max_cost_usd: Some(1.5),
+            max_runtime_seconds: 300,
+        };
+
+        let yaml = child_config(&request, &parent, Path::new("."), budget).unwrap();
+        let child: RalphConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(child.event_loop.max_iterations, 7);
+        assert_eq!(child.event_loop.max_cost_usd, Some(1.5));
+        assert_eq!(child.event_loop.max_runtime_seconds, 300);
+    }
+
+    #[test]
+    fn test_child_name_is_sanitized() {
+        let name = child_name(Some("Auth / OAuth"));
+        assert!(name.starts_with("auth---oauth-"), "{name}");
+        assert!(child_name(None).starts_with("child-"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stop_child_stops_its_process_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("grandchild.pid");
+        let mut command = tokio::process::Command::new("sh");
+        command.process_group(0);
+        let mut child = command
+            .arg("-c")
+            .arg(format!("sleep 30 & echo $! > {}; wait", pid_file.display()))
+            .spawn()
+            .unwrap();
+        while std::fs::read_to_string(&pid_file).map_or(true, |pid| pid.trim().is_empty()) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let grandchild: i32 = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+
+        stop_child(&mut child).await;
+
+        // The grandchild is reaped by init shortly after the group is signalled
+        let gone = (0..100).any(|_| {
+            std::thread::sleep(Duration::from_millis(20));
+            nix::sys::signal::kill(nix::unistd::Pid::from_raw(grandchild), None).is_err()
+        });
+        assert!(gone, "the child's process group survived");
+    }
+
+    #[tokio::test]
+    async fn test_disabled_child_loops_are_rejected() {
+        let request = SpawnRequest::parse("anything").unwrap();
+        let err = run_child_loop(
+            &request,
+            &RalphConfig::default(),
+            Path::new("."),
+            Path::new("ralph"),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ChildLoopError::Disabled));
+    }
+}
@@ -3,10 +3,14 @@
 //! This module supports both v1.x flat configuration format and v2.0 nested format.
 //! Users can switch from Python v1.x to Rust v2.0 with zero config changes.
 
-use ralph_proto::Topic;
+use crate::git_ops::CommitOptions;
+use crate::hat_predicate::{HatPredicate, PredicateError};
+use crate::verification::OutputParser;
+use ralph_proto::{Topic, TopicError};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::debug;
 
 /// Top-level configuration for Ralph Orchestrator.
@@ -34,6 +38,22 @@ pub struct RalphConfig {
     #[serde(default)]
     pub hats: HashMap<String, HatConfig>,
 
+    /// Built-in hat packs to enable (optional), e.g. `[maintenance]`.
+    /// Their hats, events, and skills are merged in by [`RalphConfig::normalize`];
+    /// anything defined here with the same name wins. See [`crate::packs`].
+    #[serde(default)]
+    pub packs: Vec<String>,
+
+    /// Legacy explicit mode (optional). The mode is derived from `hats`;
+    /// when set, it must agree with them. See [`RalphConfig::mode`].
+    #[serde(default)]
+    pub mode: Option<Mode>,
+
+    /// Workflow phases (optional), in order. Each phase enables a subset of
+    /// the hats and ends on its own completion criteria.
+    #[serde(default)]
+    pub phases: Vec<PhaseConfig>,
+
     /// Event metadata definitions (optional).
     /// Defines what each event topic means, enabling auto-derived instructions.
     /// If a hat uses custom events, define them here for proper behavior injection.
@@ -133,6 +153,150 @@ pub struct RalphConfig {
     /// RObot (Ralph-Orchestrator bot) configuration for Telegram-based interaction.
     #[serde(default, rename = "RObot")]
     pub robot: RobotConfig,
+
+    /// WASM plugins (context providers, event filters, native hats).
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>,
+
+    /// Rhai scripting hooks (event transformation, routing, termination).
+    #[serde(default)]
+    pub scripts: ScriptsConfig,
+
+    /// Browser dashboard served while a loop runs.
+    #[serde(default)]
+    pub dashboard: DashboardConfig,
+
+    /// Container environment for backend processes (hats may override).
+    #[serde(default)]
+    pub environment: Option<EnvironmentConfig>,
+
+    /// Shell commands run when a matching event is published.
+    ///
+    /// Keys are topic patterns (`build.done`, `review.*`, `*`); values are
+    /// commands run with `sh -c` in the workspace root, with the event as
+    /// JSON on stdin. Hooks run in the background and never block the loop.
+    ///
+    /// ```yaml
+    /// on_event:
+    ///   "build.done": ./scripts/notify.sh
+    ///   "loop.terminate": "curl -s -X POST -d @- https://example.com/hook"
+    /// ```
+    #[serde(default)]
+    pub on_event: HashMap<String, String>,
+
+    /// Mirrors of the event bus on external message brokers (NATS, Redis).
+    #[serde(default)]
+    pub bridges: Vec<BridgeConfig>,
+
+    /// Delivery delay per topic pattern, coalescing bursts of same-topic events.
+    ///
+    /// Events matching a pattern are held for the window and delivered as one
+    /// event whose payload joins the distinct payloads with newlines.
+    ///
+    /// ```yaml
+    /// debounce:
+    ///   "fs.changed": 5s
+    ///   "status.*": 500ms
+    /// ```
+    #[serde(default)]
+    pub debounce: BTreeMap<String, DurationSpec>,
+
+    /// Per-iteration backend/model routing rules, checked in order.
+    #[serde(default)]
+    pub routing: Vec<RouteRule>,
+
+    /// Speculative execution: run two backends per iteration and keep one.
+    #[serde(default)]
+    pub speculative: SpeculativeConfig,
+
+    /// Verification command whose result is published as `ci.*` events.
+    #[serde(default, deserialize_with = "deserialize_verify")]
+    pub verify: VerifyConfig,
+
+    /// Child orchestrations requested with `ralph.spawn_loop` events.
+    #[serde(default)]
+    pub child_loops: ChildLoopsConfig,
+
+    /// Interactive escalation: agents ask via `human.question`, the loop pauses for an answer.
+    #[serde(default)]
+    pub questions: QuestionsConfig,
+
+    /// Blocked escalation: a hat publishing `*.blocked` is paused until unblocked.
+    #[serde(default)]
+    pub blocked: BlockedConfig,
+
+    /// Summary of each iteration's output carried into the next prompt.
+    #[serde(default)]
+    pub carryover: CarryoverConfig,
+
+    /// Read-only survey iteration that plans the work before the main loop.
+    #[serde(default)]
+    pub survey: SurveyConfig,
+
+    /// Workspace paths the agent must not change, as globs.
+    ///
+    /// Enforced after every iteration: changes to matching paths (committed
+    /// or not) are reverted and a `protect.violation` event explains why.
+    ///
+    /// ```yaml
+    /// protect: [Cargo.lock, .github/**, migrations/**]
+    /// ```
+    #[serde(default)]
+    pub protect: Vec<String>,
+
+    /// Iteration limit that extends for productive runs and shrinks for stalled ones.
+    #[serde(default)]
+    pub adaptive_budget: AdaptiveBudgetConfig,
+
+    /// Where agent state (scratchpad, tasks, event journals) is persisted
+    /// between runs, for runners that start from a clean checkout.
+    #[serde(default)]
+    pub state_store: StateStoreConfig,
+
+    /// Trailers on checkpoint commits and a per-session changelog, so
+    /// downstream tooling can attribute Ralph's changes.
+    #[serde(default)]
+    pub attribution: AttributionConfig,
+
+    /// Message prefix, git identity, and signing for checkpoint commits.
+    #[serde(default)]
+    pub checkpoint: CheckpointConfig,
+
+    /// Embedding index over the workspace, searched with
+    /// `ralph tools search`.
+    #[serde(default)]
+    pub search_index: SearchIndexConfig,
+
+    /// Zip bundle of recent prompts, output, events, and changes written
+    /// when a loop ends on repeated failures or its runtime limit.
+    #[serde(default)]
+    pub forensics: ForensicsConfig,
+
+    /// Sanitizing, fencing, and optionally classifying event payloads before
+    /// they are injected into prompts.
+    #[serde(default)]
+    pub prompt_guard: PromptGuardConfig,
+
+    /// Locale of Ralph's prompt headers and sections.
+    #[serde(default)]
+    pub prompts: PromptsConfig,
+
+    /// Anthropic Messages API settings for `cli.backend: api`.
+    #[serde(default)]
+    pub api: ApiConfig,
+
+    /// Secrets fetched at startup and exported to backend processes, keyed by
+    /// environment variable name.
+    #[serde(default)]
+    pub credentials: HashMap<String, CredentialSource>,
+
+    /// Policy for shell commands agents run through `ralph tools run`.
+    #[serde(default)]
+    pub shell_tool: ShellToolConfig,
+
+    /// Encryption of session artifacts at rest.
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
 }
 
 fn default_true() -> bool {
@@ -147,6 +311,9 @@ impl Default for RalphConfig {
             cli: CliConfig::default(),
             core: CoreConfig::default(),
             hats: HashMap::new(),
+            packs: Vec::new(),
+            phases: Vec::new(),
+            mode: None,
             events: HashMap::new(),
             // V1 compatibility fields
             agent: None,
@@ -178,6 +345,115 @@ impl Default for RalphConfig {
             features: FeaturesConfig::default(),
             // RObot (Ralph-Orchestrator bot)
             robot: RobotConfig::default(),
+            // Plugins
+            plugins: vec![],
+            // Scripts
+            scripts: ScriptsConfig::default(),
+            // Dashboard
+            dashboard: DashboardConfig::default(),
+            // Execution environment
+            environment: None,
+            // Event hooks
+            on_event: HashMap::new(),
+            bridges: Vec::new(),
+            debounce: BTreeMap::new(),
+            // Routing
+            routing: vec![],
+            // Speculative execution
+            speculative: SpeculativeConfig::default(),
+            // Verification
+            verify: VerifyConfig::default(),
+            // Child loops
+            child_loops: ChildLoopsConfig::default(),
+            // Human questions
+            questions: QuestionsConfig::default(),
+            // Blocked escalation
+            blocked: BlockedConfig::default(),
+            // Iteration carry-over
+            carryover: CarryoverConfig::default(),
+            survey: SurveyConfig::default(),
+            // Protected paths
+            protect: vec![],
+            // Adaptive iteration budget
+            adaptive_budget: AdaptiveBudgetConfig::default(),
+            // Agent state persistence
+            state_store: StateStoreConfig::default(),
+            // Checkpoint attribution
+            attribution: AttributionConfig::default(),
+            checkpoint: CheckpointConfig::default(),
+            // Workspace search index
+            search_index: SearchIndexConfig::default(),
+            // Failure forensics bundle
+            forensics: ForensicsConfig::default(),
+            // Prompt-injection hardening
+            prompt_guard: PromptGuardConfig::default(),
+            // Prompt localization
+            prompts: PromptsConfig::default(),
+            // Messages API backend
+            api: ApiConfig::default(),
+            // Backend secrets
+            credentials: HashMap::new(),
+            // Guarded shell proxy
+            shell_tool: ShellToolConfig::default(),
+            // Artifact encryption
+            encryption: EncryptionConfig::default(),
+        }
+    }
+}
+
+/// A duration written with a unit (`500ms`, `5s`, `2m`, `1h`); a bare number
+/// is seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurationSpec(pub Duration);
+
+impl std::str::FromStr for DurationSpec {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let raw = raw.trim();
+        let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+        let (number, unit) = raw.split_at(split);
+        let value: u64 = number
+            .parse()
+            .map_err(|_| format!("'{raw}' is not a duration like 500ms, 5s, 2m, or 1h"))?;
+        let duration = match unit.trim() {
+            "ms" => Duration::from_millis(value),
+            "" | "s" => Duration::from_secs(value),
+            "m" => Duration::from_secs(value * 60),
+            "h" => Duration::from_secs(value * 3600),
+            other => {
+                return Err(format!(
+                    "unknown unit '{other}' in '{raw}'; use ms, s, m, or h"
+                ));
+            }
+        };
+        Ok(Self(duration))
+    }
+}
+
+impl Serialize for DurationSpec {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let millis = self.0.as_millis();
+        if millis.is_multiple_of(1000) {
+            serializer.serialize_str(&format!("{}s", millis / 1000))
+        } else {
+            serializer.serialize_str(&format!("{millis}ms"))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DurationSpec {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Seconds(u64),
+            Text(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Seconds(secs) => Ok(Self(Duration::from_secs(secs))),
+            Repr::Text(text) => text.parse().map_err(serde::de::Error::custom),
         }
     }
 }
@@ -204,6 +480,14 @@ pub struct AdaptersConfig {
     /// Amp adapter settings.
     #[serde(default)]
     pub amp: AdapterSettings,
+
+    /// Goose adapter settings.
+    #[serde(default)]
+    pub goose: AdapterSettings,
+
+    /// Messages API backend settings.
+    #[serde(default)]
+    pub api: AdapterSettings,
 }
 
 /// Per-adapter settings.
@@ -264,6 +548,23 @@ impl RalphConfig {
     pub fn normalize(&mut self) {
         let mut normalized_count = 0;
 
+        // Merge enabled packs; the config's own hats and events take precedence
+        for name in &self.packs {
+            let Some(pack) = crate::packs::find(name) else {
+                continue;
+            };
+            let contents = pack.contents();
+            for (id, hat) in contents.hats {
+                self.hats.entry(id).or_insert(hat);
+            }
+            for (topic, metadata) in contents.events {
+                self.events.entry(topic).or_insert(metadata);
+            }
+            debug!(pack = %name, "Merged hat pack");
+            normalized_count += 1;
+        }
+        self.skills.packs.clone_from(&self.packs);
+
         // Map v1 `agent` to v2 `cli.backend`
         if let Some(ref agent) = self.agent {
             debug!(from = "agent", to = "cli.backend", value = %agent, "Normalizing v1 field");
@@ -338,6 +639,27 @@ impl RalphConfig {
             }
         }
 
+        // Canonicalize topic spelling so `Build.Task` routes like `build.task`
+        let topics = self
+            .hats
+            .values_mut()
+            .flat_map(|hat| {
+                hat.triggers
+                    .iter_mut()
+                    .chain(hat.publishes.iter_mut())
+                    .chain(hat.default_publishes.iter_mut())
+            })
+            .chain(self.event_loop.starting_event.iter_mut())
+            .chain(std::iter::once(&mut self.event_loop.completion_promise));
+        for topic in topics {
+            let normalized = Topic::normalize(topic);
+            if normalized != *topic {
+                debug!(from = %topic, to = %normalized, "Normalizing topic");
+                *topic = normalized;
+                normalized_count += 1;
+            }
+        }
+
         if normalized_count > 0 {
             debug!(
                 fields_normalized = normalized_count,
@@ -346,6 +668,17 @@ impl RalphConfig {
         }
     }
 
+    /// Returns whether Ralph runs alone or coordinates hats.
+    ///
+    /// Derived from `hats`: no hats means single-hat mode.
+    pub fn mode(&self) -> Mode {
+        if self.hats.is_empty() {
+            Mode::Single
+        } else {
+            Mode::Multi
+        }
+    }
+
     /// Validates the configuration and returns warnings.
     ///
     /// This method checks for:
@@ -377,6 +710,30 @@ impl RalphConfig {
         if self.event_loop.completion_promise.trim().is_empty() {
             return Err(ConfigError::InvalidCompletionPromise);
         }
+        if let Some(name) = self
+            .packs
+            .iter()
+            .find(|name| crate::packs::find(name).is_none())
+        {
+            return Err(ConfigError::UnknownPack {
+                name: name.clone(),
+                available: crate::packs::names().join(", "),
+            });
+        }
+
+        // An explicit mode must agree with the hats; otherwise it's redundant
+        if let Some(mode) = self.mode {
+            if mode != self.mode() {
+                return Err(ConfigError::ModeMismatch {
+                    mode,
+                    hats: self.hats.len(),
+                });
+            }
+            warnings.push(ConfigWarning::DroppedField {
+                field: "mode".to_string(),
+                reason: "Mode is derived from 'hats'; remove this field".to_string(),
+            });
+        }
 
         // Check custom backend has a command
         if self.cli.backend == "custom" && self.cli.command.as_ref().is_none_or(String::is_empty) {
@@ -422,6 +779,13 @@ impl RalphConfig {
             });
         }
 
+        if self.search_index.chunk_lines == 0 {
+            warnings.push(ConfigWarning::InvalidValue {
+                field: "search_index.chunk_lines".to_string(),
+                message: "Value must be at least 1; using 1".to_string(),
+            });
+        }
+
         // Check adapter tool_permissions (dropped field)
         if self.adapters.claude.tool_permissions.is_some()
             || self.adapters.gemini.tool_permissions.is_some()
@@ -464,6 +828,27 @@ impl RalphConfig {
             }
         }
 
+        self.validate_topics(&mut warnings)?;
+        self.validate_renames(&mut warnings)?;
+        self.validate_phases(&mut warnings)?;
+        self.validate_plugins(&mut warnings)?;
+        self.validate_environments()?;
+        self.validate_hat_cli()?;
+        self.validate_mutex_groups()?;
+        self.validate_hat_predicates()?;
+        self.validate_routing(&mut warnings)?;
+        self.validate_speculative()?;
+        self.validate_state_store()?;
+        self.validate_bridges()?;
+        for pattern in self.debounce.keys() {
+            Topic::parse_pattern(pattern).map_err(|source| ConfigError::InvalidTopic {
+                field: "debounce".to_string(),
+                source,
+            })?;
+        }
+        self.checkpoint.validate()?;
+        self.validate_hat_budgets(&mut warnings);
+
         // Check for ambiguous routing: each trigger topic must map to exactly one hat
         // Per spec: "Every trigger maps to exactly one hat | No ambiguous routing"
         if !self.hats.is_empty() {
@@ -485,96 +870,732 @@ impl RalphConfig {
         Ok(warnings)
     }
 
-    /// Gets the effective backend name, resolving "auto" using the priority list.
-    pub fn effective_backend(&self) -> &str {
-        &self.cli.backend
+    /// Former topic names paired with their current names
+    /// (`events.<topic>.renamed_from`), sorted by old name.
+    pub fn topic_renames(&self) -> Vec<(&str, &str)> {
+        let mut renames: Vec<(&str, &str)> = self
+            .events
+            .iter()
+            .flat_map(|(topic, meta)| {
+                meta.renamed_from
+                    .iter()
+                    .map(move |old| (old.as_str(), topic.as_str()))
+            })
+            .collect();
+        renames.sort_unstable();
+        renames
     }
 
-    /// Returns the agent priority list for auto-detection.
-    /// If empty, returns the default priority order.
-    pub fn get_agent_priority(&self) -> Vec<&str> {
-        if self.agent_priority.is_empty() {
-            vec!["claude", "kiro", "gemini", "codex", "amp"]
-        } else {
-            self.agent_priority.iter().map(String::as_str).collect()
-        }
+    /// Former hat IDs paired with their current IDs (`hats.<id>.aliases`),
+    /// sorted by alias.
+    pub fn hat_aliases(&self) -> Vec<(&str, &str)> {
+        let mut aliases: Vec<(&str, &str)> = self
+            .hats
+            .iter()
+            .flat_map(|(id, hat)| hat.aliases.iter().map(move |a| (a.as_str(), id.as_str())))
+            .collect();
+        aliases.sort_unstable();
+        aliases
     }
 
-    /// Gets the adapter settings for a specific backend.
-    #[allow(clippy::match_same_arms)] // Explicit match arms for each backend improves readability
-    pub fn adapter_settings(&self, backend: &str) -> &AdapterSettings {
-        match backend {
-            "claude" => &self.adapters.claude,
-            "gemini" => &self.adapters.gemini,
-            "kiro" => &self.adapters.kiro,
-            "codex" => &self.adapters.codex,
-            "amp" => &self.adapters.amp,
-            _ => &self.adapters.claude, // Default fallback
+    /// Validates topic renames and hat aliases, and warns about deprecated
+    /// hats and hats still using a topic's old name.
+    fn validate_renames(&self, warnings: &mut Vec<ConfigWarning>) -> Result<(), ConfigError> {
+        let mut claimed: HashMap<&str, &str> = HashMap::new();
+        for (old, new) in self.topic_renames() {
+            let field = format!("events.{new}.renamed_from");
+            Topic::parse(old).map_err(|source| ConfigError::InvalidTopic {
+                field: field.clone(),
+                source,
+            })?;
+            if self.events.contains_key(old) {
+                return Err(ConfigError::InvalidRename {
+                    field,
+                    reason: format!("'{old}' is still configured under 'events'"),
+                });
+            }
+            if let Some(other) = claimed.insert(old, new) {
+                return Err(ConfigError::InvalidRename {
+                    field,
+                    reason: format!("'{old}' is already renamed to '{other}'"),
+                });
+            }
         }
-    }
-}
 
-/// Configuration warnings emitted during validation.
-#[derive(Debug, Clone)]
-pub enum ConfigWarning {
-    /// Feature is enabled but not yet available in v2.
-    DeferredFeature { field: String, message: String },
-    /// Field is present but ignored in v2.
-    DroppedField { field: String, reason: String },
-    /// Field has an invalid value.
-    InvalidValue { field: String, message: String },
-}
+        let mut hat_ids: Vec<&String> = self.hats.keys().collect();
+        hat_ids.sort();
+        for hat_id in &hat_ids {
+            let hat = &self.hats[*hat_id];
+            for (field, topics) in [("triggers", &hat.triggers), ("publishes", &hat.publishes)] {
+                for topic in topics {
+                    if let Some(new) = claimed.get(topic.as_str()) {
+                        warnings.push(ConfigWarning::InvalidValue {
+                            field: format!("hats.{hat_id}.{field}"),
+                            message: format!(
+                                "'{topic}' was renamed to '{new}'; events on it are routed as '{new}'"
+                            ),
+                        });
+                    }
+                }
+            }
+            if hat.deprecated {
+                warnings.push(ConfigWarning::DeprecatedHat {
+                    hat: (*hat_id).clone(),
+                });
+            }
+        }
 
-impl std::fmt::Display for ConfigWarning {
-    #[allow(clippy::match_same_arms)] // Different arms have different messages despite similar structure
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ConfigWarning::DeferredFeature { field, message }
-            | ConfigWarning::InvalidValue { field, message } => {
-                write!(f, "Warning [{field}]: {message}")
+        let mut aliased: HashMap<&str, &str> = HashMap::new();
+        for (alias, hat) in self.hat_aliases() {
+            let field = format!("hats.{hat}.aliases");
+            if alias == "ralph" || self.hats.contains_key(alias) {
+                return Err(ConfigError::InvalidRename {
+                    field,
+                    reason: format!("'{alias}' is the ID of a configured hat"),
+                });
             }
-            ConfigWarning::DroppedField { field, reason } => {
-                write!(f, "Warning [{field}]: Field ignored - {reason}")
+            if let Some(other) = aliased.insert(alias, hat) {
+                return Err(ConfigError::InvalidRename {
+                    field,
+                    reason: format!("'{alias}' is already an alias of '{other}'"),
+                });
             }
         }
+        Ok(())
     }
-}
 
-/// Event loop configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EventLoopConfig {
-    /// Inline prompt text (mutually exclusive with prompt_file).
-    pub prompt: Option<String>,
+    /// Validates `phases`: unique names, known hats, and a way to finish every
+    /// phase but the last.
+    fn validate_phases(&self, warnings: &mut Vec<ConfigWarning>) -> Result<(), ConfigError> {
+        let mut names: HashSet<&str> = HashSet::new();
+        for (index, phase) in self.phases.iter().enumerate() {
+            let invalid = |reason: String| ConfigError::InvalidPhase {
+                phase: phase.name.clone(),
+                reason,
+            };
+            if phase.name.trim().is_empty() {
+                return Err(invalid(format!("phase {} has no name", index + 1)));
+            }
+            if !names.insert(phase.name.as_str()) {
+                return Err(invalid("the name is used by another phase".to_string()));
+            }
+            if phase.hats.is_empty() {
+                return Err(invalid("no hats are enabled".to_string()));
+            }
+            if let Some(hat) = phase.hats.iter().find(|h| !self.hats.contains_key(*h)) {
+                return Err(invalid(format!("'{hat}' is not a configured hat")));
+            }
+            for topic in &phase.complete_on {
+                Topic::parse_pattern(topic).map_err(|source| ConfigError::InvalidTopic {
+                    field: format!("phases.{}.complete_on", phase.name),
+                    source,
+                })?;
+            }
 
-    /// Path to the prompt file.
-    #[serde(default = "default_prompt_file")]
-    pub prompt_file: String,
+            let last = index + 1 == self.phases.len();
+            if !last && phase.complete_on.is_empty() && phase.max_iterations.is_none() {
+                return Err(invalid(
+                    "set complete_on or max_iterations so the loop can move on".to_string(),
+                ));
+            }
+            if last && phase.max_iterations.is_some() {
+                warnings.push(ConfigWarning::InvalidValue {
+                    field: format!("phases.{}.max_iterations", phase.name),
+                    message: "The last phase has no phase to move on to; this is ignored"
+                        .to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
 
-    /// Event topic that signals loop completion (must be emitted via `ralph emit`).
-    #[serde(default = "default_completion_promise")]
-    pub completion_promise: String,
+    /// Validates topic spelling and flags near-miss topics across the topology.
+    ///
+    /// A topic that's published but never subscribed to (or subscribed to but
+    /// never published) is usually a typo when another topic is spelled
+    /// almost the same way.
+    fn validate_topics(&self, warnings: &mut Vec<ConfigWarning>) -> Result<(), ConfigError> {
+        let invalid = |field: String| move |source| ConfigError::InvalidTopic { field, source };
+        let mut hat_ids: Vec<&String> = self.hats.keys().collect();
+        hat_ids.sort();
+
+        // (field, topic) pairs, in a stable order for deterministic warnings
+        let mut published: Vec<(String, &str)> = Vec::new();
+        let mut subscribed: Vec<(String, Topic)> = Vec::new();
+        for hat_id in hat_ids {
+            let hat = &self.hats[hat_id];
+            for trigger in &hat.triggers {
+                let field = format!("hats.{hat_id}.triggers");
+                let pattern = Topic::parse_pattern(trigger).map_err(invalid(field.clone()))?;
+                subscribed.push((field, pattern));
+            }
+            for topic in hat.publishes.iter().chain(&hat.default_publishes) {
+                let field = format!("hats.{hat_id}.publishes");
+                Topic::parse(topic).map_err(invalid(field.clone()))?;
+                published.push((field, topic));
+            }
+            for step in &hat.postprocess {
+                if let Postprocessor::TableToEvents { topic: Some(topic) } = step {
+                    let field = format!("hats.{hat_id}.postprocess");
+                    Topic::parse(topic).map_err(invalid(field.clone()))?;
+                    published.push((field, topic));
+                }
+            }
+        }
+        if let Some(topic) = &self.event_loop.starting_event {
+            Topic::parse(topic).map_err(invalid("event_loop.starting_event".to_string()))?;
+            published.push(("event_loop.starting_event".to_string(), topic));
+        }
+        let completion = &self.event_loop.completion_promise;
+        Topic::parse(completion).map_err(invalid("event_loop.completion_promise".to_string()))?;
+
+        let mut reported = std::collections::HashSet::new();
+        let mut near_miss = |field: &str, topic: &str, candidates: &[&str]| {
+            let similar = candidates.iter().find(|c| is_near_miss(topic, c));
+            if let Some(similar) = similar
+                && reported.insert((
+                    topic.min(similar).to_string(),
+                    topic.max(similar).to_string(),
+                ))
+            {
+                warnings.push(ConfigWarning::NearMissTopic {
+                    field: field.to_string(),
+                    topic: topic.to_string(),
+                    similar: (*similar).to_string(),
+                });
+            }
+        };
 
-    /// Maximum number of iterations before timeout.
-    #[serde(default = "default_max_iterations")]
-    pub max_iterations: u32,
+        let concrete_triggers: Vec<&str> = subscribed
+            .iter()
+            .map(|(_, pattern)| pattern.as_str())
+            .filter(|pattern| !pattern.contains('*'))
+            .chain(std::iter::once(completion.as_str()))
+            .collect();
+        for (field, topic) in &published {
+            let consumed = *topic == completion
+                || subscribed
+                    .iter()
+                    .any(|(_, pattern)| pattern.matches_str(topic));
+            if !consumed {
+                near_miss(field, topic, &concrete_triggers);
+            }
+        }
+        let published_topics: Vec<&str> = published.iter().map(|(_, topic)| *topic).collect();
+        for (field, pattern) in &subscribed {
+            let fed = pattern.as_str().contains('*')
+                || published_topics
+                    .iter()
+                    .any(|topic| pattern.matches_str(topic));
+            if !fed {
+                near_miss(field, pattern.as_str(), &published_topics);
+            }
+        }
+        Ok(())
+    }
 
-    /// Maximum runtime in seconds.
-    #[serde(default = "default_max_runtime")]
-    pub max_runtime_seconds: u64,
+    /// Validates `plugins:` entries and hat references to them.
+    fn validate_plugins(&self, warnings: &mut Vec<ConfigWarning>) -> Result<(), ConfigError> {
+        let mut seen = std::collections::HashSet::new();
+        for plugin in &self.plugins {
+            if plugin.name.trim().is_empty() || !seen.insert(plugin.name.as_str()) {
+                return Err(ConfigError::InvalidPlugin {
+                    plugin: plugin.name.clone(),
+                    reason: "plugin names must be non-empty and unique".to_string(),
+                });
+            }
+        }
 
-    /// Maximum cost in USD before stopping.
-    pub max_cost_usd: Option<f64>,
+        for (hat_id, hat_config) in &self.hats {
+            let Some(ref name) = hat_config.plugin else {
+                continue;
+            };
+            let is_hat_plugin = self
+                .plugins
+                .iter()
+                .any(|p| &p.name == name && p.kind == PluginKind::Hat);
+            if !is_hat_plugin {
+                return Err(ConfigError::InvalidPlugin {
+                    plugin: name.clone(),
+                    reason: format!(
+                        "hat '{hat_id}' references it, but no plugin with kind 'hat' has that name"
+                    ),
+                });
+            }
+        }
 
-    /// Stop after this many consecutive failures.
-    #[serde(default = "default_max_failures")]
-    pub max_consecutive_failures: u32,
+        if !cfg!(feature = "scripting") && self.scripts.has_scripts() {
+            warnings.push(ConfigWarning::DeferredFeature {
+                field: "scripts".to_string(),
+                message: "This build of ralph does not include the scripting engine (`scripting` feature); scripts will not run".to_string(),
+            });
+        }
 
-    /// Delay in seconds before starting the next iteration.
-    /// Skipped when the next iteration is triggered by a human event.
-    #[serde(default)]
-    pub cooldown_delay_seconds: u64,
+        if !cfg!(feature = "wasm-plugins") && self.plugins.iter().any(|p| p.enabled) {
+            warnings.push(ConfigWarning::DeferredFeature {
+                field: "plugins".to_string(),
+                message: "This build of ralph does not include the WASM runtime (`wasm-plugins` feature); plugins will not be loaded".to_string(),
+            });
+        }
 
-    /// Starting hat for multi-hat mode (deprecated, use starting_event instead).
+        Ok(())
+    }
+
+    /// Validates the top-level and per-hat container environments, and the
+    /// sandbox, which replaces them.
+    fn validate_environments(&self) -> Result<(), ConfigError> {
+        if let Some(sandbox) = &self.cli.sandbox {
+            let environment = self.environment.as_ref().map(|_| "environment".to_string());
+            if let Some(field) = environment.or_else(|| {
+                self.hats
+                    .iter()
+                    .find(|(_, hat)| hat.environment.is_some())
+                    .map(|(id, _)| format!("hats.{id}.environment"))
+            }) {
+                return Err(ConfigError::MutuallyExclusive {
+                    field1: "cli.sandbox".to_string(),
+                    field2: field,
+                });
+            }
+            let invalid = |reason: &str| ConfigError::InvalidEnvironment {
+                field: "cli.sandbox".to_string(),
+                reason: reason.to_string(),
+            };
+            if sandbox.image.trim().is_empty() {
+                return Err(invalid("image must not be empty"));
+            }
+            if sandbox.network.trim().is_empty() {
+                return Err(invalid("network must not be empty"));
+            }
+            if let Some(mount) = sandbox.mounts.iter().find(|m| !m.contains(':')) {
+                return Err(invalid(&format!(
+                    "mount '{mount}' must be 'host_path:container_path[:options]'"
+                )));
+            }
+        }
+
+        let hat_envs = self.hats.iter().filter_map(|(id, hat)| {
+            Some((format!("hats.{id}.environment"), hat.environment.as_ref()?))
+        });
+        let top_level = self
+            .environment
+            .iter()
+            .map(|env| ("environment".to_string(), env));
+
+        for (field, env) in top_level.chain(hat_envs) {
+            if env.image.trim().is_empty() {
+                return Err(ConfigError::InvalidEnvironment {
+                    field,
+                    reason: "image must not be empty".to_string(),
+                });
+            }
+            if let Some(mount) = env.mounts.iter().find(|m| !m.contains(':')) {
+                return Err(ConfigError::InvalidEnvironment {
+                    field,
+                    reason: format!("mount '{mount}' must be 'host_path:container_path[:options]'"),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates hat `mutex_group` names, which become lock file names.
+    fn validate_mutex_groups(&self) -> Result<(), ConfigError> {
+        for (id, hat) in &self.hats {
+            if let Some(group) = &hat.mutex_group
+                && (group.is_empty()
+                    || !group
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            {
+                return Err(ConfigError::InvalidMutexGroup {
+                    hat: id.clone(),
+                    group: group.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates per-hat `cli:` blocks.
+    fn validate_hat_cli(&self) -> Result<(), ConfigError> {
+        for (id, hat) in &self.hats {
+            let Some(cli) = &hat.cli else {
+                continue;
+            };
+            if hat.backend.is_some() {
+                return Err(ConfigError::MutuallyExclusive {
+                    field1: format!("hats.{id}.backend"),
+                    field2: format!("hats.{id}.cli"),
+                });
+            }
+            if hat.generation.model.is_some() && cli.model.is_some() {
+                return Err(ConfigError::MutuallyExclusive {
+                    field1: format!("hats.{id}.model"),
+                    field2: format!("hats.{id}.cli.model"),
+                });
+            }
+            let invalid = |reason: &str| ConfigError::InvalidHatCli {
+                hat: id.clone(),
+                reason: reason.to_string(),
+            };
+            if let Some(mode) = &cli.prompt_mode
+                && mode != "arg"
+                && mode != "stdin"
+            {
+                return Err(invalid("prompt_mode must be 'arg' or 'stdin'"));
+            }
+            let merged = cli.apply(&self.cli);
+            if merged.backend == "custom" && merged.command.as_ref().is_none_or(String::is_empty) {
+                return Err(invalid("backend 'custom' requires a command"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates `when:` expressions on hats.
+    fn validate_hat_predicates(&self) -> Result<(), ConfigError> {
+        for (id, hat) in &self.hats {
+            if let Some(when) = &hat.when {
+                HatPredicate::parse(when).map_err(|source| ConfigError::InvalidHatPredicate {
+                    hat: id.clone(),
+                    source,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks routing predicates and warns about rules that can't take effect.
+    fn validate_routing(&self, warnings: &mut Vec<ConfigWarning>) -> Result<(), ConfigError> {
+        for (index, rule) in self.routing.iter().enumerate() {
+            if let Some(when) = &rule.when {
+                HatPredicate::parse(when)
+                    .map_err(|source| ConfigError::InvalidRoutePredicate { index, source })?;
+            }
+            if rule.backend.is_none() && rule.model.is_none() {
+                warnings.push(ConfigWarning::InvalidValue {
+                    field: format!("routing[{index}]"),
+                    message: "Rule sets neither backend nor model".to_string(),
+                });
+            }
+            for hat in &rule.hats {
+                if !self.hats.contains_key(hat) && hat != "ralph" {
+                    warnings.push(ConfigWarning::InvalidValue {
+                        field: format!("routing[{index}].hats"),
+                        message: format!("Unknown hat '{hat}'"),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that speculative execution has what its arbiter needs.
+    fn validate_speculative(&self) -> Result<(), ConfigError> {
+        let speculative = &self.speculative;
+        if !speculative.enabled {
+            return Ok(());
+        }
+        if speculative.backends.len() != 2 {
+            return Err(ConfigError::InvalidSpeculative {
+                reason: format!(
+                    "'backends' must list exactly two backends (found {})",
+                    speculative.backends.len()
+                ),
+            });
+        }
+        if speculative.arbiter == ArbiterKind::Check
+            && speculative
+                .check
+                .as_deref()
+                .is_none_or(|c| c.trim().is_empty())
+        {
+            return Err(ConfigError::InvalidSpeculative {
+                reason: "the 'check' arbiter needs a 'check' command".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that the state store backend has where to put state.
+    fn validate_state_store(&self) -> Result<(), ConfigError> {
+        let store = &self.state_store;
+        if store.backend == StateBackend::S3
+            && store.bucket.as_deref().is_none_or(|b| b.trim().is_empty())
+        {
+            return Err(ConfigError::InvalidStateStore {
+                reason: "the s3 backend needs a 'bucket'".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn validate_bridges(&self) -> Result<(), ConfigError> {
+        for (index, bridge) in self.bridges.iter().enumerate() {
+            let invalid = |reason: String| ConfigError::InvalidBridge { index, reason };
+            bridge.endpoint().map_err(invalid)?;
+            Topic::parse(&bridge.prefix).map_err(|e| invalid(format!("prefix: {e}")))?;
+            for pattern in bridge.publish.iter().chain(&bridge.subscribe) {
+                Topic::parse_pattern(pattern).map_err(|e| invalid(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Warns about hat budgets, windows, scout limits, and generation
+    /// settings that can't take effect.
+    fn validate_hat_budgets(&self, warnings: &mut Vec<ConfigWarning>) {
+        for (id, hat) in &self.hats {
+            for (index, window) in hat.windows.iter().enumerate() {
+                if window
+                    .until_seconds
+                    .is_some_and(|end| end <= window.from_seconds)
+                {
+                    warnings.push(ConfigWarning::InvalidValue {
+                        field: format!("hats.{id}.windows[{index}]"),
+                        message: "until_seconds must be greater than from_seconds; the window never opens".to_string(),
+                    });
+                }
+            }
+            if hat
+                .generation
+                .temperature
+                .is_some_and(|t| !(0.0..=2.0).contains(&t))
+            {
+                warnings.push(ConfigWarning::InvalidValue {
+                    field: format!("hats.{id}.temperature"),
+                    message: "Must be between 0.0 and 2.0".to_string(),
+                });
+            }
+            if hat.generation.max_output_tokens == Some(0) {
+                warnings.push(ConfigWarning::InvalidValue {
+                    field: format!("hats.{id}.max_output_tokens"),
+                    message: "Must be at least 1; the backend default is used".to_string(),
+                });
+            }
+            if hat.max_runtime_seconds == Some(0) {
+                warnings.push(ConfigWarning::InvalidValue {
+                    field: format!("hats.{id}.max_runtime_seconds"),
+                    message: "A zero budget exhausts the hat before it runs".to_string(),
+                });
+            }
+            if hat.scouts.as_ref().is_some_and(|s| s.max_parallel == 0) {
+                warnings.push(ConfigWarning::InvalidValue {
+                    field: format!("hats.{id}.scouts.max_parallel"),
+                    message: "Must be at least 1; scouts will run one at a time".to_string(),
+                });
+            }
+            let Some(share) = hat.max_cost_share else {
+                continue;
+            };
+            if !(share > 0.0 && share <= 1.0) {
+                warnings.push(ConfigWarning::InvalidValue {
+                    field: format!("hats.{id}.max_cost_share"),
+                    message: "Value must be greater than 0 and at most 1".to_string(),
+                });
+            } else if self.event_loop.max_cost_usd.is_none() {
+                warnings.push(ConfigWarning::InvalidValue {
+                    field: format!("hats.{id}.max_cost_share"),
+                    message: "Ignored without event_loop.max_cost_usd".to_string(),
+                });
+            }
+        }
+    }
+
+    /// Gets the effective backend name, resolving "auto" using the priority list.
+    pub fn effective_backend(&self) -> &str {
+        &self.cli.backend
+    }
+
+    /// Returns the agent priority list for auto-detection.
+    /// If empty, returns the default priority order.
+    pub fn get_agent_priority(&self) -> Vec<&str> {
+        if self.agent_priority.is_empty() {
+            vec!["claude", "kiro", "gemini", "codex", "amp"]
+        } else {
+            self.agent_priority.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// Time limit for one iteration on `backend`: `iteration_timeout_seconds`
+    /// when set, otherwise the backend's adapter `timeout`.
+    pub fn iteration_timeout(&self, backend: &str) -> Duration {
+        match self.event_loop.iteration_timeout_seconds {
+            0 => Duration::from_secs(self.adapter_settings(backend).timeout),
+            secs => Duration::from_secs(secs),
+        }
+    }
+
+    /// Gets the adapter settings for a specific backend.
+    #[allow(clippy::match_same_arms)] // Explicit match arms for each backend improves readability
+    pub fn adapter_settings(&self, backend: &str) -> &AdapterSettings {
+        match backend {
+            "claude" => &self.adapters.claude,
+            "gemini" => &self.adapters.gemini,
+            "kiro" => &self.adapters.kiro,
+            "codex" => &self.adapters.codex,
+            "amp" => &self.adapters.amp,
+            "goose" => &self.adapters.goose,
+            "api" => &self.adapters.api,
+            _ => &self.adapters.claude, // Default fallback
+        }
+    }
+}
+
+/// Whether Ralph runs alone or coordinates a team of hats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// No hats: Ralph handles every iteration itself.
+    #[serde(alias = "solo")]
+    Single,
+    /// Ralph delegates work to the configured hats via events.
+    Multi,
+}
+
+impl Mode {
+    /// Returns the config spelling of this mode.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Mode::Single => "single",
+            Mode::Multi => "multi",
+        }
+    }
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Configuration warnings emitted during validation.
+#[derive(Debug, Clone)]
+pub enum ConfigWarning {
+    /// Feature is enabled but not yet available in v2.
+    DeferredFeature { field: String, message: String },
+    /// Field is present but ignored in v2.
+    DroppedField { field: String, reason: String },
+    /// Field has an invalid value.
+    InvalidValue { field: String, message: String },
+    /// Topic nothing publishes or subscribes to, spelled almost like one that is.
+    NearMissTopic {
+        field: String,
+        topic: String,
+        similar: String,
+    },
+    /// Hat is marked `deprecated: true`.
+    DeprecatedHat { hat: String },
+}
+
+impl std::fmt::Display for ConfigWarning {
+    #[allow(clippy::match_same_arms)] // Different arms have different messages despite similar structure
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigWarning::DeferredFeature { field, message }
+            | ConfigWarning::InvalidValue { field, message } => {
+                write!(f, "Warning [{field}]: {message}")
+            }
+            ConfigWarning::DroppedField { field, reason } => {
+                write!(f, "Warning [{field}]: Field ignored - {reason}")
+            }
+            ConfigWarning::NearMissTopic {
+                field,
+                topic,
+                similar,
+            } => {
+                write!(
+                    f,
+                    "Warning [{field}]: '{topic}' is never matched - did you mean '{similar}'?"
+                )
+            }
+            ConfigWarning::DeprecatedHat { hat } => {
+                write!(
+                    f,
+                    "Warning [hats.{hat}]: Hat is deprecated - remove it once nothing targets it"
+                )
+            }
+        }
+    }
+}
+
+/// Event loop configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLoopConfig {
+    /// Inline prompt text (mutually exclusive with prompt_file).
+    pub prompt: Option<String>,
+
+    /// Path to the prompt file.
+    #[serde(default = "default_prompt_file")]
+    pub prompt_file: String,
+
+    /// Event topic that signals loop completion (must be emitted via `ralph emit`).
+    #[serde(default = "default_completion_promise")]
+    pub completion_promise: String,
+
+    /// Consecutive iterations that must emit the completion promise before
+    /// the loop stops, unless no tasks are pending.
+    ///
+    /// With the default of 1 the first completion event ends the loop. With
+    /// `completion_confirmation: 2`, a completion event while tasks remain
+    /// open is held back and the agent is asked to confirm it next iteration.
+    #[serde(default = "default_completion_confirmation")]
+    pub completion_confirmation: u32,
+
+    /// Maximum number of iterations before timeout.
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: u32,
+
+    /// Maximum runtime in seconds.
+    #[serde(default = "default_max_runtime")]
+    pub max_runtime_seconds: u64,
+
+    /// Seconds one iteration's backend may run before it is killed and the
+    /// iteration counts as failed (0 uses the backend's adapter `timeout`).
+    #[serde(default)]
+    pub iteration_timeout_seconds: u64,
+
+    /// Maximum cost in USD before stopping.
+    pub max_cost_usd: Option<f64>,
+
+    /// Stop after this many consecutive failures.
+    #[serde(default = "default_max_failures")]
+    pub max_consecutive_failures: u32,
+
+    /// Stop after Ralph repeats the same delegation this many times (0 disables).
+    ///
+    /// A coordination turn that publishes the same topic and payload as a
+    /// recent one counts as a repeat. Each repeat adds a warning to Ralph's
+    /// next prompt; reaching the limit ends the loop.
+    #[serde(default = "default_max_repeated_delegations")]
+    pub max_repeated_delegations: u32,
+
+    /// Warn Ralph off a hat's topics once this many events are queued for it
+    /// (0 disables).
+    ///
+    /// When a hat's pending queue is deeper than this, Ralph's prompt gains a
+    /// `QUEUE BACKPRESSURE` section telling it not to publish more of that hat's
+    /// trigger topics until the queue drains.
+    #[serde(default = "default_backpressure_threshold")]
+    pub backpressure_threshold: usize,
+
+    /// Run up to this many independent hats at once (1 runs one iteration at
+    /// a time).
+    ///
+    /// Hats with pending events are independent when neither publishes a
+    /// topic the other subscribes to and they aren't in the same
+    /// `mutex_group`. Each gets its own prompt and backend process in the
+    /// shared workspace; the batch counts as one iteration.
+    #[serde(default = "default_max_parallel_hats")]
+    pub max_parallel_hats: usize,
+
+    /// Delay in seconds before starting the next iteration.
+    /// Skipped when the next iteration is triggered by a human event.
+    #[serde(default)]
+    pub cooldown_delay_seconds: u64,
+
+    /// Starting hat for multi-hat mode (deprecated, use starting_event instead).
     pub starting_hat: Option<String>,
 
     /// Event to publish after Ralph completes initial coordination.
@@ -602,47 +1623,232 @@ pub struct EventLoopConfig {
     /// max_cost), consecutive failures, or explicit interrupt/stop.
     #[serde(default)]
     pub persistent: bool,
-}
 
-fn default_prompt_file() -> String {
-    "PROMPT.md".to_string()
-}
+    /// When true, a loop with no pending events parks instead of stopping.
+    ///
+    /// A parked loop runs no iterations. It polls the events file, which
+    /// `ralph emit`, the TUI, bridges, or any external writer can feed, and
+    /// wakes when new work arrives. Fallback recovery (`task.resume`) is
+    /// skipped while parking is enabled.
+    #[serde(default)]
+    pub park_when_idle: bool,
 
-fn default_completion_promise() -> String {
-    "LOOP_COMPLETE".to_string()
-}
+    /// Run a canned prompt through the backend before the loop starts.
+    ///
+    /// Checks auth, output capture, and event parsing in one round trip, and
+    /// aborts the run with a diagnosis when it fails.
+    #[serde(default)]
+    pub self_test: bool,
 
-fn default_max_iterations() -> u32 {
-    100
+    /// Seconds a parked loop waits for new events before stopping (0 waits
+    /// indefinitely). Only used with `park_when_idle`.
+    #[serde(default)]
+    pub park_timeout_seconds: u64,
+
+    /// Archive consumed events once this many megabytes have been read from
+    /// the events file (0 disables compaction).
+    ///
+    /// Consumed events move to `.ralph/events/archive-N.jsonl` and the events
+    /// file keeps only what hasn't been read yet.
+    #[serde(default = "default_compact_events_mb")]
+    pub compact_events_mb: u64,
+
+    /// Extra formats recognized when parsing events from agent output.
+    ///
+    /// `<event topic="...">` tags are always recognized. Agents often wrap
+    /// events in a markdown fence or print them as JSON instead, and those
+    /// handoffs are dropped unless the format is listed here.
+    ///
+    /// ```yaml
+    /// event_loop:
+    ///   event_formats: [fenced, json]
+    /// ```
+    #[serde(default)]
+    pub event_formats: Vec<EventFormat>,
+
+    /// Syntax agents are told to use for events written in their output.
+    ///
+    /// Prompts show this syntax as the fallback to `ralph emit`, and the
+    /// parser recognizes it. Use `macro` or `json` with backends that mangle
+    /// angle brackets.
+    ///
+    /// ```yaml
+    /// event_loop:
+    ///   event_syntax: macro   # @@event(build.done) tests: pass
+    /// ```
+    #[serde(default)]
+    pub event_syntax: EventSyntax,
+
+    /// Write every event candidate the parser saw in each iteration's output,
+    /// and why skipped ones were ignored, to
+    /// `.ralph/agent/sessions/<loop>/iter-<n>.events.json`.
+    ///
+    /// For diagnosing events the agent wrote that never reached the bus;
+    /// `ralph run --trace-events` turns it on for one run.
+    #[serde(default)]
+    pub trace_events: bool,
+
+    /// Event to seed the bus with instead of `starting_event`, set at
+    /// runtime by `ralph run --start-event`.
+    #[serde(skip)]
+    pub start_event: Option<StartEvent>,
+
+    /// Previous session whose summary and unfinished tasks go in the first
+    /// prompt, set at runtime by `ralph run --bootstrap-from`.
+    #[serde(skip)]
+    pub bootstrap_from: Option<crate::bootstrap::BootstrapSource>,
 }
 
-fn default_max_runtime() -> u64 {
-    14400 // 4 hours
+/// An explicit first event, written `topic[:payload]`.
+///
+/// Without a payload the event carries the prompt, like `starting_event`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartEvent {
+    pub topic: String,
+    pub payload: Option<String>,
 }
 
-fn default_max_failures() -> u32 {
-    5
+impl std::str::FromStr for StartEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (topic, payload) = match s.split_once(':') {
+            Some((topic, payload)) => (topic, Some(payload.to_string())),
+            None => (s, None),
+        };
+        if topic.is_empty() || topic.contains(char::is_whitespace) || topic.contains('*') {
+            return Err(format!(
+                "expected `topic[:payload]` with a concrete topic like `review.request`, got '{s}'"
+            ));
+        }
+        Ok(Self {
+            topic: topic.to_string(),
+            payload,
+        })
+    }
 }
 
-impl Default for EventLoopConfig {
+/// Syntax for events written in agent output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventSyntax {
+    /// `<event topic="build.done">payload</event>`
+    #[default]
+    Xml,
+    /// `@@event(build.done) payload` on a line of its own, or
+    /// `@@event(build.done, reviewer)` to target a hat.
+    Macro,
+    /// `{"topic": "build.done", "payload": "..."}` on a line of its own.
+    Json,
+}
+
+impl EventSyntax {
+    /// Renders an event in this syntax, for use in prompts.
+    pub fn example(self, topic: &str, payload: &str) -> String {
+        match self {
+            Self::Xml => format!("<event topic=\"{topic}\">{payload}</event>"),
+            Self::Macro => format!("@@event({topic}) {payload}"),
+            Self::Json => format!(
+                r#"{{"topic": {}, "payload": {}}}"#,
+                serde_json::Value::from(topic),
+                serde_json::Value::from(payload)
+            ),
+        }
+    }
+}
+
+/// Event format recognized in agent output in addition to `<event>` tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventFormat {
+    /// A ```` ```event ```` fenced block: the topic on the first line and the
+    /// payload below it, or a JSON object like the `json` format.
+    Fenced,
+    /// A line holding a JSON object with a `topic` (and optional `payload`
+    /// and `target`), as written to `.ralph/events.jsonl`.
+    Json,
+}
+
+fn default_prompt_file() -> String {
+    "PROMPT.md".to_string()
+}
+
+fn default_completion_promise() -> String {
+    "LOOP_COMPLETE".to_string()
+}
+
+fn default_completion_confirmation() -> u32 {
+    1
+}
+
+fn default_max_iterations() -> u32 {
+    100
+}
+
+fn default_max_runtime() -> u64 {
+    14400 // 4 hours
+}
+
+fn default_max_failures() -> u32 {
+    5
+}
+
+fn default_max_repeated_delegations() -> u32 {
+    3
+}
+
+fn default_backpressure_threshold() -> usize {
+    5
+}
+
+fn default_max_parallel_hats() -> usize {
+    1
+}
+
+fn default_compact_events_mb() -> u64 {
+    32
+}
+
+impl Default for EventLoopConfig {
     fn default() -> Self {
         Self {
             prompt: None,
             prompt_file: default_prompt_file(),
             completion_promise: default_completion_promise(),
+            completion_confirmation: default_completion_confirmation(),
             max_iterations: default_max_iterations(),
             max_runtime_seconds: default_max_runtime(),
+            iteration_timeout_seconds: 0,
             max_cost_usd: None,
             max_consecutive_failures: default_max_failures(),
+            max_repeated_delegations: default_max_repeated_delegations(),
+            backpressure_threshold: default_backpressure_threshold(),
+            max_parallel_hats: default_max_parallel_hats(),
             cooldown_delay_seconds: 0,
             starting_hat: None,
             starting_event: None,
             mutation_score_warn_threshold: None,
             persistent: false,
+            park_when_idle: false,
+            self_test: false,
+            park_timeout_seconds: 0,
+            compact_events_mb: default_compact_events_mb(),
+            event_formats: Vec::new(),
+            event_syntax: EventSyntax::default(),
+            trace_events: false,
+            start_event: None,
+            bootstrap_from: None,
         }
     }
 }
 
+impl EventLoopConfig {
+    /// Returns the compaction threshold in bytes (0 when disabled).
+    pub fn compact_events_bytes(&self) -> u64 {
+        self.compact_events_mb.saturating_mul(1024 * 1024)
+    }
+}
+
 /// Core paths and settings shared across all hats.
 ///
 /// Per spec: "Core behaviors (always injected, can customize paths)"
@@ -763,6 +1969,240 @@ pub struct CliConfig {
     /// If None, defaults to "-p" for arg mode.
     #[serde(default)]
     pub prompt_flag: Option<String>,
+
+    /// Resource limits applied to the backend process and everything it spawns.
+    #[serde(default)]
+    pub limits: ResourceLimits,
+
+    /// Retries for failures that look transient (rate limits, overload).
+    #[serde(default)]
+    pub retry: RetryPolicy,
+
+    /// Lay prompts out for prompt caching on the Claude backend.
+    ///
+    /// Sections that don't change between iterations (identity, guardrails,
+    /// objective, workflow, hat topology) come first and iteration context
+    /// (scratchpad, tasks, pending events) after them, so consecutive
+    /// iterations share a long cacheable prefix. Ignored for other backends.
+    #[serde(default)]
+    pub prompt_caching: bool,
+
+    /// Run the backend in a locked-down container.
+    #[serde(default)]
+    pub sandbox: Option<SandboxConfig>,
+}
+
+/// Sandboxed execution of backend processes.
+///
+/// Like [`EnvironmentConfig`], the backend runs inside `image` with the
+/// workspace mounted at the same path, but the container is locked down for
+/// agents that aren't trusted with the host: all capabilities are dropped,
+/// privilege escalation is disabled, the process runs as the workspace's
+/// owner, and networking follows `network`. Besides the workspace, only the
+/// main repository's `.git` and `.ralph/agent` directories (for loops in
+/// worktrees) and the listed `mounts` are visible. Git metadata is mounted
+/// read-only, so the agent can't commit; its changes are committed on the
+/// host when the loop lands or merges.
+///
+/// Example configuration:
+/// ```yaml
+/// cli:
+///   backend: claude
+///   sandbox:
+///     image: ghcr.io/acme/agent:latest
+///     network: bridge
+///     env: [ANTHROPIC_API_KEY]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    /// Container image to run the backend in; must contain the backend CLI.
+    pub image: String,
+
+    /// Container runtime CLI ("docker" or "podman").
+    #[serde(default = "default_container_runtime")]
+    pub runtime: String,
+
+    /// Network the container joins: "bridge" (the runtime's default, needed
+    /// to reach a hosted model API), "none", or a named network.
+    #[serde(default = "default_sandbox_network")]
+    pub network: String,
+
+    /// Extra bind mounts in `host:container[:options]` form (`~` expands to `$HOME`).
+    #[serde(default)]
+    pub mounts: Vec<String>,
+
+    /// Host environment variables forwarded into the container.
+    #[serde(default)]
+    pub env: Vec<String>,
+
+    /// Extra arguments passed to `<runtime> run` before the image.
+    #[serde(default)]
+    pub run_args: Vec<String>,
+}
+
+fn default_sandbox_network() -> String {
+    "bridge".to_string()
+}
+
+impl SandboxConfig {
+    /// Resolves the sandbox into the container environment a backend runs
+    /// in, for a loop in `workspace` of the repository at `repo_root`.
+    pub fn environment(&self, workspace: &Path, repo_root: &Path) -> EnvironmentConfig {
+        // Git metadata is read-only: a writable .git lets the agent plant
+        // hooks or config that the loop's own git calls on the host would run.
+        let mut mounts = Vec::new();
+        let git = workspace.join(".git");
+        mounts.push(format!("{0}:{0}:ro", git.display()));
+        if workspace != repo_root {
+            // A worktree's git metadata and shared agent state live in the main repo
+            let git = repo_root.join(".git");
+            mounts.push(format!("{0}:{0}:ro", git.display()));
+            let agent = repo_root.join(".ralph").join("agent");
+            mounts.push(format!("{0}:{0}", agent.display()));
+        }
+        mounts.extend(self.mounts.iter().cloned());
+
+        let mut run_args = vec![
+            format!("--network={}", self.network),
+            "--cap-drop=ALL".to_string(),
+            "--security-opt=no-new-privileges".to_string(),
+        ];
+        #[cfg(unix)]
+        if let Ok(metadata) = std::fs::metadata(workspace) {
+            use std::os::unix::fs::MetadataExt;
+            run_args.push(format!("--user={}:{}", metadata.uid(), metadata.gid()));
+        }
+        run_args.extend(self.run_args.iter().cloned());
+
+        EnvironmentConfig {
+            image: self.image.clone(),
+            mounts,
+            env: self.env.clone(),
+            runtime: self.runtime.clone(),
+            run_args,
+        }
+    }
+}
+
+/// Resource limits for backend child processes.
+///
+/// Applied as POSIX rlimits when the backend is spawned, so a runaway build or
+/// test started by the agent hits the limit instead of exhausting the host.
+/// Limits are per process and inherited by children. Unset fields are left at
+/// the host's defaults. Ignored on non-Unix platforms.
+///
+/// Example configuration:
+/// ```yaml
+/// cli:
+///   backend: claude
+///   limits:
+///     cpu_seconds: 3600
+///     memory_mb: 8192
+///     max_open_files: 4096
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// CPU time limit in seconds (`RLIMIT_CPU`).
+    #[serde(default)]
+    pub cpu_seconds: Option<u64>,
+
+    /// Address space limit in megabytes (`RLIMIT_AS`).
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+
+    /// Maximum number of open file descriptors (`RLIMIT_NOFILE`).
+    #[serde(default)]
+    pub max_open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Returns true if no limit is set.
+    pub fn is_empty(&self) -> bool {
+        self.cpu_seconds.is_none() && self.memory_mb.is_none() && self.max_open_files.is_none()
+    }
+}
+
+/// Retry policy for transient backend failures.
+///
+/// A run that exits non-zero is retried when its exit code is listed in
+/// `retry_on_exit_codes` or a line of its stderr contains one of
+/// `retry_on_stderr` (case-insensitive). The delay before retry `n` is
+/// `backoff_seconds * 2^(n-1)`. Only the final attempt is reported to the
+/// event loop, so a retried rate limit doesn't count as a failed iteration.
+///
+/// Example configuration:
+/// ```yaml
+/// cli:
+///   backend: claude
+///   retry:
+///     max_retries: 3
+///     backoff_seconds: 10
+///     retry_on_exit_codes: [75]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 disables retrying).
+    #[serde(default)]
+    pub max_retries: u32,
+
+    /// Delay before the first retry; doubled for each one after it.
+    #[serde(default = "default_retry_backoff")]
+    pub backoff_seconds: u64,
+
+    /// Exit codes that mark a failure as transient.
+    #[serde(default)]
+    pub retry_on_exit_codes: Vec<i32>,
+
+    /// Stderr substrings that mark a failure as transient.
+    #[serde(default = "default_retry_on_stderr")]
+    pub retry_on_stderr: Vec<String>,
+}
+
+fn default_retry_backoff() -> u64 {
+    5
+}
+
+fn default_retry_on_stderr() -> Vec<String> {
+    [
+        "overloaded",
+        "rate limit",
+        "rate_limit",
+        "too many requests",
+        "service unavailable",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            backoff_seconds: default_retry_backoff(),
+            retry_on_exit_codes: vec![],
+            retry_on_stderr: default_retry_on_stderr(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether a failed run with `exit_code` and `stderr` is worth retrying.
+    pub fn is_transient(&self, exit_code: Option<i32>, stderr: &str) -> bool {
+        if exit_code.is_some_and(|code| self.retry_on_exit_codes.contains(&code)) {
+            return true;
+        }
+        let stderr = stderr.to_lowercase();
+        self.retry_on_stderr
+            .iter()
+            .any(|pattern| stderr.contains(&pattern.to_lowercase()))
+    }
+
+    /// Delay before retry number `retry` (1-based).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u64.saturating_pow(retry.saturating_sub(1));
+        Duration::from_secs(self.backoff_seconds.saturating_mul(factor))
+    }
 }
 
 fn default_backend() -> String {
@@ -791,10 +2231,21 @@ impl Default for CliConfig {
             idle_timeout_secs: default_idle_timeout(),
             args: Vec::new(),
             prompt_flag: None,
+            limits: ResourceLimits::default(),
+            retry: RetryPolicy::default(),
+            prompt_caching: false,
+            sandbox: None,
         }
     }
 }
 
+impl CliConfig {
+    /// Returns true when prompts should use the cache-friendly layout.
+    pub fn cache_friendly_prompts(&self) -> bool {
+        self.prompt_caching && self.backend == "claude"
+    }
+}
+
 /// TUI configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TuiConfig {
@@ -933,12 +2384,17 @@ impl Default for TasksConfig {
 /// present in every prompt, and the agent loads full skill content on demand
 /// via `ralph tools skill load <name>`.
 ///
+/// Skills can also be installed from a shared index with
+/// `ralph tools skill install`; installed skills live in `.agent/skills/` and
+/// are pinned in `.agent/skills.lock`.
+///
 /// Example configuration:
 /// ```yaml
 /// skills:
 ///   enabled: true
 ///   dirs:
 ///     - ".claude/skills"
+///   index: https://skills.example.com/index.json
 ///   overrides:
 ///     pdd:
 ///       enabled: false
@@ -960,6 +2416,16 @@ pub struct SkillsConfig {
     /// Per-skill overrides keyed by skill name.
     #[serde(default)]
     pub overrides: HashMap<String, SkillOverride>,
+
+    /// Remote skill index for `ralph tools skill search/install`: an HTTP(S)
+    /// URL serving JSON, or a git repository URL or path.
+    #[serde(default)]
+    pub index: Option<String>,
+
+    /// Packs whose skills to register; copied from the top-level `packs`
+    /// by [`RalphConfig::normalize`].
+    #[serde(skip)]
+    pub packs: Vec<String>,
 }
 
 impl Default for SkillsConfig {
@@ -968,6 +2434,8 @@ impl Default for SkillsConfig {
             enabled: true, // Skills enabled by default
             dirs: vec![],
             overrides: HashMap::new(),
+            index: None,
+            packs: vec![],
         }
     }
 }
@@ -1137,6 +2605,7 @@ impl TuiConfig {
 ///     description: "Deployment has been requested"
 ///     on_trigger: "Prepare artifacts, validate config, check dependencies"
 ///     on_publish: "Signal that deployment should begin"
+///     fields: [environment, version]
 /// ```
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EventMetadata {
@@ -1153,6 +2622,21 @@ pub struct EventMetadata {
     /// Describes when/how the hat should emit this event.
     #[serde(default)]
     pub on_publish: String,
+
+    /// Payload fields a publisher must include (the handoff contract).
+    ///
+    /// Rendered into the publishing hat's prompt; events missing a field are
+    /// replaced by `contract.violation`. See [`crate::contract`].
+    #[serde(default)]
+    pub fields: Vec<String>,
+
+    /// Former names of this topic.
+    ///
+    /// Events published under an old name, whether from an old events file,
+    /// a recorded fixture, or an agent that hasn't caught up, are routed as
+    /// this topic.
+    #[serde(default)]
+    pub renamed_from: Vec<String>,
 }
 
 /// Backend configuration for a hat.
@@ -1195,6 +2679,85 @@ impl HatBackend {
             HatBackend::Custom { .. } => "custom".to_string(),
         }
     }
+
+    /// Returns the name used to look up adapter settings such as timeouts.
+    ///
+    /// Custom backends use the command's file name, so `/usr/bin/codex` maps
+    /// to `codex` and `ollama run llama3` to `ollama`.
+    pub fn adapter_name(&self) -> String {
+        match self {
+            HatBackend::Custom { command, .. } => {
+                let base_command = command.split_whitespace().next().unwrap_or(command);
+                Path::new(base_command)
+                    .file_name()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("custom")
+                    .to_string()
+            }
+            _ => self.to_cli_backend(),
+        }
+    }
+}
+
+/// A hat's own `cli:` block, laid over the top-level [`CliConfig`].
+///
+/// Unset fields inherit from the top-level `cli:`. Changing `backend` drops
+/// the inherited `command` and `args`, which belong to the other backend.
+/// ```yaml
+/// cli:
+///   backend: claude
+/// hats:
+///   planner:
+///     triggers: ["plan.request"]
+///     cli:
+///       model: claude-haiku-4-5
+///   builder:
+///     triggers: ["build.task"]
+///     cli:
+///       backend: codex
+///       command: /opt/codex/bin/codex
+///       model: gpt-5-codex
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HatCliConfig {
+    /// Backend for this hat; same values as `cli.backend`.
+    #[serde(default)]
+    pub backend: Option<String>,
+
+    /// Command override, required when `backend` is "custom".
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// How to pass prompts: "arg" or "stdin".
+    #[serde(default)]
+    pub prompt_mode: Option<String>,
+
+    /// Model to run, overriding the backend's default. A matching
+    /// `routing:` rule's model takes precedence.
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl HatCliConfig {
+    /// Returns `global` with this block's settings applied.
+    pub fn apply(&self, global: &CliConfig) -> CliConfig {
+        let mut cli = global.clone();
+        if let Some(backend) = &self.backend
+            && *backend != global.backend
+        {
+            cli.backend.clone_from(backend);
+            cli.command = None;
+            cli.args.clear();
+            cli.prompt_flag = None;
+        }
+        if let Some(command) = &self.command {
+            cli.command = Some(command.clone());
+        }
+        if let Some(prompt_mode) = &self.prompt_mode {
+            cli.prompt_mode.clone_from(prompt_mode);
+        }
+        cli
+    }
 }
 
 /// Configuration for a single hat.
@@ -1243,6 +2806,31 @@ pub struct HatConfig {
     #[serde(default)]
     pub backend: Option<HatBackend>,
 
+    /// This hat's own `cli:` settings, over the top-level `cli:`.
+    ///
+    /// Lets a cheap model plan while a stronger one implements. Can't be
+    /// combined with `backend:`; see [`HatCliConfig`].
+    #[serde(default)]
+    pub cli: Option<HatCliConfig>,
+
+    /// Model and sampling settings for this hat's backend.
+    ///
+    /// Written inline on the hat; see [`GenerationConfig`] for which
+    /// backends accept each setting.
+    /// ```yaml
+    /// hats:
+    ///   planner:
+    ///     triggers: ["plan.request"]
+    ///     reasoning_effort: high
+    ///   formatter:
+    ///     triggers: ["build.done"]
+    ///     model: claude-haiku-4-5
+    ///     temperature: 0.0
+    ///     max_output_tokens: 2048
+    /// ```
+    #[serde(flatten)]
+    pub generation: GenerationConfig,
+
     /// Default event to publish if hat forgets to write an event.
     #[serde(default)]
     pub default_publishes: Option<String>,
@@ -1252,1356 +2840,4426 @@ pub struct HatConfig {
     /// When the limit is exceeded, the orchestrator publishes `<hat_id>.exhausted`
     /// instead of activating the hat again.
     pub max_activations: Option<u32>,
-}
 
-impl HatConfig {
-    /// Converts trigger strings to Topic objects.
-    pub fn trigger_topics(&self) -> Vec<Topic> {
-        self.triggers.iter().map(|s| Topic::new(s)).collect()
-    }
+    /// Name of a `kind: hat` plugin that handles this hat's events natively.
+    ///
+    /// Plugin hats never reach the LLM: their pending events are passed to the
+    /// plugin and the events it returns are published on the bus.
+    #[serde(default)]
+    pub plugin: Option<String>,
 
-    /// Converts publish strings to Topic objects.
-    pub fn publish_topics(&self) -> Vec<Topic> {
-        self.publishes.iter().map(|s| Topic::new(s)).collect()
-    }
-}
+    /// Container environment for this hat's backend (overrides the top-level `environment`).
+    #[serde(default)]
+    pub environment: Option<EnvironmentConfig>,
 
-/// RObot (Ralph-Orchestrator bot) configuration.
-///
-/// Enables bidirectional communication between AI agents and humans
-/// during orchestration loops. When enabled, agents can emit `human.interact`
-/// events to request clarification (blocking the loop), and humans can
-/// send proactive guidance via Telegram.
-///
-/// Example configuration:
-/// ```yaml
-/// RObot:
-///   enabled: true
-///   timeout_seconds: 300
-///   checkin_interval_seconds: 120  # Optional: send status every 2 min
-///   telegram:
-///     bot_token: "..."  # Or set RALPH_TELEGRAM_BOT_TOKEN env var
-/// ```
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-pub struct RobotConfig {
-    /// Whether the RObot is enabled.
+    /// Activation predicate checked against loop state and the triggering event.
+    ///
+    /// The hat is only activated when the expression holds; otherwise the event
+    /// reaches Ralph without this hat's instructions. See [`HatPredicate`] for
+    /// the syntax.
+    /// ```yaml
+    /// hats:
+    ///   frontend_reviewer:
+    ///     triggers: ["review.request"]
+    ///     when: "iteration > 5 and payload contains 'frontend'"
+    /// ```
     #[serde(default)]
-    pub enabled: bool,
+    pub when: Option<String>,
 
-    /// Timeout in seconds for waiting on human responses.
-    /// Required when enabled (no default — must be explicit).
-    pub timeout_seconds: Option<u64>,
+    /// Largest fraction of `event_loop.max_cost_usd` this hat may spend (0.0–1.0).
+    ///
+    /// Once the hat's attributed spend reaches the cap, it is treated as
+    /// exhausted: `<hat_id>.exhausted` is published instead of activating it.
+    /// ```yaml
+    /// event_loop:
+    ///   max_cost_usd: 10.0
+    /// hats:
+    ///   reviewer:
+    ///     triggers: ["review.request"]
+    ///     max_cost_share: 0.2
+    /// ```
+    #[serde(default)]
+    pub max_cost_share: Option<f64>,
 
-    /// Interval in seconds between periodic check-in messages sent via Telegram.
-    /// When set, Ralph sends a status message every N seconds so the human
-    /// knows it's still working. If `None`, no check-ins are sent.
-    pub checkin_interval_seconds: Option<u64>,
+    /// Total seconds this hat may spend executing over the whole loop run.
+    ///
+    /// Once used up, the hat is exhausted the same way as with
+    /// `max_activations`.
+    #[serde(default)]
+    pub max_runtime_seconds: Option<u64>,
 
-    /// Telegram bot configuration.
+    /// Periods of the run, measured from loop start, when this hat may run.
+    ///
+    /// Outside every window the hat's pending events are dropped and
+    /// `<hat_id>.unavailable` is published instead. Empty means always.
+    /// ```yaml
+    /// hats:
+    ///   dependency_updater:
+    ///     triggers: ["deps.check"]
+    ///     windows:
+    ///       - until_seconds: 3600   # first hour only
+    ///     max_runtime_seconds: 900
+    /// ```
     #[serde(default)]
-    pub telegram: Option<TelegramBotConfig>,
-}
+    pub windows: Vec<HatWindow>,
 
-impl RobotConfig {
-    /// Validates the RObot config. Returns an error if enabled but misconfigured.
-    pub fn validate(&self) -> Result<(), ConfigError> {
-        if !self.enabled {
-            return Ok(());
-        }
+    /// Reuse the backend's response when this hat sends an identical prompt.
+    ///
+    /// Responses are stored by prompt hash under `.ralph/cache/responses/`,
+    /// so replays, dry-runs, and re-runs of the same planning prompt don't
+    /// pay twice. Only successful responses are cached. Iterations of a
+    /// caching hat run headless, without the PTY.
+    /// ```yaml
+    /// hats:
+    ///   planner:
+    ///     triggers: ["task.start"]
+    ///     cache_responses: true
+    /// ```
+    #[serde(default)]
+    pub cache_responses: bool,
 
-        if self.timeout_seconds.is_none() {
-            return Err(ConfigError::RobotMissingField {
-                field: "RObot.timeout_seconds".to_string(),
-                hint: "timeout_seconds is required when RObot is enabled".to_string(),
-            });
-        }
+    /// Read-only scouting prompts fanned out in parallel before each of this
+    /// hat's iterations; their answers are injected into the prompt.
+    /// ```yaml
+    /// hats:
+    ///   builder:
+    ///     triggers: ["build.task"]
+    ///     scouts:
+    ///       prompts:
+    ///         - "Summarize crates/ralph-core/src/event_loop"
+    ///         - "List the public API of crates/ralph-adapters"
+    ///       max_parallel: 2
+    ///       backend: { type: kiro, agent: scout }
+    /// ```
+    #[serde(default)]
+    pub scouts: Option<ScoutsConfig>,
 
-        // Bot token must be available from config, keychain, or env var
-        if self.resolve_bot_token().is_none() {
-            return Err(ConfigError::RobotMissingField {
-                field: "RObot.telegram.bot_token".to_string(),
-                hint: "Run `ralph bot onboard --telegram`, set RALPH_TELEGRAM_BOT_TOKEN env var, or set RObot.telegram.bot_token in config"
-                    .to_string(),
-            });
+    /// Shell command that handles this hat's events instead of a backend.
+    ///
+    /// The hat runs as a native [`CommandHat`](crate::native_hat::CommandHat):
+    /// on success it publishes `default_publishes` (or `<hat>.done`) with the
+    /// command's output, otherwise `<hat>.failed`.
+    /// ```yaml
+    /// hats:
+    ///   formatter:
+    ///     triggers: ["build.done"]
+    ///     default_publishes: "fmt.done"
+    ///     command: "cargo fmt --all"
+    /// ```
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Former IDs of this hat.
+    ///
+    /// Events that target or name an alias as their source are routed to
+    /// this hat, so events files and recordings from before a rename still
+    /// work.
+    /// ```yaml
+    /// hats:
+    ///   builder:
+    ///     aliases: [implementer]
+    /// ```
+    #[serde(default)]
+    pub aliases: Vec<String>,
+
+    /// Keep the hat for events already addressed to it, but stop delegating
+    /// new work to it.
+    ///
+    /// Ralph's hat table marks the hat as deprecated, and loading the config
+    /// warns until the hat is removed.
+    #[serde(default)]
+    pub deprecated: bool,
+
+    /// Transforms run on the hat's output, in order, before its events are
+    /// journaled and the loop reads its result.
+    /// ```yaml
+    /// hats:
+    ///   builder:
+    ///     postprocess:
+    ///       - strip_thinking
+    ///       - apply_diff
+    ///       - table_to_events: { topic: review.finding }
+    ///       - command: "scripts/redact.sh"
+    /// ```
+    #[serde(default)]
+    pub postprocess: Vec<Postprocessor>,
+
+    /// Hats in the same mutex group never run at the same time, across all
+    /// loops in the repository.
+    ///
+    /// Parallel loops each run their own hats; two hats that both rewrite
+    /// `Cargo.toml` can share a group so one waits for the other. Hats
+    /// without a group, or in different groups, still run in parallel.
+    /// ```yaml
+    /// hats:
+    ///   dependency_updater:
+    ///     mutex_group: manifests
+    ///   feature_builder:
+    ///     mutex_group: manifests
+    /// ```
+    #[serde(default)]
+    pub mutex_group: Option<String>,
+}
+
+/// A transform in a hat's `postprocess` pipeline.
+///
+/// Written as a name (`strip_thinking`, `apply_diff`, `table_to_events`) or
+/// a single-key mapping (`command: ...`, `table_to_events: {topic: ...}`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "PostprocessorRepr", into = "PostprocessorRepr")]
+pub enum Postprocessor {
+    /// Remove `<thinking>`, `<think>`, and `<reasoning>` blocks.
+    StripThinking,
+
+    /// Apply ```` ```diff ```` and ```` ```patch ```` blocks to the workspace
+    /// with `git apply`.
+    ApplyDiff,
+
+    /// Publish one event per row of each markdown table in the output.
+    TableToEvents {
+        /// Topic of every row's event. Without it, the table needs a `topic`
+        /// column.
+        #[serde(default)]
+        topic: Option<String>,
+    },
+
+    /// Pipe the output through a shell command; its stdout replaces the
+    /// output.
+    Command(String),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PostprocessorRepr {
+    Name(String),
+    Command { command: String },
+    TableToEvents { table_to_events: TableToEventsRepr },
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct TableToEventsRepr {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    topic: Option<String>,
+}
+
+impl TryFrom<PostprocessorRepr> for Postprocessor {
+    type Error = String;
+
+    fn try_from(repr: PostprocessorRepr) -> Result<Self, Self::Error> {
+        Ok(match repr {
+            PostprocessorRepr::Name(name) => match name.as_str() {
+                "strip_thinking" => Self::StripThinking,
+                "apply_diff" => Self::ApplyDiff,
+                "table_to_events" => Self::TableToEvents { topic: None },
+                _ => {
+                    return Err(format!(
+                        "unknown postprocessor '{name}' (expected strip_thinking, apply_diff, table_to_events, or command)"
+                    ));
+                }
+            },
+            PostprocessorRepr::Command { command } => Self::Command(command),
+            PostprocessorRepr::TableToEvents { table_to_events } => Self::TableToEvents {
+                topic: table_to_events.topic,
+            },
+        })
+    }
+}
+
+impl From<Postprocessor> for PostprocessorRepr {
+    fn from(step: Postprocessor) -> Self {
+        match step {
+            Postprocessor::StripThinking => Self::Name("strip_thinking".to_string()),
+            Postprocessor::ApplyDiff => Self::Name("apply_diff".to_string()),
+            Postprocessor::TableToEvents { topic: None } => {
+                Self::Name("table_to_events".to_string())
+            }
+            Postprocessor::TableToEvents { topic } => Self::TableToEvents {
+                table_to_events: TableToEventsRepr { topic },
+            },
+            Postprocessor::Command(command) => Self::Command { command },
         }
+    }
+}
 
-        Ok(())
+impl HatConfig {
+    /// Returns true if the hat may run `elapsed_secs` into the loop.
+    pub fn in_window(&self, elapsed_secs: u64) -> bool {
+        self.windows.is_empty() || self.windows.iter().any(|w| w.contains(elapsed_secs))
     }
 
-    /// Resolves the bot token from multiple sources.
-    ///
-    /// Resolution order (highest to lowest priority):
-    /// 1. `RALPH_TELEGRAM_BOT_TOKEN` environment variable
-    /// 2. `RObot.telegram.bot_token` in config file (explicit project override)
-    /// 3. OS keychain (service: "ralph", user: "telegram-bot-token")
-    pub fn resolve_bot_token(&self) -> Option<String> {
-        // 1. Env var (highest priority)
-        let env_token = std::env::var("RALPH_TELEGRAM_BOT_TOKEN").ok();
-        let config_token = self
-            .telegram
-            .as_ref()
-            .and_then(|telegram| telegram.bot_token.clone());
+    /// Converts trigger strings to Topic objects.
+    pub fn trigger_topics(&self) -> Vec<Topic> {
+        self.triggers.iter().map(|s| Topic::new(s)).collect()
+    }
 
-        if cfg!(test) {
-            return env_token.or(config_token);
+    /// Converts publish strings to Topic objects.
+    pub fn publish_topics(&self) -> Vec<Topic> {
+        self.publishes.iter().map(|s| Topic::new(s)).collect()
+    }
+}
+
+/// Generation parameters for a hat's backend.
+///
+/// Each backend takes the settings its CLI exposes and ignores the rest, with
+/// a warning when the loop starts:
+///
+/// | Setting | Passed as |
+/// |---------|-----------|
+/// | `model` | `--model` (every backend) |
+/// | `temperature` | `GOOSE_TEMPERATURE` (goose) |
+/// | `max_output_tokens` | `CLAUDE_CODE_MAX_OUTPUT_TOKENS` (claude) |
+/// | `reasoning_effort` | `MAX_THINKING_TOKENS` (claude), `-c model_reasoning_effort` (codex) |
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationConfig {
+    /// Model to run, overriding the backend's default. A matching
+    /// `routing:` rule's model takes precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Sampling temperature (0.0–2.0); lower is more deterministic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+
+    /// Cap on tokens generated per response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+
+    /// How much the model reasons before answering.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+}
+
+impl GenerationConfig {
+    /// Returns true if no setting is configured.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Reasoning effort levels, as accepted by backends that support them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    /// Returns the level as written in config.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
         }
+    }
+}
 
-        env_token
-            // 2. Config file (explicit override)
-            .or(config_token)
-            // 3. OS keychain (best effort)
-            .or_else(|| {
-                std::panic::catch_unwind(|| {
-                    keyring::Entry::new("ralph", "telegram-bot-token")
-                        .ok()
-                        .and_then(|e| e.get_password().ok())
-                })
-                .ok()
-                .flatten()
-            })
+/// A workflow phase (`phases:`).
+///
+/// While a phase is current, only its hats (and hats no phase lists) are
+/// activated. The loop moves to the next phase when one of `complete_on` is
+/// published or after `max_iterations` iterations in the phase. Finishing the
+/// last phase completes the loop.
+///
+/// Example configuration:
+/// ```yaml
+/// phases:
+///   - name: design
+///     hats: [architect]
+///     complete_on: ["design.approved"]
+///   - name: implement
+///     hats: [builder]
+///     complete_on: ["build.done"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseConfig {
+    /// Name shown in prompts and the TUI.
+    pub name: String,
+
+    /// IDs of the hats enabled during the phase.
+    pub hats: Vec<String>,
+
+    /// Topics (or patterns) that finish the phase when published.
+    #[serde(default)]
+    pub complete_on: Vec<String>,
+
+    /// Iterations after which the phase ends even if nothing in
+    /// `complete_on` was published.
+    #[serde(default)]
+    pub max_iterations: Option<u32>,
+
+    /// Extra guidance added to prompts during the phase.
+    #[serde(default)]
+    pub instructions: Option<String>,
+}
+
+impl PhaseConfig {
+    /// Returns true if publishing `topic` finishes the phase.
+    pub fn completed_by(&self, topic: &str) -> bool {
+        self.complete_on
+            .iter()
+            .any(|pattern| Topic::new(pattern.as_str()).matches_str(topic))
     }
 }
 
-/// Telegram bot configuration.
+/// A period of the loop run, in seconds since loop start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HatWindow {
+    /// Start of the window (inclusive).
+    #[serde(default)]
+    pub from_seconds: u64,
+
+    /// End of the window (exclusive); unset means until the loop ends.
+    #[serde(default)]
+    pub until_seconds: Option<u64>,
+}
+
+impl HatWindow {
+    /// Returns true if `elapsed_secs` falls inside the window.
+    pub fn contains(&self, elapsed_secs: u64) -> bool {
+        elapsed_secs >= self.from_seconds && self.until_seconds.is_none_or(|end| elapsed_secs < end)
+    }
+}
+
+/// Parallel read-only scouting before a hat's iteration.
+///
+/// Each prompt runs headless on its own backend call, at most `max_parallel`
+/// at a time. The answers are added to the hat's prompt as scout reports.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TelegramBotConfig {
-    /// Bot token. Optional if `RALPH_TELEGRAM_BOT_TOKEN` env var is set.
-    pub bot_token: Option<String>,
+pub struct ScoutsConfig {
+    /// One scouting task per entry (e.g. "Summarize module X").
+    #[serde(default)]
+    pub prompts: Vec<String>,
+
+    /// Most scouts running at once.
+    #[serde(default = "default_scout_parallelism")]
+    pub max_parallel: usize,
+
+    /// Backend for scouts (defaults to the hat's backend); point this at a
+    /// cheaper model.
+    #[serde(default)]
+    pub backend: Option<HatBackend>,
+
+    /// Seconds each scout may run before it is stopped.
+    #[serde(default = "default_scout_timeout")]
+    pub timeout_seconds: u64,
+
+    /// Longest answer kept per scout, in bytes.
+    #[serde(default = "default_scout_output_chars")]
+    pub max_output_chars: usize,
 }
 
-/// Configuration errors.
-#[derive(Debug, thiserror::Error)]
-pub enum ConfigError {
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+fn default_scout_parallelism() -> usize {
+    3
+}
 
-    #[error("YAML parse error: {0}")]
-    Yaml(#[from] serde_yaml::Error),
+fn default_scout_timeout() -> u64 {
+    300
+}
 
-    #[error(
-        "Ambiguous routing: trigger '{trigger}' is claimed by both '{hat1}' and '{hat2}'.\nFix: ensure only one hat claims this trigger or delegate with a new event.\nSee: docs/reference/troubleshooting.md#ambiguous-routing"
-    )]
-    AmbiguousRouting {
-        trigger: String,
-        hat1: String,
-        hat2: String,
-    },
+fn default_scout_output_chars() -> usize {
+    4000
+}
 
-    #[error(
-        "Mutually exclusive fields: '{field1}' and '{field2}' cannot both be specified.\nFix: remove one field or split into separate configs.\nSee: docs/reference/troubleshooting.md#mutually-exclusive-fields"
-    )]
-    MutuallyExclusive { field1: String, field2: String },
+/// What a WASM plugin provides to the orchestrator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginKind {
+    /// Contributes a section to every prompt.
+    Context,
+    /// Decides whether events read from JSONL reach the bus.
+    Filter,
+    /// Handles a hat's events without a backend call.
+    Hat,
+}
 
-    #[error("Invalid completion_promise: must be non-empty and non-whitespace")]
-    InvalidCompletionPromise,
+/// WASM plugin configuration.
+///
+/// Example configuration:
+/// ```yaml
+/// plugins:
+///   - name: repo-map
+///     path: .ralph/plugins/repo_map.wasm
+///     kind: context
+///   - name: linter
+///     path: .ralph/plugins/linter.wasm
+///     kind: hat
+///
+/// hats:
+///   linter:
+///     name: "Linter"
+///     description: "Deterministic lint pass"
+///     triggers: ["lint.request"]
+///     publishes: ["lint.done"]
+///     plugin: linter
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConfig {
+    /// Unique plugin name, referenced by `hats.<id>.plugin`.
+    pub name: String,
 
-    #[error(
-        "Custom backend requires a command.\nFix: set 'cli.command' in your config (or run `ralph init --backend custom`).\nSee: docs/reference/troubleshooting.md#custom-backend-command"
-    )]
-    CustomBackendRequiresCommand,
+    /// Path to the compiled `.wasm` module (relative to the workspace root).
+    pub path: PathBuf,
 
-    #[error(
-        "Reserved trigger '{trigger}' used by hat '{hat}' - task.start and task.resume are reserved for Ralph (the coordinator). Use a delegated event like 'work.start' instead.\nSee: docs/reference/troubleshooting.md#reserved-trigger"
-    )]
-    ReservedTrigger { trigger: String, hat: String },
+    /// What the plugin provides.
+    pub kind: PluginKind,
 
-    #[error(
-        "Hat '{hat}' is missing required 'description' field - add a short description of the hat's purpose.\nSee: docs/reference/troubleshooting.md#missing-hat-description"
-    )]
-    MissingDescription { hat: String },
+    /// Whether the plugin is loaded.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
 
-    #[error(
-        "RObot config error: {field} - {hint}\nSee: docs/reference/troubleshooting.md#robot-config"
-    )]
-    RobotMissingField { field: String, hint: String },
+/// Rhai scripting hooks.
+///
+/// Each hook is an inline Rhai script. See `ralph_core::script` for the values
+/// in scope and the expected return values.
+///
+/// Example configuration:
+/// ```yaml
+/// scripts:
+///   transform: |
+///     if event.topic == "status.ping" { return (); }  // drop noise
+///     event
+///   route: |
+///     if event.payload.contains("frontend") { "frontend_builder" }
+///   terminate: |
+///     if state.cumulative_cost > 5.0 && state.iteration > 20 { "cost plateau" }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptsConfig {
+    /// Runs for every event read from JSONL; returns the event or `()` to drop it.
+    #[serde(default)]
+    pub transform: Option<String>,
+
+    /// Runs before an event is published; returns a hat id or `()`.
+    #[serde(default)]
+    pub route: Option<String>,
+
+    /// Runs with each termination check; returns `true` or a reason to stop.
+    #[serde(default)]
+    pub terminate: Option<String>,
+
+    /// Maximum operations a single script evaluation may perform.
+    #[serde(default = "default_script_max_operations")]
+    pub max_operations: u64,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn default_script_max_operations() -> u64 {
+    100_000
+}
 
-    #[test]
-    fn test_default_config() {
-        let config = RalphConfig::default();
-        // Default config has no custom hats (uses default planner+builder)
-        assert!(config.hats.is_empty());
-        assert_eq!(config.event_loop.max_iterations, 100);
-        assert!(!config.verbose);
-        assert!(!config.features.preflight.enabled);
-        assert!(!config.features.preflight.strict);
-        assert!(config.features.preflight.skip.is_empty());
+impl Default for ScriptsConfig {
+    fn default() -> Self {
+        Self {
+            transform: None,
+            route: None,
+            terminate: None,
+            max_operations: default_script_max_operations(),
+        }
     }
+}
 
-    #[test]
-    fn test_parse_yaml_with_custom_hats() {
-        let yaml = r#"
-event_loop:
-  prompt_file: "TASK.md"
-  completion_promise: "DONE"
-  max_iterations: 50
-cli:
-  backend: "claude"
-hats:
-  implementer:
-    name: "Implementer"
-    triggers: ["task.*", "review.done"]
-    publishes: ["impl.done"]
-    instructions: "You are the implementation agent."
-"#;
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        // Custom hats are defined
-        assert_eq!(config.hats.len(), 1);
-        assert_eq!(config.event_loop.prompt_file, "TASK.md");
+impl ScriptsConfig {
+    /// Returns true if any hook has a script.
+    pub fn has_scripts(&self) -> bool {
+        self.transform.is_some() || self.route.is_some() || self.terminate.is_some()
+    }
+}
 
-        let hat = config.hats.get("implementer").unwrap();
-        assert_eq!(hat.triggers.len(), 2);
+/// A routing rule: which backend and model to use for an iteration.
+///
+/// Rules are checked in order and the first match wins. A rule matches when
+/// the active hat is in `hats` (or `hats` is empty) and its `when:` predicate
+/// holds for the triggering event. A matching rule takes precedence over the
+/// hat's own `backend:`. Setting only `model` keeps the hat's backend and
+/// passes `--model` to it.
+///
+/// Example configuration:
+/// ```yaml
+/// routing:
+///   - name: cheap-planning
+///     hats: [planner, summarizer]
+///     model: haiku
+///   - name: large-context
+///     when: "payload_len > 20000"
+///     backend: gemini
+///   - name: implementation
+///     hats: [builder]
+///     backend: claude
+///     model: opus
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteRule {
+    /// Name recorded in the loop history (defaults to `routing[<index>]`).
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Hat IDs the rule applies to; empty matches every hat.
+    #[serde(default)]
+    pub hats: Vec<String>,
+
+    /// Predicate over loop state and the triggering event (same syntax as a
+    /// hat's `when:`).
+    #[serde(default)]
+    pub when: Option<String>,
+
+    /// Backend to run instead of the hat's or the global one.
+    #[serde(default)]
+    pub backend: Option<HatBackend>,
+
+    /// Model passed to the backend as `--model <model>`.
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// Container execution environment for backend processes.
+///
+/// The backend CLI runs inside `image` with the workspace mounted at the same
+/// path and used as the working directory, so every iteration gets the same
+/// toolchain. Set it at the top level, or per hat to give a hat its own image.
+///
+/// Example configuration:
+/// ```yaml
+/// environment:
+///   image: rust:1.80
+///   mounts:
+///     - ~/.cargo/registry:/usr/local/cargo/registry
+///   env: [ANTHROPIC_API_KEY]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentConfig {
+    /// Container image to run the backend in.
+    pub image: String,
+
+    /// Extra bind mounts in `host:container[:options]` form (`~` expands to `$HOME`).
+    #[serde(default)]
+    pub mounts: Vec<String>,
+
+    /// Host environment variables forwarded into the container.
+    #[serde(default)]
+    pub env: Vec<String>,
+
+    /// Container runtime CLI ("docker" or "podman").
+    #[serde(default = "default_container_runtime")]
+    pub runtime: String,
+
+    /// Extra arguments passed to `<runtime> run` before the image.
+    #[serde(default)]
+    pub run_args: Vec<String>,
+}
+
+fn default_container_runtime() -> String {
+    "docker".to_string()
+}
+
+/// Browser dashboard configuration.
+///
+/// The dashboard mirrors the TUI in a browser: live status, the event stream,
+/// iteration history with cost, and pause/resume/stop controls. Requires a
+/// build with the `dashboard` feature.
+///
+/// Example configuration:
+/// ```yaml
+/// dashboard:
+///   enabled: true
+///   bind: "127.0.0.1:7070"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardConfig {
+    /// Whether to serve the dashboard during `ralph run`.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Address the dashboard listens on.
+    #[serde(default = "default_dashboard_bind")]
+    pub bind: String,
+}
+
+fn default_dashboard_bind() -> String {
+    "127.0.0.1:7070".to_string()
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_dashboard_bind(),
+        }
+    }
+}
+
+/// Speculative dual-backend execution.
+///
+/// Each matching iteration runs the same prompt on two backends at once, each
+/// in its own git worktree. An arbiter picks one candidate: its workspace
+/// changes are applied to the main workspace and its events are published.
+/// The other candidate's worktree and events are discarded.
+///
+/// Example configuration:
+/// ```yaml
+/// speculative:
+///   enabled: true
+///   backends: [claude, gemini]
+///   hats: [builder]
+///   arbiter: check
+///   check: "cargo test"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpeculativeConfig {
+    /// Whether speculative execution is on.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The two backends to race (same forms as a hat's `backend:`).
+    #[serde(default)]
+    pub backends: Vec<HatBackend>,
+
+    /// Hat IDs to speculate for; empty means every hat.
+    #[serde(default)]
+    pub hats: Vec<String>,
+
+    /// How the winning candidate is chosen.
+    #[serde(default)]
+    pub arbiter: ArbiterKind,
+
+    /// Command run in each candidate's worktree by the `check` arbiter.
+    #[serde(default)]
+    pub check: Option<String>,
+
+    /// Backend asked to pick a winner by the `judge` arbiter
+    /// (defaults to `cli.backend`).
+    #[serde(default)]
+    pub judge: Option<HatBackend>,
+}
+
+impl SpeculativeConfig {
+    /// Returns true if iterations for `hat` should run speculatively.
+    pub fn applies_to(&self, hat: &str) -> bool {
+        self.enabled && (self.hats.is_empty() || self.hats.iter().any(|h| h == hat))
+    }
+}
+
+/// Strategy for picking between speculative candidates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArbiterKind {
+    /// Prefer a successful run that changed files and emitted events.
+    #[default]
+    Heuristic,
+    /// Prefer the candidate whose changes pass `speculative.check`.
+    Check,
+    /// Ask the judge backend to compare both candidates.
+    Judge,
+}
+
+impl ArbiterKind {
+    /// Returns the config name of the arbiter.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Heuristic => "heuristic",
+            Self::Check => "check",
+            Self::Judge => "judge",
+        }
+    }
+}
+
+/// Policy for the guarded shell proxy, `ralph tools run -- <command>`.
+///
+/// Commands are split on `&&`, `||`, `;`, `|`, and newlines, and each part
+/// is matched against the `deny` and `allow` patterns, where `*` matches any
+/// run of characters. A matching `deny` pattern always refuses the command; a
+/// non-empty `allow` list must match every part. Every invocation is logged
+/// to `.ralph/agent/tool-audit.jsonl`, and refusals and failures are
+/// published as `tool.denied` and `tool.failed` events.
+///
+/// Example configuration:
+/// ```yaml
+/// shell_tool:
+///   enabled: true
+///   allow: ["cargo *", "git status*", "git diff*", "ls*", "rg *"]
+///   deny: ["* --force*", "rm -rf *"]
+///   timeout_seconds: 300
+///   max_output_bytes: 32768
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellToolConfig {
+    /// Instruct agents to run shell commands through `ralph tools run`.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Command patterns that may run; empty allows anything not denied.
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    /// Command patterns that are always refused.
+    #[serde(default)]
+    pub deny: Vec<String>,
+
+    /// Seconds before the command is killed and reported as failed.
+    #[serde(default = "default_shell_tool_timeout")]
+    pub timeout_seconds: u64,
+
+    /// Bytes of stdout and stderr (each) passed back before truncating.
+    #[serde(default = "default_shell_tool_max_output")]
+    pub max_output_bytes: usize,
+}
+
+fn default_shell_tool_timeout() -> u64 {
+    600
+}
+
+fn default_shell_tool_max_output() -> usize {
+    64 * 1024
+}
+
+impl Default for ShellToolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allow: vec![],
+            deny: vec![],
+            timeout_seconds: default_shell_tool_timeout(),
+            max_output_bytes: default_shell_tool_max_output(),
+        }
+    }
+}
+
+/// Encryption of session artifacts at rest.
+///
+/// When enabled, each iteration's prompt, output, and event trace are
+/// encrypted once the iteration ends, and the events journal and any
+/// forensics bundle once the loop ends, so they can sit in shared CI caches.
+/// The key is 32 bytes as 64 hex characters (`openssl rand -hex 32`), read
+/// from `credentials` or the environment variable named by `key_env`.
+///
+/// Example configuration:
+/// ```yaml
+/// encryption:
+///   enabled: true
+///   key_env: RALPH_ENCRYPTION_KEY
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    /// Encrypt session artifacts.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Environment variable (or `credentials` entry) holding the key.
+    #[serde(default = "default_encryption_key_env")]
+    pub key_env: String,
+}
+
+fn default_encryption_key_env() -> String {
+    "RALPH_ENCRYPTION_KEY".to_string()
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key_env: default_encryption_key_env(),
+        }
+    }
+}
+
+/// Verification command run after each iteration.
+///
+/// The command runs with the shell in the workspace root. Its exit status and
+/// parsed results (cargo/rustc JSON diagnostics and libtest output, or
+/// Jest/Vitest/ESLint output for the `node` preset) are published as a
+/// `ci.passed` or `ci.failed` event, so hats can react to real build and
+/// test results.
+///
+/// A preset name alone (`verify: rust`) picks the command and output parser
+/// for an ecosystem. An explicit `command` overrides the preset's command
+/// but keeps its parser.
+///
+/// Example configuration:
+/// ```yaml
+/// verify:
+///   preset: node
+///   command: "pnpm test"
+///   hats: [builder]
+///   timeout_seconds: 900
+///   triage: true
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyConfig {
+    /// Ecosystem preset supplying the default command and output parser.
+    #[serde(default)]
+    pub preset: Option<VerifyPreset>,
+
+    /// Shell command to run; unset disables verification unless a preset
+    /// is set.
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Hat IDs whose iterations are verified; empty means every hat.
+    #[serde(default)]
+    pub hats: Vec<String>,
+
+    /// Seconds before the command is killed and reported as failed.
+    #[serde(default = "default_verify_timeout")]
+    pub timeout_seconds: u64,
+
+    /// Split a failed run into one `fix.task` event per cluster of related
+    /// failures (compiler errors by file, lints by name, tests by module).
+    #[serde(default)]
+    pub triage: bool,
+}
+
+fn default_verify_timeout() -> u64 {
+    600
+}
+
+impl Default for VerifyConfig {
+    fn default() -> Self {
+        Self {
+            preset: None,
+            command: None,
+            hats: vec![],
+            timeout_seconds: default_verify_timeout(),
+            triage: false,
+        }
+    }
+}
+
+impl VerifyConfig {
+    /// Returns the command to run after an iteration of `hat`, if any.
+    pub fn command_for(&self, hat: &str) -> Option<&str> {
+        let command = self
+            .command
+            .as_deref()
+            .filter(|c| !c.trim().is_empty())
+            .or_else(|| self.preset.map(VerifyPreset::command))?;
+        (self.hats.is_empty() || self.hats.iter().any(|h| h == hat)).then_some(command)
+    }
+
+    /// Returns the parser for the command's output.
+    pub fn parser(&self) -> OutputParser {
+        self.preset
+            .map_or(OutputParser::Cargo, VerifyPreset::parser)
+    }
+}
+
+/// Accepts a preset name (`verify: rust`) as well as the full mapping.
+fn deserialize_verify<'de, D>(deserializer: D) -> Result<VerifyConfig, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Preset(VerifyPreset),
+        Config(VerifyConfig),
+    }
+
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::Preset(preset) => VerifyConfig {
+            preset: Some(preset),
+            ..VerifyConfig::default()
+        },
+        Repr::Config(config) => config,
+    })
+}
+
+/// Verification preset for a language ecosystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifyPreset {
+    /// `cargo check`, `cargo clippy -D warnings`, then `cargo test`.
+    Rust,
+    /// `npm test`, then `npm run lint` when the package defines it.
+    Node,
+}
+
+impl VerifyPreset {
+    /// Returns the shell command the preset runs.
+    pub fn command(self) -> &'static str {
+        match self {
+            Self::Rust => {
+                "cargo check --all-targets --message-format json \
+                 && cargo clippy --all-targets --message-format json -- -D warnings \
+                 && cargo test"
+            }
+            Self::Node => "npm test && npm run lint --if-present",
+        }
+    }
+
+    /// Returns the parser for the preset's output.
+    pub fn parser(self) -> OutputParser {
+        match self {
+            Self::Rust => OutputParser::Cargo,
+            Self::Node => OutputParser::Node,
+        }
+    }
+
+    /// Returns the name used in `verify:`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Node => "node",
+        }
+    }
+}
+
+/// Adaptive iteration budget.
+///
+/// Starts from `event_loop.max_iterations` and moves the limit once per
+/// `window` iterations, based on tasks closed and lines changed: steady
+/// progress near the limit extends it by `extend_by` (up to
+/// `max_iterations`), a window with no progress at all shrinks it to one more
+/// window (down to `min_iterations`). Runtime and cost caps still apply.
+///
+/// Example configuration:
+/// ```yaml
+/// adaptive_budget:
+///   enabled: true
+///   window: 5
+///   extend_by: 20
+///   min_iterations: 10
+///   max_iterations: 300
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveBudgetConfig {
+    /// Whether the limit follows progress.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Iterations per progress sample.
+    #[serde(default = "default_budget_window")]
+    pub window: u32,
+
+    /// Iterations added per extension.
+    #[serde(default = "default_budget_extend_by")]
+    pub extend_by: u32,
+
+    /// The limit never shrinks below this.
+    #[serde(default = "default_budget_min_iterations")]
+    pub min_iterations: u32,
+
+    /// The limit never grows above this.
+    #[serde(default = "default_budget_max_iterations")]
+    pub max_iterations: u32,
+}
+
+fn default_budget_window() -> u32 {
+    5
+}
+
+fn default_budget_extend_by() -> u32 {
+    20
+}
+
+fn default_budget_min_iterations() -> u32 {
+    10
+}
+
+fn default_budget_max_iterations() -> u32 {
+    300
+}
+
+impl Default for AdaptiveBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: default_budget_window(),
+            extend_by: default_budget_extend_by(),
+            min_iterations: default_budget_min_iterations(),
+            max_iterations: default_budget_max_iterations(),
+        }
+    }
+}
+
+/// State store backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateBackend {
+    /// State stays in the workspace's `.ralph/`, optionally mirrored to `path`.
+    #[default]
+    Filesystem,
+    /// State is mirrored to an S3 bucket through the `aws` CLI.
+    S3,
+}
+
+/// Agent state persistence.
+///
+/// By default agent state lives in the workspace's `.ralph/` directory and
+/// disappears with an ephemeral CI runner. Configuring a store mirrors the
+/// scratchpad, `.ralph/agent/` files, event journals, and loop history to it:
+/// they are pulled before the loop starts and pushed after every iteration
+/// and on termination.
+///
+/// Example configuration:
+/// ```yaml
+/// state_store:
+///   backend: s3
+///   bucket: ci-ralph-state
+///   prefix: my-repo/main
+/// ```
+///
+/// Or a directory the runner keeps between jobs (a cache mount):
+/// ```yaml
+/// state_store:
+///   path: /mnt/cache/ralph
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StateStoreConfig {
+    /// `filesystem` (default) or `s3`.
+    #[serde(default)]
+    pub backend: StateBackend,
+
+    /// Directory to mirror state into (filesystem backend). Unset keeps
+    /// state in the workspace only.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+
+    /// Bucket name (s3 backend).
+    #[serde(default)]
+    pub bucket: Option<String>,
+
+    /// Key prefix inside the bucket, e.g. per repository and branch.
+    #[serde(default)]
+    pub prefix: String,
+
+    /// Custom endpoint for S3-compatible stores (MinIO, R2).
+    #[serde(default)]
+    pub endpoint_url: Option<String>,
+}
+
+/// Attribution of checkpoint commits.
+///
+/// With `trailers`, the commit landing a completed loop ends with
+/// `Session-Id`, `Iteration`, `Hat`, and `Events` git trailers. With
+/// `changelog`, a summary of the tasks the session closed is added to the
+/// top of `CHANGELOG.ralph.md` and committed with it.
+///
+/// Example configuration:
+/// ```yaml
+/// attribution:
+///   trailers: true
+///   changelog: true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributionConfig {
+    /// Append structured trailers to checkpoint commits.
+    #[serde(default)]
+    pub trailers: bool,
+
+    /// Maintain `CHANGELOG.ralph.md` with one entry per completed session.
+    #[serde(default)]
+    pub changelog: bool,
+}
+
+/// How checkpoint commits are worded, attributed, and signed.
+///
+/// By default checkpoints use the repository's git identity and signing
+/// settings. `author` commits as a dedicated identity instead (both author and
+/// committer), `gpg_sign` signs with the default key (`true`) or a given key
+/// id, and `prefix` goes in front of the subject line.
+///
+/// Example configuration:
+/// ```yaml
+/// checkpoint:
+///   author: "Ralph Orchestrator <ralph@ci>"
+///   gpg_sign: "0xBOTKEY"
+///   prefix: "[ralph]"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointConfig {
+    /// Identity for checkpoint commits, as `Name <email>`.
+    #[serde(default)]
+    pub author: Option<String>,
+
+    /// Sign checkpoint commits.
+    #[serde(default)]
+    pub gpg_sign: GpgSign,
+
+    /// Text put in front of the checkpoint subject line.
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+/// `checkpoint.gpg_sign`: on, off, or a key id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GpgSign {
+    /// Sign with git's default key (`true`), or don't sign (`false`).
+    Enabled(bool),
+    /// Sign with this key id.
+    Key(String),
+}
+
+impl Default for GpgSign {
+    fn default() -> Self {
+        Self::Enabled(false)
+    }
+}
+
+impl CheckpointConfig {
+    /// Splits `author` into name and email.
+    ///
+    /// Returns `None` when no author is set or it isn't `Name <email>`.
+    pub fn identity(&self) -> Option<(String, String)> {
+        let author = self.author.as_deref()?.trim();
+        let (name, rest) = author.split_once('<')?;
+        let email = rest.strip_suffix('>')?.trim();
+        let name = name.trim();
+        (!name.is_empty() && !email.is_empty() && !email.contains(['<', '>']))
+            .then(|| (name.to_string(), email.to_string()))
+    }
+
+    /// The commit options these settings describe, without trailers.
+    pub fn commit_options(&self) -> CommitOptions {
+        CommitOptions {
+            prefix: self.prefix.clone().filter(|p| !p.trim().is_empty()),
+            identity: self.identity(),
+            signing_key: match &self.gpg_sign {
+                GpgSign::Enabled(true) => Some(String::new()),
+                GpgSign::Enabled(false) => None,
+                GpgSign::Key(key) => Some(key.trim().to_string()),
+            },
+            trailers: Vec::new(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.author.is_some() && self.identity().is_none() {
+            return Err(ConfigError::InvalidCheckpoint {
+                field: "author".to_string(),
+                reason: "expected 'Name <email>'".to_string(),
+            });
+        }
+        if let GpgSign::Key(key) = &self.gpg_sign
+            && key.trim().is_empty()
+        {
+            return Err(ConfigError::InvalidCheckpoint {
+                field: "gpg_sign".to_string(),
+                reason: "the key id is empty".to_string(),
+            });
+        }
+        if self
+            .prefix
+            .as_deref()
+            .is_some_and(|p| p.contains(['\n', '\r']))
+        {
+            return Err(ConfigError::InvalidCheckpoint {
+                field: "prefix".to_string(),
+                reason: "must fit on the subject line".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Embedding index over the workspace for `ralph tools search`.
+///
+/// When enabled, the index is built when a loop starts and refreshed after
+/// each checkpoint, re-embedding only files whose content changed.
+///
+/// Example configuration:
+/// ```yaml
+/// search_index:
+///   enabled: true
+///   exclude: ["*.lock", "fixtures/"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchIndexConfig {
+    /// Build and refresh the index during loops.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Lines per indexed chunk.
+    #[serde(default = "default_search_chunk_lines")]
+    pub chunk_lines: usize,
+
+    /// Files larger than this many KiB are skipped.
+    #[serde(default = "default_search_max_file_kb")]
+    pub max_file_kb: u64,
+
+    /// Paths left out of the index, as `protect`-style globs.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+fn default_search_chunk_lines() -> usize {
+    40
+}
+
+fn default_search_max_file_kb() -> u64 {
+    256
+}
+
+impl Default for SearchIndexConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            chunk_lines: default_search_chunk_lines(),
+            max_file_kb: default_search_max_file_kb(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+/// Forensics bundle written when a loop fails.
+///
+/// When a loop ends with `consecutive_failures` or `max_runtime`, the last
+/// few iterations' prompts and output, the tail of the events file, the diff
+/// since the last good checkpoint, and a redacted config snapshot are zipped
+/// into `.ralph/forensics/`.
+///
+/// Example configuration:
+/// ```yaml
+/// forensics:
+///   iterations: 5
+///   event_tail: 500
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForensicsConfig {
+    /// Write a bundle when the loop fails.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// How many of the most recent iterations to include.
+    #[serde(default = "default_forensics_iterations")]
+    pub iterations: u32,
+
+    /// How many of the last events file lines to include.
+    #[serde(default = "default_forensics_event_tail")]
+    pub event_tail: usize,
+}
+
+fn default_forensics_iterations() -> u32 {
+    3
+}
+
+fn default_forensics_event_tail() -> usize {
+    200
+}
+
+impl Default for ForensicsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            iterations: default_forensics_iterations(),
+            event_tail: default_forensics_event_tail(),
+        }
+    }
+}
+
+/// A mirror of the event bus on a NATS or Redis pub/sub broker.
+///
+/// Events published on the bus that match `publish` are sent to
+/// `<prefix>.<topic>`. Messages on `<prefix>.<topic>` whose topic matches
+/// `subscribe` are injected as events, like `ralph emit`.
+///
+/// Example configuration:
+/// ```yaml
+/// bridges:
+///   - url: nats://127.0.0.1:4222
+///     prefix: ralph.ci
+///     publish: ["build.*", "loop.terminate"]
+///     subscribe: ["review.request"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BridgeConfig {
+    /// `nats://[user:pass@]host[:port]` or `redis://[user:pass@]host[:port]`.
+    pub url: String,
+
+    /// Prepended to topics on the broker.
+    #[serde(default = "default_bridge_prefix")]
+    pub prefix: String,
+
+    /// Topic patterns sent to the broker (default: every event).
+    #[serde(default = "default_bridge_publish")]
+    pub publish: Vec<String>,
+
+    /// Topic patterns accepted from the broker (default: none).
+    #[serde(default)]
+    pub subscribe: Vec<String>,
+}
+
+fn default_bridge_prefix() -> String {
+    "ralph".to_string()
+}
+
+fn default_bridge_publish() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+/// Broker protocol spoken by a bridge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrokerKind {
+    Nats,
+    Redis,
+}
+
+impl BrokerKind {
+    fn default_port(self) -> u16 {
+        match self {
+            Self::Nats => 4222,
+            Self::Redis => 6379,
+        }
+    }
+}
+
+/// Where a bridge connects, parsed from its `url`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokerEndpoint {
+    pub kind: BrokerKind,
+    pub host: String,
+    pub port: u16,
+    pub user: Option<String>,
+    pub password: Option<String>,
+}
+
+impl BridgeConfig {
+    /// Parses `url` into the broker to connect to.
+    pub fn endpoint(&self) -> Result<BrokerEndpoint, String> {
+        let (scheme, rest) = self
+            .url
+            .split_once("://")
+            .ok_or_else(|| format!("'{}' is not a URL", self.url))?;
+        let kind = match scheme {
+            "nats" => BrokerKind::Nats,
+            "redis" => BrokerKind::Redis,
+            other => return Err(format!("unsupported scheme '{other}' (use nats or redis)")),
+        };
+        let authority = rest.split('/').next().unwrap_or_default();
+        let (credentials, address) = match authority.rsplit_once('@') {
+            Some((credentials, address)) => (Some(credentials), address),
+            None => (None, authority),
+        };
+        let (user, password) = match credentials.map(|c| c.split_once(':')) {
+            Some(Some((user, password))) => (
+                Some(user).filter(|u| !u.is_empty()).map(str::to_string),
+                Some(password.to_string()),
+            ),
+            // A lone token is a NATS auth token or a Redis password
+            Some(None) => (None, credentials.map(str::to_string)),
+            None => (None, None),
+        };
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|_| format!("invalid port '{port}' in '{}'", self.url))?,
+            ),
+            None => (address, kind.default_port()),
+        };
+        if host.is_empty() {
+            return Err(format!("'{}' has no host", self.url));
+        }
+        Ok(BrokerEndpoint {
+            kind,
+            host: host.to_string(),
+            port,
+            user,
+            password,
+        })
+    }
+}
+
+/// Prompt-injection hardening for event payloads.
+///
+/// Payloads published by agents and tools can echo untrusted content (file
+/// contents, web pages, test output) into the next prompt. Payloads of every
+/// event except the run's prompt and replies relayed by the robot service are
+/// treated as untrusted: control and invisible characters are stripped, and the payload
+/// is fenced in `<event-data>` tags that the prompt tells the agent to treat
+/// as data. With a `classifier`, each untrusted payload is first piped to
+/// that command; a non-zero exit withholds the payload from the prompt.
+///
+/// Example configuration:
+/// ```yaml
+/// prompt_guard:
+///   classifier: ./scripts/detect-injection.sh
+///   classifier_timeout_seconds: 10
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptGuardConfig {
+    /// Strip control, bidi-override, and zero-width characters and defuse
+    /// fence tags in untrusted payloads.
+    #[serde(default = "default_true")]
+    pub sanitize: bool,
+
+    /// Wrap untrusted payloads in `<event-data>` tags.
+    #[serde(default = "default_true")]
+    pub fence: bool,
+
+    /// Shell command that receives each untrusted event as JSON on stdin
+    /// and exits non-zero to withhold it. Its first line of stdout is logged
+    /// as the reason.
+    #[serde(default)]
+    pub classifier: Option<String>,
+
+    /// Seconds before the classifier is killed; a timeout withholds the
+    /// payload.
+    #[serde(default = "default_classifier_timeout")]
+    pub classifier_timeout_seconds: u64,
+}
+
+fn default_classifier_timeout() -> u64 {
+    10
+}
+
+impl Default for PromptGuardConfig {
+    fn default() -> Self {
+        Self {
+            sanitize: true,
+            fence: true,
+            classifier: None,
+            classifier_timeout_seconds: default_classifier_timeout(),
+        }
+    }
+}
+
+/// Prompt localization.
+///
+/// Selects the prompt pack in `<dir>/<locale>/`, which swaps Ralph's section
+/// headers (`ORIENTATION`, `WORKFLOW`, `GUARDRAILS`, ...) and optionally whole
+/// sections for another language. Event topics, commands, and code
+/// identifiers stay in English. The default `en` locale is built in; see
+/// [`crate::prompt_pack`] for the pack layout.
+///
+/// Example configuration:
+/// ```yaml
+/// prompts:
+///   locale: de
+///   dir: .ralph/prompts
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptsConfig {
+    /// Locale whose pack to use.
+    #[serde(default = "default_prompt_locale")]
+    pub locale: String,
+
+    /// Directory holding one pack directory per locale, relative to the
+    /// workspace root.
+    #[serde(default = "default_prompt_dir")]
+    pub dir: String,
+}
+
+fn default_prompt_locale() -> String {
+    crate::prompt_pack::DEFAULT_LOCALE.to_string()
+}
+
+fn default_prompt_dir() -> String {
+    ".ralph/prompts".to_string()
+}
+
+impl Default for PromptsConfig {
+    fn default() -> Self {
+        Self {
+            locale: default_prompt_locale(),
+            dir: default_prompt_dir(),
+        }
+    }
+}
+
+/// Anthropic Messages API backend.
+///
+/// With `cli.backend: api` (or a hat's `backend: api`), iterations call the
+/// Messages API over HTTP instead of running a CLI, so no agent binary has to
+/// be installed, and each iteration's token counts and cost come from the
+/// API's usage report. The API runs no tools, so the agent can't edit files
+/// or call `ralph emit`; events are parsed from its response in the
+/// `event_loop.event_syntax`. Suited to planning, review, and writing hats.
+///
+/// Example configuration:
+/// ```yaml
+/// cli:
+///   backend: api
+/// api:
+///   model: claude-sonnet-4-5
+///   max_tokens: 16000
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    /// Model to request. A routing rule's `model:` overrides it.
+    #[serde(default = "default_api_model")]
+    pub model: String,
+
+    /// API base URL.
+    #[serde(default = "default_api_base_url")]
+    pub base_url: String,
+
+    /// Environment variable (or `credentials` entry) holding the API key.
+    #[serde(default = "default_api_key_env")]
+    pub api_key_env: String,
+
+    /// Maximum output tokens per iteration.
+    #[serde(default = "default_api_max_tokens")]
+    pub max_tokens: u32,
+}
+
+fn default_api_model() -> String {
+    "claude-sonnet-4-5".to_string()
+}
+
+fn default_api_base_url() -> String {
+    "https://api.anthropic.com".to_string()
+}
+
+fn default_api_key_env() -> String {
+    "ANTHROPIC_API_KEY".to_string()
+}
+
+fn default_api_max_tokens() -> u32 {
+    16_000
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            model: default_api_model(),
+            base_url: default_api_base_url(),
+            api_key_env: default_api_key_env(),
+            max_tokens: default_api_max_tokens(),
+        }
+    }
+}
+
+/// Where a backend credential comes from.
+///
+/// Credentials are resolved once at startup and set in the environment of
+/// every backend process, so API keys don't have to live in `ralph.yml` or in
+/// shell history.
+///
+/// Example configuration:
+/// ```yaml
+/// credentials:
+///   ANTHROPIC_API_KEY: op read op://dev/anthropic/api-key
+///   OPENAI_API_KEY: pass show openai/api-key
+///   GEMINI_API_KEY:
+///     aws_secret: prod/gemini
+///     key: api_key
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CredentialSource {
+    /// Shell command that prints the secret (`op read ...`, `pass show ...`).
+    Command(String),
+
+    /// A secret in AWS Secrets Manager, read with the `aws` CLI.
+    AwsSecret {
+        /// Secret name or ARN.
+        aws_secret: String,
+        /// Field to extract when the secret string is a JSON object.
+        #[serde(default)]
+        key: Option<String>,
+        /// Region override; defaults to the CLI's configured region.
+        #[serde(default)]
+        region: Option<String>,
+    },
+}
+
+/// Child orchestrations (nested loops).
+///
+/// A hat can emit `ralph.spawn_loop` to hand a bounded sub-project to a child
+/// loop. The child runs `ralph run` in its own git worktree with its own
+/// budget, and its result comes back to the parent as a
+/// `ralph.child_loop.done` or `ralph.child_loop.failed` event. The limits here
+/// cap whatever budget a request asks for.
+///
+/// Example configuration:
+/// ```yaml
+/// child_loops:
+///   enabled: true
+///   max_depth: 1
+///   max_iterations: 30
+///   max_cost_usd: 5.0
+///   max_runtime_seconds: 3600
+///   max_per_iteration: 2
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChildLoopsConfig {
+    /// Whether `ralph.spawn_loop` requests are honored.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How deep loops may nest (1 = children may not spawn their own).
+    #[serde(default = "default_child_max_depth")]
+    pub max_depth: u32,
+
+    /// Iteration cap for each child.
+    #[serde(default = "default_child_max_iterations")]
+    pub max_iterations: u32,
+
+    /// Cost cap for each child, in USD.
+    #[serde(default)]
+    pub max_cost_usd: Option<f64>,
+
+    /// Wall-clock cap for each child, in seconds.
+    #[serde(default = "default_child_max_runtime")]
+    pub max_runtime_seconds: u64,
+
+    /// Children run one after another inside the parent's iteration, so
+    /// requests past this many in one iteration are refused.
+    #[serde(default = "default_child_max_per_iteration")]
+    pub max_per_iteration: u32,
+}
+
+fn default_child_max_depth() -> u32 {
+    1
+}
+
+fn default_child_max_iterations() -> u32 {
+    30
+}
+
+fn default_child_max_runtime() -> u64 {
+    3600
+}
+
+fn default_child_max_per_iteration() -> u32 {
+    2
+}
+
+impl Default for ChildLoopsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_depth: default_child_max_depth(),
+            max_iterations: default_child_max_iterations(),
+            max_cost_usd: None,
+            max_runtime_seconds: default_child_max_runtime(),
+            max_per_iteration: default_child_max_per_iteration(),
+        }
+    }
+}
+
+/// Single-question escalation channel.
+///
+/// When enabled, an agent that is stuck can emit `human.question` instead of
+/// guessing. The loop pauses after that iteration, shows the question in the
+/// TUI (and POSTs it to `webhook`, if set), and resumes once a `human.answer`
+/// event is written to the events file, either from the TUI prompt or with
+/// `ralph emit human.answer "..."`. If nobody answers within
+/// `timeout_seconds`, the loop resumes with an answer telling the agent to
+/// proceed on its best judgment.
+///
+/// Unlike `RObot`, no chat service is involved.
+///
+/// Example configuration:
+/// ```yaml
+/// questions:
+///   enabled: true
+///   timeout_seconds: 1800
+///   webhook: https://example.com/ralph-question
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestionsConfig {
+    /// Whether `human.question` pauses the loop.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// URL that receives each question as a JSON POST.
+    #[serde(default)]
+    pub webhook: Option<String>,
+
+    /// How long to wait for an answer before resuming without one.
+    #[serde(default = "default_question_timeout")]
+    pub timeout_seconds: u64,
+}
+
+fn default_question_timeout() -> u64 {
+    1800
+}
+
+impl Default for QuestionsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            webhook: None,
+            timeout_seconds: default_question_timeout(),
+        }
+    }
+}
+
+/// Structured escalation for blocked work.
+///
+/// When enabled, a hat that publishes a `*.blocked` topic (`build.blocked`,
+/// `deps.blocked`, ...) is paused: events routed to it wait in its queue
+/// while the rest of the topology keeps running. The loop records what the
+/// hat was working on, POSTs a notice to `webhook` (if set), and lists the
+/// blocker in Ralph's prompt. The hat resumes, with its work queued again,
+/// when a `human.unblock` event arrives or anything publishes the matching
+/// `*.unblocked` topic. When blocked work is all that's left, the loop waits
+/// up to `timeout_seconds` for that before stopping.
+///
+/// Example configuration:
+/// ```yaml
+/// blocked:
+///   enabled: true
+///   timeout_seconds: 3600
+///   webhook: https://example.com/ralph-blocked
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedConfig {
+    /// Whether `*.blocked` pauses the publishing hat.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// URL that receives each blocker as a JSON POST.
+    #[serde(default)]
+    pub webhook: Option<String>,
+
+    /// How long to wait for an unblock once only blocked work remains.
+    #[serde(default = "default_blocked_timeout")]
+    pub timeout_seconds: u64,
+}
+
+fn default_blocked_timeout() -> u64 {
+    3600
+}
+
+impl Default for BlockedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            webhook: None,
+            timeout_seconds: default_blocked_timeout(),
+        }
+    }
+}
+
+/// Iteration carry-over summaries.
+///
+/// When enabled, the orchestrator extracts a short summary from each
+/// iteration's output and injects it into the next prompt under
+/// `## PREVIOUS ITERATION`, so a fresh context doesn't start from zero.
+/// Markdown sections whose heading starts with one of `sections` are kept;
+/// if the output has none, its last `max_chars` are used.
+///
+/// Example configuration:
+/// ```yaml
+/// carryover:
+///   enabled: true
+///   max_chars: 1500
+///   sections: ["Summary", "Next steps", "Findings"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarryoverConfig {
+    /// Whether summaries are carried into the next iteration.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Upper bound on the injected summary, in bytes.
+    #[serde(default = "default_carryover_max_chars")]
+    pub max_chars: usize,
+
+    /// Headings (case-insensitive prefixes) whose sections form the summary.
+    #[serde(default = "default_carryover_sections")]
+    pub sections: Vec<String>,
+}
+
+fn default_carryover_max_chars() -> usize {
+    1500
+}
+
+fn default_carryover_sections() -> Vec<String> {
+    vec![
+        "Summary".to_string(),
+        "Next steps".to_string(),
+        "Findings".to_string(),
+    ]
+}
+
+impl Default for CarryoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_chars: default_carryover_max_chars(),
+            sections: default_carryover_sections(),
+        }
+    }
+}
+
+/// Survey phase run before the main loop.
+///
+/// When enabled, a fresh run starts with one time-boxed iteration that only
+/// assesses the repository and plans the work. Its answer is appended to the
+/// scratchpad, and the main loop starts once the plan is approved: right away
+/// with `approval: auto`, or after a yes at the terminal with `approval: human`.
+/// The survey doesn't count toward `max_iterations`. Resumed runs skip it.
+///
+/// Example configuration:
+/// ```yaml
+/// survey:
+///   enabled: true
+///   approval: human
+///   timeout_seconds: 300
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurveyConfig {
+    /// Whether fresh runs start with a survey.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Who approves the plan before the main loop starts.
+    #[serde(default)]
+    pub approval: SurveyApproval,
+
+    /// Time box for the survey iteration.
+    #[serde(default = "default_survey_timeout")]
+    pub timeout_seconds: u64,
+}
+
+/// Who approves a survey's plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SurveyApproval {
+    /// Start the main loop as soon as the survey finishes.
+    #[default]
+    Auto,
+    /// Ask at the terminal; the loop doesn't start without a yes.
+    Human,
+}
+
+fn default_survey_timeout() -> u64 {
+    600
+}
+
+impl Default for SurveyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            approval: SurveyApproval::default(),
+            timeout_seconds: default_survey_timeout(),
+        }
+    }
+}
+
+/// RObot (Ralph-Orchestrator bot) configuration.
+///
+/// Enables bidirectional communication between AI agents and humans
+/// during orchestration loops. When enabled, agents can emit `human.interact`
+/// events to request clarification (blocking the loop), and humans can
+/// send proactive guidance via Telegram.
+///
+/// Example configuration:
+/// ```yaml
+/// RObot:
+///   enabled: true
+///   timeout_seconds: 300
+///   checkin_interval_seconds: 120  # Optional: send status every 2 min
+///   telegram:
+///     bot_token: "..."  # Or set RALPH_TELEGRAM_BOT_TOKEN env var
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RobotConfig {
+    /// Whether the RObot is enabled.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Timeout in seconds for waiting on human responses.
+    /// Required when enabled (no default — must be explicit).
+    pub timeout_seconds: Option<u64>,
+
+    /// Interval in seconds between periodic check-in messages sent via Telegram.
+    /// When set, Ralph sends a status message every N seconds so the human
+    /// knows it's still working. If `None`, no check-ins are sent.
+    pub checkin_interval_seconds: Option<u64>,
+
+    /// Telegram bot configuration.
+    #[serde(default)]
+    pub telegram: Option<TelegramBotConfig>,
+}
+
+impl RobotConfig {
+    /// Validates the RObot config. Returns an error if enabled but misconfigured.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if self.timeout_seconds.is_none() {
+            return Err(ConfigError::RobotMissingField {
+                field: "RObot.timeout_seconds".to_string(),
+                hint: "timeout_seconds is required when RObot is enabled".to_string(),
+            });
+        }
+
+        // Bot token must be available from config, keychain, or env var
+        if self.resolve_bot_token().is_none() {
+            return Err(ConfigError::RobotMissingField {
+                field: "RObot.telegram.bot_token".to_string(),
+                hint: "Run `ralph bot onboard --telegram`, set RALPH_TELEGRAM_BOT_TOKEN env var, or set RObot.telegram.bot_token in config"
+                    .to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the bot token from multiple sources.
+    ///
+    /// Resolution order (highest to lowest priority):
+    /// 1. `RALPH_TELEGRAM_BOT_TOKEN` environment variable
+    /// 2. `RObot.telegram.bot_token` in config file (explicit project override)
+    /// 3. OS keychain (service: "ralph", user: "telegram-bot-token")
+    pub fn resolve_bot_token(&self) -> Option<String> {
+        // 1. Env var (highest priority)
+        let env_token = std::env::var("RALPH_TELEGRAM_BOT_TOKEN").ok();
+        let config_token = self
+            .telegram
+            .as_ref()
+            .and_then(|telegram| telegram.bot_token.clone());
+
+        if cfg!(test) {
+            return env_token.or(config_token);
+        }
+
+        env_token
+            // 2. Config file (explicit override)
+            .or(config_token)
+            // 3. OS keychain (best effort)
+            .or_else(|| {
+                std::panic::catch_unwind(|| {
+                    keyring::Entry::new("ralph", "telegram-bot-token")
+                        .ok()
+                        .and_then(|e| e.get_password().ok())
+                })
+                .ok()
+                .flatten()
+            })
+    }
+}
+
+/// Telegram bot configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramBotConfig {
+    /// Bot token. Optional if `RALPH_TELEGRAM_BOT_TOKEN` env var is set.
+    pub bot_token: Option<String>,
+}
+
+/// Returns true if `a` and `b` differ only in a way that looks like a typo:
+/// case, `_`/`-` separators, or a single edit in a topic of 5+ characters.
+fn is_near_miss(a: &str, b: &str) -> bool {
+    if a == b {
+        return false;
+    }
+    let folded = |s: &str| {
+        s.chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<String>()
+    };
+    if folded(a) == folded(b) {
+        return true;
+    }
+    a.len().min(b.len()) >= 5 && edit_distance(a, b) == 1
+}
+
+/// Levenshtein distance between `a` and `b`, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Configuration errors.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("YAML parse error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error(
+        "Ambiguous routing: trigger '{trigger}' is claimed by both '{hat1}' and '{hat2}'.\nFix: ensure only one hat claims this trigger or delegate with a new event.\nSee: docs/reference/troubleshooting.md#ambiguous-routing"
+    )]
+    AmbiguousRouting {
+        trigger: String,
+        hat1: String,
+        hat2: String,
+    },
+
+    #[error(
+        "Mutually exclusive fields: '{field1}' and '{field2}' cannot both be specified.\nFix: remove one field or split into separate configs.\nSee: docs/reference/troubleshooting.md#mutually-exclusive-fields"
+    )]
+    MutuallyExclusive { field1: String, field2: String },
+
+    #[error("Invalid completion_promise: must be non-empty and non-whitespace")]
+    InvalidCompletionPromise,
+
+    #[error(
+        "mode: {mode} conflicts with {hats} configured hat(s) - the mode is derived from 'hats' (none means single, any means multi).\nFix: remove 'mode' from your config.\nSee: docs/guide/configuration.md#hats"
+    )]
+    ModeMismatch { mode: Mode, hats: usize },
+
+    #[error(
+        "Custom backend requires a command.\nFix: set 'cli.command' in your config (or run `ralph init --backend custom`).\nSee: docs/reference/troubleshooting.md#custom-backend-command"
+    )]
+    CustomBackendRequiresCommand,
+
+    #[error(
+        "Reserved trigger '{trigger}' used by hat '{hat}' - task.start and task.resume are reserved for Ralph (the coordinator). Use a delegated event like 'work.start' instead.\nSee: docs/reference/troubleshooting.md#reserved-trigger"
+    )]
+    ReservedTrigger { trigger: String, hat: String },
+
+    #[error(
+        "Hat '{hat}' is missing required 'description' field - add a short description of the hat's purpose.\nSee: docs/reference/troubleshooting.md#missing-hat-description"
+    )]
+    MissingDescription { hat: String },
+
+    #[error(
+        "RObot config error: {field} - {hint}\nSee: docs/reference/troubleshooting.md#robot-config"
+    )]
+    RobotMissingField { field: String, hint: String },
+
+    #[error(
+        "Invalid topic in {field}: {source}\nFix: use '.'-separated segments of letters, digits, '_' and '-' (e.g. 'build.done').\nSee: docs/guide/configuration.md#topics"
+    )]
+    InvalidTopic {
+        field: String,
+        #[source]
+        source: TopicError,
+    },
+
+    #[error(
+        "Invalid bridges[{index}]: {reason}\nFix: use a nats:// or redis:// url and valid topic patterns.\nSee: docs/guide/configuration.md#bridges"
+    )]
+    InvalidBridge { index: usize, reason: String },
+
+    #[error(
+        "Invalid {field}: {reason}\nFix: each old name may point at one current hat or topic, and must not still be in use.\nSee: docs/guide/configuration.md#renaming-hats-and-topics"
+    )]
+    InvalidRename { field: String, reason: String },
+
+    #[error(
+        "Invalid phase '{phase}': {reason}\nFix: give each phase a unique name, list configured hat IDs under 'hats', and set 'complete_on' or 'max_iterations' on every phase but the last.\nSee: docs/guide/configuration.md#phases"
+    )]
+    InvalidPhase { phase: String, reason: String },
+
+    #[error(
+        "Unknown pack '{name}'\nFix: enable one of the built-in packs: {available}.\nSee: docs/guide/configuration.md#packs"
+    )]
+    UnknownPack { name: String, available: String },
+
+    #[error(
+        "Invalid checkpoint.{field}: {reason}\nFix: use an author like \"Ralph Orchestrator <ralph@ci>\", true/false or a key id for gpg_sign, and a one-line prefix.\nSee: docs/guide/configuration.md#checkpoint"
+    )]
+    InvalidCheckpoint { field: String, reason: String },
+
+    #[error(
+        "Invalid plugin '{plugin}': {reason}\nFix: check the 'plugins' section and any 'hats.<id>.plugin' references.\nSee: docs/reference/troubleshooting.md#plugins"
+    )]
+    InvalidPlugin { plugin: String, reason: String },
+
+    #[error(
+        "Invalid {field}: {reason}\nFix: set a non-empty 'image' and use 'host:container' mount specs.\nSee: docs/reference/troubleshooting.md#execution-environments"
+    )]
+    InvalidEnvironment { field: String, reason: String },
+
+    #[error(
+        "Invalid cli on hat '{hat}': {reason}\nFix: set the hat's cli fields as you would the top-level 'cli:' block.\nSee: docs/guide/configuration.md#hats"
+    )]
+    InvalidHatCli { hat: String, reason: String },
+
+    #[error(
+        "Invalid 'when' on hat '{hat}': {source}\nFix: use comparisons like \"iteration > 5\" or \"payload contains 'frontend'\".\nSee: docs/guide/configuration.md#hats"
+    )]
+    InvalidHatPredicate {
+        hat: String,
+        #[source]
+        source: PredicateError,
+    },
+
+    #[error(
+        "Invalid speculative config: {reason}\nFix: list two backends under 'speculative.backends' and set 'check' when using the check arbiter.\nSee: docs/guide/configuration.md#speculative"
+    )]
+    InvalidSpeculative { reason: String },
+
+    #[error(
+        "Invalid state_store config: {reason}\nFix: set 'state_store.bucket' or switch to the filesystem backend.\nSee: docs/guide/configuration.md#state_store"
+    )]
+    InvalidStateStore { reason: String },
+
+    #[error(
+        "Invalid 'when' on routing rule {index}: {source}\nFix: use comparisons like \"payload_len > 20000\" or \"topic starts_with 'plan.'\".\nSee: docs/guide/configuration.md#routing"
+    )]
+    InvalidRoutePredicate {
+        index: usize,
+        #[source]
+        source: PredicateError,
+    },
+
+    #[error(
+        "Invalid mutex_group '{group}' on hat '{hat}'\nFix: use letters, digits, '-' and '_' only.\nSee: docs/guide/configuration.md#hats"
+    )]
+    InvalidMutexGroup { hat: String, group: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = RalphConfig::default();
+        // Default config has no custom hats (uses default planner+builder)
+        assert!(config.hats.is_empty());
+        assert_eq!(config.event_loop.max_iterations, 100);
+        assert!(!config.verbose);
+        assert!(!config.features.preflight.enabled);
+        assert!(!config.features.preflight.strict);
+        assert!(config.features.preflight.skip.is_empty());
+    }
+
+    #[test]
+    fn test_parse_yaml_with_custom_hats() {
+        let yaml = r#"
+event_loop:
+  prompt_file: "TASK.md"
+  completion_promise: "DONE"
+  max_iterations: 50
+cli:
+  backend: "claude"
+hats:
+  implementer:
+    name: "Implementer"
+    triggers: ["task.*", "review.done"]
+    publishes: ["impl.done"]
+    instructions: "You are the implementation agent."
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        // Custom hats are defined
+        assert_eq!(config.hats.len(), 1);
+        assert_eq!(config.event_loop.prompt_file, "TASK.md");
+
+        let hat = config.hats.get("implementer").unwrap();
+        assert_eq!(hat.triggers.len(), 2);
+    }
+
+    #[test]
+    fn test_preflight_config_deserialize() {
+        let yaml = r#"
+features:
+  preflight:
+    enabled: true
+    strict: true
+    skip: ["telegram", "git"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.features.preflight.enabled);
+        assert!(config.features.preflight.strict);
+        assert_eq!(
+            config.features.preflight.skip,
+            vec!["telegram".to_string(), "git".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_yaml_v1_format() {
+        // V1 flat format - identical to Python v1.x config
+        let yaml = r#"
+agent: gemini
+prompt_file: "TASK.md"
+completion_promise: "RALPH_DONE"
+max_iterations: 75
+max_runtime: 7200
+max_cost: 10.0
+verbose: true
+"#;
+        let mut config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+
+        // Before normalization, v2 fields have defaults
+        assert_eq!(config.cli.backend, "claude"); // default
+        assert_eq!(config.event_loop.max_iterations, 100); // default
+
+        // Normalize v1 -> v2
+        config.normalize();
+
+        // After normalization, v2 fields have v1 values
+        assert_eq!(config.cli.backend, "gemini");
+        assert_eq!(config.event_loop.prompt_file, "TASK.md");
+        assert_eq!(config.event_loop.completion_promise, "RALPH_DONE");
+        assert_eq!(config.event_loop.max_iterations, 75);
+        assert_eq!(config.event_loop.max_runtime_seconds, 7200);
+        assert_eq!(config.event_loop.max_cost_usd, Some(10.0));
+        assert!(config.verbose);
+    }
+
+    #[test]
+    fn test_agent_priority() {
+        let yaml = r"
+agent: auto
+agent_priority: [gemini, claude, codex]
+";
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let priority = config.get_agent_priority();
+        assert_eq!(priority, vec!["gemini", "claude", "codex"]);
+    }
+
+    #[test]
+    fn test_default_agent_priority() {
+        let config = RalphConfig::default();
+        let priority = config.get_agent_priority();
+        assert_eq!(priority, vec!["claude", "kiro", "gemini", "codex", "amp"]);
+    }
+
+    #[test]
+    fn test_validate_deferred_features() {
+        let yaml = r"
+archive_prompts: true
+enable_metrics: true
+";
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let warnings = config.validate().unwrap();
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings
+            .iter()
+            .any(|w| matches!(w, ConfigWarning::DeferredFeature { field, .. } if field == "archive_prompts")));
+        assert!(warnings
+            .iter()
+            .any(|w| matches!(w, ConfigWarning::DeferredFeature { field, .. } if field == "enable_metrics")));
+    }
+
+    #[test]
+    fn test_validate_dropped_fields() {
+        let yaml = r#"
+max_tokens: 4096
+retry_delay: 5
+adapters:
+  claude:
+    tool_permissions: ["read", "write"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let warnings = config.validate().unwrap();
+
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings.iter().any(
+            |w| matches!(w, ConfigWarning::DroppedField { field, .. } if field == "max_tokens")
+        ));
+        assert!(warnings.iter().any(
+            |w| matches!(w, ConfigWarning::DroppedField { field, .. } if field == "retry_delay")
+        ));
+        assert!(warnings
+            .iter()
+            .any(|w| matches!(w, ConfigWarning::DroppedField { field, .. } if field == "adapters.*.tool_permissions")));
+    }
+
+    #[test]
+    fn test_suppress_warnings() {
+        let yaml = r"
+_suppress_warnings: true
+archive_prompts: true
+max_tokens: 4096
+";
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let warnings = config.validate().unwrap();
+
+        // All warnings should be suppressed
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_adapter_settings() {
+        let yaml = r"
+adapters:
+  claude:
+    timeout: 600
+    enabled: true
+  gemini:
+    timeout: 300
+    enabled: false
+";
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+
+        let claude = config.adapter_settings("claude");
+        assert_eq!(claude.timeout, 600);
+        assert!(claude.enabled);
+
+        let gemini = config.adapter_settings("gemini");
+        assert_eq!(gemini.timeout, 300);
+        assert!(!gemini.enabled);
+    }
+
+    #[test]
+    fn test_retry_policy_matches_transient_failures() {
+        let yaml = r"
+cli:
+  retry:
+    max_retries: 2
+    backoff_seconds: 3
+    retry_on_exit_codes: [75]
+";
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let retry = &config.cli.retry;
+        assert_eq!(retry.max_retries, 2);
+        assert!(retry.is_transient(Some(75), ""));
+        assert!(retry.is_transient(Some(1), "API Error: 529 {\"type\":\"overloaded_error\"}"));
+        assert!(retry.is_transient(Some(1), "Rate limit reached, try again later"));
+        assert!(!retry.is_transient(Some(1), "error[E0308]: mismatched types"));
+
+        assert_eq!(retry.backoff(1), Duration::from_secs(3));
+        assert_eq!(retry.backoff(3), Duration::from_secs(12));
+    }
+
+    #[test]
+    fn test_iteration_timeout_overrides_adapter_timeout() {
+        let yaml = r"
+adapters:
+  gemini:
+    timeout: 300
+";
+        let mut config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.iteration_timeout("gemini"), Duration::from_mins(5));
+
+        config.event_loop.iteration_timeout_seconds = 90;
+        assert_eq!(config.iteration_timeout("gemini"), Duration::from_secs(90));
+        assert_eq!(config.iteration_timeout("claude"), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn test_unknown_fields_ignored() {
+        // Unknown fields should be silently ignored (forward compatibility)
+        let yaml = r#"
+agent: claude
+unknown_field: "some value"
+future_feature: true
+"#;
+        let result: Result<RalphConfig, _> = serde_yaml::from_str(yaml);
+        // Should parse successfully, ignoring unknown fields
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_ambiguous_routing_rejected() {
+        // Per spec: "Every trigger maps to exactly one hat | No ambiguous routing"
+        // Note: using semantic events since task.start is reserved
+        let yaml = r#"
+hats:
+  planner:
+    name: "Planner"
+    description: "Plans tasks"
+    triggers: ["planning.start", "build.done"]
+  builder:
+    name: "Builder"
+    description: "Builds code"
+    triggers: ["build.task", "build.done"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::AmbiguousRouting { trigger, .. } if trigger == "build.done"),
+            "Expected AmbiguousRouting error for 'build.done', got: {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_unique_triggers_accepted() {
+        // Valid config: each trigger maps to exactly one hat
+        // Note: task.start is reserved for Ralph, so use semantic events
+        let yaml = r#"
+hats:
+  planner:
+    name: "Planner"
+    description: "Plans tasks"
+    triggers: ["planning.start", "build.done", "build.blocked"]
+  builder:
+    name: "Builder"
+    description: "Builds code"
+    triggers: ["build.task"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(
+            result.is_ok(),
+            "Expected valid config, got: {:?}",
+            result.unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_reserved_trigger_task_start_rejected() {
+        // Per design: task.start is reserved for Ralph (the coordinator)
+        let yaml = r#"
+hats:
+  my_hat:
+    name: "My Hat"
+    description: "Test hat"
+    triggers: ["task.start"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::ReservedTrigger { trigger, hat }
+                if trigger == "task.start" && hat == "my_hat"),
+            "Expected ReservedTrigger error for 'task.start', got: {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_reserved_trigger_task_resume_rejected() {
+        // Per design: task.resume is reserved for Ralph (the coordinator)
+        let yaml = r#"
+hats:
+  my_hat:
+    name: "My Hat"
+    description: "Test hat"
+    triggers: ["task.resume", "other.event"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::ReservedTrigger { trigger, hat }
+                if trigger == "task.resume" && hat == "my_hat"),
+            "Expected ReservedTrigger error for 'task.resume', got: {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_missing_description_rejected() {
+        // Description is required for all hats
+        let yaml = r#"
+hats:
+  my_hat:
+    name: "My Hat"
+    triggers: ["build.task"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::MissingDescription { hat } if hat == "my_hat"),
+            "Expected MissingDescription error, got: {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_empty_description_rejected() {
+        // Empty description should also be rejected
+        let yaml = r#"
+hats:
+  my_hat:
+    name: "My Hat"
+    description: "   "
+    triggers: ["build.task"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::MissingDescription { hat } if hat == "my_hat"),
+            "Expected MissingDescription error for empty description, got: {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_core_config_defaults() {
+        let config = RalphConfig::default();
+        assert_eq!(config.core.scratchpad, ".ralph/agent/scratchpad.md");
+        assert_eq!(config.core.specs_dir, ".ralph/specs/");
+        // Default guardrails per spec
+        assert_eq!(config.core.guardrails.len(), 5);
+        assert!(config.core.guardrails[0].contains("Fresh context"));
+        assert!(config.core.guardrails[1].contains("search first"));
+        assert!(config.core.guardrails[2].contains("Backpressure"));
+        assert!(config.core.guardrails[3].contains("Confidence protocol"));
+        assert!(config.core.guardrails[4].contains("Commit atomically"));
+    }
+
+    #[test]
+    fn test_core_config_customizable() {
+        let yaml = r#"
+core:
+  scratchpad: ".workspace/plan.md"
+  specs_dir: "./specifications/"
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.core.scratchpad, ".workspace/plan.md");
+        assert_eq!(config.core.specs_dir, "./specifications/");
+        // Guardrails should use defaults when not specified
+        assert_eq!(config.core.guardrails.len(), 5);
+    }
+
+    #[test]
+    fn test_core_config_custom_guardrails() {
+        let yaml = r#"
+core:
+  scratchpad: ".ralph/agent/scratchpad.md"
+  specs_dir: "./specs/"
+  guardrails:
+    - "Custom rule one"
+    - "Custom rule two"
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.core.guardrails.len(), 2);
+        assert_eq!(config.core.guardrails[0], "Custom rule one");
+        assert_eq!(config.core.guardrails[1], "Custom rule two");
+    }
+
+    #[test]
+    fn test_prompt_and_prompt_file_mutually_exclusive() {
+        // Both prompt and prompt_file specified in config should error
+        let yaml = r#"
+event_loop:
+  prompt: "inline text"
+  prompt_file: "custom.md"
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::MutuallyExclusive { field1, field2 }
+                if field1 == "event_loop.prompt" && field2 == "event_loop.prompt_file"),
+            "Expected MutuallyExclusive error, got: {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_prompt_with_default_prompt_file_allowed() {
+        // Having inline prompt with default prompt_file value should be OK
+        let yaml = r#"
+event_loop:
+  prompt: "inline text"
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(
+            result.is_ok(),
+            "Should allow inline prompt with default prompt_file"
+        );
+        assert_eq!(config.event_loop.prompt, Some("inline text".to_string()));
+        assert_eq!(config.event_loop.prompt_file, "PROMPT.md");
+    }
+
+    #[test]
+    fn test_custom_backend_requires_command() {
+        // Custom backend without command should error
+        let yaml = r#"
+cli:
+  backend: "custom"
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::CustomBackendRequiresCommand),
+            "Expected CustomBackendRequiresCommand error, got: {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_empty_completion_promise_rejected() {
+        let yaml = r#"
+event_loop:
+  completion_promise: "   "
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::InvalidCompletionPromise),
+            "Expected InvalidCompletionPromise error, got: {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_custom_backend_with_empty_command_errors() {
+        // Custom backend with empty command should error
+        let yaml = r#"
+cli:
+  backend: "custom"
+  command: ""
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::CustomBackendRequiresCommand),
+            "Expected CustomBackendRequiresCommand error, got: {:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_custom_backend_with_command_succeeds() {
+        // Custom backend with valid command should pass validation
+        let yaml = r#"
+cli:
+  backend: "custom"
+  command: "my-agent"
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(
+            result.is_ok(),
+            "Should allow custom backend with command: {:?}",
+            result.unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_custom_backend_requires_command_message_actionable() {
+        let err = ConfigError::CustomBackendRequiresCommand;
+        let msg = err.to_string();
+        assert!(msg.contains("cli.command"));
+        assert!(msg.contains("ralph init --backend custom"));
+        assert!(msg.contains("docs/reference/troubleshooting.md#custom-backend-command"));
+    }
+
+    #[test]
+    fn test_reserved_trigger_message_actionable() {
+        let err = ConfigError::ReservedTrigger {
+            trigger: "task.start".to_string(),
+            hat: "builder".to_string(),
+        };
+        let msg = err.to_string();
+        assert!(msg.contains("Reserved trigger"));
+        assert!(msg.contains("docs/reference/troubleshooting.md#reserved-trigger"));
+    }
+
+    #[test]
+    fn test_prompt_file_with_no_inline_allowed() {
+        // Having only prompt_file specified should be OK
+        let yaml = r#"
+event_loop:
+  prompt_file: "custom.md"
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let result = config.validate();
+
+        assert!(
+            result.is_ok(),
+            "Should allow prompt_file without inline prompt"
+        );
+        assert_eq!(config.event_loop.prompt, None);
+        assert_eq!(config.event_loop.prompt_file, "custom.md");
+    }
+
+    #[test]
+    fn test_default_prompt_file_value() {
+        let config = RalphConfig::default();
+        assert_eq!(config.event_loop.prompt_file, "PROMPT.md");
+        assert_eq!(config.event_loop.prompt, None);
     }
 
     #[test]
-    fn test_preflight_config_deserialize() {
-        let yaml = r#"
-features:
-  preflight:
-    enabled: true
-    strict: true
-    skip: ["telegram", "git"]
-"#;
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert!(config.features.preflight.enabled);
-        assert!(config.features.preflight.strict);
-        assert_eq!(
-            config.features.preflight.skip,
-            vec!["telegram".to_string(), "git".to_string()]
-        );
+    fn test_tui_config_default() {
+        let config = RalphConfig::default();
+        assert_eq!(config.tui.prefix_key, "ctrl-a");
     }
 
     #[test]
-    fn test_parse_yaml_v1_format() {
-        // V1 flat format - identical to Python v1.x config
+    fn test_tui_config_parse_ctrl_b() {
         let yaml = r#"
-agent: gemini
-prompt_file: "TASK.md"
-completion_promise: "RALPH_DONE"
-max_iterations: 75
-max_runtime: 7200
-max_cost: 10.0
-verbose: true
+tui:
+  prefix_key: "ctrl-b"
 "#;
-        let mut config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-
-        // Before normalization, v2 fields have defaults
-        assert_eq!(config.cli.backend, "claude"); // default
-        assert_eq!(config.event_loop.max_iterations, 100); // default
-
-        // Normalize v1 -> v2
-        config.normalize();
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let (key_code, key_modifiers) = config.tui.parse_prefix().unwrap();
 
-        // After normalization, v2 fields have v1 values
-        assert_eq!(config.cli.backend, "gemini");
-        assert_eq!(config.event_loop.prompt_file, "TASK.md");
-        assert_eq!(config.event_loop.completion_promise, "RALPH_DONE");
-        assert_eq!(config.event_loop.max_iterations, 75);
-        assert_eq!(config.event_loop.max_runtime_seconds, 7200);
-        assert_eq!(config.event_loop.max_cost_usd, Some(10.0));
-        assert!(config.verbose);
+        use crossterm::event::{KeyCode, KeyModifiers};
+        assert_eq!(key_code, KeyCode::Char('b'));
+        assert_eq!(key_modifiers, KeyModifiers::CONTROL);
     }
 
     #[test]
-    fn test_agent_priority() {
-        let yaml = r"
-agent: auto
-agent_priority: [gemini, claude, codex]
-";
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let priority = config.get_agent_priority();
-        assert_eq!(priority, vec!["gemini", "claude", "codex"]);
+    fn test_tui_config_parse_invalid_format() {
+        let tui_config = TuiConfig {
+            prefix_key: "invalid".to_string(),
+        };
+        let result = tui_config.parse_prefix();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Invalid prefix_key format"));
     }
 
     #[test]
-    fn test_default_agent_priority() {
-        let config = RalphConfig::default();
-        let priority = config.get_agent_priority();
-        assert_eq!(priority, vec!["claude", "kiro", "gemini", "codex", "amp"]);
+    fn test_tui_config_parse_invalid_modifier() {
+        let tui_config = TuiConfig {
+            prefix_key: "alt-a".to_string(),
+        };
+        let result = tui_config.parse_prefix();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Invalid modifier"));
     }
 
     #[test]
-    fn test_validate_deferred_features() {
-        let yaml = r"
-archive_prompts: true
-enable_metrics: true
-";
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let warnings = config.validate().unwrap();
+    fn test_tui_config_parse_invalid_key() {
+        let tui_config = TuiConfig {
+            prefix_key: "ctrl-abc".to_string(),
+        };
+        let result = tui_config.parse_prefix();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Invalid key"));
+    }
 
-        assert_eq!(warnings.len(), 2);
-        assert!(warnings
-            .iter()
-            .any(|w| matches!(w, ConfigWarning::DeferredFeature { field, .. } if field == "archive_prompts")));
-        assert!(warnings
-            .iter()
-            .any(|w| matches!(w, ConfigWarning::DeferredFeature { field, .. } if field == "enable_metrics")));
+    #[test]
+    fn test_hat_backend_named() {
+        let yaml = r#""claude""#;
+        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(backend.to_cli_backend(), "claude");
+        match backend {
+            HatBackend::Named(name) => assert_eq!(name, "claude"),
+            _ => panic!("Expected Named variant"),
+        }
     }
 
     #[test]
-    fn test_validate_dropped_fields() {
+    fn test_hat_backend_kiro_agent() {
         let yaml = r#"
-max_tokens: 4096
-retry_delay: 5
-adapters:
-  claude:
-    tool_permissions: ["read", "write"]
+type: "kiro"
+agent: "builder"
 "#;
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let warnings = config.validate().unwrap();
-
-        assert_eq!(warnings.len(), 3);
-        assert!(warnings.iter().any(
-            |w| matches!(w, ConfigWarning::DroppedField { field, .. } if field == "max_tokens")
-        ));
-        assert!(warnings.iter().any(
-            |w| matches!(w, ConfigWarning::DroppedField { field, .. } if field == "retry_delay")
-        ));
-        assert!(warnings
-            .iter()
-            .any(|w| matches!(w, ConfigWarning::DroppedField { field, .. } if field == "adapters.*.tool_permissions")));
+        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(backend.to_cli_backend(), "kiro");
+        match backend {
+            HatBackend::KiroAgent {
+                backend_type,
+                agent,
+                args,
+            } => {
+                assert_eq!(backend_type, "kiro");
+                assert_eq!(agent, "builder");
+                assert!(args.is_empty());
+            }
+            _ => panic!("Expected KiroAgent variant"),
+        }
     }
 
     #[test]
-    fn test_suppress_warnings() {
-        let yaml = r"
-_suppress_warnings: true
-archive_prompts: true
-max_tokens: 4096
-";
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let warnings = config.validate().unwrap();
+    fn test_hat_backend_kiro_agent_with_args() {
+        let yaml = r#"
+type: "kiro"
+agent: "builder"
+args: ["--verbose", "--debug"]
+"#;
+        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(backend.to_cli_backend(), "kiro");
+        match backend {
+            HatBackend::KiroAgent {
+                backend_type,
+                agent,
+                args,
+            } => {
+                assert_eq!(backend_type, "kiro");
+                assert_eq!(agent, "builder");
+                assert_eq!(args, vec!["--verbose", "--debug"]);
+            }
+            _ => panic!("Expected KiroAgent variant"),
+        }
+    }
 
-        // All warnings should be suppressed
-        assert!(warnings.is_empty());
+    #[test]
+    fn test_hat_backend_named_with_args() {
+        let yaml = r#"
+type: "claude"
+args: ["--model", "claude-sonnet-4"]
+"#;
+        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(backend.to_cli_backend(), "claude");
+        match backend {
+            HatBackend::NamedWithArgs { backend_type, args } => {
+                assert_eq!(backend_type, "claude");
+                assert_eq!(args, vec!["--model", "claude-sonnet-4"]);
+            }
+            _ => panic!("Expected NamedWithArgs variant"),
+        }
     }
 
     #[test]
-    fn test_adapter_settings() {
-        let yaml = r"
-adapters:
-  claude:
-    timeout: 600
-    enabled: true
-  gemini:
-    timeout: 300
-    enabled: false
-";
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+    fn test_hat_backend_named_with_args_empty() {
+        // type: claude without args should still work (NamedWithArgs with empty args)
+        let yaml = r#"
+type: "gemini"
+"#;
+        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(backend.to_cli_backend(), "gemini");
+        match backend {
+            HatBackend::NamedWithArgs { backend_type, args } => {
+                assert_eq!(backend_type, "gemini");
+                assert!(args.is_empty());
+            }
+            _ => panic!("Expected NamedWithArgs variant"),
+        }
+    }
 
-        let claude = config.adapter_settings("claude");
-        assert_eq!(claude.timeout, 600);
-        assert!(claude.enabled);
+    #[test]
+    fn test_hat_backend_custom() {
+        let yaml = r#"
+command: "/usr/bin/my-agent"
+args: ["--flag", "value"]
+"#;
+        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(backend.to_cli_backend(), "custom");
+        assert_eq!(backend.adapter_name(), "my-agent");
+        match backend {
+            HatBackend::Custom { command, args } => {
+                assert_eq!(command, "/usr/bin/my-agent");
+                assert_eq!(args, vec!["--flag", "value"]);
+            }
+            _ => panic!("Expected Custom variant"),
+        }
+    }
 
-        let gemini = config.adapter_settings("gemini");
-        assert_eq!(gemini.timeout, 300);
-        assert!(!gemini.enabled);
+    #[test]
+    fn test_hat_config_with_backend() {
+        let yaml = r#"
+name: "Custom Builder"
+triggers: ["build.task"]
+publishes: ["build.done"]
+instructions: "Build stuff"
+backend: "gemini"
+default_publishes: "task.done"
+"#;
+        let hat: HatConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(hat.name, "Custom Builder");
+        assert!(hat.backend.is_some());
+        match hat.backend.unwrap() {
+            HatBackend::Named(name) => assert_eq!(name, "gemini"),
+            _ => panic!("Expected Named backend"),
+        }
+        assert_eq!(hat.default_publishes, Some("task.done".to_string()));
     }
 
     #[test]
-    fn test_unknown_fields_ignored() {
-        // Unknown fields should be silently ignored (forward compatibility)
+    fn test_hat_config_without_backend() {
         let yaml = r#"
-agent: claude
-unknown_field: "some value"
-future_feature: true
+name: "Default Hat"
+triggers: ["task.start"]
+publishes: ["task.done"]
+instructions: "Do work"
 "#;
-        let result: Result<RalphConfig, _> = serde_yaml::from_str(yaml);
-        // Should parse successfully, ignoring unknown fields
-        assert!(result.is_ok());
+        let hat: HatConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(hat.name, "Default Hat");
+        assert!(hat.backend.is_none());
+        assert!(hat.default_publishes.is_none());
     }
 
     #[test]
-    fn test_ambiguous_routing_rejected() {
-        // Per spec: "Every trigger maps to exactly one hat | No ambiguous routing"
-        // Note: using semantic events since task.start is reserved
+    fn test_mixed_backends_config() {
         let yaml = r#"
+event_loop:
+  prompt_file: "TASK.md"
+  max_iterations: 50
+
+cli:
+  backend: "claude"
+
 hats:
   planner:
     name: "Planner"
-    description: "Plans tasks"
-    triggers: ["planning.start", "build.done"]
+    triggers: ["task.start"]
+    publishes: ["build.task"]
+    instructions: "Plan the work"
+    backend: "claude"
+    
   builder:
     name: "Builder"
-    description: "Builds code"
-    triggers: ["build.task", "build.done"]
+    triggers: ["build.task"]
+    publishes: ["build.done"]
+    instructions: "Build the thing"
+    backend:
+      type: "kiro"
+      agent: "builder"
+      
+  reviewer:
+    name: "Reviewer"
+    triggers: ["build.done"]
+    publishes: ["review.complete"]
+    instructions: "Review the work"
+    backend:
+      command: "/usr/local/bin/custom-agent"
+      args: ["--mode", "review"]
+    default_publishes: "review.complete"
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
+        assert_eq!(config.hats.len(), 3);
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(
-            matches!(&err, ConfigError::AmbiguousRouting { trigger, .. } if trigger == "build.done"),
-            "Expected AmbiguousRouting error for 'build.done', got: {:?}",
-            err
+        // Check planner (Named backend)
+        let planner = config.hats.get("planner").unwrap();
+        assert!(planner.backend.is_some());
+        match planner.backend.as_ref().unwrap() {
+            HatBackend::Named(name) => assert_eq!(name, "claude"),
+            _ => panic!("Expected Named backend for planner"),
+        }
+
+        // Check builder (KiroAgent backend)
+        let builder = config.hats.get("builder").unwrap();
+        assert!(builder.backend.is_some());
+        match builder.backend.as_ref().unwrap() {
+            HatBackend::KiroAgent {
+                backend_type,
+                agent,
+                args,
+            } => {
+                assert_eq!(backend_type, "kiro");
+                assert_eq!(agent, "builder");
+                assert!(args.is_empty());
+            }
+            _ => panic!("Expected KiroAgent backend for builder"),
+        }
+
+        // Check reviewer (Custom backend)
+        let reviewer = config.hats.get("reviewer").unwrap();
+        assert!(reviewer.backend.is_some());
+        match reviewer.backend.as_ref().unwrap() {
+            HatBackend::Custom { command, args } => {
+                assert_eq!(command, "/usr/local/bin/custom-agent");
+                assert_eq!(args, &vec!["--mode".to_string(), "review".to_string()]);
+            }
+            _ => panic!("Expected Custom backend for reviewer"),
+        }
+        assert_eq!(
+            reviewer.default_publishes,
+            Some("review.complete".to_string())
         );
     }
 
     #[test]
-    fn test_unique_triggers_accepted() {
-        // Valid config: each trigger maps to exactly one hat
-        // Note: task.start is reserved for Ralph, so use semantic events
-        let yaml = r#"
-hats:
-  planner:
-    name: "Planner"
-    description: "Plans tasks"
-    triggers: ["planning.start", "build.done", "build.blocked"]
-  builder:
-    name: "Builder"
-    description: "Builds code"
-    triggers: ["build.task"]
-"#;
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
-
+    fn test_features_config_auto_merge_defaults_to_false() {
+        // Per spec: auto_merge should default to false for safety
+        // This prevents automatic merging of parallel loop branches
+        let config = RalphConfig::default();
         assert!(
-            result.is_ok(),
-            "Expected valid config, got: {:?}",
-            result.unwrap_err()
+            !config.features.auto_merge,
+            "auto_merge should default to false"
         );
     }
 
     #[test]
-    fn test_reserved_trigger_task_start_rejected() {
-        // Per design: task.start is reserved for Ralph (the coordinator)
-        let yaml = r#"
-hats:
-  my_hat:
-    name: "My Hat"
-    description: "Test hat"
-    triggers: ["task.start"]
-"#;
+    fn test_features_config_auto_merge_from_yaml() {
+        // Users can opt into auto_merge via config
+        let yaml = r"
+features:
+  auto_merge: true
+";
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
-
-        assert!(result.is_err());
-        let err = result.unwrap_err();
         assert!(
-            matches!(&err, ConfigError::ReservedTrigger { trigger, hat }
-                if trigger == "task.start" && hat == "my_hat"),
-            "Expected ReservedTrigger error for 'task.start', got: {:?}",
-            err
+            config.features.auto_merge,
+            "auto_merge should be true when configured"
         );
     }
 
     #[test]
-    fn test_reserved_trigger_task_resume_rejected() {
-        // Per design: task.resume is reserved for Ralph (the coordinator)
-        let yaml = r#"
-hats:
-  my_hat:
-    name: "My Hat"
-    description: "Test hat"
-    triggers: ["task.resume", "other.event"]
-"#;
+    fn test_features_config_auto_merge_false_from_yaml() {
+        // Explicit false should work too
+        let yaml = r"
+features:
+  auto_merge: false
+";
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
-
-        assert!(result.is_err());
-        let err = result.unwrap_err();
         assert!(
-            matches!(&err, ConfigError::ReservedTrigger { trigger, hat }
-                if trigger == "task.resume" && hat == "my_hat"),
-            "Expected ReservedTrigger error for 'task.resume', got: {:?}",
-            err
+            !config.features.auto_merge,
+            "auto_merge should be false when explicitly configured"
         );
     }
 
     #[test]
-    fn test_missing_description_rejected() {
-        // Description is required for all hats
-        let yaml = r#"
-hats:
-  my_hat:
-    name: "My Hat"
-    triggers: ["build.task"]
-"#;
+    fn test_features_config_preserves_parallel_when_adding_auto_merge() {
+        // Ensure adding auto_merge doesn't break existing parallel feature
+        let yaml = r"
+features:
+  parallel: false
+  auto_merge: true
+";
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
+        assert!(!config.features.parallel, "parallel should be false");
+        assert!(config.features.auto_merge, "auto_merge should be true");
+    }
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(
-            matches!(&err, ConfigError::MissingDescription { hat } if hat == "my_hat"),
-            "Expected MissingDescription error, got: {:?}",
-            err
-        );
+    #[test]
+    fn test_skills_config_defaults_when_absent() {
+        // Configs without a skills: section should still parse (backwards compat)
+        let yaml = r"
+agent: claude
+";
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.skills.enabled);
+        assert!(config.skills.dirs.is_empty());
+        assert!(config.skills.overrides.is_empty());
     }
 
     #[test]
-    fn test_empty_description_rejected() {
-        // Empty description should also be rejected
+    fn test_skills_config_deserializes_all_fields() {
         let yaml = r#"
-hats:
-  my_hat:
-    name: "My Hat"
-    description: "   "
-    triggers: ["build.task"]
+skills:
+  enabled: true
+  dirs:
+    - ".claude/skills"
+    - "/shared/skills"
+  overrides:
+    pdd:
+      enabled: false
+    memories:
+      auto_inject: true
+      hats: ["ralph"]
+      backends: ["claude"]
+      tags: ["core"]
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
-
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(
-            matches!(&err, ConfigError::MissingDescription { hat } if hat == "my_hat"),
-            "Expected MissingDescription error for empty description, got: {:?}",
-            err
+        assert!(config.skills.enabled);
+        assert_eq!(config.skills.dirs.len(), 2);
+        assert_eq!(
+            config.skills.dirs[0],
+            std::path::PathBuf::from(".claude/skills")
         );
+        assert_eq!(config.skills.overrides.len(), 2);
+
+        let pdd = config.skills.overrides.get("pdd").unwrap();
+        assert_eq!(pdd.enabled, Some(false));
+
+        let memories = config.skills.overrides.get("memories").unwrap();
+        assert_eq!(memories.auto_inject, Some(true));
+        assert_eq!(memories.hats, vec!["ralph"]);
+        assert_eq!(memories.backends, vec!["claude"]);
+        assert_eq!(memories.tags, vec!["core"]);
     }
 
     #[test]
-    fn test_core_config_defaults() {
-        let config = RalphConfig::default();
-        assert_eq!(config.core.scratchpad, ".ralph/agent/scratchpad.md");
-        assert_eq!(config.core.specs_dir, ".ralph/specs/");
-        // Default guardrails per spec
-        assert_eq!(config.core.guardrails.len(), 5);
-        assert!(config.core.guardrails[0].contains("Fresh context"));
-        assert!(config.core.guardrails[1].contains("search first"));
-        assert!(config.core.guardrails[2].contains("Backpressure"));
-        assert!(config.core.guardrails[3].contains("Confidence protocol"));
-        assert!(config.core.guardrails[4].contains("Commit atomically"));
+    fn test_skills_config_disabled() {
+        let yaml = r"
+skills:
+  enabled: false
+";
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(!config.skills.enabled);
+        assert!(config.skills.dirs.is_empty());
     }
 
     #[test]
-    fn test_core_config_customizable() {
+    fn test_skill_override_partial_fields() {
         let yaml = r#"
-core:
-  scratchpad: ".workspace/plan.md"
-  specs_dir: "./specifications/"
+skills:
+  overrides:
+    my-skill:
+      hats: ["builder", "reviewer"]
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(config.core.scratchpad, ".workspace/plan.md");
-        assert_eq!(config.core.specs_dir, "./specifications/");
-        // Guardrails should use defaults when not specified
-        assert_eq!(config.core.guardrails.len(), 5);
+        let override_ = config.skills.overrides.get("my-skill").unwrap();
+        assert_eq!(override_.enabled, None);
+        assert_eq!(override_.auto_inject, None);
+        assert_eq!(override_.hats, vec!["builder", "reviewer"]);
+        assert!(override_.backends.is_empty());
+        assert!(override_.tags.is_empty());
     }
 
+    // ─────────────────────────────────────────────────────────────────────────
+    // ROBOT CONFIG TESTS
+    // ─────────────────────────────────────────────────────────────────────────
+
     #[test]
-    fn test_core_config_custom_guardrails() {
-        let yaml = r#"
-core:
-  scratchpad: ".ralph/agent/scratchpad.md"
-  specs_dir: "./specs/"
-  guardrails:
-    - "Custom rule one"
-    - "Custom rule two"
-"#;
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(config.core.guardrails.len(), 2);
-        assert_eq!(config.core.guardrails[0], "Custom rule one");
-        assert_eq!(config.core.guardrails[1], "Custom rule two");
+    fn test_robot_config_defaults_disabled() {
+        let config = RalphConfig::default();
+        assert!(!config.robot.enabled);
+        assert!(config.robot.timeout_seconds.is_none());
+        assert!(config.robot.telegram.is_none());
     }
 
     #[test]
-    fn test_prompt_and_prompt_file_mutually_exclusive() {
-        // Both prompt and prompt_file specified in config should error
-        let yaml = r#"
-event_loop:
-  prompt: "inline text"
-  prompt_file: "custom.md"
-"#;
+    fn test_robot_config_absent_parses_as_default() {
+        // Existing configs without RObot: section should still parse
+        let yaml = r"
+agent: claude
+";
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
-
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(
-            matches!(&err, ConfigError::MutuallyExclusive { field1, field2 }
-                if field1 == "event_loop.prompt" && field2 == "event_loop.prompt_file"),
-            "Expected MutuallyExclusive error, got: {:?}",
-            err
-        );
+        assert!(!config.robot.enabled);
+        assert!(config.robot.timeout_seconds.is_none());
     }
 
     #[test]
-    fn test_prompt_with_default_prompt_file_allowed() {
-        // Having inline prompt with default prompt_file value should be OK
+    fn test_robot_config_valid_full() {
         let yaml = r#"
-event_loop:
-  prompt: "inline text"
+RObot:
+  enabled: true
+  timeout_seconds: 300
+  telegram:
+    bot_token: "123456:ABC-DEF"
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
+        assert!(config.robot.enabled);
+        assert_eq!(config.robot.timeout_seconds, Some(300));
+        let telegram = config.robot.telegram.as_ref().unwrap();
+        assert_eq!(telegram.bot_token, Some("123456:ABC-DEF".to_string()));
 
-        assert!(
-            result.is_ok(),
-            "Should allow inline prompt with default prompt_file"
-        );
-        assert_eq!(config.event_loop.prompt, Some("inline text".to_string()));
-        assert_eq!(config.event_loop.prompt_file, "PROMPT.md");
+        // Validation should pass
+        assert!(config.validate().is_ok());
     }
 
     #[test]
-    fn test_custom_backend_requires_command() {
-        // Custom backend without command should error
-        let yaml = r#"
-cli:
-  backend: "custom"
-"#;
+    fn test_robot_config_disabled_skips_validation() {
+        // Disabled RObot config should pass validation even with missing fields
+        let yaml = r"
+RObot:
+  enabled: false
+";
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
-
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(
-            matches!(&err, ConfigError::CustomBackendRequiresCommand),
-            "Expected CustomBackendRequiresCommand error, got: {:?}",
-            err
-        );
+        assert!(!config.robot.enabled);
+        assert!(config.validate().is_ok());
     }
 
     #[test]
-    fn test_empty_completion_promise_rejected() {
+    fn test_robot_config_enabled_missing_timeout_fails() {
         let yaml = r#"
-event_loop:
-  completion_promise: "   "
+RObot:
+  enabled: true
+  telegram:
+    bot_token: "123456:ABC-DEF"
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
         let result = config.validate();
-
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(
-            matches!(&err, ConfigError::InvalidCompletionPromise),
-            "Expected InvalidCompletionPromise error, got: {:?}",
+            matches!(&err, ConfigError::RobotMissingField { field, .. }
+                if field == "RObot.timeout_seconds"),
+            "Expected RobotMissingField for timeout_seconds, got: {:?}",
             err
         );
     }
 
     #[test]
-    fn test_custom_backend_with_empty_command_errors() {
-        // Custom backend with empty command should error
-        let yaml = r#"
-cli:
-  backend: "custom"
-  command: ""
-"#;
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
-
+    fn test_robot_config_enabled_missing_timeout_and_token_fails_on_timeout_first() {
+        // Both timeout and token are missing, but timeout is checked first
+        let robot = RobotConfig {
+            enabled: true,
+            timeout_seconds: None,
+            checkin_interval_seconds: None,
+            telegram: None,
+        };
+        let result = robot.validate();
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(
-            matches!(&err, ConfigError::CustomBackendRequiresCommand),
-            "Expected CustomBackendRequiresCommand error, got: {:?}",
+            matches!(&err, ConfigError::RobotMissingField { field, .. }
+                if field == "RObot.timeout_seconds"),
+            "Expected timeout validation failure first, got: {:?}",
             err
         );
     }
 
     #[test]
-    fn test_custom_backend_with_command_succeeds() {
-        // Custom backend with valid command should pass validation
-        let yaml = r#"
-cli:
-  backend: "custom"
-  command: "my-agent"
-"#;
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
-
-        assert!(
-            result.is_ok(),
-            "Should allow custom backend with command: {:?}",
-            result.unwrap_err()
-        );
-    }
-
-    #[test]
-    fn test_custom_backend_requires_command_message_actionable() {
-        let err = ConfigError::CustomBackendRequiresCommand;
-        let msg = err.to_string();
-        assert!(msg.contains("cli.command"));
-        assert!(msg.contains("ralph init --backend custom"));
-        assert!(msg.contains("docs/reference/troubleshooting.md#custom-backend-command"));
-    }
-
-    #[test]
-    fn test_reserved_trigger_message_actionable() {
-        let err = ConfigError::ReservedTrigger {
-            trigger: "task.start".to_string(),
-            hat: "builder".to_string(),
+    fn test_robot_config_resolve_bot_token_from_config() {
+        // Config has a token — resolve_bot_token returns it
+        // (env var behavior is tested separately via integration tests since
+        // forbid(unsafe_code) prevents env var manipulation in unit tests)
+        let config = RobotConfig {
+            enabled: true,
+            timeout_seconds: Some(300),
+            checkin_interval_seconds: None,
+            telegram: Some(TelegramBotConfig {
+                bot_token: Some("config-token".to_string()),
+            }),
         };
-        let msg = err.to_string();
-        assert!(msg.contains("Reserved trigger"));
-        assert!(msg.contains("docs/reference/troubleshooting.md#reserved-trigger"));
-    }
-
-    #[test]
-    fn test_prompt_file_with_no_inline_allowed() {
-        // Having only prompt_file specified should be OK
-        let yaml = r#"
-event_loop:
-  prompt_file: "custom.md"
-"#;
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
 
-        assert!(
-            result.is_ok(),
-            "Should allow prompt_file without inline prompt"
-        );
-        assert_eq!(config.event_loop.prompt, None);
-        assert_eq!(config.event_loop.prompt_file, "custom.md");
+        // When RALPH_TELEGRAM_BOT_TOKEN is not set, config token is returned
+        // (Can't set/unset env vars in tests due to forbid(unsafe_code))
+        let resolved = config.resolve_bot_token();
+        // The result depends on whether RALPH_TELEGRAM_BOT_TOKEN is set in the
+        // test environment. We can at least assert it's Some.
+        assert!(resolved.is_some());
     }
 
     #[test]
-    fn test_default_prompt_file_value() {
-        let config = RalphConfig::default();
-        assert_eq!(config.event_loop.prompt_file, "PROMPT.md");
-        assert_eq!(config.event_loop.prompt, None);
-    }
+    fn test_robot_config_resolve_bot_token_none_without_config() {
+        // No config token and no telegram section
+        let config = RobotConfig {
+            enabled: true,
+            timeout_seconds: Some(300),
+            checkin_interval_seconds: None,
+            telegram: None,
+        };
 
-    #[test]
-    fn test_tui_config_default() {
-        let config = RalphConfig::default();
-        assert_eq!(config.tui.prefix_key, "ctrl-a");
+        // Without env var AND without config token, resolve returns None
+        // (unless RALPH_TELEGRAM_BOT_TOKEN happens to be set in test env)
+        let resolved = config.resolve_bot_token();
+        if std::env::var("RALPH_TELEGRAM_BOT_TOKEN").is_err() {
+            assert!(resolved.is_none());
+        }
     }
 
     #[test]
-    fn test_tui_config_parse_ctrl_b() {
-        let yaml = r#"
-tui:
-  prefix_key: "ctrl-b"
-"#;
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let (key_code, key_modifiers) = config.tui.parse_prefix().unwrap();
-
-        use crossterm::event::{KeyCode, KeyModifiers};
-        assert_eq!(key_code, KeyCode::Char('b'));
-        assert_eq!(key_modifiers, KeyModifiers::CONTROL);
+    fn test_robot_config_validate_with_config_token() {
+        // Validation passes when bot_token is in config
+        let robot = RobotConfig {
+            enabled: true,
+            timeout_seconds: Some(300),
+            checkin_interval_seconds: None,
+            telegram: Some(TelegramBotConfig {
+                bot_token: Some("test-token".to_string()),
+            }),
+        };
+        assert!(robot.validate().is_ok());
     }
 
     #[test]
-    fn test_tui_config_parse_invalid_format() {
-        let tui_config = TuiConfig {
-            prefix_key: "invalid".to_string(),
+    fn test_robot_config_validate_missing_telegram_section() {
+        // No telegram section at all and no env var → fails
+        // (Skip if env var happens to be set)
+        if std::env::var("RALPH_TELEGRAM_BOT_TOKEN").is_ok() {
+            return;
+        }
+
+        let robot = RobotConfig {
+            enabled: true,
+            timeout_seconds: Some(300),
+            checkin_interval_seconds: None,
+            telegram: None,
         };
-        let result = tui_config.parse_prefix();
+        let result = robot.validate();
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Invalid prefix_key format"));
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::RobotMissingField { field, .. }
+                if field == "RObot.telegram.bot_token"),
+            "Expected bot_token validation failure, got: {:?}",
+            err
+        );
     }
 
     #[test]
-    fn test_tui_config_parse_invalid_modifier() {
-        let tui_config = TuiConfig {
-            prefix_key: "alt-a".to_string(),
+    fn test_robot_config_validate_empty_bot_token() {
+        // telegram section present but bot_token is None
+        // (Skip if env var happens to be set)
+        if std::env::var("RALPH_TELEGRAM_BOT_TOKEN").is_ok() {
+            return;
+        }
+
+        let robot = RobotConfig {
+            enabled: true,
+            timeout_seconds: Some(300),
+            checkin_interval_seconds: None,
+            telegram: Some(TelegramBotConfig { bot_token: None }),
         };
-        let result = tui_config.parse_prefix();
+        let result = robot.validate();
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Invalid modifier"));
+        let err = result.unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::RobotMissingField { field, .. }
+                if field == "RObot.telegram.bot_token"),
+            "Expected bot_token validation failure, got: {:?}",
+            err
+        );
     }
 
     #[test]
-    fn test_tui_config_parse_invalid_key() {
-        let tui_config = TuiConfig {
-            prefix_key: "ctrl-abc".to_string(),
-        };
-        let result = tui_config.parse_prefix();
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Invalid key"));
+    fn test_extra_instructions_merged_during_normalize() {
+        let yaml = r#"
+_fragments:
+  shared_protocol: &shared_protocol |
+    ### Shared Protocol
+    Follow this protocol.
+
+hats:
+  builder:
+    name: "Builder"
+    triggers: ["build.start"]
+    instructions: |
+      ## BUILDER MODE
+      Build things.
+    extra_instructions:
+      - *shared_protocol
+"#;
+        let mut config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let hat = config.hats.get("builder").unwrap();
+
+        // Before normalize: extra_instructions has content, instructions does not include it
+        assert_eq!(hat.extra_instructions.len(), 1);
+        assert!(!hat.instructions.contains("Shared Protocol"));
+
+        config.normalize();
+
+        let hat = config.hats.get("builder").unwrap();
+        // After normalize: extra_instructions drained, instructions includes the fragment
+        assert!(hat.extra_instructions.is_empty());
+        assert!(hat.instructions.contains("## BUILDER MODE"));
+        assert!(hat.instructions.contains("### Shared Protocol"));
+        assert!(hat.instructions.contains("Follow this protocol."));
     }
 
     #[test]
-    fn test_hat_backend_named() {
-        let yaml = r#""claude""#;
-        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(backend.to_cli_backend(), "claude");
-        match backend {
-            HatBackend::Named(name) => assert_eq!(name, "claude"),
-            _ => panic!("Expected Named variant"),
-        }
+    fn test_extra_instructions_empty_by_default() {
+        let yaml = r#"
+hats:
+  simple:
+    name: "Simple"
+    triggers: ["start"]
+    instructions: "Do the thing."
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let hat = config.hats.get("simple").unwrap();
+        assert!(hat.extra_instructions.is_empty());
     }
 
     #[test]
-    fn test_hat_backend_kiro_agent() {
+    fn test_plugins_config_parses() {
         let yaml = r#"
-type: "kiro"
-agent: "builder"
+plugins:
+  - name: repo-map
+    path: .ralph/plugins/repo_map.wasm
+    kind: context
+  - name: linter
+    path: .ralph/plugins/linter.wasm
+    kind: hat
+    enabled: false
+hats:
+  linter:
+    name: "Linter"
+    description: "Deterministic lint pass"
+    triggers: ["lint.request"]
+    publishes: ["lint.done"]
+    plugin: linter
 "#;
-        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(backend.to_cli_backend(), "kiro");
-        match backend {
-            HatBackend::KiroAgent {
-                backend_type,
-                agent,
-                args,
-            } => {
-                assert_eq!(backend_type, "kiro");
-                assert_eq!(agent, "builder");
-                assert!(args.is_empty());
-            }
-            _ => panic!("Expected KiroAgent variant"),
-        }
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.plugins.len(), 2);
+        assert_eq!(config.plugins[0].kind, PluginKind::Context);
+        assert!(config.plugins[0].enabled);
+        assert!(!config.plugins[1].enabled);
+        assert_eq!(
+            config.hats.get("linter").unwrap().plugin.as_deref(),
+            Some("linter")
+        );
+        assert!(config.validate().is_ok());
     }
 
     #[test]
-    fn test_hat_backend_kiro_agent_with_args() {
+    fn test_hat_plugin_reference_must_exist() {
         let yaml = r#"
-type: "kiro"
-agent: "builder"
-args: ["--verbose", "--debug"]
+plugins:
+  - name: repo-map
+    path: repo_map.wasm
+    kind: context
+hats:
+  linter:
+    name: "Linter"
+    description: "Deterministic lint pass"
+    triggers: ["lint.request"]
+    plugin: repo-map
 "#;
-        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(backend.to_cli_backend(), "kiro");
-        match backend {
-            HatBackend::KiroAgent {
-                backend_type,
-                agent,
-                args,
-            } => {
-                assert_eq!(backend_type, "kiro");
-                assert_eq!(agent, "builder");
-                assert_eq!(args, vec!["--verbose", "--debug"]);
-            }
-            _ => panic!("Expected KiroAgent variant"),
-        }
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::InvalidPlugin { plugin, .. } if plugin == "repo-map"),
+            "Expected InvalidPlugin, got: {:?}",
+            err
+        );
     }
 
     #[test]
-    fn test_hat_backend_named_with_args() {
+    fn test_duplicate_plugin_names_rejected() {
+        let yaml = r"
+plugins:
+  - name: lint
+    path: a.wasm
+    kind: filter
+  - name: lint
+    path: b.wasm
+    kind: hat
+";
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidPlugin { .. })
+        ));
+    }
+
+    #[test]
+    fn test_scripts_config_parses() {
         let yaml = r#"
-type: "claude"
-args: ["--model", "claude-sonnet-4"]
+scripts:
+  route: |
+    if event.payload.contains("frontend") { "frontend_builder" }
+  max_operations: 5000
 "#;
-        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(backend.to_cli_backend(), "claude");
-        match backend {
-            HatBackend::NamedWithArgs { backend_type, args } => {
-                assert_eq!(backend_type, "claude");
-                assert_eq!(args, vec!["--model", "claude-sonnet-4"]);
-            }
-            _ => panic!("Expected NamedWithArgs variant"),
-        }
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.scripts.has_scripts());
+        assert!(config.scripts.route.is_some());
+        assert!(config.scripts.transform.is_none());
+        assert_eq!(config.scripts.max_operations, 5000);
+        assert!(!RalphConfig::default().scripts.has_scripts());
     }
 
     #[test]
-    fn test_hat_backend_named_with_args_empty() {
-        // type: claude without args should still work (NamedWithArgs with empty args)
+    fn test_environment_config_parses_with_hat_override() {
         let yaml = r#"
-type: "gemini"
+environment:
+  image: rust:1.80
+  mounts:
+    - ~/.cargo/registry:/usr/local/cargo/registry
+hats:
+  docs:
+    name: Docs
+    description: Writes docs
+    triggers: ["docs.start"]
+    environment:
+      image: node:20
+      runtime: podman
 "#;
-        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(backend.to_cli_backend(), "gemini");
-        match backend {
-            HatBackend::NamedWithArgs { backend_type, args } => {
-                assert_eq!(backend_type, "gemini");
-                assert!(args.is_empty());
-            }
-            _ => panic!("Expected NamedWithArgs variant"),
-        }
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let env = config.environment.as_ref().unwrap();
+        assert_eq!(env.image, "rust:1.80");
+        assert_eq!(env.runtime, "docker");
+        assert_eq!(env.mounts.len(), 1);
+
+        let hat_env = config.hats["docs"].environment.as_ref().unwrap();
+        assert_eq!(hat_env.image, "node:20");
+        assert_eq!(hat_env.runtime, "podman");
+        assert!(config.validate().is_ok());
     }
 
     #[test]
-    fn test_hat_backend_custom() {
+    fn test_routing_rules_parse_and_validate() {
         let yaml = r#"
-command: "/usr/bin/my-agent"
-args: ["--flag", "value"]
+hats:
+  planner:
+    name: Planner
+    description: Plans work
+    triggers: ["work.start"]
+routing:
+  - name: cheap-planning
+    hats: [planner]
+    model: haiku
+  - when: "payload_len > 20000"
+    backend:
+      type: gemini
+      args: ["--sandbox"]
+  - hats: [ghost]
 "#;
-        let backend: HatBackend = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(backend.to_cli_backend(), "custom");
-        match backend {
-            HatBackend::Custom { command, args } => {
-                assert_eq!(command, "/usr/bin/my-agent");
-                assert_eq!(args, vec!["--flag", "value"]);
-            }
-            _ => panic!("Expected Custom variant"),
-        }
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.routing.len(), 3);
+        assert_eq!(config.routing[0].model.as_deref(), Some("haiku"));
+        assert!(matches!(
+            config.routing[1].backend,
+            Some(HatBackend::NamedWithArgs { .. })
+        ));
+
+        let warnings = config.validate().unwrap();
+        let fields: Vec<_> = warnings
+            .iter()
+            .filter_map(|w| match w {
+                ConfigWarning::InvalidValue { field, .. } => Some(field.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(fields.contains(&"routing[2]"));
+        assert!(fields.contains(&"routing[2].hats"));
+
+        let bad: RalphConfig =
+            serde_yaml::from_str("routing:\n  - when: \"payload_len > 'x'\"\n    model: m\n")
+                .unwrap();
+        assert!(matches!(
+            bad.validate().unwrap_err(),
+            ConfigError::InvalidRoutePredicate { index: 0, .. }
+        ));
     }
 
     #[test]
-    fn test_hat_config_with_backend() {
+    fn test_speculative_config_validation() {
         let yaml = r#"
-name: "Custom Builder"
-triggers: ["build.task"]
-publishes: ["build.done"]
-instructions: "Build stuff"
-backend: "gemini"
-default_publishes: "task.done"
+speculative:
+  enabled: true
+  backends: [claude, gemini]
+  hats: [builder]
+  arbiter: check
+  check: "cargo test"
 "#;
-        let hat: HatConfig = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(hat.name, "Custom Builder");
-        assert!(hat.backend.is_some());
-        match hat.backend.unwrap() {
-            HatBackend::Named(name) => assert_eq!(name, "gemini"),
-            _ => panic!("Expected Named backend"),
-        }
-        assert_eq!(hat.default_publishes, Some("task.done".to_string()));
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.speculative.arbiter, ArbiterKind::Check);
+        assert!(config.speculative.applies_to("builder"));
+        assert!(!config.speculative.applies_to("reviewer"));
+        assert!(config.validate().is_ok());
+
+        let mut one_backend = config.clone();
+        one_backend.speculative.backends.pop();
+        assert!(matches!(
+            one_backend.validate().unwrap_err(),
+            ConfigError::InvalidSpeculative { .. }
+        ));
+
+        let mut no_check = config;
+        no_check.speculative.check = None;
+        assert!(matches!(
+            no_check.validate().unwrap_err(),
+            ConfigError::InvalidSpeculative { .. }
+        ));
+        assert!(!SpeculativeConfig::default().applies_to("builder"));
     }
 
     #[test]
-    fn test_hat_config_without_backend() {
+    fn test_environment_rejects_malformed_mount() {
         let yaml = r#"
-name: "Default Hat"
-triggers: ["task.start"]
-publishes: ["task.done"]
-instructions: "Do work"
+environment:
+  image: rust:1.80
+  mounts: ["/only-host-path"]
 "#;
-        let hat: HatConfig = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(hat.name, "Default Hat");
-        assert!(hat.backend.is_none());
-        assert!(hat.default_publishes.is_none());
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidEnvironment { .. }));
+        assert!(err.to_string().contains("/only-host-path"));
     }
 
     #[test]
-    fn test_mixed_backends_config() {
+    fn test_sandbox_resolves_to_locked_down_environment() {
         let yaml = r#"
-event_loop:
-  prompt_file: "TASK.md"
-  max_iterations: 50
+cli:
+  backend: claude
+  sandbox:
+    image: ghcr.io/acme/agent:latest
+    network: none
+    env: [ANTHROPIC_API_KEY]
+    run_args: ["--memory=4g"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        config.validate().unwrap();
+        let sandbox = config.cli.sandbox.as_ref().unwrap();
+        assert_eq!(sandbox.runtime, "docker");
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        let worktree = repo.join(".worktrees/loop-1");
+        std::fs::create_dir_all(&worktree).unwrap();
+
+        let env = sandbox.environment(repo, repo);
+        assert_eq!(env.image, "ghcr.io/acme/agent:latest");
+        let git = repo.join(".git").display().to_string();
+        assert_eq!(env.mounts, [format!("{git}:{git}:ro")]);
+        assert_eq!(env.env, vec!["ANTHROPIC_API_KEY"]);
+        assert_eq!(
+            env.run_args[..3],
+            [
+                "--network=none",
+                "--cap-drop=ALL",
+                "--security-opt=no-new-privileges"
+            ]
+        );
+        assert_eq!(env.run_args.last().unwrap(), "--memory=4g");
+
+        // Worktree loops also see the main repo's git metadata and agent state
+        let env = sandbox.environment(&worktree, repo);
+        let worktree_git = worktree.join(".git").display().to_string();
+        assert_eq!(env.mounts[0], format!("{worktree_git}:{worktree_git}:ro"));
+        assert_eq!(env.mounts[1], format!("{git}:{git}:ro"));
+        assert!(env.mounts[2].ends_with(".ralph/agent"));
+    }
 
+    #[test]
+    fn test_sandbox_excludes_environment() {
+        let yaml = r#"
 cli:
-  backend: "claude"
+  sandbox:
+    image: agent:latest
+hats:
+  builder:
+    name: "Builder"
+    description: "Builds"
+    triggers: ["build.task"]
+    environment:
+      image: rust:1.80
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(matches!(
+            config.validate().unwrap_err(),
+            ConfigError::MutuallyExclusive { field2, .. } if field2 == "hats.builder.environment"
+        ));
 
+        let config: RalphConfig =
+            serde_yaml::from_str("cli:\n  sandbox:\n    image: \" \"\n").unwrap();
+        assert!(matches!(
+            config.validate().unwrap_err(),
+            ConfigError::InvalidEnvironment { field, .. } if field == "cli.sandbox"
+        ));
+    }
+
+    #[test]
+    fn test_hat_cli_overrides_global_cli() {
+        let yaml = r#"
+cli:
+  backend: custom
+  command: my-agent
+  args: ["--fast"]
 hats:
   planner:
     name: "Planner"
-    triggers: ["task.start"]
-    publishes: ["build.task"]
-    instructions: "Plan the work"
-    backend: "claude"
-    
+    description: "Plans"
+    triggers: ["plan.request"]
+    cli:
+      model: claude-haiku-4-5
   builder:
     name: "Builder"
+    description: "Builds"
     triggers: ["build.task"]
-    publishes: ["build.done"]
-    instructions: "Build the thing"
-    backend:
-      type: "kiro"
-      agent: "builder"
-      
-  reviewer:
-    name: "Reviewer"
-    triggers: ["build.done"]
-    publishes: ["review.complete"]
-    instructions: "Review the work"
-    backend:
-      command: "/usr/local/bin/custom-agent"
-      args: ["--mode", "review"]
-    default_publishes: "review.complete"
+    cli:
+      backend: codex
+      prompt_mode: stdin
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(config.hats.len(), 3);
+        config.validate().unwrap();
+
+        let planner = config.hats["planner"].cli.as_ref().unwrap();
+        assert_eq!(planner.model.as_deref(), Some("claude-haiku-4-5"));
+        let cli = planner.apply(&config.cli);
+        assert_eq!(cli.backend, "custom");
+        assert_eq!(cli.command.as_deref(), Some("my-agent"));
+        assert_eq!(cli.args, ["--fast"]);
+
+        // Another backend doesn't inherit the global command or args
+        let cli = config.hats["builder"]
+            .cli
+            .as_ref()
+            .unwrap()
+            .apply(&config.cli);
+        assert_eq!(cli.backend, "codex");
+        assert!(cli.command.is_none());
+        assert!(cli.args.is_empty());
+        assert_eq!(cli.prompt_mode, "stdin");
+    }
 
-        // Check planner (Named backend)
-        let planner = config.hats.get("planner").unwrap();
-        assert!(planner.backend.is_some());
-        match planner.backend.as_ref().unwrap() {
-            HatBackend::Named(name) => assert_eq!(name, "claude"),
-            _ => panic!("Expected Named backend for planner"),
-        }
+    #[test]
+    fn test_hat_cli_validation() {
+        let hat = |extra: &str| {
+            format!(
+                "hats:\n  builder:\n    name: \"Builder\"\n    description: \"Builds\"\n    triggers: [\"build.task\"]\n{extra}"
+            )
+        };
+        let config: RalphConfig = serde_yaml::from_str(&hat(
+            "    backend: gemini\n    cli:\n      model: gemini-2.5-pro\n",
+        ))
+        .unwrap();
+        assert!(matches!(
+            config.validate().unwrap_err(),
+            ConfigError::MutuallyExclusive { field1, .. } if field1 == "hats.builder.backend"
+        ));
 
-        // Check builder (KiroAgent backend)
-        let builder = config.hats.get("builder").unwrap();
-        assert!(builder.backend.is_some());
-        match builder.backend.as_ref().unwrap() {
-            HatBackend::KiroAgent {
-                backend_type,
-                agent,
-                args,
-            } => {
-                assert_eq!(backend_type, "kiro");
-                assert_eq!(agent, "builder");
-                assert!(args.is_empty());
-            }
-            _ => panic!("Expected KiroAgent backend for builder"),
-        }
+        let config: RalphConfig = serde_yaml::from_str(&hat(
+            "    model: gpt-5\n    cli:\n      model: gpt-5-codex\n",
+        ))
+        .unwrap();
+        assert!(matches!(
+            config.validate().unwrap_err(),
+            ConfigError::MutuallyExclusive { field2, .. } if field2 == "hats.builder.cli.model"
+        ));
 
-        // Check reviewer (Custom backend)
-        let reviewer = config.hats.get("reviewer").unwrap();
-        assert!(reviewer.backend.is_some());
-        match reviewer.backend.as_ref().unwrap() {
-            HatBackend::Custom { command, args } => {
-                assert_eq!(command, "/usr/local/bin/custom-agent");
-                assert_eq!(args, &vec!["--mode".to_string(), "review".to_string()]);
-            }
-            _ => panic!("Expected Custom backend for reviewer"),
-        }
-        assert_eq!(
-            reviewer.default_publishes,
-            Some("review.complete".to_string())
-        );
+        let config: RalphConfig =
+            serde_yaml::from_str(&hat("    cli:\n      backend: custom\n")).unwrap();
+        assert!(matches!(
+            config.validate().unwrap_err(),
+            ConfigError::InvalidHatCli { hat, .. } if hat == "builder"
+        ));
+
+        let config: RalphConfig =
+            serde_yaml::from_str(&hat("    cli:\n      prompt_mode: pipe\n")).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("'arg' or 'stdin'"));
     }
 
     #[test]
-    fn test_features_config_auto_merge_defaults_to_false() {
-        // Per spec: auto_merge should default to false for safety
-        // This prevents automatic merging of parallel loop branches
+    fn test_state_store_config() {
         let config = RalphConfig::default();
-        assert!(
-            !config.features.auto_merge,
-            "auto_merge should default to false"
-        );
+        assert_eq!(config.state_store.backend, StateBackend::Filesystem);
+        assert!(config.state_store.path.is_none());
+
+        let yaml = "state_store:\n  backend: s3\n  prefix: repo/main\n";
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.state_store.backend, StateBackend::S3);
+        assert!(matches!(
+            config.validate().unwrap_err(),
+            ConfigError::InvalidStateStore { .. }
+        ));
+
+        let mut with_bucket = config;
+        with_bucket.state_store.bucket = Some("ci-state".to_string());
+        assert!(with_bucket.validate().is_ok());
     }
 
     #[test]
-    fn test_features_config_auto_merge_from_yaml() {
-        // Users can opt into auto_merge via config
-        let yaml = r"
-features:
-  auto_merge: true
-";
+    fn test_checkpoint_config() {
+        let config = RalphConfig::default();
+        assert_eq!(config.checkpoint.commit_options(), CommitOptions::default());
+
+        let yaml = r#"
+checkpoint:
+  author: "Ralph Orchestrator <ralph@ci>"
+  gpg_sign: "0xBOTKEY"
+  prefix: "[ralph]"
+"#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert!(
-            config.features.auto_merge,
-            "auto_merge should be true when configured"
+        assert!(config.validate().is_ok());
+        let options = config.checkpoint.commit_options();
+        assert_eq!(
+            options.identity,
+            Some(("Ralph Orchestrator".to_string(), "ralph@ci".to_string()))
+        );
+        assert_eq!(options.signing_key.as_deref(), Some("0xBOTKEY"));
+        assert_eq!(options.prefix.as_deref(), Some("[ralph]"));
+
+        let config: RalphConfig = serde_yaml::from_str("checkpoint:\n  gpg_sign: true\n").unwrap();
+        assert_eq!(
+            config.checkpoint.commit_options().signing_key.as_deref(),
+            Some("")
         );
+
+        for yaml in [
+            "checkpoint:\n  author: ralph@ci\n",
+            "checkpoint:\n  author: \"<ralph@ci>\"\n",
+            "checkpoint:\n  gpg_sign: \" \"\n",
+        ] {
+            let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+            assert!(
+                matches!(
+                    config.validate().unwrap_err(),
+                    ConfigError::InvalidCheckpoint { .. }
+                ),
+                "{yaml}"
+            );
+        }
     }
 
     #[test]
-    fn test_features_config_auto_merge_false_from_yaml() {
-        // Explicit false should work too
-        let yaml = r"
-features:
-  auto_merge: false
-";
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert!(
-            !config.features.auto_merge,
-            "auto_merge should be false when explicitly configured"
-        );
+    fn test_dashboard_config_defaults() {
+        let config: RalphConfig = serde_yaml::from_str("dashboard:\n  enabled: true\n").unwrap();
+        assert!(config.dashboard.enabled);
+        assert_eq!(config.dashboard.bind, "127.0.0.1:7070");
+        assert!(!RalphConfig::default().dashboard.enabled);
     }
 
     #[test]
-    fn test_features_config_preserves_parallel_when_adding_auto_merge() {
-        // Ensure adding auto_merge doesn't break existing parallel feature
+    fn test_cli_resource_limits_parse() {
         let yaml = r"
-features:
-  parallel: false
-  auto_merge: true
+cli:
+  backend: claude
+  limits:
+    cpu_seconds: 600
+    max_open_files: 1024
 ";
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert!(!config.features.parallel, "parallel should be false");
-        assert!(config.features.auto_merge, "auto_merge should be true");
+        let limits = config.cli.limits;
+        assert_eq!(limits.cpu_seconds, Some(600));
+        assert_eq!(limits.memory_mb, None);
+        assert_eq!(limits.max_open_files, Some(1024));
+        assert!(!limits.is_empty());
+        assert!(RalphConfig::default().cli.limits.is_empty());
     }
 
     #[test]
-    fn test_skills_config_defaults_when_absent() {
-        // Configs without a skills: section should still parse (backwards compat)
-        let yaml = r"
-agent: claude
-";
+    fn test_on_event_hooks_parse() {
+        let yaml = r#"
+on_event:
+  "build.done": ./notify.sh
+  "review.*": echo reviewed
+"#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert!(config.skills.enabled);
-        assert!(config.skills.dirs.is_empty());
-        assert!(config.skills.overrides.is_empty());
+        assert_eq!(config.on_event.len(), 2);
+        assert_eq!(config.on_event["build.done"], "./notify.sh");
+        assert!(RalphConfig::default().on_event.is_empty());
     }
 
     #[test]
-    fn test_skills_config_deserializes_all_fields() {
+    fn test_verify_config_selects_hats() {
         let yaml = r#"
-skills:
-  enabled: true
-  dirs:
-    - ".claude/skills"
-    - "/shared/skills"
-  overrides:
-    pdd:
-      enabled: false
-    memories:
-      auto_inject: true
-      hats: ["ralph"]
-      backends: ["claude"]
-      tags: ["core"]
+verify:
+  command: "cargo test --message-format json"
+  hats: [builder]
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert!(config.skills.enabled);
-        assert_eq!(config.skills.dirs.len(), 2);
+        assert_eq!(config.verify.timeout_seconds, 600);
         assert_eq!(
-            config.skills.dirs[0],
-            std::path::PathBuf::from(".claude/skills")
+            config.verify.command_for("builder"),
+            Some("cargo test --message-format json")
         );
-        assert_eq!(config.skills.overrides.len(), 2);
+        assert_eq!(config.verify.command_for("reviewer"), None);
+        assert_eq!(RalphConfig::default().verify.command_for("builder"), None);
+    }
 
-        let pdd = config.skills.overrides.get("pdd").unwrap();
-        assert_eq!(pdd.enabled, Some(false));
+    #[test]
+    fn test_verify_presets() {
+        let config: RalphConfig = serde_yaml::from_str("verify: rust").unwrap();
+        assert_eq!(config.verify.preset, Some(VerifyPreset::Rust));
+        let command = config.verify.command_for("builder").unwrap();
+        assert!(command.starts_with("cargo check --all-targets --message-format json && "));
+        assert!(command.contains("cargo clippy"));
+        assert!(command.ends_with("&& cargo test"));
+        assert_eq!(config.verify.parser(), OutputParser::Cargo);
 
-        let memories = config.skills.overrides.get("memories").unwrap();
-        assert_eq!(memories.auto_inject, Some(true));
-        assert_eq!(memories.hats, vec!["ralph"]);
-        assert_eq!(memories.backends, vec!["claude"]);
-        assert_eq!(memories.tags, vec!["core"]);
+        let yaml = r#"
+verify:
+  preset: node
+  command: "pnpm test"
+  triage: true
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.verify.command_for("builder"), Some("pnpm test"));
+        assert_eq!(config.verify.parser(), OutputParser::Node);
+        assert!(config.verify.triage);
+
+        assert!(serde_yaml::from_str::<RalphConfig>("verify: cobol").is_err());
     }
 
     #[test]
-    fn test_skills_config_disabled() {
+    fn test_credentials_parse_commands_and_aws_secrets() {
         let yaml = r"
-skills:
-  enabled: false
+credentials:
+  ANTHROPIC_API_KEY: op read op://dev/anthropic/api-key
+  GEMINI_API_KEY:
+    aws_secret: prod/gemini
+    key: api_key
 ";
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert!(!config.skills.enabled);
-        assert!(config.skills.dirs.is_empty());
+        assert_eq!(
+            config.credentials["ANTHROPIC_API_KEY"],
+            CredentialSource::Command("op read op://dev/anthropic/api-key".to_string())
+        );
+        assert_eq!(
+            config.credentials["GEMINI_API_KEY"],
+            CredentialSource::AwsSecret {
+                aws_secret: "prod/gemini".to_string(),
+                key: Some("api_key".to_string()),
+                region: None,
+            }
+        );
+        assert!(RalphConfig::default().credentials.is_empty());
     }
 
     #[test]
-    fn test_skill_override_partial_fields() {
+    fn test_hat_windows_parse_and_warn_when_empty() {
         let yaml = r#"
-skills:
-  overrides:
-    my-skill:
-      hats: ["builder", "reviewer"]
+hats:
+  deps:
+    name: Deps
+    description: Updates dependencies
+    triggers: ["deps.check"]
+    max_runtime_seconds: 900
+    windows:
+      - until_seconds: 3600
+      - from_seconds: 7200
+        until_seconds: 7200
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let override_ = config.skills.overrides.get("my-skill").unwrap();
-        assert_eq!(override_.enabled, None);
-        assert_eq!(override_.auto_inject, None);
-        assert_eq!(override_.hats, vec!["builder", "reviewer"]);
-        assert!(override_.backends.is_empty());
-        assert!(override_.tags.is_empty());
+        let hat = &config.hats["deps"];
+        assert_eq!(hat.max_runtime_seconds, Some(900));
+        assert!(hat.in_window(0));
+        assert!(!hat.in_window(3600));
+        assert!(!hat.in_window(7200));
+
+        let warnings = config.validate().unwrap();
+        assert!(warnings.iter().any(|w| matches!(
+            w,
+            ConfigWarning::InvalidValue { field, .. } if field == "hats.deps.windows[1]"
+        )));
     }
 
-    // ─────────────────────────────────────────────────────────────────────────
-    // ROBOT CONFIG TESTS
-    // ─────────────────────────────────────────────────────────────────────────
+    #[test]
+    fn test_start_event_parses_topic_and_payload() {
+        let bare: StartEvent = "review.request".parse().unwrap();
+        assert_eq!(bare.topic, "review.request");
+        assert_eq!(bare.payload, None);
+
+        let with_payload: StartEvent = "review.request:Check auth: tokens".parse().unwrap();
+        assert_eq!(with_payload.topic, "review.request");
+        assert_eq!(with_payload.payload.as_deref(), Some("Check auth: tokens"));
+
+        for bad in ["", ":payload", "build.*", "two words"] {
+            assert!(bad.parse::<StartEvent>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_mode_is_derived_from_hats() {
+        let solo: RalphConfig = serde_yaml::from_str("cli:\n  backend: claude\n").unwrap();
+        assert_eq!(solo.mode(), Mode::Single);
+        assert!(solo.validate().unwrap().is_empty());
+
+        let hats = r#"
+hats:
+  builder:
+    name: Builder
+    description: Builds it
+    triggers: ["build.task"]
+"#;
+        let team: RalphConfig = serde_yaml::from_str(hats).unwrap();
+        assert_eq!(team.mode(), Mode::Multi);
+    }
+
+    #[test]
+    fn test_explicit_mode_is_validated() {
+        let err = serde_yaml::from_str::<RalphConfig>("mode: singel\n").unwrap_err();
+        assert!(err.to_string().contains("unknown variant"));
+
+        // Agreeing with the hats is accepted, with a migration warning
+        let legacy: RalphConfig = serde_yaml::from_str("mode: solo\n").unwrap();
+        assert_eq!(legacy.mode, Some(Mode::Single));
+        let warnings = legacy.validate().unwrap();
+        assert!(
+            warnings
+                .iter()
+                .any(|w| matches!(w, ConfigWarning::DroppedField { field, .. } if field == "mode"))
+        );
+
+        // Contradicting them is an error rather than a silent fallback
+        let conflict: RalphConfig = serde_yaml::from_str(
+            r#"
+mode: single
+hats:
+  builder:
+    name: Builder
+    description: Builds it
+    triggers: ["build.task"]
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            conflict.validate(),
+            Err(ConfigError::ModeMismatch {
+                mode: Mode::Single,
+                hats: 1
+            })
+        ));
+        let multi: RalphConfig = serde_yaml::from_str("mode: multi\n").unwrap();
+        assert!(matches!(
+            multi.validate(),
+            Err(ConfigError::ModeMismatch {
+                mode: Mode::Multi,
+                hats: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn test_topics_are_normalized_at_load() {
+        let mut config: RalphConfig = serde_yaml::from_str(
+            r#"
+event_loop:
+  starting_event: " Build.Start"
+hats:
+  builder:
+    name: Builder
+    description: Builds it
+    triggers: ["Build.Start"]
+    publishes: ["Build.Done", "LOOP_COMPLETE"]
+"#,
+        )
+        .unwrap();
+        config.normalize();
+
+        let builder = &config.hats["builder"];
+        assert_eq!(builder.triggers, vec!["build.start"]);
+        assert_eq!(builder.publishes, vec!["build.done", "LOOP_COMPLETE"]);
+        assert_eq!(
+            config.event_loop.starting_event.as_deref(),
+            Some("build.start")
+        );
+    }
 
     #[test]
-    fn test_robot_config_defaults_disabled() {
-        let config = RalphConfig::default();
-        assert!(!config.robot.enabled);
-        assert!(config.robot.timeout_seconds.is_none());
-        assert!(config.robot.telegram.is_none());
+    fn test_bridge_endpoint_parsing() {
+        let bridge = |url: &str| BridgeConfig {
+            url: url.to_string(),
+            prefix: default_bridge_prefix(),
+            publish: default_bridge_publish(),
+            subscribe: Vec::new(),
+        };
+        assert_eq!(
+            bridge("nats://127.0.0.1").endpoint(),
+            Ok(BrokerEndpoint {
+                kind: BrokerKind::Nats,
+                host: "127.0.0.1".to_string(),
+                port: 4222,
+                user: None,
+                password: None,
+            })
+        );
+        assert_eq!(
+            bridge("redis://:secret@cache:6380/0").endpoint(),
+            Ok(BrokerEndpoint {
+                kind: BrokerKind::Redis,
+                host: "cache".to_string(),
+                port: 6380,
+                user: None,
+                password: Some("secret".to_string()),
+            })
+        );
+        let token = bridge("nats://s3cr3t@nats").endpoint().unwrap();
+        assert_eq!(
+            (token.user, token.password),
+            (None, Some("s3cr3t".to_string()))
+        );
+        assert!(bridge("amqp://broker").endpoint().is_err());
+        assert!(bridge("nats://host:port").endpoint().is_err());
+        assert!(bridge("nats://").endpoint().is_err());
     }
 
     #[test]
-    fn test_robot_config_absent_parses_as_default() {
-        // Existing configs without RObot: section should still parse
-        let yaml = r"
-agent: claude
-";
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert!(!config.robot.enabled);
-        assert!(config.robot.timeout_seconds.is_none());
+    fn test_invalid_bridge_is_rejected() {
+        let config: RalphConfig = serde_yaml::from_str(
+            r#"
+bridges:
+  - url: nats://localhost
+    subscribe: ["review.request"]
+  - url: redis://localhost
+    prefix: "ralph ci"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.bridges[0].prefix, "ralph");
+        assert_eq!(config.bridges[0].publish, vec!["*"]);
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidBridge { index: 1, .. })
+        ));
     }
 
     #[test]
-    fn test_robot_config_valid_full() {
+    fn test_invalid_mutex_group_is_rejected() {
         let yaml = r#"
-RObot:
-  enabled: true
-  timeout_seconds: 300
-  telegram:
-    bot_token: "123456:ABC-DEF"
+hats:
+  updater:
+    name: Updater
+    description: Updates dependencies
+    triggers: ["deps.update"]
+    mutex_group: "../manifests"
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert!(config.robot.enabled);
-        assert_eq!(config.robot.timeout_seconds, Some(300));
-        let telegram = config.robot.telegram.as_ref().unwrap();
-        assert_eq!(telegram.bot_token, Some("123456:ABC-DEF".to_string()));
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidMutexGroup { hat, .. }) if hat == "updater"
+        ));
 
-        // Validation should pass
+        let config: RalphConfig =
+            serde_yaml::from_str(&yaml.replace("../manifests", "manifests")).unwrap();
         assert!(config.validate().is_ok());
     }
 
     #[test]
-    fn test_robot_config_disabled_skips_validation() {
-        // Disabled RObot config should pass validation even with missing fields
-        let yaml = r"
-RObot:
-  enabled: false
-";
-        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        assert!(!config.robot.enabled);
+    fn test_debounce_windows() {
+        let config: RalphConfig = serde_yaml::from_str(
+            r#"
+debounce:
+  "test.failed": 30s
+  "build.*": 500ms
+  "review.*": 2
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.debounce["test.failed"].0, Duration::from_secs(30));
+        assert_eq!(config.debounce["build.*"].0, Duration::from_millis(500));
+        assert_eq!(config.debounce["review.*"].0, Duration::from_secs(2));
         assert!(config.validate().is_ok());
+
+        assert!(serde_yaml::from_str::<RalphConfig>("debounce:\n  test.failed: soon\n").is_err());
+        let config: RalphConfig = serde_yaml::from_str("debounce:\n  \"a..b\": 1s\n").unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidTopic { field, .. }) if field == "debounce"
+        ));
     }
 
     #[test]
-    fn test_robot_config_enabled_missing_timeout_fails() {
+    fn test_renames_and_aliases() {
         let yaml = r#"
-RObot:
-  enabled: true
-  telegram:
-    bot_token: "123456:ABC-DEF"
+hats:
+  builder:
+    name: Builder
+    description: Builds it
+    triggers: ["build.task"]
+    aliases: [implementer]
+  legacy_reviewer:
+    name: Old Reviewer
+    description: Reviews the old way
+    triggers: ["impl.done"]
+    deprecated: true
+events:
+  build.task:
+    renamed_from: [impl.task]
+  build.done:
+    renamed_from: [impl.done]
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let result = config.validate();
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(
-            matches!(&err, ConfigError::RobotMissingField { field, .. }
-                if field == "RObot.timeout_seconds"),
-            "Expected RobotMissingField for timeout_seconds, got: {:?}",
-            err
+        assert_eq!(
+            config.topic_renames(),
+            vec![("impl.done", "build.done"), ("impl.task", "build.task")]
         );
+        assert_eq!(config.hat_aliases(), vec![("implementer", "builder")]);
+
+        let warnings = config.validate().unwrap();
+        assert!(warnings.iter().any(|w| matches!(
+            w,
+            ConfigWarning::InvalidValue { field, message }
+                if field == "hats.legacy_reviewer.triggers" && message.contains("renamed to 'build.done'")
+        )));
+        assert!(warnings.iter().any(
+            |w| matches!(w, ConfigWarning::DeprecatedHat { hat } if hat == "legacy_reviewer")
+        ));
+
+        // An alias can't shadow a hat, and an old name can't still be in use
+        let mut config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        config.hats.get_mut("builder").unwrap().aliases = vec!["legacy_reviewer".to_string()];
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidRename { field, .. }) if field == "hats.builder.aliases"
+        ));
+        let mut config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        config.events.get_mut("build.done").unwrap().renamed_from = vec!["build.task".to_string()];
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidRename { .. })
+        ));
     }
 
     #[test]
-    fn test_robot_config_enabled_missing_timeout_and_token_fails_on_timeout_first() {
-        // Both timeout and token are missing, but timeout is checked first
-        let robot = RobotConfig {
-            enabled: true,
-            timeout_seconds: None,
-            checkin_interval_seconds: None,
-            telegram: None,
-        };
-        let result = robot.validate();
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(
-            matches!(&err, ConfigError::RobotMissingField { field, .. }
-                if field == "RObot.timeout_seconds"),
-            "Expected timeout validation failure first, got: {:?}",
-            err
+    fn test_hat_postprocess_pipeline() {
+        let yaml = r#"
+hats:
+  reviewer:
+    name: Reviewer
+    description: Reviews it
+    triggers: ["build.done"]
+    postprocess:
+      - strip_thinking
+      - apply_diff
+      - table_to_events: { topic: review.finding }
+      - table_to_events
+      - command: "scripts/redact.sh"
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            config.hats["reviewer"].postprocess,
+            vec![
+                Postprocessor::StripThinking,
+                Postprocessor::ApplyDiff,
+                Postprocessor::TableToEvents {
+                    topic: Some("review.finding".to_string())
+                },
+                Postprocessor::TableToEvents { topic: None },
+                Postprocessor::Command("scripts/redact.sh".to_string()),
+            ]
+        );
+        assert!(config.validate().is_ok());
+        let round_trip: RalphConfig =
+            serde_yaml::from_str(&serde_yaml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(
+            round_trip.hats["reviewer"].postprocess,
+            config.hats["reviewer"].postprocess
         );
-    }
 
-    #[test]
-    fn test_robot_config_resolve_bot_token_from_config() {
-        // Config has a token — resolve_bot_token returns it
-        // (env var behavior is tested separately via integration tests since
-        // forbid(unsafe_code) prevents env var manipulation in unit tests)
-        let config = RobotConfig {
-            enabled: true,
-            timeout_seconds: Some(300),
-            checkin_interval_seconds: None,
-            telegram: Some(TelegramBotConfig {
-                bot_token: Some("config-token".to_string()),
-            }),
-        };
+        let unknown = yaml.replace("- apply_diff", "- apply_patch");
+        assert!(serde_yaml::from_str::<RalphConfig>(&unknown).is_err());
 
-        // When RALPH_TELEGRAM_BOT_TOKEN is not set, config token is returned
-        // (Can't set/unset env vars in tests due to forbid(unsafe_code))
-        let resolved = config.resolve_bot_token();
-        // The result depends on whether RALPH_TELEGRAM_BOT_TOKEN is set in the
-        // test environment. We can at least assert it's Some.
-        assert!(resolved.is_some());
+        let invalid = yaml.replace("topic: review.finding", "topic: \"review finding\"");
+        let config: RalphConfig = serde_yaml::from_str(&invalid).unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidTopic { field, .. }) if field == "hats.reviewer.postprocess"
+        ));
     }
 
     #[test]
-    fn test_robot_config_resolve_bot_token_none_without_config() {
-        // No config token and no telegram section
-        let config = RobotConfig {
-            enabled: true,
-            timeout_seconds: Some(300),
-            checkin_interval_seconds: None,
-            telegram: None,
-        };
-
-        // Without env var AND without config token, resolve returns None
-        // (unless RALPH_TELEGRAM_BOT_TOKEN happens to be set in test env)
-        let resolved = config.resolve_bot_token();
-        if std::env::var("RALPH_TELEGRAM_BOT_TOKEN").is_err() {
-            assert!(resolved.is_none());
-        }
-    }
+    fn test_invalid_topic_is_rejected() {
+        let config: RalphConfig = serde_yaml::from_str(
+            r#"
+hats:
+  builder:
+    name: Builder
+    description: Builds it
+    triggers: ["build task"]
+"#,
+        )
+        .unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(matches!(
+            &err,
+            ConfigError::InvalidTopic { field, source: TopicError::InvalidSegment { .. } }
+                if field == "hats.builder.triggers"
+        ));
 
-    #[test]
-    fn test_robot_config_validate_with_config_token() {
-        // Validation passes when bot_token is in config
-        let robot = RobotConfig {
-            enabled: true,
-            timeout_seconds: Some(300),
-            checkin_interval_seconds: None,
-            telegram: Some(TelegramBotConfig {
-                bot_token: Some("test-token".to_string()),
-            }),
-        };
-        assert!(robot.validate().is_ok());
+        let publishes_pattern: RalphConfig = serde_yaml::from_str(
+            r#"
+hats:
+  builder:
+    name: Builder
+    description: Builds it
+    triggers: ["build.task"]
+    publishes: ["build.*"]
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            publishes_pattern.validate(),
+            Err(ConfigError::InvalidTopic {
+                source: TopicError::Wildcard(_),
+                ..
+            })
+        ));
     }
 
     #[test]
-    fn test_robot_config_validate_missing_telegram_section() {
-        // No telegram section at all and no env var → fails
-        // (Skip if env var happens to be set)
-        if std::env::var("RALPH_TELEGRAM_BOT_TOKEN").is_ok() {
-            return;
-        }
-
-        let robot = RobotConfig {
-            enabled: true,
-            timeout_seconds: Some(300),
-            checkin_interval_seconds: None,
-            telegram: None,
-        };
-        let result = robot.validate();
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(
-            matches!(&err, ConfigError::RobotMissingField { field, .. }
-                if field == "RObot.telegram.bot_token"),
-            "Expected bot_token validation failure, got: {:?}",
-            err
+    fn test_near_miss_topics_warn() {
+        let config: RalphConfig = serde_yaml::from_str(
+            r#"
+hats:
+  builder:
+    name: Builder
+    description: Builds it
+    triggers: ["build.task"]
+    publishes: ["build.done"]
+  reviewer:
+    name: Reviewer
+    description: Reviews it
+    triggers: ["build_done"]
+    publishes: ["review.approved", "build.task"]
+"#,
+        )
+        .unwrap();
+        let near_misses: Vec<_> = config
+            .validate()
+            .unwrap()
+            .into_iter()
+            .filter(|w| matches!(w, ConfigWarning::NearMissTopic { .. }))
+            .collect();
+
+        // Reported once per pair, from the publishing side
+        assert_eq!(near_misses.len(), 1);
+        assert_eq!(
+            near_misses[0].to_string(),
+            "Warning [hats.builder.publishes]: 'build.done' is never matched - did you mean 'build_done'?"
         );
     }
 
     #[test]
-    fn test_robot_config_validate_empty_bot_token() {
-        // telegram section present but bot_token is None
-        // (Skip if env var happens to be set)
-        if std::env::var("RALPH_TELEGRAM_BOT_TOKEN").is_ok() {
-            return;
-        }
-
-        let robot = RobotConfig {
-            enabled: true,
-            timeout_seconds: Some(300),
-            checkin_interval_seconds: None,
-            telegram: Some(TelegramBotConfig { bot_token: None }),
-        };
-        let result = robot.validate();
-        assert!(result.is_err());
-        let err = result.unwrap_err();
+    fn test_matched_topics_are_not_near_misses() {
+        let config: RalphConfig = serde_yaml::from_str(
+            r#"
+hats:
+  planner:
+    name: Planner
+    description: Plans it
+    triggers: ["plan.v1"]
+    publishes: ["plan.v2"]
+  builder:
+    name: Builder
+    description: Builds it
+    triggers: ["plan.v2"]
+    publishes: ["plan.v1"]
+"#,
+        )
+        .unwrap();
+        let warnings = config.validate().unwrap();
         assert!(
-            matches!(&err, ConfigError::RobotMissingField { field, .. }
-                if field == "RObot.telegram.bot_token"),
-            "Expected bot_token validation failure, got: {:?}",
-            err
+            !warnings
+                .iter()
+                .any(|w| matches!(w, ConfigWarning::NearMissTopic { .. }))
         );
+        assert_eq!(edit_distance("review.done", "reveiw.done"), 2);
+        assert!(is_near_miss("review.done", "review.don"));
+        assert!(is_near_miss("build.done", "Build.Done"));
+        // Too short for a single edit to look like a typo
+        assert!(!is_near_miss("a.ok", "a.on"));
     }
 
     #[test]
-    fn test_extra_instructions_merged_during_normalize() {
+    fn test_hat_generation_parses_inline() {
         let yaml = r#"
-_fragments:
-  shared_protocol: &shared_protocol |
-    ### Shared Protocol
-    Follow this protocol.
-
 hats:
-  builder:
-    name: "Builder"
-    triggers: ["build.start"]
-    instructions: |
-      ## BUILDER MODE
-      Build things.
-    extra_instructions:
-      - *shared_protocol
+  planner:
+    name: Planner
+    description: Plans the work
+    triggers: ["plan.request"]
+    reasoning_effort: high
+  formatter:
+    name: Formatter
+    description: Formats output
+    triggers: ["build.done"]
+    model: claude-haiku-4-5
+    temperature: 3.0
+    max_output_tokens: 2048
 "#;
-        let mut config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let hat = config.hats.get("builder").unwrap();
-
-        // Before normalize: extra_instructions has content, instructions does not include it
-        assert_eq!(hat.extra_instructions.len(), 1);
-        assert!(!hat.instructions.contains("Shared Protocol"));
-
-        config.normalize();
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            config.hats["planner"].generation,
+            GenerationConfig {
+                reasoning_effort: Some(ReasoningEffort::High),
+                ..GenerationConfig::default()
+            }
+        );
+        let formatter = &config.hats["formatter"].generation;
+        assert_eq!(formatter.model.as_deref(), Some("claude-haiku-4-5"));
+        assert_eq!(formatter.max_output_tokens, Some(2048));
+        assert_eq!(config.hats["formatter"].triggers, vec!["build.done"]);
 
-        let hat = config.hats.get("builder").unwrap();
-        // After normalize: extra_instructions drained, instructions includes the fragment
-        assert!(hat.extra_instructions.is_empty());
-        assert!(hat.instructions.contains("## BUILDER MODE"));
-        assert!(hat.instructions.contains("### Shared Protocol"));
-        assert!(hat.instructions.contains("Follow this protocol."));
+        let warnings = config.validate().unwrap();
+        assert!(warnings.iter().any(|w| matches!(
+            w,
+            ConfigWarning::InvalidValue { field, .. } if field == "hats.formatter.temperature"
+        )));
     }
 
     #[test]
-    fn test_extra_instructions_empty_by_default() {
+    fn test_hat_scouts_parse_with_defaults() {
         let yaml = r#"
 hats:
-  simple:
-    name: "Simple"
-    triggers: ["start"]
-    instructions: "Do the thing."
+  builder:
+    name: Builder
+    description: Builds things
+    triggers: ["build.task"]
+    scouts:
+      prompts: ["Summarize src/parser.rs", "List open TODOs"]
+      backend: gemini
+  reviewer:
+    name: Reviewer
+    description: Reviews changes
+    triggers: ["review.request"]
+    scouts:
+      prompts: ["Summarize the diff"]
+      max_parallel: 0
 "#;
         let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
-        let hat = config.hats.get("simple").unwrap();
-        assert!(hat.extra_instructions.is_empty());
+        let scouts = config.hats["builder"].scouts.as_ref().unwrap();
+        assert_eq!(scouts.prompts.len(), 2);
+        assert_eq!(scouts.max_parallel, 3);
+        assert_eq!(scouts.timeout_seconds, 300);
+        assert!(matches!(&scouts.backend, Some(HatBackend::Named(name)) if name == "gemini"));
+
+        let warnings = config.validate().unwrap();
+        assert!(warnings.iter().any(|w| matches!(
+            w,
+            ConfigWarning::InvalidValue { field, .. } if field == "hats.reviewer.scouts.max_parallel"
+        )));
     }
 }
@@ -0,0 +1,188 @@
+//! Handoff contracts between hats.
+//!
+//! An event declared under `events:` can list the payload fields its
+//! publisher must include:
+//!
+//! ```yaml
+//! events:
+//!   build.task:
+//!     fields: [files, acceptance]
+//! ```
+//!
+//! The contract is rendered into the prompt of whoever publishes the topic,
+//! and events read back from the agent are checked against it. A payload
+//! missing a field is replaced by a `contract.violation` event so the
+//! publisher gets told what to fix instead of the receiver working blind.
+//!
+//! A field counts as present when the payload has it as a key: a
+//! `files: ...` line (bullets and bold markers allowed), a `files: ...`
+//! entry in a comma-separated payload, or a `"files": ...` JSON key.
+
+use crate::config::EventMetadata;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+
+/// Topic published when an event payload breaks its contract.
+pub const VIOLATION_TOPIC: &str = "contract.violation";
+
+/// Required payload fields, keyed by topic.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Contracts {
+    fields: BTreeMap<String, Vec<String>>,
+}
+
+impl Contracts {
+    /// Collects the contracts declared in the `events:` config section.
+    pub fn from_events(events: &HashMap<String, EventMetadata>) -> Self {
+        let fields = events
+            .iter()
+            .filter(|(_, meta)| !meta.fields.is_empty())
+            .map(|(topic, meta)| (topic.clone(), meta.fields.clone()))
+            .collect();
+        Self { fields }
+    }
+
+    /// Returns true when no topic declares required fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns the required fields for `topic`, if it has a contract.
+    pub fn fields(&self, topic: &str) -> Option<&[String]> {
+        self.fields.get(topic).map(Vec::as_slice)
+    }
+
+    /// Returns the required fields missing from `payload` for `topic`.
+    ///
+    /// Topics without a contract never miss anything.
+    pub fn missing(&self, topic: &str, payload: &str) -> Vec<String> {
+        self.fields(topic)
+            .map(|fields| missing_fields(payload, fields))
+            .unwrap_or_default()
+    }
+
+    /// Builds the `contract.violation` payload for an event missing fields.
+    pub fn violation_payload(topic: &str, missing: &[String], payload: &str) -> String {
+        format!(
+            "`{topic}` was dropped: its payload is missing required field(s) {}. \
+             Publish it again with every field as a `field: value` line.\n\nPayload: {payload}",
+            quote_list(missing)
+        )
+    }
+
+    /// Renders the `### Handoff Contracts` prompt section for the given topics.
+    ///
+    /// Returns an empty string when none of the topics has a contract.
+    pub fn prompt_section<'a>(&self, topics: impl IntoIterator<Item = &'a str>) -> String {
+        let mut lines = Vec::new();
+        for topic in topics {
+            if let Some(fields) = self.fields(topic)
+                && !lines.iter().any(|(t, _)| *t == topic)
+            {
+                lines.push((topic, fields));
+            }
+        }
+        if lines.is_empty() {
+            return String::new();
+        }
+
+        let mut section = String::from(
+            "### Handoff Contracts\n\n\
+             These payloads MUST include each listed field as a `field: value` line. \
+             Events missing a field are rejected with `contract.violation`.\n\n",
+        );
+        for (topic, fields) in lines {
+            section.push_str(&format!("- `{topic}`: {}\n", quote_list(fields)));
+        }
+        section.push('\n');
+        section
+    }
+}
+
+/// Returns the entries of `fields` that `payload` doesn't include as keys.
+pub fn missing_fields(payload: &str, fields: &[String]) -> Vec<String> {
+    fields
+        .iter()
+        .filter(|field| !has_field(payload, field))
+        .cloned()
+        .collect()
+}
+
+fn has_field(payload: &str, field: &str) -> bool {
+    let pattern = format!(
+        r#"(?im)(?:^|[\s,;{{(*\-"])\*{{0,2}}{}\*{{0,2}}"?\s*:"#,
+        regex::escape(field)
+    );
+    Regex::new(&pattern).is_ok_and(|re| re.is_match(payload))
+}
+
+fn quote_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("`{item}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contracts() -> Contracts {
+        let mut events = HashMap::new();
+        events.insert(
+            "build.task".to_string(),
+            EventMetadata {
+                fields: vec!["files".to_string(), "acceptance".to_string()],
+                ..EventMetadata::default()
+            },
+        );
+        events.insert("build.done".to_string(), EventMetadata::default());
+        Contracts::from_events(&events)
+    }
+
+    #[test]
+    fn test_from_events_skips_topics_without_fields() {
+        let contracts = contracts();
+        assert!(contracts.fields("build.task").is_some());
+        assert!(contracts.fields("build.done").is_none());
+    }
+
+    #[test]
+    fn test_missing_fields_line_formats() {
+        let fields = vec!["files".to_string(), "acceptance".to_string()];
+
+        let lines = "Fix the parser\nfiles: src/parser.rs\n- **Acceptance**: tests pass";
+        assert!(missing_fields(lines, &fields).is_empty());
+
+        let inline = "files: src/a.rs, acceptance: tests pass";
+        assert!(missing_fields(inline, &fields).is_empty());
+
+        let json = r#"{"files": ["src/a.rs"], "acceptance": "tests pass"}"#;
+        assert!(missing_fields(json, &fields).is_empty());
+
+        let partial = "Fix the parser. profiles: none\nfiles: src/parser.rs";
+        assert_eq!(missing_fields(partial, &fields), vec!["acceptance"]);
+    }
+
+    #[test]
+    fn test_missing_without_contract_is_empty() {
+        assert!(contracts().missing("build.done", "").is_empty());
+        assert_eq!(contracts().missing("build.task", "do it").len(), 2);
+    }
+
+    #[test]
+    fn test_prompt_section_lists_only_contracted_topics() {
+        let section = contracts().prompt_section(["build.done", "build.task", "build.task"]);
+        assert!(section.starts_with("### Handoff Contracts"));
+        assert_eq!(
+            section
+                .matches("- `build.task`: `files`, `acceptance`")
+                .count(),
+            1
+        );
+        assert!(!section.contains("build.done"));
+
+        assert!(contracts().prompt_section(["build.done"]).is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+//! Cost and token attribution.
+//!
+//! Backends report what an iteration spent as a [`Usage`]. The event loop
+//! attributes each report to the hat that ran and the topic that triggered it,
+//! keeping running totals in a [`CostLedger`] on `LoopState`. The same
+//! attribution is appended to the loop history as a [`CostEntry`], so
+//! `ralph cost` can rebuild the breakdown after the run.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+
+/// Dollars and tokens spent by one backend invocation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    /// Estimated cost in USD as reported by the backend.
+    #[serde(default)]
+    pub cost_usd: f64,
+    /// Prompt tokens.
+    #[serde(default)]
+    pub input_tokens: u64,
+    /// Completion tokens.
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
+impl Usage {
+    /// Creates a usage record.
+    pub fn new(cost_usd: f64, input_tokens: u64, output_tokens: u64) -> Self {
+        Self {
+            cost_usd,
+            input_tokens,
+            output_tokens,
+        }
+    }
+
+    /// Returns input plus output tokens.
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+impl AddAssign for Usage {
+    fn add_assign(&mut self, other: Self) {
+        self.cost_usd += other.cost_usd;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+    }
+}
+
+/// One iteration's spend, attributed to a hat and its triggering topic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostEntry {
+    /// Iteration that incurred the cost.
+    pub iteration: u32,
+    /// Hat that was active when the backend ran.
+    pub hat: String,
+    /// Topic of the event that activated the hat, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// What was spent.
+    #[serde(flatten)]
+    pub usage: Usage,
+}
+
+/// Running totals per hat and per triggering topic.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CostLedger {
+    /// Spend across the whole loop.
+    pub total: Usage,
+    /// Spend keyed by hat ID.
+    pub by_hat: BTreeMap<String, Usage>,
+    /// Spend keyed by triggering topic.
+    pub by_topic: BTreeMap<String, Usage>,
+}
+
+impl CostLedger {
+    /// Adds an entry to the totals.
+    pub fn record(&mut self, entry: &CostEntry) {
+        self.total += entry.usage;
+        *self.by_hat.entry(entry.hat.clone()).or_default() += entry.usage;
+        if let Some(topic) = &entry.topic {
+            *self.by_topic.entry(topic.clone()).or_default() += entry.usage;
+        }
+    }
+
+    /// Returns what `hat` has spent so far.
+    pub fn hat(&self, hat: &str) -> Usage {
+        self.by_hat.get(hat).copied().unwrap_or_default()
+    }
+
+    /// Builds a ledger from recorded entries.
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a CostEntry>) -> Self {
+        let mut ledger = Self::default();
+        for entry in entries {
+            ledger.record(entry);
+        }
+        ledger
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hat: &str, topic: Option<&str>, cost: f64) -> CostEntry {
+        CostEntry {
+            iteration: 1,
+            hat: hat.to_string(),
+            topic: topic.map(str::to_string),
+            usage: Usage::new(cost, 100, 10),
+        }
+    }
+
+    #[test]
+    fn test_ledger_attributes_by_hat_and_topic() {
+        let ledger = CostLedger::from_entries(&[
+            entry("builder", Some("build.task"), 0.50),
+            entry("reviewer", Some("review.request"), 0.25),
+            entry("builder", Some("build.task"), 0.25),
+            entry("ralph", None, 0.10),
+        ]);
+
+        assert!((ledger.total.cost_usd - 1.10).abs() < 1e-9);
+        assert!((ledger.hat("builder").cost_usd - 0.75).abs() < 1e-9);
+        assert_eq!(ledger.hat("builder").total_tokens(), 220);
+        assert!((ledger.by_topic["review.request"].cost_usd - 0.25).abs() < 1e-9);
+        assert_eq!(ledger.by_topic.len(), 2);
+        assert_eq!(ledger.hat("unknown"), Usage::default());
+    }
+
+    #[test]
+    fn test_entry_serializes_flat() {
+        let json = serde_json::to_value(entry("builder", None, 0.5)).unwrap();
+        assert_eq!(json["hat"], "builder");
+        assert_eq!(json["cost_usd"], 0.5);
+        assert_eq!(json["input_tokens"], 100);
+        assert!(json.get("topic").is_none());
+    }
+}
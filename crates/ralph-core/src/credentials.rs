@@ -0,0 +1,213 @@
+//! Backend credentials fetched from secret managers.
+//!
+//! The `credentials` config maps environment variable names to a source: a
+//! shell command that prints the secret (`op read ...`, `pass show ...`) or a
+//! secret in AWS Secrets Manager. [`resolve`] runs every source once at
+//! startup; the loop sets the results in each backend's environment. Values
+//! are never logged, and error messages name the variable, not the secret.
+
+use crate::config::{CredentialSource, RalphConfig};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Errors from resolving credentials.
+#[derive(Debug, thiserror::Error)]
+pub enum CredentialError {
+    #[error("Invalid credential name '{0}': must be a valid environment variable name")]
+    InvalidName(String),
+
+    #[error("Failed to run the command for credential {name}: {source}")]
+    Spawn {
+        name: String,
+        source: std::io::Error,
+    },
+
+    #[error("Command for credential {name} failed: {message}")]
+    Command { name: String, message: String },
+
+    #[error("Command for credential {name} printed nothing")]
+    Empty { name: String },
+
+    #[error("AWS secret for credential {name} has no string field '{key}'")]
+    MissingKey { name: String, key: String },
+}
+
+/// Resolves every entry in `config.credentials`, returning `(name, value)`
+/// pairs sorted by name.
+///
+/// Commands run with `sh -c` in `workspace`. Stops at the first failure: a
+/// loop without its API key would only fail later, less clearly.
+pub fn resolve(
+    config: &RalphConfig,
+    workspace: &Path,
+) -> Result<Vec<(String, String)>, CredentialError> {
+    let credentials = &config.credentials;
+    let mut names: Vec<&String> = credentials.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| {
+            let value = resolve_one(name, &credentials[name], workspace)?;
+            Ok((name.clone(), value))
+        })
+        .collect()
+}
+
+fn resolve_one(
+    name: &str,
+    source: &CredentialSource,
+    workspace: &Path,
+) -> Result<String, CredentialError> {
+    if !is_env_name(name) {
+        return Err(CredentialError::InvalidName(name.to_string()));
+    }
+
+    match source {
+        CredentialSource::Command(command) => {
+            let mut shell = Command::new("sh");
+            shell.arg("-c").arg(command);
+            run(name, shell, workspace)
+        }
+        CredentialSource::AwsSecret {
+            aws_secret,
+            key,
+            region,
+        } => {
+            let mut aws = Command::new("aws");
+            aws.args([
+                "secretsmanager",
+                "get-secret-value",
+                "--secret-id",
+                aws_secret,
+                "--query",
+                "SecretString",
+                "--output",
+                "text",
+            ]);
+            if let Some(region) = region {
+                aws.args(["--region", region]);
+            }
+            let secret = run(name, aws, workspace)?;
+            match key {
+                Some(key) => json_field(&secret, key).ok_or_else(|| CredentialError::MissingKey {
+                    name: name.to_string(),
+                    key: key.clone(),
+                }),
+                None => Ok(secret),
+            }
+        }
+    }
+}
+
+/// Runs `command` and returns its stdout without the trailing newline.
+fn run(name: &str, mut command: Command, workspace: &Path) -> Result<String, CredentialError> {
+    let output = command
+        .current_dir(workspace)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|source| CredentialError::Spawn {
+            name: name.to_string(),
+            source,
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = match stderr.trim() {
+            "" => format!("exit status {}", output.status),
+            stderr => stderr.to_string(),
+        };
+        return Err(CredentialError::Command {
+            name: name.to_string(),
+            message,
+        });
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout)
+        .trim_end_matches(['\r', '\n'])
+        .to_string();
+    if value.is_empty() {
+        return Err(CredentialError::Empty {
+            name: name.to_string(),
+        });
+    }
+    Ok(value)
+}
+
+/// Extracts a string field from a JSON object secret.
+fn json_field(secret: &str, key: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(secret).ok()?;
+    value.get(key)?.as_str().map(str::to_string)
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn config(credentials: &[(&str, &str)]) -> RalphConfig {
+        let mut config = RalphConfig::default();
+        config.credentials = credentials
+            .iter()
+            .map(|(name, cmd)| (name.to_string(), CredentialSource::Command(cmd.to_string())))
+            .collect();
+        config
+    }
+
+    #[test]
+    fn test_resolve_runs_commands_in_name_order() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("key.txt"), "sk-from-file\n").unwrap();
+        let credentials = config(&[
+            ("OPENAI_API_KEY", "printf 'sk-openai'"),
+            ("ANTHROPIC_API_KEY", "cat key.txt"),
+        ]);
+
+        let resolved = resolve(&credentials, dir.path()).unwrap();
+
+        assert_eq!(
+            resolved,
+            vec![
+                ("ANTHROPIC_API_KEY".to_string(), "sk-from-file".to_string()),
+                ("OPENAI_API_KEY".to_string(), "sk-openai".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_errors_name_the_credential() {
+        let dir = tempfile::tempdir().unwrap();
+        let failing = config(&[("API_KEY", "echo 'item not found' >&2; exit 1")]);
+        let err = resolve(&failing, dir.path()).unwrap_err().to_string();
+        assert!(
+            err.contains("API_KEY") && err.contains("item not found"),
+            "{err}"
+        );
+
+        let empty = config(&[("API_KEY", "true")]);
+        assert!(matches!(
+            resolve(&empty, dir.path()),
+            Err(CredentialError::Empty { .. })
+        ));
+
+        let bad_name = config(&[("API-KEY", "echo x")]);
+        assert!(matches!(
+            resolve(&bad_name, dir.path()),
+            Err(CredentialError::InvalidName(_))
+        ));
+    }
+
+    #[test]
+    fn test_json_field() {
+        let secret = r#"{"api_key":"sk-123","count":2}"#;
+        assert_eq!(json_field(secret, "api_key").as_deref(), Some("sk-123"));
+        assert_eq!(json_field(secret, "count"), None);
+        assert_eq!(json_field("plain", "api_key"), None);
+    }
+}
@@ -9,8 +9,8 @@ mod tests {
     use std::io::{BufRead, BufReader};
     use tempfile::TempDir;
 
-    #[test]
-    fn test_event_loop_logs_iteration_started() {
+    #[tokio::test]
+    async fn test_event_loop_logs_iteration_started() {
         let temp_dir = TempDir::new().unwrap();
 
         let config = RalphConfig::default();
@@ -18,7 +18,9 @@ mod tests {
         let mut event_loop = EventLoop::with_diagnostics(config, diagnostics);
 
         // Simulate processing output (which increments iteration)
-        event_loop.process_output(&"ralph".into(), "some output", true);
+        event_loop
+            .process_output(&"ralph".into(), "some output", true)
+            .await;
 
         // Verify orchestration.jsonl was created and contains IterationStarted
         let diagnostics_dir = temp_dir.path().join(".ralph").join("diagnostics");
@@ -55,8 +57,8 @@ mod tests {
         assert_eq!(first_entry["iteration"], 1);
     }
 
-    #[test]
-    fn test_event_loop_logs_hat_selected() {
+    #[tokio::test]
+    async fn test_event_loop_logs_hat_selected() {
         let temp_dir = TempDir::new().unwrap();
 
         let config = RalphConfig::default();
@@ -64,7 +66,9 @@ mod tests {
         let mut event_loop = EventLoop::with_diagnostics(config, diagnostics);
 
         // Process output which should trigger hat selection logging
-        event_loop.process_output(&"ralph".into(), "some output", true);
+        event_loop
+            .process_output(&"ralph".into(), "some output", true)
+            .await;
 
         let diagnostics_dir = temp_dir.path().join(".ralph").join("diagnostics");
         let session_dirs: Vec<_> = std::fs::read_dir(&diagnostics_dir)
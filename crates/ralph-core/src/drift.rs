@@ -0,0 +1,120 @@
+//! Topology drift: hats publishing topics they don't declare.
+//!
+//! After each hat iteration the event loop compares the topics the hat wrote
+//! with its `publishes:` list. Undeclared topics are still routed, but each
+//! iteration that has them publishes a
+//! [`topology.drift`](crate::lifecycle::TOPOLOGY_DRIFT_TOPIC) event and adds
+//! them to the loop's [`DriftReport`], which the loop summary lists. That
+//! keeps the declared topology, and the hat table built from it, honest as
+//! prompts change.
+
+use crate::child_loop::SPAWN_TOPIC;
+use serde::{Deserialize, Serialize};
+
+/// An undeclared topic a hat has published.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DriftEntry {
+    pub hat: String,
+    pub topic: String,
+    /// Iterations that published it.
+    pub count: u32,
+    pub first_iteration: u32,
+    pub last_iteration: u32,
+}
+
+/// Every undeclared topic published during a loop, in order of first sighting.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DriftReport {
+    entries: Vec<DriftEntry>,
+}
+
+/// Checks if `topic` is one any hat may publish without declaring it: the
+/// completion promise, `human.*` escalations, and child loop requests.
+pub fn is_always_allowed(topic: &str, completion_promise: &str) -> bool {
+    topic == completion_promise || topic.starts_with("human.") || topic == SPAWN_TOPIC
+}
+
+impl DriftReport {
+    /// Records that `hat` published the undeclared `topic` in `iteration`.
+    pub fn record(&mut self, hat: &str, topic: &str, iteration: u32) {
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.hat == hat && entry.topic == topic)
+        {
+            if entry.last_iteration != iteration {
+                entry.count += 1;
+                entry.last_iteration = iteration;
+            }
+            return;
+        }
+        self.entries.push(DriftEntry {
+            hat: hat.to_string(),
+            topic: topic.to_string(),
+            count: 1,
+            first_iteration: iteration,
+            last_iteration: iteration,
+        });
+    }
+
+    pub fn entries(&self) -> &[DriftEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Renders the report as a Markdown list for the loop summary.
+    pub fn markdown(&self) -> String {
+        let mut markdown = String::new();
+        for entry in &self.entries {
+            let iterations = if entry.count == 1 {
+                format!("iteration {}", entry.first_iteration)
+            } else {
+                format!(
+                    "{} iterations, {}–{}",
+                    entry.count, entry.first_iteration, entry.last_iteration
+                )
+            };
+            markdown.push_str(&format!(
+                "- {} published `{}` ({iterations})\n",
+                entry.hat, entry.topic
+            ));
+        }
+        markdown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_counts_iterations_once() {
+        let mut report = DriftReport::default();
+        report.record("builder", "build.skipped", 2);
+        report.record("builder", "build.skipped", 2);
+        report.record("reviewer", "build.skipped", 3);
+        report.record("builder", "build.skipped", 5);
+
+        assert_eq!(report.entries().len(), 2);
+        assert_eq!(report.entries()[0].count, 2);
+        assert_eq!(report.entries()[0].first_iteration, 2);
+        assert_eq!(report.entries()[0].last_iteration, 5);
+        assert_eq!(
+            report.markdown(),
+            "- builder published `build.skipped` (2 iterations, 2–5)\n\
+             - reviewer published `build.skipped` (iteration 3)\n"
+        );
+    }
+
+    #[test]
+    fn test_always_allowed_topics() {
+        assert!(is_always_allowed("LOOP_COMPLETE", "LOOP_COMPLETE"));
+        assert!(is_always_allowed("human.question", "LOOP_COMPLETE"));
+        assert!(is_always_allowed(SPAWN_TOPIC, "LOOP_COMPLETE"));
+        assert!(!is_always_allowed("build.done", "LOOP_COMPLETE"));
+    }
+}
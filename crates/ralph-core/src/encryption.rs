@@ -0,0 +1,302 @@
+//! Encryption of session artifacts at rest.
+//!
+//! Prompts and backend output often embed proprietary code, and `.ralph/`
+//! tends to end up in shared CI caches. With `encryption.enabled`, the loop
+//! seals each iteration's files when the iteration ends and the events
+//! journal and forensics bundle when the loop ends: the file is replaced by
+//! [`MAGIC`], a random 24-byte nonce, and the XChaCha20-Poly1305 ciphertext.
+//!
+//! Sealed files keep their names. Readers go through [`read_artifact`],
+//! which passes plain files through and decrypts sealed ones, and
+//! `ralph decrypt` restores a file for other tools.
+
+use crate::config::EncryptionConfig;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Leading bytes of a sealed file.
+pub const MAGIC: &[u8] = b"RALPHENC1\n";
+
+const NONCE_LEN: usize = 24;
+
+/// Errors from resolving keys and reading sealed artifacts.
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptionError {
+    #[error("No encryption key: set {0} to 64 hex characters (e.g. `openssl rand -hex 32`)")]
+    MissingKey(String),
+
+    #[error("Invalid encryption key in {0}: expected 64 hex characters")]
+    InvalidKey(String),
+
+    #[error("{} is encrypted; set the key named by encryption.key_env to read it", .0.display())]
+    Locked(PathBuf),
+
+    #[error("Failed to decrypt {}: wrong key or corrupted file", path.display())]
+    Decrypt { path: PathBuf },
+
+    #[error("Failed to read {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Key for sealing and opening artifacts.
+#[derive(Clone)]
+pub struct ArtifactKey {
+    cipher: XChaCha20Poly1305,
+    /// Where the key came from, for error messages.
+    env: String,
+}
+
+impl std::fmt::Debug for ArtifactKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArtifactKey")
+            .field("env", &self.env)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ArtifactKey {
+    /// Parses a key of 64 hex characters; `env` names its source.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncryptionError::InvalidKey`] if `hex` isn't 32 bytes of hex.
+    pub fn from_hex(env: &str, hex: &str) -> Result<Self, EncryptionError> {
+        let hex = hex.trim();
+        let invalid = || EncryptionError::InvalidKey(env.to_string());
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid()))
+            .collect::<Result<Vec<u8>, _>>()?;
+        Ok(Self {
+            cipher: XChaCha20Poly1305::new_from_slice(&bytes).map_err(|_| invalid())?,
+            env: env.to_string(),
+        })
+    }
+
+    /// Resolves the key the loop seals with: `None` when encryption is off.
+    ///
+    /// The key is read from `credentials` first, then from the environment
+    /// variable named by `key_env`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encryption is on and the key is missing or invalid.
+    pub fn resolve(
+        config: &EncryptionConfig,
+        credentials: &[(String, String)],
+    ) -> Result<Option<Self>, EncryptionError> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        credentials
+            .iter()
+            .find(|(name, _)| *name == config.key_env)
+            .map(|(_, value)| value.clone())
+            .or_else(|| std::env::var(&config.key_env).ok())
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| EncryptionError::MissingKey(config.key_env.clone()))
+            .and_then(|key| Self::from_hex(&config.key_env, &key))
+            .map(Some)
+    }
+
+    /// Reads the key for opening artifacts from the environment variable
+    /// named by `key_env`, whether or not encryption is on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variable is set but isn't a valid key.
+    pub fn from_env(config: &EncryptionConfig) -> Result<Option<Self>, EncryptionError> {
+        std::env::var(&config.key_env)
+            .ok()
+            .filter(|key| !key.is_empty())
+            .map(|key| Self::from_hex(&config.key_env, &key))
+            .transpose()
+    }
+
+    /// Encrypts `plaintext` into the sealed format.
+    pub fn seal(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .expect("XChaCha20-Poly1305 encryption is infallible for in-memory buffers");
+        [MAGIC, nonce.as_slice(), &ciphertext].concat()
+    }
+
+    /// Decrypts sealed `data`; `None` if it isn't sealed, is truncated, or
+    /// was sealed with another key.
+    pub fn open(&self, data: &[u8]) -> Option<Vec<u8>> {
+        let rest = data.strip_prefix(MAGIC)?;
+        if rest.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .ok()
+    }
+
+    /// Encrypts the file at `path` in place, atomically.
+    ///
+    /// Returns `false` without touching the file if it doesn't exist or is
+    /// already sealed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or replaced.
+    pub fn seal_file(&self, path: &Path) -> io::Result<bool> {
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        if is_sealed(&data) {
+            return Ok(false);
+        }
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".sealing");
+        let tmp = PathBuf::from(tmp);
+        fs::write(&tmp, self.seal(&data))?;
+        fs::rename(&tmp, path)?;
+        Ok(true)
+    }
+
+    /// Decrypts the sealed file at `path` in place, atomically, so it can be
+    /// appended to again.
+    ///
+    /// Returns `false` without touching the file if it doesn't exist or isn't
+    /// sealed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read, opened, or replaced.
+    pub fn open_file(&self, path: &Path) -> Result<bool, EncryptionError> {
+        let io_error = |source| EncryptionError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(io_error(e)),
+        };
+        if !is_sealed(&data) {
+            return Ok(false);
+        }
+        let plaintext = self.open(&data).ok_or_else(|| EncryptionError::Decrypt {
+            path: path.to_path_buf(),
+        })?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".opening");
+        let tmp = PathBuf::from(tmp);
+        fs::write(&tmp, plaintext)
+            .and_then(|()| fs::rename(&tmp, path))
+            .map_err(io_error)?;
+        Ok(true)
+    }
+}
+
+/// Returns true if `data` is in the sealed format.
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Reads the file at `path`, decrypting it if it's sealed.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read, or if it's sealed and `key`
+/// is `None` or doesn't open it.
+pub fn read_artifact(path: &Path, key: Option<&ArtifactKey>) -> Result<Vec<u8>, EncryptionError> {
+    let data = fs::read(path).map_err(|source| EncryptionError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    if !is_sealed(&data) {
+        return Ok(data);
+    }
+    let key = key.ok_or_else(|| EncryptionError::Locked(path.to_path_buf()))?;
+    key.open(&data).ok_or_else(|| EncryptionError::Decrypt {
+        path: path.to_path_buf(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const HEX: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[test]
+    fn test_seal_file_round_trips_and_is_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("iter-1.out");
+        fs::write(&path, "fn secret() {}\n").unwrap();
+        let key = ArtifactKey::from_hex("KEY", HEX).unwrap();
+
+        assert!(key.seal_file(&path).unwrap());
+        let sealed = fs::read(&path).unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!String::from_utf8_lossy(&sealed).contains("secret"));
+        assert!(!key.seal_file(&path).unwrap());
+        assert!(!key.seal_file(&dir.path().join("missing")).unwrap());
+        assert!(!key.open_file(&dir.path().join("missing")).unwrap());
+
+        assert_eq!(
+            read_artifact(&path, Some(&key)).unwrap(),
+            b"fn secret() {}\n"
+        );
+        assert!(matches!(
+            read_artifact(&path, None),
+            Err(EncryptionError::Locked(_))
+        ));
+        let other = ArtifactKey::from_hex("KEY", &"ab".repeat(32)).unwrap();
+        assert!(matches!(
+            read_artifact(&path, Some(&other)),
+            Err(EncryptionError::Decrypt { .. })
+        ));
+
+        // Opening in place restores the plaintext
+        assert!(key.open_file(&path).unwrap());
+        assert_eq!(fs::read(&path).unwrap(), b"fn secret() {}\n");
+        assert!(!key.open_file(&path).unwrap());
+
+        // Plain files pass through
+        let plain = dir.path().join("plain.txt");
+        fs::write(&plain, "hello").unwrap();
+        assert_eq!(read_artifact(&plain, None).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_resolve_key() {
+        let mut config = EncryptionConfig {
+            key_env: "RALPH_TEST_NO_SUCH_ENCRYPTION_KEY".to_string(),
+            ..EncryptionConfig::default()
+        };
+        assert!(ArtifactKey::resolve(&config, &[]).unwrap().is_none());
+
+        config.enabled = true;
+        assert!(matches!(
+            ArtifactKey::resolve(&config, &[]),
+            Err(EncryptionError::MissingKey(_))
+        ));
+        let credentials = [(config.key_env.clone(), HEX.to_string())];
+        assert!(
+            ArtifactKey::resolve(&config, &credentials)
+                .unwrap()
+                .is_some()
+        );
+        let credentials = [(config.key_env.clone(), "not-hex".to_string())];
+        assert!(matches!(
+            ArtifactKey::resolve(&config, &credentials),
+            Err(EncryptionError::InvalidKey(_))
+        ));
+    }
+}
@@ -0,0 +1,284 @@
+//! Crate-wide error taxonomy.
+//!
+//! Module errors (`ConfigError`, `LockError`, `GitOpsError`, ...) stay next to
+//! the code that raises them. [`Error`] groups them into failure classes, and
+//! [`ErrorCode`] gives each class a stable string and process exit code, so
+//! embedders and the CLI can match on what kind of thing failed instead of on
+//! message text.
+
+use crate::config::ConfigError;
+use crate::credentials::CredentialError;
+use crate::git_ops::GitOpsError;
+use crate::landing::LandingError;
+use crate::loop_history::HistoryError;
+use crate::loop_lock::LockError;
+use crate::loop_registry::RegistryError;
+use crate::merge_queue::MergeQueueError;
+use crate::plugin::PluginError;
+use crate::repo_lock::RepoLockError;
+use crate::script::ScriptError;
+use crate::worktree::WorktreeError;
+use std::path::PathBuf;
+
+/// Top-level error for `ralph-core`, grouped by failure class.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// Configuration could not be loaded or is invalid.
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    /// A plugin or script referenced by the config failed to load.
+    #[error(transparent)]
+    Extension(#[from] ExtensionError),
+
+    /// A backend failed to start or run.
+    #[error("Backend error: {0}")]
+    Backend(String),
+
+    /// Event routing or parsing failed.
+    #[error(transparent)]
+    Bus(#[from] ralph_proto::Error),
+
+    /// Loop state on disk (events, history, registry) could not be read or written.
+    #[error(transparent)]
+    Journal(#[from] JournalError),
+
+    /// A git checkpoint, worktree, or merge operation failed.
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+
+    /// The workspace is locked by another loop.
+    #[error(transparent)]
+    Lock(#[from] LockError),
+
+    /// An [`Orchestrator`](crate::Orchestrator) method was called out of order.
+    #[error("Orchestrator: {0}")]
+    Lifecycle(String),
+
+    /// Any other I/O failure.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl Error {
+    /// Returns the failure class of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::Config(_) | Error::Extension(_) => ErrorCode::Config,
+            Error::Backend(_) | Error::Lifecycle(_) => ErrorCode::Backend,
+            Error::Bus(_) => ErrorCode::Bus,
+            Error::Journal(_) => ErrorCode::Journal,
+            Error::Checkpoint(_) => ErrorCode::Checkpoint,
+            Error::Lock(_) => ErrorCode::Lock,
+            Error::Io(_) => ErrorCode::Io,
+        }
+    }
+}
+
+/// Failures loading plugins or scripts.
+#[derive(Debug, thiserror::Error)]
+pub enum ExtensionError {
+    #[error(transparent)]
+    Plugin(#[from] PluginError),
+
+    #[error(transparent)]
+    Script(#[from] ScriptError),
+}
+
+/// Failures reading or writing loop state under `.ralph/`.
+#[derive(Debug, thiserror::Error)]
+pub enum JournalError {
+    #[error("Events file {}: {source}", path.display())]
+    Events {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error(transparent)]
+    History(#[from] HistoryError),
+
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+}
+
+/// Failures in git checkpoints, worktrees, and merging.
+#[derive(Debug, thiserror::Error)]
+pub enum CheckpointError {
+    #[error(transparent)]
+    Git(#[from] GitOpsError),
+
+    #[error(transparent)]
+    Worktree(#[from] WorktreeError),
+
+    #[error(transparent)]
+    MergeQueue(#[from] MergeQueueError),
+
+    #[error(transparent)]
+    Landing(#[from] LandingError),
+}
+
+macro_rules! impl_from_via {
+    ($($source:ty => $via:ty),* $(,)?) => {
+        $(
+            impl From<$source> for Error {
+                fn from(err: $source) -> Self {
+                    <$via>::from(err).into()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_via! {
+    PluginError => ExtensionError,
+    ScriptError => ExtensionError,
+    HistoryError => JournalError,
+    RegistryError => JournalError,
+    GitOpsError => CheckpointError,
+    WorktreeError => CheckpointError,
+    MergeQueueError => CheckpointError,
+    LandingError => CheckpointError,
+}
+
+/// Failure class with a stable identifier and exit code.
+///
+/// Exit codes follow `sysexits.h` and don't overlap with the loop's
+/// termination codes (0-3, 130).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    Config,
+    Backend,
+    Bus,
+    Journal,
+    Checkpoint,
+    Lock,
+    Io,
+}
+
+impl ErrorCode {
+    /// Returns the stable identifier, e.g. `"config"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::Config => "config",
+            ErrorCode::Backend => "backend",
+            ErrorCode::Bus => "bus",
+            ErrorCode::Journal => "journal",
+            ErrorCode::Checkpoint => "checkpoint",
+            ErrorCode::Lock => "lock",
+            ErrorCode::Io => "io",
+        }
+    }
+
+    /// Returns the process exit code for this failure class.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorCode::Config => 78,     // EX_CONFIG
+            ErrorCode::Backend => 69,    // EX_UNAVAILABLE
+            ErrorCode::Bus => 65,        // EX_DATAERR
+            ErrorCode::Journal => 74,    // EX_IOERR
+            ErrorCode::Checkpoint => 73, // EX_CANTCREAT
+            ErrorCode::Lock => 75,       // EX_TEMPFAIL
+            ErrorCode::Io => 74,         // EX_IOERR
+        }
+    }
+
+    /// Classifies a single error value from this crate (or `ralph-proto`).
+    ///
+    /// Returns `None` for errors this crate doesn't know about. To classify
+    /// a chain (e.g. an `anyhow::Error` with context), call this on each
+    /// source in turn and take the first match.
+    pub fn classify(err: &(dyn std::error::Error + 'static)) -> Option<Self> {
+        if let Some(err) = err.downcast_ref::<Error>() {
+            return Some(err.code());
+        }
+
+        let code = if err.is::<ConfigError>()
+            || err.is::<ExtensionError>()
+            || err.is::<PluginError>()
+            || err.is::<ScriptError>()
+            || err.is::<CredentialError>()
+        {
+            ErrorCode::Config
+        } else if err.is::<ralph_proto::Error>() {
+            ErrorCode::Bus
+        } else if err.is::<JournalError>() || err.is::<HistoryError>() || err.is::<RegistryError>()
+        {
+            ErrorCode::Journal
+        } else if err.is::<CheckpointError>()
+            || err.is::<GitOpsError>()
+            || err.is::<WorktreeError>()
+            || err.is::<MergeQueueError>()
+            || err.is::<LandingError>()
+        {
+            ErrorCode::Checkpoint
+        } else if err.is::<LockError>() || err.is::<RepoLockError>() {
+            ErrorCode::Lock
+        } else if err.is::<std::io::Error>() {
+            ErrorCode::Io
+        } else {
+            return None;
+        };
+        Some(code)
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_errors_map_to_their_class() {
+        let err: Error = ConfigError::InvalidCompletionPromise.into();
+        assert_eq!(err.code(), ErrorCode::Config);
+
+        let err: Error = RegistryError::NotFound("loop-1".to_string()).into();
+        assert_eq!(err.code(), ErrorCode::Journal);
+        assert!(matches!(err, Error::Journal(JournalError::Registry(_))));
+
+        let err: Error = GitOpsError::Git("merge failed".to_string()).into();
+        assert_eq!(err.code(), ErrorCode::Checkpoint);
+
+        let err: Error = LockError::UnsupportedPlatform.into();
+        assert_eq!(err.code(), ErrorCode::Lock);
+        assert_eq!(err.code().exit_code(), 75);
+    }
+
+    #[test]
+    fn test_classify_finds_first_known_error_in_chain() {
+        let inner = ConfigError::InvalidCompletionPromise;
+        assert_eq!(ErrorCode::classify(&inner), Some(ErrorCode::Config));
+        assert_eq!(
+            ErrorCode::classify(&WorktreeError::NotARepo("/tmp/x".to_string())),
+            Some(ErrorCode::Checkpoint)
+        );
+        assert_eq!(ErrorCode::classify(&std::fmt::Error), None);
+
+        // A contextual wrapper this crate doesn't know about is skipped.
+        #[derive(Debug, thiserror::Error)]
+        #[error("loading config")]
+        struct Context(#[source] ConfigError);
+        let wrapped = Context(ConfigError::InvalidCompletionPromise);
+        let code =
+            std::iter::successors(Some(&wrapped as &(dyn std::error::Error + 'static)), |e| {
+                e.source()
+            })
+            .find_map(ErrorCode::classify);
+        assert_eq!(code, Some(ErrorCode::Config));
+    }
+
+    #[test]
+    fn test_codes_are_stable() {
+        assert_eq!(ErrorCode::Config.as_str(), "config");
+        assert_eq!(ErrorCode::Config.exit_code(), 78);
+        assert_eq!(ErrorCode::Backend.to_string(), "backend");
+    }
+}
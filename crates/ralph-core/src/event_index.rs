@@ -0,0 +1,383 @@
+//! Index over a workspace's event journals for queries across sessions.
+//!
+//! Every run writes its own journal (`.ralph/events-<run-id>.jsonl`, or the
+//! legacy `.ralph/events.jsonl`). The index records, per journal, how much of
+//! the file has been scanned, its time range, and the topics it contains, and
+//! is cached in `.ralph/event-index.json`. Refreshing only scans bytes appended
+//! since the last query, and a query only opens journals whose time range and
+//! topics can match.
+
+use crate::event_logger::{EventHistory, EventRecord};
+use chrono::{DateTime, Utc};
+use ralph_proto::Topic;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+use tracing::warn;
+
+/// File name of the cached index inside `.ralph/`.
+pub const INDEX_FILE: &str = "event-index.json";
+
+/// What the index knows about one journal.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JournalSummary {
+    /// Journal file name, relative to `.ralph/`.
+    pub file: String,
+    /// Bytes scanned so far.
+    pub len: u64,
+    /// Number of events in the scanned bytes.
+    pub count: usize,
+    /// Timestamp of the earliest event.
+    pub first: Option<DateTime<Utc>>,
+    /// Timestamp of the latest event.
+    pub last: Option<DateTime<Utc>>,
+    /// Every topic published in the journal.
+    pub topics: BTreeSet<String>,
+}
+
+impl JournalSummary {
+    /// Run ID from an `events-<id>.jsonl` name, or the file name itself.
+    pub fn session(&self) -> &str {
+        self.file
+            .strip_prefix("events-")
+            .and_then(|rest| rest.strip_suffix(".jsonl"))
+            .unwrap_or(&self.file)
+    }
+
+    /// Folds one record into the summary.
+    fn add(&mut self, record: &EventRecord) {
+        self.count += 1;
+        self.topics.insert(record.topic.clone());
+        if let Some(ts) = parse_ts(&record.ts) {
+            self.first = Some(self.first.map_or(ts, |first| first.min(ts)));
+            self.last = Some(self.last.map_or(ts, |last| last.max(ts)));
+        }
+    }
+
+    /// Whether any event in the journal could satisfy `query`.
+    fn may_match(&self, query: &EventQuery) -> bool {
+        if let (Some(since), Some(last)) = (query.since, self.last)
+            && last < since
+        {
+            return false;
+        }
+        if let (Some(until), Some(first)) = (query.until, self.first)
+            && first > until
+        {
+            return false;
+        }
+        query
+            .topic
+            .as_ref()
+            .is_none_or(|pattern| self.topics.iter().any(|t| pattern.matches_str(t)))
+    }
+}
+
+/// Filters for [`EventIndex::query`]. Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    /// Topic or glob pattern, e.g. `build.*`.
+    pub topic: Option<Topic>,
+    /// Only events at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only events at or before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Case-insensitive substring of the payload.
+    pub payload_contains: Option<String>,
+}
+
+impl EventQuery {
+    /// Whether `record` satisfies every filter.
+    ///
+    /// Records without a parseable timestamp never match a time filter.
+    pub fn matches(&self, record: &EventRecord) -> bool {
+        if self
+            .topic
+            .as_ref()
+            .is_some_and(|pattern| !pattern.matches_str(&record.topic))
+        {
+            return false;
+        }
+        if self.since.is_some() || self.until.is_some() {
+            let Some(ts) = parse_ts(&record.ts) else {
+                return false;
+            };
+            if self.since.is_some_and(|since| ts < since)
+                || self.until.is_some_and(|until| ts > until)
+            {
+                return false;
+            }
+        }
+        self.payload_contains.as_ref().is_none_or(|needle| {
+            record
+                .payload
+                .to_lowercase()
+                .contains(&needle.to_lowercase())
+        })
+    }
+}
+
+/// An event found by a query, with the session it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventMatch {
+    /// Run ID of the journal (see [`JournalSummary::session`]).
+    pub session: String,
+    #[serde(flatten)]
+    pub record: EventRecord,
+}
+
+/// Summaries of every journal in a `.ralph/` directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EventIndex {
+    /// Journals ordered by their earliest event.
+    pub journals: Vec<JournalSummary>,
+}
+
+impl EventIndex {
+    /// Loads the cached index for `ralph_dir` and brings it up to date with
+    /// the journals on disk.
+    ///
+    /// Saving the refreshed index is best effort; a read-only workspace can
+    /// still be queried.
+    pub fn open(ralph_dir: &Path) -> io::Result<Self> {
+        let index_path = ralph_dir.join(INDEX_FILE);
+        let mut index: Self = fs::read_to_string(&index_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        if index.refresh(ralph_dir)?
+            && let Err(e) = serde_json::to_string(&index)
+                .map_err(io::Error::other)
+                .and_then(|json| fs::write(&index_path, json))
+        {
+            warn!(path = %index_path.display(), error = %e, "Failed to save event index");
+        }
+        Ok(index)
+    }
+
+    /// Rescans journals that changed since they were indexed and drops ones
+    /// that are gone. Returns whether anything changed.
+    fn refresh(&mut self, ralph_dir: &Path) -> io::Result<bool> {
+        let files = journal_files(ralph_dir)?;
+        let before = self.journals.len();
+        self.journals
+            .retain(|j| files.iter().any(|(f, _)| *f == j.file));
+        let mut changed = self.journals.len() != before;
+
+        for (file, len) in files {
+            let existing = self.journals.iter().position(|j| j.file == file);
+            let mut summary = match existing {
+                Some(i) if self.journals[i].len == len => continue,
+                // Appended to: scan only the new bytes
+                Some(i) if self.journals[i].len < len => self.journals.remove(i),
+                // Truncated or rewritten: start over
+                Some(i) => {
+                    self.journals.remove(i);
+                    JournalSummary {
+                        file: file.clone(),
+                        ..JournalSummary::default()
+                    }
+                }
+                None => JournalSummary {
+                    file: file.clone(),
+                    ..JournalSummary::default()
+                },
+            };
+            summary.len = scan(&ralph_dir.join(&file), summary.len, |record| {
+                summary.add(record);
+            })?;
+            self.journals.push(summary);
+            changed = true;
+        }
+
+        self.journals
+            .sort_by(|a, b| a.first.cmp(&b.first).then_with(|| a.file.cmp(&b.file)));
+        Ok(changed)
+    }
+
+    /// Returns matching events across all journals, oldest session first.
+    pub fn query(&self, ralph_dir: &Path, query: &EventQuery) -> io::Result<Vec<EventMatch>> {
+        let mut matches = Vec::new();
+        for journal in self.journals.iter().filter(|j| j.may_match(query)) {
+            let records = EventHistory::new(ralph_dir.join(&journal.file)).read_all()?;
+            matches.extend(
+                records
+                    .into_iter()
+                    .filter(|record| query.matches(record))
+                    .map(|record| EventMatch {
+                        session: journal.session().to_string(),
+                        record,
+                    }),
+            );
+        }
+        Ok(matches)
+    }
+}
+
+/// Journal file names in `ralph_dir` with their current lengths.
+fn journal_files(ralph_dir: &Path) -> io::Result<Vec<(String, u64)>> {
+    let entries = match fs::read_dir(ralph_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with("events")
+            && Path::new(&name)
+                .extension()
+                .is_some_and(|ext| ext == "jsonl")
+            && let Ok(meta) = entry.metadata()
+            && meta.is_file()
+        {
+            files.push((name, meta.len()));
+        }
+    }
+    Ok(files)
+}
+
+/// Feeds each complete record after byte `offset` to `f` and returns the
+/// offset just past the last complete line, so a line still being written
+/// is picked up by the next scan.
+fn scan(path: &Path, offset: u64, mut f: impl FnMut(&EventRecord)) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::new(file);
+    let mut end = offset;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let n = reader.read_line(&mut line)?;
+        if n == 0 || !line.ends_with('\n') {
+            return Ok(end);
+        }
+        end += n as u64;
+        if let Ok(record) = serde_json::from_str::<EventRecord>(&line) {
+            f(&record);
+        }
+    }
+}
+
+fn parse_ts(ts: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn line(ts: &str, topic: &str, payload: &str) -> String {
+        format!(
+            "{{\"ts\":\"{ts}\",\"iteration\":1,\"hat\":\"ralph\",\"topic\":\"{topic}\",\"payload\":\"{payload}\"}}\n"
+        )
+    }
+
+    fn append(path: &Path, text: &str) {
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap()
+            .write_all(text.as_bytes())
+            .unwrap();
+    }
+
+    fn ts(s: &str) -> DateTime<Utc> {
+        parse_ts(s).unwrap()
+    }
+
+    #[test]
+    fn test_index_summarizes_journals_and_skips_others() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("events-20260101-090000.jsonl");
+        append(&old, &line("2026-01-01T09:00:00Z", "build.done", "auth ok"));
+        append(&old, &line("2026-01-01T09:05:00Z", "review.done", "lgtm"));
+        fs::write(dir.path().join("history.jsonl"), "{}\n").unwrap();
+
+        let index = EventIndex::open(dir.path()).unwrap();
+        assert_eq!(index.journals.len(), 1);
+        let journal = &index.journals[0];
+        assert_eq!(journal.session(), "20260101-090000");
+        assert_eq!(journal.count, 2);
+        assert_eq!(journal.first, Some(ts("2026-01-01T09:00:00Z")));
+        assert_eq!(journal.last, Some(ts("2026-01-01T09:05:00Z")));
+        assert!(dir.path().join(INDEX_FILE).exists());
+    }
+
+    #[test]
+    fn test_refresh_scans_only_appended_complete_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        append(&path, &line("2026-01-01T09:00:00Z", "build.done", ""));
+        append(&path, "{\"ts\":\"2026-01-01T09:01:00Z\"");
+
+        let index = EventIndex::open(dir.path()).unwrap();
+        assert_eq!(index.journals[0].count, 1);
+
+        // Finish the partial line and add another; the cached index picks up both
+        append(&path, ",\"topic\":\"build.blocked\",\"payload\":\"\"}\n");
+        append(&path, &line("2026-01-01T09:02:00Z", "task.done", ""));
+        let index = EventIndex::open(dir.path()).unwrap();
+        let journal = &index.journals[0];
+        assert_eq!(journal.count, 3);
+        assert_eq!(journal.len, fs::metadata(&path).unwrap().len());
+        assert!(journal.topics.contains("build.blocked"));
+
+        fs::remove_file(&path).unwrap();
+        assert!(EventIndex::open(dir.path()).unwrap().journals.is_empty());
+    }
+
+    #[test]
+    fn test_query_filters_across_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        append(
+            &dir.path().join("events-20260101-090000.jsonl"),
+            &[
+                line("2026-01-01T09:00:00Z", "build.done", "Fixed AUTH bug"),
+                line("2026-01-01T09:01:00Z", "review.done", "auth reviewed"),
+            ]
+            .concat(),
+        );
+        append(
+            &dir.path().join("events-20260102-090000.jsonl"),
+            &[
+                line("2026-01-02T09:00:00Z", "build.blocked", "auth tests fail"),
+                line("2026-01-02T09:01:00Z", "build.done", "docs"),
+            ]
+            .concat(),
+        );
+        let index = EventIndex::open(dir.path()).unwrap();
+
+        let query = EventQuery {
+            topic: Some(Topic::new("build.*")),
+            payload_contains: Some("auth".to_string()),
+            ..EventQuery::default()
+        };
+        let found = index.query(dir.path(), &query).unwrap();
+        let summary: Vec<_> = found
+            .iter()
+            .map(|m| (m.session.as_str(), m.record.topic.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("20260101-090000", "build.done"),
+                ("20260102-090000", "build.blocked")
+            ]
+        );
+
+        let recent = EventQuery {
+            since: Some(ts("2026-01-02T00:00:00Z")),
+            ..EventQuery::default()
+        };
+        assert!(!index.journals[0].may_match(&recent));
+        assert_eq!(index.query(dir.path(), &recent).unwrap().len(), 2);
+    }
+}
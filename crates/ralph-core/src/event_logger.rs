@@ -3,11 +3,12 @@
 //! Logs all events to `.ralph/events.jsonl` as specified in the event-loop spec.
 //! The observer pattern allows hooking into the event bus without modifying routing.
 
+use crate::encryption::{self, ArtifactKey, EncryptionError};
+use crate::event_writer::EventWriter;
 use crate::loop_context::LoopContext;
 use ralph_proto::{Event, HatId};
 use serde::{Deserialize, Deserializer, Serialize};
-use std::fs::{self, File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::{debug, warn};
 
@@ -131,8 +132,8 @@ pub struct EventLogger {
     /// Path to the events file.
     path: PathBuf,
 
-    /// File handle for appending.
-    file: Option<File>,
+    /// Locked appender shared with other event writers.
+    writer: EventWriter,
 }
 
 impl EventLogger {
@@ -143,9 +144,10 @@ impl EventLogger {
     ///
     /// The `.ralph/` directory is created if it doesn't exist.
     pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
         Self {
-            path: path.into(),
-            file: None,
+            writer: EventWriter::new(&path),
+            path,
         }
     }
 
@@ -172,33 +174,13 @@ impl EventLogger {
         Self::new(events_path)
     }
 
-    /// Ensures the parent directory exists and opens the file.
-    fn ensure_open(&mut self) -> std::io::Result<&mut File> {
-        if self.file.is_none() {
-            if let Some(parent) = self.path.parent() {
-                fs::create_dir_all(parent)?;
-            }
-            let file = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&self.path)?;
-            self.file = Some(file);
-        }
-        Ok(self.file.as_mut().unwrap())
-    }
-
     /// Logs an event record.
     ///
-    /// Uses a single `write_all` call to ensure the JSON line is written atomically.
-    /// This prevents corruption when multiple processes append to the same file
-    /// concurrently (e.g., during parallel merge queue processing).
+    /// Appends through [`EventWriter`], which holds an advisory lock for the
+    /// write so the line can't interleave with other processes appending to
+    /// the same file (e.g., the agent or parallel merge queue processing).
     pub fn log(&mut self, record: &EventRecord) -> std::io::Result<()> {
-        let file = self.ensure_open()?;
-        let mut json = serde_json::to_string(record)?;
-        json.push('\n');
-        // Single write_all ensures atomic append on POSIX with O_APPEND
-        file.write_all(json.as_bytes())?;
-        file.flush()?;
+        self.writer.append(record)?;
         debug!(topic = %record.topic, iteration = record.iteration, "Event logged");
         Ok(())
     }
@@ -224,12 +206,23 @@ impl EventLogger {
 /// Reader for event history files.
 pub struct EventHistory {
     path: PathBuf,
+    key: Option<ArtifactKey>,
 }
 
 impl EventHistory {
     /// Creates a new history reader.
     pub fn new(path: impl Into<PathBuf>) -> Self {
-        Self { path: path.into() }
+        Self {
+            path: path.into(),
+            key: None,
+        }
+    }
+
+    /// Sets the key for opening a journal sealed by `encryption`.
+    #[must_use]
+    pub fn with_key(mut self, key: Option<ArtifactKey>) -> Self {
+        self.key = key;
+        self
     }
 
     /// Creates a reader for the default path.
@@ -256,16 +249,18 @@ impl EventHistory {
             return Ok(Vec::new());
         }
 
-        let file = File::open(&self.path)?;
-        let reader = BufReader::new(file);
+        let data =
+            encryption::read_artifact(&self.path, self.key.as_ref()).map_err(|e| match e {
+                EncryptionError::Io { source, .. } => source,
+                other => std::io::Error::new(std::io::ErrorKind::InvalidData, other),
+            })?;
         let mut records = Vec::new();
 
-        for (line_num, line) in reader.lines().enumerate() {
-            let line = line?;
+        for (line_num, line) in String::from_utf8_lossy(&data).lines().enumerate() {
             if line.trim().is_empty() {
                 continue;
             }
-            match serde_json::from_str(&line) {
+            match serde_json::from_str(line) {
                 Ok(record) => records.push(record),
                 Err(e) => {
                     warn!(line = line_num + 1, error = %e, "Failed to parse event record");
@@ -310,6 +305,7 @@ impl EventHistory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
     use tempfile::TempDir;
 
     fn make_event(topic: &str, payload: &str) -> Event {
@@ -449,7 +445,7 @@ mod tests {
         let path = tmp.path().join("events.jsonl");
 
         // Write agent-style events (without iteration field)
-        let mut file = File::create(&path).unwrap();
+        let mut file = fs::File::create(&path).unwrap();
         writeln!(
             file,
             r#"{{"topic":"build.task","payload":"Implement auth","ts":"2024-01-15T10:00:00Z"}}"#
@@ -520,7 +516,7 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("events.jsonl");
 
-        let mut file = File::create(&path).unwrap();
+        let mut file = fs::File::create(&path).unwrap();
 
         // String payload (normal case)
         writeln!(
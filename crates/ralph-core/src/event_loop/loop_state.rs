@@ -4,10 +4,26 @@
 //! state of the orchestration loop including iteration count, failures,
 //! timing, and hat activation tracking.
 
-use ralph_proto::HatId;
-use std::collections::{HashMap, HashSet};
+use super::TerminationReason;
+use crate::blocked::Blocker;
+use crate::cost::CostLedger;
+use crate::drift::DriftReport;
+use crate::verification::VerificationReport;
+use ralph_proto::{Event, HatId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::{Duration, Instant};
 
+/// Whether the loop is running iterations or holding between them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopStatus {
+    #[default]
+    Running,
+    /// Holding before the next iteration until the pause is lifted.
+    Paused,
+}
+
 /// Current state of the event loop.
 #[derive(Debug)]
 pub struct LoopState {
@@ -15,8 +31,20 @@ pub struct LoopState {
     pub iteration: u32,
     /// Number of consecutive failures.
     pub consecutive_failures: u32,
+    /// Tail of the most recent failed iteration's output.
+    pub last_error: Option<String>,
+    /// Carry-over summary of the previous iteration's output.
+    pub previous_iteration: Option<String>,
     /// Cumulative cost in USD (if tracked).
     pub cumulative_cost: f64,
+    /// Spend attributed per hat and per triggering topic.
+    pub cost_ledger: CostLedger,
+    /// Event that triggered the current iteration.
+    pub last_trigger: Option<Event>,
+    /// Result of the most recent `verify.command` run.
+    pub last_verification: Option<VerificationReport>,
+    /// `ralph.spawn_loop` payloads waiting for the runner to start them.
+    pub pending_child_loops: Vec<String>,
     /// When the loop started.
     pub started_at: Instant,
     /// The last hat that executed.
@@ -31,14 +59,40 @@ pub struct LoopState {
     pub abandoned_tasks: Vec<String>,
     /// Count of times planner dispatched an already-abandoned task.
     pub abandoned_task_redispatches: u32,
+    /// Delegations Ralph published on recent coordination turns, oldest first.
+    pub recent_delegations: VecDeque<String>,
+    /// Consecutive coordination turns that repeated a recent delegation.
+    pub repeated_delegations: u32,
+    /// Topics of the delegation being repeated.
+    pub repeated_delegation_topics: Vec<String>,
     /// Consecutive malformed JSONL lines encountered (for validation backpressure).
     pub consecutive_malformed_events: u32,
     /// Whether a completion event has been observed in JSONL.
     pub completion_requested: bool,
+    /// Consecutive iterations that ended with a completion event.
+    pub completion_streak: u32,
 
     /// Per-hat activation counts (used for max_activations).
     pub hat_activation_counts: HashMap<HatId, u32>,
 
+    /// Per-hat wall-clock time spent executing (used for max_runtime_seconds).
+    pub hat_runtime: HashMap<HatId, Duration>,
+
+    /// When the current iteration's prompt was built.
+    pub iteration_started_at: Option<Instant>,
+
+    /// HEAD when the current iteration started; the adaptive budget's diff
+    /// size is measured against it.
+    pub iteration_base: Option<String>,
+
+    /// HEAD when the first iteration started. Protected paths are diffed
+    /// against it, so a protected change the agent committed stays reverted
+    /// in later iterations instead of becoming the new baseline.
+    pub protect_base: Option<String>,
+
+    /// Closed tasks when the current iteration started (adaptive budget only).
+    pub closed_tasks_at_start: Option<usize>,
+
     /// Hats for which `<hat_id>.exhausted` has been emitted.
     pub exhausted_hats: HashSet<HatId>,
 
@@ -49,6 +103,31 @@ pub struct LoopState {
     /// Hat IDs that were active in the last iteration.
     /// Used to inject `default_publishes` when agent writes no events.
     pub last_active_hat_ids: Vec<HatId>,
+
+    /// Index of the current workflow phase; `None` until the first phase starts.
+    pub phase: Option<usize>,
+
+    /// Iteration count when the current phase started.
+    pub phase_started_iteration: u32,
+
+    /// Hats paused by a `*.blocked` event, oldest first.
+    pub blockers: Vec<Blocker>,
+
+    /// Events routed to each hat for the current iteration, kept as context
+    /// in case it blocks (only with `blocked.enabled`).
+    pub hat_triggers: HashMap<HatId, Vec<Event>>,
+
+    /// Undeclared topics hats have published.
+    pub drift: DriftReport,
+
+    /// Why the loop ended, once `loop.terminate` has been published.
+    pub termination: Option<TerminationReason>,
+
+    /// Whether the loop is paused (see [`crate::loop_control`]).
+    pub status: LoopStatus,
+
+    /// When the current pause began.
+    pub paused_at: Option<Instant>,
 }
 
 impl Default for LoopState {
@@ -56,7 +135,13 @@ impl Default for LoopState {
         Self {
             iteration: 0,
             consecutive_failures: 0,
+            last_error: None,
+            previous_iteration: None,
             cumulative_cost: 0.0,
+            cost_ledger: CostLedger::default(),
+            last_trigger: None,
+            last_verification: None,
+            pending_child_loops: Vec::new(),
             started_at: Instant::now(),
             last_hat: None,
             consecutive_blocked: 0,
@@ -64,22 +149,63 @@ impl Default for LoopState {
             task_block_counts: HashMap::new(),
             abandoned_tasks: Vec::new(),
             abandoned_task_redispatches: 0,
+            recent_delegations: VecDeque::new(),
+            repeated_delegations: 0,
+            repeated_delegation_topics: Vec::new(),
             consecutive_malformed_events: 0,
             completion_requested: false,
+            completion_streak: 0,
             hat_activation_counts: HashMap::new(),
+            hat_runtime: HashMap::new(),
+            iteration_started_at: None,
+            iteration_base: None,
+            protect_base: None,
+            closed_tasks_at_start: None,
             exhausted_hats: HashSet::new(),
             last_checkin_at: None,
             last_active_hat_ids: Vec::new(),
+            phase: None,
+            phase_started_iteration: 0,
+            blockers: Vec::new(),
+            hat_triggers: HashMap::new(),
+            drift: DriftReport::default(),
+            termination: None,
+            status: LoopStatus::Running,
+            paused_at: None,
         }
     }
 }
 
+/// Coordination turns remembered when looking for repeated delegations.
+const DELEGATION_WINDOW: usize = 4;
+
 impl LoopState {
     /// Creates a new loop state.
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Records what Ralph delegated on a coordination turn.
+    ///
+    /// Returns true when the same delegation was published on one of the
+    /// last few coordination turns; a new delegation resets the count.
+    pub fn record_delegation(&mut self, signature: String, topics: Vec<String>) -> bool {
+        let repeated = self.recent_delegations.contains(&signature);
+        if repeated {
+            self.repeated_delegations += 1;
+            self.repeated_delegation_topics = topics;
+        } else {
+            self.repeated_delegations = 0;
+            self.repeated_delegation_topics.clear();
+        }
+
+        self.recent_delegations.push_back(signature);
+        if self.recent_delegations.len() > DELEGATION_WINDOW {
+            self.recent_delegations.pop_front();
+        }
+        repeated
+    }
+
     /// Returns the elapsed time since the loop started.
     pub fn elapsed(&self) -> Duration {
         self.started_at.elapsed()
@@ -6,68 +6,160 @@ mod loop_state;
 #[cfg(test)]
 mod tests;
 
-pub use loop_state::LoopState;
-
-use crate::config::{HatBackend, InjectMode, RalphConfig};
+pub use loop_state::{LoopState, LoopStatus};
+
+use crate::blocked::{self, BlockedEvent, BlockedNotice, Blocker, UNBLOCK_TOPIC};
+use crate::bootstrap::SessionBootstrap;
+use crate::budget::{AdaptiveBudget, BudgetChange, ProgressSample};
+use crate::child_loop::{self, ChildLoopError, SPAWN_TOPIC, SpawnRequest, run_child_loop};
+use crate::config::{
+    EnvironmentConfig, GenerationConfig, HatBackend, HatCliConfig, InjectMode, PhaseConfig,
+    RalphConfig, ScoutsConfig,
+};
+use crate::contract::{self, Contracts};
+use crate::cost::{CostEntry, Usage};
+use crate::drift;
+use crate::error::ExtensionError;
 use crate::event_parser::{EventParser, MutationEvidence, MutationStatus};
 use crate::event_reader::EventReader;
+use crate::file_lock::{FileLock, LockGuard as FileLockGuard};
+use crate::hat_predicate::PredicateContext;
 use crate::hat_registry::HatRegistry;
-use crate::hatless_ralph::HatlessRalph;
+use crate::hatless_ralph::{HatlessRalph, QueuePressure};
+use crate::human_question::{self, ANSWER_TOPIC, QUESTION_TOPIC, QuestionNotice};
 use crate::instructions::InstructionBuilder;
+use crate::lifecycle;
 use crate::loop_context::LoopContext;
+use crate::loop_control;
 use crate::memory_store::{MarkdownMemoryStore, format_memories_as_markdown, truncate_to_budget};
+use crate::native_hat::NativeHat;
+use crate::plugin::{PluginEvent, PluginHost};
+use crate::prompt_guard::{self, PromptGuard};
+use crate::prompt_pack::PromptPack;
+use crate::protect::{self, ProtectedPaths, RevertedPath};
+use crate::routing::{RouteDecision, RoutingPolicy};
+use crate::scratchpad::Scratchpad;
+use crate::script::{ScriptEvent, ScriptHost, ScriptState};
 use crate::skill_registry::SkillRegistry;
-use crate::text::floor_char_boundary;
-use ralph_proto::{CheckinContext, Event, EventBus, Hat, HatId, RobotService};
-use std::path::PathBuf;
+use crate::state_view::{HatView, LoopStateView, STATE_SCHEMA_VERSION, TerminationView};
+use crate::text::{floor_char_boundary, truncate_with_ellipsis};
+use crate::verification::{VerificationReport, run_verification, triage};
+use ralph_proto::{BusSnapshot, CheckinContext, Event, EventBus, Hat, HatId, RobotService, Topic};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+/// Registers `events.<topic>.renamed_from` and `hats.<id>.aliases` with the bus.
+fn register_renames(bus: &mut EventBus, config: &RalphConfig) {
+    for (old, new) in config.topic_renames() {
+        bus.rename_topic(old, Topic::new(new));
+    }
+    for (alias, hat) in config.hat_aliases() {
+        bus.alias_hat(alias, HatId::new(hat));
+    }
+}
+
+/// Registers the `debounce` windows with the bus.
+fn register_debounce(bus: &mut EventBus, config: &RalphConfig) {
+    for (pattern, window) in &config.debounce {
+        bus.debounce(Topic::new(pattern), window.0);
+    }
+}
+
 /// Reason the event loop terminated.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Limit and failure variants carry the context that tripped them, so the
+/// banner, summary, and `loop.terminate` payload can say which hat failed or
+/// how far over budget the loop went.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum TerminationReason {
     /// Completion promise was detected in output.
     CompletionPromise,
     /// Maximum iterations reached.
-    MaxIterations,
+    MaxIterations {
+        /// Iteration limit in effect (`max_iterations`, or the adaptive budget).
+        limit: u32,
+    },
     /// Maximum runtime exceeded.
-    MaxRuntime,
+    MaxRuntime {
+        /// Configured `max_runtime_seconds`.
+        limit_seconds: u64,
+        /// Wall-clock seconds the loop had run.
+        elapsed_seconds: u64,
+    },
     /// Maximum cost exceeded.
-    MaxCost,
+    MaxCost {
+        /// Configured `max_cost_usd`.
+        limit_usd: f64,
+        /// Cumulative spend when the limit tripped.
+        spent_usd: f64,
+    },
     /// Too many consecutive failures.
-    ConsecutiveFailures,
+    ConsecutiveFailures {
+        /// Hat whose iteration failed last.
+        hat: Option<HatId>,
+        /// Failures in a row.
+        failures: u32,
+        /// Configured `max_consecutive_failures`.
+        threshold: u32,
+        /// Tail of the last failed iteration's output.
+        last_error: Option<String>,
+    },
     /// Loop thrashing detected (repeated blocked events).
-    LoopThrashing,
+    LoopThrashing {
+        /// Hat that kept dispatching abandoned tasks.
+        hat: Option<HatId>,
+        /// Tasks abandoned after repeated blocks.
+        abandoned_tasks: Vec<String>,
+    },
+    /// Ralph kept re-publishing the same delegation.
+    DelegationLoop {
+        /// Topics of the repeated delegation.
+        topics: Vec<String>,
+        /// Coordination turns that repeated it.
+        repeats: u32,
+    },
     /// Too many consecutive malformed JSONL lines in events file.
-    ValidationFailure,
+    ValidationFailure {
+        /// Malformed lines in a row.
+        malformed_lines: u32,
+    },
     /// Manually stopped.
     Stopped,
     /// Interrupted by signal (SIGINT/SIGTERM).
     Interrupted,
     /// Restart requested via Telegram `/restart` command.
     RestartRequested,
+    /// Parked with no new events for `park_timeout_seconds`.
+    IdleTimeout {
+        /// Seconds the loop sat parked.
+        idle_seconds: u64,
+    },
 }
 
 impl TerminationReason {
     /// Returns the exit code for this termination reason per spec.
     ///
     /// Per spec "Loop Termination" section:
-    /// - 0: Completion promise detected (success)
+    /// - 0: Completion promise detected (success), or a parked loop timed out
     /// - 1: Consecutive failures or unrecoverable error (failure)
     /// - 2: Max iterations, max runtime, or max cost exceeded (limit)
     /// - 130: User interrupt (SIGINT = 128 + 2)
     pub fn exit_code(&self) -> i32 {
         match self {
-            TerminationReason::CompletionPromise => 0,
-            TerminationReason::ConsecutiveFailures
-            | TerminationReason::LoopThrashing
-            | TerminationReason::ValidationFailure
+            TerminationReason::CompletionPromise | TerminationReason::IdleTimeout { .. } => 0,
+            TerminationReason::ConsecutiveFailures { .. }
+            | TerminationReason::LoopThrashing { .. }
+            | TerminationReason::DelegationLoop { .. }
+            | TerminationReason::ValidationFailure { .. }
             | TerminationReason::Stopped => 1,
-            TerminationReason::MaxIterations
-            | TerminationReason::MaxRuntime
-            | TerminationReason::MaxCost => 2,
+            TerminationReason::MaxIterations { .. }
+            | TerminationReason::MaxRuntime { .. }
+            | TerminationReason::MaxCost { .. } => 2,
             TerminationReason::Interrupted => 130,
             // Restart uses exit code 3 to signal the caller to exec-replace
             TerminationReason::RestartRequested => 3,
@@ -81,15 +173,17 @@ impl TerminationReason {
     pub fn as_str(&self) -> &'static str {
         match self {
             TerminationReason::CompletionPromise => "completed",
-            TerminationReason::MaxIterations => "max_iterations",
-            TerminationReason::MaxRuntime => "max_runtime",
-            TerminationReason::MaxCost => "max_cost",
-            TerminationReason::ConsecutiveFailures => "consecutive_failures",
-            TerminationReason::LoopThrashing => "loop_thrashing",
-            TerminationReason::ValidationFailure => "validation_failure",
+            TerminationReason::MaxIterations { .. } => "max_iterations",
+            TerminationReason::MaxRuntime { .. } => "max_runtime",
+            TerminationReason::MaxCost { .. } => "max_cost",
+            TerminationReason::ConsecutiveFailures { .. } => "consecutive_failures",
+            TerminationReason::LoopThrashing { .. } => "loop_thrashing",
+            TerminationReason::DelegationLoop { .. } => "delegation_loop",
+            TerminationReason::ValidationFailure { .. } => "validation_failure",
             TerminationReason::Stopped => "stopped",
             TerminationReason::Interrupted => "interrupted",
             TerminationReason::RestartRequested => "restart_requested",
+            TerminationReason::IdleTimeout { .. } => "idle_timeout",
         }
     }
 
@@ -97,6 +191,80 @@ impl TerminationReason {
     pub fn is_success(&self) -> bool {
         matches!(self, TerminationReason::CompletionPromise)
     }
+
+    /// One-line description of what tripped, for variants that carry context.
+    pub fn detail(&self) -> Option<String> {
+        match self {
+            TerminationReason::MaxIterations { limit } => Some(format!("{limit} iterations used")),
+            TerminationReason::MaxRuntime {
+                limit_seconds,
+                elapsed_seconds,
+            } => Some(format!(
+                "ran {elapsed_seconds}s of {limit_seconds}s allowed"
+            )),
+            TerminationReason::MaxCost {
+                limit_usd,
+                spent_usd,
+            } => Some(format!("spent ${spent_usd:.2} of ${limit_usd:.2} allowed")),
+            TerminationReason::ConsecutiveFailures {
+                hat,
+                failures,
+                threshold,
+                ..
+            } => Some(match hat {
+                Some(hat) => {
+                    format!("{failures} failures in a row (limit {threshold}), last in hat '{hat}'")
+                }
+                None => format!("{failures} failures in a row (limit {threshold})"),
+            }),
+            TerminationReason::LoopThrashing {
+                hat,
+                abandoned_tasks,
+            } => {
+                let by = hat
+                    .as_ref()
+                    .map(|h| format!(" by hat '{h}'"))
+                    .unwrap_or_default();
+                Some(format!(
+                    "abandoned tasks re-dispatched{by}: {}",
+                    abandoned_tasks.join(", ")
+                ))
+            }
+            TerminationReason::DelegationLoop { topics, repeats } => Some(format!(
+                "delegated {} again {repeats} times without progress",
+                topics.join(", ")
+            )),
+            TerminationReason::ValidationFailure { malformed_lines } => {
+                Some(format!("{malformed_lines} malformed event lines in a row"))
+            }
+            TerminationReason::IdleTimeout { idle_seconds } => {
+                Some(format!("no new events for {idle_seconds}s"))
+            }
+            TerminationReason::CompletionPromise
+            | TerminationReason::Stopped
+            | TerminationReason::Interrupted
+            | TerminationReason::RestartRequested => None,
+        }
+    }
+
+    /// Excerpt of the error that ended the loop, if one was captured.
+    pub fn last_error(&self) -> Option<&str> {
+        match self {
+            TerminationReason::ConsecutiveFailures { last_error, .. } => last_error.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// JSON form: `reason` and `exit_code` plus the variant's context fields.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_else(|_| serde_json::json!({}));
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("kind");
+            fields.insert("reason".to_string(), self.as_str().into());
+            fields.insert("exit_code".to_string(), self.exit_code().into());
+        }
+        value
+    }
 }
 
 /// The main event loop orchestrator.
@@ -119,7 +287,23 @@ pub struct EventLoop {
     skill_registry: SkillRegistry,
     /// Robot service for human-in-the-loop communication.
     /// Injected externally when `human.enabled` is true and this is the primary loop.
-    robot_service: Option<Box<dyn RobotService>>,
+    /// Shared so a check-in on the blocking pool never takes it out of the loop.
+    robot_service: Option<Arc<dyn RobotService>>,
+    /// WASM plugins (context providers, event filters, native hats).
+    plugins: PluginHost,
+    /// Rhai scripting hooks (transform, route, terminate).
+    scripts: ScriptHost,
+    /// Why a configured plugin or script failed to load, until
+    /// [`ensure_extensions_loaded`](Self::ensure_extensions_loaded) reports it.
+    extension_error: Option<ExtensionError>,
+    /// Per-iteration backend/model routing rules.
+    routing: RoutingPolicy,
+    /// Progress-driven iteration limit, when `adaptive_budget` is enabled.
+    budget: Option<AdaptiveBudget>,
+    /// Previous session's context, injected into the first prompt only.
+    bootstrap: Option<SessionBootstrap>,
+    /// Container every backend runs in, when `cli.sandbox` is set.
+    sandbox: Option<EnvironmentConfig>,
 }
 
 impl EventLoop {
@@ -165,7 +349,8 @@ impl EventLoop {
     ) -> Self {
         let registry = HatRegistry::from_config(&config);
         let instruction_builder =
-            InstructionBuilder::with_events(config.core.clone(), config.events.clone());
+            InstructionBuilder::with_events(config.core.clone(), config.events.clone())
+                .with_event_syntax(config.event_loop.event_syntax);
 
         let mut bus = EventBus::new();
 
@@ -180,6 +365,8 @@ impl EventLoop {
         // Per spec: "Ralph runs when no hat triggered — Universal fallback for orphaned events"
         let ralph_hat = ralph_proto::Hat::new("ralph", "Ralph").subscribe("*"); // Subscribe to all events
         bus.register(ralph_hat);
+        register_renames(&mut bus, &config);
+        register_debounce(&mut bus, &config);
 
         if registry.is_empty() {
             debug!("Solo mode: Ralph is the only coordinator");
@@ -214,6 +401,9 @@ impl EventLoop {
             String::new()
         };
 
+        let (plugins, scripts, extension_error) =
+            Self::load_extensions(&config, context.workspace());
+
         // When memories are enabled, add tasks CLI instructions alongside scratchpad
         let ralph = HatlessRalph::new(
             config.event_loop.completion_promise.clone(),
@@ -222,7 +412,12 @@ impl EventLoop {
             config.event_loop.starting_event.clone(),
         )
         .with_memories_enabled(config.memories.enabled)
-        .with_skill_index(skill_index);
+        .with_skill_index(skill_index)
+        .with_event_syntax(config.event_loop.event_syntax)
+        .with_contracts(Contracts::from_events(&config.events))
+        .with_cache_friendly_layout(config.cli.cache_friendly_prompts())
+        .with_prompt_pack(Self::load_prompt_pack(&config, context.workspace()))
+        .with_shell_tool(config.shell_tool.enabled);
 
         // Read timestamped events path from marker file, fall back to default
         // The marker file contains a relative path like ".ralph/events-20260127-123456.jsonl"
@@ -233,7 +428,18 @@ impl EventLoop {
                 context.workspace().join(relative)
             })
             .unwrap_or_else(|_| context.events_path());
-        let event_reader = EventReader::new(&events_path);
+        let event_reader = EventReader::new(&events_path)
+            .with_compaction(config.event_loop.compact_events_bytes());
+        let routing = RoutingPolicy::from_config(&config.routing);
+        let budget = config.adaptive_budget.enabled.then(|| {
+            AdaptiveBudget::new(&config.adaptive_budget, config.event_loop.max_iterations)
+        });
+
+        let sandbox = config
+            .cli
+            .sandbox
+            .as_ref()
+            .map(|sandbox| sandbox.environment(context.workspace(), context.repo_root()));
 
         Self {
             config,
@@ -248,6 +454,13 @@ impl EventLoop {
             loop_context: Some(context),
             skill_registry,
             robot_service: None,
+            plugins,
+            scripts,
+            extension_error,
+            routing,
+            budget,
+            bootstrap: None,
+            sandbox,
         }
     }
 
@@ -258,7 +471,8 @@ impl EventLoop {
     ) -> Self {
         let registry = HatRegistry::from_config(&config);
         let instruction_builder =
-            InstructionBuilder::with_events(config.core.clone(), config.events.clone());
+            InstructionBuilder::with_events(config.core.clone(), config.events.clone())
+                .with_event_syntax(config.event_loop.event_syntax);
 
         let mut bus = EventBus::new();
 
@@ -273,6 +487,8 @@ impl EventLoop {
         // Per spec: "Ralph runs when no hat triggered — Universal fallback for orphaned events"
         let ralph_hat = ralph_proto::Hat::new("ralph", "Ralph").subscribe("*"); // Subscribe to all events
         bus.register(ralph_hat);
+        register_renames(&mut bus, &config);
+        register_debounce(&mut bus, &config);
 
         if registry.is_empty() {
             debug!("Solo mode: Ralph is the only coordinator");
@@ -308,6 +524,8 @@ impl EventLoop {
             String::new()
         };
 
+        let (plugins, scripts, extension_error) = Self::load_extensions(&config, workspace_root);
+
         // When memories are enabled, add tasks CLI instructions alongside scratchpad
         let ralph = HatlessRalph::new(
             config.event_loop.completion_promise.clone(),
@@ -316,14 +534,28 @@ impl EventLoop {
             config.event_loop.starting_event.clone(),
         )
         .with_memories_enabled(config.memories.enabled)
-        .with_skill_index(skill_index);
+        .with_skill_index(skill_index)
+        .with_event_syntax(config.event_loop.event_syntax)
+        .with_contracts(Contracts::from_events(&config.events))
+        .with_cache_friendly_layout(config.cli.cache_friendly_prompts())
+        .with_prompt_pack(Self::load_prompt_pack(&config, workspace_root))
+        .with_shell_tool(config.shell_tool.enabled);
 
         // Read events path from marker file, fall back to default if not present
         // The marker file is written by run_loop_impl() at run startup
         let events_path = std::fs::read_to_string(".ralph/current-events")
             .map(|s| s.trim().to_string())
             .unwrap_or_else(|_| ".ralph/events.jsonl".to_string());
-        let event_reader = EventReader::new(&events_path);
+        let event_reader = EventReader::new(&events_path)
+            .with_compaction(config.event_loop.compact_events_bytes());
+        let routing = RoutingPolicy::from_config(&config.routing);
+        let budget = config.adaptive_budget.enabled.then(|| {
+            AdaptiveBudget::new(&config.adaptive_budget, config.event_loop.max_iterations)
+        });
+
+        let sandbox = config.cli.sandbox.as_ref().map(|sandbox| {
+            sandbox.environment(&config.core.workspace_root, &config.core.workspace_root)
+        });
 
         Self {
             config,
@@ -338,9 +570,97 @@ impl EventLoop {
             loop_context: None,
             skill_registry,
             robot_service: None,
+            plugins,
+            scripts,
+            extension_error,
+            routing,
+            budget,
+            bootstrap: None,
+            sandbox,
         }
     }
 
+    /// Loads the prompt pack for `prompts.locale`, falling back to English
+    /// if the pack is missing or invalid.
+    fn load_prompt_pack(config: &RalphConfig, workspace: &Path) -> PromptPack {
+        PromptPack::load(&config.prompts, workspace).unwrap_or_else(|e| {
+            warn!("{e}; using English prompts");
+            PromptPack::default()
+        })
+    }
+
+    /// Loads the configured WASM plugins and compiles the configured scripts.
+    ///
+    /// If either fails, neither is loaded and the error is returned alongside
+    /// empty hosts, to be reported by [`ensure_extensions_loaded`](Self::ensure_extensions_loaded).
+    fn load_extensions(
+        config: &RalphConfig,
+        workspace_root: &std::path::Path,
+    ) -> (PluginHost, ScriptHost, Option<ExtensionError>) {
+        let loaded = PluginHost::from_config(&config.plugins, workspace_root)
+            .map_err(ExtensionError::from)
+            .and_then(|plugins| {
+                let scripts = ScriptHost::from_config(&config.scripts)?;
+                Ok((plugins, scripts))
+            });
+        match loaded {
+            Ok((plugins, scripts)) => (plugins, scripts, None),
+            Err(e) => (PluginHost::new(), ScriptHost::new(), Some(e)),
+        }
+    }
+
+    /// Returns the error that kept a configured plugin or script from loading.
+    ///
+    /// Call it before [`initialize`](Self::initialize): a loop whose plugins
+    /// or scripts are missing would run its hats without the filters, hooks,
+    /// and handlers the config asks for, so it must not start.
+    pub fn ensure_extensions_loaded(&mut self) -> Result<(), ExtensionError> {
+        self.extension_error.take().map_or(Ok(()), Err)
+    }
+
+    /// Builds the read-only loop snapshot passed to scripts.
+    fn script_state(&self) -> ScriptState {
+        ScriptState {
+            iteration: self.state.iteration,
+            cumulative_cost: self.state.cumulative_cost,
+            consecutive_failures: self.state.consecutive_failures,
+            elapsed_seconds: self.state.elapsed().as_secs(),
+            last_hat: self.state.last_hat.as_ref().map(|h| h.to_string()),
+            pending: self
+                .bus
+                .hat_ids()
+                .map(|id| {
+                    let depth = self.bus.peek_pending(id).map_or(0, Vec::len);
+                    (id.to_string(), depth)
+                })
+                .collect(),
+        }
+    }
+
+    /// Applies the route script, targeting the event at the hat it names.
+    fn apply_script_route(&self, event: Event) -> Event {
+        if self.scripts.is_empty() || event.target.is_some() {
+            return event;
+        }
+
+        let script_event = ScriptEvent {
+            topic: event.topic.to_string(),
+            payload: event.payload.clone(),
+        };
+        let Some(target) = self.scripts.route(&script_event, &self.script_state()) else {
+            return event;
+        };
+
+        let target = HatId::new(target);
+        if self.bus.get_hat(&target).is_none() {
+            warn!(topic = %event.topic, hat = %target, "route script named an unknown hat, using topic routing");
+            return event;
+        }
+
+        debug!(topic = %event.topic, hat = %target, "Event routed by script");
+        event.with_target(target)
+    }
+
     /// Injects a robot service for human-in-the-loop communication.
     ///
     /// Call this after construction to enable `human.interact` event handling,
@@ -349,7 +669,13 @@ impl EventLoop {
     /// keeping the core event loop decoupled from any specific communication
     /// platform.
     pub fn set_robot_service(&mut self, service: Box<dyn RobotService>) {
-        self.robot_service = Some(service);
+        self.robot_service = Some(Arc::from(service));
+    }
+
+    /// Puts a previous session's summary and unfinished tasks in the next
+    /// prompt Ralph builds; see [`crate::bootstrap`].
+    pub fn set_bootstrap(&mut self, bootstrap: SessionBootstrap) {
+        self.bootstrap = Some(bootstrap);
     }
 
     /// Returns the loop context, if one was provided.
@@ -365,6 +691,19 @@ impl EventLoop {
             .unwrap_or_else(|| PathBuf::from(".ralph/agent/tasks.jsonl"))
     }
 
+    /// Returns the amendments path based on loop context or default.
+    fn amendments_path(&self) -> PathBuf {
+        self.loop_context.as_ref().map_or_else(
+            || {
+                self.config
+                    .core
+                    .workspace_root
+                    .join(".ralph/agent/amendments.jsonl")
+            },
+            LoopContext::amendments_path,
+        )
+    }
+
     /// Returns the scratchpad path based on loop context or config.
     fn scratchpad_path(&self) -> PathBuf {
         self.loop_context
@@ -388,6 +727,11 @@ impl EventLoop {
         &self.registry
     }
 
+    /// Returns this run's diagnostics directory, if diagnostics are enabled.
+    pub fn diagnostics_dir(&self) -> Option<&Path> {
+        self.diagnostics.session_dir()
+    }
+
     /// Gets the backend configuration for a hat.
     ///
     /// If the hat has a backend configured, returns that.
@@ -398,6 +742,120 @@ impl EventLoop {
             .and_then(|config| config.backend.as_ref())
     }
 
+    /// Gets the `cli:` block configured for a hat, if any.
+    ///
+    /// Apply it over the top-level `cli:` with [`HatCliConfig::apply`].
+    pub fn get_hat_cli(&self, hat_id: &HatId) -> Option<&HatCliConfig> {
+        self.registry
+            .get_config(hat_id)
+            .and_then(|config| config.cli.as_ref())
+    }
+
+    /// Gets the generation settings configured for a hat, if any.
+    pub fn get_hat_generation(&self, hat_id: &HatId) -> Option<&GenerationConfig> {
+        self.registry
+            .get_config(hat_id)
+            .map(|config| &config.generation)
+            .filter(|generation| !generation.is_empty())
+    }
+
+    /// Registers a hat implemented in Rust; see [`NativeHat`].
+    ///
+    /// The hat subscribes like any other, but its events are handled by
+    /// `handler` as soon as they are published, without a backend call.
+    pub fn register_native_hat(&mut self, hat: Hat, handler: Box<dyn NativeHat>) {
+        self.bus.register(hat.clone());
+        self.registry.register_native(hat, handler);
+    }
+
+    /// Gets the scouts configured for a hat, if any.
+    pub fn get_hat_scouts(&self, hat_id: &HatId) -> Option<&ScoutsConfig> {
+        self.registry
+            .get_config(hat_id)
+            .and_then(|config| config.scouts.as_ref())
+    }
+
+    /// Returns true if the hat opted into backend response caching.
+    pub fn hat_caches_responses(&self, hat_id: &HatId) -> bool {
+        self.registry
+            .get_config(hat_id)
+            .is_some_and(|config| config.cache_responses)
+    }
+
+    /// Returns the hat's `mutex_group`, if it has one.
+    pub fn hat_mutex_group(&self, hat_id: &HatId) -> Option<&str> {
+        self.registry
+            .get_config(hat_id)
+            .and_then(|config| config.mutex_group.as_deref())
+    }
+
+    /// Blocks until no other loop runs a hat in `hat_id`'s mutex group, and
+    /// returns the guard that keeps it that way.
+    ///
+    /// Returns `None` when the hat has no group, there's no loop context to
+    /// find the shared lock in, or locking fails (the hat then runs anyway).
+    pub fn lock_mutex_group(&self, hat_id: &HatId) -> Option<FileLockGuard> {
+        let group = self.hat_mutex_group(hat_id)?;
+        let lock = FileLock::new(self.loop_context.as_ref()?.mutex_group_path(group))
+            .inspect_err(|e| warn!(group, error = %e, "Failed to open mutex group lock"))
+            .ok()?;
+        let guard = match lock.try_exclusive() {
+            Ok(Some(guard)) => Ok(guard),
+            Ok(None) => {
+                info!(hat = %hat_id, group, "Waiting for another loop's hat in mutex group");
+                lock.exclusive()
+            }
+            Err(e) => Err(e),
+        };
+        guard
+            .inspect_err(
+                |e| warn!(group, error = %e, "Failed to lock mutex group; running unserialized"),
+            )
+            .ok()
+    }
+
+    /// Picks the backend and model for the active hat's next iteration.
+    ///
+    /// Evaluates `routing:` rules against the event that triggered the
+    /// iteration. Returns `None` when no rule matches; callers then fall back
+    /// to [`get_hat_backend`](Self::get_hat_backend) and the global backend.
+    pub fn route(&self, hat_id: &HatId) -> Option<RouteDecision> {
+        if self.routing.is_empty() {
+            return None;
+        }
+        let trigger = self.state.last_trigger.as_ref();
+        let ctx = PredicateContext {
+            // process_output increments the counter after the iteration runs.
+            iteration: self.state.iteration + 1,
+            activations: *self.state.hat_activation_counts.get(hat_id).unwrap_or(&0),
+            cost: self.state.cumulative_cost,
+            elapsed_secs: self.state.elapsed().as_secs(),
+            topic: trigger.map_or("", |e| e.topic.as_str()),
+            payload: trigger.map_or("", |e| e.payload.as_str()),
+        };
+        let decision = self.routing.select(hat_id.as_str(), &ctx)?;
+        debug!(
+            hat = %hat_id.as_str(),
+            rule = %decision.rule,
+            model = ?decision.model,
+            "Routing rule matched"
+        );
+        Some(decision)
+    }
+
+    /// Gets the container environment for a hat.
+    ///
+    /// A hat's own `environment` takes precedence over the top-level one,
+    /// then the `cli.sandbox`. Returns None when the backend should run
+    /// directly on the host.
+    pub fn get_hat_environment(&self, hat_id: &HatId) -> Option<&EnvironmentConfig> {
+        self.registry
+            .get_config(hat_id)
+            .and_then(|config| config.environment.as_ref())
+            .or(self.config.environment.as_ref())
+            .or(self.sandbox.as_ref())
+    }
+
     /// Adds an observer that receives all published events.
     ///
     /// Multiple observers can be added (e.g., session recorder + TUI).
@@ -421,41 +879,129 @@ impl EventLoop {
         self.bus.set_observer(observer);
     }
 
+    /// Returns true while `.ralph/pause-requested` exists.
+    ///
+    /// The loop runner holds between iterations until the file is removed
+    /// (by `ralph resume` or the dashboard's resume button).
+    pub fn pause_requested(&self) -> bool {
+        let path = self.loop_context.as_ref().map_or_else(
+            || {
+                self.config
+                    .core
+                    .workspace_root
+                    .join(".ralph")
+                    .join(loop_control::PAUSE_REQUESTED_FILE)
+            },
+            LoopContext::pause_requested_path,
+        );
+        loop_control::pause_requested(&path)
+    }
+
+    /// Checks for a pause request between iterations and records the loop's
+    /// [`LoopStatus`].
+    ///
+    /// Publishes `ralph.paused` when a pause takes effect and `ralph.resumed`
+    /// when it's lifted. Returns true while the loop should hold.
+    pub fn update_pause(&mut self) -> bool {
+        let requested = self.pause_requested();
+        match (requested, self.state.status) {
+            (true, LoopStatus::Running) => {
+                self.state.status = LoopStatus::Paused;
+                self.state.paused_at = Some(Instant::now());
+                info!(
+                    iteration = self.state.iteration,
+                    "Loop paused. Run `ralph resume` or use the dashboard to continue."
+                );
+                let payload = lifecycle::Paused {
+                    iteration: self.state.iteration,
+                };
+                self.publish_lifecycle(lifecycle::PAUSED_TOPIC, &payload);
+            }
+            (false, LoopStatus::Paused) => {
+                self.state.status = LoopStatus::Running;
+                let paused = self
+                    .state
+                    .paused_at
+                    .take()
+                    .map_or(Duration::ZERO, |at| at.elapsed());
+                info!(paused_secs = paused.as_secs(), "Loop resumed.");
+                let payload = lifecycle::Resumed {
+                    iteration: self.state.iteration,
+                    paused_secs: paused.as_secs_f64(),
+                };
+                self.publish_lifecycle(lifecycle::RESUMED_TOPIC, &payload);
+            }
+            _ => {}
+        }
+        requested
+    }
+
     /// Checks if any termination condition is met.
     pub fn check_termination(&self) -> Option<TerminationReason> {
         let cfg = &self.config.event_loop;
 
-        if self.state.iteration >= cfg.max_iterations {
-            return Some(TerminationReason::MaxIterations);
+        let max_iterations = self.max_iterations();
+        if self.state.iteration >= max_iterations {
+            return Some(TerminationReason::MaxIterations {
+                limit: max_iterations,
+            });
         }
 
-        if self.state.elapsed().as_secs() >= cfg.max_runtime_seconds {
-            return Some(TerminationReason::MaxRuntime);
+        let elapsed_seconds = self.state.elapsed().as_secs();
+        if elapsed_seconds >= cfg.max_runtime_seconds {
+            return Some(TerminationReason::MaxRuntime {
+                limit_seconds: cfg.max_runtime_seconds,
+                elapsed_seconds,
+            });
         }
 
         if let Some(max_cost) = cfg.max_cost_usd
             && self.state.cumulative_cost >= max_cost
         {
-            return Some(TerminationReason::MaxCost);
+            return Some(TerminationReason::MaxCost {
+                limit_usd: max_cost,
+                spent_usd: self.state.cumulative_cost,
+            });
         }
 
         if self.state.consecutive_failures >= cfg.max_consecutive_failures {
-            return Some(TerminationReason::ConsecutiveFailures);
+            return Some(TerminationReason::ConsecutiveFailures {
+                hat: self.state.last_hat.clone(),
+                failures: self.state.consecutive_failures,
+                threshold: cfg.max_consecutive_failures,
+                last_error: self.state.last_error.clone(),
+            });
         }
 
         // Check for loop thrashing: planner keeps dispatching abandoned tasks
         if self.state.abandoned_task_redispatches >= 3 {
-            return Some(TerminationReason::LoopThrashing);
+            return Some(TerminationReason::LoopThrashing {
+                hat: self.state.last_hat.clone(),
+                abandoned_tasks: self.state.abandoned_tasks.clone(),
+            });
+        }
+
+        // Check for Ralph ping-ponging the same delegation
+        if cfg.max_repeated_delegations > 0
+            && self.state.repeated_delegations >= cfg.max_repeated_delegations
+        {
+            return Some(TerminationReason::DelegationLoop {
+                topics: self.state.repeated_delegation_topics.clone(),
+                repeats: self.state.repeated_delegations,
+            });
         }
 
         // Check for validation failures: too many consecutive malformed JSONL lines
         if self.state.consecutive_malformed_events >= 3 {
-            return Some(TerminationReason::ValidationFailure);
+            return Some(TerminationReason::ValidationFailure {
+                malformed_lines: self.state.consecutive_malformed_events,
+            });
         }
 
         // Check for stop signal from Telegram /stop or CLI stop-requested
-        let stop_path =
-            std::path::Path::new(&self.config.core.workspace_root).join(".ralph/stop-requested");
+        let stop_path = std::path::Path::new(&self.config.core.workspace_root)
+            .join(".ralph")
+            .join(crate::loop_control::STOP_REQUESTED_FILE);
         if stop_path.exists() {
             let _ = std::fs::remove_file(&stop_path);
             return Some(TerminationReason::Stopped);
@@ -468,14 +1014,25 @@ impl EventLoop {
             return Some(TerminationReason::RestartRequested);
         }
 
+        if !self.scripts.is_empty()
+            && let Some(reason) = self.scripts.should_terminate(&self.script_state())
+        {
+            info!(reason = %reason, "Terminate script requested stop");
+            return Some(TerminationReason::Stopped);
+        }
+
         None
     }
 
     /// Checks if a completion event was received and returns termination reason.
     ///
     /// Completion is only accepted via JSONL events (e.g., `ralph emit`).
+    /// With `completion_confirmation` above 1, a completion event while tasks
+    /// are still pending must be repeated in consecutive iterations before the
+    /// loop stops.
     pub fn check_completion_event(&mut self) -> Option<TerminationReason> {
         if !self.state.completion_requested {
+            self.state.completion_streak = 0;
             return None;
         }
 
@@ -504,6 +1061,11 @@ impl EventLoop {
             return None;
         }
 
+        self.state.completion_streak += 1;
+        if self.completion_unconfirmed() {
+            return None;
+        }
+
         // Log warning if tasks remain open (informational only)
         if self.config.memories.enabled {
             if let Ok(false) = self.verify_tasks_complete() {
@@ -532,37 +1094,89 @@ impl EventLoop {
         Some(TerminationReason::CompletionPromise)
     }
 
+    /// Holds back a completion event that hasn't met `completion_confirmation`.
+    ///
+    /// Returns true (and asks the agent to confirm) when tasks are still
+    /// pending and the promise hasn't been repeated enough times in a row.
+    fn completion_unconfirmed(&mut self) -> bool {
+        let required = self.config.event_loop.completion_confirmation.max(1);
+        let streak = self.state.completion_streak;
+        if streak >= required {
+            return false;
+        }
+
+        let (tasks_complete, open_tasks) = if self.config.memories.enabled {
+            (self.verify_tasks_complete(), self.get_open_task_list())
+        } else {
+            (self.verify_scratchpad_complete(), Vec::new())
+        };
+        if matches!(tasks_complete, Ok(true)) {
+            return false;
+        }
+
+        info!(
+            streak,
+            required, "Completion event held back - tasks still pending, awaiting confirmation"
+        );
+        let mut payload = format!(
+            "Completion signal {streak}/{required} received, but tasks are still pending. \
+             Re-verify the work. Emit {} again next iteration to confirm, or finish the remaining tasks.",
+            self.config.event_loop.completion_promise
+        );
+        if !open_tasks.is_empty() {
+            payload.push_str("\n\nOpen tasks:\n");
+            for task in &open_tasks {
+                payload.push_str("- ");
+                payload.push_str(task);
+                payload.push('\n');
+            }
+        }
+        self.bus.publish(Event::new("task.resume", &payload));
+        true
+    }
+
     /// Initializes the loop by publishing the start event.
     pub fn initialize(&mut self, prompt_content: &str) {
-        // Use configured starting_event or default to task.start for backward compatibility
-        let topic = self
-            .config
-            .event_loop
-            .starting_event
-            .clone()
-            .unwrap_or_else(|| "task.start".to_string());
-        self.initialize_with_topic(&topic, prompt_content);
+        let start_event = self.start_event(prompt_content);
+        self.initialize_with_event(start_event, prompt_content);
+    }
+
+    /// Returns the event a fresh run starts with.
+    ///
+    /// That's the `--start-event` override if given, else the configured
+    /// `starting_event`, else `task.start` for backward compatibility. The
+    /// prompt is the payload unless the override brings its own.
+    pub fn start_event(&self, prompt_content: &str) -> Event {
+        let event_loop = &self.config.event_loop;
+        if let Some(start) = &event_loop.start_event {
+            let payload = start.payload.as_deref().unwrap_or(prompt_content);
+            return Event::new(start.topic.as_str(), payload);
+        }
+        let topic = event_loop.starting_event.as_deref().unwrap_or("task.start");
+        Event::new(topic, prompt_content)
     }
 
     /// Initializes the loop for resume mode by publishing task.resume.
     ///
-    /// Per spec: "User can run `ralph resume` to restart reading existing scratchpad."
+    /// Used by `ralph run --continue` to restart from the existing scratchpad.
     /// The planner should read the existing scratchpad rather than doing fresh gap analysis.
     pub fn initialize_resume(&mut self, prompt_content: &str) {
         // Resume always uses task.resume regardless of starting_event config
-        self.initialize_with_topic("task.resume", prompt_content);
+        self.initialize_with_event(Event::new("task.resume", prompt_content), prompt_content);
     }
 
-    /// Common initialization logic with configurable topic.
-    fn initialize_with_topic(&mut self, topic: &str, prompt_content: &str) {
+    /// Common initialization logic: records the objective and publishes the
+    /// first event.
+    fn initialize_with_event(&mut self, start_event: Event, prompt_content: &str) {
         // Store the objective so it persists across all iterations.
         // After iteration 1, bus.take_pending() consumes the start event,
         // so without this the objective would be invisible to later hats.
         self.ralph.set_objective(prompt_content.to_string());
 
-        let start_event = Event::new(topic, prompt_content);
-        self.bus.publish(start_event);
-        debug!(topic = topic, "Published {} event", topic);
+        let topic = start_event.topic.clone();
+        self.bus
+            .publish(start_event.with_source(prompt_guard::USER_SOURCE));
+        debug!(topic = %topic, "Published {} event", topic);
     }
 
     /// Gets the next hat to execute (if any have pending events).
@@ -596,6 +1210,135 @@ impl EventLoop {
         }
     }
 
+    /// Picks hats whose pending events can be handled concurrently.
+    ///
+    /// With `event_loop.max_parallel_hats` above 1, returns up to that many
+    /// custom hats with pending events that don't depend on each other:
+    /// neither publishes a topic the other subscribes to, and they aren't in
+    /// the same `mutex_group`. Returns an empty list when there's nothing to
+    /// parallelize (fewer than two such hats, solo mode, or pending human
+    /// events, which Ralph handles on his own); use
+    /// [`next_hat`](Self::next_hat) then.
+    pub fn next_parallel_hats(&self) -> Vec<HatId> {
+        let limit = self.config.event_loop.max_parallel_hats;
+        if limit < 2 || self.registry.is_empty() || self.bus.has_human_pending() {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<&HatId> = self.bus.hat_ids().collect();
+        candidates.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        let mut batch: Vec<&Hat> = Vec::new();
+        for id in candidates {
+            if batch.len() == limit {
+                break;
+            }
+            if self.bus.is_paused(id) || self.registry.native_ids().any(|native| native == id) {
+                continue;
+            }
+            let Some(hat) = self.registry.get(id) else {
+                continue;
+            };
+            // The hat must be the one its pending events activate
+            let activates = self.bus.peek_pending(id).is_some_and(|events| {
+                events.iter().any(|event| {
+                    self.registry
+                        .get_for_topic(event.topic.as_str())
+                        .is_some_and(|active| active.id == *id)
+                })
+            });
+            if activates && batch.iter().all(|other| self.hats_independent(hat, other)) {
+                batch.push(hat);
+            }
+        }
+
+        if batch.len() < 2 {
+            return Vec::new();
+        }
+        batch.into_iter().map(|hat| hat.id.clone()).collect()
+    }
+
+    /// Returns true if neither hat can trigger the other and they don't
+    /// share a mutex group.
+    fn hats_independent(&self, a: &Hat, b: &Hat) -> bool {
+        let triggers = |from: &Hat, to: &Hat| from.publishes.iter().any(|t| to.is_subscribed(t));
+        let same_group = matches!(
+            (self.hat_mutex_group(&a.id), self.hat_mutex_group(&b.id)),
+            (Some(x), Some(y)) if x == y
+        );
+        !triggers(a, b) && !triggers(b, a) && !same_group
+    }
+
+    /// Builds one coordinator prompt per hat of a batch from
+    /// [`next_parallel_hats`](Self::next_parallel_hats).
+    ///
+    /// Each prompt carries only that hat's pending events, plus a note naming
+    /// the hats running alongside it. Hats whose events were dropped (outside
+    /// their schedule, or exhausted) get no prompt. The batch is one
+    /// iteration: [`process_output`](Self::process_output) charges it to
+    /// every hat that got a prompt.
+    pub fn build_parallel_prompts(&mut self, hats: &[HatId]) -> Vec<(HatId, String)> {
+        self.begin_iteration();
+        self.state.hat_triggers.clear();
+        let ralph = HatId::new("ralph");
+
+        let mut built = Vec::new();
+        let mut trigger = None;
+        for hat in hats {
+            let prompt = self.build_coordinator_prompt(&ralph, Some(hat));
+            if self.state.last_active_hat_ids.is_empty() {
+                continue;
+            }
+            trigger = trigger.or_else(|| self.state.last_trigger.take());
+            built.push((hat.clone(), prompt));
+        }
+        self.state.last_trigger = trigger;
+        self.state.last_active_hat_ids = built.iter().map(|(hat, _)| hat.clone()).collect();
+
+        let names: Vec<&str> = built.iter().map(|(hat, _)| hat.as_str()).collect();
+        built
+            .iter()
+            .map(|(hat, prompt)| {
+                let others: Vec<&str> = names
+                    .iter()
+                    .copied()
+                    .filter(|name| *name != hat.as_str())
+                    .collect();
+                let prompt = if others.is_empty() {
+                    prompt.clone()
+                } else {
+                    format!(
+                        "{prompt}\n\n## PARALLEL HATS\n\nYou are working as `{hat}` only. \
+                         Other hats run at the same time in this workspace on their own \
+                         events ({}): leave their files and tasks alone, and publish only \
+                         your own events.\n",
+                        others
+                            .iter()
+                            .map(|name| format!("`{name}`"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                };
+                (hat.clone(), prompt)
+            })
+            .collect()
+    }
+
+    /// Delivers debounced events whose window has closed.
+    ///
+    /// Call before [`next_hat`](Self::next_hat) so coalesced bursts reach
+    /// their hats.
+    pub fn release_debounced(&mut self) {
+        let released = self.bus.release_debounced(std::time::Instant::now());
+        if released > 0 {
+            debug!(released, "Delivered debounced events");
+        }
+    }
+
+    /// When the next debounced event is due, if any are held.
+    pub fn next_debounce_due(&self) -> Option<std::time::Instant> {
+        self.bus.next_debounce_due()
+    }
+
     /// Checks if any hats have pending events.
     ///
     /// Use this after `process_output` to detect if the LLM failed to publish an event.
@@ -669,6 +1412,8 @@ impl EventLoop {
     /// primed memories to the prompt context. If a scratchpad file exists and is
     /// non-empty, its content is also prepended (before memories).
     pub fn build_prompt(&mut self, hat_id: &HatId) -> Option<String> {
+        self.begin_iteration();
+
         // Handle "ralph" hat - the constant coordinator
         // Per spec: "Hatless Ralph is constant — Cannot be replaced, overwritten, or configured away"
         if hat_id.as_str() == "ralph" {
@@ -682,105 +1427,33 @@ impl EventLoop {
                 let (guidance_events, regular_events): (Vec<_>, Vec<_>) = events
                     .into_iter()
                     .partition(|e| e.topic.as_str() == "human.guidance");
+                self.state.last_trigger = regular_events.first().cloned();
 
-                let events_context = regular_events
-                    .iter()
-                    .map(|e| Self::format_event(e))
-                    .collect::<Vec<_>>()
-                    .join("\n");
+                let events_context = self.format_events(&regular_events);
 
                 // Persist and inject human guidance into prompt if present
                 self.update_robot_guidance(guidance_events);
                 self.apply_robot_guidance();
 
                 // Build base prompt and prepend memories + scratchpad + ready tasks
-                let base_prompt = self.ralph.build_prompt(&events_context, &[]);
+                // Iteration context goes after the stable prefix (empty unless
+                // prompts use the cache-friendly layout)
+                let (stable, base_prompt) = self.ralph.build_prompt_parts(&events_context, &[]);
                 self.ralph.clear_robot_guidance();
                 let with_skills = self.prepend_auto_inject_skills(base_prompt);
-                let with_scratchpad = self.prepend_scratchpad(with_skills);
-                let final_prompt = self.prepend_ready_tasks(with_scratchpad);
+                let with_plugins = self.prepend_plugin_context(with_skills, hat_id);
+                let with_scratchpad = self.prepend_scratchpad(with_plugins);
+                let with_tasks = self.prepend_ready_tasks(with_scratchpad);
+                let with_previous = self.prepend_previous_iteration(with_tasks);
+                let with_bootstrap = self.prepend_bootstrap(with_previous);
+                let final_prompt = self.prepend_amendments(with_bootstrap);
 
                 debug!("build_prompt: routing to HatlessRalph (solo mode)");
-                return Some(final_prompt);
+                return Some(format!("{stable}{final_prompt}"));
             } else {
                 // Multi-hat mode: collect events and determine active hats
-                let mut all_hat_ids: Vec<HatId> = self.bus.hat_ids().cloned().collect();
-                // Deterministic ordering (avoid HashMap iteration order nondeterminism).
-                all_hat_ids.sort_by(|a, b| a.as_str().cmp(b.as_str()));
-
-                let mut all_events = Vec::new();
-                let mut system_events = Vec::new();
-
-                for id in &all_hat_ids {
-                    let pending = self.bus.take_pending(id);
-                    if pending.is_empty() {
-                        continue;
-                    }
-
-                    let (drop_pending, exhausted_event) = self.check_hat_exhaustion(id, &pending);
-                    if drop_pending {
-                        // Drop the pending events that would have activated the hat.
-                        if let Some(exhausted_event) = exhausted_event {
-                            all_events.push(exhausted_event.clone());
-                            system_events.push(exhausted_event);
-                        }
-                        continue;
-                    }
-
-                    all_events.extend(pending);
-                }
-
-                let mut human_events = self.bus.take_human_pending();
-                all_events.append(&mut human_events);
-
-                // Publish orchestrator-generated system events after consuming pending events,
-                // so they become visible in the event log and can be handled next iteration.
-                for event in system_events {
-                    self.bus.publish(event);
-                }
-
-                // Separate human.guidance events from regular events
-                let (guidance_events, regular_events): (Vec<_>, Vec<_>) = all_events
-                    .into_iter()
-                    .partition(|e| e.topic.as_str() == "human.guidance");
-
-                // Persist and inject human guidance before building prompt (must happen before
-                // immutable borrows from determine_active_hats)
-                self.update_robot_guidance(guidance_events);
-                self.apply_robot_guidance();
-
-                // Determine which hats are active based on regular events
-                let active_hat_ids = self.determine_active_hat_ids(&regular_events);
-                self.record_hat_activations(&active_hat_ids);
-                self.state.last_active_hat_ids = active_hat_ids.clone();
-                let active_hats = self.determine_active_hats(&regular_events);
-
-                // Format events for context
-                let events_context = regular_events
-                    .iter()
-                    .map(|e| Self::format_event(e))
-                    .collect::<Vec<_>>()
-                    .join("\n");
-
-                // Build base prompt and prepend memories + scratchpad if available
-                let base_prompt = self.ralph.build_prompt(&events_context, &active_hats);
-
-                // Build prompt with active hats - filters instructions to only active hats
-                debug!(
-                    "build_prompt: routing to HatlessRalph (multi-hat coordinator mode), active_hats: {:?}",
-                    active_hats
-                        .iter()
-                        .map(|h| h.id.as_str())
-                        .collect::<Vec<_>>()
-                );
-
-                // Clear guidance after active_hats references are no longer needed
-                self.ralph.clear_robot_guidance();
-                let with_skills = self.prepend_auto_inject_skills(base_prompt);
-                let with_scratchpad = self.prepend_scratchpad(with_skills);
-                let final_prompt = self.prepend_ready_tasks(with_scratchpad);
-
-                return Some(final_prompt);
+                self.state.hat_triggers.clear();
+                return Some(self.build_coordinator_prompt(hat_id, None));
             }
         }
 
@@ -788,11 +1461,8 @@ impl EventLoop {
         // next_hat() always returns "ralph" when custom hats are defined.
         // But we keep this code path for backward compatibility and tests.
         let events = self.bus.take_pending(&hat_id.clone());
-        let events_context = events
-            .iter()
-            .map(|e| Self::format_event(e))
-            .collect::<Vec<_>>()
-            .join("\n");
+        self.state.last_trigger = events.first().cloned();
+        let events_context = self.format_events(&events);
 
         let hat = self.registry.get(hat_id)?;
 
@@ -814,6 +1484,146 @@ impl EventLoop {
         )
     }
 
+    /// Records where the iteration starts, for runtime, protected-path, and
+    /// budget accounting.
+    fn begin_iteration(&mut self) {
+        self.state.iteration_started_at = Some(std::time::Instant::now());
+        if !self.config.protect.is_empty() || self.budget.is_some() {
+            let workspace = self.workspace();
+            self.state.iteration_base =
+                crate::utils::run_blocking(|| crate::git_ops::get_head_sha(&workspace)).ok();
+            if self.state.protect_base.is_none() && !self.config.protect.is_empty() {
+                self.state.protect_base = self.state.iteration_base.clone();
+            }
+        }
+        if self.budget.is_some() {
+            self.state.closed_tasks_at_start = Some(self.closed_task_count());
+        }
+    }
+
+    /// Builds Ralph's coordinator prompt from the hats' pending events.
+    ///
+    /// With `only`, just that hat's queue is drained and only it can be
+    /// active; the other queues are left for later iterations.
+    fn build_coordinator_prompt(&mut self, hat_id: &HatId, only: Option<&HatId>) -> String {
+        self.update_phase();
+        let mut all_hat_ids: Vec<HatId> = self.bus.hat_ids().cloned().collect();
+        // Deterministic ordering (avoid HashMap iteration order nondeterminism).
+        all_hat_ids.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        // Read queue depths before the queues are drained below.
+        let backpressure = self.queue_pressure(&all_hat_ids);
+        self.ralph.set_backpressure(backpressure);
+
+        let mut all_events = Vec::new();
+        let mut system_events = Vec::new();
+
+        for id in &all_hat_ids {
+            if only.is_some_and(|only| only != id) {
+                continue;
+            }
+            // Blocked hats keep their queue until they're unblocked
+            if self.bus.is_paused(id) {
+                continue;
+            }
+            let pending = self.bus.take_pending(id);
+            if pending.is_empty() {
+                continue;
+            }
+
+            if let Some(unavailable_event) = self.check_hat_window(id, &pending) {
+                // Outside its scheduling windows: drop the events.
+                all_events.push(unavailable_event.clone());
+                system_events.push(unavailable_event);
+                continue;
+            }
+
+            let (drop_pending, exhausted_event) = self.check_hat_exhaustion(id, &pending);
+            if drop_pending {
+                // Drop the pending events that would have activated the hat.
+                if let Some(exhausted_event) = exhausted_event {
+                    all_events.push(exhausted_event.clone());
+                    system_events.push(exhausted_event);
+                }
+                continue;
+            }
+
+            if self.config.blocked.enabled {
+                self.state.hat_triggers.insert(id.clone(), pending.clone());
+            }
+            all_events.extend(pending);
+        }
+
+        // Human events go to the coordinator, never to one hat of a batch
+        if only.is_none() {
+            let mut human_events = self.bus.take_human_pending();
+            all_events.append(&mut human_events);
+        }
+
+        // Publish orchestrator-generated system events after consuming pending events,
+        // so they become visible in the event log and can be handled next iteration.
+        for event in system_events {
+            self.bus.publish(event);
+        }
+
+        // Separate human.guidance events from regular events
+        let (guidance_events, regular_events): (Vec<_>, Vec<_>) = all_events
+            .into_iter()
+            .partition(|e| e.topic.as_str() == "human.guidance");
+        self.state.last_trigger = regular_events.first().cloned();
+
+        // Persist and inject human guidance before building prompt (must happen before
+        // immutable borrows from the active hat lookup)
+        self.update_robot_guidance(guidance_events);
+        self.apply_robot_guidance();
+
+        // Determine which hats are active based on regular events
+        let mut active_hat_ids = self.determine_active_hat_ids(&regular_events);
+        if let Some(only) = only {
+            active_hat_ids.retain(|id| id == only);
+        }
+        self.record_hat_activations(&active_hat_ids);
+        self.state.last_active_hat_ids = active_hat_ids.clone();
+        // Resolve from the recorded ids: predicates can depend on activation counts.
+        let active_hats = self.hats_for_ids(&active_hat_ids);
+
+        // Format events for context
+        let events_context = self.format_events(&regular_events);
+
+        // Build base prompt and prepend memories + scratchpad if available
+        // Iteration context goes after the stable prefix (empty unless
+        // prompts use the cache-friendly layout)
+        let (stable, base_prompt) = self.ralph.build_prompt_parts(&events_context, &active_hats);
+
+        // Build prompt with active hats - filters instructions to only active hats
+        debug!(
+            "build_prompt: routing to HatlessRalph (multi-hat coordinator mode), active_hats: {:?}",
+            active_hats
+                .iter()
+                .map(|h| h.id.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        // Clear guidance after active_hats references are no longer needed
+        self.ralph.clear_robot_guidance();
+        let with_skills = self.prepend_auto_inject_skills(base_prompt);
+        let with_plugins = self.prepend_plugin_context(with_skills, hat_id);
+        let with_scratchpad = self.prepend_scratchpad(with_plugins);
+        let with_tasks = self.prepend_ready_tasks(with_scratchpad);
+        let with_phase = self.prepend_phase(with_tasks);
+        let with_blocked = self.prepend_blockers(with_phase);
+        let with_previous = self.prepend_previous_iteration(with_blocked);
+        let with_bootstrap = self.prepend_bootstrap(with_previous);
+        let with_amendments = self.prepend_amendments(with_bootstrap);
+        let final_prompt = if active_hat_ids.is_empty() {
+            self.prepend_delegation_warning(with_amendments)
+        } else {
+            with_amendments
+        };
+
+        format!("{stable}{final_prompt}")
+    }
+
     /// Stores guidance payloads, persists them to scratchpad, and prepares them for prompt injection.
     ///
     /// Guidance events are ephemeral in the event bus (consumed by `take_pending`).
@@ -910,6 +1720,9 @@ impl EventLoop {
         // 2. RObot interaction skill — gated by robot.enabled
         self.inject_robot_skill(&mut prefix);
 
+        // 2b. Escalation channel — gated by questions.enabled
+        self.inject_questions_note(&mut prefix);
+
         // 3. Other auto-inject skills from the registry
         self.inject_custom_auto_skills(&mut prefix);
 
@@ -1028,6 +1841,26 @@ impl EventLoop {
         }
     }
 
+    /// Tells agents they may escalate a blocking decision with `human.question`.
+    ///
+    /// Gated by `questions.enabled`.
+    fn inject_questions_note(&self, prefix: &mut String) {
+        if !self.config.questions.enabled {
+            return;
+        }
+
+        if !prefix.is_empty() {
+            prefix.push_str("\n\n");
+        }
+        prefix.push_str(&format!(
+            "<human-questions>\nIf you cannot continue without a decision only a human can make, \
+             ask one specific question with `ralph emit {QUESTION_TOPIC} \"<question>\"` and end \
+             your turn. The loop pauses until the human replies; the reply arrives as a \
+             `{ANSWER_TOPIC}` event. Don't ask about anything you can find out yourself.\n\
+             </human-questions>"
+        ));
+    }
+
     /// Injects any user-configured auto-inject skills (excluding built-in ralph-tools/robot-interaction).
     fn inject_custom_auto_skills(&self, prefix: &mut String) {
         for skill in self.skill_registry.auto_inject_skills(None) {
@@ -1048,6 +1881,131 @@ impl EventLoop {
         }
     }
 
+    /// Prepends sections contributed by context-provider plugins.
+    fn prepend_plugin_context(&mut self, prompt: String, hat_id: &HatId) -> String {
+        if self.plugins.is_empty() {
+            return prompt;
+        }
+
+        let sections = self.plugins.context(self.state.iteration + 1, hat_id);
+        if sections.is_empty() {
+            return prompt;
+        }
+
+        let mut prefix = String::new();
+        for (name, text) in sections {
+            prefix.push_str(&format!("### PLUGIN: {name}\n\n{}\n\n", text.trim_end()));
+        }
+        prefix.push_str(&prompt);
+        prefix
+    }
+
+    /// Hands pending events for plugin-backed and native hats to their handlers.
+    ///
+    /// Events published by a handler may trigger other in-process hats, so
+    /// dispatch repeats until none has pending work (bounded to avoid cycles).
+    /// If a handler fails, its events are re-targeted to Ralph so the work is
+    /// not lost.
+    fn dispatch_in_process_hats(&mut self) {
+        const MAX_ROUNDS: usize = 16;
+
+        let plugin_hats: Vec<(HatId, String)> = if self.plugins.is_empty() {
+            Vec::new()
+        } else {
+            self.registry
+                .ids()
+                .filter_map(|id| {
+                    let name = self.registry.get_config(id)?.plugin.clone()?;
+                    self.plugins.has_hat(&name).then(|| (id.clone(), name))
+                })
+                .collect()
+        };
+        let native_hats: Vec<HatId> = self.registry.native_ids().cloned().collect();
+        if plugin_hats.is_empty() && native_hats.is_empty() {
+            return;
+        }
+
+        let mut settled = false;
+        for _ in 0..MAX_ROUNDS {
+            let mut dispatched = false;
+
+            for (hat_id, plugin_name) in &plugin_hats {
+                let pending = self.bus.take_pending(hat_id);
+                if pending.is_empty() {
+                    continue;
+                }
+                dispatched = true;
+
+                let result = self.plugins.handle(plugin_name, hat_id, &pending);
+                self.publish_handled(hat_id, pending, result.map_err(|e| e.to_string()));
+            }
+
+            for hat_id in &native_hats {
+                let pending = self.bus.take_pending(hat_id);
+                if pending.is_empty() {
+                    continue;
+                }
+                dispatched = true;
+
+                let _mutex = self.lock_mutex_group(hat_id);
+                let Some(handler) = self.registry.native_mut(hat_id) else {
+                    continue;
+                };
+                let result = handler.handle(hat_id, &pending).map(|published| {
+                    published
+                        .into_iter()
+                        .map(|event| event.with_source(hat_id.clone()))
+                        .collect()
+                });
+                self.publish_handled(hat_id, pending, result.map_err(|e| e.to_string()));
+            }
+
+            if !dispatched {
+                settled = true;
+                break;
+            }
+        }
+
+        // In-process handlers are done with everything they took
+        self.bus.finish_in_flight();
+        if !settled {
+            warn!(
+                "In-process hats still had pending events after {} rounds; leaving them for the next iteration",
+                MAX_ROUNDS
+            );
+        }
+    }
+
+    /// Publishes what an in-process hat returned, or hands its events to Ralph
+    /// if it failed.
+    fn publish_handled(
+        &mut self,
+        hat_id: &HatId,
+        pending: Vec<Event>,
+        result: Result<Vec<Event>, String>,
+    ) {
+        match result {
+            Ok(published) => {
+                debug!(
+                    hat = %hat_id,
+                    consumed = pending.len(),
+                    published = published.len(),
+                    "In-process hat handled events"
+                );
+                for event in published {
+                    self.track_phase(event.topic.as_str());
+                    self.bus.publish(event);
+                }
+            }
+            Err(e) => {
+                warn!(hat = %hat_id, error = %e, "In-process hat failed, routing events to Ralph");
+                for event in pending {
+                    self.bus.publish(event.with_target(HatId::new("ralph")));
+                }
+            }
+        }
+    }
+
     /// Prepends scratchpad content to the prompt if the file exists and is non-empty.
     ///
     /// The scratchpad is the agent's working memory for the current objective.
@@ -1070,14 +2028,17 @@ impl EventLoop {
             return prompt;
         }
 
-        let content = match std::fs::read_to_string(&resolved_path) {
-            Ok(c) => c,
+        // Reading through `Scratchpad` records this revision as a merge base,
+        // so `ralph tools scratchpad write --base` can detect later edits.
+        let snapshot = match Scratchpad::new(&resolved_path).read() {
+            Ok(snapshot) => snapshot,
             Err(e) => {
                 info!("Failed to read scratchpad for injection: {}", e);
                 return prompt;
             }
         };
 
+        let content = snapshot.content;
         if content.trim().is_empty() {
             debug!("Scratchpad is empty, skipping injection");
             return prompt;
@@ -1120,13 +2081,119 @@ impl EventLoop {
         info!("Injecting scratchpad ({} chars) into prompt", content.len());
 
         let mut final_prompt = format!(
-            "<scratchpad path=\"{}\">\n{}\n</scratchpad>\n\n",
-            self.config.core.scratchpad, content
+            "<scratchpad path=\"{}\" revision=\"{}\">\n{}\n</scratchpad>\n\n",
+            self.config.core.scratchpad, snapshot.revision, content
         );
         final_prompt.push_str(&prompt);
         final_prompt
     }
 
+    /// Prepends the current workflow phase, if `phases:` is configured.
+    fn prepend_phase(&self, prompt: String) -> String {
+        let (Some(index), Some(phase)) = (self.state.phase, self.current_phase()) else {
+            return prompt;
+        };
+        let phases = &self.config.phases;
+        let mut section = format!(
+            "## {}\n\nWorkflow phase {} of {}: **{}**.\nHats enabled in this phase: {}.\n",
+            self.ralph.prompt_pack().header("PHASE"),
+            index + 1,
+            phases.len(),
+            phase.name,
+            phase.hats.join(", ")
+        );
+        if !phase.complete_on.is_empty() {
+            section.push_str(&format!(
+                "The phase is complete when one of these is published: {}.\n",
+                phase.complete_on.join(", ")
+            ));
+        }
+        match phases.get(index + 1) {
+            Some(next) => section.push_str(&format!("Next phase: {}.\n", next.name)),
+            None => section.push_str("This is the last phase; finishing it completes the loop.\n"),
+        }
+        if let Some(instructions) = &phase.instructions {
+            section.push_str(&format!("\n{}\n", instructions.trim()));
+        }
+        format!("{section}\n{prompt}")
+    }
+
+    /// Lists paused hats so Ralph doesn't redo or reroute their work.
+    fn prepend_blockers(&self, prompt: String) -> String {
+        if self.state.blockers.is_empty() {
+            return prompt;
+        }
+        let mut section = format!(
+            "## {}\n\nThese hats are paused until they're unblocked. Events for them wait in \
+             their queues; don't redo their work or route it to another hat.\n\n",
+            self.ralph.prompt_pack().header("BLOCKED")
+        );
+        for blocker in &self.state.blockers {
+            let waiting = self.bus.pending_count(&HatId::new(&blocker.hat));
+            section.push_str(&format!(
+                "- **{}** published `{}` in iteration {} ({} event(s) waiting): {}\n  \
+                 Resumes on `{UNBLOCK_TOPIC}` or when `{}` is published.\n",
+                blocker.hat,
+                blocker.topic,
+                blocker.iteration,
+                waiting,
+                truncate_with_ellipsis(blocker.reason.trim(), 300),
+                blocked::unblocked_topic(&blocker.topic),
+            ));
+        }
+        format!("{section}\n{prompt}")
+    }
+
+    /// Prepends the previous iteration's carry-over summary, if one was kept.
+    ///
+    /// See [`crate::carryover`] for how the summary is extracted.
+    fn prepend_previous_iteration(&self, prompt: String) -> String {
+        let Some(summary) = &self.state.previous_iteration else {
+            return prompt;
+        };
+        let header = self.ralph.prompt_pack().header("PREVIOUS ITERATION");
+        format!("## {header}\n\n{summary}\n\n{prompt}")
+    }
+
+    /// Prepends the previous session's context the first time it's called
+    /// after [`set_bootstrap`](Self::set_bootstrap).
+    fn prepend_bootstrap(&mut self, prompt: String) -> String {
+        let Some(bootstrap) = self.bootstrap.take() else {
+            return prompt;
+        };
+        let header = self.ralph.prompt_pack().header("PREVIOUS SESSION");
+        format!("## {header}\n\n{}\n{prompt}", bootstrap.render())
+    }
+
+    /// Prepends the instructions added with `ralph amend` during the run.
+    ///
+    /// The file is read on every prompt so amendments apply from the next
+    /// iteration on. See [`crate::amendments`].
+    fn prepend_amendments(&self, prompt: String) -> String {
+        let amendments = match crate::amendments::read(&self.amendments_path()) {
+            Ok(amendments) if !amendments.is_empty() => amendments,
+            Ok(_) => return prompt,
+            Err(e) => {
+                warn!("Failed to read amendments: {}", e);
+                return prompt;
+            }
+        };
+        let mut section = format!(
+            "## {}\n\nThe human amended the objective while the loop was running. These \
+             instructions add to the objective and take precedence where they conflict \
+             with it:\n\n",
+            self.ralph.prompt_pack().header("AMENDMENTS")
+        );
+        for (index, amendment) in amendments.iter().enumerate() {
+            section.push_str(&format!(
+                "{}. {}\n",
+                index + 1,
+                amendment.text.replace('\n', "\n   ")
+            ));
+        }
+        format!("{section}\n{prompt}")
+    }
+
     /// Prepends ready tasks to the prompt if tasks are enabled and any exist.
     ///
     /// Loads the task store and formats ready (unblocked, open) tasks into
@@ -1228,14 +2295,46 @@ impl EventLoop {
 
     /// Determines which hats should be active based on pending events.
     /// Returns list of Hat references that are triggered by any pending event.
+    #[cfg(test)]
     fn determine_active_hats(&self, events: &[Event]) -> Vec<&Hat> {
-        let mut active_hats = Vec::new();
-        for id in self.determine_active_hat_ids(events) {
-            if let Some(hat) = self.registry.get(&id) {
-                active_hats.push(hat);
-            }
+        self.hats_for_ids(&self.determine_active_hat_ids(events))
+    }
+
+    /// Returns the hats whose pending queues are deeper than
+    /// `event_loop.backpressure_threshold`.
+    fn queue_pressure(&self, hat_ids: &[HatId]) -> Vec<QueuePressure> {
+        let threshold = self.config.event_loop.backpressure_threshold;
+        if threshold == 0 {
+            return Vec::new();
         }
-        active_hats
+
+        hat_ids
+            .iter()
+            .filter(|id| id.as_str() != "ralph")
+            .filter(|id| self.bus.pending_count(id) > threshold)
+            .filter_map(|id| {
+                let pending = self.bus.peek_pending(id)?;
+                let mut topics: Vec<String> = Vec::new();
+                for event in pending {
+                    if !topics.iter().any(|t| t == event.topic.as_str()) {
+                        topics.push(event.topic.as_str().to_string());
+                    }
+                }
+                let hat = self
+                    .registry
+                    .get(id)
+                    .map_or_else(|| id.as_str().to_string(), |h| h.name.clone());
+                Some(QueuePressure {
+                    hat,
+                    topics,
+                    depth: pending.len(),
+                })
+            })
+            .collect()
+    }
+
+    fn hats_for_ids(&self, ids: &[HatId]) -> Vec<&Hat> {
+        ids.iter().filter_map(|id| self.registry.get(id)).collect()
     }
 
     fn determine_active_hat_ids(&self, events: &[Event]) -> Vec<HatId> {
@@ -1243,7 +2342,11 @@ impl EventLoop {
         for event in events {
             if let Some(hat) = self.registry.get_for_topic(event.topic.as_str()) {
                 // Avoid duplicates
-                if !active_hat_ids.iter().any(|id| id == &hat.id) {
+                if !active_hat_ids.iter().any(|id| id == &hat.id)
+                    && !self.bus.is_paused(&hat.id)
+                    && self.phase_allows(&hat.id)
+                    && self.hat_predicate_allows(&hat.id, event)
+                {
                     active_hat_ids.push(hat.id.clone());
                 }
             }
@@ -1251,13 +2354,62 @@ impl EventLoop {
         active_hat_ids
     }
 
+    /// Checks a hat's `when:` predicate against the current loop state and `event`.
+    fn hat_predicate_allows(&self, hat_id: &HatId, event: &Event) -> bool {
+        let Some(predicate) = self.registry.predicate(hat_id) else {
+            return true;
+        };
+        let ctx = PredicateContext {
+            // process_output increments the counter after the iteration runs.
+            iteration: self.state.iteration + 1,
+            activations: *self.state.hat_activation_counts.get(hat_id).unwrap_or(&0),
+            cost: self.state.cumulative_cost,
+            elapsed_secs: self.state.elapsed().as_secs(),
+            topic: event.topic.as_str(),
+            payload: &event.payload,
+        };
+        let allowed = predicate.evaluate(&ctx);
+        if !allowed {
+            debug!(
+                hat = %hat_id.as_str(),
+                topic = %event.topic,
+                when = %predicate,
+                "Hat not activated: 'when' predicate is false"
+            );
+        }
+        allowed
+    }
+
     /// Formats an event for prompt context.
     ///
     /// For top-level prompts (task.start, task.resume), wraps the payload in
     /// `<top-level-prompt>` XML tags to clearly delineate the user's original request.
-    fn format_event(event: &Event) -> String {
+    /// Formats pending events for the prompt, passing untrusted payloads
+    /// through `prompt_guard`.
+    fn format_events(&self, events: &[Event]) -> String {
+        let workspace = self
+            .loop_context
+            .as_ref()
+            .map_or_else(|| PathBuf::from("."), |ctx| ctx.workspace().to_path_buf());
+        let guard = PromptGuard::new(self.config.prompt_guard.clone(), workspace);
+
+        let mut fenced_any = false;
+        let mut lines: Vec<String> = events
+            .iter()
+            .map(|event| {
+                let (payload, fenced) = guard.guard(event);
+                fenced_any |= fenced;
+                Self::format_event(event, &payload)
+            })
+            .collect();
+        if fenced_any {
+            lines.insert(0, prompt_guard::FENCE_NOTICE.to_string());
+        }
+        lines.join("\n")
+    }
+
+    fn format_event(event: &Event, payload: &str) -> String {
         let topic = &event.topic;
-        let payload = &event.payload;
 
         if topic.as_str() == "task.start" || topic.as_str() == "task.resume" {
             format!(
@@ -1273,14 +2425,37 @@ impl EventLoop {
         let Some(config) = self.registry.get_config(hat_id) else {
             return (false, None);
         };
-        let Some(max) = config.max_activations else {
-            return (false, None);
-        };
 
         let count = *self.state.hat_activation_counts.get(hat_id).unwrap_or(&0);
-        if count < max {
+        let runtime = self
+            .state
+            .hat_runtime
+            .get(hat_id)
+            .copied()
+            .unwrap_or_default();
+        let limit = if let Some(max) = config.max_activations
+            && count >= max
+        {
+            format!("- max_activations: {max}\n- activations: {count}")
+        } else if let Some(max) = config.max_runtime_seconds
+            && runtime.as_secs() >= max
+        {
+            format!(
+                "- max_runtime_seconds: {max}\n- runtime_seconds: {}",
+                runtime.as_secs()
+            )
+        } else if let Some(share) = config.max_cost_share
+            && let Some(max_cost) = self.config.event_loop.max_cost_usd
+            && self.state.cost_ledger.hat(hat_id.as_str()).cost_usd >= share * max_cost
+        {
+            let spent = self.state.cost_ledger.hat(hat_id.as_str()).cost_usd;
+            format!(
+                "- max_cost_share: {share}\n- budget_usd: {budget:.4}\n- spent_usd: {spent:.4}",
+                budget = share * max_cost
+            )
+        } else {
             return (false, None);
-        }
+        };
 
         // Emit only once per hat per run (avoid flooding).
         let should_emit = self.state.exhausted_hats.insert(hat_id.clone());
@@ -1294,18 +2469,15 @@ impl EventLoop {
         dropped_topics.sort();
 
         let payload = format!(
-            "Hat '{hat}' exhausted.\n- max_activations: {max}\n- activations: {count}\n- dropped_topics:\n  - {topics}",
+            "Hat '{hat}' exhausted.\n{limit}\n- dropped_topics:\n  - {topics}",
             hat = hat_id.as_str(),
-            max = max,
-            count = count,
             topics = dropped_topics.join("\n  - ")
         );
 
         warn!(
             hat = %hat_id.as_str(),
-            max_activations = max,
-            activations = count,
-            "Hat exhausted (max_activations reached)"
+            limit = %limit.replace('\n', ", "),
+            "Hat exhausted"
         );
 
         (
@@ -1317,6 +2489,116 @@ impl EventLoop {
         )
     }
 
+    /// Returns the workflow phase the loop is in, if `phases:` is configured.
+    pub fn current_phase(&self) -> Option<&PhaseConfig> {
+        self.config.phases.get(self.state.phase?)
+    }
+
+    /// Returns false for a hat that some phase enables, but not the current one.
+    fn phase_allows(&self, hat_id: &HatId) -> bool {
+        let Some(phase) = self.current_phase() else {
+            return true;
+        };
+        let hat = hat_id.as_str();
+        phase.hats.iter().any(|h| h == hat)
+            || !self
+                .config
+                .phases
+                .iter()
+                .any(|p| p.hats.iter().any(|h| h == hat))
+    }
+
+    /// Enters the first phase, or the next one once the current phase has
+    /// used its `max_iterations`. Runs before each prompt is built.
+    fn update_phase(&mut self) {
+        let Some(index) = self.state.phase else {
+            if !self.config.phases.is_empty() {
+                self.enter_phase(0);
+            }
+            return;
+        };
+        let used = self.state.iteration - self.state.phase_started_iteration;
+        let exhausted = self.config.phases[index]
+            .max_iterations
+            .is_some_and(|max| used >= max);
+        if exhausted && index + 1 < self.config.phases.len() {
+            info!(
+                phase = %self.config.phases[index].name,
+                iterations = used,
+                "Phase used its max_iterations"
+            );
+            self.enter_phase(index + 1);
+        }
+    }
+
+    /// Finishes the current phase if `topic` is one of its completion topics.
+    ///
+    /// Finishing the last phase requests loop completion.
+    fn track_phase(&mut self, topic: &str) {
+        let Some(index) = self.state.phase else {
+            return;
+        };
+        if !self.config.phases[index].completed_by(topic) {
+            return;
+        }
+        info!(phase = %self.config.phases[index].name, topic = %topic, "Phase complete");
+        if index + 1 < self.config.phases.len() {
+            self.enter_phase(index + 1);
+        } else {
+            self.state.completion_requested = true;
+        }
+    }
+
+    fn enter_phase(&mut self, index: usize) {
+        self.state.phase = Some(index);
+        self.state.phase_started_iteration = self.state.iteration;
+        let payload = lifecycle::PhaseStarted {
+            phase: self.config.phases[index].name.clone(),
+            index: index + 1,
+            total: self.config.phases.len(),
+            iteration: self.state.iteration,
+        };
+        info!(phase = %payload.phase, index = payload.index, "Entering phase");
+        self.publish_lifecycle(lifecycle::PHASE_STARTED_TOPIC, &payload);
+    }
+
+    /// Returns `<hat_id>.unavailable` if the hat is outside its `windows`.
+    fn check_hat_window(&self, hat_id: &HatId, dropped: &[Event]) -> Option<Event> {
+        let config = self.registry.get_config(hat_id)?;
+        let elapsed = self.state.elapsed().as_secs();
+        if config.in_window(elapsed) {
+            return None;
+        }
+
+        let windows = config
+            .windows
+            .iter()
+            .map(|w| match w.until_seconds {
+                Some(end) => format!("{}s-{end}s", w.from_seconds),
+                None => format!("{}s-", w.from_seconds),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut dropped_topics: Vec<String> = dropped.iter().map(|e| e.topic.to_string()).collect();
+        dropped_topics.sort();
+
+        info!(
+            hat = %hat_id.as_str(),
+            elapsed_secs = elapsed,
+            windows = %windows,
+            "Hat outside its scheduling windows, dropping its events"
+        );
+
+        Some(Event::new(
+            format!("{}.unavailable", hat_id.as_str()),
+            format!(
+                "Hat '{hat}' is outside its scheduling windows.\n- elapsed_seconds: {elapsed}\n- windows: {windows}\n- dropped_topics:\n  - {topics}",
+                hat = hat_id.as_str(),
+                topics = dropped_topics.join("\n  - ")
+            ),
+        ))
+    }
+
     fn record_hat_activations(&mut self, active_hat_ids: &[HatId]) {
         for hat_id in active_hat_ids {
             *self
@@ -1362,10 +2644,102 @@ impl EventLoop {
                 "No events written by hat, injecting default_publishes event"
             );
 
+            self.track_phase(default_event.topic.as_str());
             self.bus.publish(default_event);
         }
     }
 
+    /// Captures the event bus's hats, queue depths, and in-flight events.
+    pub fn bus_snapshot(&self) -> BusSnapshot {
+        self.bus.snapshot()
+    }
+
+    /// Writes [`bus_snapshot`](Self::bus_snapshot) to the loop's `bus.json`
+    /// for `ralph bus dump`. Does nothing without a loop context.
+    pub fn write_bus_snapshot(&self) -> std::io::Result<()> {
+        let Some(ctx) = &self.loop_context else {
+            return Ok(());
+        };
+        let path = ctx.bus_snapshot_path();
+        let json = serde_json::to_string_pretty(&self.bus.snapshot())?;
+        // Readers may poll the file mid-run; replace it in one step
+        let tmp = path.with_extension("json.tmp");
+        std::fs::create_dir_all(ctx.ralph_dir())?;
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(tmp, path)
+    }
+
+    /// Builds the public read model of the loop's state.
+    pub fn state_view(&self) -> LoopStateView {
+        let bus = self.bus.snapshot();
+        let hats = bus
+            .hats
+            .iter()
+            .map(|hat| HatView {
+                id: hat.id.to_string(),
+                name: hat.name.clone(),
+                activations: self
+                    .state
+                    .hat_activation_counts
+                    .get(&hat.id)
+                    .copied()
+                    .unwrap_or_default(),
+                runtime_seconds: self
+                    .state
+                    .hat_runtime
+                    .get(&hat.id)
+                    .map_or(0, Duration::as_secs),
+                cost: self.state.cost_ledger.hat(hat.id.as_str()).into(),
+                pending: self
+                    .bus
+                    .peek_pending(&hat.id)
+                    .map(|events| events.iter().map(|e| e.topic.to_string()).collect())
+                    .unwrap_or_default(),
+                paused: hat.paused,
+                exhausted: self.state.exhausted_hats.contains(&hat.id),
+            })
+            .collect();
+
+        LoopStateView {
+            schema_version: STATE_SCHEMA_VERSION,
+            updated_at: chrono::Utc::now(),
+            loop_id: self
+                .loop_context
+                .as_ref()
+                .and_then(|ctx| ctx.loop_id())
+                .map(str::to_string),
+            iteration: self.state.iteration,
+            max_iterations: self.max_iterations(),
+            elapsed_seconds: self.state.elapsed().as_secs(),
+            consecutive_failures: self.state.consecutive_failures,
+            last_hat: self.state.last_hat.as_ref().map(ToString::to_string),
+            phase: self.current_phase().map(|phase| phase.name.clone()),
+            status: self.state.status,
+            cost: self.state.cost_ledger.total.into(),
+            hats,
+            human_pending: bus.human_pending,
+            drift: self.state.drift.entries().to_vec(),
+            termination: self
+                .state
+                .termination
+                .as_ref()
+                .map(|reason| TerminationView {
+                    reason: reason.as_str().to_string(),
+                    exit_code: reason.exit_code(),
+                    detail: reason.detail(),
+                }),
+        }
+    }
+
+    /// Writes [`state_view`](Self::state_view) to the loop's `state.json`.
+    /// Does nothing without a loop context.
+    pub fn write_state_file(&self) -> std::io::Result<()> {
+        let Some(ctx) = &self.loop_context else {
+            return Ok(());
+        };
+        self.state_view().write(&ctx.state_path())
+    }
+
     /// Returns a mutable reference to the event bus for direct event publishing.
     ///
     /// This is primarily used for planning sessions to inject user responses
@@ -1376,8 +2750,11 @@ impl EventLoop {
 
     /// Processes output from a hat execution.
     ///
+    /// Robot check-ins run on the blocking thread pool, so a slow network
+    /// call doesn't stall other tasks (TUI, hooks) on the runtime.
+    ///
     /// Returns the termination reason if the loop should stop.
-    pub fn process_output(
+    pub async fn process_output(
         &mut self,
         hat_id: &HatId,
         output: &str,
@@ -1385,10 +2762,19 @@ impl EventLoop {
     ) -> Option<TerminationReason> {
         self.state.iteration += 1;
         self.state.last_hat = Some(hat_id.clone());
+        self.bus.finish_in_flight();
+
+        // Charge the iteration's wall-clock time to the hats that ran it
+        if let Some(started) = self.state.iteration_started_at.take() {
+            let elapsed = started.elapsed();
+            for active in &self.state.last_active_hat_ids {
+                *self.state.hat_runtime.entry(active.clone()).or_default() += elapsed;
+            }
+        }
 
         // Periodic robot check-in
         if let Some(interval_secs) = self.config.robot.checkin_interval_seconds
-            && let Some(ref robot_service) = self.robot_service
+            && self.robot_service.is_some()
         {
             let elapsed = self.state.elapsed();
             let interval = std::time::Duration::from_secs(interval_secs);
@@ -1398,9 +2784,18 @@ impl EventLoop {
                 .map(|t| t.elapsed())
                 .unwrap_or(elapsed);
 
-            if last >= interval {
+            if last >= interval
+                && let Some(service) = self.robot_service.clone()
+            {
                 let context = self.build_checkin_context(hat_id);
-                match robot_service.send_checkin(self.state.iteration, elapsed, Some(&context)) {
+                let iteration = self.state.iteration;
+                // The blocking pool gets its own handle, so the loop keeps the
+                // service even if this future is dropped mid-call
+                let result = crate::utils::spawn_blocking(move || {
+                    service.send_checkin(iteration, elapsed, Some(&context))
+                })
+                .await;
+                match result {
                     Ok(_) => {
                         self.state.last_checkin_at = Some(std::time::Instant::now());
                         debug!(iteration = self.state.iteration, "Sent robot check-in");
@@ -1432,12 +2827,24 @@ impl EventLoop {
         // Track failures
         if success {
             self.state.consecutive_failures = 0;
+            self.state.last_error = None;
         } else {
             self.state.consecutive_failures += 1;
+            self.state.last_error = error_excerpt(output);
+        }
+
+        // Remember what this iteration concluded for the next prompt
+        if self.config.carryover.enabled {
+            self.state.previous_iteration =
+                crate::carryover::summarize(output, &self.config.carryover).map(|summary| {
+                    let outcome = if success { "" } else { ", failed" };
+                    format!(
+                        "Iteration {} ({hat_id}{outcome}):\n{summary}",
+                        self.state.iteration
+                    )
+                });
         }
 
-        let _ = output;
-
         // Events are ONLY read from the JSONL file written by `ralph emit`.
         // This enforces tool use and prevents confabulation (agent claiming to emit without actually doing so).
         // See process_events_from_jsonl() for event processing.
@@ -1462,6 +2869,256 @@ impl EventLoop {
         self.state.cumulative_cost += cost;
     }
 
+    /// Records backend spend for the iteration that just ran.
+    ///
+    /// The spend is attributed to the primary active hat (or `hat_id` when no
+    /// custom hat was active) and to the topic that triggered the iteration.
+    /// Returns the entry so callers can journal it.
+    pub fn record_usage(&mut self, hat_id: &HatId, usage: Usage) -> CostEntry {
+        let hat = self
+            .state
+            .last_active_hat_ids
+            .first()
+            .unwrap_or(hat_id)
+            .clone();
+        self.record_hat_usage(&hat, usage)
+    }
+
+    /// Records backend spend for one hat of the iteration that just ran.
+    ///
+    /// Unlike [`record_usage`](Self::record_usage), the spend is attributed to
+    /// `hat` itself; iterations that run a batch of hats in parallel record
+    /// each hat's spend separately.
+    pub fn record_hat_usage(&mut self, hat: &HatId, usage: Usage) -> CostEntry {
+        let entry = CostEntry {
+            iteration: self.state.iteration,
+            hat: hat.to_string(),
+            topic: self
+                .state
+                .last_trigger
+                .as_ref()
+                .map(|e| e.topic.to_string()),
+            usage,
+        };
+        self.add_cost(usage.cost_usd);
+        self.state.cost_ledger.record(&entry);
+        debug!(
+            hat = %entry.hat,
+            topic = ?entry.topic,
+            cost_usd = usage.cost_usd,
+            tokens = usage.total_tokens(),
+            "Recorded iteration cost"
+        );
+        entry
+    }
+
+    /// Runs the child loops requested by `ralph.spawn_loop` events.
+    ///
+    /// Each child runs to completion with `ralph_bin` (see
+    /// [`child_loop`](crate::child_loop)); its result, or the reason it
+    /// couldn't start, is published as a `ralph.child_loop.*` event. Returns
+    /// whether any request was handled.
+    pub async fn run_child_loops(&mut self, ralph_bin: &Path) -> bool {
+        let requests = std::mem::take(&mut self.state.pending_child_loops);
+        let max_per_iteration = self.config.child_loops.max_per_iteration;
+        for (index, payload) in requests.iter().enumerate() {
+            // Children branch off this loop's own checkout, which is a worktree
+            // for parallel loops
+            let workspace = self.workspace();
+            let result = if index >= max_per_iteration as usize {
+                Err(ChildLoopError::TooMany(max_per_iteration))
+            } else {
+                match SpawnRequest::parse(payload) {
+                    Ok(request) => {
+                        run_child_loop(&request, &self.config, &workspace, ralph_bin).await
+                    }
+                    Err(e) => Err(e),
+                }
+            };
+            let event = match result {
+                Ok(result) => {
+                    info!(
+                        name = %result.name,
+                        success = result.success,
+                        "Child loop finished"
+                    );
+                    Event::new(result.topic(), result.payload())
+                }
+                Err(e) => {
+                    warn!(error = %e, "Child loop could not run");
+                    Event::new(
+                        child_loop::FAILED_TOPIC,
+                        format!("Child loop could not run: {e}\n\nRequest:\n{payload}"),
+                    )
+                }
+            };
+            self.bus.publish(event);
+        }
+        !requests.is_empty()
+    }
+
+    /// Runs `verify.command` after an iteration of `hat_id`, if one applies.
+    ///
+    /// The result is appended to the events file as `ci.passed` or
+    /// `ci.failed`, so the next [`process_events_from_jsonl_async`](Self::process_events_from_jsonl_async)
+    /// publishes it along with the agent's own events. Returns `None` when no
+    /// command applies or it couldn't be started.
+    pub async fn verify_iteration(&mut self, hat_id: &HatId) -> Option<VerificationReport> {
+        let command = self.config.verify.command_for(hat_id.as_str())?.to_string();
+        let workspace = self.workspace();
+        let timeout = Duration::from_secs(self.config.verify.timeout_seconds);
+        let parser = self.config.verify.parser();
+
+        let report = match run_verification(&command, parser, &workspace, timeout).await {
+            Ok(report) => report,
+            Err(e) => {
+                warn!(command, error = %e, "Failed to run verification command");
+                return None;
+            }
+        };
+        info!(
+            topic = report.topic(),
+            tests_passed = report.tests_passed,
+            tests_failed = report.tests_failed,
+            failures = report.failures.len(),
+            "Verification finished"
+        );
+
+        let mut events = vec![report.to_event()];
+        if self.config.verify.triage {
+            let clusters = triage(&report);
+            if !clusters.is_empty() {
+                info!(clusters = clusters.len(), "Triaged verification failures");
+            }
+            events.extend(clusters.iter().map(|c| c.to_event(&command)));
+        }
+        let lines: Vec<String> = events
+            .iter()
+            .filter_map(|event| serde_json::to_string(event).ok())
+            .collect();
+        let writer = crate::EventWriter::new(self.event_reader.path());
+        if let Err(e) = crate::utils::run_blocking(|| writer.append_lines(&lines)) {
+            warn!(error = %e, "Failed to write verification event");
+        }
+        self.state.last_verification = Some(report.clone());
+        Some(report)
+    }
+
+    /// Reverts changes the last iteration made to `protect` paths.
+    ///
+    /// Diffs the workspace against HEAD at the start of the loop and restores
+    /// every protected path that changed, including changes the agent
+    /// committed. The revert is left uncommitted. When anything was reverted,
+    /// a `protect.violation` event listing the paths is written to the events
+    /// file. Returns the reverted paths.
+    pub fn enforce_protected_paths(&mut self) -> Vec<RevertedPath> {
+        if self.config.protect.is_empty() {
+            return Vec::new();
+        }
+        let Some(base) = self.state.protect_base.clone() else {
+            return Vec::new();
+        };
+        let protected = ProtectedPaths::new(&self.config.protect);
+        let workspace = self.workspace();
+
+        let reverted = match crate::utils::run_blocking(|| protected.enforce(&workspace, &base)) {
+            Ok(reverted) => reverted,
+            Err(e) => {
+                warn!(error = %e, "Failed to enforce protected paths");
+                return Vec::new();
+            }
+        };
+        if reverted.is_empty() {
+            return reverted;
+        }
+
+        warn!(
+            paths = ?reverted.iter().map(|r| r.path.as_str()).collect::<Vec<_>>(),
+            "Reverted changes to protected paths"
+        );
+        let event = crate::event_reader::Event {
+            topic: protect::VIOLATION_TOPIC.to_string(),
+            payload: Some(protect::violation_payload(&reverted)),
+            ts: chrono::Utc::now().to_rfc3339(),
+        };
+        let writer = crate::EventWriter::new(self.event_reader.path());
+        if let Err(e) = crate::utils::run_blocking(|| writer.append(&event)) {
+            warn!(error = %e, "Failed to write protect violation event");
+        }
+        reverted
+    }
+
+    /// Feeds the last iteration's progress to the adaptive budget.
+    ///
+    /// Progress is the number of tasks closed during the iteration and the
+    /// lines changed since HEAD at its start. Returns the change made to the
+    /// iteration limit, if any. Does nothing unless `adaptive_budget` is enabled.
+    pub fn adjust_iteration_budget(&mut self) -> Option<BudgetChange> {
+        self.budget.as_ref()?;
+        let closed_before = self.state.closed_tasks_at_start.take()?;
+        let tasks_closed = self.closed_task_count().saturating_sub(closed_before);
+        let lines_changed = self
+            .state
+            .iteration_base
+            .clone()
+            .and_then(|base| {
+                let workspace = self.workspace();
+                crate::utils::run_blocking(|| {
+                    crate::git_ops::get_changed_line_count(&workspace, &base)
+                })
+                .ok()
+            })
+            .unwrap_or(0);
+
+        let iteration = self.state.iteration;
+        let sample = ProgressSample {
+            tasks_closed: u32::try_from(tasks_closed).unwrap_or(u32::MAX),
+            lines_changed,
+        };
+        let change = self.budget.as_mut()?.record(iteration, sample)?;
+        match change {
+            BudgetChange::Extended { from, to } => {
+                info!(
+                    from,
+                    to, "Progress is steady; extended the iteration budget"
+                );
+            }
+            BudgetChange::Shrunk { from, to } => {
+                info!(
+                    from,
+                    to, "No progress this window; shrank the iteration budget"
+                );
+            }
+        }
+        Some(change)
+    }
+
+    /// Returns the iteration limit: `event_loop.max_iterations`, or the
+    /// adaptive budget's current limit when enabled.
+    pub fn max_iterations(&self) -> u32 {
+        self.budget
+            .as_ref()
+            .map_or(self.config.event_loop.max_iterations, AdaptiveBudget::limit)
+    }
+
+    fn closed_task_count(&self) -> usize {
+        crate::task_store::TaskStore::load(&self.tasks_path()).map_or(0, |store| {
+            store
+                .all()
+                .iter()
+                .filter(|task| task.status == crate::task::TaskStatus::Closed)
+                .count()
+        })
+    }
+
+    /// Returns the directory the agent works in.
+    fn workspace(&self) -> PathBuf {
+        self.loop_context.as_ref().map_or_else(
+            || self.config.core.workspace_root.clone(),
+            |context| context.workspace().to_path_buf(),
+        )
+    }
+
     /// Verifies all tasks in scratchpad are complete or cancelled.
     ///
     /// Returns:
@@ -1632,6 +3289,74 @@ impl EventLoop {
     /// Returns true if Ralph should be invoked to handle orphaned events.
     pub fn process_events_from_jsonl(&mut self) -> std::io::Result<bool> {
         let result = self.event_reader.read_new_events()?;
+        Ok(self.apply_jsonl_events(result))
+    }
+
+    /// Async variant of [`process_events_from_jsonl`](Self::process_events_from_jsonl).
+    ///
+    /// Reads the events file on tokio's blocking pool; use this from async
+    /// contexts so disk IO never runs on a runtime worker.
+    pub async fn process_events_from_jsonl_async(&mut self) -> std::io::Result<bool> {
+        let result = self.event_reader.read_new_events_async().await?;
+        Ok(self.apply_jsonl_events(result))
+    }
+
+    /// Filters, validates, and publishes events read from JSONL.
+    fn apply_jsonl_events(&mut self, mut result: crate::event_reader::ParseResult) -> bool {
+        // Old topic names are resolved before anything keys off the topic
+        for event in &mut result.events {
+            if let Some(topic) = self.bus.renamed_topic(&event.topic) {
+                event.topic = topic.to_string();
+            }
+        }
+
+        // Lifecycle events were published when they happened; their lines are
+        // the record, and an agent must not be able to forge them.
+        result
+            .events
+            .retain(|event| !lifecycle::is_lifecycle_topic(&event.topic));
+
+        self.check_topology_drift(&result.events);
+
+        if !self.plugins.is_empty() {
+            let plugins = &mut self.plugins;
+            result.events.retain(|event| {
+                plugins.filter(&PluginEvent {
+                    topic: event.topic.clone(),
+                    payload: event.payload.clone().unwrap_or_default(),
+                })
+            });
+        }
+
+        if !self.scripts.is_empty() {
+            let state = self.script_state();
+            let scripts = &self.scripts;
+            result.events = std::mem::take(&mut result.events)
+                .into_iter()
+                .filter_map(|mut event| {
+                    let script_event = ScriptEvent {
+                        topic: event.topic.clone(),
+                        payload: event.payload.clone().unwrap_or_default(),
+                    };
+                    let transformed = scripts.transform(script_event, &state)?;
+                    // A rewritten topic gets the same checks as one read from the file
+                    let topic = match Topic::parse(&transformed.topic) {
+                        Ok(topic) => topic,
+                        Err(e) => {
+                            warn!(error = %e, "Dropping event: transform script returned an invalid topic");
+                            return None;
+                        }
+                    };
+                    if lifecycle::is_lifecycle_topic(topic.as_str()) {
+                        warn!(topic = %topic, "Dropping event: transform script returned a lifecycle topic");
+                        return None;
+                    }
+                    event.topic = topic.as_str().to_string();
+                    event.payload = Some(transformed.payload);
+                    Some(event)
+                })
+                .collect();
+        }
 
         // Handle malformed lines with backpressure
         for malformed in &result.malformed {
@@ -1655,7 +3380,7 @@ impl EventLoop {
         }
 
         if result.events.is_empty() && result.malformed.is_empty() {
-            return Ok(false);
+            return false;
         }
 
         let mut has_orphans = false;
@@ -1692,6 +3417,30 @@ impl EventLoop {
                 continue;
             }
 
+            if event.topic == SPAWN_TOPIC {
+                // Child loops are started by the runner via `run_child_loops`.
+                info!("Child loop requested via {}", SPAWN_TOPIC);
+                self.state.pending_child_loops.push(payload);
+                has_orphans = true;
+                continue;
+            }
+
+            if let Some(meta) = self.config.events.get(event.topic.as_str()) {
+                let missing = contract::missing_fields(&payload, &meta.fields);
+                if !missing.is_empty() {
+                    warn!(
+                        topic = %event.topic,
+                        missing = ?missing,
+                        "Event rejected: payload breaks its handoff contract"
+                    );
+                    validated_events.push(Event::new(
+                        contract::VIOLATION_TOPIC,
+                        Contracts::violation_payload(&event.topic, &missing, &payload),
+                    ));
+                    continue;
+                }
+            }
+
             if event.topic == "build.done" {
                 // Validate build.done events have backpressure evidence
                 if let Some(evidence) = EventParser::parse_backpressure_evidence(&payload) {
@@ -1956,7 +3705,9 @@ impl EventLoop {
                 );
 
                 // Send the question (includes retry with exponential backoff)
-                let send_ok = match robot_service.send_question(&payload) {
+                let send_ok = match crate::utils::run_blocking(|| {
+                    robot_service.send_question(&payload)
+                }) {
                     Ok(_message_id) => true,
                     Err(e) => {
                         warn!(
@@ -2002,14 +3753,19 @@ impl EventLoop {
                                 .unwrap_or_else(|| PathBuf::from(".ralph/events.jsonl"))
                         });
 
-                    match robot_service.wait_for_response(&events_path) {
+                    match crate::utils::run_blocking(|| {
+                        robot_service.wait_for_response(&events_path)
+                    }) {
                         Ok(Some(response)) => {
                             info!(
                                 response = %response,
                                 "Received human.response — continuing loop"
                             );
                             // Create a human.response event to inject into the bus
-                            response_event = Some(Event::new("human.response", &response));
+                            response_event = Some(
+                                Event::new("human.response", &response)
+                                    .with_source(prompt_guard::USER_SOURCE),
+                            );
                         }
                         Ok(None) => {
                             warn!(
@@ -2032,10 +3788,42 @@ impl EventLoop {
             }
         }
 
+        // A human.question pauses the loop once it's published (see below),
+        // unless the same batch already carries the answer
+        let question = if self.config.questions.enabled
+            && !validated_events
+                .iter()
+                .any(|e| e.topic.as_str() == ANSWER_TOPIC)
+        {
+            validated_events
+                .iter()
+                .find(|e| e.topic.as_str() == QUESTION_TOPIC)
+                .map(|e| e.payload.clone())
+        } else {
+            None
+        };
+
+        // Blocked escalation is applied once the batch is published (see below)
+        let escalations: Vec<(String, String)> = if self.config.blocked.enabled {
+            validated_events
+                .iter()
+                .filter(|e| {
+                    let topic = e.topic.as_str();
+                    blocked::is_blocked_topic(topic)
+                        || topic == UNBLOCK_TOPIC
+                        || topic.ends_with(blocked::UNBLOCKED_SUFFIX)
+                })
+                .map(|e| (e.topic.to_string(), e.payload.clone()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         // Publish validated events to the bus.
         // Ralph is always registered with subscribe("*"), so every event has at least
         // one subscriber. Events without a specific hat subscriber are "orphaned" —
         // Ralph handles them as the universal fallback.
+        let mut delegated = Vec::new();
         for event in validated_events {
             self.diagnostics.log_orchestration(
                 self.state.iteration,
@@ -2045,14 +3833,18 @@ impl EventLoop {
                 },
             );
 
-            if !self.registry.has_subscriber(event.topic.as_str()) {
+            let event = self.apply_script_route(event);
+            if event.target.is_none() && !self.registry.has_subscriber(event.topic.as_str()) {
                 has_orphans = true;
+            } else {
+                delegated.push((event.topic.to_string(), event.payload.clone()));
             }
 
             debug!(
                 topic = %event.topic,
                 "Publishing event from JSONL"
             );
+            self.track_phase(event.topic.as_str());
             self.bus.publish(event);
         }
 
@@ -2065,14 +3857,321 @@ impl EventLoop {
             self.bus.publish(response);
         }
 
-        Ok(has_orphans)
+        // A coordination turn is one where Ralph wore no hat
+        let coordination_turn = !delegated.is_empty()
+            && !self.registry.is_empty()
+            && self.state.last_active_hat_ids.is_empty();
+        if coordination_turn {
+            self.track_delegation(delegated);
+        }
+
+        for (topic, payload) in escalations {
+            if blocked::is_blocked_topic(&topic) {
+                self.block_hat(&topic, payload);
+            } else {
+                self.resume_blocked(&topic, &payload);
+            }
+        }
+
+        if let Some(question) = question {
+            has_orphans |= self.await_human_answer(&question);
+        }
+
+        self.dispatch_in_process_hats();
+
+        has_orphans
+    }
+
+    /// Publishes `topology.drift` when the active hats wrote topics none of
+    /// them declares, and adds those topics to the drift report.
+    ///
+    /// Ralph's coordination turns are exempt; Ralph has no declared topics.
+    fn check_topology_drift(&mut self, events: &[crate::event_reader::Event]) {
+        let hats: Vec<&Hat> = self
+            .state
+            .last_active_hat_ids
+            .iter()
+            .filter(|id| id.as_str() != "ralph")
+            .filter_map(|id| self.registry.get(id))
+            .collect();
+        if hats.is_empty() {
+            return;
+        }
+
+        let completion_promise = self.config.event_loop.completion_promise.as_str();
+        let mut topics: Vec<String> = Vec::new();
+        for event in events {
+            let topic = event.topic.as_str();
+            let declared = hats
+                .iter()
+                .any(|hat| hat.publishes.iter().any(|p| p.matches_str(topic)));
+            if !declared
+                && !drift::is_always_allowed(topic, completion_promise)
+                && !topics.iter().any(|t| t == topic)
+            {
+                topics.push(topic.to_string());
+            }
+        }
+        if topics.is_empty() {
+            return;
+        }
+
+        let hat = hats
+            .iter()
+            .map(|hat| hat.id.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let declared = hats
+            .iter()
+            .flat_map(|hat| hat.publishes.iter().map(ToString::to_string))
+            .collect();
+        warn!(
+            hat = %hat,
+            topics = ?topics,
+            "Hat published topics missing from its publishes list"
+        );
+        for topic in &topics {
+            self.state.drift.record(&hat, topic, self.state.iteration);
+        }
+        let payload = lifecycle::TopologyDrift {
+            iteration: self.state.iteration,
+            hat,
+            topics,
+            declared,
+        };
+        self.publish_lifecycle(lifecycle::TOPOLOGY_DRIFT_TOPIC, &payload);
+    }
+
+    /// Pauses until a human answers `question` with a `human.answer` event.
+    ///
+    /// The question has already been published, so the TUI shows it. Anything
+    /// written to the events file while waiting, the answer included, is
+    /// applied like any other JSONL output so nothing is published twice. If
+    /// nobody answers in time, a `human.answer` saying so is published instead.
+    ///
+    /// Returns true if the events applied while waiting include orphans.
+    fn await_human_answer(&mut self, question: &str) -> bool {
+        let timeout = Duration::from_secs(self.config.questions.timeout_seconds);
+        let events_path = self.event_reader.path().to_path_buf();
+
+        if let Some(url) = self.config.questions.webhook.as_deref() {
+            let notice = QuestionNotice {
+                question: question.to_string(),
+                iteration: self.state.iteration,
+                hat: self.state.last_hat.as_ref().map(ToString::to_string),
+                events_file: events_path.display().to_string(),
+            };
+            human_question::notify_webhook(url, &notice);
+        }
+
+        info!(
+            timeout_secs = timeout.as_secs(),
+            events_path = %events_path.display(),
+            "human.question asked — waiting for human.answer"
+        );
+
+        let reader = &mut self.event_reader;
+        let mut received = crate::event_reader::ParseResult::default();
+        let answered = crate::utils::run_blocking(|| {
+            let deadline = std::time::Instant::now() + timeout;
+            loop {
+                match reader.read_new_events() {
+                    Ok(result) => {
+                        let found = result.events.iter().any(|e| e.topic == ANSWER_TOPIC);
+                        received.events.extend(result.events);
+                        received.malformed.extend(result.malformed);
+                        if found {
+                            return true;
+                        }
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Failed to read events while waiting for human.answer")
+                    }
+                }
+                if std::time::Instant::now() >= deadline {
+                    return false;
+                }
+                std::thread::sleep(human_question::POLL_INTERVAL);
+            }
+        });
+
+        if !answered {
+            warn!(
+                timeout_secs = timeout.as_secs(),
+                "No human.answer before timeout — continuing without one"
+            );
+            self.bus.publish(Event::new(
+                ANSWER_TOPIC,
+                human_question::timeout_answer(timeout),
+            ));
+        }
+
+        if received.events.is_empty() && received.malformed.is_empty() {
+            return false;
+        }
+        self.apply_jsonl_events(received)
+    }
+
+    /// Pauses the hat that published `topic` and notifies the webhook.
+    ///
+    /// The hat is the active hat that declares the topic, or the only active
+    /// hat. Ralph is never paused; without a hat, nothing is.
+    fn block_hat(&mut self, topic: &str, reason: String) {
+        let active = &self.state.last_active_hat_ids;
+        let Some(hat_id) = active
+            .iter()
+            .find(|id| {
+                self.registry
+                    .get(id)
+                    .is_some_and(|hat| hat.publishes.iter().any(|p| p.matches_str(topic)))
+            })
+            .or(match active.as_slice() {
+                [only] => Some(only),
+                _ => None,
+            })
+            .filter(|id| id.as_str() != "ralph")
+            .cloned()
+        else {
+            debug!(topic = %topic, "Blocked event without an active hat — nothing to pause");
+            return;
+        };
+
+        let context = self
+            .state
+            .hat_triggers
+            .get(&hat_id)
+            .into_iter()
+            .flatten()
+            .map(|event| BlockedEvent {
+                topic: event.topic.to_string(),
+                payload: event.payload.clone(),
+            })
+            .collect();
+        let blocker = Blocker {
+            hat: hat_id.to_string(),
+            topic: topic.to_string(),
+            reason,
+            iteration: self.state.iteration,
+            context,
+        };
+        self.bus.pause(&hat_id);
+        warn!(
+            hat = %hat_id,
+            topic = %topic,
+            "Hat blocked — paused until {UNBLOCK_TOPIC} or {}",
+            blocked::unblocked_topic(topic)
+        );
+
+        if let Some(url) = self.config.blocked.webhook.as_deref() {
+            let notice = BlockedNotice {
+                blocker: &blocker,
+                events_file: self.event_reader.path().display().to_string(),
+                unblock: blocker.unblock_command(),
+            };
+            human_question::notify_webhook(url, &notice);
+        }
+
+        self.state.blockers.retain(|b| b.hat != blocker.hat);
+        self.state.blockers.push(blocker);
+    }
+
+    /// Resumes the blockers that `topic` and `payload` release and queues the
+    /// work they were doing again.
+    fn resume_blocked(&mut self, topic: &str, payload: &str) {
+        let all = std::mem::take(&mut self.state.blockers);
+        let (resumed, kept): (Vec<_>, Vec<_>) = all
+            .iter()
+            .cloned()
+            .partition(|b| b.resumed_by(topic, payload, &all));
+        self.state.blockers = kept;
+
+        for blocker in resumed {
+            let hat_id = HatId::new(&blocker.hat);
+            self.bus.resume(&hat_id);
+            info!(hat = %hat_id, topic = %topic, "Blocked hat resumed");
+            for event in blocker.context {
+                self.bus
+                    .publish(Event::new(event.topic, event.payload).with_target(hat_id.clone()));
+            }
+        }
+    }
+
+    /// Hats paused by `*.blocked` events, oldest first.
+    pub fn blockers(&self) -> &[Blocker] {
+        &self.state.blockers
+    }
+
+    /// Remembers a coordination turn's delegation and flags repeats.
+    fn track_delegation(&mut self, mut delegated: Vec<(String, String)>) {
+        delegated.sort();
+        let signature = delegated
+            .iter()
+            .map(|(topic, payload)| {
+                let payload = payload.split_whitespace().collect::<Vec<_>>().join(" ");
+                format!("{topic}\n{payload}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let mut topics: Vec<String> = delegated.into_iter().map(|(topic, _)| topic).collect();
+        topics.dedup();
+
+        if self.state.record_delegation(signature, topics) {
+            warn!(
+                topics = ?self.state.repeated_delegation_topics,
+                repeats = self.state.repeated_delegations,
+                "Ralph repeated a recent delegation"
+            );
+        }
+    }
+
+    /// Prepends an escalating warning when Ralph keeps repeating a delegation.
+    fn prepend_delegation_warning(&self, prompt: String) -> String {
+        let repeats = self.state.repeated_delegations;
+        if repeats == 0 {
+            return prompt;
+        }
+
+        let topics = self
+            .state
+            .repeated_delegation_topics
+            .iter()
+            .map(|t| format!("`{t}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let limit = self.config.event_loop.max_repeated_delegations;
+        let mut section = format!(
+            "## DELEGATION REPEATED\n\n\
+             You already delegated {topics} with the same payload on a recent turn \
+             ({repeats} repeat(s) so far). Publishing it again will not change the outcome.\n\n\
+             Change strategy: read what the hat reported back, narrow or rewrite the task, \
+             delegate to a different hat, or ask for human guidance.\n"
+        );
+        if limit > 0 && repeats + 1 >= limit {
+            section.push_str(
+                "\n**Final warning:** repeating this delegation again will stop the loop.\n",
+            );
+        }
+        section.push('\n');
+        section.push_str(&prompt);
+        section
+    }
+
+    /// Returns a parser for events written in agent output.
+    ///
+    /// Recognizes the configured `event_syntax` (the one prompts instruct)
+    /// plus any extra `event_formats`.
+    pub fn output_event_parser(&self) -> EventParser {
+        EventParser::new()
+            .with_syntax(self.config.event_loop.event_syntax)
+            .with_formats(&self.config.event_loop.event_formats)
     }
 
     /// Checks if output contains a completion event from Ralph.
     ///
-    /// Completion must be emitted as an `<event>` tag, not plain text.
+    /// Completion must be emitted as an event (a tag, or another configured
+    /// event format), not plain text.
     pub fn check_ralph_completion(&self, output: &str) -> bool {
-        let events = EventParser::new().parse(output);
+        let events = self.output_event_parser().parse(output);
         events
             .iter()
             .any(|event| event.topic.as_str() == self.config.event_loop.completion_promise)
@@ -2091,20 +4190,34 @@ impl EventLoop {
         let elapsed = self.state.elapsed();
         let duration_str = format_duration(elapsed);
 
-        let payload = format!(
-            "## Reason\n{}\n\n## Status\n{}\n\n## Summary\n- Iterations: {}\n- Duration: {}\n- Exit code: {}",
+        let mut payload = format!(
+            "## Reason\n{}\n\n## Status\n{}",
             reason.as_str(),
             termination_status_text(reason),
+        );
+        if let Some(detail) = reason.detail() {
+            payload.push_str(&format!("\n\n## Details\n{detail}"));
+        }
+        if let Some(error) = reason.last_error() {
+            payload.push_str(&format!("\n\n## Last Error\n```\n{error}\n```"));
+        }
+        payload.push_str(&format!(
+            "\n\n## Summary\n- Iterations: {}\n- Duration: {}\n- Exit code: {}",
             self.state.iteration,
             duration_str,
             reason.exit_code()
-        );
+        ));
 
         let event = Event::new("loop.terminate", &payload);
 
         // Publish to bus for observers (but no hat can trigger on this)
         self.bus.publish(event.clone());
 
+        self.state.termination = Some(reason.clone());
+        if let Err(e) = self.write_state_file() {
+            warn!("Failed to write state file: {}", e);
+        }
+
         info!(
             reason = %reason.as_str(),
             iterations = self.state.iteration,
@@ -2118,6 +4231,55 @@ impl EventLoop {
         event
     }
 
+    /// Publishes `ralph.iteration_started` for the iteration about to run.
+    ///
+    /// Returns the event for logging purposes.
+    pub fn publish_iteration_started(&mut self, hat_id: &HatId) -> Event {
+        // process_output increments the counter after the iteration runs.
+        let payload = lifecycle::IterationStarted {
+            iteration: self.state.iteration + 1,
+            hat: hat_id.to_string(),
+        };
+        self.publish_lifecycle(lifecycle::ITERATION_STARTED_TOPIC, &payload)
+    }
+
+    /// Publishes `ralph.hat_completed` for the iteration that just ran.
+    ///
+    /// Call before `process_output`, so subscribers see the completion ahead
+    /// of anything the iteration triggers. Returns the event for logging.
+    pub fn publish_hat_completed(
+        &mut self,
+        hat_id: &HatId,
+        success: bool,
+        duration: Duration,
+    ) -> Event {
+        let payload = lifecycle::HatCompleted {
+            iteration: self.state.iteration + 1,
+            hat: hat_id.to_string(),
+            success,
+            duration_secs: duration.as_secs_f64(),
+        };
+        self.publish_lifecycle(lifecycle::HAT_COMPLETED_TOPIC, &payload)
+    }
+
+    /// Publishes `ralph.checkpoint_created` for a commit made while landing.
+    ///
+    /// Returns the event for logging purposes.
+    pub fn publish_checkpoint_created(&mut self, commit: &str) -> Event {
+        let payload = lifecycle::CheckpointCreated {
+            iteration: self.state.iteration,
+            commit: commit.to_string(),
+        };
+        self.publish_lifecycle(lifecycle::CHECKPOINT_CREATED_TOPIC, &payload)
+    }
+
+    fn publish_lifecycle(&mut self, topic: &str, payload: &impl Serialize) -> Event {
+        let payload = serde_json::to_string(payload).unwrap_or_default();
+        let event = Event::new(topic, payload);
+        self.bus.publish_lifecycle(event.clone());
+        event
+    }
+
     /// Returns the robot service's shutdown flag, if active.
     ///
     /// Signal handlers can set this flag to interrupt `wait_for_response()`
@@ -2207,20 +4369,46 @@ fn format_duration(d: Duration) -> String {
     }
 }
 
+/// Tail of a failed iteration's output, kept for the termination report.
+fn error_excerpt(output: &str) -> Option<String> {
+    const MAX_EXCERPT: usize = 400;
+
+    let trimmed = output.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let mut start = trimmed.len().saturating_sub(MAX_EXCERPT);
+    while !trimmed.is_char_boundary(start) {
+        start += 1;
+    }
+    let excerpt = &trimmed[start..];
+    Some(if start > 0 {
+        format!("...{excerpt}")
+    } else {
+        excerpt.to_string()
+    })
+}
+
 /// Returns a human-readable status based on termination reason.
 fn termination_status_text(reason: &TerminationReason) -> &'static str {
     match reason {
         TerminationReason::CompletionPromise => "All tasks completed successfully.",
-        TerminationReason::MaxIterations => "Stopped at iteration limit.",
-        TerminationReason::MaxRuntime => "Stopped at runtime limit.",
-        TerminationReason::MaxCost => "Stopped at cost limit.",
-        TerminationReason::ConsecutiveFailures => "Too many consecutive failures.",
-        TerminationReason::LoopThrashing => {
+        TerminationReason::MaxIterations { .. } => "Stopped at iteration limit.",
+        TerminationReason::MaxRuntime { .. } => "Stopped at runtime limit.",
+        TerminationReason::MaxCost { .. } => "Stopped at cost limit.",
+        TerminationReason::ConsecutiveFailures { .. } => "Too many consecutive failures.",
+        TerminationReason::LoopThrashing { .. } => {
             "Loop thrashing detected - same hat repeatedly blocked."
         }
-        TerminationReason::ValidationFailure => "Too many consecutive malformed JSONL events.",
+        TerminationReason::DelegationLoop { .. } => {
+            "Ralph kept repeating the same delegation without progress."
+        }
+        TerminationReason::ValidationFailure { .. } => {
+            "Too many consecutive malformed JSONL events."
+        }
         TerminationReason::Stopped => "Manually stopped.",
         TerminationReason::Interrupted => "Interrupted by signal.",
         TerminationReason::RestartRequested => "Restarting by human request.",
+        TerminationReason::IdleTimeout { .. } => "Parked with no new work until the idle timeout.",
     }
 }
@@ -5,8 +5,19 @@
 //! <event topic="impl.done">payload</event>
 //! <event topic="handoff" target="reviewer">payload</event>
 //! ```
+//!
+//! Depending on the configured [`EventSyntax`] and [`EventFormat`]s, also
+//! recognizes `@@event(topic)` macros, ```` ```event ```` fenced blocks, and
+//! `{"topic": ...}` JSON lines.
+//!
+//! [`EventParser::parse_traced`] also reports each candidate it skipped and
+//! why, for `event_loop.trace_events`.
 
-use ralph_proto::{Event, HatId};
+use crate::config::{EventFormat, EventSyntax};
+use crate::text::truncate_with_ellipsis;
+use ralph_proto::{Event, HatId, Topic};
+use serde::Serialize;
+use tracing::warn;
 
 /// Strips ANSI escape sequences from a string.
 ///
@@ -65,6 +76,274 @@ fn strip_ansi(s: &str) -> String {
     String::from_utf8_lossy(&result).into_owned()
 }
 
+/// An `<event>` element found in agent output.
+#[derive(Debug)]
+struct RawTag {
+    /// Byte offset of `<event`.
+    start: usize,
+    /// Byte offset just past the closing tag.
+    end: usize,
+    /// Attributes with quotes, escapes, and entities resolved.
+    attrs: Vec<(String, String)>,
+    /// Content between the tags, with CDATA sections unwrapped.
+    payload: String,
+}
+
+impl RawTag {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+const OPEN_TAG: &str = "<event";
+const CLOSE_TAG: &str = "</event>";
+const CDATA_OPEN: &str = "<![CDATA[";
+const CDATA_CLOSE: &str = "]]>";
+
+/// Scans `output` for well-formed `<event ...>...</event>` elements.
+///
+/// Unlike a plain substring search, this tolerates what agents actually
+/// write inside events:
+/// - attribute values in single or double quotes, containing `>`, escaped
+///   quotes (`\"`), or XML entities (`&quot;`, `&amp;`, ...)
+/// - `<![CDATA[...]]>` sections, taken verbatim
+/// - `</event>` inside code fences or inline code in the payload
+/// - nested `<event>` examples in the payload
+///
+/// Self-closing tags (`<event topic="x"/>`) have an empty payload. Tags that
+/// never close are skipped.
+fn scan_event_tags(output: &str, trace: &mut ParseTrace) -> Vec<RawTag> {
+    let mut tags = Vec::new();
+    let mut pos = 0;
+
+    while let Some(found) = output[pos..].find(OPEN_TAG) {
+        let start = pos + found;
+        let after_name = start + OPEN_TAG.len();
+        // `<events>` or `<eventually` aren't event tags; `<event>` has no topic
+        if !output[after_name..].starts_with(char::is_whitespace) {
+            if output[after_name..].starts_with(['>', '/']) {
+                trace.skip(output, start, None, "tag", "no attributes, so no topic");
+            }
+            pos = after_name;
+            continue;
+        }
+        let (attrs, open_end, self_closing) = match scan_open_tag(output, after_name) {
+            Ok(open) => open,
+            Err(reason) => {
+                trace.skip(output, start, None, "tag", reason);
+                pos = after_name;
+                continue;
+            }
+        };
+
+        if self_closing {
+            tags.push(RawTag {
+                start,
+                end: open_end,
+                attrs,
+                payload: String::new(),
+            });
+            pos = open_end;
+            continue;
+        }
+
+        let Some((content_end, end)) = find_close_tag(output, open_end) else {
+            trace.skip(output, start, None, "tag", "no closing </event>");
+            pos = open_end;
+            continue;
+        };
+        tags.push(RawTag {
+            start,
+            end,
+            attrs,
+            payload: unwrap_cdata(&output[open_end..content_end]),
+        });
+        pos = end;
+    }
+
+    tags
+}
+
+/// Parses attributes from just after `<event` up to `>` or `/>`.
+///
+/// Returns the attributes, the offset past the tag, and whether it was
+/// self-closing, or why the tag is malformed.
+fn scan_open_tag(
+    output: &str,
+    from: usize,
+) -> Result<(Vec<(String, String)>, usize, bool), &'static str> {
+    const UNTERMINATED: &str = "opening tag never ends";
+    let bytes = output.as_bytes();
+    let mut attrs = Vec::new();
+    let mut i = from;
+
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        match bytes.get(i).ok_or(UNTERMINATED)? {
+            b'>' => return Ok((attrs, i + 1, false)),
+            b'/' if bytes.get(i + 1) == Some(&b'>') => return Ok((attrs, i + 2, true)),
+            b'<' => return Err("opening tag never ends before the next '<'"),
+            _ => {}
+        }
+
+        let name_start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || b"_-:".contains(&bytes[i])) {
+            i += 1;
+        }
+        if i == name_start {
+            return Err("invalid character where an attribute name should be");
+        }
+        let name = output[name_start..i].to_string();
+
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if bytes.get(i) != Some(&b'=') {
+            // Bare attribute (no value)
+            attrs.push((name, String::new()));
+            continue;
+        }
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+
+        let quote = char::from(*bytes.get(i).ok_or(UNTERMINATED)?);
+        if quote != '"' && quote != '\'' {
+            return Err("attribute value is not quoted");
+        }
+        i += 1;
+        let mut value = String::new();
+        let mut chars = output[i..].char_indices();
+        loop {
+            let (offset, c) = chars.next().ok_or("attribute value is never closed")?;
+            if c == '\\' {
+                let (_, escaped) = chars.next().ok_or("attribute value is never closed")?;
+                if escaped != quote && escaped != '\\' {
+                    value.push('\\');
+                }
+                value.push(escaped);
+            } else if c == quote {
+                i += offset + 1;
+                break;
+            } else {
+                value.push(c);
+            }
+        }
+        attrs.push((name, decode_entities(&value)));
+    }
+}
+
+/// Finds the `</event>` closing the element whose content starts at `from`.
+///
+/// Returns the offset where content ends and the offset past the closing
+/// tag. Code fences, inline code, CDATA, and nested events are skipped over.
+/// If that structure doesn't balance, falls back to the first `</event>`.
+fn find_close_tag(output: &str, from: usize) -> Option<(usize, usize)> {
+    let mut depth = 0usize;
+    let mut i = from;
+
+    while i < output.len() {
+        let rest = &output[i..];
+        let skip_to = if rest.starts_with(CDATA_OPEN) {
+            rest.find(CDATA_CLOSE).map(|end| end + CDATA_CLOSE.len())
+        } else if let Some(fenced) = rest.strip_prefix("```") {
+            fenced.find("```").map(|end| end + 6)
+        } else if let Some(quoted) = rest.strip_prefix('`') {
+            quoted
+                .find(['`', '\n'])
+                .filter(|&end| rest[1 + end..].starts_with('`'))
+                .map(|end| end + 2)
+        } else if rest.starts_with(CLOSE_TAG) {
+            if depth == 0 {
+                return Some((i, i + CLOSE_TAG.len()));
+            }
+            depth -= 1;
+            Some(CLOSE_TAG.len())
+        } else if rest.starts_with(OPEN_TAG)
+            && rest[OPEN_TAG.len()..].starts_with(char::is_whitespace)
+        {
+            depth += 1;
+            Some(OPEN_TAG.len())
+        } else {
+            None
+        };
+
+        match skip_to {
+            Some(len) => i += len,
+            None if rest.starts_with(CDATA_OPEN) || rest.starts_with("```") => break,
+            None => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+
+    output[from..]
+        .find(CLOSE_TAG)
+        .map(|idx| (from + idx, from + idx + CLOSE_TAG.len()))
+}
+
+/// Replaces `<![CDATA[...]]>` sections with their content.
+fn unwrap_cdata(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find(CDATA_OPEN) {
+        let inner = &rest[start + CDATA_OPEN.len()..];
+        let Some(end) = inner.find(CDATA_CLOSE) else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        result.push_str(&inner[..end]);
+        rest = &inner[end + CDATA_CLOSE.len()..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Decodes the predefined XML entities and numeric character references.
+fn decode_entities(value: &str) -> String {
+    if !value.contains('&') {
+        return value.to_string();
+    }
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(amp) = rest.find('&') {
+        result.push_str(&rest[..amp]);
+        let after = &rest[amp + 1..];
+        let decoded = after.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &after[..end];
+            let c = match entity {
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            }?;
+            Some((c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
 /// Evidence of backpressure checks for build.done events.
 #[derive(Debug, Clone, PartialEq)]
 pub struct BackpressureEvidence {
@@ -216,11 +495,95 @@ impl QualityReport {
     }
 }
 
+/// A place in agent output that looked like an event, and what became of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParseCandidate {
+    /// Byte offset where the candidate starts.
+    pub start: usize,
+    /// Byte offset just past it, when its end was found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+    /// How it was written: `tag`, `macro`, `fenced`, or `json`.
+    pub format: &'static str,
+    /// Topic, when one could be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// Why it was ignored; `None` when it became an event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+    /// The candidate's first line, shortened.
+    pub excerpt: String,
+}
+
+/// Every event candidate [`EventParser::parse_traced`] saw, in output order.
+///
+/// Answers "the agent emitted an event but Ralph ignored it": each
+/// malformed tag, disabled format, or invalid topic is listed with the
+/// reason it was skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ParseTrace {
+    pub candidates: Vec<ParseCandidate>,
+}
+
+impl ParseTrace {
+    /// Longest excerpt kept per candidate, in characters.
+    const EXCERPT_CHARS: usize = 120;
+
+    /// Returns the candidates that were skipped.
+    pub fn skipped(&self) -> impl Iterator<Item = &ParseCandidate> {
+        self.candidates.iter().filter(|c| c.skipped.is_some())
+    }
+
+    fn record(
+        &mut self,
+        output: &str,
+        start: usize,
+        end: Option<usize>,
+        format: &'static str,
+        topic: Option<&str>,
+        skipped: Option<String>,
+    ) {
+        let text = &output[start..end.unwrap_or(output.len())];
+        let first_line = text.lines().next().unwrap_or_default().trim();
+        self.candidates.push(ParseCandidate {
+            start,
+            end,
+            format,
+            topic: topic.map(str::to_string),
+            skipped,
+            excerpt: truncate_with_ellipsis(first_line, Self::EXCERPT_CHARS),
+        });
+    }
+
+    fn skip(
+        &mut self,
+        output: &str,
+        start: usize,
+        end: Option<usize>,
+        format: &'static str,
+        reason: &str,
+    ) {
+        self.record(output, start, end, format, None, Some(reason.to_string()));
+    }
+}
+
+/// An event found in output, with the span and format it was written in.
+struct Found {
+    start: usize,
+    end: usize,
+    format: &'static str,
+    event: Event,
+}
+
 /// Parser for extracting events from CLI output.
 #[derive(Debug, Default)]
 pub struct EventParser {
     /// The source hat ID to attach to parsed events.
     source: Option<HatId>,
+    /// Formats recognized in addition to `<event>` tags.
+    formats: Vec<EventFormat>,
+    /// Syntax agents were told to use.
+    syntax: EventSyntax,
 }
 
 impl EventParser {
@@ -235,70 +598,285 @@ impl EventParser {
         self
     }
 
+    /// Also recognizes the given formats (`<event>` tags always are).
+    pub fn with_formats(mut self, formats: &[EventFormat]) -> Self {
+        self.formats = formats.to_vec();
+        self
+    }
+
+    /// Recognizes the syntax agents were instructed to use.
+    ///
+    /// `<event>` tags are still recognized with any syntax.
+    pub fn with_syntax(mut self, syntax: EventSyntax) -> Self {
+        self.syntax = syntax;
+        self
+    }
+
     /// Parses events from CLI output text.
     ///
-    /// Returns a list of parsed events.
+    /// Returns a list of parsed events, in the order they appear.
     pub fn parse(&self, output: &str) -> Vec<Event> {
-        let mut events = Vec::new();
-        let mut remaining = output;
-
-        while let Some(start_idx) = remaining.find("<event ") {
-            let after_start = &remaining[start_idx..];
+        self.parse_traced(output).0
+    }
 
-            // Find the end of the opening tag
-            let Some(tag_end) = after_start.find('>') else {
-                remaining = &remaining[start_idx + 7..];
-                continue;
+    /// Parses events like [`parse`](Self::parse), also returning every
+    /// candidate seen and why the ones that didn't become events were skipped.
+    pub fn parse_traced(&self, output: &str) -> (Vec<Event>, ParseTrace) {
+        let mut trace = ParseTrace::default();
+        let mut found = Self::parse_tags(output, &mut trace);
+        let tag_spans: Vec<(usize, usize)> = found.iter().map(|f| (f.start, f.end)).collect();
+        found.extend(self.parse_lines(output, &tag_spans, &mut trace));
+        found.sort_by_key(|f| f.start);
+
+        let mut events = Vec::with_capacity(found.len());
+        for found in found {
+            let topic = found.event.topic.to_string();
+            let skipped = match self.finish(found.event) {
+                Ok(event) => {
+                    events.push(event);
+                    None
+                }
+                Err(reason) => Some(reason),
             };
+            trace.record(
+                output,
+                found.start,
+                Some(found.end),
+                found.format,
+                Some(&topic),
+                skipped,
+            );
+        }
+        trace.candidates.sort_by_key(|c| c.start);
+        (events, trace)
+    }
+
+    /// Normalizes the topic and attaches the parser's source hat.
+    ///
+    /// Events with an invalid topic are dropped with a warning.
+    fn finish(&self, mut event: Event) -> Result<Event, String> {
+        match Topic::parse(event.topic.as_str()) {
+            Ok(topic) => event.topic = topic,
+            Err(e) => {
+                warn!(error = %e, "Ignoring event with invalid topic");
+                return Err(format!("invalid topic: {e}"));
+            }
+        }
+        Ok(match &self.source {
+            Some(source) => event.with_source(source.clone()),
+            None => event,
+        })
+    }
 
-            let opening_tag = &after_start[..tag_end + 1];
+    /// Finds `<event>` tags, with the byte span each occupies.
+    fn parse_tags(output: &str, trace: &mut ParseTrace) -> Vec<Found> {
+        scan_event_tags(output, trace)
+            .into_iter()
+            .filter_map(|tag| {
+                let topic = match tag.attr("topic").map(str::trim) {
+                    Some(topic) if !topic.is_empty() => topic,
+                    topic => {
+                        let reason = if topic.is_some() {
+                            "empty topic attribute"
+                        } else {
+                            "no topic attribute"
+                        };
+                        trace.skip(output, tag.start, Some(tag.end), "tag", reason);
+                        return None;
+                    }
+                };
+                let mut event = Event::new(topic, tag.payload.trim());
+                if let Some(target) = tag.attr("target") {
+                    event = event.with_target(target);
+                }
+                Some(Found {
+                    start: tag.start,
+                    end: tag.end,
+                    format: "tag",
+                    event,
+                })
+            })
+            .collect()
+    }
 
-            // Parse attributes from opening tag
-            let topic = Self::extract_attr(opening_tag, "topic");
-            let target = Self::extract_attr(opening_tag, "target");
+    /// Finds line-based events (macros, fenced blocks, JSON lines) outside
+    /// the given tag spans.
+    ///
+    /// Macros and JSON lines inside other code fences are examples, not
+    /// events, and are skipped. Lines written in a format that isn't enabled
+    /// are only traced.
+    fn parse_lines(
+        &self,
+        output: &str,
+        tag_spans: &[(usize, usize)],
+        trace: &mut ParseTrace,
+    ) -> Vec<Found> {
+        let fenced = self.formats.contains(&EventFormat::Fenced);
+        let json = self.formats.contains(&EventFormat::Json) || self.syntax == EventSyntax::Json;
+        let macros = self.syntax == EventSyntax::Macro;
 
-            let Some(topic) = topic else {
-                remaining = &remaining[start_idx + tag_end + 1..];
+        let mut events = Vec::new();
+        let mut found = |start, end, format, event| {
+            events.push(Found {
+                start,
+                end,
+                format,
+                event,
+            });
+        };
+        // Open fence: (start offset, is an event fence, body)
+        let mut fence: Option<(usize, bool, String)> = None;
+        let mut offset = 0;
+
+        for line in output.split_inclusive('\n') {
+            let start = offset;
+            offset += line.len();
+            if tag_spans.iter().any(|(s, e)| start >= *s && start < *e) {
                 continue;
-            };
+            }
+            let trimmed = line.trim();
 
-            // Find the closing tag
-            let content_start = &after_start[tag_end + 1..];
-            let Some(close_idx) = content_start.find("</event>") else {
-                remaining = &remaining[start_idx + tag_end + 1..];
+            if let Some((fence_start, is_event, body)) = &mut fence {
+                if trimmed == "```" {
+                    if *is_event {
+                        match Self::parse_fenced_body(body) {
+                            Some(event) => found(*fence_start, offset, "fenced", event),
+                            None => trace.skip(
+                                output,
+                                *fence_start,
+                                Some(offset),
+                                "fenced",
+                                "no topic on the block's first line",
+                            ),
+                        }
+                    }
+                    fence = None;
+                } else {
+                    if !*is_event && let Some(format) = Self::line_format(trimmed) {
+                        trace.skip(
+                            output,
+                            start,
+                            Some(offset),
+                            format,
+                            "inside a code fence, so read as an example",
+                        );
+                    }
+                    body.push_str(line);
+                }
                 continue;
-            };
+            }
 
-            let payload = content_start[..close_idx].trim().to_string();
+            if let Some(info) = trimmed.strip_prefix("```") {
+                let is_event = info.trim() == "event";
+                if is_event && !fenced {
+                    trace.skip(
+                        output,
+                        start,
+                        None,
+                        "fenced",
+                        "fenced events not enabled (event_loop.event_formats)",
+                    );
+                }
+                fence = Some((start, fenced && is_event, String::new()));
+            } else if json && let Some(event) = Self::parse_json_event(trimmed) {
+                found(start, offset, "json", event);
+            } else if macros && let Some(event) = Self::parse_macro(trimmed) {
+                found(start, offset, "macro", event);
+            } else if let Some(format) = Self::line_format(trimmed) {
+                let reason = match (format, json, macros) {
+                    ("json", true, _) => "not a JSON object with a string topic",
+                    ("json", false, _) => "JSON events not enabled (event_loop.event_formats)",
+                    (_, _, true) => "malformed macro; expected @@event(topic) payload",
+                    _ => "macro syntax not enabled (event_loop.event_syntax)",
+                };
+                trace.skip(output, start, Some(offset), format, reason);
+            }
+        }
 
-            let mut event = Event::new(topic, payload);
+        if let Some((fence_start, true, _)) = fence {
+            trace.skip(
+                output,
+                fence_start,
+                None,
+                "fenced",
+                "fenced block never closed",
+            );
+        }
 
-            if let Some(source) = &self.source {
-                event = event.with_source(source.clone());
-            }
+        events
+    }
 
-            if let Some(target) = target {
-                event = event.with_target(target);
-            }
+    /// Returns the format a line not parsed as an event seems written in.
+    fn line_format(line: &str) -> Option<&'static str> {
+        if line.starts_with("@@event(") {
+            Some("macro")
+        } else if line.starts_with('{') && line.contains("\"topic\"") {
+            Some("json")
+        } else {
+            None
+        }
+    }
 
-            events.push(event);
+    /// Parses `@@event(topic) payload` or `@@event(topic, target) payload`.
+    fn parse_macro(line: &str) -> Option<Event> {
+        let rest = line.strip_prefix("@@event(")?;
+        let (args, payload) = rest.split_once(')')?;
+        let (topic, target) = match args.split_once(',') {
+            Some((topic, target)) => (topic.trim(), Some(target.trim())),
+            None => (args.trim(), None),
+        };
+        if topic.is_empty() || topic.contains(char::is_whitespace) {
+            return None;
+        }
 
-            // Move past this event
-            let total_consumed = start_idx + tag_end + 1 + close_idx + 8; // 8 = "</event>".len()
-            remaining = &remaining[total_consumed..];
+        let mut event = Event::new(topic, payload.trim());
+        if let Some(target) = target.filter(|t| !t.is_empty()) {
+            event = event.with_target(target);
         }
+        Some(event)
+    }
 
-        events
+    /// Parses the body of an ```` ```event ```` block.
+    ///
+    /// The body is either a JSON object or a topic on the first line with the
+    /// payload on the lines below.
+    fn parse_fenced_body(body: &str) -> Option<Event> {
+        let body = body.trim();
+        if let Some(event) = Self::parse_json_event(body) {
+            return Some(event);
+        }
+
+        let (topic, payload) = body.split_once('\n').unwrap_or((body, ""));
+        let topic = topic.trim();
+        if topic.is_empty() || topic.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(Event::new(topic, payload.trim()))
     }
 
-    /// Extracts an attribute value from an XML-like tag.
-    fn extract_attr(tag: &str, attr: &str) -> Option<String> {
-        let pattern = format!("{attr}=\"");
-        let start = tag.find(&pattern)?;
-        let value_start = start + pattern.len();
-        let rest = &tag[value_start..];
-        let end = rest.find('"')?;
-        Some(rest[..end].to_string())
+    /// Parses a `{"topic": ..., "payload": ..., "target": ...}` object.
+    ///
+    /// A non-string payload is kept as its JSON text.
+    fn parse_json_event(text: &str) -> Option<Event> {
+        if !text.starts_with('{') || !text.ends_with('}') {
+            return None;
+        }
+        let value: serde_json::Value = serde_json::from_str(text).ok()?;
+        let topic = value.get("topic")?.as_str()?.trim();
+        if topic.is_empty() || topic.contains(char::is_whitespace) {
+            return None;
+        }
+
+        let payload = match value.get("payload") {
+            None | Some(serde_json::Value::Null) => String::new(),
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        };
+        let mut event = Event::new(topic, payload);
+        if let Some(target) = value.get("target").and_then(serde_json::Value::as_str) {
+            event = event.with_target(target);
+        }
+        Some(event)
     }
 
     /// Parses backpressure evidence from build.done event payload.
@@ -634,35 +1212,9 @@ impl EventParser {
 
     /// Checks if the promise appears inside any event tag payload.
     pub fn promise_in_event_tags(output: &str, promise: &str) -> bool {
-        let mut remaining = output;
-
-        while let Some(start_idx) = remaining.find("<event ") {
-            let after_start = &remaining[start_idx..];
-
-            // Find the end of the opening tag
-            let Some(tag_end) = after_start.find('>') else {
-                remaining = &remaining[start_idx + 7..];
-                continue;
-            };
-
-            // Find the closing tag
-            let content_start = &after_start[tag_end + 1..];
-            let Some(close_idx) = content_start.find("</event>") else {
-                remaining = &remaining[start_idx + tag_end + 1..];
-                continue;
-            };
-
-            let payload = &content_start[..close_idx];
-            if payload.contains(promise) {
-                return true;
-            }
-
-            // Move past this event
-            let total_consumed = start_idx + tag_end + 1 + close_idx + 8;
-            remaining = &remaining[total_consumed..];
-        }
-
-        false
+        scan_event_tags(output, &mut ParseTrace::default())
+            .iter()
+            .any(|tag| tag.payload.contains(promise))
     }
 
     /// Strips all `<event ...>...</event>` blocks from output.
@@ -671,28 +1223,12 @@ impl EventParser {
     /// the "final output" text that should be checked for promises.
     fn strip_event_tags(output: &str) -> String {
         let mut result = String::with_capacity(output.len());
-        let mut remaining = output;
-
-        while let Some(start_idx) = remaining.find("<event ") {
-            // Add everything before this event tag
-            result.push_str(&remaining[..start_idx]);
-
-            let after_start = &remaining[start_idx..];
-
-            // Find the closing tag
-            if let Some(close_idx) = after_start.find("</event>") {
-                // Skip past the entire event block
-                remaining = &after_start[close_idx + 8..]; // 8 = "</event>".len()
-            } else {
-                // Malformed: no closing tag, keep the rest and stop
-                result.push_str(after_start);
-                remaining = "";
-                break;
-            }
+        let mut last = 0;
+        for tag in scan_event_tags(output, &mut ParseTrace::default()) {
+            result.push_str(&output[last..tag.start]);
+            last = tag.end;
         }
-
-        // Add any remaining content after the last event
-        result.push_str(remaining);
+        result.push_str(&output[last..]);
         result
     }
 }
@@ -718,6 +1254,18 @@ Some trailing text.
         assert!(events[0].payload.contains("authentication module"));
     }
 
+    #[test]
+    fn test_parse_normalizes_and_validates_topics() {
+        let output = r#"
+<event topic="Impl.Done">done</event>
+<event topic="impl done">dropped</event>
+"#;
+        let events = EventParser::new().parse(output);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].topic.as_str(), "impl.done");
+    }
+
     #[test]
     fn test_parse_event_with_target() {
         let output = r#"<event topic="handoff" target="reviewer">Please review</event>"#;
@@ -761,6 +1309,161 @@ Working on implementation...
         assert!(events.is_empty());
     }
 
+    #[test]
+    fn test_fenced_and_json_events_ignored_by_default() {
+        let output = "```event\nbuild.done\ntests: pass\n```\n{\"topic\":\"review.done\"}";
+        assert!(EventParser::new().parse(output).is_empty());
+    }
+
+    #[test]
+    fn test_parse_fenced_event_blocks() {
+        let output = r#"Handing off.
+```event
+build.done
+tests: pass
+lint: pass
+```
+```event
+{"topic": "review.request", "payload": "check auth", "target": "reviewer"}
+```
+"#;
+        let parser = EventParser::new()
+            .with_formats(&[EventFormat::Fenced])
+            .with_source("builder");
+        let events = parser.parse(output);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].topic.as_str(), "build.done");
+        assert_eq!(events[0].payload, "tests: pass\nlint: pass");
+        assert_eq!(events[0].source.as_ref().unwrap().as_str(), "builder");
+        assert_eq!(events[1].topic.as_str(), "review.request");
+        assert_eq!(events[1].target.as_ref().unwrap().as_str(), "reviewer");
+    }
+
+    #[test]
+    fn test_parse_json_lines_in_order_with_tags() {
+        let output = r#"{"topic": "build.task", "payload": {"id": 3}}
+<event topic="build.done">{"topic": "nested.json"}</event>
+  {"topic": "review.done", "payload": "ok"}
+```json
+{"topic": "example.only"}
+```
+{"topic": "has space"}
+"#;
+        let parser = EventParser::new().with_formats(&[EventFormat::Json]);
+        let topics: Vec<String> = parser
+            .parse(output)
+            .iter()
+            .map(|e| e.topic.to_string())
+            .collect();
+        assert_eq!(topics, ["build.task", "build.done", "review.done"]);
+
+        let events = parser.parse(output);
+        assert_eq!(events[0].payload, r#"{"id":3}"#);
+    }
+
+    #[test]
+    fn test_parse_macro_syntax() {
+        let output = r"Done with the build.
+@@event(build.done) tests: pass, lint: pass
+```
+@@event(example.only) ignored inside code
+```
+  @@event(review.request, reviewer)   check auth
+@@event(bad topic) nope
+";
+        let parser = EventParser::new().with_syntax(EventSyntax::Macro);
+        let events = parser.parse(output);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].topic.as_str(), "build.done");
+        assert_eq!(events[0].payload, "tests: pass, lint: pass");
+        assert_eq!(events[1].topic.as_str(), "review.request");
+        assert_eq!(events[1].target.as_ref().unwrap().as_str(), "reviewer");
+        assert_eq!(events[1].payload, "check auth");
+
+        // Macros are only recognized when they're the configured syntax
+        assert!(EventParser::new().parse(output).is_empty());
+    }
+
+    #[test]
+    fn test_parse_traced_explains_skipped_candidates() {
+        let output = r#"Working on it.
+<event topic="build.done">tests: pass</event>
+<event topic=build.done>unquoted</event>
+<event target="reviewer">no topic</event>
+<event topic="bad topic">spaces</event>
+<event topic="review.request">never closed
+```event
+impl.done
+```
+@@event(build.done) macro
+{"topic": "build.done"}
+"#;
+        let (events, trace) = EventParser::new().parse_traced(output);
+        assert_eq!(events, EventParser::new().parse(output));
+        assert_eq!(events.len(), 1);
+
+        let accepted: Vec<_> = trace
+            .candidates
+            .iter()
+            .filter(|c| c.skipped.is_none())
+            .collect();
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].topic.as_deref(), Some("build.done"));
+        assert_eq!(
+            &output[accepted[0].start..accepted[0].end.unwrap()],
+            r#"<event topic="build.done">tests: pass</event>"#
+        );
+
+        let reasons: Vec<_> = trace
+            .skipped()
+            .map(|c| (c.format, c.skipped.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                ("tag", "attribute value is not quoted"),
+                ("tag", "no topic attribute"),
+                (
+                    "tag",
+                    "invalid topic: segment 'bad topic' of 'bad topic' may only contain letters, digits, '_' and '-'"
+                ),
+                ("tag", "no closing </event>"),
+                (
+                    "fenced",
+                    "fenced events not enabled (event_loop.event_formats)"
+                ),
+                (
+                    "macro",
+                    "macro syntax not enabled (event_loop.event_syntax)"
+                ),
+                ("json", "JSON events not enabled (event_loop.event_formats)"),
+            ]
+        );
+        assert!(
+            trace
+                .candidates
+                .windows(2)
+                .all(|w| w[0].start <= w[1].start)
+        );
+        assert_eq!(
+            trace.skipped().nth(1).unwrap().excerpt,
+            r#"<event target="reviewer">no topic</event>"#
+        );
+    }
+
+    #[test]
+    fn test_syntax_examples_parse_back() {
+        for syntax in [EventSyntax::Xml, EventSyntax::Macro, EventSyntax::Json] {
+            let example = syntax.example("build.done", "tests: pass");
+            let events = EventParser::new().with_syntax(syntax).parse(&example);
+            assert_eq!(events.len(), 1, "{syntax:?}: {example}");
+            assert_eq!(events[0].topic.as_str(), "build.done");
+            assert_eq!(events[0].payload, "tests: pass");
+        }
+    }
+
     #[test]
     fn test_contains_promise_requires_last_line() {
         assert!(EventParser::contains_promise(
@@ -874,6 +1577,188 @@ Still working..."#;
         assert_eq!(stripped, "just plain text");
     }
 
+    #[test]
+    fn test_parse_payload_with_close_tag_in_code() {
+        let output = r#"<event topic="review.request">
+Check the parser fix:
+```xml
+<event topic="x">a</event>
+```
+and that `</event>` inside inline code survives.
+</event>
+<event topic="build.task">next</event>"#;
+        let events = EventParser::new().parse(output);
+
+        assert_eq!(events.len(), 2);
+        assert!(
+            events[0]
+                .payload
+                .contains("```xml\n<event topic=\"x\">a</event>\n```")
+        );
+        assert!(events[0].payload.ends_with("inline code survives."));
+        assert_eq!(events[1].topic.as_str(), "build.task");
+    }
+
+    #[test]
+    fn test_parse_cdata_payload() {
+        let output = "<event topic=\"build.done\"><![CDATA[output had </event> and <b>]]></event>";
+        let events = EventParser::new().parse(output);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload, "output had </event> and <b>");
+    }
+
+    #[test]
+    fn test_parse_nested_event_example() {
+        let output =
+            r#"<event topic="docs.done">Agents emit <event topic="a">x</event> tags.</event>"#;
+        let events = EventParser::new().parse(output);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].payload,
+            r#"Agents emit <event topic="a">x</event> tags."#
+        );
+    }
+
+    #[test]
+    fn test_parse_escaped_attributes() {
+        let output = concat!(
+            r#"<event topic='review.done' target="a\"b">one</event>"#,
+            r#"<event target="x&gt;y" topic="build.done">two</event>"#,
+            r#"<event topic="plan.done"/>"#,
+        );
+        let events = EventParser::new().parse(output);
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].topic.as_str(), "review.done");
+        assert_eq!(events[0].target.as_ref().unwrap().as_str(), "a\"b");
+        assert_eq!(events[1].target.as_ref().unwrap().as_str(), "x>y");
+        assert_eq!(events[2].topic.as_str(), "plan.done");
+        assert!(events[2].payload.is_empty());
+    }
+
+    #[test]
+    fn test_unclosed_fence_falls_back_to_first_close_tag() {
+        let output = "<event topic=\"a\">```\nunclosed</event> rest <event topic=\"b\">ok</event>";
+        let events = EventParser::new().parse(output);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].payload, "```\nunclosed");
+        assert_eq!(events[1].payload, "ok");
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &amp; b &lt;c&gt; &#65;&#x42;"),
+            "a & b <c> AB"
+        );
+        assert_eq!(decode_entities("&unknown; & &"), "&unknown; & &");
+    }
+
+    /// Small deterministic PRNG so the fuzz tests need no extra dependencies.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            usize::try_from(self.0 % 1_000_003).unwrap()
+        }
+
+        fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+            items[self.next() % items.len()]
+        }
+    }
+
+    const FRAGMENTS: &[&str] = &[
+        "<event ",
+        "<event",
+        "</event>",
+        "<event topic=\"a\">",
+        "topic=",
+        "\"",
+        "'",
+        "\\",
+        ">",
+        "/>",
+        "<",
+        "```",
+        "`",
+        "<![CDATA[",
+        "]]>",
+        "&amp;",
+        "&",
+        ";",
+        "\n",
+        " ",
+        "text",
+        "LOOP_COMPLETE",
+        "é",
+        "🎉",
+        "{\"topic\":\"j\"}",
+        "```event\n",
+    ];
+
+    #[test]
+    fn test_fuzz_parser_never_panics() {
+        let mut rng = XorShift(0x5eed_1234_abcd_ef01);
+        let parser = EventParser::new().with_formats(&[EventFormat::Fenced, EventFormat::Json]);
+
+        for _ in 0..5000 {
+            let len = rng.next() % 24;
+            let output: String = (0..len).map(|_| rng.pick(FRAGMENTS)).collect();
+
+            let events = parser.parse(&output);
+            for event in &events {
+                assert!(
+                    !event.topic.as_str().is_empty(),
+                    "empty topic from {output:?}"
+                );
+            }
+            let stripped = EventParser::strip_event_tags(&output);
+            assert!(stripped.len() <= output.len());
+            let _ = EventParser::contains_promise(&output, "LOOP_COMPLETE");
+        }
+    }
+
+    #[test]
+    fn test_fuzz_round_trips_cdata_payloads() {
+        let mut rng = XorShift(0x0dd_ba11_cafe_f00d);
+        let payload_fragments: Vec<&str> = FRAGMENTS
+            .iter()
+            .copied()
+            .filter(|f| !f.contains("]]>") && !f.contains("CDATA"))
+            .collect();
+
+        for _ in 0..2000 {
+            let len = 1 + rng.next() % 16;
+            let payload: String = (0..len).map(|_| rng.pick(&payload_fragments)).collect();
+            let target = rng.pick(&["r\"q", "a&b", "x>y", "plain"]);
+            let escaped_target = target
+                .replace('&', "&amp;")
+                .replace('"', "&quot;")
+                .replace('>', "&gt;");
+            let output = format!(
+                "noise `</event>`\n<event topic=\"fuzz.topic\" target=\"{escaped_target}\"><![CDATA[{payload}]]></event>\ntrailer"
+            );
+
+            let events = EventParser::new().parse(&output);
+            let event = events
+                .iter()
+                .find(|e| e.topic.as_str() == "fuzz.topic")
+                .unwrap_or_else(|| panic!("lost event in {output:?}"));
+            assert_eq!(
+                event.payload,
+                payload.trim(),
+                "mangled payload in {output:?}"
+            );
+            assert_eq!(event.target.as_ref().unwrap().as_str(), target);
+        }
+    }
+
     #[test]
     fn test_parse_backpressure_evidence_all_pass() {
         let payload = "tests: pass\nlint: pass\ntypecheck: pass\naudit: pass\ncoverage: pass\ncomplexity: 7\nduplication: pass\nperformance: pass";
@@ -1,10 +1,14 @@
 //! Event reader for consuming events from `.ralph/events.jsonl`.
 
+use crate::event_watcher::EventWatcher;
+use crate::file_lock::FileLock;
+use ralph_proto::Topic;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
-use std::path::PathBuf;
-use tracing::warn;
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
 
 /// Result of parsing events from a JSONL file.
 ///
@@ -95,9 +99,27 @@ pub struct Event {
 }
 
 /// Reads new events from `.ralph/events.jsonl` since last read.
+///
+/// Safe to run while other processes append: a trailing line without a
+/// newline is only consumed once it parses, so an event that is still being
+/// written is picked up on the next read instead of being reported malformed.
+///
+/// If the file shrinks below the read position (truncation) or is replaced
+/// by a new file (rotation), reading restarts from the beginning.
+///
+/// With [`with_compaction`](Self::with_compaction), consumed events are moved
+/// to `<dir>/<stem>/archive-N.jsonl` (e.g. `.ralph/events/archive-1.jsonl`)
+/// once enough has been read, so the live file stays small.
+#[derive(Debug, Clone)]
 pub struct EventReader {
     path: PathBuf,
     position: u64,
+    /// Number of lines consumed so far (for 1-indexed line numbers).
+    lines_read: u64,
+    /// Identity of the file last read, to detect rotation.
+    file_id: Option<u64>,
+    /// Compact once this many bytes have been consumed.
+    compact_after: Option<u64>,
 }
 
 impl EventReader {
@@ -106,9 +128,26 @@ impl EventReader {
         Self {
             path: path.into(),
             position: 0,
+            lines_read: 0,
+            file_id: None,
+            compact_after: None,
         }
     }
 
+    /// Archives consumed events after `threshold_bytes` have been read.
+    ///
+    /// A threshold of 0 disables compaction.
+    #[must_use]
+    pub fn with_compaction(mut self, threshold_bytes: u64) -> Self {
+        self.compact_after = (threshold_bytes > 0).then_some(threshold_bytes);
+        self
+    }
+
+    /// Returns the path of the events file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     /// Reads new events since the last read.
     ///
     /// Returns a `ParseResult` containing both successfully parsed events
@@ -125,67 +164,210 @@ impl EventReader {
         }
 
         let mut file = File::open(&self.path)?;
+        let metadata = file.metadata()?;
+        let file_id = file_identity(&metadata);
+        if metadata.len() < self.position {
+            debug!(path = %self.path.display(), "Events file truncated; reading from start");
+            self.reset();
+        } else if self.file_id.is_some() && file_id != self.file_id {
+            debug!(path = %self.path.display(), "Events file replaced; reading from start");
+            self.reset();
+        }
+        self.file_id = file_id;
         file.seek(SeekFrom::Start(self.position))?;
 
-        let reader = BufReader::new(file);
+        let mut reader = BufReader::new(file);
         let mut result = ParseResult::default();
-        let mut current_pos = self.position;
-        let mut line_number = self.count_lines_before_position();
+        let mut buf = String::new();
 
-        for line in reader.lines() {
-            let line = line?;
-            let line_bytes = line.len() as u64 + 1; // +1 for newline
-            line_number += 1;
+        loop {
+            buf.clear();
+            let bytes = reader.read_line(&mut buf)? as u64;
+            if bytes == 0 {
+                break;
+            }
+            let complete = buf.ends_with('\n');
+            let line = buf.trim_end_matches(['\n', '\r']);
 
             if line.trim().is_empty() {
-                current_pos += line_bytes;
+                if !complete {
+                    break;
+                }
+                self.consume(bytes);
                 continue;
             }
 
-            match serde_json::from_str::<Event>(&line) {
-                Ok(event) => result.events.push(event),
+            match serde_json::from_str::<Event>(line) {
+                Ok(mut event) => match Topic::parse(&event.topic) {
+                    Ok(topic) => {
+                        event.topic = topic.as_str().to_string();
+                        result.events.push(event);
+                    }
+                    Err(e) => {
+                        let line_number = self.lines_read + 1;
+                        warn!(error = %e, line_number = line_number, "Invalid event topic");
+                        result
+                            .malformed
+                            .push(MalformedLine::new(line_number, line, e.to_string()));
+                    }
+                },
+                // A writer is mid-append; leave the line for the next read.
+                Err(_) if !complete => break,
                 Err(e) => {
+                    let line_number = self.lines_read + 1;
                     warn!(error = %e, line_number = line_number, "Malformed JSON line");
                     result
                         .malformed
-                        .push(MalformedLine::new(line_number, &line, e.to_string()));
+                        .push(MalformedLine::new(line_number, line, e.to_string()));
                 }
             }
 
-            current_pos += line_bytes;
+            self.consume(bytes);
+        }
+
+        if let Some(threshold) = self.compact_after
+            && self.position >= threshold
+        {
+            match self.compact() {
+                Ok(Some(archive)) => {
+                    info!(archive = %archive.display(), "Archived consumed events");
+                }
+                Ok(None) => {}
+                Err(e) => warn!(error = %e, "Failed to compact events file"),
+            }
         }
 
-        self.position = current_pos;
         Ok(result)
     }
 
-    /// Counts lines before the current position (for line numbering).
-    fn count_lines_before_position(&self) -> u64 {
+    /// Moves consumed events to the next archive file and rewrites the
+    /// events file with only the unread tail.
+    ///
+    /// Holds the writer lock throughout, so concurrent [`EventWriter`]
+    /// appends wait for it. The tail is written to a temporary file that is
+    /// then renamed over the events file, so a crash leaves either the old
+    /// file or the new one, never a half-written mix. Writers that open the
+    /// file per append (like `EventWriter` or a shell `>>`) follow the new
+    /// file; the reader's position is adjusted to point at its start.
+    ///
+    /// Returns the archive path, or `None` if there was nothing to archive or
+    /// the file was rotated by someone else.
+    ///
+    /// [`EventWriter`]: crate::EventWriter
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if locking, reading, or writing fails.
+    pub fn compact(&mut self) -> std::io::Result<Option<PathBuf>> {
         if self.position == 0 || !self.path.exists() {
-            return 0;
+            return Ok(None);
         }
-        // Read file up to position and count newlines
-        if let Ok(file) = File::open(&self.path) {
-            let reader = BufReader::new(file);
-            let mut count = 0u64;
-            let mut bytes_read = 0u64;
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    bytes_read += line.len() as u64 + 1;
-                    if bytes_read > self.position {
-                        break;
-                    }
-                    count += 1;
-                } else {
-                    break;
-                }
+
+        let lock = FileLock::new(&self.path)?;
+        let _guard = match lock.exclusive() {
+            Ok(guard) => Some(guard),
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => None,
+            Err(e) => return Err(e),
+        };
+
+        let content = std::fs::read(&self.path)?;
+        let file_id = file_identity(&std::fs::metadata(&self.path)?);
+        let Ok(position) = usize::try_from(self.position) else {
+            return Ok(None);
+        };
+        if file_id != self.file_id || content.len() < position {
+            return Ok(None);
+        }
+
+        let (consumed, unread) = content.split_at(position);
+        let archive = next_archive_path(&self.path)?;
+        let mut archived = consumed.to_vec();
+        if !archived.ends_with(b"\n") {
+            archived.push(b'\n');
+        }
+        std::fs::write(&archive, archived)?;
+
+        let mut tmp_name = self.path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".compact");
+        let tmp = self.path.with_file_name(tmp_name);
+        let mut file = File::create(&tmp)?;
+        file.write_all(unread)?;
+        file.sync_all()?;
+        let file_id = file_identity(&file.metadata()?);
+        drop(file);
+        if let Err(e) = std::fs::rename(&tmp, &self.path) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+
+        self.reset();
+        self.file_id = file_id;
+        Ok(Some(archive))
+    }
+
+    /// Reads new events on tokio's blocking pool.
+    ///
+    /// Same as [`read_new_events`](Self::read_new_events), but the file IO
+    /// never runs on an async worker thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or read, or if the
+    /// blocking task panics.
+    pub async fn read_new_events_async(&mut self) -> std::io::Result<ParseResult> {
+        let mut reader = self.clone();
+        let (reader, result) = tokio::task::spawn_blocking(move || {
+            let result = reader.read_new_events();
+            (reader, result)
+        })
+        .await
+        .map_err(std::io::Error::other)?;
+        *self = reader;
+        result
+    }
+
+    /// Starts a filesystem watcher for this reader's file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform watcher cannot be created.
+    pub fn watch(&self) -> notify::Result<EventWatcher> {
+        EventWatcher::new(&self.path)
+    }
+
+    /// Reads new events, waiting up to `timeout` for the file to change if
+    /// nothing new is available yet.
+    ///
+    /// Unlike polling with [`read_new_events`](Self::read_new_events), this
+    /// returns as soon as an event is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or read.
+    pub fn wait_for_events(
+        &mut self,
+        watcher: &EventWatcher,
+        timeout: Duration,
+    ) -> std::io::Result<ParseResult> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            let result = self.read_new_events()?;
+            if !result.events.is_empty() || !result.malformed.is_empty() {
+                return Ok(result);
+            }
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() || !watcher.wait(remaining) {
+                return Ok(result);
             }
-            count
-        } else {
-            0
         }
     }
 
+    /// Advances past one line of `bytes` bytes.
+    fn consume(&mut self, bytes: u64) {
+        self.position += bytes;
+        self.lines_read += 1;
+    }
+
     /// Returns the current file position.
     pub fn position(&self) -> u64 {
         self.position
@@ -194,9 +376,60 @@ impl EventReader {
     /// Resets the position to the start of the file.
     pub fn reset(&mut self) {
         self.position = 0;
+        self.lines_read = 0;
     }
 }
 
+/// Lists the archives of the JSONL file at `path`
+/// (`<dir>/<stem>/archive-N.jsonl`), oldest first.
+pub fn archive_paths(path: &Path) -> Vec<PathBuf> {
+    numbered_archives(path)
+        .into_iter()
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// Returns `<dir>/<stem>/archive-N.jsonl` for the next unused N, creating
+/// the directory.
+pub(crate) fn next_archive_path(path: &Path) -> std::io::Result<PathBuf> {
+    let dir = path.with_extension("");
+    std::fs::create_dir_all(&dir)?;
+    let last = numbered_archives(path).last().map_or(0, |(n, _)| *n);
+    Ok(dir.join(format!("archive-{}.jsonl", last + 1)))
+}
+
+fn numbered_archives(path: &Path) -> Vec<(u32, PathBuf)> {
+    let mut archives: Vec<(u32, PathBuf)> = std::fs::read_dir(path.with_extension(""))
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let n = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("archive-")?
+                .strip_suffix(".jsonl")?
+                .parse::<u32>()
+                .ok()?;
+            Some((n, entry.path()))
+        })
+        .collect();
+    archives.sort_unstable();
+    archives
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)]
+fn file_identity(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,6 +486,31 @@ mod tests {
         assert!(result.malformed.is_empty());
     }
 
+    #[test]
+    fn test_topics_are_normalized_or_reported_malformed() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"{{"topic":"Build.Done","ts":"2024-01-01T00:00:00Z"}}"#
+        )
+        .unwrap();
+        writeln!(
+            file,
+            r#"{{"topic":"build done","ts":"2024-01-01T00:00:01Z"}}"#
+        )
+        .unwrap();
+        file.flush().unwrap();
+
+        let mut reader = EventReader::new(file.path());
+        let result = reader.read_new_events().unwrap();
+
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].topic, "build.done");
+        assert_eq!(result.malformed.len(), 1);
+        assert_eq!(result.malformed[0].line_number, 2);
+        assert!(result.malformed[0].error.contains("may only contain"));
+    }
+
     #[test]
     fn test_captures_malformed_lines() {
         let mut file = NamedTempFile::new().unwrap();
@@ -419,4 +677,162 @@ mod tests {
         assert_eq!(result.events[0].topic, "valid1");
         assert_eq!(result.events[1].topic, "valid2");
     }
+
+    #[test]
+    fn test_partial_trailing_line_is_deferred_until_complete() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, r#"{{"topic":"first","ts":"2024-01-01T00:00:00Z"}}"#).unwrap();
+        write!(file, r#"{{"topic":"sec"#).unwrap();
+        file.flush().unwrap();
+
+        let mut reader = EventReader::new(file.path());
+        let result = reader.read_new_events().unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert!(result.malformed.is_empty());
+
+        // Writer finishes the line
+        writeln!(file, r#"ond","ts":"2024-01-01T00:00:01Z"}}"#).unwrap();
+        file.flush().unwrap();
+
+        let result = reader.read_new_events().unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].topic, "second");
+        assert!(result.malformed.is_empty());
+    }
+
+    #[test]
+    fn test_complete_trailing_line_without_newline_is_read() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, r#"{{"topic":"last","ts":"2024-01-01T00:00:00Z"}}"#).unwrap();
+        file.flush().unwrap();
+
+        let mut reader = EventReader::new(file.path());
+        let result = reader.read_new_events().unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].topic, "last");
+    }
+
+    #[tokio::test]
+    async fn test_read_new_events_async_advances_position() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, r#"{{"topic":"one","ts":"2024-01-01T00:00:00Z"}}"#).unwrap();
+        file.flush().unwrap();
+
+        let mut reader = EventReader::new(file.path());
+        let result = reader.read_new_events_async().await.unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert!(reader.position() > 0);
+
+        writeln!(file, r#"{{"topic":"two","ts":"2024-01-01T00:00:01Z"}}"#).unwrap();
+        file.flush().unwrap();
+
+        let result = reader.read_new_events_async().await.unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].topic, "two");
+    }
+
+    #[test]
+    fn test_truncated_file_is_read_from_start() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, r#"{{"topic":"old","ts":"2024-01-01T00:00:00Z"}}"#).unwrap();
+        file.flush().unwrap();
+
+        let mut reader = EventReader::new(file.path());
+        assert_eq!(reader.read_new_events().unwrap().events.len(), 1);
+
+        std::fs::write(file.path(), "{\"topic\":\"new\",\"ts\":\"t\"}\n").unwrap();
+        let result = reader.read_new_events().unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].topic, "new");
+    }
+
+    #[test]
+    fn test_wait_for_events_returns_when_written() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let mut reader = EventReader::new(&path);
+        let watcher = reader.watch().unwrap();
+
+        let writer_path = path.clone();
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            std::fs::write(writer_path, "{\"topic\":\"ping\",\"ts\":\"t\"}\n").unwrap();
+        });
+
+        let result = reader
+            .wait_for_events(&watcher, Duration::from_secs(5))
+            .unwrap();
+        writer.join().unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].topic, "ping");
+    }
+
+    #[test]
+    fn test_compaction_archives_consumed_events() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let writer = crate::EventWriter::new(&path);
+        let event = |topic: &str| serde_json::json!({"topic": topic, "ts": "t"});
+
+        writer.append(&event("one")).unwrap();
+        writer.append(&event("two")).unwrap();
+
+        let mut reader = EventReader::new(&path).with_compaction(1);
+        assert_eq!(reader.read_new_events().unwrap().events.len(), 2);
+
+        let archive = dir.path().join("events/archive-1.jsonl");
+        assert_eq!(
+            std::fs::read_to_string(&archive).unwrap().lines().count(),
+            2
+        );
+        assert!(std::fs::read_to_string(&path).unwrap().is_empty());
+        assert_eq!(reader.position(), 0);
+
+        // Appends after compaction are picked up exactly once.
+        writer.append(&event("three")).unwrap();
+        let result = reader.read_new_events().unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].topic, "three");
+        assert!(dir.path().join("events/archive-2.jsonl").exists());
+    }
+
+    #[test]
+    fn test_compaction_keeps_unread_tail() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("events.jsonl");
+        std::fs::write(&path, "{\"topic\":\"read\",\"ts\":\"t\"}\n").unwrap();
+
+        let mut reader = EventReader::new(&path);
+        reader.read_new_events().unwrap();
+        crate::EventWriter::new(&path)
+            .append(&serde_json::json!({"topic": "unread", "ts": "t"}))
+            .unwrap();
+
+        assert!(reader.compact().unwrap().is_some());
+        let result = reader.read_new_events().unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].topic, "unread");
+    }
+
+    #[test]
+    fn test_compaction_replaces_file_and_reader_follows() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("events.jsonl");
+        std::fs::write(&path, "{\"topic\":\"one\",\"ts\":\"t\"}\n").unwrap();
+
+        let mut reader = EventReader::new(&path);
+        assert_eq!(reader.read_new_events().unwrap().events.len(), 1);
+        assert!(reader.compact().unwrap().is_some());
+        assert!(!dir.path().join("events.jsonl.compact").exists());
+
+        // A `>>` redirect opens the file per command, so it finds the new one
+        let mut writer = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        writeln!(writer, "{{\"topic\":\"two\",\"ts\":\"t\"}}").unwrap();
+        let result = reader.read_new_events().unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].topic, "two");
+    }
 }
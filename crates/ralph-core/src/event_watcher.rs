@@ -0,0 +1,113 @@
+//! Filesystem notifications for `.ralph/events.jsonl`.
+//!
+//! [`EventWatcher`] wakes a reader as soon as an agent appends to the events
+//! file instead of waiting out a poll interval. It watches the parent
+//! directory, so it keeps working when the file is created late, truncated,
+//! or replaced by rotation.
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+use tracing::debug;
+
+/// Wakes up when the watched events file changes.
+pub struct EventWatcher {
+    // Dropping the watcher stops notifications; keep it alive with the receiver.
+    _watcher: RecommendedWatcher,
+    changes: Receiver<()>,
+}
+
+impl std::fmt::Debug for EventWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventWatcher").finish_non_exhaustive()
+    }
+}
+
+impl EventWatcher {
+    /// Starts watching `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the parent directory does not exist or the
+    /// platform watcher cannot be created.
+    pub fn new(path: impl AsRef<Path>) -> notify::Result<Self> {
+        let path = path.as_ref();
+        let file_name = path.file_name().map(ToOwned::to_owned);
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let (tx, changes) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                let Ok(event) = res else { return };
+                let relevant = event.need_rescan()
+                    || event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name() == file_name.as_deref());
+                if relevant {
+                    let _ = tx.send(());
+                }
+            })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        debug!(path = %path.display(), "Watching events file");
+
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+        })
+    }
+
+    /// Blocks until the file changes or `timeout` elapses.
+    ///
+    /// Returns `true` if a change was seen. Bursts of notifications are
+    /// coalesced into a single wake-up.
+    pub fn wait(&self, timeout: Duration) -> bool {
+        match self.changes.recv_timeout(timeout) {
+            Ok(()) => {
+                while self.changes.try_recv().is_ok() {}
+                true
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => {
+                // The backend died; degrade to a plain poll interval.
+                std::thread::sleep(timeout);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_wait_times_out_without_changes() {
+        let tmp = TempDir::new().unwrap();
+        let watcher = EventWatcher::new(tmp.path().join("events.jsonl")).unwrap();
+        assert!(!watcher.wait(Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn test_wait_wakes_on_append() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("events.jsonl");
+        let watcher = EventWatcher::new(&path).unwrap();
+
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(
+            file,
+            r#"{{"topic":"build.done","ts":"2024-01-01T00:00:00Z"}}"#
+        )
+        .unwrap();
+        file.flush().unwrap();
+
+        assert!(watcher.wait(Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,188 @@
+//! Event writer for appending to `.ralph/events.jsonl`.
+//!
+//! Several processes append to the same events file: the orchestrator, the
+//! agent via `ralph emit`, the TUI, and the control API. Each append holds an
+//! exclusive [`FileLock`] and stages the whole batch in memory before writing
+//! it with a single `write_all`, so concurrent writers never interleave partial
+//! lines. If a previous writer died mid-line, the dangling fragment is
+//! terminated first so it can't swallow the next event.
+
+use crate::file_lock::FileLock;
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Appends JSONL records to an events file under an advisory lock.
+#[derive(Debug, Clone)]
+pub struct EventWriter {
+    path: PathBuf,
+}
+
+impl EventWriter {
+    /// Creates a writer for the given events file.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the path to the events file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Serializes `record` as one JSON line and appends it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization, locking, or the write fails.
+    pub fn append<T: Serialize>(&self, record: &T) -> io::Result<()> {
+        let line = serde_json::to_string(record)?;
+        self.append_lines([line])
+    }
+
+    /// Appends pre-serialized JSON lines as a single atomic batch.
+    ///
+    /// Lines must not contain newlines; a trailing newline is added to each.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if a line contains a newline, or an error if
+    /// locking or the write fails.
+    pub fn append_lines<I, S>(&self, lines: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut batch = String::new();
+        for line in lines {
+            let line = line.as_ref();
+            if line.contains('\n') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "event lines must not contain newlines",
+                ));
+            }
+            batch.push_str(line);
+            batch.push('\n');
+        }
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        // Creates the parent directory as a side effect.
+        let lock = FileLock::new(&self.path)?;
+        let _guard = match lock.exclusive() {
+            Ok(guard) => Some(guard),
+            // No advisory locks on this platform: fall back to a plain O_APPEND write.
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => None,
+            Err(e) => return Err(e),
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)?;
+
+        if ends_mid_line(&mut file)? {
+            debug!(path = %self.path.display(), "Terminating partial line in events file");
+            batch.insert(0, '\n');
+        }
+
+        file.write_all(batch.as_bytes())?;
+        file.flush()
+    }
+}
+
+/// Returns true if the file is non-empty and its last byte isn't a newline.
+fn ends_mid_line(file: &mut std::fs::File) -> io::Result<bool> {
+    if file.metadata()?.len() == 0 {
+        return Ok(false);
+    }
+    file.seek(SeekFrom::End(-1))?;
+    let mut last = [0u8; 1];
+    file.read_exact(&mut last)?;
+    Ok(last[0] != b'\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_reader::EventReader;
+    use tempfile::TempDir;
+
+    fn event(topic: &str) -> serde_json::Value {
+        serde_json::json!({"topic": topic, "payload": "p", "ts": "2024-01-01T00:00:00Z"})
+    }
+
+    #[test]
+    fn test_append_creates_file_and_parent_dir() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join(".ralph/events.jsonl");
+
+        EventWriter::new(&path).append(&event("first")).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert!(content.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_append_terminates_dangling_partial_line() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("events.jsonl");
+        std::fs::write(&path, r#"{"topic":"cut"#).unwrap();
+
+        EventWriter::new(&path).append(&event("next")).unwrap();
+
+        let mut reader = EventReader::new(&path);
+        let result = reader.read_new_events().unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].topic, "next");
+        assert_eq!(result.malformed.len(), 1);
+    }
+
+    #[test]
+    fn test_append_lines_rejects_embedded_newline() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("events.jsonl");
+
+        let err = EventWriter::new(&path)
+            .append_lines(["{}\n{}"])
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_concurrent_writers_do_not_interleave() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("events.jsonl");
+        let payload = "x".repeat(8192);
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let writer = EventWriter::new(&path);
+                let payload = payload.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        let record = serde_json::json!({
+                            "topic": format!("writer.{i}"),
+                            "payload": payload,
+                            "ts": "2024-01-01T00:00:00Z",
+                        });
+                        writer.append(&record).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let result = EventReader::new(&path).read_new_events().unwrap();
+        assert_eq!(result.events.len(), 200);
+        assert!(result.malformed.is_empty());
+    }
+}
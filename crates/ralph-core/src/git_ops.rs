@@ -31,6 +31,60 @@ impl AutoCommitResult {
     }
 }
 
+/// How an auto-commit is worded, attributed, and signed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitOptions {
+    /// Prepended to the subject line.
+    pub prefix: Option<String>,
+
+    /// Name and email used as both author and committer, instead of the
+    /// repository's configured identity.
+    pub identity: Option<(String, String)>,
+
+    /// Sign the commit: `Some("")` with the default key, otherwise with the
+    /// given key id.
+    pub signing_key: Option<String>,
+
+    /// Git trailers (`Key: value` lines) ending the message.
+    pub trailers: Vec<(String, String)>,
+}
+
+impl CommitOptions {
+    fn message(&self, subject: &str) -> String {
+        let mut message = match self.prefix.as_deref().map(str::trim_end) {
+            Some(prefix) if !prefix.is_empty() => format!("{prefix} {subject}"),
+            _ => subject.to_string(),
+        };
+        if !self.trailers.is_empty() {
+            message.push('\n');
+            for (key, value) in &self.trailers {
+                message.push_str(&format!("\n{key}: {value}"));
+            }
+        }
+        message
+    }
+
+    fn args(&self, message: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some((name, email)) = &self.identity {
+            args.extend([
+                "-c".to_string(),
+                format!("user.name={name}"),
+                "-c".to_string(),
+                format!("user.email={email}"),
+            ]);
+        }
+        args.push("commit".to_string());
+        match self.signing_key.as_deref() {
+            Some("") => args.push("--gpg-sign".to_string()),
+            Some(key) => args.push(format!("--gpg-sign={key}")),
+            None => {}
+        }
+        args.extend(["-m".to_string(), message.to_string()]);
+        args
+    }
+}
+
 /// Errors that can occur during git operations.
 #[derive(Debug, thiserror::Error)]
 pub enum GitOpsError {
@@ -47,6 +101,43 @@ pub enum GitOpsError {
     ConfigMissing(String),
 }
 
+/// A `git` command that never runs the repository's hooks or fsmonitor.
+///
+/// Agents can write to the workspace, so git run by the loop on the host
+/// must not execute programs the repository names.
+pub fn git_command() -> Command {
+    let mut command = Command::new("git");
+    command.args([
+        "-c",
+        "core.hooksPath=/dev/null",
+        "-c",
+        "core.fsmonitor=false",
+    ]);
+    command
+}
+
+/// Run `git` with `args` in `path` and return its stdout.
+///
+/// A non-zero exit becomes [`GitOpsError::Git`] carrying git's stderr.
+pub fn run_git(path: impl AsRef<Path>, args: &[&str]) -> Result<String, GitOpsError> {
+    run_git_bytes(path, args).map(|stdout| String::from_utf8_lossy(&stdout).into_owned())
+}
+
+/// Like [`run_git`], but returns stdout as raw bytes (e.g. for binary diffs).
+pub fn run_git_bytes(path: impl AsRef<Path>, args: &[&str]) -> Result<Vec<u8>, GitOpsError> {
+    let output = git_command()
+        .args(args)
+        .current_dir(path.as_ref())
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitOpsError::Git(stderr.trim().to_string()));
+    }
+
+    Ok(output.stdout)
+}
+
 /// Check if the working directory has uncommitted changes.
 ///
 /// Returns true if there are:
@@ -60,7 +151,7 @@ pub enum GitOpsError {
 pub fn has_uncommitted_changes(path: impl AsRef<Path>) -> Result<bool, GitOpsError> {
     let path = path.as_ref();
 
-    let output = Command::new("git")
+    let output = git_command()
         .args(["status", "--porcelain"])
         .current_dir(path)
         .output()?;
@@ -96,6 +187,16 @@ pub fn has_uncommitted_changes(path: impl AsRef<Path>) -> Result<bool, GitOpsErr
 pub fn auto_commit_changes(
     path: impl AsRef<Path>,
     loop_id: &str,
+) -> Result<AutoCommitResult, GitOpsError> {
+    auto_commit_changes_with(path, loop_id, &CommitOptions::default())
+}
+
+/// Auto-commit like [`auto_commit_changes`], with the message prefix,
+/// identity, signing, and trailers in `options`.
+pub fn auto_commit_changes_with(
+    path: impl AsRef<Path>,
+    loop_id: &str,
+    options: &CommitOptions,
 ) -> Result<AutoCommitResult, GitOpsError> {
     let path = path.as_ref();
 
@@ -105,7 +206,7 @@ pub fn auto_commit_changes(
     }
 
     // Stage all changes (including untracked files)
-    let output = Command::new("git")
+    let output = git_command()
         .args(["add", "-A"])
         .current_dir(path)
         .output()?;
@@ -127,10 +228,13 @@ pub fn auto_commit_changes(
     }
 
     // Create the commit
-    let commit_message = format!("chore: auto-commit before merge (loop {})", loop_id);
+    let commit_message = options.message(&format!(
+        "chore: auto-commit before merge (loop {})",
+        loop_id
+    ));
 
-    let output = Command::new("git")
-        .args(["commit", "-m", &commit_message])
+    let output = git_command()
+        .args(options.args(&commit_message))
         .current_dir(path)
         .output()?;
 
@@ -159,34 +263,13 @@ pub fn auto_commit_changes(
 
 /// Count the number of files staged for commit.
 fn count_staged_files(path: &Path) -> Result<usize, GitOpsError> {
-    let output = Command::new("git")
-        .args(["diff", "--cached", "--name-only"])
-        .current_dir(path)
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(GitOpsError::Git(stderr.to_string()));
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = run_git(path, &["diff", "--cached", "--name-only"])?;
     Ok(stdout.lines().filter(|line| !line.is_empty()).count())
 }
 
 /// Get the HEAD commit SHA.
 pub fn get_head_sha(path: impl AsRef<Path>) -> Result<String, GitOpsError> {
-    let path = path.as_ref();
-    let output = Command::new("git")
-        .args(["rev-parse", "HEAD"])
-        .current_dir(path)
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(GitOpsError::Git(stderr.to_string()));
-    }
-
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    Ok(run_git(path, &["rev-parse", "HEAD"])?.trim().to_string())
 }
 
 /// Get the current branch name.
@@ -198,18 +281,9 @@ pub fn get_head_sha(path: impl AsRef<Path>) -> Result<String, GitOpsError> {
 ///
 /// * `path` - Path to the git repository (or worktree)
 pub fn get_current_branch(path: impl AsRef<Path>) -> Result<String, GitOpsError> {
-    let path = path.as_ref();
-    let output = Command::new("git")
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-        .current_dir(path)
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(GitOpsError::Git(stderr.to_string()));
-    }
-
-    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let branch = run_git(path, &["rev-parse", "--abbrev-ref", "HEAD"])?
+        .trim()
+        .to_string();
 
     // "HEAD" indicates detached HEAD state
     if branch == "HEAD" {
@@ -235,7 +309,7 @@ pub fn clean_stashes(path: impl AsRef<Path>) -> Result<usize, GitOpsError> {
     let path = path.as_ref();
 
     // First, count existing stashes
-    let output = Command::new("git")
+    let output = git_command()
         .args(["stash", "list"])
         .current_dir(path)
         .output()?;
@@ -255,7 +329,7 @@ pub fn clean_stashes(path: impl AsRef<Path>) -> Result<usize, GitOpsError> {
     }
 
     // Clear all stashes
-    let output = Command::new("git")
+    let output = git_command()
         .args(["stash", "clear"])
         .current_dir(path)
         .output()?;
@@ -283,10 +357,7 @@ pub fn prune_remote_refs(path: impl AsRef<Path>) -> Result<(), GitOpsError> {
     let path = path.as_ref();
 
     // Check if 'origin' remote exists before pruning
-    let output = Command::new("git")
-        .args(["remote"])
-        .current_dir(path)
-        .output()?;
+    let output = git_command().args(["remote"]).current_dir(path).output()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -299,7 +370,7 @@ pub fn prune_remote_refs(path: impl AsRef<Path>) -> Result<(), GitOpsError> {
         return Ok(());
     }
 
-    let output = Command::new("git")
+    let output = git_command()
         .args(["remote", "prune", "origin"])
         .current_dir(path)
         .output()?;
@@ -338,18 +409,48 @@ pub fn is_working_tree_clean(path: impl AsRef<Path>) -> Result<bool, GitOpsError
 ///
 /// * `path` - Path to the git repository (or worktree)
 pub fn get_commit_summary(path: impl AsRef<Path>) -> Result<String, GitOpsError> {
-    let path = path.as_ref();
-    let output = Command::new("git")
-        .args(["log", "-1", "--format=%h: %s"])
-        .current_dir(path)
-        .output()?;
+    Ok(run_git(path, &["log", "-1", "--format=%h: %s"])?
+        .trim()
+        .to_string())
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(GitOpsError::Git(stderr.to_string()));
-    }
+/// List the commits on HEAD since `base`, oldest first.
+///
+/// Returns `(sha, subject)` pairs for `base..HEAD`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the git repository (or worktree)
+/// * `base` - Commit the range starts after
+pub fn get_commits_since(
+    path: impl AsRef<Path>,
+    base: &str,
+) -> Result<Vec<(String, String)>, GitOpsError> {
+    let range = format!("{base}..HEAD");
+    Ok(
+        run_git(path, &["log", "--reverse", "--format=%H%x09%s", &range])?
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(sha, subject)| (sha.to_string(), subject.to_string()))
+            .collect(),
+    )
+}
 
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+/// Count lines inserted plus deleted in the working tree since `base`.
+///
+/// Covers commits made since `base` as well as staged and unstaged changes.
+/// Untracked files and binary files are not counted.
+///
+/// # Arguments
+///
+/// * `path` - Path to the git repository (or worktree)
+/// * `base` - Commit to diff against
+pub fn get_changed_line_count(path: impl AsRef<Path>, base: &str) -> Result<u64, GitOpsError> {
+    Ok(run_git(path, &["diff", "--numstat", base, "--"])?
+        .lines()
+        .flat_map(|line| line.split('\t').take(2))
+        .filter_map(|count| count.parse::<u64>().ok())
+        .sum())
 }
 
 /// Get a list of files that were modified in the most recent commits.
@@ -368,7 +469,7 @@ pub fn get_recent_files(path: impl AsRef<Path>, limit: usize) -> Result<Vec<Stri
     let ranges = ["HEAD~5..HEAD", "HEAD~2..HEAD", "HEAD~1..HEAD"];
 
     for range in ranges {
-        let output = Command::new("git")
+        let output = git_command()
             .args(["diff", "--name-only", range, "--"])
             .current_dir(path)
             .output()?;
@@ -389,7 +490,7 @@ pub fn get_recent_files(path: impl AsRef<Path>, limit: usize) -> Result<Vec<Stri
     }
 
     // Fall back to listing all tracked files (for new repos with one commit)
-    let output = Command::new("git")
+    let output = git_command()
         .args(["ls-files", "--"])
         .current_dir(path)
         .output()?;
@@ -490,6 +591,26 @@ mod tests {
         assert!(has_uncommitted_changes(temp.path()).unwrap());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_auto_commit_skips_repository_hooks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        init_git_repo(temp.path());
+
+        let marker = temp.path().join("hook-ran");
+        let hook = temp.path().join(".git/hooks/pre-commit");
+        fs::write(&hook, format!("#!/bin/sh\ntouch '{}'\n", marker.display())).unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(temp.path().join("new_file.txt"), "content").unwrap();
+
+        let result = auto_commit_changes(temp.path(), "test-loop").unwrap();
+
+        assert!(result.committed);
+        assert!(!marker.exists());
+    }
+
     #[test]
     fn test_auto_commit_no_changes() {
         let temp = TempDir::new().unwrap();
@@ -528,6 +649,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_auto_commit_with_trailers() {
+        let temp = TempDir::new().unwrap();
+        init_git_repo(temp.path());
+
+        fs::write(temp.path().join("feature.txt"), "new feature").unwrap();
+        let options = CommitOptions {
+            trailers: vec![
+                ("Session-Id".to_string(), "loop-123".to_string()),
+                ("Iteration".to_string(), "7".to_string()),
+            ],
+            ..CommitOptions::default()
+        };
+        auto_commit_changes_with(temp.path(), "loop-123", &options).unwrap();
+
+        let output = Command::new("git")
+            .args(["log", "-1", "--pretty=%s|%(trailers:key=Session-Id,valueonly,separator=)|%(trailers:key=Iteration,valueonly,separator=)"])
+            .current_dir(temp.path())
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            "chore: auto-commit before merge (loop loop-123)|loop-123|7"
+        );
+    }
+
+    #[test]
+    fn test_auto_commit_with_prefix_and_identity() {
+        let temp = TempDir::new().unwrap();
+        init_git_repo(temp.path());
+
+        fs::write(temp.path().join("feature.txt"), "new feature").unwrap();
+        let options = CommitOptions {
+            prefix: Some("[ralph]".to_string()),
+            identity: Some(("Ralph Orchestrator".to_string(), "ralph@ci".to_string())),
+            ..CommitOptions::default()
+        };
+        auto_commit_changes_with(temp.path(), "loop-123", &options).unwrap();
+
+        let output = Command::new("git")
+            .args(["log", "-1", "--pretty=%s|%an <%ae>|%cn <%ce>"])
+            .current_dir(temp.path())
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            "[ralph] chore: auto-commit before merge (loop loop-123)|Ralph Orchestrator <ralph@ci>|Ralph Orchestrator <ralph@ci>"
+        );
+    }
+
+    #[test]
+    fn test_commit_options_signing_args() {
+        let args = |signing_key: Option<&str>| {
+            CommitOptions {
+                signing_key: signing_key.map(str::to_string),
+                ..CommitOptions::default()
+            }
+            .args("msg")
+        };
+        assert_eq!(args(None), ["commit", "-m", "msg"]);
+        assert_eq!(args(Some("")), ["commit", "--gpg-sign", "-m", "msg"]);
+        assert_eq!(
+            args(Some("ABCD1234")),
+            ["commit", "--gpg-sign=ABCD1234", "-m", "msg"]
+        );
+    }
+
     #[test]
     fn test_auto_commit_staged_changes() {
         let temp = TempDir::new().unwrap();
@@ -0,0 +1,586 @@
+//! Activation predicates for hats (`when:` in hat config).
+//!
+//! A predicate is a small boolean expression checked before a hat is
+//! activated for an event. It can look at loop state and at the triggering
+//! event:
+//!
+//! ```yaml
+//! hats:
+//!   reviewer:
+//!     triggers: ["build.done"]
+//!     when: "iteration > 5 and payload contains 'frontend'"
+//! ```
+//!
+//! Grammar:
+//!
+//! ```text
+//! expr       := or
+//! or         := and (("or" | "||") and)*
+//! and        := unary (("and" | "&&") unary)*
+//! unary      := ("not" | "!") unary | "(" expr ")" | comparison
+//! comparison := operand op operand
+//! op         := "==" | "!=" | ">" | ">=" | "<" | "<="
+//!             | "contains" | "starts_with" | "ends_with"
+//! operand    := variable | number | 'string' | "string"
+//! ```
+//!
+//! Variables: `iteration`, `activations` (times this hat has been activated),
+//! `cost` (cumulative USD), `elapsed` (seconds since the loop started),
+//! `topic`, `payload`, and `payload_len` (payload size in bytes). Operands are type-checked when the predicate is
+//! parsed, so a config with `payload > 3` is rejected up front.
+
+use std::fmt;
+
+/// Loop and event state a predicate is evaluated against.
+#[derive(Debug, Clone, Copy)]
+pub struct PredicateContext<'a> {
+    /// Current iteration (1-indexed).
+    pub iteration: u32,
+    /// Times the hat has been activated so far this run.
+    pub activations: u32,
+    /// Cumulative cost in USD.
+    pub cost: f64,
+    /// Seconds since the loop started.
+    pub elapsed_secs: u64,
+    /// Topic of the triggering event.
+    pub topic: &'a str,
+    /// Payload of the triggering event.
+    pub payload: &'a str,
+}
+
+/// Error parsing a hat predicate.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{message} (at position {position})")]
+pub struct PredicateError {
+    /// Byte offset in the expression where the problem was found.
+    pub position: usize,
+    /// What went wrong.
+    pub message: String,
+}
+
+/// A parsed `when:` expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HatPredicate {
+    source: String,
+    expr: Expr,
+}
+
+impl HatPredicate {
+    /// Parses and type-checks a predicate expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the expression is malformed, references an unknown
+    /// variable, or compares values of the wrong type.
+    pub fn parse(source: &str) -> Result<Self, PredicateError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            end: source.len(),
+        };
+        let expr = parser.parse_or()?;
+        if let Some((token, position)) = parser.tokens.get(parser.pos) {
+            return Err(PredicateError {
+                position: *position,
+                message: format!("unexpected {token}"),
+            });
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    /// Returns the original expression text.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluates the predicate.
+    pub fn evaluate(&self, ctx: &PredicateContext<'_>) -> bool {
+        self.expr.evaluate(ctx)
+    }
+}
+
+impl fmt::Display for HatPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variable {
+    Iteration,
+    Activations,
+    Cost,
+    Elapsed,
+    Topic,
+    Payload,
+    PayloadLen,
+}
+
+impl Variable {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "iteration" => Self::Iteration,
+            "activations" => Self::Activations,
+            "cost" => Self::Cost,
+            "elapsed" => Self::Elapsed,
+            "topic" => Self::Topic,
+            "payload" => Self::Payload,
+            "payload_len" => Self::PayloadLen,
+            _ => return None,
+        })
+    }
+
+    fn is_numeric(self) -> bool {
+        !matches!(self, Self::Topic | Self::Payload)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Var(Variable),
+    Number(f64),
+    Str(String),
+}
+
+impl Operand {
+    fn is_numeric(&self) -> bool {
+        match self {
+            Self::Var(var) => var.is_numeric(),
+            Self::Number(_) => true,
+            Self::Str(_) => false,
+        }
+    }
+
+    fn number(&self, ctx: &PredicateContext<'_>) -> f64 {
+        match self {
+            Self::Var(Variable::Iteration) => f64::from(ctx.iteration),
+            Self::Var(Variable::Activations) => f64::from(ctx.activations),
+            Self::Var(Variable::Cost) => ctx.cost,
+            #[allow(clippy::cast_precision_loss)]
+            Self::Var(Variable::Elapsed) => ctx.elapsed_secs as f64,
+            #[allow(clippy::cast_precision_loss)]
+            Self::Var(Variable::PayloadLen) => ctx.payload.len() as f64,
+            Self::Number(n) => *n,
+            // Ruled out by type checking at parse time.
+            Self::Var(Variable::Topic | Variable::Payload) | Self::Str(_) => f64::NAN,
+        }
+    }
+
+    fn text<'a>(&'a self, ctx: &PredicateContext<'a>) -> &'a str {
+        match self {
+            Self::Var(Variable::Topic) => ctx.topic,
+            Self::Var(Variable::Payload) => ctx.payload,
+            Self::Str(s) => s,
+            // Ruled out by type checking at parse time.
+            Self::Var(_) | Self::Number(_) => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+impl Op {
+    fn is_ordering(self) -> bool {
+        matches!(self, Self::Gt | Self::Ge | Self::Lt | Self::Le)
+    }
+
+    fn is_text(self) -> bool {
+        matches!(self, Self::Contains | Self::StartsWith | Self::EndsWith)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Compare(Operand, Op, Operand),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn evaluate(&self, ctx: &PredicateContext<'_>) -> bool {
+        match self {
+            Expr::Not(inner) => !inner.evaluate(ctx),
+            Expr::And(a, b) => a.evaluate(ctx) && b.evaluate(ctx),
+            Expr::Or(a, b) => a.evaluate(ctx) || b.evaluate(ctx),
+            Expr::Compare(lhs, op, rhs) if lhs.is_numeric() => {
+                let (l, r) = (lhs.number(ctx), rhs.number(ctx));
+                match op {
+                    Op::Eq => (l - r).abs() < f64::EPSILON,
+                    Op::Ne => (l - r).abs() >= f64::EPSILON,
+                    Op::Gt => l > r,
+                    Op::Ge => l >= r,
+                    Op::Lt => l < r,
+                    Op::Le => l <= r,
+                    Op::Contains | Op::StartsWith | Op::EndsWith => false,
+                }
+            }
+            Expr::Compare(lhs, op, rhs) => {
+                let (l, r) = (lhs.text(ctx), rhs.text(ctx));
+                match op {
+                    Op::Eq => l == r,
+                    Op::Ne => l != r,
+                    Op::Contains => l.contains(r),
+                    Op::StartsWith => l.starts_with(r),
+                    Op::EndsWith => l.ends_with(r),
+                    Op::Gt | Op::Ge | Op::Lt | Op::Le => false,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{name}'"),
+            Token::Number(n) => write!(f, "number {n}"),
+            Token::Str(s) => write!(f, "string '{s}'"),
+            Token::Op(op) => write!(f, "operator {op:?}"),
+            Token::And => f.write_str("'and'"),
+            Token::Or => f.write_str("'or'"),
+            Token::Not => f.write_str("'not'"),
+            Token::LParen => f.write_str("'('"),
+            Token::RParen => f.write_str("')'"),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, PredicateError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let two = source.get(start..start + 2);
+        let token = match c {
+            '(' => {
+                chars.next();
+                Token::LParen
+            }
+            ')' => {
+                chars.next();
+                Token::RParen
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, ch)) if ch == c => break,
+                        Some((_, ch)) => value.push(ch),
+                        None => {
+                            return Err(PredicateError {
+                                position: start,
+                                message: "unterminated string".to_string(),
+                            });
+                        }
+                    }
+                }
+                Token::Str(value)
+            }
+            _ if matches!(two, Some("==" | "!=" | ">=" | "<=" | "&&" | "||")) => {
+                chars.next();
+                chars.next();
+                match two {
+                    Some("==") => Token::Op(Op::Eq),
+                    Some("!=") => Token::Op(Op::Ne),
+                    Some(">=") => Token::Op(Op::Ge),
+                    Some("<=") => Token::Op(Op::Le),
+                    Some("&&") => Token::And,
+                    _ => Token::Or,
+                }
+            }
+            '>' | '<' | '!' => {
+                chars.next();
+                match c {
+                    '>' => Token::Op(Op::Gt),
+                    '<' => Token::Op(Op::Lt),
+                    _ => Token::Not,
+                }
+            }
+            _ if c.is_ascii_digit() => {
+                let mut end = start;
+                while let Some(&(i, ch)) = chars.peek() {
+                    if !(ch.is_ascii_digit() || ch == '.') {
+                        break;
+                    }
+                    end = i + ch.len_utf8();
+                    chars.next();
+                }
+                let text = &source[start..end];
+                Token::Number(text.parse().map_err(|_| PredicateError {
+                    position: start,
+                    message: format!("invalid number '{text}'"),
+                })?)
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let mut end = start;
+                while let Some(&(i, ch)) = chars.peek() {
+                    if !(ch.is_alphanumeric() || ch == '_') {
+                        break;
+                    }
+                    end = i + ch.len_utf8();
+                    chars.next();
+                }
+                match &source[start..end] {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "contains" => Token::Op(Op::Contains),
+                    "starts_with" => Token::Op(Op::StartsWith),
+                    "ends_with" => Token::Op(Op::EndsWith),
+                    name => Token::Ident(name.to_string()),
+                }
+            }
+            _ => {
+                return Err(PredicateError {
+                    position: start,
+                    message: format!("unexpected character '{c}'"),
+                });
+            }
+        };
+        tokens.push((token, start));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [(Token, usize)],
+    pos: usize,
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(_, pos)| *pos)
+    }
+
+    fn error(&self, message: impl Into<String>) -> PredicateError {
+        PredicateError {
+            position: self.position(),
+            message: message.into(),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, PredicateError> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, PredicateError> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, PredicateError> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.parse_or()?;
+                if self.next() != Some(Token::RParen) {
+                    return Err(self.error("expected ')'"));
+                }
+                Ok(expr)
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, PredicateError> {
+        let start = self.position();
+        let lhs = self.parse_operand()?;
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            _ => return Err(self.error("expected a comparison operator")),
+        };
+        let rhs = self.parse_operand()?;
+
+        let type_error = |message: &str| PredicateError {
+            position: start,
+            message: message.to_string(),
+        };
+        if lhs.is_numeric() != rhs.is_numeric() {
+            return Err(type_error("cannot compare a number with a string"));
+        }
+        if lhs.is_numeric() && op.is_text() {
+            return Err(type_error(
+                "contains/starts_with/ends_with need string operands",
+            ));
+        }
+        if !lhs.is_numeric() && op.is_ordering() {
+            return Err(type_error("<, <=, >, >= need numeric operands"));
+        }
+
+        Ok(Expr::Compare(lhs, op, rhs))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, PredicateError> {
+        let position = self.position();
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Operand::Number(n)),
+            Some(Token::Str(s)) => Ok(Operand::Str(s)),
+            Some(Token::Ident(name)) => Variable::from_name(&name)
+                .map(Operand::Var)
+                .ok_or_else(|| PredicateError {
+                    position,
+                    message: format!(
+                        "unknown variable '{name}' (expected iteration, activations, cost, elapsed, topic, payload, or payload_len)"
+                    ),
+                }),
+            Some(token) => Err(PredicateError {
+                position,
+                message: format!("expected a value, found {token}"),
+            }),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx<'a>(iteration: u32, topic: &'a str, payload: &'a str) -> PredicateContext<'a> {
+        PredicateContext {
+            iteration,
+            activations: 0,
+            cost: 0.0,
+            elapsed_secs: 0,
+            topic,
+            payload,
+        }
+    }
+
+    fn eval(expr: &str, ctx: &PredicateContext<'_>) -> bool {
+        HatPredicate::parse(expr).unwrap().evaluate(ctx)
+    }
+
+    #[test]
+    fn test_numeric_comparisons() {
+        let c = ctx(6, "build.done", "");
+        assert!(eval("iteration > 5", &c));
+        assert!(!eval("iteration < 5", &c));
+        assert!(eval("iteration >= 6 and iteration <= 6", &c));
+        assert!(eval("iteration == 6", &c));
+        assert!(eval("activations != 1", &c));
+        assert!(eval("cost < 2.5", &c));
+    }
+
+    #[test]
+    fn test_payload_len() {
+        let c = ctx(1, "build.task", "twelve bytes");
+        assert!(eval("payload_len == 12", &c));
+        assert!(eval("payload_len > 10 and payload_len < 20", &c));
+        assert!(HatPredicate::parse("payload_len contains 'x'").is_err());
+    }
+
+    #[test]
+    fn test_string_operators() {
+        let c = ctx(1, "build.done", "Fix the frontend layout");
+        assert!(eval("payload contains 'frontend'", &c));
+        assert!(eval(r#"payload contains "frontend""#, &c));
+        assert!(!eval("payload contains 'backend'", &c));
+        assert!(eval("topic starts_with 'build.'", &c));
+        assert!(eval("topic ends_with '.done'", &c));
+        assert!(eval("topic == 'build.done'", &c));
+    }
+
+    #[test]
+    fn test_boolean_operators_and_precedence() {
+        let c = ctx(3, "build.done", "frontend");
+        // `and` binds tighter than `or`.
+        assert!(eval(
+            "iteration > 5 and topic == 'x' or payload contains 'front'",
+            &c
+        ));
+        assert!(!eval(
+            "iteration > 5 and (topic == 'x' or payload contains 'front')",
+            &c
+        ));
+        assert!(eval("not iteration > 5", &c));
+        assert!(eval(
+            "!(iteration > 5) && iteration > 1 || topic == 'x'",
+            &c
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = HatPredicate::parse("payload > 3").unwrap_err();
+        assert!(err.message.contains("cannot compare"), "{err}");
+
+        let err = HatPredicate::parse("payload > 'a'").unwrap_err();
+        assert!(err.message.contains("numeric"), "{err}");
+
+        let err = HatPredicate::parse("iterations > 3").unwrap_err();
+        assert!(
+            err.message.contains("unknown variable 'iterations'"),
+            "{err}"
+        );
+        assert_eq!(err.position, 0);
+
+        assert!(HatPredicate::parse("iteration >").is_err());
+        assert!(HatPredicate::parse("(iteration > 1").is_err());
+        assert!(HatPredicate::parse("payload contains 'x").is_err());
+        assert!(HatPredicate::parse("iteration > 1 iteration").is_err());
+        assert!(HatPredicate::parse("").is_err());
+    }
+}
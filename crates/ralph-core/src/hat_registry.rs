@@ -1,14 +1,30 @@
 //! Hat registry for managing agent personas.
 
 use crate::config::{HatConfig, RalphConfig};
+use crate::hat_predicate::HatPredicate;
+use crate::native_hat::{CommandHat, NativeHat};
 use ralph_proto::{Hat, HatId, Topic};
 use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// A registered native hat's handler.
+struct NativeHandler(Box<dyn NativeHat>);
+
+impl fmt::Debug for NativeHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NativeHandler(..)")
+    }
+}
 
 /// Registry for managing and creating hats from configuration.
 #[derive(Debug, Default)]
 pub struct HatRegistry {
     hats: BTreeMap<HatId, Hat>,
     configs: BTreeMap<HatId, HatConfig>,
+    /// Parsed `when:` predicates, keyed by hat.
+    predicates: BTreeMap<HatId, HatPredicate>,
+    /// Handlers for hats implemented in Rust, keyed by hat.
+    natives: BTreeMap<HatId, NativeHandler>,
     /// Prefix index for O(1) early-exit on no-match lookups.
     /// Contains all first segments of subscription patterns (e.g., "task" from "task.*").
     /// Also contains "*" if any global wildcard exists.
@@ -29,6 +45,15 @@ impl HatRegistry {
 
         for (id, hat_config) in &config.hats {
             let hat = Self::hat_from_config(id, hat_config);
+            if let Some(command) = &hat_config.command {
+                let mut handler = CommandHat::new(command, &config.core.workspace_root);
+                if let Some(topic) = &hat_config.default_publishes {
+                    handler = handler.with_success_topic(topic);
+                }
+                registry
+                    .natives
+                    .insert(hat.id.clone(), NativeHandler(Box::new(handler)));
+            }
             registry.register_with_config(hat, hat_config.clone());
         }
 
@@ -56,9 +81,38 @@ impl HatRegistry {
         let id = hat.id.clone();
         self.index_hat_subscriptions(&hat);
         self.hats.insert(id.clone(), hat);
+        // Invalid expressions are rejected by config validation; skip them here.
+        if let Some(predicate) = config
+            .when
+            .as_deref()
+            .and_then(|when| HatPredicate::parse(when).ok())
+        {
+            self.predicates.insert(id.clone(), predicate);
+        }
         self.configs.insert(id, config);
     }
 
+    /// Registers a hat whose events are handled in-process by `handler`.
+    pub fn register_native(&mut self, hat: Hat, handler: Box<dyn NativeHat>) {
+        self.natives.insert(hat.id.clone(), NativeHandler(handler));
+        self.register(hat);
+    }
+
+    /// Returns true if the hat is handled by a native handler.
+    pub fn is_native(&self, id: &HatId) -> bool {
+        self.natives.contains_key(id)
+    }
+
+    /// IDs of hats with native handlers.
+    pub fn native_ids(&self) -> impl Iterator<Item = &HatId> {
+        self.natives.keys()
+    }
+
+    /// Gets a native hat's handler.
+    pub(crate) fn native_mut(&mut self, id: &HatId) -> Option<&mut dyn NativeHat> {
+        Some(self.natives.get_mut(id)?.0.as_mut())
+    }
+
     /// Indexes a hat's subscriptions for O(1) prefix lookup.
     fn index_hat_subscriptions(&mut self, hat: &Hat) {
         for sub in &hat.subscriptions {
@@ -85,6 +139,11 @@ impl HatRegistry {
         self.configs.get(id)
     }
 
+    /// Gets a hat's activation predicate, if it has one.
+    pub fn predicate(&self, id: &HatId) -> Option<&HatPredicate> {
+        self.predicates.get(id)
+    }
+
     /// Returns all hats in the registry.
     pub fn all(&self) -> impl Iterator<Item = &Hat> {
         self.hats.values()
@@ -386,4 +445,54 @@ hats:
         assert_eq!(subs[1].id.as_str(), "middle");
         assert_eq!(subs[2].id.as_str(), "zebra");
     }
+
+    #[test]
+    fn test_command_hats_are_native() {
+        let yaml = r#"
+hats:
+  formatter:
+    name: "Formatter"
+    triggers: ["build.done"]
+    command: "cargo fmt --all"
+  builder:
+    name: "Builder"
+    triggers: ["build.task"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut registry = HatRegistry::from_config(&config);
+
+        assert!(registry.is_native(&HatId::new("formatter")));
+        assert!(!registry.is_native(&HatId::new("builder")));
+        assert!(registry.native_mut(&HatId::new("formatter")).is_some());
+        let subs = registry.subscribers(&Topic::new("build.done"));
+        assert_eq!(subs[0].id.as_str(), "formatter");
+    }
+
+    #[test]
+    fn test_register_native_subscribes_hat() {
+        #[derive(Debug)]
+        struct Noop;
+        impl NativeHat for Noop {
+            fn handle(
+                &mut self,
+                _hat_id: &HatId,
+                _events: &[ralph_proto::Event],
+            ) -> Result<Vec<ralph_proto::Event>, crate::native_hat::NativeHatError> {
+                Ok(Vec::new())
+            }
+        }
+
+        let mut registry = HatRegistry::new();
+        registry.register_native(
+            Hat::new("changelog", "Changelog").subscribe("release.*"),
+            Box::new(Noop),
+        );
+
+        assert!(registry.is_native(&HatId::new("changelog")));
+        assert_eq!(
+            registry.native_ids().collect::<Vec<_>>(),
+            vec![&HatId::new("changelog")]
+        );
+        assert!(registry.has_subscriber("release.cut"));
+    }
 }
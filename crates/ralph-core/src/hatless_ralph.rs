@@ -2,12 +2,17 @@
 //!
 //! Ralph is always present, cannot be configured away, and acts as a universal fallback.
 
-use crate::config::CoreConfig;
+use crate::config::{CoreConfig, EventSyntax};
+use crate::contract::Contracts;
 use crate::hat_registry::HatRegistry;
+use crate::prompt_pack::PromptPack;
 use ralph_proto::Topic;
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Guardrail added when the `shell_tool` policy is enabled.
+const SHELL_TOOL_GUARDRAIL: &str = "Run shell commands through `ralph tools run -- <command>`, not directly; refused or failed commands are published as `tool.denied` / `tool.failed` events";
+
 /// Hatless Ralph - the constant coordinator.
 pub struct HatlessRalph {
     completion_promise: String,
@@ -27,6 +32,30 @@ pub struct HatlessRalph {
     /// Collected robot guidance messages for injection into prompts.
     /// Set by EventLoop before build_prompt(), cleared after injection.
     robot_guidance: Vec<String>,
+    /// Syntax shown for events written in output when `ralph emit` can't run.
+    event_syntax: EventSyntax,
+    /// Hats whose pending queues are over the backpressure threshold.
+    /// Set by EventLoop before every build_prompt() in multi-hat mode.
+    backpressure: Vec<QueuePressure>,
+    /// Required payload fields per topic, rendered for whoever publishes it.
+    contracts: Contracts,
+    /// Whether stable sections are laid out first for prompt caching.
+    cache_friendly: bool,
+    /// Localized headers and section templates.
+    pack: PromptPack,
+    /// Whether agents are told to run shell commands via `ralph tools run`.
+    shell_tool: bool,
+}
+
+/// A hat whose pending queue is deeper than the backpressure threshold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuePressure {
+    /// Name of the backed-up hat.
+    pub hat: String,
+    /// Topics of the events waiting in its queue.
+    pub topics: Vec<String>,
+    /// Number of events waiting in its queue.
+    pub depth: usize,
 }
 
 /// Hat topology for multi-hat mode prompt generation.
@@ -50,6 +79,8 @@ pub struct HatInfo {
     pub instructions: String,
     /// Maps each published event to the hats that receive it.
     pub event_receivers: HashMap<String, Vec<EventReceiver>>,
+    /// Marked `deprecated: true`: Ralph shouldn't delegate new work to it.
+    pub deprecated: bool,
 }
 
 impl HatInfo {
@@ -133,6 +164,9 @@ impl HatTopology {
                         .collect(),
                     instructions: hat.instructions.clone(),
                     event_receivers,
+                    deprecated: registry
+                        .get_config(&hat.id)
+                        .is_some_and(|config| config.deprecated),
                 }
             })
             .collect();
@@ -170,6 +204,12 @@ impl HatlessRalph {
             objective: None,
             skill_index: String::new(),
             robot_guidance: Vec::new(),
+            event_syntax: EventSyntax::default(),
+            backpressure: Vec::new(),
+            contracts: Contracts::default(),
+            cache_friendly: false,
+            pack: PromptPack::default(),
+            shell_tool: false,
         }
     }
 
@@ -191,6 +231,44 @@ impl HatlessRalph {
         self
     }
 
+    /// Sets the syntax shown for events written directly in output.
+    pub fn with_event_syntax(mut self, syntax: EventSyntax) -> Self {
+        self.event_syntax = syntax;
+        self
+    }
+
+    /// Sets the handoff contracts shown to the hats that publish each topic.
+    pub fn with_contracts(mut self, contracts: Contracts) -> Self {
+        self.contracts = contracts;
+        self
+    }
+
+    /// Lays prompts out for prompt caching.
+    ///
+    /// See [`HatlessRalph::build_prompt_parts`] for the resulting order.
+    pub fn with_cache_friendly_layout(mut self, enabled: bool) -> Self {
+        self.cache_friendly = enabled;
+        self
+    }
+
+    /// Sets the prompt pack that localizes headers and sections.
+    pub fn with_prompt_pack(mut self, pack: PromptPack) -> Self {
+        self.pack = pack;
+        self
+    }
+
+    /// Tells agents to run shell commands through the guarded proxy,
+    /// `ralph tools run -- <command>`.
+    pub fn with_shell_tool(mut self, enabled: bool) -> Self {
+        self.shell_tool = enabled;
+        self
+    }
+
+    /// The prompt pack, for sections other prompt builders add.
+    pub fn prompt_pack(&self) -> &PromptPack {
+        &self.pack
+    }
+
     /// Stores the user's original objective so it persists across all iterations.
     ///
     /// Called once during initialization. The objective is injected into every
@@ -216,6 +294,41 @@ impl HatlessRalph {
         self.robot_guidance.clear();
     }
 
+    /// Sets the hats whose queues are backed up, replacing any previous set.
+    ///
+    /// Called by `EventLoop::build_prompt()` with queue depths read from the
+    /// event bus. An empty list removes the `## QUEUE BACKPRESSURE` section.
+    pub fn set_backpressure(&mut self, backpressure: Vec<QueuePressure>) {
+        self.backpressure = backpressure;
+    }
+
+    /// Renders the `## QUEUE BACKPRESSURE` section, or an empty string when no
+    /// queue is over the threshold.
+    fn backpressure_section(&self) -> String {
+        if self.backpressure.is_empty() {
+            return String::new();
+        }
+
+        let mut section = format!(
+            "## {}\n\nThese hats already have more work queued than they can take in one turn:\n\n",
+            self.pack.header("QUEUE BACKPRESSURE")
+        );
+        for pressure in &self.backpressure {
+            let topics = pressure
+                .topics
+                .iter()
+                .map(|t| format!("`{t}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            section.push_str(&format!(
+                "- DO NOT publish more {topics} events until {} drains its queue ({} pending).\n",
+                pressure.hat, pressure.depth
+            ));
+        }
+        section.push_str("\nLet the queued work finish before delegating more of it.\n\n");
+        section
+    }
+
     /// Collects robot guidance and returns the formatted prompt section.
     ///
     /// Squashes multiple guidance messages into a numbered list format.
@@ -225,7 +338,7 @@ impl HatlessRalph {
             return String::new();
         }
 
-        let mut section = String::from("## ROBOT GUIDANCE\n\n");
+        let mut section = format!("## {}\n\n", self.pack.header("ROBOT GUIDANCE"));
 
         if self.robot_guidance.len() == 1 {
             section.push_str(&self.robot_guidance[0]);
@@ -248,6 +361,31 @@ impl HatlessRalph {
     ///
     /// For solo mode (no hats), pass an empty slice: `&[]`
     pub fn build_prompt(&self, context: &str, active_hats: &[&ralph_proto::Hat]) -> String {
+        let (stable, volatile) = self.build_prompt_parts(context, active_hats);
+        format!("{stable}{volatile}")
+    }
+
+    /// Builds Ralph's prompt split into a stable prefix and a volatile rest.
+    ///
+    /// With the cache-friendly layout, the prefix holds the sections that stay
+    /// the same from one iteration to the next (identity, guardrails, skill
+    /// index, objective, event syntax, workflow, hat topology) so a backend's
+    /// prompt cache can reuse it. Guidance, pending events, the active hat and
+    /// completion instructions follow in the rest. Callers that add iteration
+    /// context should put it at the start of the rest, not before the prefix.
+    ///
+    /// Without it, the prefix is empty and the rest is the whole prompt.
+    pub fn build_prompt_parts(
+        &self,
+        context: &str,
+        active_hats: &[&ralph_proto::Hat],
+    ) -> (String, String) {
+        // Check if any active hat has custom instructions
+        // If so, skip the generic workflow - the hat's instructions ARE the workflow
+        let has_custom_workflow = active_hats
+            .iter()
+            .any(|h| !h.instructions.trim().is_empty());
+
         let mut prompt = self.core_prompt();
 
         // Inject skill index between GUARDRAILS and OBJECTIVE
@@ -261,25 +399,30 @@ impl HatlessRalph {
             prompt.push_str(&self.objective_section(obj));
         }
 
-        // Inject robot guidance (collected from human.guidance events, cleared after injection)
-        let guidance = self.collect_robot_guidance();
-        if !guidance.is_empty() {
-            prompt.push_str(&guidance);
-        }
+        if self.cache_friendly {
+            prompt.push_str(&self.event_writing_section());
+            if !has_custom_workflow {
+                prompt.push_str(&self.workflow_section());
+            }
+            if active_hats.is_empty()
+                && let Some(topology) = &self.hat_topology
+            {
+                prompt.push_str(&self.hats_section(topology, active_hats));
+            }
 
-        // Include pending events BEFORE workflow so Ralph sees the task first
-        if !context.trim().is_empty() {
-            prompt.push_str("## PENDING EVENTS\n\n");
-            prompt.push_str("You MUST handle these events in this iteration:\n\n");
-            prompt.push_str(context);
-            prompt.push_str("\n\n");
+            let mut volatile = self.iteration_sections(context);
+            if !active_hats.is_empty()
+                && let Some(topology) = &self.hat_topology
+            {
+                volatile.push_str(&self.hats_section(topology, active_hats));
+            }
+            if active_hats.is_empty() {
+                volatile.push_str(&self.done_section(self.objective.as_deref()));
+            }
+            return (prompt, volatile);
         }
 
-        // Check if any active hat has custom instructions
-        // If so, skip the generic workflow - the hat's instructions ARE the workflow
-        let has_custom_workflow = active_hats
-            .iter()
-            .any(|h| !h.instructions.trim().is_empty());
+        prompt.push_str(&self.iteration_sections(context));
 
         if !has_custom_workflow {
             prompt.push_str(&self.workflow_section());
@@ -297,13 +440,36 @@ impl HatlessRalph {
             prompt.push_str(&self.done_section(self.objective.as_deref()));
         }
 
-        prompt
+        (String::new(), prompt)
+    }
+
+    /// Renders the sections that change every iteration: robot guidance,
+    /// queue backpressure, and pending events.
+    fn iteration_sections(&self, context: &str) -> String {
+        // Inject robot guidance (collected from human.guidance events, cleared after injection)
+        let mut sections = self.collect_robot_guidance();
+
+        sections.push_str(&self.backpressure_section());
+
+        // Include pending events BEFORE workflow so Ralph sees the task first
+        if !context.trim().is_empty() {
+            sections.push_str(&format!("## {}\n\n", self.pack.header("PENDING EVENTS")));
+            sections.push_str("You MUST handle these events in this iteration:\n\n");
+            sections.push_str(context);
+            sections.push_str("\n\n");
+        }
+
+        sections
     }
 
     /// Generates the OBJECTIVE section - the primary goal Ralph must achieve.
     fn objective_section(&self, objective: &str) -> String {
+        let header = self.pack.header("OBJECTIVE");
+        if let Some(body) = self.pack.section("objective", &[("objective", objective)]) {
+            return format!("## {header}\n\n{body}");
+        }
         format!(
-            r"## OBJECTIVE
+            r"## {header}
 
 **This is your primary goal. All work must advance this objective.**
 
@@ -339,10 +505,13 @@ You MUST NOT get distracted by workflow mechanics — they serve this goal.
 
     fn core_prompt(&self) -> String {
         // Adapt guardrails based on whether scratchpad or memories mode is active
+        let shell_tool = self.shell_tool.then_some(SHELL_TOOL_GUARDRAIL);
         let guardrails = self
             .core
             .guardrails
             .iter()
+            .map(String::as_str)
+            .chain(shell_tool)
             .enumerate()
             .map(|(i, g)| {
                 // Replace scratchpad reference with memories reference when memories are enabled
@@ -352,16 +521,20 @@ You MUST NOT get distracted by workflow mechanics — they serve this goal.
                         "save learnings to memories for next time",
                     )
                 } else {
-                    g.clone()
+                    g.to_string()
                 };
                 format!("{}. {guardrail}", 999 + i)
             })
             .collect::<Vec<_>>()
             .join("\n");
 
-        let mut prompt = if self.memories_enabled {
-            r"
-### 0a. ORIENTATION
+        let orientation = self.pack.header("ORIENTATION");
+        let mut prompt = if let Some(body) = self.pack.section("orientation", &[]) {
+            format!("\n### 0a. {orientation}\n{body}")
+        } else if self.memories_enabled {
+            format!(
+                r"
+### 0a. {orientation}
 You are Ralph. You are running in a loop. You have fresh context each iteration.
 You MUST complete only one atomic task for the overall objective. Leave work for future iterations.
 
@@ -370,22 +543,25 @@ You MUST complete only one atomic task for the overall objective. Leave work for
 2. Review your `<ready-tasks>` (auto-injected above) to see what work exists
 3. If tasks exist, pick one. If not, create them from your plan.
 "
+            )
         } else {
-            r"
-### 0a. ORIENTATION
+            format!(
+                r"
+### 0a. {orientation}
 You are Ralph. You are running in a loop. You have fresh context each iteration.
 You MUST complete only one atomic task for the overall objective. Leave work for future iterations.
 "
-        }
-        .to_string();
+            )
+        };
 
         // SCRATCHPAD section - ALWAYS present
         prompt.push_str(&format!(
-            r"### 0b. SCRATCHPAD
+            r"### 0b. {header}
 `{scratchpad}` is your thinking journal for THIS objective.
 Its content is auto-injected in `<scratchpad>` tags at the top of your context each iteration.
 
 **Always append** new entries to the end of the file (most recent = bottom).
+If other hats or a human may be editing it too, rewrite it with `ralph tools scratchpad write --base <revision>` (revision from the `<scratchpad>` tag) so concurrent edits are merged instead of overwritten.
 
 **Use for:**
 - Current understanding and reasoning
@@ -397,6 +573,7 @@ Its content is auto-injected in `<scratchpad>` tags at the top of your context e
 - Checklists or todo lists (use `ralph tools task add`)
 
 ",
+            header = self.pack.header("SCRATCHPAD"),
             scratchpad = self.core.scratchpad,
         ));
 
@@ -406,7 +583,7 @@ Its content is auto-injected in `<scratchpad>` tags at the top of your context e
 
         // Add state management guidance
         prompt.push_str(&format!(
-            "### STATE MANAGEMENT\n\n\
+            "### {header}\n\n\
 **Tasks** (`ralph tools task`) — What needs to be done:\n\
 - Work items, their status, priorities, and dependencies\n\
 - Source of truth for progress across iterations\n\
@@ -428,6 +605,7 @@ Its content is auto-injected in `<scratchpad>` tags at the top of your context e
 \n\
 **Rule:** Work items go in tasks. Thinking goes in scratchpad. Learnings go in memories.\n\
 \n",
+            header = self.pack.header("STATE MANAGEMENT"),
             scratchpad = self.core.scratchpad,
         ));
 
@@ -450,7 +628,10 @@ Its content is auto-injected in `<scratchpad>` tags at the top of your context e
                 .collect();
 
             if !md_files.is_empty() {
-                prompt.push_str("### AVAILABLE CONTEXT FILES\n\n");
+                prompt.push_str(&format!(
+                    "### {}\n\n",
+                    self.pack.header("AVAILABLE CONTEXT FILES")
+                ));
                 prompt.push_str(
                     "Context files in `.ralph/agent/` (read if relevant to current work):\n",
                 );
@@ -462,38 +643,62 @@ Its content is auto-injected in `<scratchpad>` tags at the top of your context e
         }
 
         prompt.push_str(&format!(
-            r"### GUARDRAILS
+            r"### {header}
 {guardrails}
 
 ",
+            header = self.pack.header("GUARDRAILS"),
             guardrails = guardrails,
         ));
 
+        // Iteration context follows the stable prefix in the cache-friendly layout
+        if self.cache_friendly {
+            prompt = prompt
+                .replace("(auto-injected above)", "(auto-injected below)")
+                .replace("at the top of your context", "after these instructions");
+        }
+
         prompt
     }
 
     fn workflow_section(&self) -> String {
+        let header = self.pack.header("WORKFLOW");
+        let scratchpad = self.core.scratchpad.as_str();
+
         // Different workflow for solo mode vs multi-hat mode
         if self.hat_topology.is_some() {
             // Check for fast path: starting_event set AND no scratchpad
             if self.is_fresh_start() {
+                let starting_event = self.starting_event.as_deref().unwrap();
+                if let Some(body) = self
+                    .pack
+                    .section("workflow-fast-path", &[("starting_event", starting_event)])
+                {
+                    return format!("## {header}\n\n{body}");
+                }
                 // Fast path: immediate delegation without planning
                 return format!(
-                    r"## WORKFLOW
+                    r"## {header}
 
-**FAST PATH**: You MUST publish `{}` immediately to start the hat workflow.
+**FAST PATH**: You MUST publish `{starting_event}` immediately to start the hat workflow.
 You MUST NOT plan or analyze — delegate now.
 
-",
-                    self.starting_event.as_ref().unwrap()
+"
                 );
             }
 
+            if let Some(body) = self
+                .pack
+                .section("workflow-coordinator", &[("scratchpad", scratchpad)])
+            {
+                return format!("## {header}\n\n{body}");
+            }
+
             // Multi-hat mode: Ralph coordinates and delegates
             if self.memories_enabled {
                 // Memories mode: reference both scratchpad AND tasks CLI
                 format!(
-                    r"## WORKFLOW
+                    r"## {header}
 
 ### 1. PLAN
 You MUST update `{scratchpad}` with your understanding and plan.
@@ -504,12 +709,11 @@ You MUST publish exactly ONE event to hand off to specialized hats.
 You MUST NOT do implementation work — delegation is your only job.
 
 ",
-                    scratchpad = self.core.scratchpad
                 )
             } else {
                 // Scratchpad-only mode (legacy)
                 format!(
-                    r"## WORKFLOW
+                    r"## {header}
 
 ### 1. PLAN
 You MUST update `{scratchpad}` with prioritized tasks to complete the objective end-to-end.
@@ -519,15 +723,21 @@ You MUST publish exactly ONE event to hand off to specialized hats.
 You MUST NOT do implementation work — delegation is your only job.
 
 ",
-                    scratchpad = self.core.scratchpad
                 )
             }
         } else {
+            if let Some(body) = self
+                .pack
+                .section("workflow-solo", &[("scratchpad", scratchpad)])
+            {
+                return format!("## {header}\n\n{body}");
+            }
+
             // Solo mode: Ralph does everything
             if self.memories_enabled {
                 // Memories mode: reference both scratchpad AND tasks CLI
                 format!(
-                    r"## WORKFLOW
+                    r"## {header}
 
 ### 1. Study the prompt.
 You MUST study, explore, and research what needs to be done.
@@ -551,12 +761,11 @@ You MUST update scratchpad with what you learned (tasks track what remains).
 You MUST exit after completing ONE task.
 
 ",
-                    scratchpad = self.core.scratchpad
                 )
             } else {
                 // Scratchpad-only mode (legacy)
                 format!(
-                    r"## WORKFLOW
+                    r"## {header}
 
 ### 1. Study the prompt.
 You MUST study, explore, and research what needs to be done.
@@ -579,7 +788,6 @@ You MUST mark the task `[x]` in scratchpad when complete.
 You MUST continue until all tasks are `[x]` or `[~]`.
 
 ",
-                    scratchpad = self.core.scratchpad
                 )
             }
         }
@@ -592,7 +800,10 @@ You MUST continue until all tasks are `[x]` or `[~]`.
         // The hat just needs its instructions and publishing guide
         if active_hats.is_empty() {
             // Ralph is coordinating - show full topology for delegation decisions
-            section.push_str("## HATS\n\nDelegate via events.\n\n");
+            section.push_str(&format!(
+                "## {}\n\nDelegate via events.\n\n",
+                self.pack.header("HATS")
+            ));
 
             // Include starting_event instruction if configured
             if let Some(ref starting_event) = self.starting_event {
@@ -614,7 +825,8 @@ You MUST continue until all tasks are `[x]` or `[~]`.
                         ralph_triggers.push(pub_event.as_str());
                     }
                 }
-                for sub_event in &hat.subscribes_to {
+                // Deprecated hats only finish work already addressed to them
+                for sub_event in hat.subscribes_to.iter().filter(|_| !hat.deprecated) {
                     if !ralph_publishes.contains(&sub_event.as_str()) {
                         ralph_publishes.push(sub_event.as_str());
                     }
@@ -636,9 +848,14 @@ You MUST continue until all tasks are `[x]` or `[~]`.
             for hat in &topology.hats {
                 let subscribes = hat.subscribes_to.join(", ");
                 let publishes = hat.publishes.join(", ");
+                let description = if hat.deprecated {
+                    format!("DEPRECATED - do not delegate new work. {}", hat.description)
+                } else {
+                    hat.description.clone()
+                };
                 section.push_str(&format!(
                     "| {} | {} | {} | {} |\n",
-                    hat.name, subscribes, publishes, hat.description
+                    hat.name, subscribes, publishes, description
                 ));
             }
 
@@ -657,11 +874,17 @@ You MUST continue until all tasks are `[x]` or `[~]`.
                 ));
             }
 
+            section.push_str(
+                &self
+                    .contracts
+                    .prompt_section(ralph_publishes.iter().copied()),
+            );
+
             // Validate topology and log warnings for unreachable hats
             self.validate_topology_reachability(topology);
         } else {
             // Specific hat(s) active - minimal section with just instructions + guide
-            section.push_str("## ACTIVE HAT\n\n");
+            section.push_str(&format!("## {}\n\n", self.pack.header("ACTIVE HAT")));
 
             for active_hat in active_hats {
                 // Find matching HatInfo from topology to access event_receivers
@@ -681,6 +904,12 @@ You MUST continue until all tasks are `[x]` or `[~]`.
                     section.push_str(&guide);
                     section.push('\n');
                 }
+
+                section.push_str(
+                    &self
+                        .contracts
+                        .prompt_section(active_hat.publishes.iter().map(|t| t.as_str())),
+                );
             }
         }
 
@@ -800,6 +1029,18 @@ You MUST continue until all tasks are `[x]` or `[~]`.
     }
 
     fn event_writing_section(&self) -> String {
+        let header = self.pack.header("EVENT WRITING");
+        let output_example = self.event_syntax.example("build.done", "tests: pass");
+        if let Some(body) = self.pack.section(
+            "event-writing",
+            &[
+                ("scratchpad", &self.core.scratchpad),
+                ("event_example", &output_example),
+            ],
+        ) {
+            return format!("## {header}\n\n{body}");
+        }
+
         // Always use scratchpad for detailed output (scratchpad is always present)
         let detailed_output_hint = format!(
             "You SHOULD write detailed output to `{}` and emit only a brief event.",
@@ -807,7 +1048,7 @@ You MUST continue until all tasks are `[x]` or `[~]`.
         );
 
         format!(
-            r#"## EVENT WRITING
+            r#"## {header}
 
 Events are routing signals, not data transport. You SHOULD keep payloads brief.
 
@@ -819,19 +1060,36 @@ ralph emit "review.done" --json '{{"status": "approved", "issues": 0}}'
 
 You MUST NOT use echo/cat to write events because shell escaping breaks JSON.
 
+If you cannot run `ralph emit`, write the event on its own line in your response instead:
+```text
+{output_example}
+```
+
 {detailed_output_hint}
 
 **Constraints:**
 - You MUST stop working after publishing an event because a new iteration will start with fresh context
 - You MUST NOT continue with additional work after publishing because the next iteration handles it with the appropriate hat persona
 "#,
-            detailed_output_hint = detailed_output_hint
+            detailed_output_hint = detailed_output_hint,
+            output_example = output_example,
         )
     }
 
     fn done_section(&self, objective: Option<&str>) -> String {
+        let header = self.pack.header("DONE");
+        if let Some(body) = self.pack.section(
+            "done",
+            &[
+                ("completion_promise", &self.completion_promise),
+                ("objective", objective.unwrap_or_default()),
+            ],
+        ) {
+            return format!("## {header}\n\n{body}");
+        }
+
         let mut section = format!(
-            r"## DONE
+            r"## {header}
 
 You MUST emit a completion event `{}` when the objective is complete and all tasks are done.
 You MUST use `ralph emit` (stdout text does NOT end the loop).
@@ -920,6 +1178,50 @@ mod tests {
         assert!(prompt.contains("LOOP_COMPLETE"));
     }
 
+    #[test]
+    fn test_event_writing_shows_configured_syntax() {
+        let registry = HatRegistry::new();
+        let ralph = HatlessRalph::new("LOOP_COMPLETE", CoreConfig::default(), &registry, None);
+        assert!(
+            ralph
+                .build_prompt("", &[])
+                .contains("<event topic=\"build.done\">tests: pass</event>")
+        );
+
+        let ralph = ralph.with_event_syntax(EventSyntax::Macro);
+        let prompt = ralph.build_prompt("", &[]);
+        assert!(prompt.contains("@@event(build.done) tests: pass"));
+        assert!(!prompt.contains("<event topic="));
+    }
+
+    #[test]
+    fn test_deprecated_hat_is_not_offered_for_delegation() {
+        let yaml = r#"
+hats:
+  builder:
+    name: "Builder"
+    description: "Builds"
+    triggers: ["build.task"]
+    publishes: ["build.done"]
+  old_builder:
+    name: "Old Builder"
+    description: "Builds the old way"
+    triggers: ["legacy.task"]
+    publishes: ["build.done"]
+    deprecated: true
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let registry = HatRegistry::from_config(&config);
+        let ralph = HatlessRalph::new("LOOP_COMPLETE", config.core.clone(), &registry, None);
+
+        let prompt = ralph.build_prompt("", &[]);
+
+        assert!(prompt.contains(
+            "| Old Builder | legacy.task | build.done | DEPRECATED - do not delegate new work. Builds the old way |"
+        ));
+        assert!(prompt.contains("You MUST only publish events from this list: `build.task`\n"));
+    }
+
     #[test]
     fn test_prompt_with_hats() {
         // Test multi-hat mode WITHOUT starting_event (no fast path)
@@ -1694,6 +1996,23 @@ hats:
         );
     }
 
+    #[test]
+    fn test_shell_tool_adds_numbered_guardrail() {
+        let config = RalphConfig::default();
+        let registry = HatRegistry::new();
+        let ralph = HatlessRalph::new("LOOP_COMPLETE", config.core.clone(), &registry, None);
+        assert!(!ralph.build_prompt("", &[]).contains("ralph tools run"));
+
+        let prompt = ralph.with_shell_tool(true).build_prompt("", &[]);
+        let index = 999 + config.core.guardrails.len();
+        assert!(
+            prompt.contains(&format!(
+                "{index}. Run shell commands through `ralph tools run"
+            )),
+            "Shell tool guardrail should follow the configured ones"
+        );
+    }
+
     // === Task Completion Verification Tests ===
 
     #[test]
@@ -2083,6 +2402,34 @@ hats:
         );
     }
 
+    #[test]
+    fn test_handoff_contract_shown_to_active_publisher() {
+        let yaml = r#"
+hats:
+  builder:
+    name: "Builder"
+    triggers: ["build.task"]
+    publishes: ["build.done"]
+events:
+  build.done:
+    fields: [summary]
+  build.task:
+    fields: [files]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let registry = HatRegistry::from_config(&config);
+        let ralph = HatlessRalph::new("LOOP_COMPLETE", config.core.clone(), &registry, None)
+            .with_contracts(Contracts::from_events(&config.events));
+
+        let builder = registry.get(&ralph_proto::HatId::new("builder")).unwrap();
+        let prompt = ralph.build_prompt("[build.task] Build the feature", &[builder]);
+
+        assert!(prompt.contains("### Handoff Contracts"));
+        assert!(prompt.contains("- `build.done`: `summary`"));
+        // The builder receives build.task; its contract belongs to the publisher.
+        assert!(!prompt.contains("- `build.task`: `files`"));
+    }
+
     #[test]
     fn test_event_publishing_guide_no_publishes() {
         // When a hat doesn't publish any events, no guide should appear
@@ -2463,4 +2810,113 @@ hats:
             "Should NOT include ROBOT GUIDANCE when no guidance set"
         );
     }
+
+    #[test]
+    fn test_backpressure_section_lists_backed_up_hats() {
+        let config = RalphConfig::default();
+        let registry = HatRegistry::new();
+        let mut ralph = HatlessRalph::new("LOOP_COMPLETE", config.core.clone(), &registry, None);
+        ralph.set_backpressure(vec![QueuePressure {
+            hat: "Builder".to_string(),
+            topics: vec!["build.task".to_string()],
+            depth: 7,
+        }]);
+
+        let prompt = ralph.build_prompt("Event: build.task - Do the work", &[]);
+
+        assert!(prompt.contains("## QUEUE BACKPRESSURE"));
+        assert!(prompt.contains(
+            "DO NOT publish more `build.task` events until Builder drains its queue (7 pending)."
+        ));
+        let backpressure_pos = prompt.find("## QUEUE BACKPRESSURE").unwrap();
+        let events_pos = prompt.find("## PENDING EVENTS").unwrap();
+        assert!(backpressure_pos < events_pos);
+
+        ralph.set_backpressure(Vec::new());
+        let prompt = ralph.build_prompt("", &[]);
+        assert!(!prompt.contains("## QUEUE BACKPRESSURE"));
+    }
+
+    #[test]
+    fn test_cache_friendly_layout_puts_stable_sections_first() {
+        let yaml = r#"
+hats:
+  builder:
+    name: "Builder"
+    triggers: ["build.task"]
+    publishes: ["build.done"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let registry = HatRegistry::from_config(&config);
+        let mut ralph = HatlessRalph::new("LOOP_COMPLETE", config.core.clone(), &registry, None)
+            .with_cache_friendly_layout(true);
+        ralph.set_objective("Build feature X".to_string());
+
+        let (stable, volatile) = ralph.build_prompt_parts("Event: task.start - go", &[]);
+        assert!(stable.contains("## OBJECTIVE"));
+        assert!(stable.contains("## WORKFLOW"));
+        assert!(stable.contains("## HATS"));
+        assert!(!stable.contains("## PENDING EVENTS"));
+        assert!(volatile.starts_with("## PENDING EVENTS"));
+        assert!(volatile.contains("LOOP_COMPLETE"));
+
+        // The prefix doesn't depend on the iteration's events.
+        let (next_stable, _) = ralph.build_prompt_parts("Event: build.done - ok", &[]);
+        assert_eq!(stable, next_stable);
+    }
+
+    #[test]
+    fn test_default_layout_has_no_stable_prefix() {
+        let config = RalphConfig::default();
+        let registry = HatRegistry::new();
+        let ralph = HatlessRalph::new("LOOP_COMPLETE", config.core.clone(), &registry, None);
+
+        let (stable, volatile) = ralph.build_prompt_parts("Event: task.start - go", &[]);
+        assert!(stable.is_empty());
+        assert_eq!(volatile, ralph.build_prompt("Event: task.start - go", &[]));
+    }
+
+    #[test]
+    fn test_prompt_pack_localizes_headers_not_topics() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("headers.yml"),
+            "ORIENTATION: ORIENTIERUNG\nWORKFLOW: ARBEITSABLAUF\nGUARDRAILS: LEITPLANKEN\nHATS: HÜTE\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("workflow-coordinator.md"),
+            "Halte deinen Plan in `{scratchpad}` fest und delegiere mit genau EINEM Event.\n",
+        )
+        .unwrap();
+        let pack = PromptPack::from_dir(temp_dir.path()).unwrap();
+
+        let yaml = r#"
+hats:
+  builder:
+    name: "Builder"
+    triggers: ["build.task"]
+    publishes: ["build.done"]
+"#;
+        let config: RalphConfig = serde_yaml::from_str(yaml).unwrap();
+        let registry = HatRegistry::from_config(&config);
+        let ralph = HatlessRalph::new("LOOP_COMPLETE", config.core.clone(), &registry, None)
+            .with_prompt_pack(pack);
+
+        let prompt = ralph.build_prompt("", &[]);
+        assert!(prompt.contains("### 0a. ORIENTIERUNG"));
+        assert!(prompt.contains("### LEITPLANKEN"));
+        assert!(prompt.contains("## HÜTE"));
+        assert!(prompt.contains(&format!(
+            "## ARBEITSABLAUF\n\nHalte deinen Plan in `{}` fest",
+            config.core.scratchpad
+        )));
+        assert!(!prompt.contains("### 1. PLAN"));
+
+        // Headers without a translation, topics, and commands stay English
+        assert!(prompt.contains("### 0b. SCRATCHPAD"));
+        assert!(prompt.contains("## EVENT WRITING"));
+        assert!(prompt.contains("| Builder | build.task | build.done |"));
+        assert!(prompt.contains("ralph emit \"build.done\""));
+    }
 }
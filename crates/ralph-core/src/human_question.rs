@@ -0,0 +1,108 @@
+//! Single-question escalation to a human.
+//!
+//! An agent that can't proceed without a decision emits `human.question`.
+//! The event loop publishes it, notifies the configured webhook, and waits
+//! for a `human.answer` event to land in the events file before starting the
+//! next iteration. See [`QuestionsConfig`](crate::QuestionsConfig).
+
+use serde::Serialize;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Topic an agent emits to ask the human something.
+pub const QUESTION_TOPIC: &str = "human.question";
+
+/// Topic carrying the human's reply.
+pub const ANSWER_TOPIC: &str = "human.answer";
+
+/// How often the events file is checked while waiting for an answer.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Body POSTed to the questions webhook.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuestionNotice {
+    /// The question as the agent wrote it.
+    pub question: String,
+    /// Iteration that asked.
+    pub iteration: u32,
+    /// Hat that was active, if any.
+    pub hat: Option<String>,
+    /// Events file an answer should be written to.
+    pub events_file: String,
+}
+
+/// POSTs `notice` to `url` in the background.
+///
+/// Delivery is best-effort: failures are logged and the loop keeps waiting
+/// for an answer either way. Outside a tokio runtime nothing is sent. Also
+/// used for [`BlockedNotice`](crate::blocked::BlockedNotice)s.
+pub fn notify_webhook(url: &str, notice: &impl Serialize) {
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        debug!("No runtime available — skipping escalation webhook");
+        return;
+    };
+    let notice = match serde_json::to_value(notice) {
+        Ok(notice) => notice,
+        Err(e) => {
+            warn!(error = %e, "Failed to serialize escalation webhook body");
+            return;
+        }
+    };
+    let url = url.to_string();
+    handle.spawn(async move {
+        let result = reqwest::Client::new()
+            .post(&url)
+            .timeout(Duration::from_secs(30))
+            .json(&notice)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        if let Err(e) = result {
+            warn!(url = %url, error = %e, "Failed to deliver escalation webhook");
+        }
+    });
+}
+
+/// Answer injected when nobody replies in time.
+pub fn timeout_answer(timeout: Duration) -> String {
+    format!(
+        "No answer within {}s. Proceed with your best judgment and note the assumption you made.",
+        timeout.as_secs()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notice_serializes_for_webhook() {
+        let notice = QuestionNotice {
+            question: "Postgres or SQLite?".to_string(),
+            iteration: 4,
+            hat: Some("planner".to_string()),
+            events_file: ".ralph/events.jsonl".to_string(),
+        };
+        let json = serde_json::to_value(&notice).unwrap();
+        assert_eq!(json["question"], "Postgres or SQLite?");
+        assert_eq!(json["iteration"], 4);
+        assert_eq!(json["hat"], "planner");
+    }
+
+    #[test]
+    fn test_timeout_answer_mentions_timeout() {
+        let answer = timeout_answer(Duration::from_secs(90));
+        assert!(answer.starts_with("No answer within 90s."));
+    }
+
+    #[test]
+    fn test_notify_webhook_without_runtime_is_noop() {
+        let notice = QuestionNotice {
+            question: "q".to_string(),
+            iteration: 1,
+            hat: None,
+            events_file: String::new(),
+        };
+        notify_webhook("http://127.0.0.1:9/unused", &notice);
+    }
+}
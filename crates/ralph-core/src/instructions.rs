@@ -5,7 +5,7 @@
 //! - 1, 2, 3: Workflow phases
 //! - 999+: Guardrails (higher = more important)
 
-use crate::config::{CoreConfig, EventMetadata};
+use crate::config::{CoreConfig, EventMetadata, EventSyntax};
 use ralph_proto::Hat;
 use std::collections::HashMap;
 
@@ -18,6 +18,8 @@ pub struct InstructionBuilder {
     core: CoreConfig,
     /// Event metadata for deriving instructions from pub/sub contracts.
     events: HashMap<String, EventMetadata>,
+    /// Syntax shown for events written directly in output.
+    event_syntax: EventSyntax,
 }
 
 impl InstructionBuilder {
@@ -26,12 +28,25 @@ impl InstructionBuilder {
         Self {
             core,
             events: HashMap::new(),
+            event_syntax: EventSyntax::default(),
         }
     }
 
     /// Creates a new instruction builder with event metadata for custom hats.
     pub fn with_events(core: CoreConfig, events: HashMap<String, EventMetadata>) -> Self {
-        Self { core, events }
+        Self {
+            core,
+            events,
+            event_syntax: EventSyntax::default(),
+        }
+    }
+
+    /// Sets the syntax shown for events written directly in output.
+    ///
+    /// Must match the parser's syntax (see `event_loop.event_syntax`).
+    pub fn with_event_syntax(mut self, syntax: EventSyntax) -> Self {
+        self.event_syntax = syntax;
+        self
     }
 
     /// Derives instructions from a hat's pub/sub contract and event metadata.
@@ -159,8 +174,9 @@ impl InstructionBuilder {
             (
                 format!("You publish to: {}", topics_list),
                 format!(
-                    "\n\nYou MUST publish one of these events: {}\nYou MUST NOT end the iteration without publishing because this will terminate the loop.",
-                    topics_backticked
+                    "\n\nYou MUST publish one of these events: {}\nYou MUST NOT end the iteration without publishing because this will terminate the loop.\nIf `ralph emit` is unavailable, write the event on its own line: `{}`",
+                    topics_backticked,
+                    self.event_syntax.example(topics[0], "<summary>")
                 ),
             )
         };
@@ -294,6 +310,20 @@ mod tests {
         assert!(instructions.contains("You MUST NOT end the iteration without publishing"));
     }
 
+    #[test]
+    fn test_must_publish_shows_configured_event_syntax() {
+        use ralph_proto::Topic;
+
+        let hat = Hat::new("builder", "Builder").with_publishes(vec![Topic::new("build.done")]);
+
+        let instructions = default_builder().build_custom_hat(&hat, "");
+        assert!(instructions.contains("`<event topic=\"build.done\"><summary></event>`"));
+
+        let builder = default_builder().with_event_syntax(EventSyntax::Json);
+        let instructions = builder.build_custom_hat(&hat, "");
+        assert!(instructions.contains(r#"`{"topic": "build.done", "payload": "<summary>"}`"#));
+    }
+
     #[test]
     fn test_must_publish_not_injected_when_no_publishes() {
         let builder = default_builder();
@@ -10,7 +10,8 @@
 //! handoffs between Ralph loops.
 
 use crate::git_ops::{
-    AutoCommitResult, auto_commit_changes, clean_stashes, is_working_tree_clean, prune_remote_refs,
+    AutoCommitResult, CommitOptions, auto_commit_changes_with, clean_stashes,
+    is_working_tree_clean, prune_remote_refs,
 };
 use crate::handoff::{HandoffError, HandoffWriter};
 use crate::loop_context::LoopContext;
@@ -70,6 +71,9 @@ pub struct LandingConfig {
 
     /// Whether to generate the handoff file.
     pub generate_handoff: bool,
+
+    /// Message prefix, identity, signing, and trailers for the auto-commit.
+    pub commit: CommitOptions,
 }
 
 impl Default for LandingConfig {
@@ -79,6 +83,7 @@ impl Default for LandingConfig {
             clear_stashes: true,
             prune_refs: true,
             generate_handoff: true,
+            commit: CommitOptions::default(),
         }
     }
 }
@@ -134,7 +139,7 @@ impl LandingHandler {
 
         // Step 2: Auto-commit uncommitted changes
         let commit_result = if self.config.auto_commit {
-            match auto_commit_changes(workspace, &loop_id) {
+            match auto_commit_changes_with(workspace, &loop_id, &self.config.commit) {
                 Ok(result) => {
                     if result.committed {
                         info!(
@@ -366,6 +371,7 @@ mod tests {
             clear_stashes: false,
             prune_refs: false,
             generate_handoff: false,
+            commit: CommitOptions::default(),
         };
 
         let handler = LandingHandler::with_config(ctx.clone(), config);
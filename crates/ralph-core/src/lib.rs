@@ -4,29 +4,52 @@
 //!
 //! This crate provides:
 //! - The main orchestration loop for coordinating multiple agents
+//! - An embeddable `Orchestrator` that drives the loop with a pluggable `Executor`
 //! - Configuration loading and management
 //! - State management for agent sessions
 //! - Message routing between agents
 //! - Terminal capture for session recording
 //! - Benchmark task definitions and workspace isolation
 
+pub mod amendments;
+pub mod attribution;
+pub mod audit;
+pub mod blocked;
+pub mod bootstrap;
+pub mod budget;
+pub mod carryover;
+pub mod child_loop;
 #[cfg(feature = "recording")]
 mod cli_capture;
 mod config;
+pub mod contract;
+pub mod cost;
+pub mod credentials;
 pub mod diagnostics;
+pub mod drift;
+pub mod encryption;
+pub mod error;
+mod event_index;
 mod event_logger;
 mod event_loop;
 mod event_parser;
 mod event_reader;
+mod event_watcher;
+mod event_writer;
 pub mod file_lock;
+pub mod forensics;
 mod git_ops;
 mod handoff;
+mod hat_predicate;
 mod hat_registry;
 mod hatless_ralph;
+pub mod human_question;
 mod instructions;
 mod landing;
+pub mod lifecycle;
 pub mod loop_completion;
 pub mod loop_context;
+pub mod loop_control;
 pub mod loop_history;
 pub mod loop_lock;
 mod loop_name;
@@ -35,46 +58,85 @@ mod memory;
 pub mod memory_parser;
 mod memory_store;
 pub mod merge_queue;
+pub mod native_hat;
+mod orchestrator;
+pub mod packs;
+pub mod plan_skeleton;
 pub mod planning_session;
+pub mod plugin;
+pub mod postprocess;
 pub mod preflight;
+pub mod prompt_guard;
+pub mod prompt_pack;
+pub mod protect;
+pub mod repo_lock;
+pub mod repro;
+mod routing;
+pub mod run_manifest;
+pub mod scouts;
+pub mod scratchpad;
+pub mod script;
+pub mod search_index;
+pub mod self_test;
 #[cfg(feature = "recording")]
 mod session_player;
 #[cfg(feature = "recording")]
 mod session_recorder;
+pub mod shell_tool;
 pub mod skill;
+pub mod skill_index;
 pub mod skill_registry;
+pub mod speculative;
+pub mod state_store;
+pub mod state_view;
 mod summary_writer;
+pub mod survey;
 pub mod task;
 pub mod task_definition;
 pub mod task_store;
 pub mod testing;
 mod text;
 pub mod utils;
+pub mod verification;
 pub mod workspace;
 pub mod worktree;
 
 #[cfg(feature = "recording")]
 pub use cli_capture::{CliCapture, CliCapturePair};
 pub use config::{
-    CliConfig, ConfigError, CoreConfig, EventLoopConfig, EventMetadata, FeaturesConfig, HatBackend,
-    HatConfig, InjectMode, MemoriesConfig, MemoriesFilter, RalphConfig, SkillOverride,
-    SkillsConfig,
+    AdaptiveBudgetConfig, ApiConfig, ArbiterKind, AttributionConfig, BlockedConfig, BridgeConfig,
+    BrokerEndpoint, BrokerKind, CarryoverConfig, CheckpointConfig, ChildLoopsConfig, CliConfig,
+    ConfigError, CoreConfig, CredentialSource, DashboardConfig, DurationSpec, EncryptionConfig,
+    EnvironmentConfig, EventFormat, EventLoopConfig, EventMetadata, EventSyntax, FeaturesConfig,
+    ForensicsConfig, GenerationConfig, GpgSign, HatBackend, HatCliConfig, HatConfig, HatWindow,
+    InjectMode, MemoriesConfig, MemoriesFilter, Mode, PluginConfig, PluginKind, Postprocessor,
+    PromptGuardConfig, PromptsConfig, QuestionsConfig, RalphConfig, ReasoningEffort,
+    ResourceLimits, RetryPolicy, RouteRule, ScoutsConfig, ScriptsConfig, SearchIndexConfig,
+    ShellToolConfig, SkillOverride, SkillsConfig, SpeculativeConfig, StartEvent, StateBackend,
+    StateStoreConfig, SurveyApproval, SurveyConfig, VerifyConfig, VerifyPreset,
 };
+pub use cost::{CostEntry, CostLedger, Usage};
 // Re-export loop_name types (also available via FeaturesConfig.loop_naming)
 pub use diagnostics::DiagnosticsCollector;
+pub use error::{CheckpointError, Error, ErrorCode, ExtensionError, JournalError};
+pub use event_index::{EventIndex, EventMatch, EventQuery, JournalSummary};
 pub use event_logger::{EventHistory, EventLogger, EventRecord};
-pub use event_loop::{EventLoop, LoopState, TerminationReason, UserPrompt};
-pub use event_parser::EventParser;
-pub use event_reader::{Event, EventReader, MalformedLine, ParseResult};
+pub use event_loop::{EventLoop, LoopState, LoopStatus, TerminationReason, UserPrompt};
+pub use event_parser::{EventParser, ParseCandidate, ParseTrace};
+pub use event_reader::{Event, EventReader, MalformedLine, ParseResult, archive_paths};
+pub use event_watcher::EventWatcher;
+pub use event_writer::EventWriter;
 pub use file_lock::{FileLock, LockGuard as FileLockGuard, LockedFile};
 pub use git_ops::{
-    AutoCommitResult, GitOpsError, auto_commit_changes, clean_stashes, get_commit_summary,
-    get_current_branch, get_head_sha, get_recent_files, has_uncommitted_changes,
-    is_working_tree_clean, prune_remote_refs,
+    AutoCommitResult, CommitOptions, GitOpsError, auto_commit_changes, auto_commit_changes_with,
+    clean_stashes, get_changed_line_count, get_commit_summary, get_commits_since,
+    get_current_branch, get_head_sha, get_recent_files, git_command, has_uncommitted_changes,
+    is_working_tree_clean, prune_remote_refs, run_git, run_git_bytes,
 };
 pub use handoff::{HandoffError, HandoffResult, HandoffWriter};
+pub use hat_predicate::{HatPredicate, PredicateContext, PredicateError};
 pub use hat_registry::HatRegistry;
-pub use hatless_ralph::{HatInfo, HatTopology, HatlessRalph};
+pub use hatless_ralph::{HatInfo, HatTopology, HatlessRalph, QueuePressure};
 pub use instructions::InstructionBuilder;
 pub use landing::{LandingConfig, LandingError, LandingHandler, LandingResult};
 pub use loop_completion::{CompletionAction, CompletionError, LoopCompletionHandler};
@@ -92,20 +154,31 @@ pub use merge_queue::{
     MergeQueueError, MergeState, SteeringDecision, merge_button_state, merge_execution_summary,
     merge_needs_steering, smart_merge_summary,
 };
+pub use native_hat::{CommandHat, NativeHat, NativeHatError};
+pub use orchestrator::{
+    ExecutionRequest, ExecutionResponse, Executor, Orchestrator, OutputSink, Progress, Step,
+};
 pub use planning_session::{
     ConversationEntry, ConversationType, PlanningSession, PlanningSessionError, SessionMetadata,
     SessionStatus,
 };
+pub use plugin::{PluginError, PluginEvent, PluginHost};
 pub use preflight::{
     AcceptanceCriterion, CheckResult, CheckStatus, PreflightCheck, PreflightReport,
     PreflightRunner, extract_acceptance_criteria, extract_all_criteria, extract_criteria_from_file,
 };
+pub use repo_lock::{RepoLock, RepoLockError, RepoLockGuard, RepoLockOwner};
+pub use routing::{RouteDecision, RoutingPolicy};
+pub use scratchpad::{Scratchpad, ScratchpadSnapshot, WriteOutcome};
+pub use script::{ScriptError, ScriptEvent, ScriptHost, ScriptState};
 #[cfg(feature = "recording")]
 pub use session_player::{PlayerConfig, ReplayMode, SessionPlayer, TimestampedRecord};
 #[cfg(feature = "recording")]
 pub use session_recorder::{Record, SessionRecorder};
 pub use skill::{SkillEntry, SkillFrontmatter, SkillSource, parse_frontmatter};
+pub use skill_index::{IndexEntry, SkillIndex, SkillIndexClient, SkillIndexError, SkillLock};
 pub use skill_registry::SkillRegistry;
+pub use state_view::{LoopStateView, STATE_SCHEMA_VERSION};
 pub use summary_writer::SummaryWriter;
 pub use task::{Task, TaskStatus};
 pub use task_definition::{
@@ -113,6 +186,9 @@ pub use task_definition::{
 };
 pub use task_store::TaskStore;
 pub use text::{floor_char_boundary, truncate_with_ellipsis};
+pub use verification::{
+    FailureCluster, FailureKind, OutputParser, VerificationReport, run_verification, triage,
+};
 pub use workspace::{
     CleanupPolicy, TaskWorkspace, VerificationResult, WorkspaceError, WorkspaceInfo,
     WorkspaceManager,
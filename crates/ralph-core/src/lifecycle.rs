@@ -0,0 +1,118 @@
+//! Orchestrator lifecycle events.
+//!
+//! The event loop publishes these on the bus and the runner appends them to
+//! the events file, so hats, hooks, webhooks, and the TUI all observe the
+//! same stream. Only the loop publishes them: lines with these topics in the
+//! events file are a record, and are never read back as input.
+//!
+//! Payloads are JSON objects. Hats receive a lifecycle event only when they
+//! subscribe to it by name or pattern (`ralph.hat_completed`, `ralph.*`);
+//! the global wildcard `*` doesn't match them.
+
+use serde::{Deserialize, Serialize};
+
+/// Published when a hat's iteration starts.
+pub const ITERATION_STARTED_TOPIC: &str = "ralph.iteration_started";
+
+/// Published when a hat's iteration finishes, before its events are routed.
+pub const HAT_COMPLETED_TOPIC: &str = "ralph.hat_completed";
+
+/// Published when landing commits the loop's work.
+pub const CHECKPOINT_CREATED_TOPIC: &str = "ralph.checkpoint_created";
+
+/// Published when the loop enters a workflow phase (`phases:`).
+pub const PHASE_STARTED_TOPIC: &str = "ralph.phase_started";
+
+/// Published when a hat emits topics missing from its `publishes:` list.
+pub const TOPOLOGY_DRIFT_TOPIC: &str = "topology.drift";
+
+/// Written to the events file before each iteration runs; never published on
+/// the bus. See [`crate::repro`].
+pub const ITERATION_MANIFEST_TOPIC: &str = "ralph.iteration_manifest";
+
+/// Published when the loop starts holding between iterations because a pause
+/// was requested. See [`crate::loop_control`].
+pub const PAUSED_TOPIC: &str = "ralph.paused";
+
+/// Published when a paused loop continues.
+pub const RESUMED_TOPIC: &str = "ralph.resumed";
+
+/// Written to the events file by `ralph amend`; never published on the bus.
+/// See [`crate::amendments`].
+pub const AMENDED_TOPIC: &str = "ralph.amended";
+
+/// Returns true if `topic` is a lifecycle topic.
+pub fn is_lifecycle_topic(topic: &str) -> bool {
+    matches!(
+        topic,
+        ITERATION_STARTED_TOPIC
+            | HAT_COMPLETED_TOPIC
+            | CHECKPOINT_CREATED_TOPIC
+            | PHASE_STARTED_TOPIC
+            | TOPOLOGY_DRIFT_TOPIC
+            | PAUSED_TOPIC
+            | RESUMED_TOPIC
+            | ITERATION_MANIFEST_TOPIC
+            | AMENDED_TOPIC
+    )
+}
+
+/// Payload of `ralph.iteration_started`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IterationStarted {
+    pub iteration: u32,
+    pub hat: String,
+}
+
+/// Payload of `ralph.hat_completed`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HatCompleted {
+    pub iteration: u32,
+    pub hat: String,
+    pub success: bool,
+    pub duration_secs: f64,
+}
+
+/// Payload of `ralph.phase_started`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseStarted {
+    pub phase: String,
+    /// Position of the phase, from 1.
+    pub index: usize,
+    /// Number of configured phases.
+    pub total: usize,
+    pub iteration: u32,
+}
+
+/// Payload of `topology.drift`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopologyDrift {
+    pub iteration: u32,
+    pub hat: String,
+    /// Topics the hat published without declaring them.
+    pub topics: Vec<String>,
+    /// The hat's `publishes:` list.
+    pub declared: Vec<String>,
+}
+
+/// Payload of `ralph.paused`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Paused {
+    /// Iterations completed before the pause.
+    pub iteration: u32,
+}
+
+/// Payload of `ralph.resumed`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Resumed {
+    pub iteration: u32,
+    /// How long the loop was paused.
+    pub paused_secs: f64,
+}
+
+/// Payload of `ralph.checkpoint_created`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointCreated {
+    pub iteration: u32,
+    pub commit: String,
+}
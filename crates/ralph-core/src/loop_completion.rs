@@ -33,8 +33,8 @@
 //! assert!(matches!(action, CompletionAction::Enqueued { .. }));
 //! ```
 
-use crate::git_ops::auto_commit_changes;
-use crate::landing::{LandingHandler, LandingResult};
+use crate::git_ops::{CommitOptions, auto_commit_changes_with};
+use crate::landing::{LandingConfig, LandingHandler, LandingResult};
 use crate::loop_context::LoopContext;
 use crate::merge_queue::{MergeQueue, MergeQueueError};
 use tracing::{debug, info, warn};
@@ -109,6 +109,10 @@ pub enum CompletionError {
 pub struct LoopCompletionHandler {
     /// Whether auto-merge is enabled (default: true).
     auto_merge: bool,
+
+    /// Message prefix, identity, signing, and trailers for the commits made
+    /// on completion.
+    commit: CommitOptions,
 }
 
 impl Default for LoopCompletionHandler {
@@ -125,7 +129,25 @@ impl LoopCompletionHandler {
     /// * `auto_merge` - If true, completed worktree loops are enqueued for merge-ralph.
     ///   If false, worktrees are left for manual merge.
     pub fn new(auto_merge: bool) -> Self {
-        Self { auto_merge }
+        Self {
+            auto_merge,
+            commit: CommitOptions::default(),
+        }
+    }
+
+    /// Appends `trailers` to the commits made on completion.
+    #[must_use]
+    pub fn with_trailers(mut self, trailers: Vec<(String, String)>) -> Self {
+        self.commit.trailers = trailers;
+        self
+    }
+
+    /// Words, attributes, and signs the commits made on completion per
+    /// `options`. Replaces any trailers set before.
+    #[must_use]
+    pub fn with_commit_options(mut self, options: CommitOptions) -> Self {
+        self.commit = options;
+        self
     }
 
     /// Handles loop completion, taking appropriate action based on context.
@@ -177,7 +199,7 @@ impl LoopCompletionHandler {
 
         if self.auto_merge {
             // Auto-commit any uncommitted changes before enqueueing
-            match auto_commit_changes(context.workspace(), &loop_id) {
+            match auto_commit_changes_with(context.workspace(), &loop_id, &self.commit) {
                 Ok(result) => {
                     if result.committed {
                         info!(
@@ -229,7 +251,13 @@ impl LoopCompletionHandler {
     ///
     /// Returns the landing result if successful, or None if landing failed.
     fn execute_landing(&self, context: &LoopContext, prompt: &str) -> Option<LandingResult> {
-        let handler = LandingHandler::new(context.clone());
+        let handler = LandingHandler::with_config(
+            context.clone(),
+            LandingConfig {
+                commit: self.commit.clone(),
+                ..LandingConfig::default()
+            },
+        );
 
         match handler.land(prompt) {
             Ok(result) => {
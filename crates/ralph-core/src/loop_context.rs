@@ -20,6 +20,7 @@
 //! │   ├── memories.md           # Symlinked in worktrees
 //! │   ├── tasks.jsonl           # Isolated per worktree
 //! │   ├── scratchpad.md         # Isolated per worktree
+//! │   ├── amendments.jsonl      # Isolated per worktree
 //! │   └── context.md            # Worktree metadata (worktrees only)
 //! ├── specs/                    # Specification files (symlinked in worktrees)
 //! ├── tasks/                    # Code task files (symlinked in worktrees)
@@ -174,6 +175,23 @@ impl LoopContext {
         self.ralph_dir().join("current-events")
     }
 
+    /// Path to the active run's events file, as named by the marker file.
+    ///
+    /// Relative paths in the marker are resolved against the workspace.
+    /// Returns `None` when no run has written the marker.
+    pub fn active_events_path(&self) -> Option<PathBuf> {
+        std::fs::read_to_string(self.current_events_marker())
+            .ok()
+            .map(|marker| self.workspace.join(marker.trim()))
+    }
+
+    /// Path to the events file that `ralph emit` and friends write to: the
+    /// active run's file, or [`events_path`](Self::events_path) without one.
+    pub fn current_events_path(&self) -> PathBuf {
+        self.active_events_path()
+            .unwrap_or_else(|| self.events_path())
+    }
+
     /// Path to the tasks JSONL file.
     ///
     /// Each loop has its own isolated tasks file.
@@ -188,6 +206,20 @@ impl LoopContext {
         self.agent_dir().join("scratchpad.md")
     }
 
+    /// Path to the pause signal file; the loop holds between iterations while
+    /// it exists. See [`crate::loop_control`].
+    pub fn pause_requested_path(&self) -> PathBuf {
+        self.ralph_dir()
+            .join(crate::loop_control::PAUSE_REQUESTED_FILE)
+    }
+
+    /// Path to the amendments JSONL file (`ralph amend`).
+    ///
+    /// Each loop has its own isolated amendments.
+    pub fn amendments_path(&self) -> PathBuf {
+        self.agent_dir().join("amendments.jsonl")
+    }
+
     /// Path to the memories markdown file.
     ///
     /// For primary loops, this is the actual memories file.
@@ -260,6 +292,13 @@ impl LoopContext {
         self.agent_dir().join("handoff.md")
     }
 
+    /// Path to the directory of per-iteration backend output.
+    ///
+    /// Each run writes `<session>/iter-<n>.out` here while the iteration runs.
+    pub fn sessions_dir(&self) -> PathBuf {
+        self.agent_dir().join("sessions")
+    }
+
     /// Path to the diagnostics directory.
     ///
     /// Each loop has its own diagnostics output.
@@ -267,6 +306,29 @@ impl LoopContext {
         self.ralph_dir().join("diagnostics")
     }
 
+    /// Path to the `ralph-run.json` manifest written when the run ends.
+    pub fn run_manifest_path(&self) -> PathBuf {
+        self.ralph_dir()
+            .join(crate::run_manifest::RUN_MANIFEST_FILE)
+    }
+
+    /// Path to the directory of failure forensics bundles.
+    pub fn forensics_dir(&self) -> PathBuf {
+        self.ralph_dir().join("forensics")
+    }
+
+    /// Path to the public state file written every iteration.
+    ///
+    /// See [`crate::state_view`] for its format.
+    pub fn state_path(&self) -> PathBuf {
+        self.agent_dir().join("state.json")
+    }
+
+    /// Path to the event bus snapshot written during each iteration.
+    pub fn bus_snapshot_path(&self) -> PathBuf {
+        self.ralph_dir().join("bus.json")
+    }
+
     /// Path to the loop history JSONL file.
     ///
     /// Event-sourced history for crash recovery and debugging.
@@ -287,6 +349,26 @@ impl LoopContext {
         self.repo_root.join(".ralph").join("merge-queue.jsonl")
     }
 
+    /// Path to the backend response cache directory.
+    ///
+    /// The cache is shared across all loops (in main repo).
+    pub fn response_cache_dir(&self) -> PathBuf {
+        self.repo_root
+            .join(".ralph")
+            .join("cache")
+            .join("responses")
+    }
+
+    /// Path locked while a hat in mutex group `group` runs.
+    ///
+    /// The lock is shared across all loops (in main repo); [`FileLock`]
+    /// adds the `.lock` extension.
+    ///
+    /// [`FileLock`]: crate::file_lock::FileLock
+    pub fn mutex_group_path(&self, group: &str) -> PathBuf {
+        self.repo_root.join(".ralph").join("mutex").join(group)
+    }
+
     /// Path to the loop registry JSON file.
     ///
     /// The registry is shared across all loops (in main repo).
@@ -721,6 +803,10 @@ mod tests {
             ctx.loop_registry_path(),
             PathBuf::from("/project/.ralph/loops.json")
         );
+        assert_eq!(
+            ctx.response_cache_dir(),
+            PathBuf::from("/project/.ralph/cache/responses")
+        );
     }
 
     #[test]
@@ -791,6 +877,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_current_events_path_follows_marker() {
+        let temp = TempDir::new().unwrap();
+        let ctx = LoopContext::primary(temp.path().to_path_buf());
+        assert_eq!(ctx.active_events_path(), None);
+        assert_eq!(ctx.current_events_path(), ctx.events_path());
+
+        std::fs::create_dir_all(ctx.ralph_dir()).unwrap();
+        std::fs::write(
+            ctx.current_events_marker(),
+            ".ralph/events-20260101-000000.jsonl\n",
+        )
+        .unwrap();
+        assert_eq!(
+            ctx.current_events_path(),
+            temp.path().join(".ralph/events-20260101-000000.jsonl")
+        );
+
+        // Absolute paths are used as they are
+        std::fs::write(ctx.current_events_marker(), "/elsewhere/events.jsonl").unwrap();
+        assert_eq!(
+            ctx.active_events_path(),
+            Some(PathBuf::from("/elsewhere/events.jsonl"))
+        );
+    }
+
     #[test]
     fn test_planning_sessions_paths() {
         let ctx = LoopContext::primary(PathBuf::from("/project"));
@@ -0,0 +1,102 @@
+//! Pausing, resuming, and stopping a running loop.
+//!
+//! A loop holds before its next iteration while `.ralph/pause-requested`
+//! exists; the iteration in flight always finishes. `ralph pause`,
+//! `ralph resume`, and the dashboard create and remove the file, and the
+//! event loop picks the change up between iterations (see
+//! `EventLoop::update_pause`). While paused, the loop's state is in
+//! `LoopState::status` and `.ralph/agent/state.json`, and the transitions are
+//! published as `ralph.paused` and `ralph.resumed`.
+//!
+//! `.ralph/stop-requested` ends the loop at its next termination check
+//! (`EventLoop::check_termination`), which removes the file.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Name of the pause signal file in the loop's `.ralph/` directory.
+pub const PAUSE_REQUESTED_FILE: &str = "pause-requested";
+
+/// Name of the stop signal file in the loop's `.ralph/` directory.
+pub const STOP_REQUESTED_FILE: &str = "stop-requested";
+
+/// Asks the loop to hold after its current iteration.
+///
+/// Returns false if a pause was already requested.
+///
+/// # Errors
+///
+/// Returns an error if the signal file can't be created.
+pub fn request_pause(path: &Path) -> io::Result<bool> {
+    if path.exists() {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, "")?;
+    Ok(true)
+}
+
+/// Lets a paused loop continue.
+///
+/// Returns false if no pause was requested.
+///
+/// # Errors
+///
+/// Returns an error if the signal file can't be removed.
+pub fn request_resume(path: &Path) -> io::Result<bool> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns true while a pause is requested.
+pub fn pause_requested(path: &Path) -> bool {
+    path.exists()
+}
+
+/// Asks the loop to stop at its next termination check.
+///
+/// # Errors
+///
+/// Returns an error if the signal file can't be created.
+pub fn request_stop(path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_and_resume_are_idempotent() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join(".ralph").join(PAUSE_REQUESTED_FILE);
+
+        assert!(!pause_requested(&path));
+        assert!(request_pause(&path).unwrap());
+        assert!(!request_pause(&path).unwrap());
+        assert!(pause_requested(&path));
+
+        assert!(request_resume(&path).unwrap());
+        assert!(!request_resume(&path).unwrap());
+        assert!(!pause_requested(&path));
+    }
+
+    #[test]
+    fn test_request_stop_creates_signal_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join(".ralph").join(STOP_REQUESTED_FILE);
+
+        request_stop(&path).unwrap();
+        request_stop(&path).unwrap();
+        assert!(path.exists());
+    }
+}
@@ -15,7 +15,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::cost::{CostEntry, CostLedger};
 use crate::file_lock::FileLock;
+use crate::routing::RouteDecision;
 
 /// Errors that can occur during history operations.
 #[derive(Debug, Error)]
@@ -64,7 +66,7 @@ impl HistoryEvent {
 }
 
 /// Types of events that can be recorded in loop history.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum HistoryEventType {
     /// Loop started with given prompt.
@@ -79,6 +81,40 @@ pub enum HistoryEventType {
     /// Iteration completed.
     IterationCompleted { iteration: u32, success: bool },
 
+    /// Backend and model chosen for an iteration.
+    ///
+    /// `rule` names the `routing:` rule that matched; `None` means the hat's
+    /// or the global backend was used.
+    IterationRouted {
+        iteration: u32,
+        hat: String,
+        backend: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rule: Option<String>,
+    },
+
+    /// A speculative iteration picked a winner among its candidate backends.
+    SpeculationResolved {
+        iteration: u32,
+        hat: String,
+        candidates: Vec<String>,
+        winner: String,
+        arbiter: String,
+        reason: String,
+    },
+
+    /// Backend spend for an iteration, attributed to a hat and topic.
+    CostRecorded(CostEntry),
+
+    /// A caching hat's prompt was looked up in the response cache.
+    ResponseCacheLookup {
+        iteration: u32,
+        hat: String,
+        hit: bool,
+    },
+
     /// Loop completed successfully.
     LoopCompleted { reason: String },
 
@@ -231,6 +267,16 @@ impl LoopHistory {
         Ok(None)
     }
 
+    /// Rebuilds per-hat and per-topic spend from recorded costs.
+    pub fn cost_ledger(&self) -> Result<CostLedger, HistoryError> {
+        let events = self.read_all()?;
+        let entries = events.iter().filter_map(|event| match &event.event_type {
+            HistoryEventType::CostRecorded(entry) => Some(entry),
+            _ => None,
+        });
+        Ok(CostLedger::from_entries(entries))
+    }
+
     /// Get summary statistics about the loop.
     pub fn summary(&self) -> Result<HistorySummary, HistoryError> {
         let events = self.read_all()?;
@@ -252,6 +298,13 @@ impl LoopHistory {
                 HistoryEventType::EventPublished { .. } => {
                     summary.events_published += 1;
                 }
+                HistoryEventType::ResponseCacheLookup { hit, .. } => {
+                    if *hit {
+                        summary.cache_hits += 1;
+                    } else {
+                        summary.cache_misses += 1;
+                    }
+                }
                 HistoryEventType::LoopCompleted { reason } => {
                     summary.completed = true;
                     summary.completion_reason = Some(reason.clone());
@@ -310,6 +363,64 @@ impl LoopHistory {
         }))
     }
 
+    /// Record which backend and model ran an iteration.
+    pub fn record_route(
+        &self,
+        iteration: u32,
+        hat: &str,
+        backend: &str,
+        route: Option<&RouteDecision>,
+    ) -> Result<(), HistoryError> {
+        self.append(HistoryEvent::new(HistoryEventType::IterationRouted {
+            iteration,
+            hat: hat.to_string(),
+            backend: backend.to_string(),
+            model: route.and_then(|r| r.model.clone()),
+            rule: route.map(|r| r.rule.clone()),
+        }))
+    }
+
+    /// Record which speculative candidate won an iteration.
+    pub fn record_speculation(
+        &self,
+        iteration: u32,
+        hat: &str,
+        candidates: &[String],
+        winner: &str,
+        arbiter: &str,
+        reason: &str,
+    ) -> Result<(), HistoryError> {
+        self.append(HistoryEvent::new(HistoryEventType::SpeculationResolved {
+            iteration,
+            hat: hat.to_string(),
+            candidates: candidates.to_vec(),
+            winner: winner.to_string(),
+            arbiter: arbiter.to_string(),
+            reason: reason.to_string(),
+        }))
+    }
+
+    /// Record iteration cost event.
+    pub fn record_cost(&self, entry: &CostEntry) -> Result<(), HistoryError> {
+        self.append(HistoryEvent::new(HistoryEventType::CostRecorded(
+            entry.clone(),
+        )))
+    }
+
+    /// Record whether a caching hat's prompt was served from the response cache.
+    pub fn record_cache_lookup(
+        &self,
+        iteration: u32,
+        hat: &str,
+        hit: bool,
+    ) -> Result<(), HistoryError> {
+        self.append(HistoryEvent::new(HistoryEventType::ResponseCacheLookup {
+            iteration,
+            hat: hat.to_string(),
+            hit,
+        }))
+    }
+
     /// Record loop completed event.
     pub fn record_completed(&self, reason: &str) -> Result<(), HistoryError> {
         self.append(HistoryEvent::new(HistoryEventType::LoopCompleted {
@@ -317,6 +428,20 @@ impl LoopHistory {
         }))
     }
 
+    /// Record loop completed event with the termination context attached.
+    pub fn record_completed_with_context(
+        &self,
+        reason: &str,
+        context: serde_json::Value,
+    ) -> Result<(), HistoryError> {
+        self.append(HistoryEvent::with_data(
+            HistoryEventType::LoopCompleted {
+                reason: reason.to_string(),
+            },
+            context,
+        ))
+    }
+
     /// Record loop resumed event.
     pub fn record_resumed(&self, from_iteration: u32) -> Result<(), HistoryError> {
         self.append(HistoryEvent::new(HistoryEventType::LoopResumed {
@@ -384,6 +509,12 @@ pub struct HistorySummary {
     /// Number of events published.
     pub events_published: u32,
 
+    /// Iterations served from the response cache.
+    pub cache_hits: u32,
+
+    /// Caching-hat iterations that had to run the backend.
+    pub cache_misses: u32,
+
     /// Whether the loop completed successfully.
     pub completed: bool,
 
@@ -586,4 +717,118 @@ mod tests {
             HistoryEventType::LoopStarted { prompt } if prompt == "test"
         ));
     }
+
+    #[test]
+    fn test_cost_ledger_from_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = LoopHistory::new(temp_dir.path().join("history.jsonl"));
+
+        history.record_iteration_started(1).unwrap();
+        history
+            .record_cost(&CostEntry {
+                iteration: 1,
+                hat: "builder".to_string(),
+                topic: Some("build.task".to_string()),
+                usage: crate::cost::Usage::new(0.4, 1000, 200),
+            })
+            .unwrap();
+        history
+            .record_cost(&CostEntry {
+                iteration: 2,
+                hat: "reviewer".to_string(),
+                topic: Some("review.request".to_string()),
+                usage: crate::cost::Usage::new(0.1, 300, 50),
+            })
+            .unwrap();
+
+        let ledger = history.cost_ledger().unwrap();
+        assert!((ledger.total.cost_usd - 0.5).abs() < 1e-9);
+        assert_eq!(ledger.hat("builder").input_tokens, 1000);
+        assert!((ledger.by_topic["review.request"].cost_usd - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_record_route() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = LoopHistory::new(temp_dir.path().join("history.jsonl"));
+
+        let route = RouteDecision {
+            rule: "cheap-planning".to_string(),
+            backend: None,
+            model: Some("haiku".to_string()),
+        };
+        history
+            .record_route(1, "planner", "claude", Some(&route))
+            .unwrap();
+        history.record_route(2, "builder", "claude", None).unwrap();
+
+        let events = history.read_all().unwrap();
+        assert_eq!(
+            events[0].event_type,
+            HistoryEventType::IterationRouted {
+                iteration: 1,
+                hat: "planner".to_string(),
+                backend: "claude".to_string(),
+                model: Some("haiku".to_string()),
+                rule: Some("cheap-planning".to_string()),
+            }
+        );
+        let raw = std::fs::read_to_string(history.path()).unwrap();
+        let second = raw.lines().nth(1).unwrap();
+        assert!(second.contains(r#""kind":"iteration_routed""#));
+        assert!(!second.contains("model"));
+    }
+
+    #[test]
+    fn test_record_speculation() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = LoopHistory::new(temp_dir.path().join("history.jsonl"));
+
+        history
+            .record_speculation(
+                3,
+                "builder",
+                &["claude".to_string(), "gemini".to_string()],
+                "gemini",
+                "check",
+                "check passed",
+            )
+            .unwrap();
+
+        let events = history.read_all().unwrap();
+        assert_eq!(
+            events[0].event_type,
+            HistoryEventType::SpeculationResolved {
+                iteration: 3,
+                hat: "builder".to_string(),
+                candidates: vec!["claude".to_string(), "gemini".to_string()],
+                winner: "gemini".to_string(),
+                arbiter: "check".to_string(),
+                reason: "check passed".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_cache_lookups_counted_in_summary() {
+        let (_dir, history) = temp_history();
+
+        history.record_cache_lookup(1, "planner", false).unwrap();
+        history.record_cache_lookup(2, "planner", true).unwrap();
+        history.record_cache_lookup(3, "planner", true).unwrap();
+
+        let events = history.read_all().unwrap();
+        assert_eq!(
+            events[1].event_type,
+            HistoryEventType::ResponseCacheLookup {
+                iteration: 2,
+                hat: "planner".to_string(),
+                hit: true,
+            }
+        );
+
+        let summary = history.summary().unwrap();
+        assert_eq!(summary.cache_hits, 2);
+        assert_eq!(summary.cache_misses, 1);
+    }
 }
@@ -0,0 +1,174 @@
+//! Hats implemented in Rust instead of by an agent backend.
+//!
+//! A [`NativeHat`] is registered in the [`HatRegistry`](crate::HatRegistry)
+//! alongside the LLM hats and subscribes to topics the same way. Its pending
+//! events are handed to [`NativeHat::handle`] right after they are published,
+//! and the events it returns are published in turn, all without a backend
+//! call. Use it for deterministic steps: running a formatter, applying a
+//! patch, updating a changelog.
+//!
+//! Embedders register handlers with
+//! [`EventLoop::register_native_hat`](crate::EventLoop::register_native_hat).
+//! Hats configured with `command:` get the built-in [`CommandHat`].
+
+use crate::text::floor_char_boundary;
+use ralph_proto::{Event, HatId};
+use std::path::PathBuf;
+use std::process::Command;
+use thiserror::Error;
+
+/// Largest slice of command output carried in a published payload.
+const MAX_PAYLOAD_BYTES: usize = 4_000;
+
+/// Errors a native hat can report.
+///
+/// A failing hat's events are routed to Ralph so the work is not lost.
+#[derive(Debug, Error)]
+pub enum NativeHatError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// A hat handled in-process by Rust code.
+pub trait NativeHat: Send + Sync {
+    /// Handles the hat's pending events and returns the events to publish.
+    fn handle(&mut self, hat_id: &HatId, events: &[Event]) -> Result<Vec<Event>, NativeHatError>;
+}
+
+/// Runs a shell command for each batch of events (`command:` on a hat).
+///
+/// The triggering topics and payloads are passed in `RALPH_EVENT_TOPICS` and
+/// `RALPH_EVENT_PAYLOAD`. On success the hat publishes `success_topic` with
+/// the command's output; on a non-zero exit it publishes `<hat>.failed`.
+#[derive(Debug, Clone)]
+pub struct CommandHat {
+    command: String,
+    workspace: PathBuf,
+    success_topic: Option<String>,
+}
+
+impl CommandHat {
+    /// Creates a hat that runs `command` in `workspace`.
+    pub fn new(command: impl Into<String>, workspace: impl Into<PathBuf>) -> Self {
+        Self {
+            command: command.into(),
+            workspace: workspace.into(),
+            success_topic: None,
+        }
+    }
+
+    /// Topic published when the command succeeds (defaults to `<hat>.done`).
+    #[must_use]
+    pub fn with_success_topic(mut self, topic: impl Into<String>) -> Self {
+        self.success_topic = Some(topic.into());
+        self
+    }
+
+    fn shell(&self) -> Command {
+        #[cfg(windows)]
+        let mut command = {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(&self.command);
+            command
+        };
+        #[cfg(not(windows))]
+        let mut command = {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(&self.command);
+            command
+        };
+        command.current_dir(&self.workspace);
+        command
+    }
+}
+
+impl NativeHat for CommandHat {
+    fn handle(&mut self, hat_id: &HatId, events: &[Event]) -> Result<Vec<Event>, NativeHatError> {
+        let topics: Vec<&str> = events.iter().map(|e| e.topic.as_str()).collect();
+        let payload: Vec<&str> = events.iter().map(|e| e.payload.as_str()).collect();
+        let output = crate::utils::run_blocking(|| {
+            self.shell()
+                .env("RALPH_EVENT_TOPICS", topics.join(" "))
+                .env("RALPH_EVENT_PAYLOAD", payload.join("\n\n"))
+                .output()
+        })?;
+
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        let text = tail(text.trim());
+
+        let topic = if output.status.success() {
+            self.success_topic
+                .clone()
+                .unwrap_or_else(|| format!("{hat_id}.done"))
+        } else {
+            format!("{hat_id}.failed")
+        };
+        Ok(vec![Event::new(topic, text)])
+    }
+}
+
+/// Keeps the last `MAX_PAYLOAD_BYTES` of `text`.
+fn tail(text: &str) -> String {
+    if text.len() <= MAX_PAYLOAD_BYTES {
+        return text.to_string();
+    }
+    let start = floor_char_boundary(text, text.len() - MAX_PAYLOAD_BYTES);
+    format!("...{}", &text[start..])
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_command_hat_publishes_output_on_success() {
+        let dir = TempDir::new().unwrap();
+        let mut hat = CommandHat::new("echo \"formatted $RALPH_EVENT_TOPICS\"", dir.path())
+            .with_success_topic("fmt.done");
+
+        let published = hat
+            .handle(
+                &HatId::new("formatter"),
+                &[Event::new("build.done", "src/lib.rs")],
+            )
+            .unwrap();
+
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].topic.as_str(), "fmt.done");
+        assert_eq!(published[0].payload, "formatted build.done");
+    }
+
+    #[test]
+    fn test_command_hat_publishes_failed_on_nonzero_exit() {
+        let dir = TempDir::new().unwrap();
+        let mut hat = CommandHat::new("echo broken >&2; exit 3", dir.path());
+
+        let published = hat
+            .handle(&HatId::new("formatter"), &[Event::new("build.done", "")])
+            .unwrap();
+
+        assert_eq!(published[0].topic.as_str(), "formatter.failed");
+        assert_eq!(published[0].payload, "broken");
+    }
+
+    #[test]
+    fn test_command_hat_receives_payloads() {
+        let dir = TempDir::new().unwrap();
+        let mut hat = CommandHat::new("printf '%s' \"$RALPH_EVENT_PAYLOAD\"", dir.path());
+
+        let published = hat
+            .handle(
+                &HatId::new("changelog"),
+                &[Event::new("a", "first"), Event::new("b", "second")],
+            )
+            .unwrap();
+
+        assert_eq!(published[0].topic.as_str(), "changelog.done");
+        assert_eq!(published[0].payload, "first\n\nsecond");
+    }
+}
@@ -0,0 +1,693 @@
+//! Embeddable orchestration loop.
+//!
+//! [`Orchestrator`] drives an [`EventLoop`] with a pluggable [`Executor`], so
+//! other Rust programs can run Ralph without shelling out to the binary:
+//!
+//! ```ignore
+//! let mut orchestrator = Orchestrator::new(config, my_executor);
+//! let mut progress = orchestrator.subscribe();
+//! orchestrator.start("Build a todo app")?;
+//! let reason = orchestrator.run().await?;
+//! ```
+//!
+//! Each [`step`](Orchestrator::step) is one iteration: pick the next hat,
+//! build its prompt, execute it, and fold the output and any events written
+//! to `events.jsonl` back into the loop. Termination checks, fallback
+//! recovery, `verify:` commands, child loops, and `default_publishes` follow
+//! the same rules as `ralph run`.
+//! Terminal UI, PTY handling, and merge-queue bookkeeping stay in the CLI.
+
+use crate::config::{EnvironmentConfig, HatBackend, HatCliConfig, RalphConfig};
+use crate::cost::Usage;
+use crate::error::Error;
+use crate::event_loop::{EventLoop, TerminationReason};
+use crate::lifecycle::{CHECKPOINT_CREATED_TOPIC, CheckpointCreated};
+use crate::loop_context::LoopContext;
+use async_trait::async_trait;
+use ralph_proto::{Event, HatId};
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Capacity of the progress channel; slow subscribers skip ahead when lagging.
+const PROGRESS_CHANNEL_CAPACITY: usize = 256;
+
+/// Fallback events injected in a row before the loop gives up.
+const MAX_FALLBACK_ATTEMPTS: u32 = 3;
+
+/// One iteration's work, handed to the [`Executor`].
+#[derive(Debug, Clone)]
+pub struct ExecutionRequest<'a> {
+    /// Iteration number (1-indexed).
+    pub iteration: u32,
+    /// Hat the prompt was built for (`ralph` in multi-hat mode).
+    pub hat_id: &'a HatId,
+    /// Hat doing the work, used for backend and environment selection.
+    pub active_hat_id: &'a HatId,
+    /// The full prompt.
+    pub prompt: &'a str,
+    /// Backend override from a routing rule or the hat, if any.
+    pub backend: Option<&'a HatBackend>,
+    /// The hat's `cli:` block, if it has one and no routing rule picked a backend.
+    pub cli: Option<&'a HatCliConfig>,
+    /// Model chosen by a routing rule, if any.
+    pub model: Option<&'a str>,
+    /// Container environment for this hat, if any.
+    pub environment: Option<&'a EnvironmentConfig>,
+    /// Loop configuration (adapter timeouts, resource limits, ...).
+    pub config: &'a RalphConfig,
+    /// Where to stream output as it arrives, for progress subscribers.
+    pub output: OutputSink,
+}
+
+/// Streams an iteration's output to [`Progress::OutputChunk`] subscribers.
+///
+/// Executors that can stream should [`send`](Self::send) text (or write bytes
+/// through its [`io::Write`] impl) as it arrives. When an executor sends
+/// nothing, the orchestrator publishes the whole output as one chunk once the
+/// iteration finishes.
+#[derive(Debug, Clone)]
+pub struct OutputSink {
+    iteration: u32,
+    hat: HatId,
+    sender: broadcast::Sender<Progress>,
+    streamed: Arc<AtomicBool>,
+    /// Bytes of a UTF-8 character split across writes.
+    partial: Vec<u8>,
+}
+
+impl OutputSink {
+    fn new(iteration: u32, hat: HatId, sender: broadcast::Sender<Progress>) -> Self {
+        Self {
+            iteration,
+            hat,
+            sender,
+            streamed: Arc::new(AtomicBool::new(false)),
+            partial: Vec::new(),
+        }
+    }
+
+    /// Publishes a chunk of output.
+    pub fn send(&self, text: impl Into<String>) {
+        let text = text.into();
+        if text.is_empty() {
+            return;
+        }
+        self.streamed.store(true, Ordering::Relaxed);
+        // No subscribers is fine.
+        let _ = self.sender.send(Progress::OutputChunk {
+            iteration: self.iteration,
+            hat: self.hat.clone(),
+            text,
+        });
+    }
+
+    /// Returns true once any chunk has been sent through this sink or a clone.
+    pub fn streamed(&self) -> bool {
+        self.streamed.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for OutputSink {
+    /// A sink nobody listens to, for calling an executor directly.
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(1);
+        Self::new(0, HatId::new("ralph"), sender)
+    }
+}
+
+impl io::Write for OutputSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.partial.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.partial) {
+            Ok(_) => self.partial.len(),
+            // Hold back an incomplete character for the next write
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.partial.len(),
+        };
+        let bytes: Vec<u8> = self.partial.drain(..valid).collect();
+        self.send(String::from_utf8_lossy(&bytes));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// What an [`Executor`] returns for one iteration.
+#[derive(Debug, Clone)]
+pub struct ExecutionResponse {
+    /// Agent output, scanned for events and the completion promise.
+    pub output: String,
+    /// Whether the backend reported success.
+    pub success: bool,
+    /// Set to stop the loop immediately (e.g. the user interrupted the backend).
+    pub termination: Option<TerminationReason>,
+    /// Cost and tokens reported by the backend, when it reports them.
+    pub usage: Option<Usage>,
+}
+
+impl ExecutionResponse {
+    /// Creates a response that lets the loop continue.
+    pub fn new(output: impl Into<String>, success: bool) -> Self {
+        Self {
+            output: output.into(),
+            success,
+            termination: None,
+            usage: None,
+        }
+    }
+
+    /// Attaches the backend's usage report, recorded against the iteration's hat.
+    #[must_use]
+    pub fn with_usage(mut self, usage: Usage) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+}
+
+/// Runs prompts against an agent backend.
+///
+/// `ralph-adapters` provides an implementation backed by the CLI backends;
+/// embedders can supply their own (an HTTP API, a mock for tests, ...).
+#[async_trait]
+pub trait Executor: Send {
+    /// Executes one iteration's prompt.
+    async fn execute(&mut self, request: ExecutionRequest<'_>) -> Result<ExecutionResponse, Error>;
+}
+
+/// Progress notifications published by an [`Orchestrator`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Progress {
+    /// An event was published on the bus.
+    EventPublished(Event),
+    /// An iteration is about to execute.
+    IterationStarted { iteration: u32, hat: HatId },
+    /// Output from the running iteration, in arrival order.
+    OutputChunk {
+        iteration: u32,
+        hat: HatId,
+        text: String,
+    },
+    /// An iteration finished executing.
+    IterationFinished {
+        iteration: u32,
+        hat: HatId,
+        success: bool,
+        duration: Duration,
+    },
+    /// The loop's work was committed (`ralph.checkpoint_created`).
+    CheckpointCreated { iteration: u32, commit: String },
+    /// The loop is holding because a pause was requested.
+    Paused,
+    /// The loop stopped.
+    Terminated(TerminationReason),
+}
+
+/// Result of a single [`Orchestrator::step`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// The iteration completed (or recovery was scheduled); call `step` again.
+    Continue,
+    /// A pause is requested; no iteration ran.
+    Paused,
+    /// The loop is over.
+    Terminated(TerminationReason),
+}
+
+/// An embeddable orchestration loop.
+pub struct Orchestrator<E> {
+    event_loop: EventLoop,
+    executor: E,
+    progress: broadcast::Sender<Progress>,
+    consecutive_fallbacks: u32,
+    started: bool,
+    termination: Option<TerminationReason>,
+}
+
+impl<E: Executor> Orchestrator<E> {
+    /// Creates an orchestrator for the primary workspace in `config.core.workspace_root`.
+    pub fn new(config: RalphConfig, executor: E) -> Self {
+        let context = LoopContext::primary(config.core.workspace_root.clone());
+        Self::with_context(config, context, executor)
+    }
+
+    /// Creates an orchestrator for a specific loop context (e.g. a worktree loop).
+    pub fn with_context(config: RalphConfig, context: LoopContext, executor: E) -> Self {
+        let mut event_loop = EventLoop::with_context(config, context);
+        let (progress, _) = broadcast::channel(PROGRESS_CHANNEL_CAPACITY);
+
+        let sender = progress.clone();
+        event_loop.add_observer(move |event: &Event| {
+            // No subscribers is fine.
+            let _ = sender.send(Progress::EventPublished(event.clone()));
+            if event.topic.as_str() == CHECKPOINT_CREATED_TOPIC
+                && let Ok(checkpoint) = serde_json::from_str::<CheckpointCreated>(&event.payload)
+            {
+                let _ = sender.send(Progress::CheckpointCreated {
+                    iteration: checkpoint.iteration,
+                    commit: checkpoint.commit,
+                });
+            }
+        });
+
+        Self {
+            event_loop,
+            executor,
+            progress,
+            consecutive_fallbacks: 0,
+            started: false,
+            termination: None,
+        }
+    }
+
+    /// Subscribes to progress notifications.
+    ///
+    /// Subscribe before [`start`](Self::start) to see the initial event.
+    pub fn subscribe(&self) -> broadcast::Receiver<Progress> {
+        self.progress.subscribe()
+    }
+
+    /// Returns the underlying event loop.
+    pub fn event_loop(&self) -> &EventLoop {
+        &self.event_loop
+    }
+
+    /// Returns the underlying event loop mutably, e.g. to add observers.
+    pub fn event_loop_mut(&mut self) -> &mut EventLoop {
+        &mut self.event_loop
+    }
+
+    /// Returns the executor.
+    pub fn executor(&self) -> &E {
+        &self.executor
+    }
+
+    /// Returns why the loop stopped, if it has.
+    pub fn termination(&self) -> Option<&TerminationReason> {
+        self.termination.as_ref()
+    }
+
+    /// Starts a fresh loop by publishing the configured starting event with `prompt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the loop was already started, or a configured plugin
+    /// or script couldn't be loaded.
+    pub fn start(&mut self, prompt: &str) -> Result<(), Error> {
+        self.ensure_not_started()?;
+        self.event_loop.ensure_extensions_loaded()?;
+        self.event_loop.initialize(prompt);
+        self.started = true;
+        Ok(())
+    }
+
+    /// Resumes a loop from existing state by publishing `task.resume` with `prompt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the loop was already started, or a configured plugin
+    /// or script couldn't be loaded.
+    pub fn resume(&mut self, prompt: &str) -> Result<(), Error> {
+        self.ensure_not_started()?;
+        self.event_loop.ensure_extensions_loaded()?;
+        self.event_loop.initialize_resume(prompt);
+        self.started = true;
+        Ok(())
+    }
+
+    fn ensure_not_started(&self) -> Result<(), Error> {
+        if self.started {
+            return Err(Error::Lifecycle("already started".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Runs one iteration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the loop hasn't been started or the executor fails.
+    /// The loop is not terminated by an executor error; the caller decides
+    /// whether to retry or [`shutdown`](Self::shutdown).
+    pub async fn step(&mut self) -> Result<Step, Error> {
+        if let Some(reason) = &self.termination {
+            return Ok(Step::Terminated(reason.clone()));
+        }
+        if !self.started {
+            return Err(Error::Lifecycle(
+                "not started; call start() or resume() first".to_string(),
+            ));
+        }
+
+        if let Some(reason) = self.event_loop.check_termination() {
+            return Ok(self.terminate(reason));
+        }
+
+        if self.event_loop.update_pause() {
+            let _ = self.progress.send(Progress::Paused);
+            return Ok(Step::Paused);
+        }
+
+        let Some(hat_id) = self.event_loop.next_hat().cloned() else {
+            return Ok(self.recover());
+        };
+        self.consecutive_fallbacks = 0;
+
+        let iteration = self.event_loop.state().iteration + 1;
+        let active_hat_id = if hat_id.as_str() == "ralph" {
+            self.event_loop.get_active_hat_id()
+        } else {
+            hat_id.clone()
+        };
+
+        let Some(prompt) = self.event_loop.build_prompt(&hat_id) else {
+            warn!(hat = %hat_id, "Failed to build prompt");
+            return Ok(Step::Continue);
+        };
+
+        let _ = self.progress.send(Progress::IterationStarted {
+            iteration,
+            hat: active_hat_id.clone(),
+        });
+        let started = Instant::now();
+
+        let route = self.event_loop.route(&active_hat_id);
+        let output = OutputSink::new(iteration, active_hat_id.clone(), self.progress.clone());
+        let request = ExecutionRequest {
+            iteration,
+            hat_id: &hat_id,
+            active_hat_id: &active_hat_id,
+            prompt: &prompt,
+            backend: route
+                .as_ref()
+                .and_then(|route| route.backend.as_ref())
+                .or_else(|| self.event_loop.get_hat_backend(&active_hat_id)),
+            cli: self
+                .event_loop
+                .get_hat_cli(&active_hat_id)
+                .filter(|_| route.as_ref().is_none_or(|route| route.backend.is_none())),
+            model: route.as_ref().and_then(|route| route.model.as_deref()),
+            environment: self.event_loop.get_hat_environment(&active_hat_id),
+            config: self.event_loop.config(),
+            output: output.clone(),
+        };
+        let response = self.executor.execute(request).await?;
+        if !output.streamed() {
+            output.send(response.output.as_str());
+        }
+
+        let _ = self.progress.send(Progress::IterationFinished {
+            iteration,
+            hat: active_hat_id.clone(),
+            success: response.success,
+            duration: started.elapsed(),
+        });
+
+        if let Some(reason) = response.termination {
+            return Ok(self.terminate(reason));
+        }
+
+        if let Some(usage) = response.usage {
+            self.event_loop.record_usage(&hat_id, usage);
+        }
+
+        if let Some(reason) = self
+            .event_loop
+            .process_output(&hat_id, &response.output, response.success)
+            .await
+        {
+            return Ok(self.terminate(reason));
+        }
+
+        self.event_loop.verify_iteration(&active_hat_id).await;
+
+        let agent_wrote_events = matches!(
+            self.event_loop
+                .process_events_from_jsonl_async()
+                .await
+                .inspect_err(|e| warn!(error = %e, "Failed to read events from JSONL")),
+            Ok(true)
+        );
+
+        // Embedders run child loops with the `ralph` found on PATH.
+        self.event_loop
+            .run_child_loops(std::path::Path::new("ralph"))
+            .await;
+
+        // Inject default_publishes for active hats only when the agent wrote no events.
+        if !agent_wrote_events {
+            let active_hats = self.event_loop.state().last_active_hat_ids.clone();
+            for active_hat_id in &active_hats {
+                self.event_loop.check_default_publishes(active_hat_id);
+                if self.event_loop.has_pending_events() {
+                    break;
+                }
+            }
+        }
+
+        if let Some(reason) = self.event_loop.check_completion_event() {
+            return Ok(self.terminate(reason));
+        }
+
+        Ok(Step::Continue)
+    }
+
+    /// Runs iterations until the loop terminates.
+    ///
+    /// Honors `event_loop.cooldown_delay_seconds` between iterations and
+    /// polls once a second while paused.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first executor error.
+    pub async fn run(&mut self) -> Result<TerminationReason, Error> {
+        loop {
+            match self.step().await? {
+                Step::Terminated(reason) => return Ok(reason),
+                Step::Paused => tokio::time::sleep(Duration::from_secs(1)).await,
+                Step::Continue => {
+                    let cooldown = self.event_loop.config().event_loop.cooldown_delay_seconds;
+                    if cooldown > 0 && !self.event_loop.has_pending_human_events() {
+                        tokio::time::sleep(Duration::from_secs(cooldown)).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stops the loop, publishing `loop.terminate` with `reason`.
+    ///
+    /// Returns the reason the loop ended; if it had already terminated, that
+    /// earlier reason is returned and nothing is published.
+    pub fn shutdown(&mut self, reason: TerminationReason) -> TerminationReason {
+        if let Some(existing) = &self.termination {
+            return existing.clone();
+        }
+        self.terminate(reason);
+        self.termination
+            .clone()
+            .unwrap_or(TerminationReason::Stopped)
+    }
+
+    /// Handles "no hat has pending events" by injecting a fallback event.
+    fn recover(&mut self) -> Step {
+        self.consecutive_fallbacks += 1;
+        if self.consecutive_fallbacks > MAX_FALLBACK_ATTEMPTS {
+            warn!(
+                attempts = self.consecutive_fallbacks,
+                "Fallback recovery exhausted, terminating"
+            );
+            return self.terminate(TerminationReason::Stopped);
+        }
+        if self.event_loop.inject_fallback_event() {
+            debug!("Injected fallback event");
+            return Step::Continue;
+        }
+        warn!("No hats with pending events and fallback not available, terminating");
+        self.terminate(TerminationReason::Stopped)
+    }
+
+    fn terminate(&mut self, reason: TerminationReason) -> Step {
+        info!(reason = reason.as_str(), "Loop terminated");
+        self.event_loop.publish_terminate_event(&reason);
+        self.termination = Some(reason.clone());
+        let _ = self.progress.send(Progress::Terminated(reason.clone()));
+        Step::Terminated(reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_writer::EventWriter;
+    use tempfile::TempDir;
+
+    /// Records prompts and emits the completion event on a chosen iteration.
+    struct ScriptedExecutor {
+        complete_on: u32,
+        prompts: Vec<String>,
+    }
+
+    #[async_trait]
+    impl Executor for ScriptedExecutor {
+        async fn execute(
+            &mut self,
+            request: ExecutionRequest<'_>,
+        ) -> Result<ExecutionResponse, Error> {
+            self.prompts.push(request.prompt.to_string());
+            if request.iteration == self.complete_on {
+                let events_path = request
+                    .config
+                    .core
+                    .workspace_root
+                    .join(".ralph/events.jsonl");
+                EventWriter::new(events_path).append(&serde_json::json!({
+                    "topic": "LOOP_COMPLETE",
+                    "payload": "done",
+                    "ts": "2024-01-01T00:00:00Z",
+                }))?;
+            }
+            Ok(ExecutionResponse::new("working on it", true)
+                .with_usage(Usage::new(0.01, 1000, 200)))
+        }
+    }
+
+    fn config(tmp: &TempDir) -> RalphConfig {
+        let mut config = RalphConfig::default();
+        config.core.workspace_root = tmp.path().to_path_buf();
+        config.event_loop.max_iterations = 5;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_run_until_completion_promise() {
+        let tmp = TempDir::new().unwrap();
+        let executor = ScriptedExecutor {
+            complete_on: 2,
+            prompts: Vec::new(),
+        };
+        let mut orchestrator = Orchestrator::new(config(&tmp), executor);
+        let mut progress = orchestrator.subscribe();
+
+        orchestrator.start("Write a haiku").unwrap();
+        let reason = orchestrator.run().await.unwrap();
+
+        assert_eq!(reason, TerminationReason::CompletionPromise);
+        assert_eq!(orchestrator.executor().prompts.len(), 2);
+        assert!(orchestrator.executor().prompts[0].contains("Write a haiku"));
+        let state = orchestrator.event_loop().state();
+        assert!((state.cumulative_cost - 0.02).abs() < 1e-9);
+        assert_eq!(state.cost_ledger.total.output_tokens, 400);
+
+        let mut saw_iteration = false;
+        let mut saw_terminated = false;
+        while let Ok(update) = progress.try_recv() {
+            match update {
+                Progress::IterationStarted { .. } => saw_iteration = true,
+                Progress::Terminated(r) => saw_terminated = r == reason,
+                _ => {}
+            }
+        }
+        assert!(saw_iteration && saw_terminated);
+    }
+
+    /// Streams its output in two writes, splitting a multi-byte character.
+    struct StreamingExecutor;
+
+    #[async_trait]
+    impl Executor for StreamingExecutor {
+        async fn execute(
+            &mut self,
+            mut request: ExecutionRequest<'_>,
+        ) -> Result<ExecutionResponse, Error> {
+            use std::io::Write;
+            let bytes = "caf\u{e9} ok".as_bytes();
+            request.output.write_all(&bytes[..4])?;
+            request.output.write_all(&bytes[4..])?;
+            Ok(ExecutionResponse::new("caf\u{e9} ok", true))
+        }
+    }
+
+    fn chunks(progress: &mut broadcast::Receiver<Progress>) -> Vec<String> {
+        let mut chunks = Vec::new();
+        while let Ok(update) = progress.try_recv() {
+            if let Progress::OutputChunk { text, .. } = update {
+                chunks.push(text);
+            }
+        }
+        chunks
+    }
+
+    #[tokio::test]
+    async fn test_output_chunks_streamed_or_sent_whole() {
+        let tmp = TempDir::new().unwrap();
+        let mut orchestrator = Orchestrator::new(config(&tmp), StreamingExecutor);
+        let mut progress = orchestrator.subscribe();
+        orchestrator.start("Task").unwrap();
+        orchestrator.step().await.unwrap();
+        assert_eq!(chunks(&mut progress), vec!["caf", "\u{e9} ok"]);
+
+        // An executor that doesn't stream gets its output sent in one chunk
+        let executor = ScriptedExecutor {
+            complete_on: 0,
+            prompts: Vec::new(),
+        };
+        let mut orchestrator = Orchestrator::new(config(&tmp), executor);
+        let mut progress = orchestrator.subscribe();
+        orchestrator.start("Task").unwrap();
+        orchestrator.step().await.unwrap();
+        assert_eq!(chunks(&mut progress), vec!["working on it"]);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_progress() {
+        let tmp = TempDir::new().unwrap();
+        let executor = ScriptedExecutor {
+            complete_on: 0,
+            prompts: Vec::new(),
+        };
+        let mut orchestrator = Orchestrator::new(config(&tmp), executor);
+        let mut progress = orchestrator.subscribe();
+
+        orchestrator
+            .event_loop_mut()
+            .publish_checkpoint_created("abc123");
+
+        let mut checkpoint = None;
+        while let Ok(update) = progress.try_recv() {
+            if let Progress::CheckpointCreated { commit, .. } = update {
+                checkpoint = Some(commit);
+            }
+        }
+        assert_eq!(checkpoint.as_deref(), Some("abc123"));
+    }
+
+    #[tokio::test]
+    async fn test_step_requires_start_and_stops_after_termination() {
+        let tmp = TempDir::new().unwrap();
+        let executor = ScriptedExecutor {
+            complete_on: 1,
+            prompts: Vec::new(),
+        };
+        let mut orchestrator = Orchestrator::new(config(&tmp), executor);
+
+        assert!(orchestrator.step().await.is_err());
+
+        orchestrator.start("Task").unwrap();
+        assert!(orchestrator.start("Again").is_err());
+
+        let reason = orchestrator.shutdown(TerminationReason::Interrupted);
+        assert_eq!(reason, TerminationReason::Interrupted);
+        assert_eq!(
+            orchestrator.step().await.unwrap(),
+            Step::Terminated(TerminationReason::Interrupted)
+        );
+        assert!(orchestrator.executor().prompts.is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+//! Built-in hat packs.
+//!
+//! A pack bundles hats, event metadata, and skills for a recurring job, and is
+//! switched on with `packs: [<name>]` in `ralph.yml`. [`RalphConfig::normalize`]
+//! merges a pack's hats and events into the config; hats and events the config
+//! defines itself win. The skill registry picks up the pack's skills, which
+//! user skills with the same name replace.
+//!
+//! [`RalphConfig::normalize`]: crate::RalphConfig::normalize
+
+use crate::config::{EventMetadata, HatConfig};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A pack compiled into the binary.
+#[derive(Debug, Clone, Copy)]
+pub struct Pack {
+    /// Name used in `packs:`.
+    pub name: &'static str,
+    /// One-line summary.
+    pub description: &'static str,
+    /// YAML with `hats:` and `events:` sections.
+    config: &'static str,
+    /// Skill files (with frontmatter) keyed by fallback name.
+    skills: &'static [(&'static str, &'static str)],
+}
+
+/// Hats and events a pack adds to the config.
+#[derive(Debug, Default, Deserialize)]
+pub struct PackContents {
+    #[serde(default)]
+    pub hats: HashMap<String, HatConfig>,
+    #[serde(default)]
+    pub events: HashMap<String, EventMetadata>,
+}
+
+/// Every built-in pack.
+pub const PACKS: &[Pack] = &[Pack {
+    name: "maintenance",
+    description: "Dependency audits and bumps, with cargo audit / npm audit findings as events and changelog updates",
+    config: include_str!("../data/packs/maintenance/pack.yml"),
+    skills: &[
+        (
+            "security-audit",
+            include_str!("../data/packs/maintenance/security-audit.md"),
+        ),
+        (
+            "dependency-updates",
+            include_str!("../data/packs/maintenance/dependency-updates.md"),
+        ),
+        (
+            "changelog",
+            include_str!("../data/packs/maintenance/changelog.md"),
+        ),
+    ],
+}];
+
+/// Looks up a built-in pack by name.
+pub fn find(name: &str) -> Option<&'static Pack> {
+    PACKS.iter().find(|pack| pack.name == name)
+}
+
+/// Names of every built-in pack, for error messages.
+pub fn names() -> Vec<&'static str> {
+    PACKS.iter().map(|pack| pack.name).collect()
+}
+
+impl Pack {
+    /// Parses the pack's hats and events.
+    ///
+    /// Pack YAML is compiled in and checked by tests, so this only fails on a
+    /// broken build.
+    pub fn contents(&self) -> PackContents {
+        serde_yaml::from_str(self.config)
+            .unwrap_or_else(|e| panic!("built-in pack '{}' is invalid: {e}", self.name))
+    }
+
+    /// The pack's skill files as `(fallback name, raw content)` pairs.
+    pub fn skills(&self) -> &'static [(&'static str, &'static str)] {
+        self.skills
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RalphConfig;
+    use crate::skill_registry::SkillRegistry;
+    use std::path::Path;
+
+    #[test]
+    fn test_builtin_packs_validate() {
+        for pack in PACKS {
+            let mut config = RalphConfig::parse_yaml(&format!("packs: [{}]\n", pack.name)).unwrap();
+            config.normalize();
+            assert_eq!(
+                config.hats.len(),
+                pack.contents().hats.len(),
+                "{}",
+                pack.name
+            );
+            config
+                .validate()
+                .unwrap_or_else(|e| panic!("pack '{}' doesn't validate: {e}", pack.name));
+
+            let registry =
+                SkillRegistry::from_config(&config.skills, Path::new("."), None).unwrap();
+            for (name, _) in pack.skills() {
+                assert!(
+                    registry.get(name).is_some(),
+                    "{} missing skill {name}",
+                    pack.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_config_hats_override_pack_hats() {
+        let mut config = RalphConfig::parse_yaml(
+            r#"
+packs: [maintenance]
+hats:
+  changelog_writer:
+    name: "Release Notes"
+    description: "Writes release notes instead"
+    triggers: ["deps.updated"]
+    publishes: ["notes.updated"]
+events:
+  deps.updated:
+    description: "Ours"
+"#,
+        )
+        .unwrap();
+        config.normalize();
+
+        assert_eq!(config.hats["changelog_writer"].name, "Release Notes");
+        assert!(config.hats.contains_key("dep_auditor"));
+        assert_eq!(config.events["deps.updated"].description, "Ours");
+        assert!(config.events.contains_key("security.advisory"));
+    }
+
+    #[test]
+    fn test_unknown_pack_is_rejected() {
+        let mut config = RalphConfig::parse_yaml("packs: [nightly]\n").unwrap();
+        config.normalize();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("nightly"));
+        assert!(err.to_string().contains("maintenance"));
+    }
+}
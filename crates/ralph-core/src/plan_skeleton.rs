@@ -0,0 +1,428 @@
+//! Backend-free plan skeletons for `ralph run --plan-only`.
+//!
+//! Everything here is deterministic: a map of the repository from its file
+//! list, the objective's own list items, mentioned paths that exist, and the
+//! scratchpad's open tasks. The result is a starting plan for a human or the
+//! first iteration to refine, produced without spending anything on a model.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::process::Command;
+
+/// Most top-level areas listed in the repo map.
+const MAX_AREAS: usize = 12;
+/// Most steps taken from the objective.
+const MAX_STEPS: usize = 20;
+/// Most lines of the objective quoted in the skeleton.
+const MAX_OBJECTIVE_LINES: usize = 6;
+/// Files walked when the workspace isn't a git repository.
+const MAX_WALKED_FILES: usize = 20_000;
+/// Directories skipped when walking a workspace that isn't a git repository.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "dist", "build", "vendor"];
+
+/// A build system recognized from a root manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toolchain {
+    pub name: &'static str,
+    pub manifest: &'static str,
+    pub build: &'static str,
+    pub test: &'static str,
+}
+
+const TOOLCHAINS: &[Toolchain] = &[
+    Toolchain {
+        name: "Rust",
+        manifest: "Cargo.toml",
+        build: "cargo build",
+        test: "cargo test",
+    },
+    Toolchain {
+        name: "Node",
+        manifest: "package.json",
+        build: "npm run build",
+        test: "npm test",
+    },
+    Toolchain {
+        name: "Go",
+        manifest: "go.mod",
+        build: "go build ./...",
+        test: "go test ./...",
+    },
+    Toolchain {
+        name: "Python",
+        manifest: "pyproject.toml",
+        build: "pip install -e .",
+        test: "pytest",
+    },
+    Toolchain {
+        name: "Make",
+        manifest: "Makefile",
+        build: "make",
+        test: "make test",
+    },
+];
+
+/// A summary of the repository's layout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoMap {
+    pub total_files: usize,
+    /// Top-level directories (with a trailing `/`) or files, with file counts,
+    /// largest first.
+    pub areas: Vec<(String, usize)>,
+    pub toolchains: Vec<Toolchain>,
+}
+
+impl RepoMap {
+    /// Maps the files git tracks in `workspace`, or walks it outside git.
+    pub fn scan(workspace: &Path) -> Self {
+        Self::from_paths(workspace_files(workspace, false))
+    }
+
+    /// Builds a map from workspace-relative paths using `/` separators.
+    pub fn from_paths<I, S>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut total_files = 0;
+        let mut areas: BTreeMap<String, usize> = BTreeMap::new();
+        let mut root_files = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            total_files += 1;
+            let area = match path.split_once('/') {
+                Some((dir, _)) => format!("{dir}/"),
+                None => {
+                    root_files.push(path.to_string());
+                    path.to_string()
+                }
+            };
+            *areas.entry(area).or_default() += 1;
+        }
+        let mut areas: Vec<(String, usize)> = areas.into_iter().collect();
+        areas.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        areas.truncate(MAX_AREAS);
+
+        let toolchains = TOOLCHAINS
+            .iter()
+            .filter(|toolchain| root_files.iter().any(|f| f == toolchain.manifest))
+            .cloned()
+            .collect();
+        Self {
+            total_files,
+            areas,
+            toolchains,
+        }
+    }
+}
+
+/// Workspace-relative paths of the files git tracks in `workspace` (and, with
+/// `include_untracked`, new files it doesn't ignore), or of the files found by
+/// walking it outside git.
+pub fn workspace_files(workspace: &Path, include_untracked: bool) -> Vec<String> {
+    let mut args = vec!["ls-files", "-z"];
+    if include_untracked {
+        args.extend(["--cached", "--others", "--exclude-standard"]);
+    }
+    let tracked = Command::new("git")
+        .args(&args)
+        .current_dir(workspace)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            output
+                .stdout
+                .split(|byte| *byte == 0)
+                .filter(|path| !path.is_empty())
+                .map(|path| String::from_utf8_lossy(path).into_owned())
+                .collect::<Vec<_>>()
+        });
+    tracked.unwrap_or_else(|| {
+        let mut files = Vec::new();
+        walk(workspace, workspace, &mut files);
+        files
+    })
+}
+
+fn walk(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    entries.sort_by_key(std::fs::DirEntry::file_name);
+    for entry in entries {
+        if files.len() >= MAX_WALKED_FILES {
+            return;
+        }
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            if !SKIPPED_DIRS.contains(&name.as_ref()) {
+                walk(root, &path, files);
+            }
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+}
+
+/// The inputs a plan skeleton is drawn from.
+#[derive(Debug, Clone)]
+pub struct PlanInputs<'a> {
+    /// The prompt or task text.
+    pub objective: &'a str,
+    pub repo: &'a RepoMap,
+    /// Simulated hat route, one step per entry.
+    pub route: Vec<String>,
+    /// How the simulated route ends.
+    pub route_end: Option<String>,
+    /// Workspace paths the objective mentions.
+    pub mentioned_paths: Vec<String>,
+    /// Current scratchpad content.
+    pub scratchpad: &'a str,
+}
+
+/// Renders the plan skeleton as a scratchpad section.
+pub fn render(inputs: &PlanInputs<'_>) -> String {
+    let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
+    let mut out = format!(
+        "## PLAN SKELETON ({timestamp})\n\n\
+         Drafted by `ralph run --plan-only` from local heuristics; no backend was called. \
+         Refine the steps before building.\n"
+    );
+
+    out.push_str("\n### Objective\n\n");
+    let lines: Vec<&str> = inputs
+        .objective
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    for line in lines.iter().take(MAX_OBJECTIVE_LINES) {
+        let _ = writeln!(out, "> {line}");
+    }
+    if lines.len() > MAX_OBJECTIVE_LINES {
+        out.push_str("> ...\n");
+    }
+
+    out.push_str("\n### Repo map\n\n");
+    let _ = writeln!(out, "- {} files", inputs.repo.total_files);
+    for toolchain in &inputs.repo.toolchains {
+        let _ = writeln!(
+            out,
+            "- {} (`{}`): build with `{}`, test with `{}`",
+            toolchain.name, toolchain.manifest, toolchain.build, toolchain.test
+        );
+    }
+    for (area, count) in &inputs.repo.areas {
+        let _ = writeln!(out, "- `{area}` ({count})");
+    }
+
+    if !inputs.route.is_empty() {
+        out.push_str("\n### Route\n\n");
+        for (i, step) in inputs.route.iter().enumerate() {
+            let _ = writeln!(out, "{}. {step}", i + 1);
+        }
+        if let Some(end) = &inputs.route_end {
+            let _ = writeln!(out, "\n{end}");
+        }
+    }
+
+    out.push_str("\n### Steps\n\n");
+    let mut steps = objective_steps(inputs.objective);
+    if steps.is_empty() {
+        steps.push("Break the objective into concrete changes".to_string());
+        if !inputs.mentioned_paths.is_empty() {
+            steps.push("Read and change the files mentioned below".to_string());
+        }
+        steps.push("Add or update tests for the change".to_string());
+    }
+    if let Some(toolchain) = inputs.repo.toolchains.first() {
+        steps.push(format!(
+            "Verify with `{}` and `{}`",
+            toolchain.build, toolchain.test
+        ));
+    }
+    for step in steps {
+        let _ = writeln!(out, "- [ ] {step}");
+    }
+
+    if !inputs.mentioned_paths.is_empty() {
+        out.push_str("\n### Files mentioned\n\n");
+        for path in &inputs.mentioned_paths {
+            let _ = writeln!(out, "- `{path}`");
+        }
+    }
+
+    let open = open_tasks(inputs.scratchpad);
+    if !open.is_empty() {
+        out.push_str("\n### Open tasks already in the scratchpad\n\n");
+        for task in open {
+            let _ = writeln!(out, "- [ ] {task}");
+        }
+    }
+    out
+}
+
+/// List items in the objective (`-`, `*`, `1.`, or `- [ ]`), in order.
+pub fn objective_steps(objective: &str) -> Vec<String> {
+    objective
+        .lines()
+        .filter_map(|line| list_item(line.trim()))
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .take(MAX_STEPS)
+        .collect()
+}
+
+fn list_item(line: &str) -> Option<&str> {
+    let item = if let Some(rest) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        rest
+    } else {
+        let digits = line.find(|c: char| !c.is_ascii_digit())?;
+        if digits == 0 {
+            return None;
+        }
+        line[digits..]
+            .strip_prefix(". ")
+            .or_else(|| line[digits..].strip_prefix(") "))?
+    };
+    let item = item
+        .strip_prefix("[ ] ")
+        .or_else(|| item.strip_prefix("[x] "))
+        .unwrap_or(item);
+    Some(item.trim())
+}
+
+/// Unchecked `- [ ]` items in the scratchpad.
+pub fn open_tasks(scratchpad: &str) -> Vec<String> {
+    scratchpad
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            line.strip_prefix("- [ ] ")
+                .or_else(|| line.strip_prefix("* [ ] "))
+        })
+        .map(|task| task.trim().to_string())
+        .filter(|task| !task.is_empty())
+        .collect()
+}
+
+/// Path-like words in the objective that exist in `workspace`, in order.
+pub fn mentioned_paths(objective: &str, workspace: &Path) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for word in objective.split_whitespace() {
+        let word = word.trim_matches(|c: char| {
+            matches!(
+                c,
+                '`' | '"' | '\'' | '(' | ')' | '[' | ']' | ',' | ';' | ':'
+            )
+        });
+        let word = word.trim_end_matches('.');
+        let word = word.strip_prefix("./").unwrap_or(word);
+        let looks_like_path = word.contains('/')
+            || word
+                .rsplit_once('.')
+                .is_some_and(|(stem, ext)| !stem.is_empty() && (1..=5).contains(&ext.len()));
+        if !looks_like_path || word.contains("://") || word.starts_with('/') || word.contains("..")
+        {
+            continue;
+        }
+        if workspace.join(word).exists() && !paths.iter().any(|p| p == word) {
+            paths.push(word.to_string());
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_repo_map_counts_areas_and_detects_toolchains() {
+        let map = RepoMap::from_paths([
+            "Cargo.toml",
+            "README.md",
+            "crates/a/src/lib.rs",
+            "crates/b/src/lib.rs",
+            "docs/guide.md",
+        ]);
+        assert_eq!(map.total_files, 5);
+        assert_eq!(map.areas[0], ("crates/".to_string(), 2));
+        assert_eq!(map.toolchains.len(), 1);
+        assert_eq!(map.toolchains[0].test, "cargo test");
+    }
+
+    #[test]
+    fn test_repo_map_walks_outside_git() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/x")).unwrap();
+        fs::write(dir.path().join("package.json"), "{}").unwrap();
+        fs::write(dir.path().join("src/index.js"), "").unwrap();
+        fs::write(dir.path().join("node_modules/x/index.js"), "").unwrap();
+
+        let map = RepoMap::scan(dir.path());
+        assert_eq!(map.total_files, 2);
+        assert_eq!(map.toolchains[0].name, "Node");
+    }
+
+    #[test]
+    fn test_objective_steps_reads_list_items() {
+        let objective = "Add OAuth login.\n\n- Add the provider config\n2. Wire the callback route\n* [ ] Update the docs\n-not a list item";
+        assert_eq!(
+            objective_steps(objective),
+            vec![
+                "Add the provider config",
+                "Wire the callback route",
+                "Update the docs"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_open_tasks_skips_checked_items() {
+        let scratchpad = "## Tasks\n- [x] Done already\n- [ ] Still open\n  - [ ] Nested open\n";
+        assert_eq!(open_tasks(scratchpad), vec!["Still open", "Nested open"]);
+    }
+
+    #[test]
+    fn test_mentioned_paths_must_exist() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/auth.rs"), "").unwrap();
+
+        let objective = "Fix `src/auth.rs`, then src/missing.rs. See https://example.com/x.rs (or ./src/auth.rs).";
+        assert_eq!(mentioned_paths(objective, dir.path()), vec!["src/auth.rs"]);
+    }
+
+    #[test]
+    fn test_render_falls_back_to_generic_steps() {
+        let repo = RepoMap::from_paths(["Cargo.toml", "src/lib.rs"]);
+        let skeleton = render(&PlanInputs {
+            objective: "Make the parser faster",
+            repo: &repo,
+            route: vec!["task.start -> builder".to_string()],
+            route_end: Some("'builder' publishes LOOP_COMPLETE".to_string()),
+            mentioned_paths: Vec::new(),
+            scratchpad: "- [ ] Profile the lexer\n",
+        });
+        assert!(skeleton.starts_with("## PLAN SKELETON ("));
+        assert!(skeleton.contains("> Make the parser faster"));
+        assert!(skeleton.contains("1. task.start -> builder"));
+        assert!(skeleton.contains("- [ ] Break the objective into concrete changes"));
+        assert!(skeleton.contains("- [ ] Verify with `cargo build` and `cargo test`"));
+        assert!(
+            skeleton
+                .contains("### Open tasks already in the scratchpad\n\n- [ ] Profile the lexer")
+        );
+    }
+}
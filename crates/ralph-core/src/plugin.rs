@@ -0,0 +1,620 @@
+//! WASM plugin host for context providers, event filters, and native hats.
+//!
+//! Plugins are compiled WebAssembly modules declared under `plugins:` in the
+//! config. They let users extend the orchestrator without recompiling ralph:
+//!
+//! - **Context providers** contribute a markdown section to every prompt
+//! - **Event filters** can drop events read from JSONL before they reach the bus
+//! - **Hat plugins** handle a hat's pending events deterministically (no LLM call)
+//!   and publish result events, e.g. a linter hat
+//!
+//! # ABI
+//!
+//! Data crosses the boundary as UTF-8 JSON. A plugin module must export:
+//!
+//! - `memory` — the module's linear memory
+//! - `ralph_alloc(len: i32) -> i32` — returns a buffer the host writes input into
+//!
+//! Plus the entry point for its kind:
+//!
+//! | Kind      | Export                                  | Input                 | Output          |
+//! |-----------|-----------------------------------------|-----------------------|-----------------|
+//! | `context` | `ralph_context(ptr: i32, len: i32) -> i64` | `ContextRequest`   | markdown text   |
+//! | `filter`  | `ralph_filter(ptr: i32, len: i32) -> i32`  | `PluginEvent`      | `1` keep, `0` drop |
+//! | `hat`     | `ralph_handle(ptr: i32, len: i32) -> i64`  | `HatRequest`       | `[PluginEvent]` |
+//!
+//! `i64` results pack the output buffer as `(ptr << 32) | len`; a zero length
+//! means "no output". Modules are instantiated without any host imports (no
+//! WASI), so plugins cannot touch the filesystem or network, every call is
+//! bounded by a fuel budget, and linear memory is capped per plugin.
+//!
+//! The runtime is only compiled in with the `wasm-plugins` cargo feature.
+//! A configured plugin that can't be loaded, including one a build without
+//! the runtime can't run, keeps the loop from starting
+//! (see [`EventLoop::ensure_extensions_loaded`](crate::EventLoop::ensure_extensions_loaded)).
+
+use crate::config::{PluginConfig, PluginKind};
+use ralph_proto::{Event, HatId};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// Fuel granted to a single plugin call before it is aborted.
+#[cfg_attr(not(feature = "wasm-plugins"), allow(dead_code))]
+const FUEL_PER_CALL: u64 = 50_000_000;
+
+/// Linear memory a single plugin may grow to.
+#[cfg_attr(not(feature = "wasm-plugins"), allow(dead_code))]
+const MEMORY_PER_PLUGIN: usize = 64 * 1024 * 1024;
+
+/// Errors that can occur while loading or calling plugins.
+#[derive(Debug, thiserror::Error)]
+pub enum PluginError {
+    /// IO error reading a plugin module.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The binary was built without the WASM runtime.
+    #[error(
+        "Plugin '{name}' requires the `wasm-plugins` feature, which this build does not include"
+    )]
+    Unsupported { name: String },
+
+    /// The module does not follow the plugin ABI.
+    #[error("Plugin '{name}' does not implement the plugin ABI: {reason}")]
+    Abi { name: String, reason: String },
+
+    /// The module trapped or ran out of fuel.
+    #[error("Plugin '{name}' failed: {reason}")]
+    Runtime { name: String, reason: String },
+}
+
+/// An event as exchanged with plugins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginEvent {
+    pub topic: String,
+    #[serde(default)]
+    pub payload: String,
+}
+
+impl From<&Event> for PluginEvent {
+    fn from(event: &Event) -> Self {
+        Self {
+            topic: event.topic.to_string(),
+            payload: event.payload.clone(),
+        }
+    }
+}
+
+/// Input passed to `ralph_context`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextRequest<'a> {
+    pub iteration: u32,
+    pub hat: &'a str,
+}
+
+/// Input passed to `ralph_handle`.
+#[derive(Debug, Clone, Serialize)]
+pub struct HatRequest<'a> {
+    pub hat: &'a str,
+    pub events: Vec<PluginEvent>,
+}
+
+/// A plugin module ready to be called.
+#[cfg_attr(not(feature = "wasm-plugins"), allow(dead_code))]
+struct LoadedPlugin {
+    name: String,
+    kind: PluginKind,
+    #[cfg(feature = "wasm-plugins")]
+    instance: runtime::WasmInstance,
+}
+
+/// Loads and dispatches to configured plugins.
+#[derive(Default)]
+pub struct PluginHost {
+    plugins: Vec<LoadedPlugin>,
+}
+
+impl std::fmt::Debug for PluginHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginHost")
+            .field(
+                "plugins",
+                &self.plugins.iter().map(|p| &p.name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl PluginHost {
+    /// Creates a host with no plugins loaded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every enabled plugin from configuration.
+    ///
+    /// Relative plugin paths are resolved against `workspace_root`.
+    pub fn from_config(
+        configs: &[PluginConfig],
+        workspace_root: &Path,
+    ) -> Result<Self, PluginError> {
+        let mut host = Self::new();
+
+        for config in configs.iter().filter(|c| c.enabled) {
+            let path = if config.path.is_absolute() {
+                config.path.clone()
+            } else {
+                workspace_root.join(&config.path)
+            };
+            host.plugins.push(LoadedPlugin::load(config, &path)?);
+        }
+
+        Ok(host)
+    }
+
+    /// Returns true if no plugins are loaded.
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Returns true if a hat plugin with this name is loaded.
+    pub fn has_hat(&self, name: &str) -> bool {
+        self.plugins
+            .iter()
+            .any(|p| p.kind == PluginKind::Hat && p.name == name)
+    }
+
+    /// Collects context sections from all context-provider plugins.
+    ///
+    /// Failing plugins are logged and skipped so a broken plugin never blocks
+    /// an iteration.
+    pub fn context(&mut self, iteration: u32, hat_id: &HatId) -> Vec<(String, String)> {
+        let request = ContextRequest {
+            iteration,
+            hat: hat_id.as_str(),
+        };
+        let Ok(input) = serde_json::to_vec(&request) else {
+            return Vec::new();
+        };
+
+        let mut sections = Vec::new();
+        for plugin in self
+            .plugins
+            .iter_mut()
+            .filter(|p| p.kind == PluginKind::Context)
+        {
+            match plugin.call_context(&input) {
+                Ok(Some(text)) if !text.trim().is_empty() => {
+                    sections.push((plugin.name.clone(), text));
+                }
+                Ok(_) => {}
+                Err(e) => warn!(plugin = %plugin.name, error = %e, "Context plugin failed"),
+            }
+        }
+        sections
+    }
+
+    /// Runs an event through all filter plugins.
+    ///
+    /// Returns false if any filter dropped the event. Failing filters keep the
+    /// event, so a broken plugin cannot silently swallow handoffs.
+    pub fn filter(&mut self, event: &PluginEvent) -> bool {
+        let Ok(input) = serde_json::to_vec(event) else {
+            return true;
+        };
+
+        for plugin in self
+            .plugins
+            .iter_mut()
+            .filter(|p| p.kind == PluginKind::Filter)
+        {
+            match plugin.call_filter(&input) {
+                Ok(true) => {}
+                Ok(false) => {
+                    debug!(plugin = %plugin.name, topic = %event.topic, "Event dropped by filter plugin");
+                    return false;
+                }
+                Err(e) => warn!(plugin = %plugin.name, error = %e, "Filter plugin failed"),
+            }
+        }
+        true
+    }
+
+    /// Hands a hat's pending events to its plugin and returns the events it publishes.
+    pub fn handle(
+        &mut self,
+        plugin_name: &str,
+        hat_id: &HatId,
+        events: &[Event],
+    ) -> Result<Vec<Event>, PluginError> {
+        let Some(plugin) = self
+            .plugins
+            .iter_mut()
+            .find(|p| p.kind == PluginKind::Hat && p.name == plugin_name)
+        else {
+            return Err(PluginError::Abi {
+                name: plugin_name.to_string(),
+                reason: "no hat plugin with this name is loaded".to_string(),
+            });
+        };
+
+        let request = HatRequest {
+            hat: hat_id.as_str(),
+            events: events.iter().map(PluginEvent::from).collect(),
+        };
+        let input = serde_json::to_vec(&request).map_err(|e| PluginError::Abi {
+            name: plugin_name.to_string(),
+            reason: e.to_string(),
+        })?;
+
+        let published: Vec<PluginEvent> = match plugin.call_handle(&input)? {
+            Some(output) => serde_json::from_str(&output).map_err(|e| PluginError::Abi {
+                name: plugin_name.to_string(),
+                reason: format!("ralph_handle returned invalid JSON: {e}"),
+            })?,
+            None => Vec::new(),
+        };
+
+        Ok(published
+            .into_iter()
+            .map(|e| Event::new(e.topic, e.payload).with_source(hat_id.clone()))
+            .collect())
+    }
+}
+
+#[cfg(feature = "wasm-plugins")]
+impl LoadedPlugin {
+    fn load(config: &PluginConfig, path: &Path) -> Result<Self, PluginError> {
+        let bytes = std::fs::read(path)?;
+        let instance = runtime::WasmInstance::new(&config.name, &bytes, config.kind)?;
+        debug!(plugin = %config.name, kind = ?config.kind, path = %path.display(), "Loaded WASM plugin");
+        Ok(Self {
+            name: config.name.clone(),
+            kind: config.kind,
+            instance,
+        })
+    }
+
+    fn call_context(&mut self, input: &[u8]) -> Result<Option<String>, PluginError> {
+        self.instance.call_packed("ralph_context", input)
+    }
+
+    fn call_filter(&mut self, input: &[u8]) -> Result<bool, PluginError> {
+        self.instance.call_filter(input)
+    }
+
+    fn call_handle(&mut self, input: &[u8]) -> Result<Option<String>, PluginError> {
+        self.instance.call_packed("ralph_handle", input)
+    }
+}
+
+#[cfg(not(feature = "wasm-plugins"))]
+#[allow(dead_code)]
+impl LoadedPlugin {
+    fn load(config: &PluginConfig, path: &Path) -> Result<Self, PluginError> {
+        debug!(plugin = %config.name, path = %path.display(), "WASM runtime not compiled in");
+        Err(PluginError::Unsupported {
+            name: config.name.clone(),
+        })
+    }
+
+    fn unsupported(&self) -> PluginError {
+        PluginError::Unsupported {
+            name: self.name.clone(),
+        }
+    }
+
+    fn call_context(&mut self, _input: &[u8]) -> Result<Option<String>, PluginError> {
+        Err(self.unsupported())
+    }
+
+    fn call_filter(&mut self, _input: &[u8]) -> Result<bool, PluginError> {
+        Err(self.unsupported())
+    }
+
+    fn call_handle(&mut self, _input: &[u8]) -> Result<Option<String>, PluginError> {
+        Err(self.unsupported())
+    }
+}
+
+#[cfg(feature = "wasm-plugins")]
+mod runtime {
+    use super::{FUEL_PER_CALL, MEMORY_PER_PLUGIN, PluginError, PluginKind};
+    use wasmtime::{
+        Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+        TypedFunc,
+    };
+
+    /// A single instantiated plugin module with its own store.
+    pub(super) struct WasmInstance {
+        name: String,
+        store: Store<StoreLimits>,
+        instance: Instance,
+        memory: Memory,
+        alloc: TypedFunc<i32, i32>,
+    }
+
+    impl WasmInstance {
+        pub(super) fn new(name: &str, bytes: &[u8], kind: PluginKind) -> Result<Self, PluginError> {
+            let runtime_err = |e: wasmtime::Error| PluginError::Runtime {
+                name: name.to_string(),
+                reason: e.to_string(),
+            };
+            let abi_err = |reason: String| PluginError::Abi {
+                name: name.to_string(),
+                reason,
+            };
+
+            let mut config = Config::new();
+            config.consume_fuel(true);
+            let engine = Engine::new(&config).map_err(runtime_err)?;
+            let module = Module::new(&engine, bytes).map_err(runtime_err)?;
+            let limits = StoreLimitsBuilder::new()
+                .memory_size(MEMORY_PER_PLUGIN)
+                .instances(1)
+                .build();
+            let mut store = Store::new(&engine, limits);
+            store.limiter(|limits| limits);
+            store.set_fuel(FUEL_PER_CALL).map_err(runtime_err)?;
+
+            // No host imports: plugins are pure functions over their input.
+            let linker = Linker::new(&engine);
+            let instance = linker
+                .instantiate(&mut store, &module)
+                .map_err(runtime_err)?;
+
+            let memory = instance
+                .get_memory(&mut store, "memory")
+                .ok_or_else(|| abi_err("missing exported `memory`".to_string()))?;
+            let alloc = instance
+                .get_typed_func::<i32, i32>(&mut store, "ralph_alloc")
+                .map_err(|e| abi_err(format!("ralph_alloc: {e}")))?;
+
+            let entry = match kind {
+                PluginKind::Context => "ralph_context",
+                PluginKind::Filter => "ralph_filter",
+                PluginKind::Hat => "ralph_handle",
+            };
+            if instance.get_func(&mut store, entry).is_none() {
+                return Err(abi_err(format!("missing exported `{entry}`")));
+            }
+
+            Ok(Self {
+                name: name.to_string(),
+                store,
+                instance,
+                memory,
+                alloc,
+            })
+        }
+
+        fn runtime_err(&self, e: impl std::fmt::Display) -> PluginError {
+            PluginError::Runtime {
+                name: self.name.clone(),
+                reason: e.to_string(),
+            }
+        }
+
+        fn abi_err(&self, reason: String) -> PluginError {
+            PluginError::Abi {
+                name: self.name.clone(),
+                reason,
+            }
+        }
+
+        /// Refuels the store and copies `input` into guest memory.
+        fn write_input(&mut self, input: &[u8]) -> Result<(i32, i32), PluginError> {
+            self.store
+                .set_fuel(FUEL_PER_CALL)
+                .map_err(|e| self.runtime_err(e))?;
+
+            let len = i32::try_from(input.len())
+                .map_err(|_| self.abi_err("input exceeds 2 GiB".to_string()))?;
+            let ptr = self
+                .alloc
+                .call(&mut self.store, len)
+                .map_err(|e| self.runtime_err(e))?;
+            let offset = usize::try_from(ptr)
+                .map_err(|_| self.abi_err(format!("ralph_alloc returned {ptr}")))?;
+            self.memory
+                .write(&mut self.store, offset, input)
+                .map_err(|e| self.runtime_err(e))?;
+            Ok((ptr, len))
+        }
+
+        /// Calls an entry point returning a packed `(ptr << 32) | len` buffer.
+        pub(super) fn call_packed(
+            &mut self,
+            export: &str,
+            input: &[u8],
+        ) -> Result<Option<String>, PluginError> {
+            let func = self
+                .instance
+                .get_typed_func::<(i32, i32), i64>(&mut self.store, export)
+                .map_err(|e| self.abi_err(format!("{export}: {e}")))?;
+            let args = self.write_input(input)?;
+            let packed = func
+                .call(&mut self.store, args)
+                .map_err(|e| self.runtime_err(e))?;
+
+            let packed = packed as u64;
+            let ptr = (packed >> 32) as usize;
+            let len = (packed & 0xFFFF_FFFF) as usize;
+            if len == 0 {
+                return Ok(None);
+            }
+
+            let mut buf = vec![0u8; len];
+            self.memory
+                .read(&self.store, ptr, &mut buf)
+                .map_err(|e| self.runtime_err(e))?;
+            String::from_utf8(buf)
+                .map(Some)
+                .map_err(|_| self.abi_err(format!("{export} returned non-UTF-8 output")))
+        }
+
+        /// Calls `ralph_filter`, returning whether the event should be kept.
+        pub(super) fn call_filter(&mut self, input: &[u8]) -> Result<bool, PluginError> {
+            let func = self
+                .instance
+                .get_typed_func::<(i32, i32), i32>(&mut self.store, "ralph_filter")
+                .map_err(|e| self.abi_err(format!("ralph_filter: {e}")))?;
+            let args = self.write_input(input)?;
+            let keep = func
+                .call(&mut self.store, args)
+                .map_err(|e| self.runtime_err(e))?;
+            Ok(keep != 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn plugin_config(name: &str, kind: PluginKind) -> PluginConfig {
+        PluginConfig {
+            name: name.to_string(),
+            path: PathBuf::from(format!("{name}.wasm")),
+            kind,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_empty_config_loads_empty_host() {
+        let host = PluginHost::from_config(&[], Path::new(".")).unwrap();
+        assert!(host.is_empty());
+        assert!(!host.has_hat("linter"));
+    }
+
+    #[test]
+    fn test_disabled_plugins_are_skipped() {
+        let mut config = plugin_config("linter", PluginKind::Hat);
+        config.enabled = false;
+
+        let host = PluginHost::from_config(&[config], Path::new(".")).unwrap();
+        assert!(host.is_empty());
+    }
+
+    #[cfg(not(feature = "wasm-plugins"))]
+    #[test]
+    fn test_plugins_unsupported_without_runtime() {
+        let config = plugin_config("linter", PluginKind::Hat);
+
+        let err = PluginHost::from_config(&[config], Path::new(".")).unwrap_err();
+        assert!(matches!(err, PluginError::Unsupported { ref name } if name == "linter"));
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[test]
+    fn test_missing_module_is_io_error() {
+        let config = plugin_config("does-not-exist", PluginKind::Context);
+
+        let err = PluginHost::from_config(&[config], Path::new("/nonexistent")).unwrap_err();
+        assert!(matches!(err, PluginError::Io(_)));
+    }
+
+    /// Keeps events unless their topic starts with `n`; handles any request by
+    /// publishing `lint.done`.
+    #[cfg(feature = "wasm-plugins")]
+    const LINT_WAT: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (data (i32.const 16) "[{\"topic\":\"lint.done\",\"payload\":\"clean\"}]")
+          (func (export "ralph_alloc") (param i32) (result i32)
+            (i32.const 1024))
+          (func (export "ralph_filter") (param $ptr i32) (param $len i32) (result i32)
+            ;; input is `{"topic":"...`, so the topic starts 10 bytes in
+            (i32.ne (i32.load8_u offset=10 (local.get $ptr)) (i32.const 110)))
+          (func (export "ralph_handle") (param i32 i32) (result i64)
+            (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 41))))
+    "#;
+
+    #[cfg(feature = "wasm-plugins")]
+    #[test]
+    fn test_wasm_plugin_filters_and_handles_events() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("gate.wasm"), LINT_WAT).unwrap();
+        std::fs::write(temp_dir.path().join("linter.wasm"), LINT_WAT).unwrap();
+        let configs = [
+            plugin_config("gate", PluginKind::Filter),
+            plugin_config("linter", PluginKind::Hat),
+        ];
+
+        let mut host = PluginHost::from_config(&configs, temp_dir.path()).unwrap();
+        assert!(host.has_hat("linter"));
+        assert!(host.filter(&PluginEvent {
+            topic: "lint.request".to_string(),
+            payload: "src/main.rs".to_string(),
+        }));
+        assert!(!host.filter(&PluginEvent {
+            topic: "noise.tick".to_string(),
+            payload: String::new(),
+        }));
+
+        let linter = HatId::new("linter");
+        let published = host
+            .handle(
+                "linter",
+                &linter,
+                &[Event::new("lint.request", "src/main.rs")],
+            )
+            .unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].topic.as_str(), "lint.done");
+        assert_eq!(published[0].payload, "clean");
+        assert_eq!(published[0].source, Some(linter));
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[test]
+    fn test_wasm_plugin_memory_is_capped() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        // 2048 pages is 128 MiB, over the per-plugin cap.
+        let wat = r#"
+            (module
+              (memory (export "memory") 2048)
+              (func (export "ralph_alloc") (param i32) (result i32)
+                (i32.const 0))
+              (func (export "ralph_context") (param i32 i32) (result i64)
+                (i64.const 0)))
+        "#;
+        std::fs::write(temp_dir.path().join("greedy.wasm"), wat).unwrap();
+        let config = plugin_config("greedy", PluginKind::Context);
+
+        let err = PluginHost::from_config(&[config], temp_dir.path()).unwrap_err();
+        assert!(matches!(err, PluginError::Runtime { ref name, .. } if name == "greedy"));
+    }
+
+    #[test]
+    fn test_empty_host_passes_events_through() {
+        let mut host = PluginHost::new();
+        let event = PluginEvent {
+            topic: "build.done".to_string(),
+            payload: "tests: pass".to_string(),
+        };
+
+        assert!(host.filter(&event));
+        assert!(host.context(1, &HatId::new("ralph")).is_empty());
+    }
+
+    #[test]
+    fn test_handle_unknown_plugin_errors() {
+        let mut host = PluginHost::new();
+        let err = host
+            .handle("linter", &HatId::new("linter"), &[])
+            .unwrap_err();
+        assert!(matches!(err, PluginError::Abi { .. }));
+    }
+
+    #[test]
+    fn test_plugin_event_from_event() {
+        let event = Event::new("lint.request", "src/main.rs");
+        let plugin_event = PluginEvent::from(&event);
+        assert_eq!(plugin_event.topic, "lint.request");
+        assert_eq!(plugin_event.payload, "src/main.rs");
+    }
+}
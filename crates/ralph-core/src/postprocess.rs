@@ -0,0 +1,423 @@
+//! Per-hat output postprocessors (`hats.<id>.postprocess` in ralph.yml).
+//!
+//! A hat's output runs through its pipeline before the loop journals the
+//! events in it, summarizes it for the next iteration, or checks it for the
+//! completion promise. Built-in transforms cover the common cases: stripping
+//! chain-of-thought, applying diff blocks, and turning tables into events. A
+//! `command` step pipes the output through any shell command.
+//!
+//! Steps that publish (`table_to_events`, and `apply_diff` when a patch
+//! doesn't apply) return their events to the caller, which writes them to the
+//! events file like `ralph emit` does.
+
+use crate::config::Postprocessor;
+use crate::event_reader::Event;
+use crate::git_ops::git_command;
+use crate::text::truncate_with_ellipsis;
+use ralph_proto::Topic;
+use regex::Regex;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Topic published when a diff block doesn't apply.
+pub const DIFF_REJECTED_TOPIC: &str = "postprocess.diff_rejected";
+
+/// How long a `command` step may run before it is killed.
+const COMMAND_TIMEOUT: Duration = Duration::from_mins(2);
+
+const COMMAND_POLL: Duration = Duration::from_millis(20);
+
+/// Most characters of a rejected patch quoted back in the event.
+const MAX_QUOTED_PATCH: usize = 4000;
+
+static THINKING_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<thinking>.*?</thinking>|<think>.*?</think>|<reasoning>.*?</reasoning>")
+        .unwrap()
+});
+
+static BLANK_RUN_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n{3,}").unwrap());
+
+/// A hat's output after its pipeline ran.
+#[derive(Debug, Clone)]
+pub struct Postprocessed {
+    /// The transformed output.
+    pub output: String,
+    /// Events the steps published, in order.
+    pub events: Vec<Event>,
+}
+
+/// Runs `pipeline` over `output` from `hat`, in `workspace`.
+///
+/// A step that fails is logged and skipped, so its input passes through.
+pub fn run(pipeline: &[Postprocessor], output: &str, workspace: &Path, hat: &str) -> Postprocessed {
+    let mut processed = Postprocessed {
+        output: output.to_string(),
+        events: Vec::new(),
+    };
+    for step in pipeline {
+        match step {
+            Postprocessor::StripThinking => processed.output = strip_thinking(&processed.output),
+            Postprocessor::ApplyDiff => processed
+                .events
+                .extend(apply_diffs(&processed.output, workspace)),
+            Postprocessor::TableToEvents { topic } => processed
+                .events
+                .extend(table_events(&processed.output, topic.as_deref())),
+            Postprocessor::Command(command) => {
+                match run_command(command, &processed.output, workspace, hat) {
+                    Ok(output) => processed.output = output,
+                    Err(e) => {
+                        warn!(hat, command, error = %e, "Postprocess command failed; keeping its input");
+                    }
+                }
+            }
+        }
+    }
+    processed
+}
+
+/// Removes `<thinking>`, `<think>`, and `<reasoning>` blocks.
+fn strip_thinking(output: &str) -> String {
+    let stripped = THINKING_RE.replace_all(output, "");
+    BLANK_RUN_RE.replace_all(&stripped, "\n\n").into_owned()
+}
+
+/// Bodies of the fenced code blocks whose language is one of `languages`.
+fn fenced_blocks(output: &str, languages: &[&str]) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        match current.as_mut() {
+            Some(body) if trimmed == "```" => {
+                blocks.push(format!("{}\n", body.join("\n")));
+                current = None;
+            }
+            Some(body) => body.push(line),
+            None => {
+                if let Some(info) = trimmed.strip_prefix("```") {
+                    let language = info.split_whitespace().next().unwrap_or_default();
+                    if languages.contains(&language) {
+                        current = Some(Vec::new());
+                    }
+                }
+            }
+        }
+    }
+    blocks
+}
+
+/// Applies each diff block with `git apply`.
+///
+/// Blocks that are already applied (the hat edited the files and also
+/// printed the diff) are skipped. Each block that doesn't apply becomes a
+/// [`DIFF_REJECTED_TOPIC`] event quoting git's error and the patch.
+fn apply_diffs(output: &str, workspace: &Path) -> Vec<Event> {
+    let mut events = Vec::new();
+    for patch in fenced_blocks(output, &["diff", "patch"]) {
+        if git_apply(&["--check", "--reverse"], &patch, workspace).is_ok() {
+            debug!("Diff block is already applied");
+            continue;
+        }
+        match git_apply(&[], &patch, workspace) {
+            Ok(()) => info!("Applied a diff block from the hat's output"),
+            Err(error) => {
+                warn!(error = %error, "Diff block from the hat's output didn't apply");
+                events.push(event(
+                    DIFF_REJECTED_TOPIC,
+                    format!(
+                        "A diff block from the last iteration didn't apply, so its changes are NOT in the workspace.\n\
+                         git apply: {}\n\
+                         Regenerate the diff against the current files, or make the change directly.\n\n\
+                         ```diff\n{}```",
+                        error.trim(),
+                        truncate_with_ellipsis(&patch, MAX_QUOTED_PATCH)
+                    ),
+                ));
+            }
+        }
+    }
+    events
+}
+
+/// Runs `git apply` with `args` on `patch`, returning git's error output on
+/// failure.
+fn git_apply(args: &[&str], patch: &str, workspace: &Path) -> Result<(), String> {
+    let mut child = git_command()
+        .arg("apply")
+        .args(args)
+        .args(["--whitespace=nowarn", "-"])
+        .current_dir(workspace)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(patch.as_bytes());
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+}
+
+/// One event per row of each markdown table.
+///
+/// Every row's topic is `topic` or, without it, the row's `topic` column.
+/// The payload is the `payload` column if there is one, otherwise the other
+/// columns as `Header: value` lines. Rows without a valid topic are skipped.
+fn table_events(output: &str, topic: Option<&str>) -> Vec<Event> {
+    let mut events = Vec::new();
+    for table in tables(output) {
+        let Some((header, rows)) = table.split_first() else {
+            continue;
+        };
+        let topic_column = header.iter().position(|h| h.eq_ignore_ascii_case("topic"));
+        let payload_column = header
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case("payload"));
+
+        for row in rows {
+            let cell = |i: usize| row.get(i).map(String::as_str).unwrap_or_default();
+            let row_topic = match (topic, topic_column) {
+                (Some(topic), _) => topic,
+                (None, Some(i)) => cell(i),
+                (None, None) => {
+                    debug!("Table has no topic column; skipping it");
+                    break;
+                }
+            };
+            if Topic::parse(row_topic).is_err() {
+                warn!(
+                    topic = row_topic,
+                    "Skipping table row with an invalid topic"
+                );
+                continue;
+            }
+            let payload = match payload_column {
+                Some(i) => cell(i).to_string(),
+                None => header
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| Some(*i) != topic_column && !cell(*i).is_empty())
+                    .map(|(i, h)| format!("{h}: {}", cell(i)))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            };
+            events.push(event(row_topic, payload));
+        }
+    }
+    events
+}
+
+/// Markdown tables outside code blocks, as a header row followed by body
+/// rows.
+fn tables(output: &str) -> Vec<Vec<Vec<String>>> {
+    let mut tables = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    for line in output.lines().chain(std::iter::once("")) {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if !in_fence && trimmed.starts_with('|') {
+            current.push(trimmed);
+            continue;
+        }
+        if current.len() >= 2 && is_separator(current[1]) {
+            let mut rows: Vec<Vec<String>> = current.iter().map(|line| cells(line)).collect();
+            rows.remove(1);
+            tables.push(rows);
+        }
+        current.clear();
+    }
+    tables
+}
+
+fn cells(line: &str) -> Vec<String> {
+    let line = line.trim().trim_start_matches('|');
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|')
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+/// `|---|:---:|` and the like.
+fn is_separator(line: &str) -> bool {
+    cells(line).iter().all(|cell| {
+        let dashes = cell.trim_matches(':');
+        !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+    })
+}
+
+fn event(topic: &str, payload: String) -> Event {
+    Event {
+        topic: topic.to_string(),
+        payload: Some(payload),
+        ts: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// Pipes `input` through `sh -c command` and returns its stdout.
+///
+/// # Errors
+///
+/// Returns an error if the command can't start, exits non-zero, or runs
+/// longer than [`COMMAND_TIMEOUT`].
+fn run_command(command: &str, input: &str, workspace: &Path, hat: &str) -> io::Result<String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(workspace)
+        .env("RALPH_HAT", hat)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Feed and drain the pipes on their own threads so a large output can't
+    // fill a pipe buffer and stall the command.
+    let mut stdin = child.stdin.take();
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || {
+        if let Some(stdin) = stdin.as_mut() {
+            // A command that exits without reading closes the pipe
+            let _ = stdin.write_all(input.as_bytes());
+        }
+    });
+    let mut stdout = child.stdout.take();
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(stdout) = stdout.as_mut() {
+            let _ = stdout.read_to_string(&mut output);
+        }
+        output
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= COMMAND_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("timed out after {}s", COMMAND_TIMEOUT.as_secs()),
+            ));
+        }
+        std::thread::sleep(COMMAND_POLL);
+    };
+    let _ = writer.join();
+    let output = reader.join().unwrap_or_default();
+
+    if status.success() {
+        Ok(output)
+    } else {
+        Err(io::Error::other(format!("exit status {status}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_strip_thinking() {
+        let output = "<thinking>\nplan\nmore plan\n</thinking>\n\n\nDone.\n<think>x</think>\nBye";
+        assert_eq!(strip_thinking(output), "\n\nDone.\n\nBye");
+    }
+
+    #[test]
+    fn test_table_to_events() {
+        let output = "\
+Findings:
+
+| topic | file | issue |
+|-------|------|-------|
+| review.finding | src/a.rs | unwrap on user input |
+| not a topic! | src/b.rs | skipped |
+
+```
+| ignored | table |
+|---|---|
+| in | fence |
+```
+";
+        let events = table_events(output, None);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].topic, "review.finding");
+        assert_eq!(
+            events[0].payload.as_deref(),
+            Some("file: src/a.rs\nissue: unwrap on user input")
+        );
+
+        let output = "| payload |\n| :---: |\n| first |\n| second |\n";
+        let events = table_events(output, Some("task.add"));
+        let payloads: Vec<_> = events.iter().filter_map(|e| e.payload.as_deref()).collect();
+        assert_eq!(payloads, vec!["first", "second"]);
+        assert!(events.iter().all(|e| e.topic == "task.add"));
+    }
+
+    #[test]
+    fn test_apply_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("greeting.txt"), "hello\n").unwrap();
+        let output = "\
+Here is the change:
+
+```diff
+--- a/greeting.txt
++++ b/greeting.txt
+@@ -1 +1 @@
+-hello
++hello, world
+```
+";
+        let pipeline = [Postprocessor::ApplyDiff];
+        let processed = run(&pipeline, output, dir.path(), "builder");
+        assert!(processed.events.is_empty());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("greeting.txt")).unwrap(),
+            "hello, world\n"
+        );
+
+        // Running it again finds the diff already applied
+        let processed = run(&pipeline, output, dir.path(), "builder");
+        assert!(processed.events.is_empty());
+
+        fs::write(dir.path().join("greeting.txt"), "goodbye\n").unwrap();
+        let processed = run(&pipeline, output, dir.path(), "builder");
+        assert_eq!(processed.events.len(), 1);
+        assert_eq!(processed.events[0].topic, DIFF_REJECTED_TOPIC);
+        let payload = processed.events[0].payload.as_deref().unwrap();
+        assert!(payload.contains("+hello, world"));
+    }
+
+    #[test]
+    fn test_pipeline_runs_steps_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let pipeline = [
+            Postprocessor::StripThinking,
+            Postprocessor::Command("tr a-z A-Z; echo \"by $RALPH_HAT\"".to_string()),
+            Postprocessor::Command("exit 3".to_string()),
+        ];
+        let processed = run(
+            &pipeline,
+            "<thinking>secret</thinking>done",
+            dir.path(),
+            "builder",
+        );
+        assert_eq!(processed.output, "DONEby builder\n");
+    }
+}
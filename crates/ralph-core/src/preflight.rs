@@ -110,6 +110,7 @@ impl PreflightRunner {
                 Box::new(GitCleanCheck),
                 Box::new(PathsExistCheck),
                 Box::new(ToolsInPathCheck::default()),
+                Box::new(ContainerRuntimeCheck),
                 Box::new(SpecCompletenessCheck),
             ],
         }
@@ -381,6 +382,63 @@ impl PreflightCheck for ToolsInPathCheck {
     }
 }
 
+struct ContainerRuntimeCheck;
+
+#[async_trait]
+impl PreflightCheck for ContainerRuntimeCheck {
+    fn name(&self) -> &'static str {
+        "environment"
+    }
+
+    async fn run(&self, config: &RalphConfig) -> CheckResult {
+        let mut runtimes: Vec<&str> = config
+            .environment
+            .iter()
+            .chain(
+                config
+                    .hats
+                    .values()
+                    .filter_map(|hat| hat.environment.as_ref()),
+            )
+            .map(|env| env.runtime.as_str())
+            .chain(
+                config
+                    .cli
+                    .sandbox
+                    .iter()
+                    .map(|sandbox| sandbox.runtime.as_str()),
+            )
+            .collect();
+        runtimes.sort_unstable();
+        runtimes.dedup();
+
+        if runtimes.is_empty() {
+            return CheckResult::pass(self.name(), "No container environment configured");
+        }
+
+        let missing: Vec<&str> = runtimes
+            .iter()
+            .copied()
+            .filter(|runtime| find_executable(runtime).is_none())
+            .collect();
+        if missing.is_empty() {
+            CheckResult::pass(
+                self.name(),
+                format!("Container runtime available ({})", runtimes.join(", ")),
+            )
+        } else {
+            CheckResult::fail(
+                self.name(),
+                "Container runtime not found",
+                format!(
+                    "Install {} or remove the 'environment' and 'cli.sandbox' config",
+                    missing.join(", ")
+                ),
+            )
+        }
+    }
+}
+
 struct SpecCompletenessCheck;
 
 #[async_trait]
@@ -942,6 +1000,30 @@ mod tests {
         assert!(result.message.unwrap_or_default().contains("Missing"));
     }
 
+    #[tokio::test]
+    async fn environment_check_fails_when_runtime_missing() {
+        let mut config = RalphConfig::default();
+        let check = ContainerRuntimeCheck;
+        assert_eq!(check.run(&config).await.status, CheckStatus::Pass);
+
+        config.environment = Some(crate::config::EnvironmentConfig {
+            image: "rust:1.80".to_string(),
+            mounts: Vec::new(),
+            env: Vec::new(),
+            runtime: "definitely-not-a-runtime".to_string(),
+            run_args: Vec::new(),
+        });
+        let result = check.run(&config).await;
+
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert!(
+            result
+                .message
+                .unwrap_or_default()
+                .contains("definitely-not-a-runtime")
+        );
+    }
+
     #[tokio::test]
     async fn tools_check_warns_on_missing_optional_tools() {
         let temp = tempfile::tempdir().expect("tempdir");
@@ -0,0 +1,253 @@
+//! Localized prompt packs.
+//!
+//! Ralph's prompts are English by default. A prompt pack swaps the section
+//! headers, and optionally whole sections, for another language. Event
+//! topics, `ralph` commands, and code identifiers stay in English, so routing
+//! and tooling work the same in every locale. A pack is a directory,
+//! `<prompts.dir>/<locale>/`, with:
+//!
+//! - `headers.yml`, mapping English header keys (`ORIENTATION`, `WORKFLOW`,
+//!   ...) to localized headers. Missing keys keep the English header.
+//! - `<section>.md` files, each replacing one section's body. `{name}`
+//!   placeholders are filled in when the prompt is built.
+//!
+//! See [`PromptsConfig`](crate::PromptsConfig).
+
+use crate::config::PromptsConfig;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Locale of the built-in prompts.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Header keys a pack can localize.
+pub const HEADER_KEYS: &[&str] = &[
+    "ORIENTATION",
+    "SCRATCHPAD",
+    "STATE MANAGEMENT",
+    "AVAILABLE CONTEXT FILES",
+    "GUARDRAILS",
+    "OBJECTIVE",
+    "PENDING EVENTS",
+    "WORKFLOW",
+    "HATS",
+    "ACTIVE HAT",
+    "EVENT WRITING",
+    "DONE",
+    "ROBOT GUIDANCE",
+    "QUEUE BACKPRESSURE",
+    "PHASE",
+    "BLOCKED",
+    "PREVIOUS ITERATION",
+    "PREVIOUS SESSION",
+    "AMENDMENTS",
+];
+
+/// Sections a pack can replace, with the placeholders each template may use.
+pub const SECTION_KEYS: &[(&str, &[&str])] = &[
+    ("orientation", &[]),
+    ("objective", &["objective"]),
+    ("workflow-solo", &["scratchpad"]),
+    ("workflow-coordinator", &["scratchpad"]),
+    ("workflow-fast-path", &["starting_event"]),
+    ("event-writing", &["scratchpad", "event_example"]),
+    ("done", &["completion_promise", "objective"]),
+];
+
+/// Errors from loading a prompt pack.
+#[derive(Debug, thiserror::Error)]
+pub enum PromptPackError {
+    #[error("Prompt pack for locale '{locale}' not found at {}", path.display())]
+    NotFound { locale: String, path: PathBuf },
+
+    #[error("Failed to read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid {}: {source}", path.display())]
+    Headers {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+
+    #[error("Unknown prompt header '{key}' in {}; known headers: {}", path.display(), HEADER_KEYS.join(", "))]
+    UnknownHeader { key: String, path: PathBuf },
+
+    #[error("Unknown prompt section '{}'; known sections: {}", path.display(), section_names())]
+    UnknownSection { path: PathBuf },
+}
+
+fn section_names() -> String {
+    SECTION_KEYS
+        .iter()
+        .map(|(key, _)| format!("{key}.md"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Localized headers and section templates. The default pack is English.
+#[derive(Debug, Clone, Default)]
+pub struct PromptPack {
+    headers: HashMap<String, String>,
+    sections: HashMap<String, String>,
+}
+
+impl PromptPack {
+    /// Loads the pack for `config.locale` from `config.dir` under `workspace`.
+    ///
+    /// The default locale needs no directory; with one, it can override
+    /// individual headers and sections like any other pack.
+    pub fn load(config: &PromptsConfig, workspace: &Path) -> Result<Self, PromptPackError> {
+        let path = workspace.join(&config.dir).join(&config.locale);
+        if !path.is_dir() {
+            if config.locale == DEFAULT_LOCALE {
+                return Ok(Self::default());
+            }
+            return Err(PromptPackError::NotFound {
+                locale: config.locale.clone(),
+                path,
+            });
+        }
+        Self::from_dir(&path)
+    }
+
+    /// Loads a pack directory.
+    pub fn from_dir(dir: &Path) -> Result<Self, PromptPackError> {
+        let read = |path: PathBuf| {
+            fs::read_to_string(&path).map_err(|source| PromptPackError::Read { path, source })
+        };
+        let mut pack = Self::default();
+
+        let headers_path = dir.join("headers.yml");
+        if headers_path.is_file() {
+            let raw = read(headers_path.clone())?;
+            let headers: HashMap<String, String> =
+                serde_yaml::from_str(&raw).map_err(|source| PromptPackError::Headers {
+                    path: headers_path.clone(),
+                    source,
+                })?;
+            if let Some(key) = headers
+                .keys()
+                .find(|key| !HEADER_KEYS.contains(&key.as_str()))
+            {
+                return Err(PromptPackError::UnknownHeader {
+                    key: key.clone(),
+                    path: headers_path,
+                });
+            }
+            pack.headers = headers;
+        }
+
+        let entries = fs::read_dir(dir).map_err(|source| PromptPackError::Read {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+            let key = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .to_string();
+            if !SECTION_KEYS.iter().any(|(known, _)| *known == key) {
+                return Err(PromptPackError::UnknownSection { path });
+            }
+            let template = read(path)?;
+            pack.sections.insert(key, template);
+        }
+
+        Ok(pack)
+    }
+
+    /// The header for `key`, localized if the pack has it.
+    pub fn header<'a>(&'a self, key: &'a str) -> &'a str {
+        self.headers.get(key).map_or(key, String::as_str)
+    }
+
+    /// Renders the pack's template for `section`, if it has one.
+    ///
+    /// Each `{name}` in the template is replaced with the value for `name`
+    /// in `vars`. The result ends with a blank line, like the built-in
+    /// sections.
+    pub fn section(&self, section: &str, vars: &[(&str, &str)]) -> Option<String> {
+        let template = self.sections.get(section)?;
+        let mut rendered = vars
+            .iter()
+            .fold(template.trim_end().to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{name}}}"), value)
+            });
+        rendered.push_str("\n\n");
+        Some(rendered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(locale: &str) -> PromptsConfig {
+        PromptsConfig {
+            locale: locale.to_string(),
+            ..PromptsConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_default_locale_needs_no_pack() {
+        let temp_dir = TempDir::new().unwrap();
+        let pack = PromptPack::load(&config("en"), temp_dir.path()).unwrap();
+        assert_eq!(pack.header("WORKFLOW"), "WORKFLOW");
+        assert!(pack.section("done", &[]).is_none());
+
+        let err = PromptPack::load(&config("de"), temp_dir.path()).unwrap_err();
+        assert!(err.to_string().contains("locale 'de' not found"));
+    }
+
+    #[test]
+    fn test_pack_localizes_headers_and_sections() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join(".ralph/prompts/de");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("headers.yml"),
+            "WORKFLOW: ARBEITSABLAUF\nGUARDRAILS: LEITPLANKEN\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("done.md"),
+            "Sende `{completion_promise}` mit `ralph emit`, wenn das Ziel erreicht ist.\n",
+        )
+        .unwrap();
+
+        let pack = PromptPack::load(&config("de"), temp_dir.path()).unwrap();
+        assert_eq!(pack.header("WORKFLOW"), "ARBEITSABLAUF");
+        assert_eq!(pack.header("OBJECTIVE"), "OBJECTIVE");
+        assert_eq!(
+            pack.section("done", &[("completion_promise", "LOOP_COMPLETE")])
+                .unwrap(),
+            "Sende `LOOP_COMPLETE` mit `ralph emit`, wenn das Ziel erreicht ist.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("headers.yml"), "WORKFLOWS: X\n").unwrap();
+        let err = PromptPack::from_dir(temp_dir.path()).unwrap_err();
+        assert!(
+            matches!(err, PromptPackError::UnknownHeader { ref key, .. } if key == "WORKFLOWS")
+        );
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("workflow.md"), "...").unwrap();
+        let err = PromptPack::from_dir(temp_dir.path()).unwrap_err();
+        assert!(err.to_string().contains("workflow-solo.md"));
+    }
+}
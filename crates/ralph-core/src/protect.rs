@@ -0,0 +1,270 @@
+//! Protected workspace paths.
+//!
+//! Paths listed under `protect:` must not change during a loop. Guardrail
+//! prose alone doesn't stop an agent from touching them, so after each
+//! iteration the orchestrator diffs the workspace against the commit the
+//! loop started from and reverts every protected path that changed,
+//! committed or not. A `protect.violation` event tells the next iteration
+//! what was undone and why.
+//!
+//! Patterns are globs: `*` and `?` stay within one path segment, `**` spans
+//! segments, and a trailing `/` means everything below a directory. Like
+//! `.gitignore`, a pattern without a `/` matches a file name at any depth.
+//!
+//! ```yaml
+//! protect: [Cargo.lock, .github/**, migrations/]
+//! ```
+
+use crate::git_ops::{GitOpsError, run_git};
+use regex::Regex;
+use std::path::Path;
+use std::process::Command;
+
+/// Topic published after protected paths are reverted.
+pub const VIOLATION_TOPIC: &str = "protect.violation";
+
+/// A protected path that an iteration changed and that was reverted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevertedPath {
+    /// Path relative to the workspace root.
+    pub path: String,
+    /// The `protect` pattern it matched.
+    pub pattern: String,
+}
+
+/// Compiled `protect` patterns.
+#[derive(Debug, Clone, Default)]
+pub struct ProtectedPaths {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl ProtectedPaths {
+    /// Compiles the configured patterns.
+    pub fn new(patterns: &[String]) -> Self {
+        let patterns = patterns
+            .iter()
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| (p.clone(), glob_to_regex(p.trim())))
+            .collect();
+        Self { patterns }
+    }
+
+    /// Returns true when nothing is protected.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Returns the first pattern protecting `path`, if any.
+    pub fn matching_pattern(&self, path: &str) -> Option<&str> {
+        self.patterns
+            .iter()
+            .find(|(_, re)| re.is_match(path))
+            .map(|(pattern, _)| pattern.as_str())
+    }
+
+    /// Reverts every protected path that changed in `workspace` since `base`.
+    ///
+    /// Paths that existed at `base` are checked out from it; paths created
+    /// since are deleted. Returns what was reverted.
+    pub fn enforce(&self, workspace: &Path, base: &str) -> Result<Vec<RevertedPath>, GitOpsError> {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut reverted = Vec::new();
+        for path in changed_paths(workspace, base)? {
+            let Some(pattern) = self.matching_pattern(&path) else {
+                continue;
+            };
+            revert_path(workspace, base, &path)?;
+            reverted.push(RevertedPath {
+                path,
+                pattern: pattern.to_string(),
+            });
+        }
+        Ok(reverted)
+    }
+}
+
+/// Builds the `protect.violation` payload for reverted paths.
+pub fn violation_payload(reverted: &[RevertedPath]) -> String {
+    let mut payload = String::from(
+        "Protected paths were changed and have been reverted. \
+         Do not modify them; find another way or ask for human guidance.\n",
+    );
+    for item in reverted {
+        payload.push_str(&format!(
+            "- {} (protected by `{}`)\n",
+            item.path, item.pattern
+        ));
+    }
+    payload
+}
+
+/// Translates a protect glob into an anchored regex over relative paths.
+fn glob_to_regex(pattern: &str) -> Regex {
+    let mut pattern = pattern.trim_start_matches("./").to_string();
+    if pattern.ends_with('/') {
+        pattern.push_str("**");
+    }
+    // Without a slash, the pattern names a file at any depth
+    let anchored = pattern.trim_end_matches("/**").contains('/');
+    let pattern = pattern.trim_start_matches('/');
+
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).unwrap_or_else(|_| Regex::new("^$").unwrap())
+}
+
+/// Lists paths that differ from `base`: committed, staged, unstaged, and untracked.
+fn changed_paths(workspace: &Path, base: &str) -> Result<Vec<String>, GitOpsError> {
+    let mut paths = git_lines(
+        workspace,
+        &["diff", "--name-only", "--no-renames", "-z", base],
+    )?;
+    for path in git_lines(
+        workspace,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )? {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+fn revert_path(workspace: &Path, base: &str, path: &str) -> Result<(), GitOpsError> {
+    let existed = Command::new("git")
+        .args(["cat-file", "-e", &format!("{base}:{path}")])
+        .current_dir(workspace)
+        .output()?
+        .status
+        .success();
+
+    if existed {
+        git_lines(workspace, &["checkout", base, "--", path])?;
+    } else {
+        git_lines(
+            workspace,
+            &["rm", "-q", "--cached", "--ignore-unmatch", "--", path],
+        )?;
+        let file = workspace.join(path);
+        if file.exists() {
+            std::fs::remove_file(file)?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs git and splits its NUL-separated output.
+fn git_lines(workspace: &Path, args: &[&str]) -> Result<Vec<String>, GitOpsError> {
+    Ok(run_git(workspace, args)?
+        .split('\0')
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn protected(patterns: &[&str]) -> ProtectedPaths {
+        ProtectedPaths::new(
+            &patterns
+                .iter()
+                .map(|p| (*p).to_string())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        if let Err(err) = run_git(dir, args) {
+            panic!("git {args:?} failed: {err}");
+        }
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        let paths = protected(&["Cargo.lock", ".github/**", "migrations/", "docs/*.md"]);
+
+        assert_eq!(paths.matching_pattern("Cargo.lock"), Some("Cargo.lock"));
+        assert_eq!(
+            paths.matching_pattern("crates/a/Cargo.lock"),
+            Some("Cargo.lock")
+        );
+        assert!(paths.matching_pattern(".github/workflows/ci.yml").is_some());
+        assert!(paths.matching_pattern("migrations/001_init.sql").is_some());
+        assert!(paths.matching_pattern("docs/guide.md").is_some());
+        assert!(paths.matching_pattern("docs/guide/intro.md").is_none());
+        assert!(paths.matching_pattern("src/migrations.rs").is_none());
+        assert!(paths.matching_pattern("Cargo.toml").is_none());
+    }
+
+    #[test]
+    fn test_enforce_reverts_committed_modified_and_new_paths() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        git(dir, &["init", "-q"]);
+        git(dir, &["config", "user.email", "test@example.com"]);
+        git(dir, &["config", "user.name", "Test"]);
+        std::fs::write(dir.join("Cargo.lock"), "original").unwrap();
+        std::fs::write(dir.join("main.rs"), "fn main() {}").unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-q", "-m", "init"]);
+        let base = crate::git_ops::get_head_sha(dir).unwrap();
+
+        // A committed change, an unstaged change, and a new file.
+        std::fs::write(dir.join("Cargo.lock"), "changed").unwrap();
+        git(dir, &["commit", "-q", "-am", "bump lock"]);
+        std::fs::write(dir.join("main.rs"), "fn main() { run() }").unwrap();
+        std::fs::create_dir_all(dir.join(".github")).unwrap();
+        std::fs::write(dir.join(".github/ci.yml"), "on: push").unwrap();
+
+        let reverted = protected(&["Cargo.lock", ".github/**"])
+            .enforce(dir, &base)
+            .unwrap();
+
+        let mut paths: Vec<_> = reverted.iter().map(|r| r.path.as_str()).collect();
+        paths.sort_unstable();
+        assert_eq!(paths, vec![".github/ci.yml", "Cargo.lock"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("Cargo.lock")).unwrap(),
+            "original"
+        );
+        assert!(!dir.join(".github/ci.yml").exists());
+        // Unprotected work is left alone.
+        assert_eq!(
+            std::fs::read_to_string(dir.join("main.rs")).unwrap(),
+            "fn main() { run() }"
+        );
+    }
+
+    #[test]
+    fn test_violation_payload_lists_paths() {
+        let payload = violation_payload(&[RevertedPath {
+            path: "Cargo.lock".to_string(),
+            pattern: "Cargo.lock".to_string(),
+        }]);
+        assert!(payload.contains("- Cargo.lock (protected by `Cargo.lock`)"));
+    }
+}
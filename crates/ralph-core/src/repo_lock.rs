@@ -0,0 +1,466 @@
+//! Cooperative repo lock for clones shared between users.
+//!
+//! [`LoopLock`](crate::LoopLock) keeps one primary loop per workspace on a
+//! single machine and sends later loops into worktrees. That's right for one
+//! person running parallel loops, but when two people run Ralph in the same
+//! clone (a shared dev box, a network mount) their runs collide. The repo
+//! lock at `.ralph/agent/LOCK` records who is running — pid, host, user, and
+//! session — so a run from anyone else is refused until the owner finishes or
+//! the lock goes stale, or is taken over with `ralph run --force`.
+//!
+//! The owner refreshes a heartbeat in the lock while it runs. A lock is stale
+//! when its process is gone (checked on the same host) or its heartbeat is
+//! older than [`STALE_AFTER`] (the only signal from other hosts).
+
+use crate::file_lock::{FileLock, LockGuard};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How often the owner refreshes its heartbeat.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Heartbeat age after which another host's lock is considered stale.
+pub const STALE_AFTER: Duration = Duration::from_mins(5);
+
+/// Who holds the repo lock.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoLockOwner {
+    /// Process ID of the run.
+    pub pid: u32,
+
+    /// Host the run is on.
+    pub host: String,
+
+    /// User who started the run.
+    pub user: String,
+
+    /// Session id of the run.
+    pub session: String,
+
+    /// Short summary of the prompt.
+    pub prompt: String,
+
+    /// When the lock was acquired.
+    pub started: DateTime<Utc>,
+
+    /// Last time the owner refreshed the lock.
+    pub heartbeat: DateTime<Utc>,
+}
+
+impl RepoLockOwner {
+    /// Describes this process as a lock owner.
+    pub fn current(session: impl Into<String>, prompt: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            pid: std::process::id(),
+            host: current_host(),
+            user: current_user(),
+            session: session.into(),
+            prompt: prompt.into(),
+            started: now,
+            heartbeat: now,
+        }
+    }
+
+    /// Returns true if this owner is the current user on the current host.
+    pub fn is_current_user(&self) -> bool {
+        self.host == current_host() && self.user == current_user()
+    }
+
+    /// Returns true if the owner's run is evidently gone: its process has
+    /// exited (same host) or its heartbeat stopped more than [`STALE_AFTER`] ago.
+    pub fn is_stale(&self) -> bool {
+        if self.host == current_host() && !is_process_alive(self.pid) {
+            return true;
+        }
+        let age = Utc::now().signed_duration_since(self.heartbeat);
+        age.to_std().is_ok_and(|age| age > STALE_AFTER)
+    }
+
+    /// One-line description for messages, e.g. `alice@devbox (PID 4242, session s-1)`.
+    pub fn describe(&self) -> String {
+        format!(
+            "{}@{} (PID {}, session {})",
+            self.user, self.host, self.pid, self.session
+        )
+    }
+}
+
+/// Errors from the repo lock.
+#[derive(Debug, thiserror::Error)]
+pub enum RepoLockError {
+    /// Another user's run holds the lock.
+    #[error(
+        "Ralph is already running in this repo as {}, since {}. \
+         Wait for it to finish, or take over with `ralph run --force`.",
+        .0.describe(),
+        .0.started.format("%Y-%m-%d %H:%M UTC")
+    )]
+    Held(Box<RepoLockOwner>),
+
+    /// IO error reading or writing the lock.
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Held repo lock. Stops the heartbeat and removes the lock when dropped,
+/// unless another run has taken it over.
+#[derive(Debug)]
+pub struct RepoLockGuard {
+    path: PathBuf,
+    session: String,
+    lost: Arc<AtomicBool>,
+    stop: Option<mpsc::Sender<()>>,
+    heartbeat: Option<JoinHandle<()>>,
+}
+
+impl RepoLockGuard {
+    /// Returns the owner that took the lock over, if one has.
+    ///
+    /// Checked by the loop between iterations so a run that was taken over
+    /// with `--force` stops instead of colliding with the new owner.
+    pub fn taken_over_by(&self) -> Option<RepoLockOwner> {
+        if !self.lost.load(Ordering::Relaxed) {
+            return None;
+        }
+        RepoLock::read_path(&self.path)
+            .ok()
+            .flatten()
+            .filter(|owner| owner.session != self.session)
+    }
+}
+
+impl Drop for RepoLockGuard {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(handle) = self.heartbeat.take() {
+            let _ = handle.join();
+        }
+        let _lock = exclusive(&self.path);
+        if let Ok(Some(owner)) = RepoLock::read_path(&self.path)
+            && owner.session == self.session
+        {
+            let _ = fs::remove_file(&self.path);
+            tracing::debug!("Released repo lock at {}", self.path.display());
+        }
+    }
+}
+
+/// The repo lock.
+pub struct RepoLock;
+
+impl RepoLock {
+    /// The lock file, relative to the repository root.
+    pub const LOCK_FILE: &'static str = ".ralph/agent/LOCK";
+
+    /// Acquires the repo lock for `owner`.
+    ///
+    /// Returns `Ok(None)` when the lock is held by another live run of the
+    /// same user on the same host: that's one person's parallel loops, which
+    /// [`LoopLock`](crate::LoopLock) coordinates. A stale lock is replaced.
+    /// A live lock held by anyone else is an error unless `force` is set, in
+    /// which case the lock is taken over and the previous owner stops at its
+    /// next iteration boundary.
+    ///
+    /// The check and the write happen under a [`FileLock`], so two runs
+    /// racing for the lock can't both get it.
+    pub fn acquire(
+        repo_root: impl AsRef<Path>,
+        owner: RepoLockOwner,
+        force: bool,
+    ) -> Result<Option<RepoLockGuard>, RepoLockError> {
+        let path = repo_root.as_ref().join(Self::LOCK_FILE);
+        let lock = exclusive(&path)?;
+
+        match Self::read_path(&path)? {
+            Some(existing) => {
+                if existing.is_stale() {
+                    tracing::info!("Replacing stale repo lock held by {}", existing.describe());
+                } else if existing.is_current_user() && !force {
+                    tracing::debug!("Repo lock held by our own run {}", existing.describe());
+                    return Ok(None);
+                } else if force {
+                    tracing::warn!("Taking over repo lock from {}", existing.describe());
+                } else {
+                    return Err(RepoLockError::Held(Box::new(existing)));
+                }
+                write_owner(&path, &owner)?;
+            }
+            // An unreadable lock file is garbage, not an owner
+            None if path.exists() => write_owner(&path, &owner)?,
+            None => create_owner(&path, &owner)?,
+        }
+        drop(lock);
+
+        let session = owner.session.clone();
+        let lost = Arc::new(AtomicBool::new(false));
+        let (stop, stopped) = mpsc::channel();
+        let heartbeat = {
+            let path = path.clone();
+            let lost = Arc::clone(&lost);
+            std::thread::Builder::new()
+                .name("repo-lock-heartbeat".to_string())
+                .spawn(move || heartbeat_loop(&path, owner, &lost, &stopped))?
+        };
+
+        Ok(Some(RepoLockGuard {
+            path,
+            session,
+            lost,
+            stop: Some(stop),
+            heartbeat: Some(heartbeat),
+        }))
+    }
+
+    /// Reads the current lock owner, if any.
+    pub fn read_existing(
+        repo_root: impl AsRef<Path>,
+    ) -> Result<Option<RepoLockOwner>, RepoLockError> {
+        Ok(Self::read_path(&repo_root.as_ref().join(Self::LOCK_FILE))?)
+    }
+
+    /// Reads a lock file. A missing or unparseable file means no owner.
+    fn read_path(path: &Path) -> io::Result<Option<RepoLockOwner>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents).ok()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Refreshes the heartbeat until stopped, flagging a takeover.
+fn heartbeat_loop(
+    path: &Path,
+    mut owner: RepoLockOwner,
+    lost: &AtomicBool,
+    stopped: &mpsc::Receiver<()>,
+) {
+    while let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(HEARTBEAT_INTERVAL) {
+        let _lock = exclusive(path);
+        match RepoLock::read_path(path) {
+            Ok(Some(current)) if current.session != owner.session => {
+                lost.store(true, Ordering::Relaxed);
+                return;
+            }
+            // Ours, or deleted from under us (e.g. by `ralph clean`): rewrite it
+            Ok(_) => {
+                owner.heartbeat = Utc::now();
+                if let Err(e) = write_owner(path, &owner) {
+                    tracing::warn!("Failed to refresh repo lock heartbeat: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to read repo lock: {}", e),
+        }
+    }
+}
+
+/// Locks the repo lock's sibling `.lock` file, where the platform supports it.
+fn exclusive(path: &Path) -> io::Result<Option<LockGuard>> {
+    match FileLock::new(path)?.exclusive() {
+        Ok(guard) => Ok(Some(guard)),
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes `owner` to a new lock file, failing if one already exists.
+fn create_owner(path: &Path, owner: &RepoLockOwner) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(owner).map_err(io::Error::other)?;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    io::Write::write_all(&mut file, json.as_bytes())
+}
+
+/// Writes `owner` to `path` atomically.
+fn write_owner(path: &Path, owner: &RepoLockOwner) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!("tmp-{}", owner.pid));
+    let json = serde_json::to_string_pretty(owner).map_err(io::Error::other)?;
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)
+}
+
+fn current_host() -> String {
+    #[cfg(unix)]
+    if let Ok(host) = nix::unistd::gethostname()
+        && let Some(host) = host.to_str()
+    {
+        return host.to_string();
+    }
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[cfg(unix)]
+fn is_process_alive(pid: u32) -> bool {
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+    i32::try_from(pid).is_ok_and(|pid| {
+        // EPERM means the process exists but belongs to another user
+        matches!(
+            kill(Pid::from_raw(pid), None),
+            Ok(()) | Err(nix::errno::Errno::EPERM)
+        )
+    })
+}
+
+#[cfg(not(unix))]
+fn is_process_alive(_pid: u32) -> bool {
+    // No cheap liveness check; rely on the heartbeat.
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn other_user(session: &str) -> RepoLockOwner {
+        RepoLockOwner {
+            user: "someone-else".to_string(),
+            ..RepoLockOwner::current(session, "their task")
+        }
+    }
+
+    #[test]
+    fn test_acquire_writes_owner_and_release_removes_it() {
+        let dir = TempDir::new().unwrap();
+        let guard = RepoLock::acquire(dir.path(), RepoLockOwner::current("s-1", "task"), false)
+            .unwrap()
+            .expect("lock acquired");
+
+        let owner = RepoLock::read_existing(dir.path()).unwrap().unwrap();
+        assert_eq!(owner.session, "s-1");
+        assert_eq!(owner.pid, std::process::id());
+        assert!(owner.is_current_user());
+        assert!(!owner.is_stale());
+        assert!(guard.taken_over_by().is_none());
+
+        drop(guard);
+        assert!(RepoLock::read_existing(dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_same_user_runs_share_the_lock() {
+        let dir = TempDir::new().unwrap();
+        let _guard = RepoLock::acquire(dir.path(), RepoLockOwner::current("s-1", "a"), false)
+            .unwrap()
+            .unwrap();
+
+        let second =
+            RepoLock::acquire(dir.path(), RepoLockOwner::current("s-2", "b"), false).unwrap();
+        assert!(second.is_none());
+        let owner = RepoLock::read_existing(dir.path()).unwrap().unwrap();
+        assert_eq!(owner.session, "s-1");
+    }
+
+    #[test]
+    fn test_other_user_is_refused_unless_forced() {
+        let dir = TempDir::new().unwrap();
+        write_owner(&dir.path().join(RepoLock::LOCK_FILE), &other_user("theirs")).unwrap();
+
+        let err =
+            RepoLock::acquire(dir.path(), RepoLockOwner::current("mine", "t"), false).unwrap_err();
+        assert!(matches!(&err, RepoLockError::Held(owner) if owner.session == "theirs"));
+        assert!(err.to_string().contains("someone-else@"));
+        assert!(err.to_string().contains("--force"));
+
+        let guard = RepoLock::acquire(dir.path(), RepoLockOwner::current("mine", "t"), true)
+            .unwrap()
+            .unwrap();
+        let owner = RepoLock::read_existing(dir.path()).unwrap().unwrap();
+        assert_eq!(owner.session, "mine");
+        drop(guard);
+    }
+
+    #[test]
+    fn test_stale_locks_are_replaced() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(RepoLock::LOCK_FILE);
+
+        let silent = RepoLockOwner {
+            host: "elsewhere".to_string(),
+            heartbeat: Utc::now() - chrono::Duration::minutes(10),
+            ..other_user("silent")
+        };
+        assert!(silent.is_stale());
+        write_owner(&path, &silent).unwrap();
+        let guard =
+            RepoLock::acquire(dir.path(), RepoLockOwner::current("mine", "t"), false).unwrap();
+        assert!(guard.is_some());
+        drop(guard);
+
+        #[cfg(unix)]
+        {
+            let exited = RepoLockOwner {
+                pid: i32::MAX as u32,
+                ..other_user("exited")
+            };
+            assert!(exited.is_stale());
+        }
+    }
+
+    #[test]
+    fn test_racing_runs_get_one_lock() {
+        let dir = TempDir::new().unwrap();
+        let barrier = Arc::new(std::sync::Barrier::new(8));
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let root = dir.path().to_path_buf();
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    RepoLock::acquire(&root, RepoLockOwner::current(format!("s-{i}"), "t"), false)
+                        .unwrap()
+                })
+            })
+            .collect();
+        let guards: Vec<_> = handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .collect();
+
+        assert_eq!(guards.len(), 1);
+    }
+
+    #[test]
+    fn test_taken_over_lock_is_left_to_the_new_owner() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(RepoLock::LOCK_FILE);
+        let guard = RepoLock::acquire(dir.path(), RepoLockOwner::current("mine", "t"), false)
+            .unwrap()
+            .unwrap();
+
+        write_owner(&path, &other_user("theirs")).unwrap();
+        guard.lost.store(true, Ordering::Relaxed);
+        assert_eq!(guard.taken_over_by().unwrap().session, "theirs");
+
+        drop(guard);
+        let owner = RepoLock::read_existing(dir.path()).unwrap().unwrap();
+        assert_eq!(owner.session, "theirs");
+    }
+}
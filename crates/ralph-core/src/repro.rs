@@ -0,0 +1,255 @@
+//! Per-iteration reproducibility manifests.
+//!
+//! Before each iteration runs, the runner records what went into it (the
+//! commit, any uncommitted changes, the backend and model, the config, and
+//! the prompt) as a `ralph.iteration_manifest` line in the events file, and
+//! saves the rendered prompt next to the iteration's output. `ralph repro`
+//! reads the manifest back to rebuild the iteration's inputs.
+//!
+//! Hashes are 64-bit FNV-1a over length-prefixed parts: stable across builds
+//! and platforms, and meant for spotting drift rather than resisting tampering.
+
+use crate::config::RalphConfig;
+use crate::event_logger::EventRecord;
+use crate::git_ops::run_git_bytes;
+use crate::lifecycle::ITERATION_MANIFEST_TOPIC;
+use ralph_proto::Event;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+/// Longest backend version string kept in a manifest.
+const MAX_VERSION_LEN: usize = 80;
+
+/// Inputs of one iteration, as recorded before it ran.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IterationManifest {
+    pub iteration: u32,
+    pub hat: String,
+    /// Commit checked out when the iteration started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    /// Fingerprint of uncommitted changes; absent when the tree was clean.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dirty: Option<String>,
+    pub backend: String,
+    /// First line of `<backend command> --version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub config_hash: String,
+    pub prompt_hash: String,
+    /// Saved copy of the rendered prompt, relative to the workspace root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_file: Option<String>,
+}
+
+impl IterationManifest {
+    /// Returns the `ralph.iteration_manifest` event carrying this manifest.
+    pub fn to_event(&self) -> Event {
+        let payload = serde_json::to_string(self).unwrap_or_default();
+        Event::new(ITERATION_MANIFEST_TOPIC, payload)
+    }
+
+    /// Returns the manifest for `iteration` in an events file's records.
+    ///
+    /// A restarted loop can record the same iteration twice; the last one wins.
+    pub fn find(records: &[EventRecord], iteration: u32) -> Option<Self> {
+        records
+            .iter()
+            .rev()
+            .filter(|record| record.topic == ITERATION_MANIFEST_TOPIC)
+            .filter_map(|record| serde_json::from_str::<Self>(&record.payload).ok())
+            .find(|manifest| manifest.iteration == iteration)
+    }
+}
+
+/// Hashes `parts` into a 16-digit hex string.
+pub fn fingerprint<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> String {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut hash = OFFSET;
+    for part in parts {
+        // Length prefix keeps ("ab", "c") and ("a", "bc") apart
+        for byte in (part.len() as u64).to_le_bytes().iter().chain(part) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+/// Hashes a rendered prompt.
+pub fn prompt_hash(prompt: &str) -> String {
+    fingerprint([prompt.as_bytes()])
+}
+
+/// Hashes the effective configuration.
+///
+/// Goes through a JSON value so map keys are sorted and the hash doesn't
+/// depend on `HashMap` iteration order.
+pub fn config_hash(config: &RalphConfig) -> String {
+    let canonical = serde_json::to_value(config)
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+    fingerprint([canonical.as_bytes()])
+}
+
+/// Fingerprints the uncommitted changes in `workspace`.
+///
+/// Covers the diff against HEAD plus the paths and contents of untracked
+/// files. Returns `None` when the tree is clean or isn't a git repository.
+pub fn dirty_fingerprint(workspace: &Path) -> Option<String> {
+    let status = run_git_bytes(
+        workspace,
+        &["status", "--porcelain", "-z", "--untracked-files=all"],
+    )
+    .ok()?;
+    if status.is_empty() {
+        return None;
+    }
+    let diff = run_git_bytes(workspace, &["diff", "HEAD", "--binary"]).ok()?;
+
+    let mut untracked: Vec<(Vec<u8>, Vec<u8>)> = status
+        .split(|byte| *byte == 0)
+        .filter_map(|entry| entry.strip_prefix(b"?? "))
+        .map(|path| {
+            let contents = std::str::from_utf8(path)
+                .ok()
+                .and_then(|path| std::fs::read(workspace.join(path)).ok())
+                .unwrap_or_default();
+            (path.to_vec(), contents)
+        })
+        .collect();
+    untracked.sort();
+
+    let parts = std::iter::once(diff.as_slice()).chain(
+        untracked
+            .iter()
+            .flat_map(|(path, contents)| [path.as_slice(), contents.as_slice()]),
+    );
+    Some(fingerprint(parts))
+}
+
+/// Returns the first line `<command> --version` prints, if it runs.
+pub fn backend_version(command: &str) -> Option<String> {
+    let output = Command::new(command).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?;
+    Some(line.chars().take(MAX_VERSION_LEN).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn manifest(iteration: u32, prompt: &str) -> IterationManifest {
+        IterationManifest {
+            iteration,
+            hat: "builder".to_string(),
+            head: Some("a".repeat(40)),
+            dirty: None,
+            backend: "claude".to_string(),
+            backend_version: Some("2.1.0 (Claude Code)".to_string()),
+            model: Some("opus".to_string()),
+            config_hash: config_hash(&RalphConfig::default()),
+            prompt_hash: prompt_hash(prompt),
+            prompt_file: Some(format!(
+                ".ralph/agent/sessions/primary/iter-{iteration}.prompt"
+            )),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_separates_parts() {
+        assert_ne!(
+            fingerprint([b"ab".as_slice(), b"c"]),
+            fingerprint([b"a".as_slice(), b"bc"])
+        );
+        assert_eq!(fingerprint([b"x".as_slice()]).len(), 16);
+    }
+
+    #[test]
+    fn test_config_hash_tracks_changes() {
+        let config = RalphConfig::default();
+        let mut changed = RalphConfig::default();
+        changed.event_loop.max_iterations += 1;
+
+        assert_eq!(config_hash(&config), config_hash(&RalphConfig::default()));
+        assert_ne!(config_hash(&config), config_hash(&changed));
+    }
+
+    #[test]
+    fn test_manifest_round_trips_through_the_events_file() {
+        let records: Vec<EventRecord> =
+            [manifest(1, "first"), manifest(2, "old"), manifest(2, "new")]
+                .iter()
+                .map(|m| {
+                    EventRecord::new(
+                        m.iteration,
+                        "loop",
+                        &m.to_event(),
+                        None::<&ralph_proto::HatId>,
+                    )
+                })
+                .collect();
+        assert!(
+            records.iter().all(|r| r.payload.len() < 500),
+            "payload would be truncated"
+        );
+
+        assert_eq!(
+            IterationManifest::find(&records, 2),
+            Some(manifest(2, "new"))
+        );
+        assert_eq!(
+            IterationManifest::find(&records, 1),
+            Some(manifest(1, "first"))
+        );
+        assert_eq!(IterationManifest::find(&records, 3), None);
+    }
+
+    #[test]
+    fn test_dirty_fingerprint_covers_untracked_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test"]);
+        fs::write(root.join("tracked.txt"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        assert_eq!(dirty_fingerprint(root), None);
+
+        fs::write(root.join("new.txt"), "a").unwrap();
+        let first = dirty_fingerprint(root).unwrap();
+        fs::write(root.join("new.txt"), "b").unwrap();
+        let second = dirty_fingerprint(root).unwrap();
+        assert_ne!(first, second);
+
+        fs::write(root.join("new.txt"), "a").unwrap();
+        assert_eq!(dirty_fingerprint(root), Some(first));
+    }
+
+    #[test]
+    fn test_dirty_fingerprint_outside_a_repo_is_none() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(dirty_fingerprint(dir.path()), None);
+    }
+}
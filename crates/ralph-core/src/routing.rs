@@ -0,0 +1,158 @@
+//! Per-iteration backend and model routing (`routing:` in ralph.yml).
+//!
+//! A [`RoutingPolicy`] holds the configured [`RouteRule`]s with their `when:`
+//! predicates parsed. Before each iteration the event loop asks it for a
+//! [`RouteDecision`] for the active hat; the first matching rule wins, and no
+//! match means the hat's own `backend:` (or the global backend) is used.
+
+use crate::config::{HatBackend, RouteRule};
+use crate::hat_predicate::{HatPredicate, PredicateContext};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// The backend and model chosen for one iteration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteDecision {
+    /// Name of the rule that matched.
+    pub rule: String,
+    /// Backend override, if the rule sets one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<HatBackend>,
+    /// Model override, if the rule sets one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    name: String,
+    hats: Vec<String>,
+    predicate: Option<HatPredicate>,
+    backend: Option<HatBackend>,
+    model: Option<String>,
+}
+
+/// Ordered routing rules, ready to evaluate.
+#[derive(Debug, Clone, Default)]
+pub struct RoutingPolicy {
+    rules: Vec<CompiledRule>,
+}
+
+impl RoutingPolicy {
+    /// Compiles `routing:` rules from config.
+    ///
+    /// Rules with an unparseable `when:` are skipped; config validation
+    /// rejects them before a loop starts.
+    pub fn from_config(rules: &[RouteRule]) -> Self {
+        let rules = rules
+            .iter()
+            .enumerate()
+            .filter_map(|(index, rule)| {
+                let predicate = match rule.when.as_deref().map(HatPredicate::parse) {
+                    Some(Ok(predicate)) => Some(predicate),
+                    Some(Err(e)) => {
+                        warn!(index, error = %e, "Skipping routing rule with invalid 'when'");
+                        return None;
+                    }
+                    None => None,
+                };
+                Some(CompiledRule {
+                    name: rule
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("routing[{index}]")),
+                    hats: rule.hats.clone(),
+                    predicate,
+                    backend: rule.backend.clone(),
+                    model: rule.model.clone(),
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Returns true if no rules are configured.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Picks the route for `hat`, or `None` to use its default backend.
+    pub fn select(&self, hat: &str, ctx: &PredicateContext<'_>) -> Option<RouteDecision> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                (rule.hats.is_empty() || rule.hats.iter().any(|h| h == hat))
+                    && rule.predicate.as_ref().is_none_or(|p| p.evaluate(ctx))
+            })
+            .map(|rule| RouteDecision {
+                rule: rule.name.clone(),
+                backend: rule.backend.clone(),
+                model: rule.model.clone(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(payload: &str) -> PredicateContext<'_> {
+        PredicateContext {
+            iteration: 1,
+            activations: 1,
+            cost: 0.0,
+            elapsed_secs: 0,
+            topic: "build.task",
+            payload,
+        }
+    }
+
+    fn policy(yaml: &str) -> RoutingPolicy {
+        let rules: Vec<RouteRule> = serde_yaml::from_str(yaml).unwrap();
+        RoutingPolicy::from_config(&rules)
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let policy = policy(
+            r#"
+- name: cheap-planning
+  hats: [planner]
+  model: haiku
+- name: large-context
+  when: "payload_len > 10"
+  backend: gemini
+- hats: [builder]
+  model: opus
+"#,
+        );
+
+        let planner = policy.select("planner", &ctx("x")).unwrap();
+        assert_eq!(planner.rule, "cheap-planning");
+        assert_eq!(planner.model.as_deref(), Some("haiku"));
+        assert!(planner.backend.is_none());
+
+        let big = policy
+            .select("builder", &ctx("a payload well over ten bytes"))
+            .unwrap();
+        assert_eq!(big.rule, "large-context");
+        assert_eq!(big.backend.unwrap().to_cli_backend(), "gemini");
+
+        let small = policy.select("builder", &ctx("short")).unwrap();
+        assert_eq!(small.rule, "routing[2]");
+        assert_eq!(small.model.as_deref(), Some("opus"));
+
+        assert!(policy.select("reviewer", &ctx("short")).is_none());
+    }
+
+    #[test]
+    fn test_invalid_predicate_is_skipped() {
+        let policy = policy(
+            r#"
+- when: "payload > 3"
+  model: haiku
+"#,
+        );
+        assert!(policy.is_empty());
+    }
+}
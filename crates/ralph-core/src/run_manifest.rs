@@ -0,0 +1,326 @@
+//! Run manifest for CI pipelines (`.ralph/ralph-run.json`).
+//!
+//! When a run ends, the runner writes one JSON document describing it: how it
+//! terminated, what it cost, how many iterations ran, the branch and commits
+//! it produced, where its transcript lives, and how many events of each topic
+//! were published. CI uploads it as an artifact, and later pipeline steps read
+//! it instead of scraping logs or `summary.md`.
+//!
+//! The layout is versioned by [`SCHEMA_VERSION`]; within a version, fields are
+//! only ever added.
+
+use crate::cost::Usage;
+use crate::encryption::ArtifactKey;
+use crate::event_logger::EventHistory;
+use crate::event_loop::{LoopState, TerminationReason};
+use crate::git_ops;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File name of the manifest, in the loop's `.ralph/` directory.
+pub const RUN_MANIFEST_FILE: &str = "ralph-run.json";
+
+/// Version of the manifest layout.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// What a manifest is built from.
+#[derive(Debug)]
+pub struct Run<'a> {
+    pub loop_id: &'a str,
+    pub reason: &'a TerminationReason,
+    pub state: &'a LoopState,
+    pub workspace: &'a Path,
+    /// HEAD when the run started; commits after it count as produced.
+    pub base_commit: Option<&'a str>,
+    pub events_path: &'a Path,
+    /// Directory holding the loop's `iter-<n>.prompt` and `iter-<n>.out` files.
+    pub session_dir: &'a Path,
+    /// The `--record-session` file, if one was recorded.
+    pub recording: Option<&'a Path>,
+    /// The forensics bundle written for the run, if any.
+    pub forensics: Option<&'a Path>,
+    /// Opens a sealed events file.
+    pub key: Option<&'a ArtifactKey>,
+}
+
+/// The `ralph-run.json` document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunManifest {
+    pub version: u32,
+    pub ralph_version: String,
+    pub loop_id: String,
+    /// RFC 3339 timestamps.
+    pub started_at: String,
+    pub finished_at: String,
+    pub duration_secs: f64,
+    pub termination: Termination,
+    pub iterations: u32,
+    pub cost: RunCost,
+    /// Activations per hat.
+    pub hats: BTreeMap<String, u32>,
+    pub git: GitSummary,
+    pub transcript: Transcript,
+    pub events: EventCounts,
+    /// Path of the forensics bundle, when the run failed and one was written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forensics: Option<String>,
+}
+
+/// Why the run ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Termination {
+    /// Reason code, as in `loop.terminate` events (e.g. `completed`).
+    pub reason: String,
+    /// Process exit code `ralph run` returns for the reason.
+    pub exit_code: i32,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Spend across the run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunCost {
+    pub total_usd: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub by_hat: BTreeMap<String, Usage>,
+}
+
+/// Where the run's work landed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitSummary {
+    /// Branch checked out when the run ended (None on a detached HEAD).
+    pub branch: Option<String>,
+    /// HEAD when the run started.
+    pub base: Option<String>,
+    /// HEAD when the run ended.
+    pub head: Option<String>,
+    /// Commits made since `base`, oldest first.
+    pub commits: Vec<Commit>,
+}
+
+/// A commit the run produced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Commit {
+    pub sha: String,
+    pub subject: String,
+}
+
+/// Where the run's record lives, relative to the workspace when inside it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transcript {
+    /// Events journal (`ralph export`, `ralph sessions`).
+    pub events: String,
+    /// Each iteration's prompt and output.
+    pub session_dir: String,
+    /// `--record-session` file for `ralph replay`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recording: Option<String>,
+}
+
+/// Events published during the run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventCounts {
+    pub total: usize,
+    pub by_topic: BTreeMap<String, usize>,
+}
+
+impl Run<'_> {
+    /// Collects the manifest from the loop state, git, and the events file.
+    ///
+    /// Git and the events file are best effort: outside a repository the git
+    /// fields are empty, and an unreadable events file counts no events.
+    pub fn manifest(&self) -> RunManifest {
+        let finished = chrono::Utc::now();
+        let elapsed = self.state.elapsed();
+        let started = finished - chrono::Duration::from_std(elapsed).unwrap_or_default();
+
+        let ledger = &self.state.cost_ledger;
+        let mut hats: BTreeMap<String, u32> = self
+            .state
+            .hat_activation_counts
+            .iter()
+            .map(|(hat, count)| (hat.to_string(), *count))
+            .collect();
+        hats.retain(|_, count| *count > 0);
+
+        let records = EventHistory::new(self.events_path)
+            .with_key(self.key.cloned())
+            .read_all()
+            .unwrap_or_default();
+        let mut by_topic = BTreeMap::new();
+        for record in &records {
+            *by_topic.entry(record.topic.clone()).or_insert(0) += 1;
+        }
+
+        RunManifest {
+            version: SCHEMA_VERSION,
+            ralph_version: env!("CARGO_PKG_VERSION").to_string(),
+            loop_id: self.loop_id.to_string(),
+            started_at: started.to_rfc3339(),
+            finished_at: finished.to_rfc3339(),
+            duration_secs: elapsed.as_secs_f64(),
+            termination: Termination {
+                reason: self.reason.as_str().to_string(),
+                exit_code: self.reason.exit_code(),
+                success: self.reason.is_success(),
+                detail: self.reason.detail(),
+                last_error: self.reason.last_error().map(str::to_string),
+            },
+            iterations: self.state.iteration,
+            cost: RunCost {
+                total_usd: self.state.cumulative_cost,
+                input_tokens: ledger.total.input_tokens,
+                output_tokens: ledger.total.output_tokens,
+                by_hat: ledger.by_hat.clone(),
+            },
+            hats,
+            git: self.git_summary(),
+            transcript: Transcript {
+                events: self.display(self.events_path),
+                session_dir: self.display(self.session_dir),
+                recording: self.recording.map(|path| self.display(path)),
+            },
+            events: EventCounts {
+                total: records.len(),
+                by_topic,
+            },
+            forensics: self.forensics.map(|path| self.display(path)),
+        }
+    }
+
+    fn git_summary(&self) -> GitSummary {
+        let commits = self
+            .base_commit
+            .and_then(|base| git_ops::get_commits_since(self.workspace, base).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(sha, subject)| Commit { sha, subject })
+            .collect();
+        GitSummary {
+            branch: git_ops::get_current_branch(self.workspace).ok(),
+            base: self.base_commit.map(str::to_string),
+            head: git_ops::get_head_sha(self.workspace).ok(),
+            commits,
+        }
+    }
+
+    fn display(&self, path: &Path) -> String {
+        path.strip_prefix(self.workspace)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+}
+
+impl RunManifest {
+    /// Writes the manifest as pretty-printed JSON, replacing `path` atomically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be written.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        fs::write(&tmp, serde_json::to_string_pretty(self)? + "\n")?;
+        fs::rename(&tmp, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_logger::EventLogger;
+    use crate::git_ops::run_git;
+    use ralph_proto::{Event, HatId};
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        run_git(dir, args).unwrap().trim().to_string()
+    }
+
+    fn commit(dir: &Path, file: &str, message: &str) {
+        fs::write(dir.join(file), message).unwrap();
+        git(dir, &["add", file]);
+        git(dir, &["commit", "-m", message]);
+    }
+
+    #[test]
+    fn test_manifest_records_commits_events_and_termination() {
+        let temp = tempfile::tempdir().unwrap();
+        let workspace = temp.path();
+        git(workspace, &["init", "--initial-branch=main"]);
+        git(workspace, &["config", "user.email", "test@test.local"]);
+        git(workspace, &["config", "user.name", "Test User"]);
+        commit(workspace, "README.md", "Initial commit");
+        let base = git(workspace, &["rev-parse", "HEAD"]);
+        commit(workspace, "src.rs", "Add parser");
+        commit(workspace, "test.rs", "Test parser");
+
+        let events_path = workspace.join(".ralph/events-1.jsonl");
+        let mut logger = EventLogger::new(&events_path);
+        logger
+            .log_event(1, "builder", &Event::new("build.done", "ok"), None)
+            .unwrap();
+        logger
+            .log_event(2, "builder", &Event::new("build.done", "ok"), None)
+            .unwrap();
+        logger
+            .log_event(2, "ralph", &Event::new("LOOP_COMPLETE", ""), None)
+            .unwrap();
+
+        let mut state = LoopState::new();
+        state.iteration = 2;
+        state.cumulative_cost = 0.5;
+        state.hat_activation_counts.insert(HatId::new("builder"), 2);
+        let run = Run {
+            loop_id: "primary-1",
+            reason: &TerminationReason::CompletionPromise,
+            state: &state,
+            workspace,
+            base_commit: Some(&base),
+            events_path: &events_path,
+            session_dir: &workspace.join(".ralph/agent/sessions/primary-1"),
+            recording: None,
+            forensics: None,
+            key: None,
+        };
+        let manifest = run.manifest();
+
+        assert_eq!(manifest.version, SCHEMA_VERSION);
+        assert_eq!(manifest.termination.reason, "completed");
+        assert_eq!(manifest.termination.exit_code, 0);
+        assert!(manifest.termination.success);
+        assert_eq!(manifest.iterations, 2);
+        assert_eq!(manifest.hats["builder"], 2);
+        assert_eq!(manifest.git.branch.as_deref(), Some("main"));
+        let subjects: Vec<&str> = manifest
+            .git
+            .commits
+            .iter()
+            .map(|c| c.subject.as_str())
+            .collect();
+        assert_eq!(subjects, ["Add parser", "Test parser"]);
+        assert_eq!(
+            manifest.git.head.as_ref(),
+            Some(&manifest.git.commits[1].sha)
+        );
+        assert_eq!(manifest.transcript.events, ".ralph/events-1.jsonl");
+        assert_eq!(manifest.events.total, 3);
+        assert_eq!(manifest.events.by_topic["build.done"], 2);
+
+        let path = workspace.join(".ralph").join(RUN_MANIFEST_FILE);
+        manifest.write(&path).unwrap();
+        let read: RunManifest = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(read, manifest);
+    }
+}
@@ -0,0 +1,107 @@
+//! Read-only scouts run before a hat's iteration (`scouts:` on a hat).
+//!
+//! Scouts answer small, self-contained questions ("summarize module X") in
+//! parallel so the main iteration starts with that context instead of
+//! spending its own turns gathering it. This module covers the prompt and
+//! report side. Running the backends is left to the caller (see
+//! `ralph-adapters`).
+
+use crate::text::floor_char_boundary;
+use std::fmt::Write as _;
+
+/// A scout's answer to one task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoutReport {
+    /// The scouting task as configured.
+    pub task: String,
+    /// The backend's answer.
+    pub output: String,
+    /// Whether the scout finished successfully.
+    pub success: bool,
+}
+
+/// Wraps a scouting task in read-only instructions.
+pub fn scout_prompt(task: &str) -> String {
+    format!(
+        "You are a read-only scout gathering context for another agent.\n\
+         Do not modify files, commit, run commands that change state, or emit events.\n\
+         Read what you need and answer concisely, with file paths where useful.\n\n\
+         ## Task\n\n{task}\n"
+    )
+}
+
+/// Renders reports as a `<scout-reports>` block to put before a prompt.
+///
+/// Each answer is cut to `max_output_chars` bytes. Failed scouts are listed
+/// so the agent knows the context is missing. Returns an empty string when
+/// there are no reports.
+pub fn render_reports(reports: &[ScoutReport], max_output_chars: usize) -> String {
+    if reports.is_empty() {
+        return String::new();
+    }
+
+    let mut block = String::from("<scout-reports>\n");
+    for (index, report) in reports.iter().enumerate() {
+        let _ = writeln!(block, "## Scout {}: {}\n", index + 1, report.task.trim());
+        let output = report.output.trim();
+        if !report.success {
+            block.push_str("(scout failed; gather this context yourself if you need it)\n\n");
+        } else if output.len() > max_output_chars {
+            let end = floor_char_boundary(output, max_output_chars);
+            let _ = writeln!(block, "{}\n[...]\n", output[..end].trim_end());
+        } else {
+            let _ = writeln!(block, "{output}\n");
+        }
+    }
+    block.push_str("</scout-reports>\n\n");
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(task: &str, output: &str, success: bool) -> ScoutReport {
+        ScoutReport {
+            task: task.to_string(),
+            output: output.to_string(),
+            success,
+        }
+    }
+
+    #[test]
+    fn test_scout_prompt_is_read_only() {
+        let prompt = scout_prompt("Summarize src/event_loop");
+        assert!(prompt.contains("Do not modify files"));
+        assert!(prompt.ends_with("## Task\n\nSummarize src/event_loop\n"));
+    }
+
+    #[test]
+    fn test_render_reports_in_order() {
+        let block = render_reports(
+            &[
+                report("Summarize A", "A parses config.\n", true),
+                report("Summarize B", "partial", false),
+            ],
+            1000,
+        );
+        assert!(
+            block.starts_with("<scout-reports>\n## Scout 1: Summarize A\n\nA parses config.\n")
+        );
+        assert!(block.contains("## Scout 2: Summarize B\n\n(scout failed"));
+        assert!(!block.contains("partial"));
+        assert!(block.ends_with("</scout-reports>\n\n"));
+    }
+
+    #[test]
+    fn test_render_reports_truncates_long_answers() {
+        let block = render_reports(&[report("t", &"ü".repeat(100), true)], 51);
+        assert!(block.contains("\n[...]\n"));
+        assert!(!block.contains(&"ü".repeat(26)));
+    }
+
+    #[test]
+    fn test_render_reports_empty() {
+        assert_eq!(render_reports(&[], 100), "");
+    }
+}
@@ -0,0 +1,496 @@
+//! Conflict-aware scratchpad access.
+//!
+//! Parallel hats, worktree loops, and humans can all edit the scratchpad. A
+//! plain read-modify-write would silently drop whichever edit landed first, so
+//! writes go through [`Scratchpad::write`] with the revision the writer
+//! started from:
+//!
+//! - If the file is still at that revision, the new content is written.
+//! - If someone else changed it in the meantime, the two edits are merged
+//!   line by line against the base revision. Edits to different lines merge
+//!   cleanly; text both sides added at the same spot is kept, theirs first.
+//! - If both sides changed the same lines differently, nothing is written and
+//!   the conflicted text (with `<<<<<<<` markers) is saved next to the
+//!   scratchpad for Ralph to resolve.
+//!
+//! A revision is a hash of the content. Every [`read`](Scratchpad::read)
+//! keeps a copy of the revision it returned, so a later write can find its
+//! base. Only the most recent copies are kept.
+
+use crate::file_lock::FileLock;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Base revisions kept for merging.
+const KEPT_REVISIONS: usize = 20;
+
+/// Topic published when a scratchpad write conflicts.
+pub const CONFLICT_TOPIC: &str = "scratchpad.conflict";
+
+/// The scratchpad's content at one point in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScratchpadSnapshot {
+    /// File content (empty if the scratchpad doesn't exist yet).
+    pub content: String,
+    /// Content hash identifying this revision.
+    pub revision: String,
+}
+
+/// What a [`Scratchpad::write`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteOutcome {
+    /// The scratchpad was unchanged since the base; the content was written.
+    Written { revision: String },
+    /// Someone else changed the scratchpad; both edits were merged and written.
+    Merged { revision: String },
+    /// Both sides changed the same lines. The scratchpad was left as is and
+    /// the conflicted text was saved to `conflict_path`.
+    Conflict {
+        /// Revision currently on disk.
+        current_revision: String,
+        /// File holding the merge with conflict markers.
+        conflict_path: PathBuf,
+    },
+}
+
+/// Handle to a scratchpad file.
+#[derive(Debug, Clone)]
+pub struct Scratchpad {
+    path: PathBuf,
+}
+
+impl Scratchpad {
+    /// Creates a handle for the scratchpad at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the scratchpad path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the current content and remembers it as a merge base.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file or the revision store can't be accessed.
+    pub fn read(&self) -> io::Result<ScratchpadSnapshot> {
+        let lock = FileLock::new(&self.path)?;
+        let _guard = lock.shared()?;
+        let snapshot = snapshot(self.read_content()?);
+        self.remember(&snapshot)?;
+        Ok(snapshot)
+    }
+
+    /// Appends `text` as a new paragraph at the end of the scratchpad.
+    ///
+    /// Appends never conflict: they are applied to whatever is current.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be written.
+    pub fn append(&self, text: &str) -> io::Result<String> {
+        let lock = FileLock::new(&self.path)?;
+        let _guard = lock.exclusive()?;
+        let mut content = self.read_content()?;
+        if !content.is_empty() && !content.ends_with("\n\n") {
+            content.push_str(if content.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        content.push_str(text.trim_end());
+        content.push('\n');
+        self.write_content(&content)
+    }
+
+    /// Replaces the scratchpad with `content`, merging with edits made since
+    /// `base_revision`.
+    ///
+    /// With no base revision the content is written unconditionally.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or written. A conflict is
+    /// not an error; see [`WriteOutcome::Conflict`].
+    pub fn write(&self, base_revision: Option<&str>, content: &str) -> io::Result<WriteOutcome> {
+        let lock = FileLock::new(&self.path)?;
+        let _guard = lock.exclusive()?;
+        let current = snapshot(self.read_content()?);
+
+        let Some(base_revision) = base_revision.filter(|base| *base != current.revision) else {
+            let revision = self.write_content(content)?;
+            return Ok(WriteOutcome::Written { revision });
+        };
+
+        // An unknown base (pruned or never read) is merged as if from empty,
+        // which keeps both sides' text.
+        let base = fs::read_to_string(self.revision_path(base_revision)).unwrap_or_default();
+        match merge3(&base, &current.content, content) {
+            Ok(merged) => {
+                let revision = self.write_content(&merged)?;
+                Ok(WriteOutcome::Merged { revision })
+            }
+            Err(conflicted) => {
+                let conflict_path = self
+                    .revisions_dir()
+                    .join(format!("conflict-{}.md", current.revision));
+                fs::create_dir_all(self.revisions_dir())?;
+                fs::write(&conflict_path, conflicted)?;
+                Ok(WriteOutcome::Conflict {
+                    current_revision: current.revision,
+                    conflict_path,
+                })
+            }
+        }
+    }
+
+    fn read_content(&self) -> io::Result<String> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes `content`, remembers it as a base, and returns its revision.
+    fn write_content(&self, content: &str) -> io::Result<String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, content)?;
+        let snapshot = snapshot(content.to_string());
+        self.remember(&snapshot)?;
+        Ok(snapshot.revision)
+    }
+
+    /// `.scratchpad.md.revisions/` next to the scratchpad.
+    fn revisions_dir(&self) -> PathBuf {
+        let name = self
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "scratchpad".to_string());
+        self.path.with_file_name(format!(".{name}.revisions"))
+    }
+
+    fn revision_path(&self, revision: &str) -> PathBuf {
+        // Revisions are hex; anything else can't name a stored base.
+        let revision = if revision.chars().all(|c| c.is_ascii_hexdigit()) {
+            revision
+        } else {
+            "invalid"
+        };
+        self.revisions_dir().join(format!("{revision}.md"))
+    }
+
+    fn remember(&self, snapshot: &ScratchpadSnapshot) -> io::Result<()> {
+        let dir = self.revisions_dir();
+        fs::create_dir_all(&dir)?;
+        let path = self.revision_path(&snapshot.revision);
+        if !path.exists() {
+            fs::write(&path, &snapshot.content)?;
+        }
+        prune_revisions(&dir)
+    }
+}
+
+fn snapshot(content: String) -> ScratchpadSnapshot {
+    let revision = revision_of(&content);
+    ScratchpadSnapshot { content, revision }
+}
+
+/// Returns the revision id of `content` (64-bit FNV-1a, hex).
+pub fn revision_of(content: &str) -> String {
+    let hash = content
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{hash:016x}")
+}
+
+/// Keeps the newest [`KEPT_REVISIONS`] stored bases.
+fn prune_revisions(dir: &Path) -> io::Result<()> {
+    let mut revisions: Vec<_> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.ends_with(".md") && !name.starts_with("conflict-")
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    if revisions.len() <= KEPT_REVISIONS {
+        return Ok(());
+    }
+    revisions.sort();
+    for (_, path) in &revisions[..revisions.len() - KEPT_REVISIONS] {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// A run of base lines `[start, end)` replaced by `lines`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Hunk<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+/// Line-based three-way merge of `ours` and `theirs` against `base`.
+///
+/// Returns the merged text, or `Err` with the text including conflict markers.
+fn merge3(base: &str, theirs: &str, ours: &str) -> Result<String, String> {
+    let base_lines: Vec<&str> = base.lines().collect();
+    let their_hunks = diff(&base_lines, &theirs.lines().collect::<Vec<_>>());
+    let our_hunks = diff(&base_lines, &ours.lines().collect::<Vec<_>>());
+
+    let mut out: Vec<&str> = Vec::new();
+    let mut conflicted = false;
+    let mut pos = 0;
+    let (mut t, mut o) = (0, 0);
+
+    while t < their_hunks.len() || o < our_hunks.len() {
+        // Start a group with whichever hunk comes first, then pull in every
+        // hunk from either side that overlaps it.
+        let first = match (their_hunks.get(t), our_hunks.get(o)) {
+            (Some(th), Some(oh)) if th.start <= oh.start => th,
+            (Some(_) | None, Some(oh)) => oh,
+            (Some(th), None) => th,
+            (None, None) => unreachable!(),
+        };
+        let (start, mut end) = (first.start, first.end);
+        let (t0, o0) = (t, o);
+        loop {
+            let mut grew = false;
+            while let Some(h) = their_hunks.get(t).filter(|h| overlaps(h, start, end)) {
+                end = end.max(h.end);
+                t += 1;
+                grew = true;
+            }
+            while let Some(h) = our_hunks.get(o).filter(|h| overlaps(h, start, end)) {
+                end = end.max(h.end);
+                o += 1;
+                grew = true;
+            }
+            if !grew {
+                break;
+            }
+        }
+
+        out.extend_from_slice(&base_lines[pos..start]);
+        let theirs_group = &their_hunks[t0..t];
+        let ours_group = &our_hunks[o0..o];
+        let their_side = apply(&base_lines, start, end, theirs_group);
+        let our_side = apply(&base_lines, start, end, ours_group);
+
+        if ours_group.is_empty() || their_side == our_side {
+            out.extend(their_side);
+        } else if theirs_group.is_empty() {
+            out.extend(our_side);
+        } else if start == end {
+            // Both sides only inserted text here: keep both, theirs first.
+            out.extend(their_side);
+            out.extend(our_side);
+        } else {
+            conflicted = true;
+            out.push("<<<<<<< current");
+            out.extend(their_side);
+            out.push("=======");
+            out.extend(our_side);
+            out.push(">>>>>>> yours");
+        }
+        pos = end;
+    }
+    out.extend_from_slice(&base_lines[pos..]);
+
+    let mut merged = out.join("\n");
+    if !merged.is_empty() && (ours.ends_with('\n') || theirs.ends_with('\n')) {
+        merged.push('\n');
+    }
+    if conflicted { Err(merged) } else { Ok(merged) }
+}
+
+/// Whether `hunk` touches the base range `[start, end)`.
+///
+/// Insertions at the same point overlap each other, and an insertion at
+/// either edge of a replaced range overlaps it.
+fn overlaps(hunk: &Hunk<'_>, start: usize, end: usize) -> bool {
+    if hunk.start == hunk.end || start == end {
+        (hunk.start >= start && hunk.start <= end) || (start >= hunk.start && start <= hunk.end)
+    } else {
+        hunk.start < end && start < hunk.end
+    }
+}
+
+/// Applies `hunks` to `base[start..end]`.
+fn apply<'a>(base: &[&'a str], start: usize, end: usize, hunks: &[Hunk<'a>]) -> Vec<&'a str> {
+    let mut out = Vec::new();
+    let mut pos = start;
+    for hunk in hunks {
+        out.extend_from_slice(&base[pos..hunk.start]);
+        out.extend_from_slice(&hunk.lines);
+        pos = hunk.end;
+    }
+    out.extend_from_slice(&base[pos..end]);
+    out
+}
+
+/// Line diff of `other` against `base`, as ordered hunks.
+///
+/// Strips the common prefix and suffix, then runs an LCS over the middle,
+/// which keeps the common cases (appends, small edits) cheap.
+fn diff<'a>(base: &[&'a str], other: &[&'a str]) -> Vec<Hunk<'a>> {
+    let prefix = base.iter().zip(other).take_while(|(a, b)| a == b).count();
+    let suffix = base[prefix..]
+        .iter()
+        .rev()
+        .zip(other[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &base[prefix..base.len() - suffix];
+    let b = &other[prefix..other.len() - suffix];
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0_u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let mut current: Option<Hunk<'a>> = None;
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            hunks.extend(current.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let hunk = current.get_or_insert_with(|| Hunk {
+            start: prefix + i,
+            end: prefix + i,
+            lines: Vec::new(),
+        });
+        if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            hunk.lines.push(b[j]);
+            j += 1;
+        } else {
+            i += 1;
+            hunk.end = prefix + i;
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_merge3_combines_edits_to_different_lines() {
+        let base = "# Plan\nstep one\nstep two\nstep three\n";
+        let theirs = "# Plan\nstep one (done)\nstep two\nstep three\n";
+        let ours = "# Plan\nstep one\nstep two\nstep three\n\n## Notes\nuse the cache\n";
+        assert_eq!(
+            merge3(base, theirs, ours).unwrap(),
+            "# Plan\nstep one (done)\nstep two\nstep three\n\n## Notes\nuse the cache\n"
+        );
+    }
+
+    #[test]
+    fn test_merge3_keeps_both_appends() {
+        let base = "# Notes\n";
+        let theirs = "# Notes\nhuman: prefer sqlite\n";
+        let ours = "# Notes\nagent: added migrations\n";
+        assert_eq!(
+            merge3(base, theirs, ours).unwrap(),
+            "# Notes\nhuman: prefer sqlite\nagent: added migrations\n"
+        );
+    }
+
+    #[test]
+    fn test_merge3_marks_conflicting_changes() {
+        let base = "goal: ship v1\n";
+        let theirs = "goal: ship v2\n";
+        let ours = "goal: ship v1.1\n";
+        assert_eq!(
+            merge3(base, theirs, ours).unwrap_err(),
+            "<<<<<<< current\ngoal: ship v2\n=======\ngoal: ship v1.1\n>>>>>>> yours\n"
+        );
+    }
+
+    #[test]
+    fn test_write_detects_concurrent_edits() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("scratchpad.md");
+        fs::write(&path, "# Plan\nA\nB\n").unwrap();
+        let pad = Scratchpad::new(&path);
+
+        // Unchanged since the base: a plain write.
+        let base = pad.read().unwrap();
+        let outcome = pad
+            .write(Some(&base.revision), "# Plan\nA\nB\nC\n")
+            .unwrap();
+        let WriteOutcome::Written { revision } = outcome else {
+            panic!("expected a plain write, got {outcome:?}");
+        };
+        assert_eq!(revision, revision_of("# Plan\nA\nB\nC\n"));
+
+        // A human edits line A while the agent (from the same base) edits B.
+        let base = pad.read().unwrap();
+        fs::write(&path, "# Plan\nA (human)\nB\nC\n").unwrap();
+        let outcome = pad
+            .write(Some(&base.revision), "# Plan\nA\nB (agent)\nC\n")
+            .unwrap();
+        assert!(matches!(outcome, WriteOutcome::Merged { .. }));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Plan\nA (human)\nB (agent)\nC\n"
+        );
+
+        // Both change the same line: the file is left alone.
+        let base = pad.read().unwrap();
+        fs::write(&path, "# Plan\nA (human)\nB (human)\nC\n").unwrap();
+        let outcome = pad
+            .write(Some(&base.revision), "# Plan\nA (human)\nB (agent v2)\nC\n")
+            .unwrap();
+        let WriteOutcome::Conflict { conflict_path, .. } = outcome else {
+            panic!("expected a conflict, got {outcome:?}");
+        };
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Plan\nA (human)\nB (human)\nC\n"
+        );
+        assert!(
+            fs::read_to_string(conflict_path)
+                .unwrap()
+                .contains("<<<<<<< current\nB (human)\n=======\nB (agent v2)\n>>>>>>> yours")
+        );
+    }
+
+    #[test]
+    fn test_append_adds_paragraph() {
+        let temp_dir = TempDir::new().unwrap();
+        let pad = Scratchpad::new(temp_dir.path().join("agent/scratchpad.md"));
+        pad.append("first note").unwrap();
+        pad.append("second note\n").unwrap();
+        assert_eq!(
+            fs::read_to_string(pad.path()).unwrap(),
+            "first note\n\nsecond note\n"
+        );
+    }
+}
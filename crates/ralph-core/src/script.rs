@@ -0,0 +1,446 @@
+//! Rhai scripting hooks for lightweight loop customization.
+//!
+//! Scripts are small snippets declared under `scripts:` in the config. They are
+//! a lighter-weight alternative to WASM plugins for three decisions:
+//!
+//! - **transform** — runs for every event read from JSONL. Returns the (possibly
+//!   modified) `event` map to keep it, or `()` to drop it.
+//! - **route** — runs before an event is published. Returns a hat id to deliver
+//!   the event directly to that hat, or `()` for normal topic routing.
+//! - **terminate** — runs with every termination check. Returns `true` or a
+//!   reason string to stop the loop, or `false`/`()` to keep going.
+//!
+//! Every script sees a read-only snapshot of the loop:
+//!
+//! ```text
+//! event   #{ topic: "build.done", payload: "..." }      (transform/route only)
+//! state   #{ iteration, cumulative_cost, consecutive_failures,
+//!            elapsed_seconds, last_hat }
+//! pending #{ "builder": 2, "reviewer": 0, ... }         (queue depth per hat)
+//! ```
+//!
+//! Scripts run sandboxed: they have no filesystem, network, or process access,
+//! cannot mutate the loop except through their return value, and are bounded
+//! by an operation budget. A failing script logs a warning and falls back to
+//! the default behavior.
+//!
+//! The engine is only compiled in with the `scripting` cargo feature. Scripts
+//! that don't compile, or a build without the engine, keep the loop from
+//! starting.
+
+use crate::config::ScriptsConfig;
+use std::collections::BTreeMap;
+
+/// Errors that can occur while compiling scripts.
+#[derive(Debug, thiserror::Error)]
+pub enum ScriptError {
+    /// The binary was built without the scripting engine.
+    #[error("Scripts require the `scripting` feature, which this build does not include")]
+    Unsupported,
+
+    /// A script failed to compile.
+    #[error("Failed to compile '{hook}' script: {reason}")]
+    Compile { hook: &'static str, reason: String },
+}
+
+/// An event as seen by scripts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptEvent {
+    pub topic: String,
+    pub payload: String,
+}
+
+/// Read-only snapshot of loop state exposed to scripts.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptState {
+    pub iteration: u32,
+    pub cumulative_cost: f64,
+    pub consecutive_failures: u32,
+    pub elapsed_seconds: u64,
+    pub last_hat: Option<String>,
+    /// Pending event count per hat on the bus.
+    pub pending: BTreeMap<String, usize>,
+}
+
+/// Compiled scripting hooks.
+#[derive(Default)]
+pub struct ScriptHost {
+    #[cfg(feature = "scripting")]
+    inner: Option<engine::Hooks>,
+}
+
+impl std::fmt::Debug for ScriptHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptHost")
+            .field("active", &!self.is_empty())
+            .finish()
+    }
+}
+
+impl ScriptHost {
+    /// Creates a host with no scripts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compiles the configured scripts.
+    pub fn from_config(config: &ScriptsConfig) -> Result<Self, ScriptError> {
+        if !config.has_scripts() {
+            return Ok(Self::new());
+        }
+
+        #[cfg(feature = "scripting")]
+        {
+            Ok(Self {
+                inner: Some(engine::Hooks::compile(config)?),
+            })
+        }
+
+        #[cfg(not(feature = "scripting"))]
+        {
+            Err(ScriptError::Unsupported)
+        }
+    }
+
+    /// Returns true if no scripts are active.
+    pub fn is_empty(&self) -> bool {
+        #[cfg(feature = "scripting")]
+        {
+            self.inner.is_none()
+        }
+
+        #[cfg(not(feature = "scripting"))]
+        {
+            true
+        }
+    }
+
+    /// Runs the transform hook. Returns `None` if the script dropped the event.
+    pub fn transform(&self, event: ScriptEvent, state: &ScriptState) -> Option<ScriptEvent> {
+        #[cfg(feature = "scripting")]
+        if let Some(ref hooks) = self.inner {
+            return hooks.transform(event, state);
+        }
+
+        let _ = state;
+        Some(event)
+    }
+
+    /// Runs the route hook. Returns the hat id the event should be delivered to.
+    pub fn route(&self, event: &ScriptEvent, state: &ScriptState) -> Option<String> {
+        #[cfg(feature = "scripting")]
+        if let Some(ref hooks) = self.inner {
+            return hooks.route(event, state);
+        }
+
+        let _ = (event, state);
+        None
+    }
+
+    /// Runs the terminate hook. Returns the script's reason when it asks to stop.
+    pub fn should_terminate(&self, state: &ScriptState) -> Option<String> {
+        #[cfg(feature = "scripting")]
+        if let Some(ref hooks) = self.inner {
+            return hooks.should_terminate(state);
+        }
+
+        let _ = state;
+        None
+    }
+}
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use super::{ScriptError, ScriptEvent, ScriptState};
+    use crate::config::ScriptsConfig;
+    use rhai::{AST, Dynamic, Engine, Map, Scope};
+    use tracing::{debug, warn};
+
+    pub(super) struct Hooks {
+        engine: Engine,
+        transform: Option<AST>,
+        route: Option<AST>,
+        terminate: Option<AST>,
+    }
+
+    impl Hooks {
+        pub(super) fn compile(config: &ScriptsConfig) -> Result<Self, ScriptError> {
+            let mut engine = Engine::new();
+            engine.set_max_operations(config.max_operations);
+            engine.set_max_call_levels(32);
+            engine.set_max_string_size(1024 * 1024);
+            engine.on_print(|text| debug!(target: "ralph::script", "{}", text));
+            engine.on_debug(|text, _, _| debug!(target: "ralph::script", "{}", text));
+
+            let compile = |hook: &'static str, source: Option<&str>| {
+                source
+                    .map(|src| {
+                        engine.compile(src).map_err(|e| ScriptError::Compile {
+                            hook,
+                            reason: e.to_string(),
+                        })
+                    })
+                    .transpose()
+            };
+
+            let transform = compile("transform", config.transform.as_deref())?;
+            let route = compile("route", config.route.as_deref())?;
+            let terminate = compile("terminate", config.terminate.as_deref())?;
+
+            Ok(Self {
+                engine,
+                transform,
+                route,
+                terminate,
+            })
+        }
+
+        fn eval(
+            &self,
+            hook: &str,
+            ast: &AST,
+            event: Option<&ScriptEvent>,
+            state: &ScriptState,
+        ) -> Option<Dynamic> {
+            let mut scope = Scope::new();
+            if let Some(event) = event {
+                scope.push("event", event_map(event));
+            }
+            scope.push_constant("state", state_map(state));
+            scope.push_constant("pending", pending_map(state));
+
+            match self.engine.eval_ast_with_scope::<Dynamic>(&mut scope, ast) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    warn!(hook = hook, error = %e, "Script failed, using default behavior");
+                    None
+                }
+            }
+        }
+
+        pub(super) fn transform(
+            &self,
+            event: ScriptEvent,
+            state: &ScriptState,
+        ) -> Option<ScriptEvent> {
+            let Some(ref ast) = self.transform else {
+                return Some(event);
+            };
+            let Some(value) = self.eval("transform", ast, Some(&event), state) else {
+                return Some(event);
+            };
+
+            if value.is_unit() {
+                debug!(topic = %event.topic, "Event dropped by transform script");
+                return None;
+            }
+
+            match value.try_cast::<Map>() {
+                Some(map) => Some(ScriptEvent {
+                    topic: map_string(&map, "topic").unwrap_or(event.topic),
+                    payload: map_string(&map, "payload").unwrap_or(event.payload),
+                }),
+                None => {
+                    warn!("transform script must return an event map or (); keeping event");
+                    Some(event)
+                }
+            }
+        }
+
+        pub(super) fn route(&self, event: &ScriptEvent, state: &ScriptState) -> Option<String> {
+            let ast = self.route.as_ref()?;
+            let value = self.eval("route", ast, Some(event), state)?;
+            if value.is_unit() {
+                return None;
+            }
+            match value.into_string() {
+                Ok(hat) if !hat.trim().is_empty() => Some(hat),
+                _ => {
+                    warn!("route script must return a hat id string or ()");
+                    None
+                }
+            }
+        }
+
+        pub(super) fn should_terminate(&self, state: &ScriptState) -> Option<String> {
+            let ast = self.terminate.as_ref()?;
+            let value = self.eval("terminate", ast, None, state)?;
+            if let Ok(stop) = value.as_bool() {
+                return stop.then(|| "terminate script returned true".to_string());
+            }
+            if value.is_unit() {
+                return None;
+            }
+            value.into_string().ok()
+        }
+    }
+
+    fn event_map(event: &ScriptEvent) -> Map {
+        let mut map = Map::new();
+        map.insert("topic".into(), event.topic.clone().into());
+        map.insert("payload".into(), event.payload.clone().into());
+        map
+    }
+
+    fn state_map(state: &ScriptState) -> Map {
+        let mut map = Map::new();
+        map.insert("iteration".into(), i64::from(state.iteration).into());
+        map.insert("cumulative_cost".into(), state.cumulative_cost.into());
+        map.insert(
+            "consecutive_failures".into(),
+            i64::from(state.consecutive_failures).into(),
+        );
+        map.insert(
+            "elapsed_seconds".into(),
+            (state.elapsed_seconds as i64).into(),
+        );
+        map.insert(
+            "last_hat".into(),
+            state.last_hat.clone().map_or(Dynamic::UNIT, Dynamic::from),
+        );
+        map
+    }
+
+    fn pending_map(state: &ScriptState) -> Map {
+        state
+            .pending
+            .iter()
+            .map(|(hat, count)| (hat.as_str().into(), (*count as i64).into()))
+            .collect()
+    }
+
+    fn map_string(map: &Map, key: &str) -> Option<String> {
+        map.get(key).and_then(|v| v.clone().into_string().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(topic: &str, payload: &str) -> ScriptEvent {
+        ScriptEvent {
+            topic: topic.to_string(),
+            payload: payload.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_no_scripts_is_passthrough() {
+        let host = ScriptHost::from_config(&ScriptsConfig::default()).unwrap();
+        let state = ScriptState::default();
+
+        assert!(host.is_empty());
+        assert_eq!(
+            host.transform(event("build.done", "ok"), &state),
+            Some(event("build.done", "ok"))
+        );
+        assert_eq!(host.route(&event("build.done", "ok"), &state), None);
+        assert_eq!(host.should_terminate(&state), None);
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    #[test]
+    fn test_scripts_unsupported_without_engine() {
+        let config = ScriptsConfig {
+            terminate: Some("state.iteration > 5".to_string()),
+            ..ScriptsConfig::default()
+        };
+        assert!(matches!(
+            ScriptHost::from_config(&config),
+            Err(ScriptError::Unsupported)
+        ));
+    }
+
+    #[cfg(feature = "scripting")]
+    mod with_engine {
+        use super::*;
+
+        fn host(config: ScriptsConfig) -> ScriptHost {
+            ScriptHost::from_config(&config).unwrap()
+        }
+
+        #[test]
+        fn test_transform_rewrites_and_drops() {
+            let host = host(ScriptsConfig {
+                transform: Some(
+                    r#"
+                    if event.topic == "noise.ping" { return (); }
+                    event.payload += " [seen]";
+                    event
+                    "#
+                    .to_string(),
+                ),
+                ..ScriptsConfig::default()
+            });
+            let state = ScriptState::default();
+
+            assert_eq!(host.transform(event("noise.ping", ""), &state), None);
+            assert_eq!(
+                host.transform(event("build.done", "ok"), &state),
+                Some(event("build.done", "ok [seen]"))
+            );
+        }
+
+        #[test]
+        fn test_route_by_payload() {
+            let host = host(ScriptsConfig {
+                route: Some(
+                    r#"if event.payload.contains("frontend") { "frontend_builder" }"#.to_string(),
+                ),
+                ..ScriptsConfig::default()
+            });
+            let state = ScriptState::default();
+
+            assert_eq!(
+                host.route(&event("build.task", "fix frontend nav"), &state),
+                Some("frontend_builder".to_string())
+            );
+            assert_eq!(host.route(&event("build.task", "fix api"), &state), None);
+        }
+
+        #[test]
+        fn test_terminate_sees_state_and_pending() {
+            let host = host(ScriptsConfig {
+                terminate: Some(
+                    r#"if state.iteration > 3 && pending["builder"] == 0 { "builder idle" }"#
+                        .to_string(),
+                ),
+                ..ScriptsConfig::default()
+            });
+            let mut state = ScriptState {
+                iteration: 4,
+                ..ScriptState::default()
+            };
+            state.pending.insert("builder".to_string(), 0);
+
+            assert_eq!(
+                host.should_terminate(&state),
+                Some("builder idle".to_string())
+            );
+
+            state.iteration = 1;
+            assert_eq!(host.should_terminate(&state), None);
+        }
+
+        #[test]
+        fn test_runaway_script_falls_back() {
+            let host = host(ScriptsConfig {
+                terminate: Some("loop {}".to_string()),
+                max_operations: 1_000,
+                ..ScriptsConfig::default()
+            });
+            assert_eq!(host.should_terminate(&ScriptState::default()), None);
+        }
+
+        #[test]
+        fn test_compile_error_reported() {
+            let err = ScriptHost::from_config(&ScriptsConfig {
+                route: Some("if {".to_string()),
+                ..ScriptsConfig::default()
+            })
+            .unwrap_err();
+            assert!(matches!(err, ScriptError::Compile { hook: "route", .. }));
+        }
+    }
+}
@@ -0,0 +1,525 @@
+//! Embedding index over the workspace (`search_index:` in ralph.yml).
+//!
+//! Fresh-context iterations otherwise re-grep the repository whenever they
+//! need to find code. The index splits every text file into chunks of lines
+//! and stores an embedding of each in `.ralph/search-index.json`. It is built
+//! when a loop starts and refreshed on each checkpoint, re-embedding only the
+//! files whose content changed. `ralph tools search "query"` ranks chunks by
+//! similarity to the query and prints the best snippets.
+//!
+//! Embeddings are computed locally, without a model: identifiers are split
+//! into words (`parseConfig` and `parse_config` both give `parse` and
+//! `config`), hashed into a fixed number of dimensions, and weighted at query
+//! time by how rare each word is across the index, so a common word in the
+//! query counts for less than a distinctive one.
+
+use crate::config::SearchIndexConfig;
+use crate::plan_skeleton::workspace_files;
+use crate::protect::ProtectedPaths;
+use crate::scratchpad::revision_of;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Index file, relative to the workspace root.
+pub const INDEX_FILE: &str = ".ralph/search-index.json";
+
+/// Bumped when the embedding changes, so old indexes are rebuilt.
+const VERSION: u32 = 1;
+
+/// Number of hashed dimensions.
+const DIMENSIONS: u32 = 4096;
+
+/// Most lines shown for a hit.
+const SNIPPET_LINES: usize = 8;
+
+/// Words too common in prose and code to tell chunks apart.
+const STOPWORDS: &[&str] = &[
+    "an", "and", "are", "as", "be", "by", "fn", "for", "from", "if", "in", "is", "it", "let",
+    "mut", "of", "on", "or", "pub", "self", "the", "this", "to", "use", "with",
+];
+
+/// The settings an index was built with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Settings {
+    chunk_lines: usize,
+    max_file_kb: u64,
+    exclude: Vec<String>,
+}
+
+impl From<&SearchIndexConfig> for Settings {
+    fn from(config: &SearchIndexConfig) -> Self {
+        Self {
+            chunk_lines: config.chunk_lines.max(1),
+            max_file_kb: config.max_file_kb,
+            exclude: config.exclude.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    /// Content revision the chunks were embedded from.
+    revision: String,
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Chunk {
+    /// First line, 1-based.
+    start_line: usize,
+    /// Last line, inclusive.
+    end_line: usize,
+    /// Sparse unit vector: `(dimension, weight)`, sorted by dimension.
+    vector: Vec<(u32, f32)>,
+}
+
+/// The workspace's embedding index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchIndex {
+    version: u32,
+    settings: Settings,
+    files: BTreeMap<String, IndexedFile>,
+}
+
+/// What a refresh did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpdateStats {
+    /// Files (re-)embedded because they were new or changed.
+    pub embedded: usize,
+    /// Files whose embeddings were kept.
+    pub unchanged: usize,
+    /// Files dropped because they were deleted or are now excluded.
+    pub removed: usize,
+}
+
+/// A chunk that matched a query.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    /// Workspace-relative path.
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Cosine similarity to the query, between 0 and 1.
+    pub score: f32,
+    /// The most relevant lines of the chunk.
+    pub snippet: String,
+    /// The file changed since it was indexed, so lines may have moved.
+    pub stale: bool,
+}
+
+impl SearchIndex {
+    /// An empty index with the given settings.
+    pub fn new(config: &SearchIndexConfig) -> Self {
+        Self {
+            version: VERSION,
+            settings: Settings::from(config),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Loads the workspace's index.
+    ///
+    /// Returns `None` when there is none, or when it was built by a different
+    /// version and has to be rebuilt.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index exists but can't be read.
+    pub fn load(workspace: &Path) -> io::Result<Option<Self>> {
+        let content = match fs::read_to_string(workspace.join(INDEX_FILE)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        Ok(serde_json::from_str::<Self>(&content)
+            .ok()
+            .filter(|index| index.version == VERSION))
+    }
+
+    /// Writes the index to the workspace.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index can't be written.
+    pub fn save(&self, workspace: &Path) -> io::Result<()> {
+        let path = workspace.join(INDEX_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(self).map_err(io::Error::other)?)?;
+        fs::rename(tmp, path)
+    }
+
+    /// Number of indexed files.
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Number of indexed chunks.
+    pub fn chunk_count(&self) -> usize {
+        self.files.values().map(|file| file.chunks.len()).sum()
+    }
+
+    /// Brings the index up to date with the workspace.
+    ///
+    /// New and changed files are embedded; deleted and excluded files are
+    /// dropped. Binary files, files over `max_file_kb`, and `.ralph/` are
+    /// skipped.
+    pub fn refresh(&mut self, workspace: &Path) -> UpdateStats {
+        let exclude = ProtectedPaths::new(&self.settings.exclude);
+        let max_bytes = self.settings.max_file_kb.saturating_mul(1024);
+        let mut stats = UpdateStats::default();
+        let mut seen = BTreeSet::new();
+
+        for path in workspace_files(workspace, true) {
+            if path.starts_with(".ralph/") || exclude.matching_pattern(&path).is_some() {
+                continue;
+            }
+            let full = workspace.join(&path);
+            if !fs::metadata(&full).is_ok_and(|meta| meta.is_file() && meta.len() <= max_bytes) {
+                continue;
+            }
+            let Some(content) = read_text(&full) else {
+                continue;
+            };
+            let revision = revision_of(&content);
+            seen.insert(path.clone());
+            if self
+                .files
+                .get(&path)
+                .is_some_and(|file| file.revision == revision)
+            {
+                stats.unchanged += 1;
+                continue;
+            }
+            let chunks = chunk(&path, &content, self.settings.chunk_lines);
+            self.files.insert(path, IndexedFile { revision, chunks });
+            stats.embedded += 1;
+        }
+
+        let before = self.files.len();
+        self.files.retain(|path, _| seen.contains(path));
+        stats.removed = before - self.files.len();
+        stats
+    }
+
+    /// The `limit` chunks most similar to `query`, best first.
+    ///
+    /// Snippets are read from the workspace as it is now.
+    pub fn search(&self, workspace: &Path, query: &str, limit: usize) -> Vec<SearchHit> {
+        let query_vector = embed(query);
+        if query_vector.is_empty() {
+            return Vec::new();
+        }
+
+        // Weight each query dimension by how rare it is across chunks
+        let total = self.chunk_count() as f32;
+        let mut frequency: HashMap<u32, u32> = query_vector.iter().map(|(d, _)| (*d, 0)).collect();
+        for chunk in self.files.values().flat_map(|file| &file.chunks) {
+            for (dimension, _) in &chunk.vector {
+                if let Some(count) = frequency.get_mut(dimension) {
+                    *count += 1;
+                }
+            }
+        }
+        let mut weighted: Vec<(u32, f32)> = query_vector
+            .iter()
+            .map(|(d, w)| (*d, w * (1.0 + total / (1.0 + frequency[d] as f32)).ln()))
+            .collect();
+        normalize(&mut weighted);
+
+        let mut scored: Vec<(f32, &str, &Chunk)> = self
+            .files
+            .iter()
+            .flat_map(|(path, file)| file.chunks.iter().map(move |chunk| (path, chunk)))
+            .filter_map(|(path, chunk)| {
+                let score = dot(&weighted, &chunk.vector);
+                (score > 0.0).then_some((score, path.as_str(), chunk))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+        let query_words: Vec<String> = words(query).collect();
+        scored
+            .into_iter()
+            .filter_map(|(score, path, chunk)| {
+                let content = read_text(&workspace.join(path))?;
+                let stale = self.files[path].revision != revision_of(&content);
+                Some(SearchHit {
+                    path: path.to_string(),
+                    start_line: chunk.start_line,
+                    end_line: chunk.end_line,
+                    score,
+                    snippet: snippet(&content, chunk, &query_words),
+                    stale,
+                })
+            })
+            .take(limit)
+            .collect()
+    }
+}
+
+/// Loads the workspace's index, rebuilding it if `config` changed, refreshes
+/// it, and saves it.
+///
+/// # Errors
+///
+/// Returns an error if the index can't be read or written.
+pub fn update(workspace: &Path, config: &SearchIndexConfig) -> io::Result<UpdateStats> {
+    let mut index = SearchIndex::load(workspace)?
+        .filter(|index| index.settings == Settings::from(config))
+        .unwrap_or_else(|| SearchIndex::new(config));
+    let stats = index.refresh(workspace);
+    index.save(workspace)?;
+    Ok(stats)
+}
+
+/// Reads `path` as UTF-8 text, or `None` for unreadable and binary files.
+fn read_text(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    if bytes.contains(&0) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Splits a file into chunks of `chunk_lines` lines and embeds each one.
+///
+/// The path is embedded with every chunk, so `landing` finds `landing.rs`.
+fn chunk(path: &str, content: &str, chunk_lines: usize) -> Vec<Chunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .chunks(chunk_lines)
+        .enumerate()
+        .filter(|(_, window)| window.iter().any(|line| !line.trim().is_empty()))
+        .map(|(i, window)| {
+            let start_line = i * chunk_lines + 1;
+            Chunk {
+                start_line,
+                end_line: start_line + window.len() - 1,
+                vector: embed(&format!("{path}\n{}", window.join("\n"))),
+            }
+        })
+        .collect()
+}
+
+/// Lowercase words of `text`, with identifiers split at `_` and case changes.
+///
+/// A compound identifier is kept whole as well, so an exact match outranks
+/// its parts appearing separately.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|token| !token.is_empty())
+        .flat_map(|token| {
+            let parts = split_identifier(token);
+            let whole = (parts.len() > 1).then(|| token.to_lowercase().replace('_', ""));
+            parts.into_iter().chain(whole)
+        })
+        .filter(|word| {
+            word.chars().count() > 1
+                && !word.chars().all(|c| c.is_ascii_digit())
+                && !STOPWORDS.contains(&word.as_str())
+        })
+}
+
+/// `parseHTTPConfig_v2` → `parse`, `http`, `config`, `v2`.
+fn split_identifier(token: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    for segment in token.split('_').filter(|s| !s.is_empty()) {
+        let chars: Vec<char> = segment.chars().collect();
+        let mut current = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let boundary = c.is_uppercase()
+                && i > 0
+                && (chars[i - 1].is_lowercase()
+                    || chars.get(i + 1).is_some_and(|next| next.is_lowercase()));
+            if boundary && !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            current.extend(c.to_lowercase());
+        }
+        if !current.is_empty() {
+            parts.push(current);
+        }
+    }
+    parts
+}
+
+/// Hashed, log-scaled, unit-length term vector of `text`.
+fn embed(text: &str) -> Vec<(u32, f32)> {
+    let mut counts: BTreeMap<u32, f32> = BTreeMap::new();
+    for word in words(text) {
+        *counts.entry(dimension(&word)).or_default() += 1.0;
+    }
+    let mut vector: Vec<(u32, f32)> = counts
+        .into_iter()
+        .map(|(d, count)| (d, 1.0 + count.ln()))
+        .collect();
+    normalize(&mut vector);
+    // Four decimals keep the index file small without changing rankings
+    for (_, weight) in &mut vector {
+        *weight = (*weight * 10_000.0).round() / 10_000.0;
+    }
+    vector
+}
+
+fn dimension(word: &str) -> u32 {
+    let hash = word.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    hash % DIMENSIONS
+}
+
+fn normalize(vector: &mut [(u32, f32)]) {
+    let norm = vector.iter().map(|(_, w)| w * w).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for (_, weight) in vector {
+            *weight /= norm;
+        }
+    }
+}
+
+/// Dot product of a query vector with a sorted chunk vector.
+fn dot(query: &[(u32, f32)], chunk: &[(u32, f32)]) -> f32 {
+    query
+        .iter()
+        .filter_map(|(d, w)| {
+            chunk
+                .binary_search_by_key(d, |(dimension, _)| *dimension)
+                .ok()
+                .map(|i| w * chunk[i].1)
+        })
+        .sum()
+}
+
+/// Up to [`SNIPPET_LINES`] lines of the chunk, starting just before the first
+/// line that mentions a query word.
+fn snippet(content: &str, chunk: &Chunk, query_words: &[String]) -> String {
+    let lines: Vec<&str> = content
+        .lines()
+        .skip(chunk.start_line - 1)
+        .take(chunk.end_line + 1 - chunk.start_line)
+        .collect();
+    let first_match = lines
+        .iter()
+        .position(|line| {
+            let line = line.to_lowercase();
+            query_words.iter().any(|word| line.contains(word.as_str()))
+        })
+        .unwrap_or(0);
+    lines
+        .iter()
+        .skip(first_match.saturating_sub(1))
+        .take(SNIPPET_LINES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(
+            src.join("landing.rs"),
+            "/// Lands the plane.\npub fn land_the_plane(prompt: &str) {\n    auto_commit_changes();\n    write_handoff(prompt);\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            src.join("parser.rs"),
+            "pub fn parseEventPayload(input: &str) -> Event {\n    tokenize(input)\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("logo.png"), [0x89, b'P', b'N', b'G', 0, 1]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_split_identifier() {
+        assert_eq!(
+            split_identifier("parseHTTPConfig_v2"),
+            vec!["parse", "http", "config", "v2"]
+        );
+        assert_eq!(
+            split_identifier("land_the_plane"),
+            vec!["land", "the", "plane"]
+        );
+        let words: Vec<String> = words("parse_event for the EventLoop").collect();
+        assert_eq!(
+            words,
+            vec!["parse", "event", "parseevent", "event", "loop", "eventloop"]
+        );
+    }
+
+    #[test]
+    fn test_search_ranks_relevant_chunk_first() {
+        let dir = workspace();
+        let mut index = SearchIndex::new(&SearchIndexConfig::default());
+        let stats = index.refresh(dir.path());
+        assert_eq!(stats.embedded, 2, "binary files are skipped");
+
+        let hits = index.search(dir.path(), "parse event payload", 5);
+        assert_eq!(hits[0].path, "src/parser.rs");
+        assert!(hits[0].snippet.starts_with("pub fn parseEventPayload"));
+        assert!(!hits[0].stale);
+
+        let hits = index.search(dir.path(), "handoff after landing", 5);
+        assert_eq!(hits[0].path, "src/landing.rs");
+        assert_eq!((hits[0].start_line, hits[0].end_line), (1, 5));
+        assert!(index.search(dir.path(), "the", 5).is_empty());
+    }
+
+    #[test]
+    fn test_refresh_is_incremental() {
+        let dir = workspace();
+        let config = SearchIndexConfig::default();
+        assert_eq!(update(dir.path(), &config).unwrap().embedded, 2);
+
+        fs::write(dir.path().join("src/parser.rs"), "fn tokenize() {}\n").unwrap();
+        fs::remove_file(dir.path().join("src/landing.rs")).unwrap();
+        fs::write(dir.path().join("README.md"), "# Project\n").unwrap();
+        let stats = update(dir.path(), &config).unwrap();
+        assert_eq!(
+            stats,
+            UpdateStats {
+                embedded: 2,
+                unchanged: 0,
+                removed: 1
+            }
+        );
+        assert_eq!(update(dir.path(), &config).unwrap().unchanged, 2);
+
+        // Changed settings rebuild from scratch
+        let excluding = SearchIndexConfig {
+            exclude: vec!["*.md".to_string()],
+            ..config
+        };
+        let stats = update(dir.path(), &excluding).unwrap();
+        assert_eq!((stats.embedded, stats.unchanged), (1, 0));
+        let index = SearchIndex::load(dir.path()).unwrap().unwrap();
+        assert_eq!(index.file_count(), 1);
+    }
+
+    #[test]
+    fn test_search_marks_stale_hits() {
+        let dir = workspace();
+        let mut index = SearchIndex::new(&SearchIndexConfig::default());
+        index.refresh(dir.path());
+        fs::write(
+            dir.path().join("src/parser.rs"),
+            "// moved\npub fn parseEventPayload() {}\n",
+        )
+        .unwrap();
+
+        let hits = index.search(dir.path(), "parseEventPayload", 1);
+        assert!(hits[0].stale);
+    }
+}
@@ -0,0 +1,204 @@
+//! Backend self-test before the main loop (`event_loop.self_test`).
+//!
+//! A tiny canned prompt goes through the configured backend before the first
+//! iteration. The reply has to come back through output capture and carry an
+//! event the parser recognizes. When it doesn't, the run aborts with a diagnosis
+//! instead of spending its first iteration finding out the backend is broken.
+
+use crate::EventSyntax;
+use crate::event_parser::EventParser;
+use crate::text::truncate_with_ellipsis;
+use std::fmt;
+
+/// Topic the self-test asks the backend to emit.
+pub const TOPIC: &str = "self_test.ok";
+
+/// How long the backend gets to answer.
+pub const TIMEOUT_SECONDS: u64 = 120;
+
+const PAYLOAD: &str = "ralph self-test";
+
+/// Lowercase fragments of errors that mean the backend isn't signed in.
+const AUTH_MARKERS: &[&str] = &[
+    "unauthorized",
+    "not logged in",
+    "not authenticated",
+    "authentication_error",
+    "authentication failed",
+    "invalid api key",
+    "invalid x-api-key",
+    "api key not",
+    "/login",
+];
+
+/// Builds the self-test prompt, asking for one event in `syntax`.
+pub fn build_prompt(syntax: EventSyntax) -> String {
+    format!(
+        r"## SELF-TEST
+
+This is a connectivity check before the real work starts.
+
+You MUST NOT read or modify files, run commands, or use tools.
+You MUST reply with exactly this line and nothing else:
+
+{}
+",
+        syntax.example(TOPIC, PAYLOAD)
+    )
+}
+
+/// Why a self-test round trip failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// The backend didn't answer within the time box.
+    TimedOut { seconds: u64 },
+    /// The backend answered with an authentication error.
+    NotAuthenticated { excerpt: String },
+    /// The backend exited with an error.
+    BackendFailed { excerpt: String },
+    /// The backend exited cleanly but no output was captured.
+    NoOutput,
+    /// Output came back, but without a parseable `self_test.ok` event.
+    NoEvent { excerpt: String },
+}
+
+impl Failure {
+    /// What to check next, for the abort message.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::TimedOut { .. } => {
+                "Check that the backend can reach its API, or that it isn't waiting on an interactive prompt."
+            }
+            Self::NotAuthenticated { .. } => {
+                "Sign the backend CLI in (or set its API key) and try again."
+            }
+            Self::BackendFailed { .. } => {
+                "Run the backend command by hand to see the full error; check `cli.command` and `cli.args`."
+            }
+            Self::NoOutput => {
+                "Output capture is broken: check `cli.prompt_mode` and that the backend writes to stdout."
+            }
+            Self::NoEvent { .. } => {
+                "Events in the backend's output won't be seen: check `event_loop.event_syntax` and `event_loop.event_formats`."
+            }
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimedOut { seconds } => write!(f, "no reply within {seconds}s"),
+            Self::NotAuthenticated { excerpt } => {
+                write!(f, "backend is not authenticated: {excerpt}")
+            }
+            Self::BackendFailed { excerpt } if excerpt.is_empty() => {
+                write!(f, "backend exited with an error and no output")
+            }
+            Self::BackendFailed { excerpt } => write!(f, "backend exited with an error: {excerpt}"),
+            Self::NoOutput => write!(f, "backend exited cleanly but produced no output"),
+            Self::NoEvent { excerpt } => {
+                write!(
+                    f,
+                    "reply had no `{TOPIC}` event the parser recognized: {excerpt}"
+                )
+            }
+        }
+    }
+}
+
+/// Checks a self-test reply.
+///
+/// `output` is the captured text (stream-json backends already reduced to
+/// their text), `success` whether the backend exited cleanly.
+///
+/// # Errors
+///
+/// Returns the first [`Failure`] that explains the reply.
+pub fn check(output: &str, success: bool, parser: &EventParser) -> Result<(), Failure> {
+    let parsed = parser
+        .parse(output)
+        .iter()
+        .any(|e| e.topic.as_str() == TOPIC);
+    if success && parsed {
+        return Ok(());
+    }
+
+    let excerpt = excerpt(output);
+    let lower = output.to_lowercase();
+    if AUTH_MARKERS.iter().any(|marker| lower.contains(marker)) {
+        return Err(Failure::NotAuthenticated { excerpt });
+    }
+    if !success {
+        return Err(Failure::BackendFailed { excerpt });
+    }
+    if output.trim().is_empty() {
+        return Err(Failure::NoOutput);
+    }
+    Err(Failure::NoEvent { excerpt })
+}
+
+/// Last non-empty line of `output`, shortened for an error message.
+fn excerpt(output: &str) -> String {
+    let line = output
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    truncate_with_ellipsis(line, 200)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EventFormat;
+
+    #[test]
+    fn test_prompt_shows_event_in_configured_syntax() {
+        let prompt = build_prompt(EventSyntax::Macro);
+        assert!(prompt.contains("@@event(self_test.ok) ralph self-test"));
+        assert!(prompt.contains("You MUST NOT read or modify files"));
+    }
+
+    #[test]
+    fn test_check_accepts_event_in_each_syntax() {
+        for syntax in [EventSyntax::Xml, EventSyntax::Macro, EventSyntax::Json] {
+            let output = format!("Sure.\n{}\n", syntax.example(TOPIC, PAYLOAD));
+            let parser = EventParser::new().with_syntax(syntax);
+            assert_eq!(check(&output, true, &parser), Ok(()), "{syntax:?}");
+        }
+    }
+
+    #[test]
+    fn test_check_diagnoses_failures() {
+        let parser = EventParser::new();
+
+        assert_eq!(
+            check(
+                "Error: Invalid API key · Please run /login\n",
+                false,
+                &parser
+            ),
+            Err(Failure::NotAuthenticated {
+                excerpt: "Error: Invalid API key · Please run /login".to_string()
+            })
+        );
+        assert_eq!(
+            check("command not found: claud\n", false, &parser),
+            Err(Failure::BackendFailed {
+                excerpt: "command not found: claud".to_string()
+            })
+        );
+        assert_eq!(check("  \n", true, &parser), Err(Failure::NoOutput));
+
+        // A fenced event is only understood when the format is enabled
+        let fenced = "```event\nself_test.ok\nralph self-test\n```\n";
+        assert!(matches!(
+            check(fenced, true, &parser),
+            Err(Failure::NoEvent { .. })
+        ));
+        let parser = EventParser::new().with_formats(&[EventFormat::Fenced]);
+        assert_eq!(check(fenced, true, &parser), Ok(()));
+    }
+}
@@ -0,0 +1,627 @@
+//! Remote skill index and the `.agent/skills.lock` lockfile.
+//!
+//! A skill index lists skills that can be installed into a workspace. It is
+//! either an HTTP(S) URL serving JSON, or a git repository:
+//!
+//! - **HTTP JSON**: `{"skills": [{"name", "version", "description", "tags",
+//!   "url"}]}`. `url` points at the skill markdown and may be relative to the
+//!   index URL. The same name may appear with several versions.
+//! - **Git**: a repository with `<name>/SKILL.md` (or `<name>.md`) files. The
+//!   version of every skill is the commit it was read from.
+//!
+//! Installed skills are written to `.agent/skills/<name>/SKILL.md`, which the
+//! [`SkillRegistry`](crate::SkillRegistry) scans automatically. Each install
+//! is pinned in `.agent/skills.lock` with its exact version and a content
+//! checksum, so `ralph tools skill install` with no arguments reproduces the
+//! same skills on every machine.
+
+use crate::git_ops::{self, GitOpsError};
+use crate::scratchpad::revision_of;
+use crate::skill::parse_frontmatter;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Directory installed skills are written to, relative to the workspace.
+pub const INSTALL_DIR: &str = ".agent/skills";
+
+/// Lockfile path, relative to the workspace.
+pub const LOCK_FILE: &str = ".agent/skills.lock";
+
+/// Errors from fetching an index or installing skills.
+#[derive(Debug, thiserror::Error)]
+pub enum SkillIndexError {
+    /// No `skills.index` is configured.
+    #[error("no skill index configured (set skills.index in ralph.yml)")]
+    NotConfigured,
+
+    /// The HTTP request failed.
+    #[error("failed to fetch {url}: {message}")]
+    Http { url: String, message: String },
+
+    /// A git command failed.
+    #[error("git {command} failed: {message}")]
+    Git { command: String, message: String },
+
+    /// The index or lockfile couldn't be parsed.
+    #[error("invalid {what}: {message}")]
+    Parse { what: String, message: String },
+
+    /// The skill (or version) isn't in the index.
+    #[error("skill '{0}' not found in index")]
+    NotFound(String),
+
+    /// Downloaded content doesn't match the checksum in the lockfile.
+    #[error("checksum mismatch for skill '{name}': lockfile has {expected}, got {actual}")]
+    ChecksumMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// Filesystem error.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Where a skill index lives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexSource {
+    /// JSON served over HTTP(S).
+    Http(String),
+    /// A git repository (remote URL or local path).
+    Git(String),
+}
+
+impl IndexSource {
+    /// Interprets a `skills.index` value.
+    ///
+    /// HTTP(S) URLs are JSON indexes unless they end in `.git`; `git+` prefixes
+    /// force git. Everything else (ssh URLs, local paths) is a git repository.
+    pub fn parse(location: &str) -> Self {
+        let location = location.trim();
+        if let Some(url) = location.strip_prefix("git+") {
+            return Self::Git(url.to_string());
+        }
+        let is_http = location.starts_with("http://") || location.starts_with("https://");
+        let is_git = std::path::Path::new(location.trim_end_matches('/'))
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("git"));
+        if is_http && !is_git {
+            Self::Http(location.to_string())
+        } else {
+            Self::Git(location.to_string())
+        }
+    }
+
+    /// The location as written in the lockfile.
+    pub fn location(&self) -> &str {
+        match self {
+            Self::Http(url) | Self::Git(url) => url,
+        }
+    }
+}
+
+/// One skill version listed in an index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// HTTP indexes: URL of the skill markdown (absolute or index-relative).
+    /// Git indexes: path of the skill file in the repository.
+    #[serde(default)]
+    pub url: String,
+}
+
+/// A fetched skill index.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkillIndex {
+    #[serde(default)]
+    pub skills: Vec<IndexEntry>,
+}
+
+impl SkillIndex {
+    /// Returns the latest version of each skill matching `term`
+    /// (case-insensitive, against name, description, and tags).
+    pub fn search(&self, term: &str) -> Vec<&IndexEntry> {
+        let term = term.to_lowercase();
+        let mut latest: BTreeMap<&str, &IndexEntry> = BTreeMap::new();
+        for entry in self.skills.iter().filter(|entry| {
+            entry.name.to_lowercase().contains(&term)
+                || entry.description.to_lowercase().contains(&term)
+                || entry.tags.iter().any(|t| t.to_lowercase().contains(&term))
+        }) {
+            latest
+                .entry(&entry.name)
+                .and_modify(|current| {
+                    if compare_versions(&entry.version, &current.version) == Ordering::Greater {
+                        *current = entry;
+                    }
+                })
+                .or_insert(entry);
+        }
+        latest.into_values().collect()
+    }
+
+    /// Finds `name` at `version`, or its latest version if none is given.
+    pub fn find(&self, name: &str, version: Option<&str>) -> Option<&IndexEntry> {
+        let mut candidates = self.skills.iter().filter(|entry| entry.name == name);
+        match version {
+            Some(version) => candidates.find(|entry| entry.version == version),
+            None => candidates.max_by(|a, b| compare_versions(&a.version, &b.version)),
+        }
+    }
+}
+
+/// Compares dotted versions numerically where possible (`1.10` > `1.9`).
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |v: &str| -> Vec<String> {
+        v.trim_start_matches('v')
+            .split(['.', '-'])
+            .map(str::to_string)
+            .collect()
+    };
+    for (x, y) in parts(a).iter().zip(parts(b).iter()) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    parts(a).len().cmp(&parts(b).len())
+}
+
+/// A skill pinned in the lockfile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedSkill {
+    /// Version from the index (a commit for git indexes).
+    pub version: String,
+    /// Index the skill came from.
+    pub source: String,
+    /// Exact location fetched: a URL, or `<commit>:<path>` for git.
+    pub resolved: String,
+    /// SHA-256 of the installed file, as `sha256:<hex>`.
+    pub checksum: String,
+}
+
+/// Contents of `.agent/skills.lock`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillLock {
+    #[serde(default)]
+    pub skills: BTreeMap<String, LockedSkill>,
+}
+
+impl SkillLock {
+    /// Loads the lockfile in `workspace`, or an empty lock if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be read or parsed.
+    pub fn load(workspace: &Path) -> Result<Self, SkillIndexError> {
+        let path = workspace.join(LOCK_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| SkillIndexError::Parse {
+                what: path.display().to_string(),
+                message: e.to_string(),
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the lockfile to `workspace`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be written.
+    pub fn save(&self, workspace: &Path) -> Result<(), SkillIndexError> {
+        let path = workspace.join(LOCK_FILE);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut content = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        content.push('\n');
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Client for one skill index.
+#[derive(Debug, Clone)]
+pub struct SkillIndexClient {
+    source: IndexSource,
+    /// Where git indexes are cloned.
+    cache_dir: PathBuf,
+}
+
+impl SkillIndexClient {
+    /// Creates a client for `location`, caching git clones under
+    /// `.ralph/cache/skill-index` in `workspace`.
+    pub fn new(location: &str, workspace: &Path) -> Self {
+        let source = IndexSource::parse(location);
+        let cache_dir = workspace
+            .join(".ralph/cache/skill-index")
+            .join(revision_of(source.location()));
+        Self { source, cache_dir }
+    }
+
+    /// The index this client reads.
+    pub fn source(&self) -> &IndexSource {
+        &self.source
+    }
+
+    /// Fetches the index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index can't be downloaded, cloned, or parsed.
+    pub async fn fetch_index(&self) -> Result<SkillIndex, SkillIndexError> {
+        match &self.source {
+            IndexSource::Http(url) => {
+                let body = http_get(url).await?;
+                serde_json::from_str(&body).map_err(|e| SkillIndexError::Parse {
+                    what: format!("skill index {url}"),
+                    message: e.to_string(),
+                })
+            }
+            IndexSource::Git(url) => {
+                let commit = self.sync_git(url)?;
+                self.scan_git(&commit)
+            }
+        }
+    }
+
+    /// Installs `entry` into `workspace` and pins it in the lockfile.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the skill can't be fetched or written.
+    pub async fn install(
+        &self,
+        workspace: &Path,
+        entry: &IndexEntry,
+    ) -> Result<LockedSkill, SkillIndexError> {
+        let resolved = match &self.source {
+            IndexSource::Http(url) => resolve_url(url, &entry.url),
+            IndexSource::Git(_) => format!("{}:{}", entry.version, entry.url),
+        };
+        let content = self.fetch_resolved(&resolved).await?;
+        write_skill(workspace, &entry.name, &content)?;
+
+        let locked = LockedSkill {
+            version: entry.version.clone(),
+            source: self.source.location().to_string(),
+            resolved,
+            checksum: checksum(&content),
+        };
+        let mut lock = SkillLock::load(workspace)?;
+        lock.skills.insert(entry.name.clone(), locked.clone());
+        lock.save(workspace)?;
+        Ok(locked)
+    }
+
+    /// Reinstalls a locked skill exactly as pinned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the skill can't be fetched, or its content no
+    /// longer matches the lockfile checksum.
+    pub async fn install_locked(
+        &self,
+        workspace: &Path,
+        name: &str,
+        locked: &LockedSkill,
+    ) -> Result<(), SkillIndexError> {
+        if matches!(self.source, IndexSource::Git(_)) {
+            self.sync_git(self.source.location())?;
+        }
+        let content = self.fetch_resolved(&locked.resolved).await?;
+        let actual = checksum(&content);
+        if actual != locked.checksum {
+            return Err(SkillIndexError::ChecksumMismatch {
+                name: name.to_string(),
+                expected: locked.checksum.clone(),
+                actual,
+            });
+        }
+        write_skill(workspace, name, &content)
+    }
+
+    async fn fetch_resolved(&self, resolved: &str) -> Result<String, SkillIndexError> {
+        match &self.source {
+            IndexSource::Http(_) => http_get(resolved).await,
+            IndexSource::Git(_) => self.git(&["show", resolved]),
+        }
+    }
+
+    /// Clones or updates the git index and returns the checked-out commit.
+    fn sync_git(&self, url: &str) -> Result<String, SkillIndexError> {
+        if self.cache_dir.join(".git").is_dir() {
+            self.git(&["fetch", "--quiet", "origin"])?;
+            self.git(&["reset", "--quiet", "--hard", "FETCH_HEAD"])?;
+        } else {
+            if let Some(parent) = self.cache_dir.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let target = self.cache_dir.to_string_lossy();
+            run_git(Path::new("."), &["clone", "--quiet", url, &target])?;
+        }
+        Ok(self.git(&["rev-parse", "HEAD"])?.trim().to_string())
+    }
+
+    /// Lists the skills in the cloned index at `commit`.
+    fn scan_git(&self, commit: &str) -> Result<SkillIndex, SkillIndexError> {
+        let mut skills = Vec::new();
+        for dir_entry in std::fs::read_dir(&self.cache_dir)?.flatten() {
+            let path = dir_entry.path();
+            let file_name = dir_entry.file_name().to_string_lossy().into_owned();
+            if file_name.starts_with('.') {
+                continue;
+            }
+            let (name, relative) = if path.is_dir() && path.join("SKILL.md").is_file() {
+                (file_name.clone(), format!("{file_name}/SKILL.md"))
+            } else if let Some(stem) = file_name.strip_suffix(".md")
+                && path.is_file()
+                && !file_name.eq_ignore_ascii_case("README.md")
+            {
+                (stem.to_string(), file_name.clone())
+            } else {
+                continue;
+            };
+            let raw = std::fs::read_to_string(self.cache_dir.join(&relative))?;
+            let frontmatter = parse_frontmatter(&raw).0.unwrap_or_default();
+            skills.push(IndexEntry {
+                name: frontmatter.name.unwrap_or(name),
+                version: commit.to_string(),
+                description: frontmatter.description.unwrap_or_default(),
+                tags: frontmatter.tags,
+                url: relative,
+            });
+        }
+        skills.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(SkillIndex { skills })
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String, SkillIndexError> {
+        run_git(&self.cache_dir, args)
+    }
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<String, SkillIndexError> {
+    git_ops::run_git(dir, args).map_err(|err| match err {
+        GitOpsError::Io(err) => SkillIndexError::Io(err),
+        GitOpsError::Git(message) | GitOpsError::ConfigMissing(message) => SkillIndexError::Git {
+            command: args.first().copied().unwrap_or_default().to_string(),
+            message,
+        },
+    })
+}
+
+/// The lockfile checksum of `content`.
+fn checksum(content: &str) -> String {
+    format!("sha256:{:x}", Sha256::digest(content.as_bytes()))
+}
+
+async fn http_get(url: &str) -> Result<String, SkillIndexError> {
+    let http_error = |message: String| SkillIndexError::Http {
+        url: url.to_string(),
+        message,
+    };
+    let response = reqwest::Client::new()
+        .get(url)
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| http_error(e.to_string()))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(http_error(format!("HTTP {status}")));
+    }
+    response.text().await.map_err(|e| http_error(e.to_string()))
+}
+
+/// Resolves a skill URL relative to the index URL.
+fn resolve_url(index_url: &str, skill_url: &str) -> String {
+    if skill_url.contains("://") {
+        return skill_url.to_string();
+    }
+    if skill_url.starts_with('/') {
+        // Keep scheme and host from the index URL.
+        let host_end = index_url
+            .find("://")
+            .and_then(|scheme| index_url[scheme + 3..].find('/').map(|i| scheme + 3 + i))
+            .unwrap_or(index_url.len());
+        return format!("{}{skill_url}", &index_url[..host_end]);
+    }
+    let base = index_url
+        .rsplit_once('/')
+        .map_or(index_url, |(base, _)| base);
+    format!("{base}/{skill_url}")
+}
+
+fn write_skill(workspace: &Path, name: &str, content: &str) -> Result<(), SkillIndexError> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(SkillIndexError::Parse {
+            what: "skill name".to_string(),
+            message: format!("'{name}' can't be used as a directory name"),
+        });
+    }
+    let dir = workspace.join(INSTALL_DIR).join(name);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("SKILL.md"), content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(name: &str, version: &str, description: &str) -> IndexEntry {
+        IndexEntry {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: description.to_string(),
+            tags: vec![],
+            url: format!("{name}-{version}.md"),
+        }
+    }
+
+    #[test]
+    fn test_index_source_parse() {
+        assert_eq!(
+            IndexSource::parse("https://skills.example.com/index.json"),
+            IndexSource::Http("https://skills.example.com/index.json".to_string())
+        );
+        assert_eq!(
+            IndexSource::parse("https://github.com/acme/skills.git"),
+            IndexSource::Git("https://github.com/acme/skills.git".to_string())
+        );
+        assert_eq!(
+            IndexSource::parse("git+https://example.com/skills"),
+            IndexSource::Git("https://example.com/skills".to_string())
+        );
+        assert_eq!(
+            IndexSource::parse("../team-skills"),
+            IndexSource::Git("../team-skills".to_string())
+        );
+    }
+
+    #[test]
+    fn test_search_and_find_pick_latest_version() {
+        let index = SkillIndex {
+            skills: vec![
+                entry("rust-testing", "1.9.0", "Write cargo tests"),
+                entry("rust-testing", "1.10.0", "Write cargo tests"),
+                entry("go-lint", "0.1.0", "Run golangci-lint"),
+            ],
+        };
+
+        let found = index.search("CARGO");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].version, "1.10.0");
+
+        assert_eq!(
+            index.find("rust-testing", None).map(|e| e.version.as_str()),
+            Some("1.10.0")
+        );
+        assert_eq!(
+            index
+                .find("rust-testing", Some("1.9.0"))
+                .map(|e| e.url.as_str()),
+            Some("rust-testing-1.9.0.md")
+        );
+        assert!(index.find("rust-testing", Some("2.0.0")).is_none());
+    }
+
+    #[test]
+    fn test_checksum_is_sha256() {
+        assert_eq!(
+            checksum("abc"),
+            "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let index = "https://example.com/skills/index.json";
+        assert_eq!(
+            resolve_url(index, "a/SKILL.md"),
+            "https://example.com/skills/a/SKILL.md"
+        );
+        assert_eq!(
+            resolve_url(index, "/raw/a.md"),
+            "https://example.com/raw/a.md"
+        );
+        assert_eq!(
+            resolve_url(index, "https://cdn.example.com/a.md"),
+            "https://cdn.example.com/a.md"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_git_index_install_and_reinstall_from_lock() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("index");
+        std::fs::create_dir_all(repo.join("rust-testing")).unwrap();
+        std::fs::write(
+            repo.join("rust-testing/SKILL.md"),
+            "---\ndescription: Write cargo tests\ntags: [rust]\n---\nUse cargo nextest.\n",
+        )
+        .unwrap();
+        for args in [
+            &["init", "--quiet"][..],
+            &["add", "."],
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@test.local",
+                "commit",
+                "--quiet",
+                "-m",
+                "init",
+            ],
+        ] {
+            run_git(&repo, args).unwrap();
+        }
+
+        let workspace = temp_dir.path().join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        let client = SkillIndexClient::new(&repo.to_string_lossy(), &workspace);
+        let index = client.fetch_index().await.unwrap();
+        let found = index.search("rust");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].url, "rust-testing/SKILL.md");
+
+        let locked = client.install(&workspace, found[0]).await.unwrap();
+        let installed = workspace.join(".agent/skills/rust-testing/SKILL.md");
+        assert!(
+            std::fs::read_to_string(&installed)
+                .unwrap()
+                .contains("Use cargo nextest.")
+        );
+        let lock = SkillLock::load(&workspace).unwrap();
+        assert_eq!(lock.skills["rust-testing"], locked);
+        assert!(locked.resolved.ends_with(":rust-testing/SKILL.md"));
+        assert_eq!(locked.checksum.len(), "sha256:".len() + 64);
+
+        // A teammate installs from the lockfile after the index moved on.
+        std::fs::write(repo.join("rust-testing/SKILL.md"), "changed\n").unwrap();
+        run_git(&repo, &["add", "."]).unwrap();
+        run_git(
+            &repo,
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@test.local",
+                "commit",
+                "--quiet",
+                "-m",
+                "update",
+            ],
+        )
+        .unwrap();
+        std::fs::remove_file(&installed).unwrap();
+        client
+            .install_locked(&workspace, "rust-testing", &locked)
+            .await
+            .unwrap();
+        assert!(
+            std::fs::read_to_string(&installed)
+                .unwrap()
+                .contains("Use cargo nextest.")
+        );
+    }
+}
@@ -1,7 +1,8 @@
 //! Skill registry for discovering, storing, and providing access to skills.
 //!
 //! The registry manages both built-in skills (compiled into the binary) and
-//! user-defined skills (discovered from configured directories).
+//! user-defined skills (discovered from configured directories and
+//! `.agent/skills/`, where `ralph tools skill install` puts them).
 
 use crate::config::{SkillOverride, SkillsConfig};
 use crate::skill::{SkillEntry, SkillSource, parse_frontmatter};
@@ -189,8 +190,17 @@ impl SkillRegistry {
     ) -> Result<Self> {
         let mut registry = Self::new(active_backend);
 
-        // 1. Register built-in skills
+        // 1. Register built-in skills, then those of enabled packs
         registry.register_builtins()?;
+        for pack in config
+            .packs
+            .iter()
+            .filter_map(|name| crate::packs::find(name))
+        {
+            for (name, raw) in pack.skills() {
+                registry.register_builtin(name, raw)?;
+            }
+        }
 
         // 2. Scan configured directories
         for dir in &config.dirs {
@@ -198,7 +208,13 @@ impl SkillRegistry {
             registry.scan_directory(&resolved)?;
         }
 
-        // 3. Apply config overrides
+        // 3. Scan skills installed from the skill index
+        let installed = workspace_root.join(crate::skill_index::INSTALL_DIR);
+        if installed.is_dir() {
+            registry.scan_directory(&installed)?;
+        }
+
+        // 4. Apply config overrides
         registry.apply_overrides(&config.overrides);
 
         Ok(registry)
@@ -674,6 +690,8 @@ mod tests {
                 );
                 m
             },
+            index: None,
+            packs: vec![],
         };
 
         let registry = SkillRegistry::from_config(&config, tmp.path(), Some("claude")).unwrap();
@@ -708,9 +726,30 @@ mod tests {
             enabled: true,
             dirs: vec![std::path::PathBuf::from(".claude/skills")],
             overrides: HashMap::new(),
+            index: None,
+            packs: vec![],
         };
 
         let registry = SkillRegistry::from_config(&config, &workspace_dir, None).unwrap();
         assert!(registry.get("test-driven-development").is_some());
     }
+
+    #[test]
+    fn test_from_config_discovers_installed_skills() {
+        let tmp = TempDir::new().unwrap();
+        let installed = tmp.path().join(".agent/skills/rust-testing");
+        fs::create_dir_all(&installed).unwrap();
+        fs::write(
+            installed.join("SKILL.md"),
+            "---\ndescription: Write cargo tests\n---\nUse cargo nextest.\n",
+        )
+        .unwrap();
+
+        let registry =
+            SkillRegistry::from_config(&SkillsConfig::default(), tmp.path(), None).unwrap();
+        assert_eq!(
+            registry.get("rust-testing").unwrap().description,
+            "Write cargo tests"
+        );
+    }
 }
@@ -0,0 +1,474 @@
+//! Speculative dual-backend execution (`speculative:` in ralph.yml).
+//!
+//! Each candidate backend runs in its own git worktree with its own events
+//! file. Once both finish, their changes are collected as a diff against the
+//! worktree's starting state, an arbiter picks a winner, the winner's diff is
+//! applied to the main workspace and its events are appended to the main
+//! events file, and both worktrees are removed.
+//!
+//! This module covers the workspace side. Running the backends and asking a
+//! judge backend is left to the caller (see `ralph-adapters`).
+
+use crate::event_writer::EventWriter;
+use crate::git_ops::run_git;
+use crate::text::floor_char_boundary;
+use crate::worktree::{WorktreeConfig, WorktreeError, create_worktree, remove_worktree};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::{debug, warn};
+
+/// Events file used inside a candidate worktree, relative to its root.
+const CANDIDATE_EVENTS: &str = ".ralph/events-speculative.jsonl";
+
+/// Pathspec that keeps Ralph's own state out of candidate diffs.
+const EXCLUDE_RALPH_DIR: &str = ":(exclude).ralph";
+
+/// Largest slice of a diff or output shown to the judge, per candidate.
+const JUDGE_EXCERPT_BYTES: usize = 8_000;
+
+/// A backend's isolated workspace for one speculative iteration.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Name shown in logs and the loop history (usually the backend name).
+    pub label: String,
+    /// Root of the candidate's worktree.
+    pub path: PathBuf,
+    /// Tree of the worktree's starting state; diffs are taken against it.
+    baseline: String,
+}
+
+/// What a candidate produced.
+#[derive(Debug, Clone)]
+pub struct CandidateReport {
+    /// Candidate label.
+    pub label: String,
+    /// Backend output.
+    pub output: String,
+    /// Whether the backend exited successfully.
+    pub success: bool,
+    /// Binary diff of the candidate's file changes.
+    pub diff: String,
+    /// Raw JSONL event lines the candidate wrote with `ralph emit`.
+    pub events: Vec<String>,
+    /// Result of `speculative.check`, when it was run.
+    pub check_passed: Option<bool>,
+}
+
+/// The arbiter's choice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verdict {
+    /// Index of the winning candidate.
+    pub winner: usize,
+    /// Why it won, for logs and the loop history.
+    pub reason: String,
+}
+
+impl Candidate {
+    /// Events file the candidate's `ralph emit` calls write to.
+    pub fn events_path(&self) -> PathBuf {
+        self.path.join(CANDIDATE_EVENTS)
+    }
+
+    /// Stages the candidate's changes and collects its diff and events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git fails or the events file can't be read.
+    pub fn collect(&self, output: String, success: bool) -> Result<CandidateReport, WorktreeError> {
+        stage_all(&self.path)?;
+        let diff = run_git(
+            &self.path,
+            &[
+                "diff",
+                "--cached",
+                "--binary",
+                &self.baseline,
+                "--",
+                ".",
+                EXCLUDE_RALPH_DIR,
+            ],
+        )?;
+        let events = match fs::read_to_string(self.events_path()) {
+            Ok(content) => content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(CandidateReport {
+            label: self.label.clone(),
+            output,
+            success,
+            diff,
+            events,
+            check_passed: None,
+        })
+    }
+
+    /// Runs `command` with the shell in the candidate's worktree.
+    ///
+    /// Returns true if it exits successfully.
+    pub fn run_check(&self, command: &str) -> bool {
+        let mut cmd = if cfg!(windows) {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", command]);
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", command]);
+            cmd
+        };
+        match cmd
+            .current_dir(&self.path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+        {
+            Ok(status) => status.success(),
+            Err(e) => {
+                warn!(candidate = %self.label, error = %e, "Failed to run speculative check");
+                false
+            }
+        }
+    }
+}
+
+/// Creates one worktree per label for `iteration`.
+///
+/// Each worktree starts from the main workspace's current state (HEAD plus
+/// uncommitted and untracked files) and gets its own events file. Leftover
+/// worktrees from an interrupted run are replaced.
+///
+/// # Errors
+///
+/// Returns an error if a worktree can't be created. Worktrees created before
+/// the failure are removed.
+pub fn prepare_candidates(
+    repo_root: &Path,
+    iteration: u32,
+    labels: &[String],
+) -> Result<Vec<Candidate>, WorktreeError> {
+    let config = WorktreeConfig::default();
+    let mut candidates = Vec::with_capacity(labels.len());
+    for (index, label) in labels.iter().enumerate() {
+        let id = candidate_id(iteration, index);
+        match prepare_candidate(repo_root, &id, label, &config) {
+            Ok(candidate) => candidates.push(candidate),
+            Err(e) => {
+                discard(repo_root, &candidates);
+                return Err(e);
+            }
+        }
+    }
+    Ok(candidates)
+}
+
+fn candidate_id(iteration: u32, index: usize) -> String {
+    let suffix = char::from(b'a' + u8::try_from(index % 26).unwrap_or(0));
+    format!("speculative-{iteration}-{suffix}")
+}
+
+fn prepare_candidate(
+    repo_root: &Path,
+    id: &str,
+    label: &str,
+    config: &WorktreeConfig,
+) -> Result<Candidate, WorktreeError> {
+    let stale = config.worktree_path(repo_root).join(id);
+    if stale.exists() {
+        debug!(path = %stale.display(), "Removing stale speculative worktree");
+        remove_worktree(repo_root, &stale)?;
+    }
+    // A branch can outlive its worktree if a previous run was killed.
+    let _ = run_git(repo_root, &["branch", "-D", &format!("ralph/{id}")]);
+
+    let worktree = create_worktree(repo_root, id, config)?;
+    let path = worktree.path;
+
+    stage_all(&path)?;
+    let baseline = run_git(&path, &["write-tree"])?.trim().to_string();
+
+    let events_path = path.join(CANDIDATE_EVENTS);
+    if let Some(parent) = events_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&events_path, "")?;
+    fs::write(path.join(".ralph/current-events"), CANDIDATE_EVENTS)?;
+
+    Ok(Candidate {
+        label: label.to_string(),
+        path,
+        baseline,
+    })
+}
+
+/// Picks a winner without asking a model.
+///
+/// Candidates are ranked by, in order: passing `check`, a successful exit,
+/// having changed files, and having emitted events. Ties go to the first
+/// candidate (the first backend listed in config).
+pub fn arbitrate(reports: &[CandidateReport]) -> Verdict {
+    let score = |r: &CandidateReport| {
+        (
+            r.check_passed == Some(true),
+            r.success,
+            !r.diff.is_empty(),
+            !r.events.is_empty(),
+        )
+    };
+    let winner = reports
+        .iter()
+        .enumerate()
+        .fold(None::<usize>, |best, (index, report)| match best {
+            Some(b) if score(&reports[b]) >= score(report) => Some(b),
+            _ => Some(index),
+        })
+        .unwrap_or(0);
+
+    let mut reason = Vec::new();
+    if let Some(report) = reports.get(winner) {
+        match report.check_passed {
+            Some(true) => reason.push("check passed"),
+            Some(false) => reason.push("check failed"),
+            None => {}
+        }
+        reason.push(if report.success {
+            "backend succeeded"
+        } else {
+            "backend failed"
+        });
+        if !report.diff.is_empty() {
+            reason.push("changed files");
+        }
+        if !report.events.is_empty() {
+            reason.push("emitted events");
+        }
+    }
+    Verdict {
+        winner,
+        reason: reason.join(", "),
+    }
+}
+
+/// Builds the prompt asking a judge backend to choose between candidates.
+pub fn judge_prompt(task: &str, reports: &[CandidateReport]) -> String {
+    let mut prompt = String::from(
+        "You are judging two attempts at the same task. Pick the one that makes the most \
+         correct progress on the task. Prefer working, focused changes over larger ones.\n\n",
+    );
+    let _ = writeln!(prompt, "## Task\n\n{}\n", excerpt(task));
+    for (index, report) in reports.iter().enumerate() {
+        let _ = writeln!(
+            prompt,
+            "## Candidate {} ({})\n\nExit: {}\n\n### Diff\n\n```diff\n{}\n```\n\n### Output\n\n```\n{}\n```\n",
+            candidate_letter(index),
+            report.label,
+            if report.success { "success" } else { "failure" },
+            excerpt(&report.diff),
+            excerpt(&report.output),
+        );
+    }
+    prompt.push_str("Reply with a final line of exactly `WINNER: A` or `WINNER: B`.\n");
+    prompt
+}
+
+/// Reads the judge's choice from its output.
+///
+/// Uses the last `WINNER: <letter>` line; returns `None` if there is none or
+/// it names a candidate that doesn't exist.
+pub fn parse_verdict(output: &str, candidates: usize) -> Option<usize> {
+    output.lines().rev().find_map(|line| {
+        let line = line.trim().trim_matches(|c| c == '*' || c == '`');
+        let rest = line
+            .get(..7)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("winner:"))
+            .map(|_| line[7..].trim())?;
+        let letter = rest.chars().next()?.to_ascii_uppercase();
+        let index = usize::from(u8::try_from(letter).ok()?.checked_sub(b'A')?);
+        (index < candidates).then_some(index)
+    })
+}
+
+/// Applies the winner's changes and events to the main workspace.
+///
+/// # Errors
+///
+/// Returns an error if the diff doesn't apply or the events can't be written.
+pub fn commit(
+    repo_root: &Path,
+    winner: &CandidateReport,
+    events: &EventWriter,
+) -> Result<(), WorktreeError> {
+    if !winner.diff.is_empty() {
+        let mut child = Command::new("git")
+            .args(["apply", "--binary", "--whitespace=nowarn", "-"])
+            .current_dir(repo_root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(winner.diff.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(WorktreeError::Git(format!(
+                "failed to apply changes from '{}': {}",
+                winner.label,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+    }
+    events.append_lines(&winner.events)?;
+    Ok(())
+}
+
+/// Removes candidate worktrees, logging failures.
+pub fn discard(repo_root: &Path, candidates: &[Candidate]) {
+    for candidate in candidates {
+        if let Err(e) = remove_worktree(repo_root, &candidate.path) {
+            warn!(
+                candidate = %candidate.label,
+                path = %candidate.path.display(),
+                error = %e,
+                "Failed to remove speculative worktree"
+            );
+        }
+    }
+}
+
+fn candidate_letter(index: usize) -> char {
+    char::from(b'A' + u8::try_from(index % 26).unwrap_or(0))
+}
+
+fn excerpt(text: &str) -> &str {
+    &text[..floor_char_boundary(text, JUDGE_EXCERPT_BYTES)]
+}
+
+fn stage_all(path: &Path) -> Result<(), WorktreeError> {
+    run_git(path, &["add", "-A"])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn init_git_repo(dir: &Path) {
+        for args in [
+            &["init", "--initial-branch=main"][..],
+            &["config", "user.email", "test@test.local"],
+            &["config", "user.name", "Test User"],
+        ] {
+            run_git(dir, args).unwrap();
+        }
+        fs::write(dir.join("README.md"), "# Test\n").unwrap();
+        fs::write(dir.join(".gitignore"), ".worktrees/\n.ralph/\n").unwrap();
+        run_git(dir, &["add", "."]).unwrap();
+        run_git(dir, &["commit", "-m", "Initial commit"]).unwrap();
+    }
+
+    fn report(label: &str, success: bool, diff: &str, check: Option<bool>) -> CandidateReport {
+        CandidateReport {
+            label: label.to_string(),
+            output: String::new(),
+            success,
+            diff: diff.to_string(),
+            events: Vec::new(),
+            check_passed: check,
+        }
+    }
+
+    #[test]
+    fn test_arbitrate_prefers_passing_check_then_first() {
+        let verdict = arbitrate(&[
+            report("claude", true, "diff a", Some(false)),
+            report("gemini", true, "diff b", Some(true)),
+        ]);
+        assert_eq!(verdict.winner, 1);
+        assert!(verdict.reason.starts_with("check passed"));
+
+        let tie = arbitrate(&[
+            report("claude", true, "diff a", None),
+            report("gemini", true, "diff b", None),
+        ]);
+        assert_eq!(tie.winner, 0);
+
+        let failed_first = arbitrate(&[
+            report("claude", false, "", None),
+            report("gemini", true, "", None),
+        ]);
+        assert_eq!(failed_first.winner, 1);
+    }
+
+    #[test]
+    fn test_parse_verdict() {
+        assert_eq!(parse_verdict("B looks better.\nWINNER: B", 2), Some(1));
+        assert_eq!(parse_verdict("**Winner: a**", 2), Some(0));
+        assert_eq!(parse_verdict("WINNER: C", 2), None);
+        assert_eq!(parse_verdict("no decision", 2), None);
+
+        let prompt = judge_prompt("Fix the bug", &[report("claude", true, "+x", None)]);
+        assert!(prompt.contains("## Candidate A (claude)"));
+        assert!(prompt.contains("WINNER: A"));
+    }
+
+    #[test]
+    fn test_candidates_commit_winner_and_discard() {
+        let tmp = TempDir::new().unwrap();
+        let repo = tmp.path();
+        init_git_repo(repo);
+        fs::write(repo.join("README.md"), "# Test\nuncommitted\n").unwrap();
+
+        let labels = vec!["claude".to_string(), "gemini".to_string()];
+        let candidates = prepare_candidates(repo, 3, &labels).unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert!(candidates[0].path.ends_with("speculative-3-a"));
+
+        // Candidate A adds a file and emits an event; B changes nothing.
+        fs::write(candidates[0].path.join("feature.txt"), "done\n").unwrap();
+        fs::write(
+            candidates[0].events_path(),
+            "{\"topic\":\"build.done\",\"payload\":\"ok\"}\n",
+        )
+        .unwrap();
+
+        let reports: Vec<_> = candidates
+            .iter()
+            .map(|c| c.collect(String::new(), true).unwrap())
+            .collect();
+        assert!(reports[0].diff.contains("feature.txt"));
+        assert!(!reports[0].diff.contains("README.md"));
+        assert!(reports[1].diff.is_empty());
+
+        let verdict = arbitrate(&reports);
+        assert_eq!(verdict.winner, 0);
+
+        let events_path = repo.join(".ralph/events.jsonl");
+        commit(repo, &reports[0], &EventWriter::new(&events_path)).unwrap();
+        discard(repo, &candidates);
+
+        assert_eq!(
+            fs::read_to_string(repo.join("feature.txt")).unwrap(),
+            "done\n"
+        );
+        assert!(
+            fs::read_to_string(repo.join("README.md"))
+                .unwrap()
+                .contains("uncommitted")
+        );
+        assert!(
+            fs::read_to_string(&events_path)
+                .unwrap()
+                .contains("build.done")
+        );
+        assert!(!candidates[0].path.exists());
+        assert!(!candidates[1].path.exists());
+    }
+}
@@ -0,0 +1,496 @@
+//! Pluggable storage for agent state.
+//!
+//! Agent state lives under the workspace's `.ralph/` directory, which an
+//! ephemeral CI runner throws away after every job. A [`StateStore`] keeps a
+//! copy somewhere that outlives the runner, and [`StateSync`] mirrors the
+//! state files between it and `.ralph/`: everything is pulled before the
+//! loop starts and changed files are pushed after each iteration.
+//!
+//! Keys are paths relative to `.ralph/` with `/` separators, e.g.
+//! `agent/scratchpad.md` or `events-20260101-120000.jsonl`. Two stores ship
+//! here: [`FsStateStore`] (a directory, such as a runner cache mount) and
+//! [`S3StateStore`] (a bucket, through the `aws` CLI).
+
+use crate::config::{StateBackend, StateStoreConfig};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Errors from reading or writing a state store.
+#[derive(Debug, thiserror::Error)]
+pub enum StateStoreError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("State store command failed: {0}")]
+    Command(String),
+}
+
+/// A place agent state can be persisted to and restored from.
+pub trait StateStore: Send + Sync {
+    /// Human-readable location, for logs.
+    fn describe(&self) -> String;
+
+    /// Lists every stored key.
+    fn list(&self) -> Result<Vec<String>, StateStoreError>;
+
+    /// Reads a key, or `None` when it isn't stored.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StateStoreError>;
+
+    /// Writes a key, replacing any previous value.
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), StateStoreError>;
+}
+
+/// Stores state as files under a directory.
+#[derive(Debug, Clone)]
+pub struct FsStateStore {
+    root: PathBuf,
+}
+
+impl FsStateStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl StateStore for FsStateStore {
+    fn describe(&self) -> String {
+        self.root.display().to_string()
+    }
+
+    fn list(&self) -> Result<Vec<String>, StateStoreError> {
+        let mut keys = Vec::new();
+        if self.root.is_dir() {
+            collect_files(&self.root, &self.root, &mut keys)?;
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StateStoreError> {
+        match std::fs::read(self.root.join(key)) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), StateStoreError> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+}
+
+/// Stores state in an S3 bucket (or an S3-compatible service).
+///
+/// Shells out to the `aws` CLI, so credentials, region, and profiles come
+/// from the runner's usual AWS environment.
+#[derive(Debug, Clone)]
+pub struct S3StateStore {
+    bucket: String,
+    prefix: String,
+    endpoint_url: Option<String>,
+    program: String,
+}
+
+impl S3StateStore {
+    pub fn new(bucket: &str, prefix: &str, endpoint_url: Option<&str>) -> Self {
+        Self {
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+            endpoint_url: endpoint_url.map(str::to_string),
+            program: "aws".to_string(),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        if self.prefix.is_empty() {
+            format!("s3://{}/{key}", self.bucket)
+        } else {
+            format!("s3://{}/{}/{key}", self.bucket, self.prefix)
+        }
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(&self.program);
+        command.arg("s3").args(args);
+        if let Some(endpoint) = &self.endpoint_url {
+            command.args(["--endpoint-url", endpoint]);
+        }
+        command
+    }
+}
+
+impl StateStore for S3StateStore {
+    fn describe(&self) -> String {
+        self.url("")
+    }
+
+    fn list(&self) -> Result<Vec<String>, StateStoreError> {
+        let output = self
+            .command(&["ls", "--recursive", &self.url("")])
+            .output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // `aws s3 ls` exits 1 without output when nothing is stored yet
+        if !output.status.success() && !stderr.trim().is_empty() {
+            return Err(StateStoreError::Command(stderr.trim().to_string()));
+        }
+
+        let object_prefix = if self.prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", self.prefix)
+        };
+        let mut keys: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(listed_object)
+            .filter_map(|object| object.strip_prefix(&object_prefix).map(str::to_string))
+            .filter(|key| !key.is_empty())
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StateStoreError> {
+        let output = self
+            .command(&["cp", "--quiet", &self.url(key), "-"])
+            .output()?;
+        if output.status.success() {
+            return Ok(Some(output.stdout));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("404") || stderr.contains("Not Found") {
+            return Ok(None);
+        }
+        Err(StateStoreError::Command(stderr.trim().to_string()))
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), StateStoreError> {
+        let mut child = self
+            .command(&["cp", "--quiet", "-", &self.url(key)])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(data)?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(StateStoreError::Command(stderr.trim().to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the object name from an `aws s3 ls --recursive` line
+/// (`<date> <time> <size> <name>`).
+fn listed_object(line: &str) -> Option<&str> {
+    let mut rest = line.trim_start();
+    for _ in 0..3 {
+        let end = rest.find(char::is_whitespace)?;
+        rest = rest[end..].trim_start();
+    }
+    (!rest.is_empty()).then_some(rest)
+}
+
+/// Returns true for `.ralph/`-relative paths that count as agent state.
+///
+/// That's everything under `agent/` except the repo lock and iteration
+/// output (`agent/sessions/`), plus the event journals, the active-journal
+/// marker, and the loop history. Caches, locks, and diagnostics stay local.
+pub fn is_state_key(key: &str) -> bool {
+    if key.is_empty()
+        || key.starts_with('/')
+        || key.split('/').any(|part| part.is_empty() || part == "..")
+    {
+        return false;
+    }
+    if let Some(agent_path) = key.strip_prefix("agent/") {
+        return !agent_path.starts_with("LOCK") && !agent_path.starts_with("sessions/");
+    }
+    if key.contains('/') {
+        return false;
+    }
+    matches!(key, "history.jsonl" | "current-events")
+        || (key.starts_with("events")
+            && Path::new(key).extension().is_some_and(|ext| ext == "jsonl"))
+}
+
+/// Builds the store configured under `state_store`, if any.
+///
+/// The filesystem backend without a `path` means state stays in the
+/// workspace, so there's nothing to mirror.
+pub fn from_config(config: &StateStoreConfig) -> Option<Box<dyn StateStore>> {
+    match config.backend {
+        StateBackend::Filesystem => config
+            .path
+            .as_ref()
+            .map(|path| Box::new(FsStateStore::new(path)) as Box<dyn StateStore>),
+        StateBackend::S3 => {
+            let bucket = config.bucket.as_deref()?;
+            Some(Box::new(S3StateStore::new(
+                bucket,
+                &config.prefix,
+                config.endpoint_url.as_deref(),
+            )))
+        }
+    }
+}
+
+/// Mirrors agent state between a `.ralph/` directory and a store.
+pub struct StateSync {
+    store: Box<dyn StateStore>,
+    ralph_dir: PathBuf,
+    /// Modification time and size of each file as last pulled or pushed.
+    synced: Mutex<HashMap<String, (SystemTime, u64)>>,
+}
+
+impl StateSync {
+    pub fn new(store: Box<dyn StateStore>, ralph_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            store,
+            ralph_dir: ralph_dir.into(),
+            synced: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Builds a sync for the configured store, if any.
+    pub fn from_config(config: &StateStoreConfig, ralph_dir: impl Into<PathBuf>) -> Option<Self> {
+        from_config(config).map(|store| Self::new(store, ralph_dir))
+    }
+
+    /// Where state is mirrored to.
+    pub fn describe(&self) -> String {
+        self.store.describe()
+    }
+
+    /// Restores every stored state file into `.ralph/`, replacing local
+    /// copies. Returns how many files were restored.
+    pub fn pull(&self) -> Result<usize, StateStoreError> {
+        let mut restored = 0;
+        for key in self.store.list()? {
+            if !is_state_key(&key) {
+                continue;
+            }
+            let Some(data) = self.store.get(&key)? else {
+                continue;
+            };
+            let path = self.ralph_dir.join(&key);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, data)?;
+            self.mark_synced(&key, &path);
+            restored += 1;
+        }
+        Ok(restored)
+    }
+
+    /// Uploads state files that changed since the last pull or push.
+    /// Returns how many files were uploaded.
+    pub fn push(&self) -> Result<usize, StateStoreError> {
+        let mut files = Vec::new();
+        if self.ralph_dir.is_dir() {
+            collect_files(&self.ralph_dir, &self.ralph_dir, &mut files)?;
+        }
+
+        let mut uploaded = 0;
+        for key in files.into_iter().filter(|key| is_state_key(key)) {
+            let path = self.ralph_dir.join(&key);
+            let Some(stamp) = file_stamp(&path) else {
+                continue;
+            };
+            if self.synced_stamp(&key) == Some(stamp) {
+                continue;
+            }
+            self.store.put(&key, &std::fs::read(&path)?)?;
+            self.mark_synced(&key, &path);
+            uploaded += 1;
+        }
+        Ok(uploaded)
+    }
+
+    fn synced_stamp(&self, key: &str) -> Option<(SystemTime, u64)> {
+        let synced = self.synced.lock().unwrap_or_else(|e| e.into_inner());
+        synced.get(key).copied()
+    }
+
+    fn mark_synced(&self, key: &str, path: &Path) {
+        if let Some(stamp) = file_stamp(path) {
+            let mut synced = self.synced.lock().unwrap_or_else(|e| e.into_inner());
+            synced.insert(key.to_string(), stamp);
+        }
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Collects regular files below `dir` as `/`-separated paths relative to
+/// `root`. Symlinks (shared memories and specs in worktree loops) are skipped.
+fn collect_files(root: &Path, dir: &Path, keys: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            collect_files(root, &path, keys)?;
+        } else if file_type.is_file()
+            && let Ok(relative) = path.strip_prefix(root)
+        {
+            let key: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            keys.push(key.join("/"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_state_keys() {
+        assert!(is_state_key("agent/scratchpad.md"));
+        assert!(is_state_key("agent/tasks.jsonl"));
+        assert!(is_state_key("events-20260101-120000.jsonl"));
+        assert!(is_state_key("events.jsonl"));
+        assert!(is_state_key("history.jsonl"));
+        assert!(is_state_key("current-events"));
+
+        assert!(!is_state_key("loop.lock"));
+        assert!(!is_state_key("cache/responses/abc.json"));
+        assert!(!is_state_key("diagnostics/events.jsonl"));
+        assert!(!is_state_key("agent/../../etc/passwd"));
+        assert!(!is_state_key("agent/LOCK"));
+        assert!(!is_state_key(
+            "agent/sessions/primary-20260101-120000/iter-1.out"
+        ));
+        assert!(!is_state_key("/agent/scratchpad.md"));
+    }
+
+    #[test]
+    fn test_push_then_pull_restores_state_on_fresh_workspace() {
+        let store_dir = TempDir::new().unwrap();
+        let first = TempDir::new().unwrap();
+        std::fs::create_dir_all(first.path().join("agent")).unwrap();
+        std::fs::write(first.path().join("agent/scratchpad.md"), "- [x] parse").unwrap();
+        std::fs::write(first.path().join("events-1.jsonl"), "{}\n").unwrap();
+        std::fs::write(first.path().join("loop.lock"), "123").unwrap();
+
+        let sync = StateSync::new(Box::new(FsStateStore::new(store_dir.path())), first.path());
+        assert_eq!(sync.push().unwrap(), 2);
+        // Nothing changed since the last push
+        assert_eq!(sync.push().unwrap(), 0);
+        assert!(!store_dir.path().join("loop.lock").exists());
+
+        let second = TempDir::new().unwrap();
+        let sync = StateSync::new(Box::new(FsStateStore::new(store_dir.path())), second.path());
+        assert_eq!(sync.pull().unwrap(), 2);
+        assert_eq!(
+            std::fs::read_to_string(second.path().join("agent/scratchpad.md")).unwrap(),
+            "- [x] parse"
+        );
+        // Pulled files aren't pushed back until they change
+        assert_eq!(sync.push().unwrap(), 0);
+        std::fs::write(
+            second.path().join("agent/scratchpad.md"),
+            "- [x] parse\n- [ ] emit",
+        )
+        .unwrap();
+        assert_eq!(sync.push().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_from_config() {
+        assert!(from_config(&StateStoreConfig::default()).is_none());
+
+        let config = StateStoreConfig {
+            path: Some(PathBuf::from("/mnt/cache/ralph")),
+            ..StateStoreConfig::default()
+        };
+        assert_eq!(from_config(&config).unwrap().describe(), "/mnt/cache/ralph");
+
+        let config = StateStoreConfig {
+            backend: StateBackend::S3,
+            bucket: Some("ci-state".to_string()),
+            prefix: "/repo/main/".to_string(),
+            ..StateStoreConfig::default()
+        };
+        assert_eq!(
+            from_config(&config).unwrap().describe(),
+            "s3://ci-state/repo/main/"
+        );
+    }
+
+    #[test]
+    fn test_listed_object() {
+        assert_eq!(
+            listed_object("2026-01-01 12:00:00       1234 repo/agent/scratch pad.md"),
+            Some("repo/agent/scratch pad.md")
+        );
+        assert_eq!(listed_object("                           PRE agent/"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_s3_store_drives_aws_cli() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // A fake `aws` that backs `s3 ls/cp` with a local directory
+        let temp = TempDir::new().unwrap();
+        let bucket = temp.path().join("bucket");
+        let script = temp.path().join("aws");
+        std::fs::write(
+            &script,
+            format!(
+                r#"#!/bin/sh
+root="{}"
+shift
+case "$1" in
+  ls) cd "$root" 2>/dev/null || exit 1
+      find . -type f | sed 's|^\./||' | while read -r f; do echo "2026-01-01 12:00:00 1 $f"; done ;;
+  cp) if [ "$4" = "-" ]; then
+        f="$root/${{3#s3://ci-state/}}"
+        [ -f "$f" ] || {{ echo "An error occurred (404) when calling the HeadObject operation: Not Found" >&2; exit 1; }}
+        cat "$f"
+      else
+        f="$root/${{4#s3://ci-state/}}"
+        mkdir -p "$(dirname "$f")" && cat > "$f"
+      fi ;;
+esac
+"#,
+                bucket.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut store = S3StateStore::new("ci-state", "repo", None);
+        store.program = script.display().to_string();
+
+        assert!(store.list().unwrap().is_empty());
+        assert_eq!(store.get("agent/scratchpad.md").unwrap(), None);
+        store.put("agent/scratchpad.md", b"notes").unwrap();
+        assert_eq!(store.list().unwrap(), vec!["agent/scratchpad.md"]);
+        assert_eq!(
+            store.get("agent/scratchpad.md").unwrap(),
+            Some(b"notes".to_vec())
+        );
+    }
+}
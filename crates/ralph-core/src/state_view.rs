@@ -0,0 +1,194 @@
+//! Public read model of a running loop.
+//!
+//! Each iteration the loop writes a [`LoopStateView`] to
+//! `.ralph/agent/state.json`, so dashboards and scripts can follow a run
+//! without scraping logs. The file is replaced in one step, so readers never
+//! see a partial write.
+//!
+//! The JSON shape is an interface: fields are only added within a
+//! [`STATE_SCHEMA_VERSION`], never renamed or removed. Readers should ignore
+//! fields they don't know and check `schema_version` before relying on the
+//! rest.
+
+use crate::drift::DriftEntry;
+use crate::event_loop::LoopStatus;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+/// Version of the state file's JSON shape.
+pub const STATE_SCHEMA_VERSION: u32 = 1;
+
+/// Snapshot of a loop's progress, written to the state file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoopStateView {
+    /// Always [`STATE_SCHEMA_VERSION`] for files this build writes.
+    pub schema_version: u32,
+    /// When the file was written.
+    pub updated_at: DateTime<Utc>,
+    /// Loop ID, for worktree loops.
+    pub loop_id: Option<String>,
+    /// Iterations completed so far.
+    pub iteration: u32,
+    /// Iteration limit in effect.
+    pub max_iterations: u32,
+    /// Wall-clock seconds since the loop started.
+    pub elapsed_seconds: u64,
+    /// Failed iterations in a row.
+    pub consecutive_failures: u32,
+    /// Hat that ran the last iteration.
+    pub last_hat: Option<String>,
+    /// Current workflow phase, if `phases:` is configured.
+    pub phase: Option<String>,
+    /// Whether the loop is running or paused between iterations.
+    #[serde(default)]
+    pub status: LoopStatus,
+    /// Spend across the whole loop.
+    pub cost: CostView,
+    /// Every registered hat, sorted by ID.
+    pub hats: Vec<HatView>,
+    /// Human interaction events (`human.*`) waiting to be taken.
+    pub human_pending: usize,
+    /// Topics hats published without declaring them.
+    #[serde(default)]
+    pub drift: Vec<DriftEntry>,
+    /// How the loop ended; `None` while it's running.
+    pub termination: Option<TerminationView>,
+}
+
+/// One hat in a [`LoopStateView`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HatView {
+    pub id: String,
+    pub name: String,
+    /// Iterations the hat has run.
+    pub activations: u32,
+    /// Wall-clock seconds the hat has run.
+    pub runtime_seconds: u64,
+    /// What the hat has spent.
+    pub cost: CostView,
+    /// Topics of the events queued for the hat, oldest first.
+    pub pending: Vec<String>,
+    /// Whether the hat is paused after publishing a `*.blocked` topic.
+    pub paused: bool,
+    /// Whether the hat hit `max_activations` or `max_runtime_seconds`.
+    pub exhausted: bool,
+}
+
+/// Spend in a [`LoopStateView`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostView {
+    pub usd: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// How a loop ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TerminationView {
+    /// Reason, as in the `loop.terminate` payload (`completed`,
+    /// `max_iterations`, ...).
+    pub reason: String,
+    /// Process exit code for the reason.
+    pub exit_code: i32,
+    /// What tripped, for limits and failures.
+    pub detail: Option<String>,
+}
+
+impl From<crate::Usage> for CostView {
+    fn from(usage: crate::Usage) -> Self {
+        Self {
+            usd: usage.cost_usd,
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+        }
+    }
+}
+
+impl LoopStateView {
+    /// Writes the view to `path`, replacing the previous file in one step.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(tmp, path)
+    }
+
+    /// Reads a state file.
+    ///
+    /// Files from a newer schema are read as far as this build understands
+    /// them; compare `schema_version` to [`STATE_SCHEMA_VERSION`] to tell.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(io::Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn view() -> LoopStateView {
+        LoopStateView {
+            schema_version: STATE_SCHEMA_VERSION,
+            updated_at: Utc::now(),
+            loop_id: None,
+            iteration: 3,
+            max_iterations: 100,
+            elapsed_seconds: 42,
+            consecutive_failures: 0,
+            last_hat: Some("builder".to_string()),
+            phase: None,
+            status: LoopStatus::Running,
+            cost: CostView {
+                usd: 0.5,
+                input_tokens: 1000,
+                output_tokens: 200,
+            },
+            hats: vec![HatView {
+                id: "builder".to_string(),
+                name: "Builder".to_string(),
+                activations: 2,
+                runtime_seconds: 30,
+                cost: CostView::default(),
+                pending: vec!["build.task".to_string()],
+                paused: false,
+                exhausted: false,
+            }],
+            human_pending: 0,
+            drift: Vec::new(),
+            termination: None,
+        }
+    }
+
+    #[test]
+    fn test_write_then_read() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(".ralph/agent/state.json");
+
+        let view = view();
+        view.write(&path).unwrap();
+        assert_eq!(LoopStateView::read(&path).unwrap(), view);
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+
+    #[test]
+    fn test_newer_schema_fields_are_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state.json");
+        let mut json = serde_json::to_value(view()).unwrap();
+        json["schema_version"] = 2.into();
+        json["hats"][0]["model"] = "opus".into();
+        json["queue_latency_ms"] = 12.into();
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        let read = LoopStateView::read(&path).unwrap();
+        assert_eq!(read.schema_version, 2);
+        assert_eq!(read.hats[0].pending, vec!["build.task"]);
+    }
+}
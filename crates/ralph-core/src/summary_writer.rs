@@ -72,6 +72,11 @@ impl SummaryWriter {
         }
     }
 
+    /// Returns the path the summary is written to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     /// Writes the summary file based on loop state and termination reason.
     ///
     /// This is called by the orchestrator when the loop terminates.
@@ -128,6 +133,9 @@ impl SummaryWriter {
         // Status
         let status = self.status_text(reason);
         content.push_str(&format!("**Status:** {status}\n"));
+        if let Some(detail) = reason.detail() {
+            content.push_str(&format!("**Details:** {detail}\n"));
+        }
         content.push_str(&format!("**Iterations:** {}\n", state.iteration));
         content.push_str(&format!(
             "**Duration:** {}\n",
@@ -139,6 +147,14 @@ impl SummaryWriter {
             content.push_str(&format!("**Est. cost:** ${:.2}\n", state.cumulative_cost));
         }
 
+        // Last error (for failure terminations)
+        if let Some(error) = reason.last_error() {
+            content.push('\n');
+            content.push_str("## Last Error\n\n```\n");
+            content.push_str(error);
+            content.push_str("\n```\n");
+        }
+
         // Tasks section (read from scratchpad if available)
         content.push('\n');
         content.push_str("## Tasks\n\n");
@@ -153,6 +169,13 @@ impl SummaryWriter {
         content.push_str("## Events\n\n");
         content.push_str(&self.summarize_events());
 
+        // Topology drift (topics hats published without declaring them)
+        if !state.drift.is_empty() {
+            content.push('\n');
+            content.push_str("## Topology Drift\n\n");
+            content.push_str(&state.drift.markdown());
+        }
+
         // Final commit section
         if let Some(commit) = final_commit {
             content.push('\n');
@@ -211,15 +234,21 @@ impl SummaryWriter {
     fn status_text(&self, reason: &TerminationReason) -> &'static str {
         match reason {
             TerminationReason::CompletionPromise => "Completed successfully",
-            TerminationReason::MaxIterations => "Stopped: max iterations reached",
-            TerminationReason::MaxRuntime => "Stopped: max runtime exceeded",
-            TerminationReason::MaxCost => "Stopped: max cost exceeded",
-            TerminationReason::ConsecutiveFailures => "Failed: too many consecutive failures",
-            TerminationReason::LoopThrashing => "Failed: loop thrashing detected",
-            TerminationReason::ValidationFailure => "Failed: too many malformed JSONL events",
+            TerminationReason::MaxIterations { .. } => "Stopped: max iterations reached",
+            TerminationReason::MaxRuntime { .. } => "Stopped: max runtime exceeded",
+            TerminationReason::MaxCost { .. } => "Stopped: max cost exceeded",
+            TerminationReason::ConsecutiveFailures { .. } => {
+                "Failed: too many consecutive failures"
+            }
+            TerminationReason::LoopThrashing { .. } => "Failed: loop thrashing detected",
+            TerminationReason::DelegationLoop { .. } => "Failed: repeated delegation detected",
+            TerminationReason::ValidationFailure { .. } => {
+                "Failed: too many malformed JSONL events"
+            }
             TerminationReason::Stopped => "Stopped manually",
             TerminationReason::Interrupted => "Interrupted by signal",
             TerminationReason::RestartRequested => "Restarting by human request",
+            TerminationReason::IdleTimeout { .. } => "Stopped: idle timeout while parked",
         }
     }
 
@@ -320,6 +349,7 @@ mod tests {
             exhausted_hats: std::collections::HashSet::new(),
             last_checkin_at: None,
             last_active_hat_ids: Vec::new(),
+            ..LoopState::default()
         }
     }
 
@@ -332,11 +362,16 @@ mod tests {
             "Completed successfully"
         );
         assert_eq!(
-            writer.status_text(&TerminationReason::MaxIterations),
+            writer.status_text(&TerminationReason::MaxIterations { limit: 12 }),
             "Stopped: max iterations reached"
         );
         assert_eq!(
-            writer.status_text(&TerminationReason::ConsecutiveFailures),
+            writer.status_text(&TerminationReason::ConsecutiveFailures {
+                hat: None,
+                failures: 5,
+                threshold: 5,
+                last_error: None,
+            }),
             "Failed: too many consecutive failures"
         );
         assert_eq!(
@@ -400,6 +435,46 @@ More text here.
         assert!(content.contains("## Events"));
         assert!(content.contains("## Final Commit"));
         assert!(content.contains("abc1234: feat(auth): add tokens"));
+        assert!(!content.contains("## Topology Drift"));
+
+        let mut state = test_state();
+        state.drift.record("builder", "build.skipped", 4);
+        let content = writer.generate_content_with_landing(
+            &TerminationReason::CompletionPromise,
+            &state,
+            None,
+            None,
+            None,
+        );
+        assert!(
+            content.contains(
+                "## Topology Drift\n\n- builder published `build.skipped` (iteration 4)\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_content_includes_failure_context() {
+        let writer = SummaryWriter::default();
+        let state = test_state();
+
+        let content = writer.generate_content_with_landing(
+            &TerminationReason::ConsecutiveFailures {
+                hat: Some(ralph_proto::HatId::new("builder")),
+                failures: 5,
+                threshold: 5,
+                last_error: Some("error[E0308]: mismatched types".to_string()),
+            },
+            &state,
+            None,
+            None,
+            None,
+        );
+
+        assert!(
+            content.contains("**Details:** 5 failures in a row (limit 5), last in hat 'builder'")
+        );
+        assert!(content.contains("## Last Error\n\n```\nerror[E0308]: mismatched types\n```"));
     }
 
     #[test]
@@ -0,0 +1,75 @@
+//! Survey phase before the main loop.
+//!
+//! A survey is one time-boxed, read-only iteration that assesses the
+//! repository and plans the work before the loop starts spending its real
+//! budget. The agent only answers; the orchestrator appends the answer to the
+//! scratchpad so every later iteration starts from the plan.
+//! See [`SurveyConfig`](crate::SurveyConfig).
+
+/// Builds the survey prompt for `objective`.
+pub fn build_prompt(objective: &str, timeout_seconds: u64) -> String {
+    format!(
+        r"## SURVEY
+
+This is a read-only survey before any work starts. You have {timeout_seconds} seconds.
+
+You MUST NOT modify files, commit, or publish events.
+You MUST only read the repository and answer with the sections below.
+
+### Repo assessment
+Layout, build and test commands, conventions, and risks that matter for the objective.
+
+### Plan
+Numbered steps that achieve the objective, with the files each step touches.
+
+### Open questions
+Decisions a human should make before the work starts. Write `None` if there are none.
+
+Your answer is saved to the scratchpad and approved before the build loop begins.
+
+## OBJECTIVE
+
+> {objective}
+"
+    )
+}
+
+/// Formats a survey answer for appending to the scratchpad.
+pub fn scratchpad_entry(output: &str) -> String {
+    let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
+    format!("\n## SURVEY ({timestamp})\n\n{}\n", output.trim())
+}
+
+/// Returns true if a terminal answer approves the plan.
+pub fn is_approval(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_is_read_only_and_carries_objective() {
+        let prompt = build_prompt("Add OAuth login", 300);
+        assert!(prompt.contains("You MUST NOT modify files"));
+        assert!(prompt.contains("300 seconds"));
+        assert!(prompt.contains("### Plan"));
+        assert!(prompt.contains("> Add OAuth login"));
+    }
+
+    #[test]
+    fn test_scratchpad_entry_wraps_output() {
+        let entry = scratchpad_entry("\n### Plan\n1. Do it\n\n");
+        assert!(entry.starts_with("\n## SURVEY ("));
+        assert!(entry.ends_with("### Plan\n1. Do it\n"));
+    }
+
+    #[test]
+    fn test_is_approval() {
+        assert!(is_approval("y\n"));
+        assert!(is_approval(" Yes "));
+        assert!(!is_approval(""));
+        assert!(!is_approval("n"));
+    }
+}
@@ -25,6 +25,38 @@ pub fn format_elapsed(duration: Duration) -> String {
     format!("{mins:02}:{secs:02}")
 }
 
+/// Runs blocking work (file or git IO, blocking HTTP) without stalling the runtime.
+///
+/// On a multi-threaded tokio runtime the current worker hands its other tasks
+/// off before `f` runs, so the TUI and other loop tasks keep making progress.
+/// Outside a runtime, or on a current-thread runtime, `f` simply runs inline.
+pub fn run_blocking<T>(f: impl FnOnce() -> T) -> T {
+    use tokio::runtime::{Handle, RuntimeFlavor};
+
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
+/// Runs blocking work on tokio's blocking thread pool and waits for it.
+///
+/// Unlike [`run_blocking`], the calling task yields while `f` runs, so other
+/// tasks keep its worker thread, on any runtime flavor. A panic in `f` is
+/// resumed in the caller.
+pub async fn spawn_blocking<T, F>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(value) => value,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,7 +73,7 @@ mod tests {
 
     #[test]
     fn format_elapsed_one_minute() {
-        assert_eq!(format_elapsed(Duration::from_secs(60)), "01:00");
+        assert_eq!(format_elapsed(Duration::from_mins(1)), "01:00");
     }
 
     #[test]
@@ -55,6 +87,30 @@ mod tests {
         assert_eq!(format_elapsed(Duration::from_secs(3661)), "61:01");
     }
 
+    #[test]
+    fn run_blocking_without_runtime_runs_inline() {
+        assert_eq!(run_blocking(|| 2 + 2), 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn run_blocking_on_multi_thread_runtime() {
+        let value = run_blocking(|| {
+            std::thread::sleep(Duration::from_millis(10));
+            "done"
+        });
+        assert_eq!(value, "done");
+    }
+
+    #[tokio::test]
+    async fn spawn_blocking_on_current_thread_runtime() {
+        let value = spawn_blocking(|| {
+            std::thread::sleep(Duration::from_millis(10));
+            "done"
+        })
+        .await;
+        assert_eq!(value, "done");
+    }
+
     #[test]
     fn format_elapsed_pads_single_digits() {
         // Ensure single-digit values are zero-padded
@@ -0,0 +1,1097 @@
+//! Build and test results as events (`verify:` in ralph.yml).
+//!
+//! After an iteration, the configured verification command runs in the
+//! workspace and its result is turned into a [`VerificationReport`]: pass or
+//! fail from the exit status, plus test counts and failure details parsed from
+//! the output. The report is published as a `ci.passed` or `ci.failed` event
+//! so downstream hats act on what the build actually did rather than on the
+//! agent's own claims.
+//!
+//! The [`OutputParser`] picks which tools' output is understood. The cargo
+//! parser (the default) handles three shapes, which may be mixed in one run:
+//!
+//! - cargo/rustc JSON diagnostics (`--message-format json`)
+//! - libtest's human output (`test foo ... FAILED`, `---- foo stdout ----`)
+//! - libtest JSON (`--format json`)
+//!
+//! The node parser handles Jest, Vitest, `node --test` (TAP), ESLint's
+//! stylish output, and tsc errors.
+//!
+//! Anything else still yields pass/fail, with the tail of the output attached
+//! to failures.
+//!
+//! With `triage: true`, a failed run is also split into [`FailureCluster`]s
+//! (compiler errors by file, lints by name, test failures by module), each
+//! published as its own `fix.task` event so the builder gets focused work
+//! items instead of one wall of text.
+
+use crate::event_reader::Event;
+use crate::text::truncate_with_ellipsis;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Topic published when the verification command succeeds.
+pub const PASSED_TOPIC: &str = "ci.passed";
+
+/// Topic published when the verification command fails or times out.
+pub const FAILED_TOPIC: &str = "ci.failed";
+
+/// Topic of the per-cluster work items published by triage.
+pub const FIX_TASK_TOPIC: &str = "fix.task";
+
+/// At most this many failures are reported.
+const MAX_FAILURES: usize = 20;
+
+/// Failure messages are truncated to this many characters.
+const MAX_MESSAGE_CHARS: usize = 1000;
+
+/// Lines of output attached when no failures could be parsed.
+const TAIL_LINES: usize = 30;
+
+/// Which ecosystem's tool output a verification command produces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputParser {
+    /// cargo/rustc diagnostics and libtest output.
+    #[default]
+    Cargo,
+    /// Jest, Vitest, `node --test`, ESLint, and tsc output.
+    Node,
+}
+
+/// One failed test or compiler error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Failure {
+    /// Test name, or the error code for compiler errors (`E0308`, `error`).
+    pub name: String,
+    /// `file:line` of the failure, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Panic message or diagnostic text.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub message: String,
+}
+
+/// Result of one verification run; the payload of `ci.*` events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    /// The command that ran.
+    pub command: String,
+    /// Whether the command exited successfully within its timeout.
+    pub passed: bool,
+    /// Exit code, if the process exited normally.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Whether the command was killed for running too long.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    /// Passing tests counted from libtest summaries.
+    #[serde(default)]
+    pub tests_passed: u32,
+    /// Failing tests counted from libtest summaries.
+    #[serde(default)]
+    pub tests_failed: u32,
+    /// Parsed failures, capped at 20.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<Failure>,
+    /// Last lines of output, set when the run failed without parsed failures.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub output_tail: String,
+}
+
+impl VerificationReport {
+    /// Builds a report from the command's exit status and combined output,
+    /// parsed as cargo output.
+    pub fn from_output(command: &str, exit_code: Option<i32>, output: &str) -> Self {
+        Self::parse(OutputParser::Cargo, command, exit_code, output)
+    }
+
+    /// Builds a report, parsing the output with `parser`.
+    pub fn parse(
+        parser: OutputParser,
+        command: &str,
+        exit_code: Option<i32>,
+        output: &str,
+    ) -> Self {
+        let parsed = match parser {
+            OutputParser::Cargo => parse_output(output),
+            OutputParser::Node => parse_node_output(output),
+        };
+        let passed = exit_code == Some(0);
+        let output_tail = if !passed && parsed.failures.is_empty() {
+            tail(output)
+        } else {
+            String::new()
+        };
+        Self {
+            command: command.to_string(),
+            passed,
+            exit_code,
+            timed_out: false,
+            tests_passed: parsed.tests_passed,
+            tests_failed: parsed.tests_failed,
+            failures: parsed.failures,
+            output_tail,
+        }
+    }
+
+    /// Returns `ci.passed` or `ci.failed`.
+    pub fn topic(&self) -> &'static str {
+        if self.passed {
+            PASSED_TOPIC
+        } else {
+            FAILED_TOPIC
+        }
+    }
+
+    /// Converts the report into an events-file record.
+    pub fn to_event(&self) -> Event {
+        Event {
+            topic: self.topic().to_string(),
+            payload: serde_json::to_string(self).ok(),
+            ts: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// What kind of problem a failure is; decides how failures are clustered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FailureKind {
+    /// Compiler error (`E0308`, `TS2322`, `error`); clustered by file.
+    Compile,
+    /// Lint denied as an error (`clippy::needless_return`,
+    /// `eslint::no-unused-vars`); clustered by lint.
+    Lint,
+    /// Failed test; clustered by module path.
+    Test,
+}
+
+impl FailureKind {
+    /// Returns the name used in `fix.task` payloads.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Compile => "compile",
+            Self::Lint => "lint",
+            Self::Test => "test",
+        }
+    }
+}
+
+impl Failure {
+    /// Classifies the failure from its name.
+    pub fn kind(&self) -> FailureKind {
+        let name = self.name.as_str();
+        let is_error_code = name.len() == 5
+            && name.starts_with('E')
+            && name[1..].chars().all(|c| c.is_ascii_digit());
+        let is_tsc_code = name.len() > 2
+            && name.starts_with("TS")
+            && name[2..].chars().all(|c| c.is_ascii_digit());
+        if name == "error" || is_error_code || is_tsc_code {
+            FailureKind::Compile
+        } else if ["clippy::", "rustdoc::", "eslint::"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        {
+            FailureKind::Lint
+        } else {
+            FailureKind::Test
+        }
+    }
+
+    fn file(&self) -> Option<&str> {
+        let location = self.location.as_deref()?;
+        Some(location.rsplit_once(':').map_or(location, |(file, _)| file))
+    }
+}
+
+/// Related failures that make one focused fix task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailureCluster {
+    /// Kind shared by every failure in the cluster.
+    pub kind: FailureKind,
+    /// File, lint name, or test module the failures share.
+    pub key: String,
+    /// The clustered failures, in report order.
+    pub failures: Vec<Failure>,
+}
+
+impl FailureCluster {
+    /// Converts the cluster into a `fix.task` events-file record.
+    ///
+    /// The payload is `field: value` lines, so it works with handoff contracts.
+    pub fn to_event(&self, command: &str) -> Event {
+        let noun = match self.kind {
+            FailureKind::Compile => "compiler error",
+            FailureKind::Lint => "lint",
+            FailureKind::Test => "failing test",
+        };
+        let count = self.failures.len();
+        let mut files: Vec<&str> = self.failures.iter().filter_map(Failure::file).collect();
+        files.dedup();
+
+        let mut payload = format!(
+            "Fix {count} {noun}{} in {}\nkind: {}\n",
+            if count == 1 { "" } else { "s" },
+            self.key,
+            self.kind.as_str()
+        );
+        if !files.is_empty() {
+            payload.push_str(&format!("files: {}\n", files.join(", ")));
+        }
+        payload.push_str("failures:\n");
+        for failure in &self.failures {
+            let summary = failure
+                .message
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty() && !line.starts_with("thread '"))
+                .unwrap_or_default();
+            payload.push_str(&format!("- {}", failure.name));
+            if let Some(location) = &failure.location {
+                payload.push_str(&format!(" at {location}"));
+            }
+            if !summary.is_empty() {
+                payload.push_str(&format!(": {}", truncate_with_ellipsis(summary, 200)));
+            }
+            payload.push('\n');
+        }
+        payload.push_str(&format!(
+            "acceptance: `{command}` no longer reports these failures"
+        ));
+
+        Event {
+            topic: FIX_TASK_TOPIC.to_string(),
+            payload: Some(payload),
+            ts: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Groups a failed report's failures into fix tasks.
+///
+/// Compiler errors come first, since nothing else can be fixed or tested
+/// until the build compiles, then lints, then test failures. Returns nothing
+/// for passing runs and runs without parsed failures.
+pub fn triage(report: &VerificationReport) -> Vec<FailureCluster> {
+    if report.passed {
+        return Vec::new();
+    }
+
+    let mut clusters: Vec<FailureCluster> = Vec::new();
+    for failure in &report.failures {
+        let kind = failure.kind();
+        let key = match kind {
+            FailureKind::Compile => failure.file().unwrap_or("the build").to_string(),
+            FailureKind::Lint => failure.name.clone(),
+            FailureKind::Test => failure
+                .name
+                .rsplit_once("::")
+                .map_or(failure.name.as_str(), |(module, _)| module)
+                .to_string(),
+        };
+        match clusters.iter_mut().find(|c| c.kind == kind && c.key == key) {
+            Some(cluster) => cluster.failures.push(failure.clone()),
+            None => clusters.push(FailureCluster {
+                kind,
+                key,
+                failures: vec![failure.clone()],
+            }),
+        }
+    }
+    clusters.sort_by_key(|c| c.kind);
+    clusters
+}
+
+/// Runs `command` with the shell in `workspace` and reports the result,
+/// parsing its output with `parser`.
+///
+/// The command is killed if it runs longer than `timeout`.
+///
+/// # Errors
+///
+/// Returns an error if the shell can't be started.
+pub async fn run_verification(
+    command: &str,
+    parser: OutputParser,
+    workspace: &Path,
+    timeout: Duration,
+) -> std::io::Result<VerificationReport> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    let child = cmd
+        .current_dir(workspace)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => {
+            let output = output?;
+            let mut combined = String::from_utf8_lossy(&output.stdout).into_owned();
+            combined.push('\n');
+            combined.push_str(&String::from_utf8_lossy(&output.stderr));
+            Ok(VerificationReport::parse(
+                parser,
+                command,
+                output.status.code(),
+                &combined,
+            ))
+        }
+        Err(_) => Ok(VerificationReport {
+            command: command.to_string(),
+            passed: false,
+            exit_code: None,
+            timed_out: true,
+            tests_passed: 0,
+            tests_failed: 0,
+            failures: vec![],
+            output_tail: format!("Timed out after {}s", timeout.as_secs()),
+        }),
+    }
+}
+
+#[derive(Debug, Default)]
+struct ParsedOutput {
+    tests_passed: u32,
+    tests_failed: u32,
+    failures: Vec<Failure>,
+}
+
+impl ParsedOutput {
+    fn push(&mut self, failure: Failure) {
+        if self.failures.len() < MAX_FAILURES && !self.failures.iter().any(|f| f == &failure) {
+            self.failures.push(failure);
+        }
+    }
+}
+
+/// Extracts test counts and failures from verification output.
+fn parse_output(output: &str) -> ParsedOutput {
+    let mut parsed = ParsedOutput::default();
+    // libtest human output: names from `test x ... FAILED`, details from
+    // `---- x stdout ----` sections.
+    let mut failed_tests: Vec<String> = Vec::new();
+    let mut sections: HashMap<String, Vec<&str>> = HashMap::new();
+    let mut section: Option<String> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('{')
+            && let Ok(value) = serde_json::from_str::<Value>(trimmed)
+        {
+            section = None;
+            parse_json_line(&value, &mut parsed);
+            continue;
+        }
+
+        if let Some(name) = trimmed
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            section = Some(name.to_string());
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix("test result: ") {
+            section = None;
+            parse_summary(rest, &mut parsed);
+            continue;
+        }
+        if let Some(name) = trimmed
+            .strip_prefix("test ")
+            .and_then(|rest| rest.strip_suffix(" ... FAILED"))
+        {
+            if !failed_tests.iter().any(|t| t == name) {
+                failed_tests.push(name.to_string());
+            }
+            continue;
+        }
+        if trimmed == "failures:" {
+            section = None;
+            continue;
+        }
+        if let Some(ref name) = section {
+            sections.entry(name.clone()).or_default().push(line);
+        }
+    }
+
+    for name in failed_tests {
+        let message = sections
+            .get(&name)
+            .map(|lines| lines.join("\n").trim().to_string())
+            .unwrap_or_default();
+        parsed.push(Failure {
+            location: panic_location(&message),
+            message: truncate_with_ellipsis(&message, MAX_MESSAGE_CHARS),
+            name,
+        });
+    }
+    parsed
+}
+
+fn parse_json_line(value: &Value, parsed: &mut ParsedOutput) {
+    // cargo --message-format json
+    if value["reason"] == "compiler-message" && value["message"]["level"] == "error" {
+        let message = &value["message"];
+        let Some(spans) = message["spans"].as_array().filter(|s| !s.is_empty()) else {
+            // "aborting due to N previous errors" and similar summaries
+            return;
+        };
+        let location = spans
+            .iter()
+            .find(|span| span["is_primary"] == true)
+            .or_else(|| spans.first())
+            .and_then(|span| {
+                Some(format!(
+                    "{}:{}",
+                    span["file_name"].as_str()?,
+                    span["line_start"].as_u64()?
+                ))
+            });
+        parsed.push(Failure {
+            name: message["code"]["code"]
+                .as_str()
+                .unwrap_or("error")
+                .to_string(),
+            location,
+            message: truncate_with_ellipsis(
+                message["message"].as_str().unwrap_or_default(),
+                MAX_MESSAGE_CHARS,
+            ),
+        });
+        return;
+    }
+
+    // libtest --format json
+    match (value["type"].as_str(), value["event"].as_str()) {
+        (Some("test"), Some("failed")) => {
+            let message = value["stdout"].as_str().unwrap_or_default().trim();
+            parsed.push(Failure {
+                name: value["name"].as_str().unwrap_or("unknown").to_string(),
+                location: panic_location(message),
+                message: truncate_with_ellipsis(message, MAX_MESSAGE_CHARS),
+            });
+        }
+        (Some("suite"), Some("ok" | "failed")) => {
+            parsed.tests_passed += count(&value["passed"]);
+            parsed.tests_failed += count(&value["failed"]);
+        }
+        _ => {}
+    }
+}
+
+fn count(value: &Value) -> u32 {
+    value
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+        .unwrap_or(0)
+}
+
+/// Parses `ok. 3 passed; 1 failed; 0 ignored; ...` from a libtest summary.
+fn parse_summary(summary: &str, parsed: &mut ParsedOutput) {
+    for part in summary.split(';') {
+        let mut words = part.split_whitespace().rev();
+        let (Some(label), Some(number)) = (words.next(), words.next()) else {
+            continue;
+        };
+        let Ok(number) = number.parse::<u32>() else {
+            continue;
+        };
+        match label {
+            "passed" => parsed.tests_passed += number,
+            "failed" => parsed.tests_failed += number,
+            _ => {}
+        }
+    }
+}
+
+/// Finds `file:line` in a panic message (`panicked at src/lib.rs:10:5:`).
+fn panic_location(message: &str) -> Option<String> {
+    let rest = message.split("panicked at ").nth(1)?;
+    let location = rest.split_whitespace().next()?.trim_end_matches([':', ',']);
+    let mut parts = location.rsplitn(3, ':');
+    let (_column, line, file) = (parts.next()?, parts.next()?, parts.next()?);
+    line.parse::<u32>().ok()?;
+    Some(format!("{file}:{line}"))
+}
+
+/// A Jest, Vitest, or TAP test failure whose details are still being read.
+struct PendingFailure<'a> {
+    name: String,
+    location: Option<String>,
+    lines: Vec<&'a str>,
+}
+
+impl PendingFailure<'_> {
+    fn finish(self, parsed: &mut ParsedOutput) {
+        let message = self.lines.join("\n").trim().to_string();
+        parsed.push(Failure {
+            location: self.location.or_else(|| source_location(&message)),
+            message: truncate_with_ellipsis(&message, MAX_MESSAGE_CHARS),
+            name: self.name,
+        });
+    }
+}
+
+/// Extracts test counts and failures from Node tool output.
+///
+/// Test failures are named `file::title` when the file is known, so triage
+/// clusters them by test file. ESLint errors are named `eslint::<rule>` and
+/// tsc errors by their `TS` code.
+fn parse_node_output(output: &str) -> ParsedOutput {
+    let mut parsed = ParsedOutput::default();
+    // Jest prints `FAIL <file>` before the `●` blocks of its failed tests
+    let mut test_file: Option<String> = None;
+    // ESLint prints the file on its own line before its problems
+    let mut lint_file: Option<String> = None;
+    let mut pending: Option<PendingFailure> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        let starts_failure = if let Some(rest) = trimmed.strip_prefix("FAIL ") {
+            let rest = rest.trim();
+            match rest.split_once(" > ") {
+                // Vitest: `FAIL  src/a.test.ts > suite > test`
+                Some((file, title)) => Some((format!("{file}::{title}"), None)),
+                // Jest: `FAIL src/a.test.js`
+                None => {
+                    test_file = Some(rest.to_string());
+                    None
+                }
+            }
+        } else if let Some(title) = trimmed.strip_prefix("● ") {
+            if title == "Test suite failed to run" {
+                Some(("error".to_string(), test_file.clone()))
+            } else {
+                let name = match &test_file {
+                    Some(file) => format!("{file}::{title}"),
+                    None => title.to_string(),
+                };
+                Some((name, None))
+            }
+        } else {
+            trimmed
+                .strip_prefix("not ok ")
+                .map(|rest| (tap_test_name(rest).to_string(), None))
+        };
+        if let Some((name, location)) = starts_failure {
+            if let Some(failure) = pending.take() {
+                failure.finish(&mut parsed);
+            }
+            pending = Some(PendingFailure {
+                name,
+                location,
+                lines: Vec::new(),
+            });
+            continue;
+        }
+
+        let ends_failure = trimmed.starts_with("PASS ")
+            || trimmed.starts_with("ok ")
+            || trimmed.starts_with("Test Suites:")
+            || trimmed.starts_with('⎯')
+            || trimmed.starts_with("# ");
+        if ends_failure && let Some(failure) = pending.take() {
+            failure.finish(&mut parsed);
+        }
+
+        if let Some(rest) = trimmed
+            .strip_prefix("Tests:")
+            .or_else(|| trimmed.strip_prefix("Tests "))
+        {
+            // Jest `1 failed, 2 passed, 3 total`; Vitest `1 failed | 2 passed (3)`
+            for part in rest.split([',', '|']) {
+                parse_count(part, &mut parsed);
+            }
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix("# ") {
+            // TAP `# pass 2`
+            let mut words = rest.split_whitespace();
+            if let (Some(label), Some(number)) = (words.next(), words.next()) {
+                parse_count(&format!("{number} {label}"), &mut parsed);
+            }
+            continue;
+        }
+
+        if let Some(failure) = tsc_error(trimmed) {
+            parsed.push(failure);
+            continue;
+        }
+
+        if line == trimmed && looks_like_path(trimmed) {
+            if let Some(failure) = pending.take() {
+                failure.finish(&mut parsed);
+            }
+            lint_file = Some(trimmed.to_string());
+            continue;
+        }
+        if let Some(file) = &lint_file
+            && let Some(failure) = eslint_error(file, trimmed)
+        {
+            parsed.push(failure);
+            continue;
+        }
+
+        if let Some(failure) = pending.as_mut() {
+            failure.lines.push(line);
+        }
+    }
+    if let Some(failure) = pending {
+        failure.finish(&mut parsed);
+    }
+    parsed
+}
+
+/// Adds `N passed` / `N failed` to the counts.
+fn parse_count(part: &str, parsed: &mut ParsedOutput) {
+    let mut words = part.split_whitespace();
+    let (Some(number), Some(label)) = (words.next(), words.next()) else {
+        return;
+    };
+    let Ok(number) = number.parse::<u32>() else {
+        return;
+    };
+    match label {
+        "passed" | "pass" => parsed.tests_passed += number,
+        "failed" | "fail" => parsed.tests_failed += number,
+        _ => {}
+    }
+}
+
+/// Returns the description of a TAP `not ok 3 - name # comment` line.
+fn tap_test_name(rest: &str) -> &str {
+    let rest = rest.trim_start_matches(|c: char| c.is_ascii_digit()).trim();
+    let rest = rest.strip_prefix("- ").unwrap_or(rest);
+    rest.split(" # ").next().unwrap_or(rest).trim()
+}
+
+/// Parses `src/a.ts(3,5): error TS2322: ...` and `src/a.ts:3:5 - error TS2322: ...`.
+fn tsc_error(line: &str) -> Option<Failure> {
+    let (position, rest) = line
+        .split_once(": error TS")
+        .or_else(|| line.split_once(" - error TS"))?;
+    let (code, message) = rest.split_once(':')?;
+    if code.is_empty() || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let location = match position.split_once('(') {
+        Some((file, coords)) => format!("{file}:{}", coords.split(',').next()?),
+        None => {
+            let mut parts = position.rsplitn(3, ':');
+            let (_column, line, file) = (parts.next()?, parts.next()?, parts.next()?);
+            format!("{file}:{line}")
+        }
+    };
+    Some(Failure {
+        name: format!("TS{code}"),
+        location: Some(location),
+        message: truncate_with_ellipsis(message.trim(), MAX_MESSAGE_CHARS),
+    })
+}
+
+/// Parses an ESLint stylish problem line: `12:5  error  Message  rule-id`.
+fn eslint_error(file: &str, line: &str) -> Option<Failure> {
+    let mut words = line.split_whitespace();
+    let position = words.next()?;
+    let (line_number, column) = position.split_once(':')?;
+    if line_number.parse::<u32>().is_err() || column.parse::<u32>().is_err() {
+        return None;
+    }
+    if words.next()? != "error" {
+        return None;
+    }
+    let mut rest: Vec<&str> = words.collect();
+    let is_rule = |word: &str| {
+        word.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '/' | '@'))
+            && word.contains('-')
+    };
+    let name = match rest.last() {
+        Some(rule) if rest.len() > 1 && is_rule(rule) => {
+            let rule = format!("eslint::{rule}");
+            rest.pop();
+            rule
+        }
+        // Parse errors have no rule
+        _ => "error".to_string(),
+    };
+    Some(Failure {
+        name,
+        location: Some(format!("{file}:{line_number}")),
+        message: truncate_with_ellipsis(&rest.join(" "), MAX_MESSAGE_CHARS),
+    })
+}
+
+/// Whether an unindented line is a bare source file path (ESLint's file header).
+fn looks_like_path(line: &str) -> bool {
+    !line.contains(char::is_whitespace)
+        && (line.starts_with('/') || line.contains('/') || line.contains('\\'))
+        && Path::new(line).extension().is_some_and(|ext| {
+            ["js", "jsx", "ts", "tsx", "mjs", "cjs", "vue", "svelte"]
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Finds the first `file:line:column` outside `node_modules` in a stack
+/// trace or TAP diagnostic.
+fn source_location(message: &str) -> Option<String> {
+    message
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| matches!(c, '(' | ')' | '\'' | '"' | ',' | '❯'))
+                .trim_start_matches("file://")
+        })
+        .filter(|word| !word.contains("node_modules") && !word.starts_with("node:"))
+        .find_map(|word| {
+            let mut parts = word.rsplitn(3, ':');
+            let (column, line, file) = (parts.next()?, parts.next()?, parts.next()?);
+            column.parse::<u32>().ok()?;
+            line.parse::<u32>().ok()?;
+            file.contains('.').then(|| format!("{file}:{line}"))
+        })
+}
+
+fn tail(output: &str) -> String {
+    let lines: Vec<&str> = output.trim_end().lines().collect();
+    let start = lines.len().saturating_sub(TAIL_LINES);
+    truncate_with_ellipsis(&lines[start..].join("\n"), MAX_MESSAGE_CHARS * 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_cargo_json_compiler_errors() {
+        let output = r#"{"reason":"compiler-message","message":{"message":"mismatched types","code":{"code":"E0308"},"level":"error","spans":[{"file_name":"src/lib.rs","line_start":12,"is_primary":true}]}}
+{"reason":"compiler-message","message":{"message":"unused variable: `x`","code":null,"level":"warning","spans":[{"file_name":"src/lib.rs","line_start":3,"is_primary":true}]}}
+{"reason":"compiler-message","message":{"message":"aborting due to 1 previous error","code":null,"level":"error","spans":[]}}
+{"reason":"build-finished","success":false}"#;
+
+        let report = VerificationReport::from_output("cargo build", Some(101), output);
+        assert!(!report.passed);
+        assert_eq!(report.topic(), FAILED_TOPIC);
+        assert_eq!(
+            report.failures,
+            vec![Failure {
+                name: "E0308".to_string(),
+                location: Some("src/lib.rs:12".to_string()),
+                message: "mismatched types".to_string(),
+            }]
+        );
+        assert!(report.output_tail.is_empty());
+    }
+
+    #[test]
+    fn test_parses_libtest_human_output() {
+        let output = "\
+running 3 tests
+test tests::adds ... ok
+test tests::subtracts ... FAILED
+test tests::multiplies ... ok
+
+failures:
+
+---- tests::subtracts stdout ----
+
+thread 'tests::subtracts' panicked at src/math.rs:20:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+
+failures:
+    tests::subtracts
+
+test result: FAILED. 2 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out
+";
+        let report = VerificationReport::from_output("cargo test", Some(101), output);
+        assert_eq!(report.tests_passed, 2);
+        assert_eq!(report.tests_failed, 1);
+        assert_eq!(report.failures.len(), 1);
+        let failure = &report.failures[0];
+        assert_eq!(failure.name, "tests::subtracts");
+        assert_eq!(failure.location.as_deref(), Some("src/math.rs:20"));
+        assert!(failure.message.contains("left: 1"));
+        assert!(!failure.message.contains("failures:"));
+    }
+
+    #[test]
+    fn test_parses_libtest_json_and_builds_event() {
+        let output = r#"{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "event": "ok", "name": "a" }
+{ "type": "test", "event": "failed", "name": "b", "stdout": "thread 'b' panicked at src/b.rs:4:5:\nboom\n" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 0 }"#;
+        let report = VerificationReport::from_output("cargo test", Some(101), output);
+        assert_eq!((report.tests_passed, report.tests_failed), (1, 1));
+        assert_eq!(report.failures[0].name, "b");
+        assert_eq!(report.failures[0].location.as_deref(), Some("src/b.rs:4"));
+
+        let event = report.to_event();
+        assert_eq!(event.topic, "ci.failed");
+        let payload: VerificationReport =
+            serde_json::from_str(event.payload.as_deref().unwrap()).unwrap();
+        assert_eq!(payload, report);
+    }
+
+    #[test]
+    fn test_parses_jest_and_eslint_output() {
+        let output = "\
+> app@1.0.0 test
+> jest
+
+FAIL src/math.test.js
+  ● math › adds numbers
+
+    expect(received).toBe(expected) // Object.is equality
+
+    Expected: 4
+    Received: 3
+
+      3 | test('adds numbers', () => {
+    > 4 |   expect(add(1, 2)).toBe(4);
+        |                     ^
+
+      at Object.toBe (src/math.test.js:4:21)
+
+  ● math › parses input
+
+    TypeError: Cannot read properties of undefined
+
+      at parse (src/parse.js:10:3)
+      at Object.<anonymous> (src/math.test.js:9:5)
+
+PASS src/util.test.js
+
+Test Suites: 1 failed, 1 passed, 2 total
+Tests:       2 failed, 5 passed, 7 total
+
+/home/dev/app/src/index.js
+   3:7   error    'unused' is assigned a value but never used  no-unused-vars
+  12:1   warning  Unexpected console statement                  no-console
+  20:10  error    Parsing error: Unexpected token
+
+✖ 3 problems (2 errors, 1 warning)
+";
+        let report = VerificationReport::parse(OutputParser::Node, "npm test", Some(1), output);
+        assert_eq!((report.tests_passed, report.tests_failed), (5, 2));
+
+        let names: Vec<_> = report.failures.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "src/math.test.js::math › adds numbers",
+                "src/math.test.js::math › parses input",
+                "eslint::no-unused-vars",
+                "error",
+            ]
+        );
+        let adds = &report.failures[0];
+        assert_eq!(adds.location.as_deref(), Some("src/math.test.js:4"));
+        assert!(adds.message.contains("Received: 3"));
+        assert!(!adds.message.contains("parses input"));
+        assert_eq!(
+            report.failures[1].location.as_deref(),
+            Some("src/parse.js:10")
+        );
+        assert_eq!(
+            report.failures[2].location.as_deref(),
+            Some("/home/dev/app/src/index.js:3")
+        );
+        assert_eq!(
+            report.failures[2].message,
+            "'unused' is assigned a value but never used"
+        );
+        assert_eq!(report.failures[3].kind(), FailureKind::Compile);
+
+        let clusters = triage(&report);
+        let keys: Vec<_> = clusters.iter().map(|c| (c.kind, c.key.as_str())).collect();
+        assert_eq!(
+            keys,
+            [
+                (FailureKind::Compile, "/home/dev/app/src/index.js"),
+                (FailureKind::Lint, "eslint::no-unused-vars"),
+                (FailureKind::Test, "src/math.test.js"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parses_vitest_tap_and_tsc_output() {
+        let vitest = "\
+ ❯ src/math.test.ts (2 tests | 1 failed) 4ms
+   × math > adds numbers
+
+⎯⎯⎯⎯⎯⎯⎯ Failed Tests 1 ⎯⎯⎯⎯⎯⎯⎯
+
+ FAIL  src/math.test.ts > math > adds numbers
+AssertionError: expected 3 to be 4 // Object.is equality
+ ❯ src/math.test.ts:5:22
+
+⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯⎯[1/1]⎯
+
+ Test Files  1 failed (1)
+      Tests  1 failed | 1 passed (2)
+";
+        let report = VerificationReport::parse(OutputParser::Node, "npx vitest", Some(1), vitest);
+        assert_eq!((report.tests_passed, report.tests_failed), (1, 1));
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(
+            report.failures[0].name,
+            "src/math.test.ts::math > adds numbers"
+        );
+        assert_eq!(
+            report.failures[0].location.as_deref(),
+            Some("src/math.test.ts:5")
+        );
+        assert!(report.failures[0].message.starts_with("AssertionError"));
+
+        let tap = "\
+TAP version 13
+# Subtest: adds numbers
+not ok 1 - adds numbers
+  ---
+  duration_ms: 0.8
+  location: '/app/test/math.test.mjs:4:1'
+  error: 'Expected values to be strictly equal'
+  ...
+ok 2 - parses input
+1..2
+# pass 1
+# fail 1
+src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.
+";
+        let report = VerificationReport::parse(OutputParser::Node, "npm test", Some(1), tap);
+        assert_eq!((report.tests_passed, report.tests_failed), (1, 1));
+        assert_eq!(report.failures[0].name, "adds numbers");
+        assert_eq!(
+            report.failures[0].location.as_deref(),
+            Some("/app/test/math.test.mjs:4")
+        );
+        assert_eq!(report.failures[1].name, "TS2322");
+        assert_eq!(report.failures[1].kind(), FailureKind::Compile);
+        assert_eq!(
+            report.failures[1].location.as_deref(),
+            Some("src/index.ts:3")
+        );
+    }
+
+    #[test]
+    fn test_triage_clusters_by_kind_and_key() {
+        let failure = |name: &str, location: Option<&str>, message: &str| Failure {
+            name: name.to_string(),
+            location: location.map(str::to_string),
+            message: message.to_string(),
+        };
+        let report = VerificationReport {
+            command: "cargo clippy && cargo test".to_string(),
+            passed: false,
+            exit_code: Some(101),
+            timed_out: false,
+            tests_passed: 0,
+            tests_failed: 3,
+            failures: vec![
+                failure(
+                    "parser::tests::a",
+                    Some("src/parser.rs:10"),
+                    "thread 'a' panicked at src/parser.rs:10:5:\nboom",
+                ),
+                failure("E0308", Some("src/lib.rs:12"), "mismatched types"),
+                failure("parser::tests::b", None, ""),
+                failure(
+                    "clippy::needless_return",
+                    Some("src/main.rs:3"),
+                    "unneeded `return`",
+                ),
+                failure("error", Some("src/lib.rs:40"), "cannot find value `x`"),
+                failure("lexer::tests::c", None, ""),
+            ],
+            output_tail: String::new(),
+        };
+
+        let clusters = triage(&report);
+        let keys: Vec<_> = clusters.iter().map(|c| (c.kind, c.key.as_str())).collect();
+        assert_eq!(
+            keys,
+            [
+                (FailureKind::Compile, "src/lib.rs"),
+                (FailureKind::Lint, "clippy::needless_return"),
+                (FailureKind::Test, "parser::tests"),
+                (FailureKind::Test, "lexer::tests"),
+            ]
+        );
+        assert_eq!(clusters[0].failures.len(), 2);
+
+        let event = clusters[2].to_event(&report.command);
+        assert_eq!(event.topic, FIX_TASK_TOPIC);
+        let payload = event.payload.unwrap();
+        assert!(payload.starts_with("Fix 2 failing tests in parser::tests\n"));
+        assert!(payload.contains("kind: test\n"));
+        assert!(payload.contains("files: src/parser.rs\n"));
+        assert!(payload.contains("- parser::tests::a at src/parser.rs:10: boom\n"));
+        assert!(payload.ends_with(
+            "acceptance: `cargo clippy && cargo test` no longer reports these failures"
+        ));
+
+        let passing = VerificationReport {
+            passed: true,
+            ..report
+        };
+        assert!(triage(&passing).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_verification_reports_exit_status() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let passed = run_verification(
+            "echo ok",
+            OutputParser::Cargo,
+            dir.path(),
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+        assert!(passed.passed);
+        assert_eq!(passed.topic(), PASSED_TOPIC);
+        assert!(passed.output_tail.is_empty());
+
+        let failed = run_verification(
+            "echo 'linker exploded' >&2; exit 3",
+            OutputParser::Cargo,
+            dir.path(),
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+        assert!(!failed.passed);
+        assert_eq!(failed.exit_code, Some(3));
+        assert!(failed.output_tail.contains("linker exploded"));
+
+        let slow = run_verification(
+            "sleep 5",
+            OutputParser::Cargo,
+            dir.path(),
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap();
+        assert!(slow.timed_out);
+        assert!(!slow.passed);
+    }
+}
@@ -28,6 +28,7 @@
 //! }
 //! ```
 
+use crate::git_ops::GitOpsError;
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
@@ -123,6 +124,15 @@ pub enum WorktreeError {
     BranchExists(String),
 }
 
+impl From<GitOpsError> for WorktreeError {
+    fn from(err: GitOpsError) -> Self {
+        match err {
+            GitOpsError::Io(err) => Self::Io(err),
+            GitOpsError::Git(message) | GitOpsError::ConfigMissing(message) => Self::Git(message),
+        }
+    }
+}
+
 /// Create a new worktree for a parallel Ralph loop.
 ///
 /// Creates a new branch and worktree at `{config.worktree_dir}/{loop_id}`.
@@ -624,10 +624,10 @@ Still working..."#;
         let line = make_write_line("Quick output", 0);
         let fixture_path = create_fixture(temp_dir.path(), "quick.jsonl", &line);
 
-        let config = SmokeTestConfig::new(&fixture_path).with_timeout(Duration::from_secs(60));
+        let config = SmokeTestConfig::new(&fixture_path).with_timeout(Duration::from_mins(1));
 
         // Verify config was set
-        assert_eq!(config.timeout, Duration::from_secs(60));
+        assert_eq!(config.timeout, Duration::from_mins(1));
 
         let result = SmokeRunner::run(&config).expect("Should complete within 60s");
         assert!(result.completed_successfully());
@@ -911,6 +911,8 @@ mod skills_smoke_tests {
             enabled: true,
             dirs: vec![skills_fixtures_dir()],
             overrides: HashMap::new(),
+            index: None,
+            packs: vec![],
         };
 
         let registry = SkillRegistry::from_config(&config, std::path::Path::new("."), None)
@@ -953,6 +955,8 @@ mod skills_smoke_tests {
             enabled: true,
             dirs: vec![skills_fixtures_dir()],
             overrides: HashMap::new(),
+            index: None,
+            packs: vec![],
         };
 
         let registry =
@@ -999,6 +1003,8 @@ mod skills_smoke_tests {
             enabled: true,
             dirs: vec![skills_fixtures_dir()],
             overrides: HashMap::new(),
+            index: None,
+            packs: vec![],
         };
 
         let registry =
@@ -1039,6 +1045,8 @@ mod skills_smoke_tests {
             enabled: true,
             dirs: vec![skills_fixtures_dir()],
             overrides: HashMap::new(),
+            index: None,
+            packs: vec![],
         };
 
         let registry =
@@ -1222,6 +1230,8 @@ skills:
             enabled: true,
             dirs: vec![skills_fixtures_dir()],
             overrides,
+            index: None,
+            packs: vec![],
         };
 
         let registry =
@@ -1283,6 +1293,8 @@ skills:
             enabled: true,
             dirs: vec![skills_fixtures_dir()],
             overrides: HashMap::new(),
+            index: None,
+            packs: vec![],
         };
 
         let registry =
@@ -4,7 +4,7 @@ use crate::{HatId, Topic};
 use serde::{Deserialize, Serialize};
 
 /// An event in the pub/sub system.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Event {
     /// The routing topic for this event.
     pub topic: Topic,
@@ -2,10 +2,14 @@
 //!
 //! The event bus routes events to subscribed hats based on topic patterns.
 //! Multiple observers can be added to receive all published events for
-//! recording, TUI updates, and benchmarking purposes.
+//! recording, TUI updates, and benchmarking purposes. [`EventBus::snapshot`]
+//! captures the routing state for debugging. Topics registered with
+//! [`EventBus::debounce`] are held and coalesced before delivery.
 
-use crate::{Event, Hat, HatId};
-use std::collections::BTreeMap;
+use crate::{Event, Hat, HatId, Topic};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{Duration, Instant};
 
 /// Type alias for the observer callback function.
 type Observer = Box<dyn Fn(&Event) + Send + 'static>;
@@ -25,6 +29,65 @@ pub struct EventBus {
     /// Observers that receive all published events.
     /// Multiple observers can be registered (e.g., session recorder + TUI).
     observers: Vec<Observer>,
+
+    /// Old topic names mapped to the topics that replaced them.
+    topic_renames: BTreeMap<String, Topic>,
+
+    /// Old hat IDs mapped to the hats that replaced them.
+    hat_aliases: BTreeMap<HatId, HatId>,
+
+    /// Events taken by each hat that haven't finished being handled.
+    in_flight: BTreeMap<HatId, Vec<Event>>,
+
+    /// Hats whose queues are held until resumed.
+    paused: BTreeSet<HatId>,
+
+    /// Topic patterns whose events are coalesced, with their windows.
+    debounce: Vec<(Topic, Duration)>,
+
+    /// Debounced events waiting for their window to close, keyed by topic
+    /// and target.
+    held: BTreeMap<(String, Option<HatId>), Held>,
+}
+
+/// A burst of same-topic events being coalesced into one delivery.
+struct Held {
+    event: Event,
+    payloads: Vec<String>,
+    due: Instant,
+}
+
+/// Serializable view of an [`EventBus`]'s routing state.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BusSnapshot {
+    /// Registered hats, sorted by ID.
+    pub hats: Vec<HatSnapshot>,
+    /// Human interaction events (`human.*`) waiting to be taken.
+    #[serde(default)]
+    pub human_pending: usize,
+    /// Events each hat has taken and is still handling.
+    #[serde(default)]
+    pub in_flight: BTreeMap<HatId, Vec<Event>>,
+    /// Old topic names mapped to the topics that replaced them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub topic_renames: BTreeMap<String, Topic>,
+    /// Old hat IDs mapped to the hats that replaced them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hat_aliases: BTreeMap<HatId, HatId>,
+}
+
+/// One registered hat in a [`BusSnapshot`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HatSnapshot {
+    pub id: HatId,
+    pub name: String,
+    /// Topic patterns the hat subscribes to.
+    pub subscriptions: Vec<Topic>,
+    /// Number of events queued for the hat.
+    pub pending: usize,
+    /// Whether the hat's queue is held (see [`EventBus::pause`]).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paused: bool,
 }
 
 impl EventBus {
@@ -71,17 +134,136 @@ impl EventBus {
         self.pending.entry(id).or_default();
     }
 
+    /// Routes events published on `old` as `new`.
+    ///
+    /// Lets events files and recordings written before a topic was renamed
+    /// still reach the hats that now subscribe to the new name.
+    pub fn rename_topic(&mut self, old: impl Into<String>, new: Topic) {
+        self.topic_renames.insert(old.into(), new);
+    }
+
+    /// Routes events that name `alias` as their target or source to `hat`.
+    pub fn alias_hat(&mut self, alias: impl Into<HatId>, hat: HatId) {
+        self.hat_aliases.insert(alias.into(), hat);
+    }
+
+    /// Coalesces events whose topic matches `pattern` for `window`.
+    ///
+    /// The first matching event starts the window; events with the same
+    /// topic and target published before it closes are merged into it, their
+    /// distinct payloads joined with newlines. The merged event is delivered
+    /// by [`release_debounced`](Self::release_debounced) once the window has
+    /// closed. Observers still see every event as it is published.
+    pub fn debounce(&mut self, pattern: Topic, window: Duration) {
+        self.debounce.push((pattern, window));
+    }
+
+    /// Delivers held events whose window closed by `now`, returning how many
+    /// were delivered.
+    pub fn release_debounced(&mut self, now: Instant) -> usize {
+        let due: Vec<_> = self
+            .held
+            .iter()
+            .filter(|(_, held)| held.due <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &due {
+            if let Some(Held {
+                mut event,
+                payloads,
+                ..
+            }) = self.held.remove(key)
+            {
+                event.payload = payloads
+                    .into_iter()
+                    .filter(|payload| !payload.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.route(event);
+            }
+        }
+        due.len()
+    }
+
+    /// When the earliest held event is due, if any are held.
+    pub fn next_debounce_due(&self) -> Option<Instant> {
+        self.held.values().map(|held| held.due).min()
+    }
+
+    /// Current name of `topic`, if it was renamed.
+    pub fn renamed_topic(&self, topic: &str) -> Option<&Topic> {
+        self.topic_renames.get(topic)
+    }
+
+    /// Rewrites renamed topics and aliased hat IDs to their current names.
+    fn resolve_renames(&self, mut event: Event) -> Event {
+        if let Some(topic) = self.topic_renames.get(event.topic.as_str()) {
+            event.topic = topic.clone();
+        }
+        for hat in [&mut event.target, &mut event.source].into_iter().flatten() {
+            if let Some(current) = self.hat_aliases.get(hat) {
+                *hat = current.clone();
+            }
+        }
+        event
+    }
+
     /// Publishes an event to all subscribed hats.
     ///
     /// Returns the list of hat IDs that received the event.
     /// If an observer is set, it receives the event before routing.
-    #[allow(clippy::needless_pass_by_value)] // Event is cloned to multiple recipients
+    /// Renamed topics and aliased hats are resolved first, so observers
+    /// and hats only ever see current names. Debounced events are held and
+    /// reach no hat yet.
     pub fn publish(&mut self, event: Event) -> Vec<HatId> {
+        let event = self.resolve_renames(event);
+
         // Notify all observers before routing
         for observer in &self.observers {
             observer(&event);
         }
 
+        if !event.topic.as_str().starts_with("human.")
+            && let Some(window) = self.debounce_window(&event.topic)
+        {
+            self.hold(event, window);
+            return Vec::new();
+        }
+
+        self.route(event)
+    }
+
+    /// Debounce window for `topic`, from the first matching pattern.
+    fn debounce_window(&self, topic: &Topic) -> Option<Duration> {
+        self.debounce
+            .iter()
+            .find(|(pattern, _)| pattern.matches(topic))
+            .map(|(_, window)| *window)
+    }
+
+    /// Merges `event` into the burst held for its topic and target.
+    fn hold(&mut self, event: Event, window: Duration) {
+        let key = (event.topic.as_str().to_string(), event.target.clone());
+        match self.held.get_mut(&key) {
+            Some(held) => {
+                if !held.payloads.contains(&event.payload) {
+                    held.payloads.push(event.payload);
+                }
+            }
+            None => {
+                let held = Held {
+                    payloads: vec![event.payload.clone()],
+                    event,
+                    due: Instant::now() + window,
+                };
+                self.held.insert(key, held);
+            }
+        }
+    }
+
+    /// Queues `event` for its target, or for the hats subscribed to it.
+    #[allow(clippy::needless_pass_by_value)] // Event is cloned to multiple recipients
+    fn route(&mut self, event: Event) -> Vec<HatId> {
         if event.topic.as_str().starts_with("human.") {
             self.human_pending.push(event);
             return Vec::new();
@@ -137,9 +319,50 @@ impl EventBus {
         recipients
     }
 
+    /// Publishes an orchestrator lifecycle event.
+    ///
+    /// Observers see every lifecycle event, but only hats that subscribe to
+    /// the topic by name or pattern receive it; global wildcard (`*`) hats
+    /// don't, so bookkeeping events never wake the fallback hat.
+    ///
+    /// Returns the list of hat IDs that received the event.
+    #[allow(clippy::needless_pass_by_value)] // Event is cloned to multiple recipients
+    pub fn publish_lifecycle(&mut self, event: Event) -> Vec<HatId> {
+        for observer in &self.observers {
+            observer(&event);
+        }
+
+        let mut recipients = Vec::new();
+        for (id, hat) in &self.hats {
+            if hat.has_specific_subscription(&event.topic) {
+                self.pending
+                    .entry(id.clone())
+                    .or_default()
+                    .push(event.clone());
+                recipients.push(id.clone());
+            }
+        }
+        recipients
+    }
+
     /// Takes all pending events for a hat.
+    ///
+    /// The events stay listed as in flight in [`snapshot`](Self::snapshot)
+    /// until [`finish_in_flight`](Self::finish_in_flight).
     pub fn take_pending(&mut self, hat_id: &HatId) -> Vec<Event> {
-        self.pending.remove(hat_id).unwrap_or_default()
+        let events = self.pending.remove(hat_id).unwrap_or_default();
+        if !events.is_empty() {
+            self.in_flight
+                .entry(hat_id.clone())
+                .or_default()
+                .extend(events.iter().cloned());
+        }
+        events
+    }
+
+    /// Marks every taken event as handled.
+    pub fn finish_in_flight(&mut self) {
+        self.in_flight.clear();
     }
 
     /// Takes all pending human interaction events.
@@ -157,9 +380,14 @@ impl EventBus {
         &self.human_pending
     }
 
-    /// Checks if there are any pending events for any hat.
+    /// Returns how many events are queued for a hat.
+    pub fn pending_count(&self, hat_id: &HatId) -> usize {
+        self.pending.get(hat_id).map_or(0, Vec::len)
+    }
+
+    /// Checks if there are any pending events for any hat that isn't paused.
     pub fn has_pending(&self) -> bool {
-        !self.human_pending.is_empty() || self.pending.values().any(|events| !events.is_empty())
+        !self.human_pending.is_empty() || self.next_hat_with_pending().is_some()
     }
 
     /// Checks if there are any pending human interaction events.
@@ -167,15 +395,31 @@ impl EventBus {
         !self.human_pending.is_empty()
     }
 
-    /// Returns the next hat with pending events.
+    /// Returns the next hat with pending events, skipping paused hats.
     /// BTreeMap iteration is already sorted by key.
     pub fn next_hat_with_pending(&self) -> Option<&HatId> {
         self.pending
             .iter()
-            .find(|(_, events)| !events.is_empty())
+            .find(|(id, events)| !events.is_empty() && !self.paused.contains(*id))
             .map(|(id, _)| id)
     }
 
+    /// Holds a hat's queue: events are still routed to it, but it no longer
+    /// counts as having pending work until [`resume`](Self::resume).
+    pub fn pause(&mut self, hat_id: &HatId) {
+        self.paused.insert(hat_id.clone());
+    }
+
+    /// Releases a paused hat's queue. Returns false if it wasn't paused.
+    pub fn resume(&mut self, hat_id: &HatId) -> bool {
+        self.paused.remove(hat_id)
+    }
+
+    /// Checks if a hat's queue is held.
+    pub fn is_paused(&self, hat_id: &HatId) -> bool {
+        self.paused.contains(hat_id)
+    }
+
     /// Gets a hat by ID.
     pub fn get_hat(&self, id: &HatId) -> Option<&Hat> {
         self.hats.get(id)
@@ -185,6 +429,27 @@ impl EventBus {
     pub fn hat_ids(&self) -> impl Iterator<Item = &HatId> {
         self.hats.keys()
     }
+
+    /// Captures registered hats, queue depths, and in-flight events.
+    pub fn snapshot(&self) -> BusSnapshot {
+        BusSnapshot {
+            hats: self
+                .hats
+                .values()
+                .map(|hat| HatSnapshot {
+                    id: hat.id.clone(),
+                    name: hat.name.clone(),
+                    subscriptions: hat.subscriptions.clone(),
+                    pending: self.pending_count(&hat.id),
+                    paused: self.is_paused(&hat.id),
+                })
+                .collect(),
+            human_pending: self.human_pending.len(),
+            in_flight: self.in_flight.clone(),
+            topic_renames: self.topic_renames.clone(),
+            hat_aliases: self.hat_aliases.clone(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -235,6 +500,75 @@ mod tests {
         assert_eq!(recipients[0].as_str(), "reviewer");
     }
 
+    #[test]
+    fn test_renamed_topics_and_aliased_hats() {
+        let mut bus = EventBus::new();
+        bus.register(Hat::new("builder", "Builder").subscribe("build.task"));
+        bus.register(Hat::new("reviewer", "Reviewer").subscribe("review.request"));
+        bus.rename_topic("impl.task", Topic::new("build.task"));
+        bus.alias_hat("implementer", HatId::new("builder"));
+
+        // An old topic reaches the hat subscribed to its new name
+        let recipients = bus.publish(Event::new("impl.task", "Add login"));
+        assert_eq!(recipients, vec![HatId::new("builder")]);
+        let pending = bus.take_pending(&HatId::new("builder"));
+        assert_eq!(pending[0].topic.as_str(), "build.task");
+
+        // A handoff to the old hat ID reaches the hat that replaced it
+        let event = Event::new("handoff", "Please fix")
+            .with_source("reviewer")
+            .with_target("implementer");
+        assert_eq!(bus.publish(event), vec![HatId::new("builder")]);
+
+        let event = Event::new("review.request", "Done").with_source("implementer");
+        bus.publish(event);
+        let pending = bus.take_pending(&HatId::new("reviewer"));
+        assert_eq!(pending[0].source, Some(HatId::new("builder")));
+    }
+
+    #[test]
+    fn test_debounce_coalesces_burst_into_one_delivery() {
+        let mut bus = EventBus::new();
+        bus.register(Hat::new("watcher", "Watcher").subscribe("fs.*"));
+        bus.debounce(Topic::new("fs.changed"), Duration::from_secs(5));
+        let watcher = HatId::new("watcher");
+
+        for path in ["src/a.rs", "src/b.rs", "src/a.rs"] {
+            assert!(bus.publish(Event::new("fs.changed", path)).is_empty());
+        }
+        bus.publish(Event::new("fs.deleted", "src/c.rs"));
+        assert_eq!(bus.pending_count(&watcher), 1, "other topics aren't held");
+
+        let due = bus.next_debounce_due().expect("burst is held");
+        assert_eq!(
+            bus.release_debounced(due.checked_sub(Duration::from_millis(1)).unwrap()),
+            0
+        );
+        assert_eq!(bus.release_debounced(due), 1);
+        assert!(bus.next_debounce_due().is_none());
+
+        let events = bus.take_pending(&watcher);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].topic.as_str(), "fs.changed");
+        assert_eq!(events[1].payload, "src/a.rs\nsrc/b.rs");
+    }
+
+    #[test]
+    fn test_debounce_keeps_targets_apart() {
+        let mut bus = EventBus::new();
+        bus.register(Hat::new("a", "A"));
+        bus.register(Hat::new("b", "B"));
+        bus.debounce(Topic::new("status.*"), Duration::ZERO);
+
+        bus.publish(Event::new("status.ping", "1").with_target(HatId::new("a")));
+        bus.publish(Event::new("status.ping", "2").with_target(HatId::new("b")));
+        bus.publish(Event::new("status.ping", "3").with_target(HatId::new("a")));
+
+        assert_eq!(bus.release_debounced(Instant::now()), 2);
+        assert_eq!(bus.take_pending(&HatId::new("a"))[0].payload, "1\n3");
+        assert_eq!(bus.take_pending(&HatId::new("b"))[0].payload, "2");
+    }
+
     #[test]
     fn test_take_pending() {
         let mut bus = EventBus::new();
@@ -398,4 +732,102 @@ mod tests {
         let peeked_after_take = bus.peek_pending(&hat_id);
         assert!(peeked_after_take.is_none() || peeked_after_take.unwrap().is_empty());
     }
+
+    #[test]
+    fn test_pending_count() {
+        let mut bus = EventBus::new();
+        bus.register(Hat::new("impl", "Implementer").subscribe("task.*"));
+        let hat_id = HatId::new("impl");
+
+        assert_eq!(bus.pending_count(&hat_id), 0);
+        bus.publish(Event::new("task.start", "1"));
+        bus.publish(Event::new("task.start", "2"));
+        assert_eq!(bus.pending_count(&hat_id), 2);
+
+        bus.take_pending(&hat_id);
+        assert_eq!(bus.pending_count(&hat_id), 0);
+    }
+
+    #[test]
+    fn test_paused_hats_keep_their_queue() {
+        let mut bus = EventBus::new();
+        bus.register(Hat::new("builder", "Builder").subscribe("build.*"));
+        bus.register(Hat::new("reviewer", "Reviewer").subscribe("review.*"));
+        let builder = HatId::new("builder");
+
+        bus.pause(&builder);
+        bus.publish(Event::new("build.task", "Add login"));
+        assert_eq!(bus.pending_count(&builder), 1);
+        assert!(!bus.has_pending());
+        assert!(bus.snapshot().hats[0].paused);
+
+        bus.publish(Event::new("review.request", "PR 1"));
+        assert_eq!(
+            bus.next_hat_with_pending().map(HatId::as_str),
+            Some("reviewer")
+        );
+
+        assert!(bus.resume(&builder));
+        assert!(!bus.resume(&builder));
+        assert_eq!(bus.next_hat_with_pending(), Some(&builder));
+    }
+
+    #[test]
+    fn test_publish_lifecycle_skips_global_wildcards() {
+        use std::sync::{Arc, Mutex};
+
+        let mut bus = EventBus::new();
+        bus.register(Hat::new("ralph", "Ralph").subscribe("*"));
+        bus.register(Hat::new("auditor", "Auditor").subscribe("ralph.hat_completed"));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        bus.add_observer(move |event| seen_clone.lock().unwrap().push(event.topic.clone()));
+
+        let recipients = bus.publish_lifecycle(Event::new("ralph.hat_completed", "{}"));
+        assert_eq!(recipients, vec![HatId::new("auditor")]);
+
+        assert!(
+            bus.publish_lifecycle(Event::new("ralph.iteration_started", "{}"))
+                .is_empty()
+        );
+        assert_eq!(bus.pending_count(&HatId::new("ralph")), 0);
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_snapshot_tracks_queues_and_in_flight_events() {
+        let mut bus = EventBus::new();
+        bus.register(Hat::new("builder", "Builder").subscribe("build.*"));
+        bus.register(Hat::new("reviewer", "Reviewer").subscribe("review.*"));
+        bus.publish(Event::new("build.task", "Add login"));
+        bus.publish(Event::new("review.request", "PR 1"));
+        bus.publish(Event::new("human.response", "yes"));
+
+        let builder = HatId::new("builder");
+        let snapshot = bus.snapshot();
+        let pending: Vec<(&str, usize)> = snapshot
+            .hats
+            .iter()
+            .map(|hat| (hat.id.as_str(), hat.pending))
+            .collect();
+        assert_eq!(pending, vec![("builder", 1), ("reviewer", 1)]);
+        assert_eq!(snapshot.human_pending, 1);
+        assert!(snapshot.in_flight.is_empty());
+
+        let taken = bus.take_pending(&builder);
+        let snapshot = bus.snapshot();
+        assert_eq!(snapshot.hats[0].pending, 0);
+        assert_eq!(snapshot.in_flight.get(&builder), Some(&taken));
+
+        // Round-trips through JSON for `ralph bus dump`
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BusSnapshot>(&json).unwrap(),
+            snapshot
+        );
+
+        bus.finish_in_flight();
+        assert!(bus.snapshot().in_flight.is_empty());
+    }
 }
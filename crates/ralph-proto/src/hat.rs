@@ -210,7 +210,7 @@ mod tests {
         let hat = Hat::default_planner();
         assert_eq!(hat.id.as_str(), "planner");
         assert!(hat.is_subscribed(&Topic::new("task.start")));
-        assert!(hat.is_subscribed(&Topic::new("task.resume"))); // For ralph resume
+        assert!(hat.is_subscribed(&Topic::new("task.resume"))); // For ralph run --continue
         assert!(hat.is_subscribed(&Topic::new("build.done")));
         assert!(hat.is_subscribed(&Topic::new("build.blocked")));
         assert!(!hat.is_subscribed(&Topic::new("build.task")));
@@ -21,10 +21,10 @@ mod ux_event;
 pub use daemon::{DaemonAdapter, StartLoopFn};
 pub use error::{Error, Result};
 pub use event::Event;
-pub use event_bus::EventBus;
+pub use event_bus::{BusSnapshot, EventBus, HatSnapshot};
 pub use hat::{Hat, HatId};
 pub use robot::{CheckinContext, RobotService};
-pub use topic::Topic;
+pub use topic::{MAX_TOPIC_DEPTH, Topic, TopicError};
 pub use ux_event::{
     FrameCapture, TerminalColorMode, TerminalResize, TerminalWrite, TuiFrame, UxEvent,
 };
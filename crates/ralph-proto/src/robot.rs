@@ -31,7 +31,7 @@ pub struct CheckinContext {
 ///
 /// Implementors handle platform-specific concerns: sending messages,
 /// waiting for responses, and periodic check-ins. The event loop holds
-/// an `Option<Box<dyn RobotService>>` and calls these methods when
+/// the service behind an `Arc` and calls these methods when
 /// `human.interact` events are detected.
 pub trait RobotService: Send + Sync {
     /// Send a question to the human and store it as pending.
@@ -69,5 +69,5 @@ pub trait RobotService: Send + Sync {
     /// Stop the service gracefully.
     ///
     /// Called during loop termination to cleanly shut down the backend.
-    fn stop(self: Box<Self>);
+    fn stop(&self);
 }
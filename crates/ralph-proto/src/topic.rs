@@ -2,8 +2,40 @@
 //!
 //! Topics are routing keys used to match events to subscribers.
 //! Supports glob-style patterns like `impl.*` to match `impl.done`.
+//!
+//! Topics from outside the orchestrator (config files, agent output, the
+//! events file) go through [`Topic::parse`] or [`Topic::parse_pattern`]:
+//! - Surrounding whitespace is trimmed.
+//! - Dotted topics are lowercased, so `Build.Task` and `build.task` are the
+//!   same topic. Single-segment topics keep their case: uppercase ones are
+//!   sentinels like `LOOP_COMPLETE`.
+//! - Segments are non-empty and use letters, digits, `_` and `-`.
+//! - A topic has at most [`MAX_TOPIC_DEPTH`] segments.
 
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Maximum number of `.`-separated segments in a topic.
+pub const MAX_TOPIC_DEPTH: usize = 8;
+
+/// Why a topic string was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TopicError {
+    #[error("topic is empty")]
+    Empty,
+
+    #[error("topic '{0}' has an empty segment")]
+    EmptySegment(String),
+
+    #[error("segment '{segment}' of '{topic}' may only contain letters, digits, '_' and '-'")]
+    InvalidSegment { topic: String, segment: String },
+
+    #[error("topic '{0}' has more than {MAX_TOPIC_DEPTH} segments")]
+    TooDeep(String),
+
+    #[error("topic '{0}' contains a wildcard; only subscriptions may use '*'")]
+    Wildcard(String),
+}
 
 /// A topic for event routing.
 ///
@@ -17,6 +49,54 @@ impl Topic {
         Self(topic.into())
     }
 
+    /// Parses a concrete topic, such as one an event is published on.
+    pub fn parse(raw: &str) -> Result<Self, TopicError> {
+        let topic = Self::parse_pattern(raw)?;
+        if topic.0.split('.').any(|segment| segment == "*") {
+            return Err(TopicError::Wildcard(topic.0));
+        }
+        Ok(topic)
+    }
+
+    /// Parses a subscription, where any segment may be the `*` wildcard.
+    pub fn parse_pattern(raw: &str) -> Result<Self, TopicError> {
+        let topic = Self::normalize(raw);
+        if topic.is_empty() {
+            return Err(TopicError::Empty);
+        }
+        if topic.split('.').count() > MAX_TOPIC_DEPTH {
+            return Err(TopicError::TooDeep(topic));
+        }
+        for segment in topic.split('.') {
+            if segment.is_empty() {
+                return Err(TopicError::EmptySegment(topic));
+            }
+            let valid = segment == "*"
+                || segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !valid {
+                return Err(TopicError::InvalidSegment {
+                    segment: segment.to_string(),
+                    topic,
+                });
+            }
+        }
+        Ok(Self(topic))
+    }
+
+    /// Returns the canonical spelling of `raw`, without validating it.
+    ///
+    /// Trims whitespace and lowercases dotted topics; see the module docs.
+    pub fn normalize(raw: &str) -> String {
+        let trimmed = raw.trim();
+        if trimmed.contains('.') {
+            trimmed.to_ascii_lowercase()
+        } else {
+            trimmed.to_string()
+        }
+    }
+
     /// Returns the topic as a string slice.
     pub fn as_str(&self) -> &str {
         &self.0
@@ -144,4 +224,49 @@ mod tests {
         let pattern = Topic::new("impl.*");
         assert!(!pattern.matches(&Topic::new("impl.sub.done")));
     }
+
+    #[test]
+    fn test_parse_normalizes_dotted_topics() {
+        assert_eq!(Topic::parse(" Build.Task ").unwrap().as_str(), "build.task");
+        assert_eq!(
+            Topic::parse("LOOP_COMPLETE").unwrap().as_str(),
+            "LOOP_COMPLETE"
+        );
+        assert_eq!(
+            Topic::parse_pattern("Review.*").unwrap().as_str(),
+            "review.*"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_topics() {
+        assert_eq!(Topic::parse("  "), Err(TopicError::Empty));
+        assert_eq!(
+            Topic::parse("build..done"),
+            Err(TopicError::EmptySegment("build..done".to_string()))
+        );
+        assert_eq!(
+            Topic::parse("build.task done"),
+            Err(TopicError::InvalidSegment {
+                topic: "build.task done".to_string(),
+                segment: "task done".to_string(),
+            })
+        );
+        assert!(matches!(
+            Topic::parse("a.b.c.d.e.f.g.h.i"),
+            Err(TopicError::TooDeep(_))
+        ));
+        assert!(Topic::parse("a.b.c.d.e.f.g.h").is_ok());
+    }
+
+    #[test]
+    fn test_wildcards_are_only_valid_in_patterns() {
+        assert_eq!(
+            Topic::parse("impl.*"),
+            Err(TopicError::Wildcard("impl.*".to_string()))
+        );
+        assert!(Topic::parse_pattern("impl.*").is_ok());
+        assert!(Topic::parse_pattern("*").is_ok());
+        assert!(Topic::parse_pattern("impl.do*").is_err());
+    }
 }
@@ -1,6 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use chrono::Utc;
+use ralph_core::EventWriter;
 
 use crate::error::TelegramResult;
 use crate::state::{StateManager, TelegramState};
@@ -141,42 +142,17 @@ impl MessageHandler {
         ralph_dir.join("events.jsonl")
     }
 
-    /// Append an event line to the given file atomically.
+    /// Append an event line under the events file's writer lock.
     fn append_event(&self, path: &Path, event_line: &str) -> TelegramResult<()> {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| {
-                crate::error::TelegramError::EventWrite(format!(
-                    "failed to create directory {}: {}",
-                    parent.display(),
-                    e
-                ))
-            })?;
-        }
-
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(path)
+        EventWriter::new(path)
+            .append_lines([event_line])
             .map_err(|e| {
                 crate::error::TelegramError::EventWrite(format!(
-                    "failed to open {}: {}",
+                    "failed to write to {}: {}",
                     path.display(),
                     e
                 ))
-            })?;
-
-        writeln!(file, "{}", event_line).map_err(|e| {
-            crate::error::TelegramError::EventWrite(format!(
-                "failed to write to {}: {}",
-                path.display(),
-                e
-            ))
-        })?;
-
-        Ok(())
+            })
     }
 }
 
@@ -377,7 +377,7 @@ impl TelegramService {
     /// Stop the Telegram service gracefully.
     ///
     /// Signals the background polling task to shut down.
-    pub fn stop(self) {
+    pub fn stop(&self) {
         // Send farewell if we know the chat ID
         if let Ok(state) = self.state_manager.load_or_default()
             && let Some(chat_id) = state.chat_id
@@ -779,8 +779,8 @@ impl ralph_proto::RobotService for TelegramService {
         self.shutdown.clone()
     }
 
-    fn stop(self: Box<Self>) {
-        TelegramService::stop(*self);
+    fn stop(&self) {
+        TelegramService::stop(self);
     }
 }
 
@@ -994,16 +994,11 @@ mod tests {
         let pos_after_first = pos;
 
         // Append a human.response
-        let mut file = std::fs::OpenOptions::new()
-            .append(true)
-            .open(&events_path)
+        ralph_core::EventWriter::new(&events_path)
+            .append_lines([
+                r#"{"topic":"human.response","payload":"yes","ts":"2026-01-30T00:02:00Z"}"#,
+            ])
             .unwrap();
-        writeln!(
-            file,
-            r#"{{"topic":"human.response","payload":"yes","ts":"2026-01-30T00:02:00Z"}}"#
-        )
-        .unwrap();
-        file.flush().unwrap();
 
         // Should find the response starting from where we left off
         let result = TelegramService::check_for_response(&events_path, &mut pos).unwrap();
@@ -1033,16 +1028,11 @@ mod tests {
         let writer_path = events_path.clone();
         let writer = std::thread::spawn(move || {
             std::thread::sleep(Duration::from_millis(200));
-            let mut file = std::fs::OpenOptions::new()
-                .append(true)
-                .open(&writer_path)
+            ralph_core::EventWriter::new(&writer_path)
+                .append_lines([
+                    r#"{"topic":"human.response","payload":"Go with plan A","ts":"2026-01-30T00:00:00Z"}"#,
+                ])
                 .unwrap();
-            writeln!(
-                file,
-                r#"{{"topic":"human.response","payload":"Go with plan A","ts":"2026-01-30T00:00:00Z"}}"#
-            )
-            .unwrap();
-            file.flush().unwrap();
         });
 
         let result = service.wait_for_response(&events_path).unwrap();
@@ -79,11 +79,20 @@ pub fn dispatch_action(action: Action, state: &mut TuiState, viewport_height: us
             state.prev_match();
         }
         Action::GuidanceNext => {
-            state.start_guidance(crate::state::GuidanceMode::Next);
+            // While an agent is waiting on a question, ':' reopens the answer prompt
+            let mode = if state.pending_question.is_some() {
+                crate::state::GuidanceMode::Answer
+            } else {
+                crate::state::GuidanceMode::Next
+            };
+            state.start_guidance(mode);
         }
         Action::GuidanceNow => {
             state.start_guidance(crate::state::GuidanceMode::Now);
         }
+        Action::InjectEvent => {
+            state.start_guidance(crate::state::GuidanceMode::Event);
+        }
         Action::None => {}
     }
     false
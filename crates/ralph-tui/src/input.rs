@@ -40,6 +40,8 @@ pub enum Action {
     GuidanceNext,
     /// Open guidance input for current iteration (urgent)
     GuidanceNow,
+    /// Open input for publishing an event (`topic: message` or a `human.note`)
+    InjectEvent,
     /// Key not mapped to any action
     None,
 }
@@ -57,6 +59,9 @@ pub enum Action {
 /// - `/`: Start search
 /// - `n`: Next search match
 /// - `N`: Previous search match
+/// - `:`: Guidance for next iteration
+/// - `!`: Guidance for current iteration
+/// - `i`: Publish an event or note
 /// - `?`: Show help
 /// - `Esc`: Dismiss help/cancel search
 pub fn map_key(key: KeyEvent) -> Action {
@@ -82,6 +87,7 @@ pub fn map_key(key: KeyEvent) -> Action {
         // Guidance
         KeyCode::Char(':') => Action::GuidanceNext,
         KeyCode::Char('!') => Action::GuidanceNow,
+        KeyCode::Char('i') => Action::InjectEvent,
 
         // Help
         KeyCode::Char('?') => Action::ShowHelp,
@@ -209,6 +215,12 @@ mod tests {
         assert_eq!(map_key(key), Action::GuidanceNow);
     }
 
+    #[test]
+    fn i_returns_inject_event() {
+        let key = KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE);
+        assert_eq!(map_key(key), Action::InjectEvent);
+    }
+
     // AC17: Unknown Key Returns None
     #[test]
     fn unknown_key_returns_none() {
@@ -1,6 +1,7 @@
 //! State management for the TUI.
 
-use ralph_proto::{Event, HatId};
+use ralph_core::{EventWriter, lifecycle};
+use ralph_proto::{Event, HatId, Topic};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
@@ -100,6 +101,11 @@ pub enum GuidanceMode {
     Next,
     /// Guidance for the current iteration (written immediately to events.jsonl)
     Now,
+    /// Reply to a pending `human.question` (written to events.jsonl as `human.answer`)
+    Answer,
+    /// Free-form event published to events.jsonl: `topic: message`, or plain
+    /// text as a `human.note`
+    Event,
 }
 
 /// Result of attempting to send guidance.
@@ -199,6 +205,12 @@ pub struct TuiState {
     /// Brief flash message after attempting to send guidance.
     /// (mode, result, when)
     pub guidance_flash: Option<(GuidanceMode, GuidanceResult, Instant)>,
+    /// Question an agent asked via `human.question`, until it's answered.
+    pub pending_question: Option<String>,
+    /// Current workflow phase, e.g. "implement 2/3" (only with `phases:`).
+    pub phase: Option<String>,
+    /// Whether the loop is holding between iterations (`ralph pause`).
+    pub paused: bool,
 }
 
 impl TuiState {
@@ -240,6 +252,9 @@ impl TuiState {
             guidance_next_queue: Arc::new(Mutex::new(Vec::new())),
             events_path: None,
             guidance_flash: None,
+            pending_question: None,
+            phase: None,
+            paused: false,
         }
     }
 
@@ -282,6 +297,9 @@ impl TuiState {
             guidance_next_queue: Arc::new(Mutex::new(Vec::new())),
             events_path: None,
             guidance_flash: None,
+            pending_question: None,
+            phase: None,
+            paused: false,
         }
     }
 
@@ -317,6 +335,8 @@ impl TuiState {
                 let saved_pending_backend = self.pending_backend.clone();
                 let saved_guidance_next_queue = Arc::clone(&self.guidance_next_queue);
                 let saved_events_path = self.events_path.clone();
+                let saved_pending_question = self.pending_question.take();
+                let saved_phase = self.phase.take();
                 *self = Self::new();
                 self.hat_map = saved_hat_map;
                 self.loop_started = saved_loop_started; // Keep original timer
@@ -328,6 +348,8 @@ impl TuiState {
                 self.pending_backend = saved_pending_backend;
                 self.guidance_next_queue = saved_guidance_next_queue;
                 self.events_path = saved_events_path;
+                self.pending_question = saved_pending_question;
+                self.phase = saved_phase;
                 if let Some((hat_id, hat_display)) = custom_hat.clone() {
                     self.pending_hat = Some((hat_id, hat_display));
                 } else {
@@ -336,6 +358,26 @@ impl TuiState {
                 self.last_event = Some(topic.to_string());
                 self.last_event_at = Some(now);
             }
+            "human.question" => {
+                self.pending_question = Some(event.payload.clone());
+                // Open the answer prompt unless the user is mid-way through typing guidance
+                if self.guidance_mode.is_none() {
+                    self.start_guidance(GuidanceMode::Answer);
+                }
+            }
+            lifecycle::PHASE_STARTED_TOPIC => {
+                if let Ok(phase) = serde_json::from_str::<lifecycle::PhaseStarted>(&event.payload) {
+                    self.phase = Some(format!("{} {}/{}", phase.phase, phase.index, phase.total));
+                }
+            }
+            lifecycle::PAUSED_TOPIC => self.paused = true,
+            lifecycle::RESUMED_TOPIC => self.paused = false,
+            "human.answer" => {
+                self.pending_question = None;
+                if self.guidance_mode == Some(GuidanceMode::Answer) {
+                    self.cancel_guidance();
+                }
+            }
             "task.resume" => {
                 // Don't reset timer on resume - keep counting from TUI init
                 if custom_hat.is_none() {
@@ -729,6 +771,9 @@ impl TuiState {
     ///
     /// For `GuidanceMode::Next`, pushes to the shared queue (drained by loop_runner).
     /// For `GuidanceMode::Now`, writes directly to events.jsonl.
+    /// For `GuidanceMode::Answer`, writes a `human.answer` to events.jsonl,
+    /// which resumes a loop paused on a `human.question`.
+    /// For `GuidanceMode::Event`, writes the event parsed by [`parse_event_input`].
     ///
     /// Returns true if guidance was sent successfully.
     pub fn send_guidance(&mut self) -> bool {
@@ -753,7 +798,26 @@ impl TuiState {
                 }
             }
             GuidanceMode::Now => {
-                let ok = self.write_guidance_event(&input);
+                let ok = self.write_guidance_event("human.guidance", &input);
+                if ok {
+                    (true, GuidanceResult::Sent)
+                } else {
+                    (false, GuidanceResult::Failed)
+                }
+            }
+            GuidanceMode::Answer => {
+                let ok = self.write_guidance_event("human.answer", &input);
+                if ok {
+                    self.pending_question = None;
+                    (true, GuidanceResult::Sent)
+                } else {
+                    (false, GuidanceResult::Failed)
+                }
+            }
+            GuidanceMode::Event => {
+                let ok = parse_event_input(&input).is_some_and(|(topic, message)| {
+                    self.write_guidance_event(topic.as_str(), &message)
+                });
                 if ok {
                     (true, GuidanceResult::Sent)
                 } else {
@@ -768,35 +832,20 @@ impl TuiState {
         ok
     }
 
-    /// Writes a human.guidance event directly to events.jsonl.
-    fn write_guidance_event(&self, message: &str) -> bool {
+    /// Writes a human event (`human.guidance`, `human.answer`, ...) directly to events.jsonl.
+    fn write_guidance_event(&self, topic: &str, message: &str) -> bool {
         let Some(ref path) = self.events_path else {
             return false;
         };
 
         let timestamp = chrono::Utc::now().to_rfc3339();
         let event = serde_json::json!({
-            "topic": "human.guidance",
+            "topic": topic,
             "payload": message,
             "ts": timestamp,
         });
 
-        let line = match serde_json::to_string(&event) {
-            Ok(l) => l,
-            Err(_) => return false,
-        };
-
-        use std::io::Write;
-        let mut file = match std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(path)
-        {
-            Ok(f) => f,
-            Err(_) => return false,
-        };
-
-        file.write_all(line.as_bytes()).is_ok() && file.write_all(b"\n").is_ok()
+        EventWriter::new(path).append(&event).is_ok()
     }
 
     /// Returns true if guidance input is currently active.
@@ -831,6 +880,24 @@ impl Default for TuiState {
     }
 }
 
+/// Splits event input into its topic and message.
+///
+/// `build.blocked: tests hang` publishes on `build.blocked`; text without a
+/// leading `topic:` is a `human.note`. Returns `None` when the leading word
+/// looks like a topic but isn't a valid one (e.g. `build.*: ...`), rather
+/// than quietly publishing it as a note.
+pub fn parse_event_input(input: &str) -> Option<(Topic, String)> {
+    if let Some((head, message)) = input.split_once(':')
+        && !head.is_empty()
+        && !head.contains(char::is_whitespace)
+    {
+        return Topic::parse(head)
+            .ok()
+            .map(|topic| (topic, message.trim().to_string()));
+    }
+    Some((Topic::new("human.note"), input.trim().to_string()))
+}
+
 // ============================================================================
 // IterationBuffer - Content storage for a single iteration
 // ============================================================================
@@ -2324,6 +2391,95 @@ mod tests {
             assert!(!state.send_guidance());
         }
 
+        #[test]
+        fn human_question_opens_answer_prompt() {
+            let mut state = TuiState::new();
+            state.update(&Event::new("human.question", "Postgres or SQLite?"));
+            assert_eq!(
+                state.pending_question.as_deref(),
+                Some("Postgres or SQLite?")
+            );
+            assert_eq!(state.guidance_mode, Some(GuidanceMode::Answer));
+
+            // An answer from elsewhere (e.g. `ralph emit`) closes the prompt
+            state.update(&Event::new("human.answer", "SQLite"));
+            assert!(state.pending_question.is_none());
+            assert!(!state.is_guidance_active());
+        }
+
+        #[test]
+        fn human_question_keeps_guidance_being_typed() {
+            let mut state = TuiState::new();
+            state.start_guidance(GuidanceMode::Now);
+            state.guidance_input = "half typed".to_string();
+            state.update(&Event::new("human.question", "Which region?"));
+            assert_eq!(state.guidance_mode, Some(GuidanceMode::Now));
+            assert_eq!(state.guidance_input, "half typed");
+            assert!(state.pending_question.is_some());
+        }
+
+        #[test]
+        fn send_guidance_answer_writes_human_answer() {
+            let dir = tempfile::tempdir().unwrap();
+            let events_path = dir.path().join("events.jsonl");
+
+            let mut state = TuiState::new();
+            state.events_path = Some(events_path.clone());
+            state.update(&Event::new("human.question", "Postgres or SQLite?"));
+            state.guidance_input = "SQLite".to_string();
+            assert!(state.send_guidance());
+            assert!(state.pending_question.is_none());
+
+            let content = std::fs::read_to_string(&events_path).unwrap();
+            let event: serde_json::Value = serde_json::from_str(content.trim()).unwrap();
+            assert_eq!(event["topic"], "human.answer");
+            assert_eq!(event["payload"], "SQLite");
+        }
+
+        #[test]
+        fn send_event_writes_topic_and_message() {
+            let dir = tempfile::tempdir().unwrap();
+            let events_path = dir.path().join("events.jsonl");
+
+            let mut state = TuiState::new();
+            state.events_path = Some(events_path.clone());
+            state.start_guidance(GuidanceMode::Event);
+            state.guidance_input = "build.blocked: tests hang on CI".to_string();
+            assert!(state.send_guidance());
+            state.start_guidance(GuidanceMode::Event);
+            state.guidance_input = "looks good so far".to_string();
+            assert!(state.send_guidance());
+
+            let content = std::fs::read_to_string(&events_path).unwrap();
+            let events: Vec<serde_json::Value> = content
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+            assert_eq!(events[0]["topic"], "build.blocked");
+            assert_eq!(events[0]["payload"], "tests hang on CI");
+            assert_eq!(events[1]["topic"], "human.note");
+            assert_eq!(events[1]["payload"], "looks good so far");
+        }
+
+        #[test]
+        fn parse_event_input_rejects_invalid_topic() {
+            assert!(parse_event_input("build.*: go").is_none());
+            let (topic, message) = parse_event_input("note to self: check logs").unwrap();
+            assert_eq!(topic.as_str(), "human.note");
+            assert_eq!(message, "note to self: check logs");
+
+            let dir = tempfile::tempdir().unwrap();
+            let mut state = TuiState::new();
+            state.events_path = Some(dir.path().join("events.jsonl"));
+            state.start_guidance(GuidanceMode::Event);
+            state.guidance_input = "build.*: go".to_string();
+            assert!(!state.send_guidance());
+            assert_eq!(
+                state.active_guidance_flash(),
+                Some((GuidanceMode::Event, GuidanceResult::Failed))
+            );
+        }
+
         #[test]
         fn is_guidance_active_reflects_mode() {
             let mut state = TuiState::new();
@@ -1,4 +1,5 @@
 use crate::state::TuiState;
+use ralph_core::truncate_with_ellipsis;
 use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Color, Style},
@@ -27,8 +28,16 @@ impl Widget for Footer<'_> {
         // Guidance input mode takes priority
         if let Some(mode) = self.state.guidance_mode {
             let label = match mode {
-                crate::state::GuidanceMode::Next => "guidance (next)",
-                crate::state::GuidanceMode::Now => "guidance (now!)",
+                crate::state::GuidanceMode::Next => "guidance (next)".to_string(),
+                crate::state::GuidanceMode::Now => "guidance (now!)".to_string(),
+                crate::state::GuidanceMode::Event => "event [topic: ]message".to_string(),
+                crate::state::GuidanceMode::Answer => format!(
+                    "answer \"{}\"",
+                    truncate_with_ellipsis(
+                        self.state.pending_question.as_deref().unwrap_or_default(),
+                        60
+                    )
+                ),
             };
             let line = Line::from(vec![
                 Span::raw(" "),
@@ -49,6 +58,16 @@ impl Widget for Footer<'_> {
                 (crate::state::GuidanceMode::Now, crate::state::GuidanceResult::Sent) => {
                     ("\u{2713} guidance sent (now!)", Color::Green)
                 }
+                (crate::state::GuidanceMode::Answer, crate::state::GuidanceResult::Sent) => {
+                    ("\u{2713} answer sent", Color::Green)
+                }
+                (crate::state::GuidanceMode::Event, crate::state::GuidanceResult::Sent) => {
+                    ("\u{2713} event published", Color::Green)
+                }
+                (crate::state::GuidanceMode::Event, crate::state::GuidanceResult::Failed) => (
+                    "\u{2717} failed to publish event (invalid topic?)",
+                    Color::Red,
+                ),
                 (_, crate::state::GuidanceResult::Failed) => {
                     ("\u{2717} failed to send guidance", Color::Red)
                 }
@@ -130,16 +149,12 @@ impl Widget for Footer<'_> {
         };
         left_spans.push(Span::raw(elapsed_display));
 
-        let indicator_text = if self.state.loop_completed {
-            "■ DONE"
+        let (indicator_text, indicator_style) = if self.state.loop_completed {
+            ("■ DONE", Style::default().fg(Color::Blue))
+        } else if self.state.paused {
+            ("⏸ PAUSED", Style::default().fg(Color::Yellow))
         } else {
-            "◉ ACTIVE"
-        };
-
-        let indicator_style = if self.state.loop_completed {
-            Style::default().fg(Color::Blue)
-        } else {
-            Style::default().fg(Color::Green)
+            ("◉ ACTIVE", Style::default().fg(Color::Green))
         };
 
         // Calculate left content width for layout
@@ -174,6 +189,7 @@ pub fn render(state: &TuiState) -> Footer<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ralph_proto::Event;
     use ratatui::Terminal;
     use ratatui::backend::TestBackend;
 
@@ -337,6 +353,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn footer_shows_paused_until_resumed() {
+        let mut state = TuiState::new();
+        state.update(&Event::new("ralph.paused", r#"{"iteration":3}"#));
+
+        let text = render_to_string(&state);
+        assert!(text.contains("PAUSED"), "should show PAUSED, got: {}", text);
+
+        state.update(&Event::new(
+            "ralph.resumed",
+            r#"{"iteration":3,"paused_secs":12.0}"#,
+        ));
+        let text = render_to_string(&state);
+        assert!(text.contains("ACTIVE"), "should show ACTIVE, got: {}", text);
+    }
+
     #[test]
     fn footer_shows_active_at_startup() {
         // Given fresh state (loop not yet completed)
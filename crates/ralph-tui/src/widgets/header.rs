@@ -14,7 +14,7 @@ use ratatui::{
 // - Priority 1: Iteration counter [iter N/M] - always shown (TUI pagination)
 // - Priority 2: Mode indicator [LIVE]/[REVIEW] (▶/◀ compressed) - always shown
 // - Priority 3: Hat display, Scroll indicator - compressed at 50
-// - Priority 4: Iteration elapsed time MM:SS - hidden at 50
+// - Priority 4: Iteration elapsed time MM:SS, workflow phase - hidden at 50
 // - Priority 5: Idle countdown - hidden at 40
 // - Priority 6: Help hint - hidden at 65
 // ============================================================================
@@ -76,6 +76,13 @@ pub fn render(state: &TuiState, width: u16) -> Paragraph<'static> {
         spans.push(Span::raw(emoji.to_string()));
     }
 
+    // Priority 4: Workflow phase - hidden at WIDTH_COMPRESS and below
+    if let Some(phase) = &state.phase
+        && width > WIDTH_COMPRESS
+    {
+        spans.push(Span::raw(format!(" | phase: {phase}")));
+    }
+
     // Priority 5: Idle countdown - hidden at WIDTH_MINIMAL and below
     if let Some(idle) = state.idle_timeout_remaining
         && width > WIDTH_MINIMAL
@@ -218,6 +225,24 @@ mod tests {
         assert!(text.contains("04:32"), "should show 04:32, got: {}", text);
     }
 
+    #[test]
+    fn header_shows_phase_from_lifecycle_event() {
+        let mut state = TuiState::new();
+        state.update(&Event::new(
+            "ralph.phase_started",
+            r#"{"phase":"implement","index":2,"total":3,"iteration":4}"#,
+        ));
+
+        let text = render_to_string_with_width(&state, 120);
+        assert!(
+            text.contains("phase: implement 2/3"),
+            "should show the phase, got: {}",
+            text
+        );
+        let text = render_to_string_with_width(&state, 50);
+        assert!(!text.contains("phase:"), "hidden when narrow: {}", text);
+    }
+
     #[test]
     fn header_shows_hat() {
         let mut state = TuiState::new();
@@ -699,7 +724,7 @@ mod tests {
         let event = Event::new("task.start", "");
         state.update(&event);
         if let Some(iteration) = state.iterations.get_mut(0) {
-            iteration.elapsed = Some(Duration::from_secs(300));
+            iteration.elapsed = Some(Duration::from_mins(5));
         }
 
         let text = render_to_string(&state);
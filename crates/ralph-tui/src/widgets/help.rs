@@ -72,6 +72,14 @@ pub fn render(f: &mut Frame, area: Rect) {
             Span::styled("  !", Style::default().fg(Color::Cyan)),
             Span::raw("      Send guidance (now, current iteration)"),
         ]),
+        Line::from(vec![
+            Span::styled("  :", Style::default().fg(Color::Cyan)),
+            Span::raw("      Answer an agent's question (when asked)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  i", Style::default().fg(Color::Cyan)),
+            Span::raw("      Publish an event (topic: message) or note"),
+        ]),
         Line::from(""),
         Line::from(Span::styled("Other:", Style::default().fg(Color::Yellow))),
         Line::from(vec![